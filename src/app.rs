@@ -0,0 +1,693 @@
+//! # App Module
+//! The owner of the pages: it holds the unlocked session and handles what the
+//! [navigator](Navigator) leaves over. The changes a page asks for run as
+//! [commands](crate::service::undo::Command) on the session's
+//! [undo stack](crate::service::undo::UndoStack), so `Ctrl+Z` and `Ctrl+Y` undo and redo them;
+//! after each change, the page shown is reloaded from the new state. Messages become
+//! [toasts](Toasts).
+//!
+//! [`run`] is the whole application: it logs the user in, or registers the first user, then starts
+//! the [full-screen interface](crate::models::settings::Settings::wants_full_screen) if it is asked
+//! for and built. Otherwise it opens the page given with
+//! [`OPEN_FLAG`](crate::nav::routes::OPEN_FLAG) over the dashboard and hands the pages to the
+//! [tabs](Tabs) until the user quits. The lock screen and the login pages run
+//! outside the tabs, since the owner takes the user or session back from them; with the `fido2`
+//! feature they ask the security keys plugged in over USB (`UsbSecurityKey`). Once the
+//! database is open, everything drawn is colored with the user's [theme](Theme), which a
+//! `NO_COLOR` environment variable turns off, or spelled out for the [accessible mode](accessible),
+//! and shown in their [language](i18n); the login pages come before the settings are known, so
+//! they are drawn plain and in English.
+
+use crate::clock::{Clock, SystemClock};
+use crate::db::storage::{Session, detect_users};
+use crate::nav::Navigator;
+use crate::nav::routes::Route;
+use crate::nav::tabs::Tabs;
+use crate::models::settings::Settings;
+use crate::pages::accessible;
+use crate::pages::i18n::{self, tr, tr_with};
+use crate::pages::keybindings::{KEYBINDINGS_FILE, KeyMap};
+use crate::pages::line_editor::{Key, LineEditor};
+use crate::pages::lock::{AutoLock, LockScreenPage, LockStep};
+use crate::pages::login::{LoginPage, LoginStep};
+use crate::pages::register::{RegisterPage, RegisterStep};
+use crate::pages::settings::SettingChange;
+use crate::pages::theme::Theme;
+use crate::pages::toast::{ToastKind, Toasts};
+use crate::pages::{AccountChange, Page, PageAction, Size};
+#[cfg(feature = "fido2")]
+use crate::security::fido2::usb::UsbSecurityKey;
+use crate::service::settings::set_list_preferences;
+use crate::service::undo::Command;
+use crate::terminal::RawMode;
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// The folder the database files are kept in, relative to where the application is started.
+pub const DATABASES_DIR: &str = "databases";
+
+/// # App struct
+/// The session the pages work on, and the toasts shown under them.
+pub struct App {
+    /// The unlocked database, with its undo stack.
+    pub session: Session,
+    /// The folder the session is saved in when the user's account or preferences change.
+    databases_dir: PathBuf,
+    /// The messages shown under the pages.
+    pub toasts: Toasts,
+    /// The source of the current time, for commands and toasts.
+    clock: Arc<dyn Clock>,
+}
+
+impl App {
+    /// Creates the owner of an unlocked session, kept in `databases_dir`.
+    #[must_use]
+    pub fn new(session: Session, databases_dir: PathBuf, clock: Arc<dyn Clock>) -> Self {
+        Self { session, databases_dir, toasts: Toasts::default(), clock }
+    }
+
+    /// The source of the current time the session runs on.
+    #[must_use]
+    pub fn clock(&self) -> &Arc<dyn Clock> {
+        &self.clock
+    }
+
+    /// Handles what the navigator leaves over, e.g. as the `owner` of [`Navigator::run`]:
+    /// [`PageAction::Open`] opens the route's page, [`PageAction::Refresh`] applies the changes
+    /// the page asks for, [`PageAction::Undo`] and
+    /// [`PageAction::Redo`] step through the undo stack, and messages become toasts.
+    ///
+    /// # Returns
+    /// * `PageAction` - What is left to do, such as [`PageAction::Quit`]; the rest comes back as
+    ///   `None`.
+    pub fn handle(&mut self, navigator: &mut Navigator, action: PageAction) -> PageAction {
+        let action = match action {
+            PageAction::Open(route) => match navigator.open(&route, &self.session.state, &self.clock) {
+                Ok(()) => PageAction::None,
+                Err(err) => PageAction::ShowError(err.to_string()),
+            },
+            PageAction::Refresh => self.apply_changes(navigator),
+            PageAction::Undo => self.step(navigator, false),
+            PageAction::Redo => self.step(navigator, true),
+            other => other,
+        };
+        self.toasts.handle(action, self.clock.as_ref())
+    }
+
+    /// Applies the changes the current page asks for: runs its commands in order, stopping at the
+    /// first that fails, and reloads the page, then saves the changes to the user's account at
+    /// once, switching the session to a new lock-after time or accessible mode. A page that
+    /// created an epic or story is replaced by the new item's page, and one that saved an edit or
+    /// deleted its story is closed; one that only changed the account is left as it is, unless the
+    /// change failed.
+    fn apply_changes(&mut self, navigator: &mut Navigator) -> PageAction {
+        let Some(page) = navigator.current_mut() else { return PageAction::None };
+        let mut action = PageAction::None;
+        let mut ran = false;
+        let mut created = None;
+        let mut finished = false;
+        while let Some(command) = page.take_command() {
+            ran = true;
+            let shown: Option<fn(String) -> Route> = match command {
+                Command::CreateEpic { .. } => Some(Route::Epic),
+                Command::CreateStory { .. } => Some(Route::Story),
+                _ => None,
+            };
+            let closes = matches!(command, Command::EditItem { .. } | Command::DeleteStory { .. });
+            match self.session.undo.execute(&mut self.session.state, command, self.clock.as_ref()) {
+                Ok(uuid) => {
+                    created = shown.zip(uuid).map(|(route, uuid)| route(uuid.to_string())).or(created);
+                    finished |= closes;
+                }
+                Err(err) => {
+                    action = PageAction::ShowError(err.to_string());
+                    break;
+                }
+            }
+        }
+        let mut changed = false;
+        let mut failed = false;
+        let mut lock_after_changed = false;
+        while let Some(change) = page.take_account_change() {
+            changed = true;
+            lock_after_changed |= matches!(change, AccountChange::Setting(SettingChange::LockAfter(_)));
+            match self.apply_account_change(change) {
+                PageAction::None => (),
+                result => {
+                    failed |= matches!(result, PageAction::ShowError(_));
+                    action = result;
+                }
+            }
+        }
+        if changed
+            && self.session.state.is_dirty()
+            && let Err(err) = self.session.save(&self.databases_dir)
+        {
+            action = PageAction::ShowError(err.to_string());
+        }
+        match created.map(|route| route.open(&self.session.state, &self.clock)) {
+            Some(Ok(page)) => {
+                navigator.replace(page);
+            }
+            Some(Err(err)) => action = PageAction::ShowError(err.to_string()),
+            None if finished => action = PageAction::Pop,
+            None if ran || failed || !changed => page.reload(&self.session.state),
+            None => (),
+        }
+        if lock_after_changed {
+            navigator.update_auto_lock(&self.session.state.settings);
+        }
+        if changed {
+            navigator.set_linear(accessible::from_settings(&self.session.state.settings));
+        }
+        action
+    }
+
+    /// Applies one change to the user's account or preferences to the session. A new password
+    /// or username is written at once, with the database key rewrapped or the user's entries in
+    /// shared databases renamed; if that fails, the files and the session are left as they were.
+    ///
+    /// # Returns
+    /// * `PageAction` - A message for the user, or `None`.
+    fn apply_account_change(&mut self, change: AccountChange) -> PageAction {
+        let state = &mut self.session.state;
+        match change {
+            AccountChange::ListPreferences(list, preferences) => set_list_preferences(state, list, preferences),
+            AccountChange::Setting(change) => {
+                if let SettingChange::Locale(locale) = change {
+                    i18n::set_locale(locale);
+                }
+                change.apply(state);
+            }
+            AccountChange::Password(change) => {
+                return match change.apply(&mut self.session, &self.databases_dir, self.clock.as_ref()) {
+                    Ok(()) => PageAction::Notify(tr("settings-password-changed")),
+                    Err(err) => PageAction::ShowError(err.to_string()),
+                };
+            }
+            AccountChange::Username(change) => {
+                return match change.apply(&mut self.session, &self.databases_dir, self.clock.as_ref()) {
+                    Ok(_) => PageAction::Notify(tr_with("settings-username-changed", &[("name", &self.session.state.user.username)])),
+                    Err(err) => PageAction::ShowError(err.to_string()),
+                };
+            }
+        }
+        PageAction::None
+    }
+
+    /// Handles what the tab shown leaves over, e.g. as the `owner` of [`Tabs::run`]: switches
+    /// tabs, or closes the tab whose last page closed, and hands the rest to [`App::handle`] with
+    /// the tab's pages.
+    ///
+    /// # Returns
+    /// * `PageAction` - What is left to do, as from [`App::handle`].
+    pub fn handle_tabs(&mut self, tabs: &mut Tabs, action: PageAction) -> PageAction {
+        let action = match action {
+            PageAction::SwitchTab(_) | PageAction::Quit => tabs.dispatch(action, &self.session.state, &self.clock),
+            other => {
+                return match tabs.current_mut() {
+                    Some(navigator) => self.handle(navigator, other),
+                    None => other,
+                };
+            }
+        };
+        self.toasts.handle(action, self.clock.as_ref())
+    }
+
+    /// Undoes the most recent change, or redoes the most recently undone one, and reloads the
+    /// current page.
+    fn step(&mut self, navigator: &mut Navigator, redo: bool) -> PageAction {
+        let Session { state, undo, .. } = &mut self.session;
+        let change = if redo { undo.redo(state) } else { undo.undo(state) };
+        let Some(change) = change else { return PageAction::ShowError(tr(if redo { "redo-nothing" } else { "undo-nothing" })) };
+        if let Some(page) = navigator.current_mut() {
+            page.reload(state);
+        }
+        PageAction::Notify(tr_with(if redo { "redo-done" } else { "undo-done" }, &[("change", &change)]))
+    }
+}
+
+/// Runs the application on the databases in `databases_dir`: logs the user in, shows the pages,
+/// and saves the database when the user quits or the input ends.
+///
+/// # Arguments
+/// * `databases_dir` - The folder holding the database files and [`KEYBINDINGS_FILE`].
+/// * `args` - The command-line arguments, for [`Route::from_args`] and
+///   [`Settings::wants_full_screen`](crate::models::settings::Settings::wants_full_screen).
+/// * `keys` - The keys typed.
+/// * `draw` - Shows a page's rendered lines.
+/// # Errors
+/// * `RouteError` - If the route given with `--open` can't be parsed.
+/// * `std::io::Error` - If the databases folder can't be read, or the full-screen interface
+///   can't use the terminal.
+/// * Same as [`Session::save`].
+pub fn run(databases_dir: &Path, args: &[String], keys: &mut dyn Iterator<Item = Key>, draw: &mut dyn FnMut(Vec<String>)) -> Result<(), Box<dyn std::error::Error>> {
+    let route = Route::from_args(args).transpose()?.unwrap_or(Route::Dashboard);
+    match KeyMap::load(&databases_dir.join(KEYBINDINGS_FILE)) {
+        Ok(keymap) => KeyMap::set_active(keymap),
+        Err(err) => draw(vec![err.to_string()]),
+    }
+    let clock: Arc<dyn Clock> = Arc::new(SystemClock);
+    let mut editor = LineEditor::new();
+    editor.set_echo(RawMode::is_active());
+    let Some(session) = sign_in(databases_dir, &clock, &mut editor, keys, draw)? else { return Ok(()) };
+    i18n::set_locale(session.state.settings.locale);
+    if session.state.settings.wants_full_screen(args) {
+        #[cfg(feature = "tui")]
+        {
+            let mut app = App::new(session, databases_dir.to_path_buf(), Arc::clone(&clock));
+            let mut screen = crate::tui::TuiApp::new(Some(AutoLock::new(&app.session.state.settings, clock.as_ref())));
+            while crate::tui::run(&mut screen, &mut app)? && unlock(&mut app, &clock, &mut editor, keys, draw) {
+                screen.unlock(clock.as_ref());
+            }
+            if app.session.state.is_dirty() {
+                app.session.save(databases_dir)?;
+            }
+            return Ok(());
+        }
+        #[cfg(not(feature = "tui"))]
+        draw(vec![tr("tui-unavailable")]);
+    }
+
+    let mut app = App::new(session, databases_dir.to_path_buf(), Arc::clone(&clock));
+    let state = &app.session.state;
+    let mut tabs = Tabs::new(state, &clock)?;
+    if let Some(navigator) = tabs.current_mut() {
+        navigator.sign_in(Some(AutoLock::new(&state.settings, clock.as_ref())));
+        navigator.set_linear(accessible::from_settings(&state.settings));
+        if route != Route::Dashboard
+            && let Err(err) = navigator.open(&route, state, &clock)
+        {
+            app.toasts.push(ToastKind::Error, err.to_string(), clock.as_ref());
+        }
+    }
+    let app = RefCell::new(app);
+    let size = Size::from_env();
+    loop {
+        let mut locked = false;
+        tabs.run(
+            &mut editor,
+            keys,
+            size,
+            clock.as_ref(),
+            &mut |mut lines| {
+                let app = app.borrow();
+                lines.extend(app.toasts.render(clock.as_ref()));
+                draw(present(&app.session.state.settings, lines));
+            },
+            &mut |tabs, action| match action {
+                // The lock screen runs outside the tabs, which stop until it is unlocked.
+                PageAction::Lock => {
+                    locked = true;
+                    PageAction::Quit
+                }
+                action => app.borrow_mut().handle_tabs(tabs, action),
+            },
+        );
+        if !locked || !unlock(&mut app.borrow_mut(), &clock, &mut editor, keys, draw) {
+            break;
+        }
+        if let Some(navigator) = tabs.current_mut() {
+            navigator.unlock(clock.as_ref());
+        }
+    }
+    let mut app = app.into_inner();
+    if app.session.state.is_dirty() {
+        app.session.save(databases_dir)?;
+    }
+    Ok(())
+}
+
+/// Logs one of the users in `databases_dir` in, or registers the first user if there are none.
+///
+/// # Returns
+/// * `Result<Option<Session>, std::io::Error>` - The unlocked session, or `None` if the input
+///   ended first.
+/// # Errors
+/// * `std::io::Error` - If the databases folder can't be read.
+fn sign_in(
+    databases_dir: &Path,
+    clock: &Arc<dyn Clock>,
+    editor: &mut LineEditor,
+    keys: &mut dyn Iterator<Item = Key>,
+    draw: &mut dyn FnMut(Vec<String>),
+) -> Result<Option<Session>, std::io::Error> {
+    let users = detect_users(databases_dir)?;
+    if users.is_empty() {
+        let mut page = RegisterPage::new(users, databases_dir.to_path_buf(), Arc::clone(clock));
+        let registered = show_until(&mut page, |page| page.step() == RegisterStep::Registered, editor, keys, draw);
+        return Ok(registered.then(|| page.into_session()).flatten());
+    }
+    let page = LoginPage::new(users, databases_dir.to_path_buf(), Arc::clone(clock));
+    #[cfg(feature = "fido2")]
+    let page = page.with_security_key(Arc::new(UsbSecurityKey));
+    let mut page = page;
+    let logged_in = show_until(&mut page, |page| page.step() == LoginStep::LoggedIn, editor, keys, draw);
+    Ok(logged_in.then(|| page.into_session()).flatten())
+}
+
+/// Shows the lock screen until the user unlocks the session, then takes the user back into it.
+///
+/// # Returns
+/// * `bool` - Whether the session was unlocked; `false` if the input ended first.
+fn unlock(app: &mut App, clock: &Arc<dyn Clock>, editor: &mut LineEditor, keys: &mut dyn Iterator<Item = Key>, draw: &mut dyn FnMut(Vec<String>)) -> bool {
+    let page = LockScreenPage::new(app.session.state.user.clone(), Arc::clone(clock));
+    #[cfg(feature = "fido2")]
+    let page = page.with_security_key(Arc::new(UsbSecurityKey));
+    let mut page = page;
+    let settings = app.session.state.settings.clone();
+    if !show_until(&mut page, |page| page.step() == LockStep::Unlocked, editor, keys, &mut |lines| draw(present(&settings, lines))) {
+        return false;
+    }
+    if let Some(user) = page.take_user().filter(|user| *user != app.session.state.user) {
+        // A backup code or HOTP counter was used up.
+        app.session.state.user = user;
+        app.session.state.mark_dirty();
+    }
+    true
+}
+
+/// Presents the lines drawn as the user's settings ask: in plain labeled text in the
+/// [accessible mode](accessible), otherwise colored with their [theme](Theme).
+fn present(settings: &Settings, lines: Vec<String>) -> Vec<String> {
+    if accessible::from_settings(settings) { accessible::linearize(lines) } else { Theme::from_settings(settings).apply(lines) }
+}
+
+/// Shows a page that runs outside the navigator until `done` says it finished, with the last
+/// error under it.
+///
+/// # Returns
+/// * `bool` - Whether the page finished; `false` if the input ended first.
+fn show_until<P: Page>(page: &mut P, done: impl Fn(&P) -> bool, editor: &mut LineEditor, keys: &mut dyn Iterator<Item = Key>, draw: &mut dyn FnMut(Vec<String>)) -> bool {
+    let mut error = None;
+    while !done(page) {
+        let mut lines = page.render(Size::from_env());
+        lines.extend(error.take());
+        draw(lines);
+        editor.set_masked(page.masks_input());
+        let Some(input) = editor.read_line(keys) else { return false };
+        if let PageAction::ShowError(message) = page.handle_input(&input) {
+            error = Some(message);
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{FixedClock, SystemClock};
+    use chrono::{DateTime, Duration};
+    use crate::db::ClearTextDBState;
+    use crate::models::Status;
+    use crate::nav::tabs::Tab;
+    use crate::models::settings::{ListName, ListPreferences, ListSort, Locale, ThemeName};
+    use crate::pages::kanban::KanbanPage;
+    use crate::pages::line_editor::decode_keys;
+    use crate::service::{epics::create_epic, get_story, settings::set_theme, stories::create_story};
+    use crate::users::User;
+    use uuid::Uuid;
+
+    /// The lines drawn, without the tab bar over the pages shown in the tabs.
+    fn without_tab_bar(mut lines: Vec<String>) -> Vec<String> {
+        if lines.first().is_some_and(|line| line.starts_with("<1 ") || line.starts_with("1 ")) {
+            lines.remove(0);
+        }
+        lines
+    }
+
+    #[test]
+    fn test_run_logs_in_and_opens_the_route_given() {
+        let databases_dir = std::env::temp_dir().join(format!("ironyyy-app-{}", Uuid::new_v4()));
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        create_epic(&mut state, "Launch", String::new(), &SystemClock).unwrap();
+        set_theme(&mut state, ThemeName::Monochrome);
+        Session::new(state, "password").unwrap().save(&databases_dir).unwrap();
+        let args = ["ironyyy", "--open", "epic/EPIC-1"].map(str::to_string);
+
+        let mut drawn = Vec::new();
+        let mut keys = decode_keys(b"alice\npassword\nZ\npassword\nQ\n").into_iter();
+        run(&databases_dir, &args, &mut keys, &mut |lines| drawn.push(without_tab_bar(lines)[0].clone())).unwrap();
+        assert_eq!(drawn, ["=== Log In ===", "=== Log In ===", "=== EPIC-1 Launch ===", "=== Locked ===", "=== EPIC-1 Launch ==="]);
+
+        let args = ["ironyyy", "--open=nowhere"].map(str::to_string);
+        assert_eq!(run(&databases_dir, &args, &mut std::iter::empty(), &mut |_| ()).unwrap_err().to_string(), "There is no page called \"nowhere\"");
+        #[cfg(not(feature = "tui"))]
+        {
+            let args = ["ironyyy", "--tui"].map(str::to_string);
+            let mut drawn = Vec::new();
+            run(&databases_dir, &args, &mut decode_keys(b"alice\npassword\nQ\n").into_iter(), &mut |lines| drawn.push(without_tab_bar(lines)[0].clone())).unwrap();
+            assert_eq!(drawn[2..], [tr("tui-unavailable"), "=== Dashboard ===".to_string()]);
+        }
+        std::fs::remove_dir_all(&databases_dir).unwrap();
+    }
+
+    #[test]
+    fn test_list_preferences_are_saved_as_they_change() {
+        let databases_dir = std::env::temp_dir().join(format!("ironyyy-app-{}", Uuid::new_v4()));
+        let state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let user_uuid = state.user.user_uuid;
+        Session::new(state, "password").unwrap().save(&databases_dir).unwrap();
+        let args = ["ironyyy", "--open", "epics"].map(str::to_string);
+
+        run(&databases_dir, &args, &mut decode_keys(b"alice\npassword\ns due\nf launch\n").into_iter(), &mut |_| ()).unwrap();
+        let session = Session::unlock(&databases_dir, user_uuid, "password").unwrap();
+        assert_eq!(session.state.settings.list(ListName::Epics), ListPreferences { sort: ListSort::DueDate, filter: "launch".to_string() });
+        std::fs::remove_dir_all(&databases_dir).unwrap();
+    }
+
+    #[test]
+    fn test_password_changed_in_the_settings_opens_the_database() {
+        let databases_dir = std::env::temp_dir().join(format!("ironyyy-app-{}", Uuid::new_v4()));
+        let state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let user_uuid = state.user.user_uuid;
+        Session::new(state, "password").unwrap().save(&databases_dir).unwrap();
+        let args = ["ironyyy", "--open", "settings"].map(str::to_string);
+
+        let mut drawn = Vec::new();
+        let keys = b"alice\npassword\n6\nwrong\ncorrect horse battery\ncorrect horse battery\n6\npassword\ncorrect horse battery\ncorrect horse battery\nq\n";
+        run(&databases_dir, &args, &mut decode_keys(keys).into_iter(), &mut |lines| drawn.push(without_tab_bar(lines))).unwrap();
+        assert!(drawn.iter().any(|lines| lines.last().is_some_and(|line| line.contains("The current password is wrong"))));
+        assert!(drawn.iter().any(|lines| lines.last().is_some_and(|line| line.contains("The password has been changed."))));
+        assert!(Session::unlock(&databases_dir, user_uuid, "password").is_err());
+        assert!(Session::unlock(&databases_dir, user_uuid, "correct horse battery").is_ok());
+        std::fs::remove_dir_all(&databases_dir).unwrap();
+    }
+
+    #[test]
+    fn test_username_changed_in_the_settings_renames_the_account() {
+        let databases_dir = std::env::temp_dir().join(format!("ironyyy-app-{}", Uuid::new_v4()));
+        let state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let user_uuid = state.user.user_uuid;
+        Session::new(state, "password").unwrap().save(&databases_dir).unwrap();
+        Session::new(ClearTextDBState::new(User::new("bob".to_string(), "password").unwrap()), "password").unwrap().save(&databases_dir).unwrap();
+        let args = ["ironyyy", "--open", "settings"].map(str::to_string);
+
+        let mut drawn = Vec::new();
+        run(&databases_dir, &args, &mut decode_keys(b"alice\npassword\n7 bob\n\n7 alicia\nq\n").into_iter(), &mut |lines| drawn.push(without_tab_bar(lines))).unwrap();
+        let after_taken = drawn.iter().position(|lines| lines.last().is_some_and(|line| line.contains("bob"))).unwrap();
+        assert_eq!(drawn[after_taken][7], "7. Username: alice");
+        assert!(drawn.iter().any(|lines| lines.last().is_some_and(|line| line.contains("You are now alicia."))));
+        let mut users = detect_users(&databases_dir).unwrap();
+        users.sort_by(|a, b| a.1.cmp(&b.1));
+        assert_eq!(users.iter().map(|(_, name)| name.as_str()).collect::<Vec<_>>(), ["alicia", "bob"]);
+        assert_eq!(Session::unlock(&databases_dir, user_uuid, "password").unwrap().state.user.username, "alicia");
+        std::fs::remove_dir_all(&databases_dir).unwrap();
+    }
+
+    #[test]
+    fn test_theme_chosen_in_the_settings_colors_the_pages() {
+        let databases_dir = std::env::temp_dir().join(format!("ironyyy-app-{}", Uuid::new_v4()));
+        let state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let user_uuid = state.user.user_uuid;
+        Session::new(state, "password").unwrap().save(&databases_dir).unwrap();
+        let args = ["ironyyy", "--open", "settings"].map(str::to_string);
+
+        let mut drawn = Vec::new();
+        run(&databases_dir, &args, &mut decode_keys(b"alice\npassword\n1 light\n").into_iter(), &mut |lines| drawn.push(without_tab_bar(lines)[0].clone())).unwrap();
+        let themed = |theme| Theme::from_settings(&Settings { theme, ..Settings::default() }).apply(vec![tr("settings-title")]).remove(0);
+        assert_eq!(drawn[2..], [themed(ThemeName::Default), themed(ThemeName::Light)]);
+        assert_eq!(Session::unlock(&databases_dir, user_uuid, "password").unwrap().state.settings.theme, ThemeName::Light);
+        std::fs::remove_dir_all(&databases_dir).unwrap();
+    }
+
+    #[test]
+    fn test_language_chosen_in_the_settings_is_shown_and_kept() {
+        let databases_dir = std::env::temp_dir().join(format!("ironyyy-app-{}", Uuid::new_v4()));
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        set_theme(&mut state, ThemeName::Monochrome);
+        Session::new(state, "password").unwrap().save(&databases_dir).unwrap();
+        let args = ["ironyyy", "--open", "settings"].map(str::to_string);
+
+        let mut drawn = Vec::new();
+        run(&databases_dir, &args, &mut decode_keys(b"alice\npassword\n2 en-XA\n").into_iter(), &mut |lines| drawn.push(without_tab_bar(lines)[0].clone())).unwrap();
+        assert_eq!(drawn[2..], ["=== Settings ===", "=== Séttíngs ==="]);
+        i18n::set_locale(Locale::English);
+
+        let mut drawn = Vec::new();
+        run(&databases_dir, &args, &mut decode_keys(b"alice\npassword\n").into_iter(), &mut |lines| drawn.push(without_tab_bar(lines)[0].clone())).unwrap();
+        assert_eq!(drawn[2..], ["=== Séttíngs ==="]);
+        i18n::set_locale(Locale::English);
+        std::fs::remove_dir_all(&databases_dir).unwrap();
+    }
+
+    #[test]
+    fn test_ctrl_z_undoes_the_last_change_before_it_is_saved() {
+        let databases_dir = std::env::temp_dir().join(format!("ironyyy-app-{}", Uuid::new_v4()));
+        let state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let user_uuid = state.user.user_uuid;
+        Session::new(state, "password").unwrap().save(&databases_dir).unwrap();
+
+        let mut drawn = Vec::new();
+        let keys = b"alice\npassword\ne\nLaunch\n:w\n\ny\nq\ne\nDocs\n:w\n\ny\n\x1a\x03";
+        run(&databases_dir, &["ironyyy".to_string()], &mut decode_keys(keys).into_iter(), &mut |lines| drawn.push(without_tab_bar(lines))).unwrap();
+        assert!(drawn.last().unwrap().iter().any(|line| line.contains("Undid create epic \"Docs\".")));
+        let state = Session::unlock(&databases_dir, user_uuid, "password").unwrap().state;
+        assert_eq!(state.epics.iter().map(|epic| epic.title.as_str()).collect::<Vec<_>>(), ["Launch"]);
+        std::fs::remove_dir_all(&databases_dir).unwrap();
+    }
+
+    #[test]
+    fn test_lock_after_changed_in_the_settings_applies_at_once() {
+        let databases_dir = std::env::temp_dir().join(format!("ironyyy-app-{}", Uuid::new_v4()));
+        let clock: Arc<dyn Clock> = Arc::new(SystemClock);
+        let idle = FixedClock::new(DateTime::from_timestamp(1_700_000_000, 0).unwrap());
+        let state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let mut app = App::new(Session::new(state, "password").unwrap(), databases_dir.clone(), Arc::clone(&clock));
+        let mut navigator = Navigator::new(Route::Settings.open(&app.session.state, &clock).unwrap());
+        navigator.sign_in(Some(AutoLock::new(&app.session.state.settings, &idle)));
+
+        idle.advance(Duration::hours(1));
+        assert!(matches!(navigator.check_idle(&idle), PageAction::None));
+        let action = navigator.handle_input("5 1");
+        assert!(matches!(app.handle(&mut navigator, action), PageAction::None));
+        idle.advance(Duration::minutes(2));
+        assert!(matches!(navigator.check_idle(&idle), PageAction::Lock));
+
+        // The password typed on the page is echoed masked, never as typed.
+        navigator.handle_input("6");
+        let mut editor = LineEditor::new();
+        assert_eq!(navigator.read_input(&mut editor, &mut decode_keys(b"secret").into_iter()), None);
+        assert_eq!(editor.render(""), "******");
+        std::fs::remove_dir_all(&databases_dir).unwrap();
+    }
+
+    #[test]
+    fn test_accessible_mode_spells_out_the_pages() {
+        let databases_dir = std::env::temp_dir().join(format!("ironyyy-app-{}", Uuid::new_v4()));
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let epic_uuid = create_epic(&mut state, "Launch", String::new(), &SystemClock).unwrap();
+        create_story(&mut state, epic_uuid, "Login", String::new(), &SystemClock).unwrap();
+        Session::new(state.clone(), "password").unwrap().save(&databases_dir).unwrap();
+        let args = ["ironyyy", "--open", "settings"].map(str::to_string);
+
+        let mut drawn = Vec::new();
+        run(&databases_dir, &args, &mut decode_keys(b"alice\npassword\n4\n").into_iter(), &mut |lines| drawn.push(without_tab_bar(lines))).unwrap();
+        assert_eq!(drawn[3][..5], ["Page: Settings", "1. Theme: default", "2. Language: en", "3. Full screen: off", "4. Accessible mode: on"]);
+
+        let args = ["ironyyy", "--open", "board"].map(str::to_string);
+        let mut drawn = Vec::new();
+        run(&databases_dir, &args, &mut decode_keys(b"alice\npassword\n").into_iter(), &mut |lines| drawn.push(without_tab_bar(lines))).unwrap();
+        assert_eq!(drawn[2], accessible::render(&KanbanPage::new(&state, Arc::new(SystemClock)), Size::from_env()));
+        std::fs::remove_dir_all(&databases_dir).unwrap();
+    }
+
+    #[test]
+    fn test_tabs_switch_with_g_or_a_bare_number() {
+        let databases_dir = std::env::temp_dir().join(format!("ironyyy-app-{}", Uuid::new_v4()));
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        set_theme(&mut state, ThemeName::Monochrome);
+        Session::new(state, "password").unwrap().save(&databases_dir).unwrap();
+
+        let mut drawn = Vec::new();
+        let keys = b"alice\npassword\n3\ng 2\n6\nq\ng 6\nq\n";
+        run(&databases_dir, &["ironyyy".to_string()], &mut decode_keys(keys).into_iter(), &mut |lines| drawn.push(lines[..2].join(" | "))).unwrap();
+        let bar = |active: usize| Tab::ALL.iter().map(|tab| if tab.number() == active { format!("<{} {}>", tab.number(), tab.title()) } else { format!("{} {}", tab.number(), tab.title()) }).collect::<Vec<_>>().join("  ");
+        assert_eq!(
+            drawn[2..],
+            [
+                format!("{} | === Dashboard ===", bar(1)),
+                format!("{} | === Board ===", bar(3)),
+                format!("{} | === Epics ===", bar(2)),
+                // The epic list reads numbers itself, and closing a tab's first page shows the dashboard.
+                format!("{} | === Epics ===", bar(2)),
+                format!("{} | === Dashboard ===", bar(1)),
+                format!("{} | === Settings ===", bar(6)),
+                format!("{} | === Dashboard ===", bar(1)),
+            ]
+        );
+        std::fs::remove_dir_all(&databases_dir).unwrap();
+    }
+
+    #[test]
+    fn test_changes_made_through_the_pages_are_kept() {
+        let databases_dir = std::env::temp_dir().join(format!("ironyyy-app-{}", Uuid::new_v4()));
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let user_uuid = state.user.user_uuid;
+        set_theme(&mut state, ThemeName::Monochrome);
+        Session::new(state, "password").unwrap().save(&databases_dir).unwrap();
+
+        let mut drawn = Vec::new();
+        let keys = b"alice\npassword\ne\nLaunch\n:w\n\ny\ne\n1\nLaunch v2\ns\ny\ns\nLogin\n:w\nopen\n\ny\nt in progress\nq\n\
+            s\nDocs\n:w\nopen\n\ny\nd\nq\ng 6\n1 light\ng 1\n";
+        run(&databases_dir, &["ironyyy".to_string()], &mut decode_keys(keys).into_iter(), &mut |lines| drawn.push(without_tab_bar(lines))).unwrap();
+        let shown = |heading: &str| drawn.iter().filter(|lines| lines[0] == heading).collect::<Vec<_>>();
+        // The edit page closes once saved, showing the epic under its new title, and deleting a
+        // story closes its page, leaving the epic with the story that is left.
+        assert_eq!((shown("=== EPIC-1 Launch ===").len(), shown("=== Edit EPIC-1 Launch ===").len()), (1, 4));
+        assert!(shown("=== EPIC-1 Launch v2 ===").last().unwrap().iter().any(|line| line == "1  STORY-1  Login  In Progress"));
+        assert!(shown("=== EPIC-1 Launch v2 ===").last().unwrap().iter().all(|line| !line.contains("Docs")));
+        assert!(drawn.last().unwrap().contains(&"Epics: 1 (1 to do, 0 in progress, 0 done)".to_string()));
+        let state = Session::unlock(&databases_dir, user_uuid, "password").unwrap().state;
+        assert_eq!(state.epics.iter().map(|epic| epic.title.as_str()).collect::<Vec<_>>(), ["Launch v2"]);
+        assert_eq!(state.stories.iter().map(|story| (story.title.as_str(), story.status)).collect::<Vec<_>>(), [("Login", Status::InProgress)]);
+        assert_eq!(state.epics[0].story_uuids, [state.stories[0].story_uuid]);
+        assert_eq!(state.settings.theme, ThemeName::Light);
+        std::fs::remove_dir_all(&databases_dir).unwrap();
+    }
+
+    #[test]
+    fn test_routes_asked_for_are_opened_from_the_session() {
+        let clock: Arc<dyn Clock> = Arc::new(SystemClock);
+        let state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let mut app = App::new(Session::new(state, "password").unwrap(), std::env::temp_dir(), Arc::clone(&clock));
+        let mut navigator = Navigator::new(Route::Dashboard.open(&app.session.state, &clock).unwrap());
+        let mut drawn = Vec::new();
+        let mut keys = decode_keys(b"/\rq\re\rLaunch\r:w\r\ry\rq\r").into_iter();
+        navigator.run(&mut LineEditor::new(), &mut keys, Size::default(), clock.as_ref(), &mut |lines| drawn.push(lines[0].clone()), &mut |navigator, action| app.handle(navigator, action));
+        assert_eq!(drawn[..4], ["=== Dashboard ===", "=== Search ===", "=== Dashboard ===", "=== New Epic ==="]);
+        assert_eq!(drawn[drawn.len() - 2..], ["=== EPIC-1 Launch ===", "=== Dashboard ==="]);
+        assert_eq!(app.session.undo.undo_label(), Some("create epic \"Launch\""));
+    }
+
+    #[test]
+    fn test_board_moves_are_undone_and_redone_with_the_shortcuts() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let epic_uuid = create_epic(&mut state, "Launch", String::new(), &SystemClock).unwrap();
+        let story_uuid = create_story(&mut state, epic_uuid, "Login", String::new(), &SystemClock).unwrap();
+        let clock: Arc<dyn Clock> = Arc::new(SystemClock);
+        let mut app = App::new(Session::new(state.clone(), "password").unwrap(), std::env::temp_dir(), Arc::clone(&clock));
+        let mut navigator = Navigator::new(Box::new(KanbanPage::new(&state, Arc::clone(&clock))));
+        let mut editor = LineEditor::new();
+        let wide = Size::new(160, 40);
+        let mut type_keys = |navigator: &mut Navigator, app: &mut App, bytes: &[u8]| {
+            navigator.run(&mut editor, &mut decode_keys(bytes).into_iter(), wide, clock.as_ref(), &mut |_| (), &mut |navigator, action| app.handle(navigator, action));
+        };
+
+        type_keys(&mut navigator, &mut app, b"L\r");
+        assert_eq!(get_story(&app.session.state, story_uuid).unwrap().status, Status::InProgress);
+        assert_eq!(app.session.undo.undo_label(), Some("set story status to InProgress"));
+
+        type_keys(&mut navigator, &mut app, b"\x1a");
+        assert_eq!(get_story(&app.session.state, story_uuid).unwrap().status, Status::Open);
+        assert!(navigator.render(wide)[1].starts_with("Open (1)"));
+        type_keys(&mut navigator, &mut app, b"\x19");
+        assert_eq!(get_story(&app.session.state, story_uuid).unwrap().status, Status::InProgress);
+        assert!(navigator.render(wide)[1].starts_with("Open (0)"));
+        type_keys(&mut navigator, &mut app, b"\x19");
+
+        let messages: Vec<&str> = app.toasts.log().map(|toast| toast.message.as_str()).collect();
+        assert_eq!(messages, ["Undid set story status to InProgress.", "Redid set story status to InProgress.", "There is nothing to redo."]);
+    }
+}
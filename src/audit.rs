@@ -0,0 +1,37 @@
+//! # Audit Module
+//! A record of security-relevant account changes, stored in the encrypted database.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// # Audit Event
+/// A security-relevant change to an account.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum AuditEvent {
+    /// Two-factor authentication (TOTP or HOTP) was turned off.
+    SecondFactorDisabled,
+    /// A TOTP authenticator was enrolled as the second factor.
+    SecondFactorEnabled,
+    /// The account password was changed, and the database's data key wrapped with the new key.
+    PasswordChanged,
+    /// The account was renamed.
+    UsernameChanged,
+}
+
+/// # Audit Entry
+/// A single audit log record.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct AuditEntry {
+    /// When the event happened.
+    pub timestamp: DateTime<Utc>,
+    /// What happened.
+    pub event: AuditEvent,
+}
+
+impl AuditEntry {
+    /// Creates a new audit entry.
+    #[must_use]
+    pub fn new(timestamp: DateTime<Utc>, event: AuditEvent) -> Self {
+        Self { timestamp, event }
+    }
+}
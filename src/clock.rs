@@ -0,0 +1,73 @@
+//! # Clock Module
+//! An injectable source of the current time, used for TOTP verification and timestamps so that
+//! time-dependent behavior can be tested deterministically.
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use std::cell::Cell;
+
+/// # Clock trait
+/// Provides the current time.
+pub trait Clock {
+    /// Returns the current time in UTC.
+    fn now(&self) -> DateTime<Utc>;
+
+    /// Returns the current time as whole seconds since the Unix epoch (0 for pre-epoch times).
+    fn unix_seconds(&self) -> u64 {
+        u64::try_from(self.now().timestamp()).unwrap_or(0)
+    }
+
+    /// Returns the current calendar date in UTC, for due-date comparisons.
+    fn today(&self) -> NaiveDate {
+        self.now().date_naive()
+    }
+}
+
+/// # System Clock
+/// The real wall clock.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// # Fixed Clock
+/// A clock that only moves when told to, for tests.
+///
+/// # Examples
+/// ```rust
+/// use ironyyy::clock::{Clock, FixedClock};
+/// use chrono::{DateTime, Duration};
+/// let clock = FixedClock::new(DateTime::from_timestamp(59, 0).unwrap());
+/// assert_eq!(clock.unix_seconds(), 59);
+/// clock.advance(Duration::seconds(1));
+/// assert_eq!(clock.unix_seconds(), 60);
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FixedClock(Cell<DateTime<Utc>>);
+
+impl FixedClock {
+    /// Creates a clock frozen at the given time.
+    #[must_use]
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self(Cell::new(now))
+    }
+
+    /// Sets the clock to the given time.
+    pub fn set(&self, now: DateTime<Utc>) {
+        self.0.set(now);
+    }
+
+    /// Moves the clock forward (or backward, for negative durations).
+    pub fn advance(&self, by: Duration) {
+        self.0.set(self.0.get() + by);
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0.get()
+    }
+}
@@ -4,54 +4,258 @@
 //! * Each user has their own database file (in JSON format) stored in the `databases` folder.
 //! * Each database file is named after the user's UUID (e.g., `<user_uuid>.json`).
 //! * The database file contains all of the user's epics and stories, as well as their account information.
-//! * Each database file is encrypted with a vetted postquantum algorithm (via the `rustls` crate) using a high-entropy key reproducibly derived by concatenating the user's password and their (already-random) UUID.
+//! * Each database file is encrypted with AES-256-GCM (via the `aes-gcm` crate) under a random 256-bit data key, with a fresh nonce on every save.
+//! * The data key is stored in the file's clear header, wrapped with AES-256-GCM by a password key derived with Argon2id from the user's password, salted with their (already-random) UUID. The data key also encrypts the attachments. Changing the password replaces the data key and encrypts everything again, so the old password stops opening new saves.
+//! * Once the user registers a FIDO2 security key, the password key is combined with a secret only the user's security keys can unwrap, so the file can't be opened without one of them.
+
+pub mod attachments;
+pub mod migrations;
+pub mod shared;
+pub mod storage;
 
 use rand_core::{TryRngCore, OsRng};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
-use crate::models::{epics::Epic, stories::Story};
+use crate::models::{attachments::Attachment, epics::Epic, labels::Label, sprints::Sprint, stories::Story, tasks::Task};
+use crate::models::custom_fields::FieldDefinition;
+use crate::models::events::Event;
+use crate::models::goals::Goal;
+use crate::models::history::HistoryEntry;
+use crate::models::projects::Project;
+use crate::models::roles::{Membership, Role};
+use crate::models::settings::Settings;
+use crate::models::statuses::{StatusDefinition, default_statuses};
+use crate::models::work_logs::WorkLog;
+use crate::models::workflow::Workflow;
+use crate::security::{Argon2EncryptionKey, Ciphertext, SecurityError};
 use crate::users::User;
 
+/// The folder that holds every user's database file.
+pub const DATABASES_FOLDER: &str = "databases";
+
 /// # Clear Text Database State struct
 /// Represents the state of a user's database, including their account info, epics, and stories.
 #[derive(Clone, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
 pub struct ClearTextDBState {
+    /// The schema version this state was saved with (see [`migrations`]).
+    #[serde(default)]
+    pub schema_version: u32,
     /// The user account information.
     pub user: User,
     /// The list of epics associated with the user.
     pub epics: Vec<Epic>,
     /// The list of stories associated with the user.
     pub stories: Vec<Story>,
+    /// The list of tasks belonging to the user's stories.
+    #[serde(default)]
+    pub tasks: Vec<Task>,
+    /// The labels defined in this database.
+    #[serde(default)]
+    pub labels: Vec<Label>,
+    /// The sprints planned in this database.
+    #[serde(default)]
+    pub sprints: Vec<Sprint>,
+    /// Metadata for the files attached to epics and stories.
+    #[serde(default)]
+    pub attachments: Vec<Attachment>,
+    /// The workflow statuses available in this database. Databases saved before statuses were
+    /// configurable get the built-ins.
+    #[serde(default = "default_statuses")]
+    pub statuses: Vec<StatusDefinition>,
+    /// The status transition rules for this database.
+    #[serde(default)]
+    pub workflow: Workflow,
+    /// The change history of every epic and story, oldest first.
+    #[serde(default)]
+    pub history: Vec<HistoryEntry>,
+    /// Time logged against stories.
+    #[serde(default)]
+    pub work_logs: Vec<WorkLog>,
+    /// Whether an epic is closed automatically once all of its stories are done. When off, such
+    /// epics are offered for closing instead.
+    #[serde(default)]
+    pub auto_close_epics: bool,
+    /// The custom fields defined in this database.
+    #[serde(default)]
+    pub custom_fields: Vec<FieldDefinition>,
+    /// The projects in this database.
+    #[serde(default)]
+    pub projects: Vec<Project>,
+    /// The project currently being worked in; new epics are added to it.
+    #[serde(default)]
+    pub current_project: Option<Uuid>,
+    /// The goals that epics in this database work towards.
+    #[serde(default)]
+    pub goals: Vec<Goal>,
+    /// The number the next new epic gets.
+    #[serde(default)]
+    pub next_epic_number: u32,
+    /// The number the next new story gets.
+    #[serde(default)]
+    pub next_story_number: u32,
+    /// The user's interface preferences.
+    #[serde(default)]
+    pub settings: Settings,
+    /// The members of a [shared](shared) database and their roles, encrypted with the data so
+    /// they can't be changed without it. Empty in a user's own database.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub members: Vec<Membership>,
+    /// Whether the state has changed since it was loaded or last saved. Not saved itself.
+    #[serde(skip)]
+    dirty: bool,
+    /// Events emitted since they were last taken (see [`crate::service::events`]). Not saved.
+    #[serde(skip)]
+    events: Vec<Event>,
+    /// What the user who opened the database may do; the owner of their own database, or their
+    /// role in a [shared](shared) one. Not saved.
+    #[serde(skip)]
+    role: Role,
 }
 
 impl ClearTextDBState {
-    /// Creates a new, empty ClearTextDBState for the given user.
+    /// Creates a new, empty `ClearTextDBState` for the given user.
+    #[must_use]
     pub fn new(user: User) -> Self {
         Self {
+            schema_version: migrations::SCHEMA_VERSION,
             user,
             epics: Vec::new(),
             stories: Vec::new(),
+            tasks: Vec::new(),
+            labels: Vec::new(),
+            sprints: Vec::new(),
+            attachments: Vec::new(),
+            statuses: default_statuses(),
+            workflow: Workflow::default(),
+            history: Vec::new(),
+            work_logs: Vec::new(),
+            auto_close_epics: false,
+            custom_fields: Vec::new(),
+            projects: Vec::new(),
+            current_project: None,
+            goals: Vec::new(),
+            next_epic_number: 1,
+            next_story_number: 1,
+            settings: Settings::default(),
+            members: Vec::new(),
+            dirty: false,
+            events: Vec::new(),
+            role: Role::Owner,
         }
     }
 
-    /// Converts the ClearTextDBState into a CypherTextDBState by encrypting the data.
-    pub fn to_cypher_text(self) -> Result<CypherTextDBState, Box<dyn std::error::Error>> {
-        
-        let serialized_data = serde_json::to_vec(&self)?;
-        let encrypted_data = todo!("Implement encryption of serialized_data using a postquantum algorithm");
+    /// Whether the state has unsaved changes.
+    #[must_use]
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Records that the state has unsaved changes. The service layer calls this on every mutation.
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Records that the state has just been saved.
+    pub fn mark_saved(&mut self) {
+        self.dirty = false;
+    }
+
+    /// Queues an event for subscribers. The service layer calls this when it changes an epic or
+    /// story.
+    pub fn emit(&mut self, event: Event) {
+        self.events.push(event);
+    }
+
+    /// What the user who opened the database may do.
+    #[must_use]
+    pub fn role(&self) -> Role {
+        self.role
+    }
+
+    /// Sets what the user who opened the database may do. The service layer refuses the changes
+    /// the role doesn't allow.
+    pub fn set_role(&mut self, role: Role) {
+        self.role = role;
+    }
+
+    /// Takes the queued events, oldest first, leaving none queued.
+    pub fn take_events(&mut self) -> Vec<Event> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Converts the `ClearTextDBState` into a `CypherTextDBState` by encrypting the data.
+    ///
+    /// # Errors
+    /// Returns an error if serialization, random number generation, or encryption fails.
+    pub fn to_cypher_text(&self, encryption_key: &Argon2EncryptionKey) -> Result<CypherTextDBState, Box<dyn std::error::Error>> {
         // Bytes to indicate whether later decryption was successful or not
         let mut indicator = [0u8; 16];
-        let _ = OsRng.try_fill_bytes(&mut indicator)?;
-        
+        OsRng.try_fill_bytes(&mut indicator).map_err(|_| SecurityError::TryRngCore)?;
+        let mut nonce = [0u8; 12];
+        OsRng.try_fill_bytes(&mut nonce).map_err(|_| SecurityError::TryRngCore)?;
+
+        let serialized_data = serde_json::to_string(&(indicator, self))?;
+        let encrypted_data = Ciphertext::encrypt(&serialized_data, encryption_key, &nonce)?;
+
         Ok(CypherTextDBState {
             user_uuid: self.user.user_uuid,
-            username: self.user.username,
+            username: self.user.username.clone(),
             indicator,
-            encrypted_data, // In a real implementation, this would be encrypted data
+            nonce,
+            encrypted_data: encrypted_data.0,
+            password_slot: None,
+            security_key_slots: Vec::new(),
         })
     }
 }
 
+/// # Key Slot struct
+/// A database's data key, encrypted (wrapped) with another key, such as the one derived from the
+/// user's password.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct KeySlot {
+    /// AES-GCM nonce used for `wrapped_key`.
+    pub nonce: [u8; 12],
+    /// The data key, encrypted with the wrapping key.
+    pub wrapped_key: Vec<u8>,
+}
+
+impl KeySlot {
+    /// Wraps `data_key` with `wrapping_key`.
+    ///
+    /// # Errors
+    /// * `SecurityError` - If the nonce can't be generated or encryption fails.
+    pub fn wrap(data_key: &Argon2EncryptionKey, wrapping_key: &Argon2EncryptionKey) -> Result<Self, SecurityError> {
+        let mut nonce = [0u8; 12];
+        OsRng.try_fill_bytes(&mut nonce).map_err(|_| SecurityError::TryRngCore)?;
+        let wrapped_key = Ciphertext::encrypt_bytes(&data_key.0, wrapping_key, &nonce)?.0;
+        Ok(Self { nonce, wrapped_key })
+    }
+
+    /// Unwraps the data key with `wrapping_key`.
+    ///
+    /// # Errors
+    /// * `SecurityError::AesGcm` - If `wrapping_key` is wrong or the slot was tampered with.
+    /// * `SecurityError::Hash` - If the unwrapped key has the wrong length.
+    pub fn unwrap_key(&self, wrapping_key: &Argon2EncryptionKey) -> Result<Argon2EncryptionKey, SecurityError> {
+        let data_key = Ciphertext(self.wrapped_key.clone()).decrypt_bytes(wrapping_key, &self.nonce)?;
+        Ok(Argon2EncryptionKey(data_key.try_into().map_err(|_| SecurityError::Hash)?))
+    }
+}
+
+/// # Security Key Slot struct
+/// A database's security key secret, wrapped with the `hmac-secret` output of one FIDO2 security
+/// key. It is in clear, so the key can be asked for its output before the database is open.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct SecurityKeySlot {
+    /// The credential ID of the security key.
+    pub credential_id: Vec<u8>,
+    /// The salt the security key's `hmac-secret` output is asked for over.
+    pub salt: [u8; 32],
+    /// The security key secret, wrapped with the `hmac-secret` output.
+    pub key_slot: KeySlot,
+}
+
 /// # Cypher Text Database State struct
 /// Represents the encrypted state of a user's database as a vector of bytes.
 #[derive(Clone, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
@@ -62,57 +266,56 @@ pub struct CypherTextDBState {
     pub username: String,
     /// Indicator bytes in clear
     pub indicator: [u8; 16],
+    /// AES-GCM nonce used for `encrypted_data`
+    pub nonce: [u8; 12],
     /// The encrypted data as a vector of bytes.
     pub encrypted_data: Vec<u8>,
+    /// The data key, wrapped with the key derived from the user's password. Databases saved
+    /// before data keys have none: their data key is the password-derived key itself.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password_slot: Option<KeySlot>,
+    /// The security key secret, wrapped for each of the user's FIDO2 security keys. While there
+    /// are any, the password slot is wrapped with the password key combined with that secret (see
+    /// [`crate::security::fido2::combine_keys`]), so the file can't be opened without one of them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub security_key_slots: Vec<SecurityKeySlot>,
 }
-/*
+
 impl CypherTextDBState {
-    /// Converts the CypherTextDBState back into a ClearTextDBState by decrypting the data.
-    pub fn to_clear_text(self) -> Result<ClearTextDBState, Box<dyn std::error::Error>> {
-        let decrypted_data = todo!("Implement decryption of self.encrypted_data using a postquantum algorithm");
-        let clear_text_db_state: ClearTextDBState = serde_json::from_slice(&decrypted_data)?;
-        Ok(clear_text_db_state)
+    /// Converts the `CypherTextDBState` back into a `ClearTextDBState` by decrypting the data.
+    ///
+    /// # Errors
+    /// Returns an error if decryption fails (e.g. wrong key), the payload cannot be parsed or
+    /// upgraded to the current schema, or the decrypted indicator does not match the clear one.
+    pub fn to_clear_text(&self, encryption_key: &Argon2EncryptionKey) -> Result<ClearTextDBState, Box<dyn std::error::Error>> {
+        let decrypted_data = Ciphertext(self.encrypted_data.clone()).decrypt(encryption_key, &self.nonce)?;
+        let (indicator, clear_text_db_state): ([u8; 16], serde_json::Value) = serde_json::from_str(&decrypted_data)?;
+        if indicator != self.indicator {
+            return Err("Database indicator mismatch".into());
+        }
+        migrations::upgrade(clear_text_db_state)
     }
 }
-*/
 
 
 /// # Scan for DB function
-/// Scans the `databases` folder for existing user database files. Each file is parsed to extract the username and UUID, which are stored in an in-memory list of existing users for login purposes.
-/// 
+/// Scans the `databases` folder for existing user database files and lists their users, for the
+/// login page. The folder is created if it doesn't exist yet.
+///
+/// ## Returns
+/// * `std::io::Result<Vec<(Uuid, String)>>` - Each user's UUID and username, sorted by username.
+///
+/// ## Errors
+/// Returns an error if the `databases` folder cannot be created or read.
+///
 /// ## Example
-/// ```rust
+/// ```rust,no_run
 /// use ironyyy::db::scan_for_db;
-/// scan_for_db();
+/// for (_, username) in scan_for_db().unwrap() {
+///     println!("{username}");
+/// }
 /// ```
-/// 
-/// ## Navigation side effects
-/// Takes the user to the LoginOrRegister page after scanning for existing databases.
-pub fn scan_for_db() -> std::io::Result<()> {
-    // Scan the `databases` folder for existing user database files.
-    // For each file found, parse it to extract the username and UUID.
-    // Store the extracted information in an in-memory list of existing users.
-    let target_folder = "databases";
-    
-    // Check if the target folder exists
-    match std::fs::read_dir(target_folder) {
-        Ok(_) => (),
-        Err(_) => {
-            // If the folder does not exist, create it
-            std::fs::create_dir(target_folder)?;
-        }
-    };
-
-    for entry in std::fs::read_dir(target_folder)? {
-        let entry = entry?;
-        let path = entry.path();
-        if path.is_file() {
-            // Here, you would typically open the file, decrypt it, and parse the JSON
-            // to extract the username and UUID. For simplicity, we'll just print the file name.
-            todo!("Implement database file parsing to extract username and UUID")
-        }
-    }
-
-
-    Ok(())
-}
\ No newline at end of file
+pub fn scan_for_db() -> std::io::Result<Vec<(Uuid, String)>> {
+    std::fs::create_dir_all(DATABASES_FOLDER)?;
+    storage::detect_users(std::path::Path::new(DATABASES_FOLDER))
+}
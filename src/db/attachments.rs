@@ -0,0 +1,349 @@
+//! # Attachments
+//! Encrypted storage for files attached to epics and stories.
+//!
+//! Each attachment is encrypted with the user's database key and written to
+//! `<databases>/<user_uuid>/attachments/<attachment_uuid>.bin`. Its metadata (name, size, digest,
+//! nonce) lives in the encrypted database itself. When the data key changes, every attachment is
+//! [encrypted again](reencrypt_attachments).
+
+use super::ClearTextDBState;
+use super::storage::write_replacing;
+use crate::models::attachments::Attachment;
+use crate::models::roles::Permission;
+use crate::security::{Argon2EncryptionKey, Ciphertext, SecurityError};
+use crate::clock::Clock;
+use crate::service::{ServiceError, get_epic, get_story, require, update_epic, update_story};
+use rand_core::{OsRng, TryRngCore};
+use sha2::{Digest, Sha256};
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// # Attachment Target enum
+/// The item a file is attached to.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum AttachmentTarget {
+    /// An epic, by UUID.
+    Epic(Uuid),
+    /// A story, by UUID.
+    Story(Uuid),
+}
+
+/// Returns the path of an attachment's encrypted file.
+///
+/// # Arguments
+/// * `databases_dir` - The folder holding the database files (normally [`super::DATABASES_FOLDER`]).
+/// * `user_uuid` - The owner of the database.
+/// * `attachment_uuid` - The attachment.
+#[must_use]
+pub fn attachment_path(databases_dir: &Path, user_uuid: Uuid, attachment_uuid: Uuid) -> PathBuf {
+    databases_dir
+        .join(user_uuid.to_string())
+        .join("attachments")
+        .join(format!("{attachment_uuid}.bin"))
+}
+
+/// Encrypts a file's contents, stores them, and attaches the file to an epic or story.
+///
+/// # Arguments
+/// * `state` - The database state to record the attachment in.
+/// * `encryption_key` - The user's database key.
+/// * `databases_dir` - The folder holding the database files.
+/// * `target` - The epic or story to attach the file to.
+/// * `name` - The file name to remember.
+/// * `contents` - The file contents.
+/// * `clock` - The source of the history timestamp.
+/// # Returns
+/// * `Result<Uuid, Box<dyn std::error::Error>>` - The UUID of the new attachment.
+/// # Errors
+/// * `ServiceError::EpicNotFound` / `ServiceError::StoryNotFound` - If the target does not exist.
+/// * `SecurityError` - If the nonce could not be generated or encryption fails.
+/// * `std::io::Error` - If the encrypted file could not be written.
+pub fn save_attachment(
+    state: &mut ClearTextDBState,
+    encryption_key: &Argon2EncryptionKey,
+    databases_dir: &Path,
+    target: AttachmentTarget,
+    name: String,
+    contents: &[u8],
+    clock: &dyn Clock,
+) -> Result<Uuid, Box<dyn std::error::Error>> {
+    require(state, Permission::Edit)?;
+    // Fail on a missing target before anything is written.
+    match target {
+        AttachmentTarget::Epic(epic_uuid) => {
+            get_epic(state, epic_uuid)?;
+        }
+        AttachmentTarget::Story(story_uuid) => {
+            get_story(state, story_uuid)?;
+        }
+    }
+
+    let mut nonce = [0u8; 12];
+    OsRng.try_fill_bytes(&mut nonce).map_err(|_| SecurityError::TryRngCore)?;
+    let attachment = Attachment {
+        attachment_uuid: Uuid::new_v4(),
+        name,
+        size: u64::try_from(contents.len())?,
+        sha256: Sha256::digest(contents).to_vec(),
+        nonce,
+    };
+    let encrypted = Ciphertext::encrypt_bytes(contents, encryption_key, &nonce)?;
+    write_private_file(&attachment_path(databases_dir, state.user.user_uuid, attachment.attachment_uuid), &encrypted.0)?;
+
+    let attachment_uuid = attachment.attachment_uuid;
+    match target {
+        AttachmentTarget::Epic(epic_uuid) => update_epic(state, epic_uuid, clock, |epic| epic.attachments.push(attachment_uuid))?,
+        AttachmentTarget::Story(story_uuid) => update_story(state, story_uuid, clock, |story| story.attachments.push(attachment_uuid))?,
+    }
+    state.mark_dirty();
+    state.attachments.push(attachment);
+    Ok(attachment_uuid)
+}
+
+/// Reads, decrypts, and checks an attachment's contents.
+///
+/// # Errors
+/// * `ServiceError::AttachmentNotFound` - If the attachment is not in the database.
+/// * `std::io::Error` - If the encrypted file could not be read.
+/// * `SecurityError::AesGcm` - If the file was tampered with or the key is wrong.
+/// * A digest mismatch error if the decrypted contents don't match the recorded hash.
+pub fn extract_attachment(
+    state: &ClearTextDBState,
+    encryption_key: &Argon2EncryptionKey,
+    databases_dir: &Path,
+    attachment_uuid: Uuid,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let attachment = get_attachment(state, attachment_uuid)?;
+    let encrypted = std::fs::read(attachment_path(databases_dir, state.user.user_uuid, attachment_uuid))?;
+    let contents = Ciphertext(encrypted).decrypt_bytes(encryption_key, &attachment.nonce)?;
+    if Sha256::digest(&contents)[..] != attachment.sha256[..] {
+        return Err("Attachment digest mismatch".into());
+    }
+    Ok(contents)
+}
+
+/// Decrypts an attachment and writes its contents to `destination`.
+///
+/// # Errors
+/// Same as [`extract_attachment`], plus `std::io::Error` if `destination` could not be written.
+pub fn extract_attachment_to(
+    state: &ClearTextDBState,
+    encryption_key: &Argon2EncryptionKey,
+    databases_dir: &Path,
+    attachment_uuid: Uuid,
+    destination: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let contents = extract_attachment(state, encryption_key, databases_dir, attachment_uuid)?;
+    std::fs::write(destination, contents)?;
+    Ok(())
+}
+
+/// Removes an attachment's metadata, its links from epics and stories, and its encrypted file.
+///
+/// # Returns
+/// * `Result<Attachment, Box<dyn std::error::Error>>` - The removed attachment's metadata.
+/// # Errors
+/// * `ServiceError::AttachmentNotFound` - If the attachment is not in the database.
+/// * `std::io::Error` - If the encrypted file exists but could not be removed.
+pub fn delete_attachment(
+    state: &mut ClearTextDBState,
+    databases_dir: &Path,
+    attachment_uuid: Uuid,
+    clock: &dyn Clock,
+) -> Result<Attachment, Box<dyn std::error::Error>> {
+    require(state, Permission::Edit)?;
+    let index = state
+        .attachments
+        .iter()
+        .position(|attachment| attachment.attachment_uuid == attachment_uuid)
+        .ok_or(ServiceError::AttachmentNotFound(attachment_uuid))?;
+    delete_attachment_files(databases_dir, state.user.user_uuid, &[attachment_uuid])?;
+    let epic_uuids: Vec<Uuid> = state.epics.iter().filter(|epic| epic.attachments.contains(&attachment_uuid)).map(|epic| epic.epic_uuid).collect();
+    for epic_uuid in epic_uuids {
+        update_epic(state, epic_uuid, clock, |epic| epic.attachments.retain(|uuid| *uuid != attachment_uuid))?;
+    }
+    let story_uuids: Vec<Uuid> = state.stories.iter().filter(|story| story.attachments.contains(&attachment_uuid)).map(|story| story.story_uuid).collect();
+    for story_uuid in story_uuids {
+        update_story(state, story_uuid, clock, |story| story.attachments.retain(|uuid| *uuid != attachment_uuid))?;
+    }
+    state.mark_dirty();
+    Ok(state.attachments.remove(index))
+}
+
+/// Removes the encrypted files of attachments whose metadata is already gone, e.g. the UUIDs
+/// returned by [`crate::service::stories::delete_story`]. Files that don't exist are skipped.
+///
+/// # Errors
+/// * `std::io::Error` - If a file exists but could not be removed.
+pub fn delete_attachment_files(databases_dir: &Path, user_uuid: Uuid, attachment_uuids: &[Uuid]) -> std::io::Result<()> {
+    for &attachment_uuid in attachment_uuids {
+        match std::fs::remove_file(attachment_path(databases_dir, user_uuid, attachment_uuid)) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err),
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+/// # Staged Attachments struct
+/// Attachments encrypted again with a new data key by [`reencrypt_attachments`], written next to
+/// the files they replace until the database saved with the new key is written.
+#[must_use = "staged attachments must be committed or discarded"]
+pub struct StagedAttachments {
+    /// Each attachment's current file and the new file waiting to replace it.
+    files: Vec<(PathBuf, PathBuf)>,
+}
+
+impl StagedAttachments {
+    /// Moves the new files over the old ones, once the database with the new data key is saved.
+    ///
+    /// # Errors
+    /// * `std::io::Error` - If a file can't be moved.
+    pub fn commit(self) -> std::io::Result<()> {
+        for (path, staged) in self.files {
+            std::fs::rename(staged, path)?;
+        }
+        Ok(())
+    }
+
+    /// Removes the new files, leaving the attachments encrypted with the old data key, e.g. when
+    /// the database couldn't be saved.
+    pub fn discard(self) {
+        for (_, staged) in self.files {
+            // Best effort: a leftover file is overwritten by the next attempt.
+            let _ = std::fs::remove_file(staged);
+        }
+    }
+}
+
+/// Encrypts every attachment again with `new_key`, with fresh nonces recorded in `state`. The new
+/// files are written next to the old ones and only replace them on
+/// [`StagedAttachments::commit`], so the database must be saved with the new key first.
+/// Attachments whose file is missing are skipped.
+///
+/// # Errors
+/// * `std::io::Error` - If a file can't be read or written.
+/// * `SecurityError` - If a file can't be decrypted with `old_key` or a nonce can't be generated.
+pub fn reencrypt_attachments(
+    state: &mut ClearTextDBState,
+    old_key: &Argon2EncryptionKey,
+    new_key: &Argon2EncryptionKey,
+    databases_dir: &Path,
+) -> Result<StagedAttachments, Box<dyn std::error::Error>> {
+    let mut staged = StagedAttachments { files: Vec::new() };
+    for attachment in &mut state.attachments {
+        let path = attachment_path(databases_dir, state.user.user_uuid, attachment.attachment_uuid);
+        let mut staged_path = path.clone().into_os_string();
+        staged_path.push(".new");
+        let staged_path = PathBuf::from(staged_path);
+        match reencrypt_file(&path, &staged_path, attachment.nonce, old_key, new_key) {
+            Ok(Some(nonce)) => {
+                attachment.nonce = nonce;
+                staged.files.push((path, staged_path));
+            }
+            Ok(None) => {}
+            Err(err) => {
+                staged.discard();
+                return Err(err);
+            }
+        }
+    }
+    Ok(staged)
+}
+
+/// Decrypts the attachment file at `path` and writes it to `staged_path` encrypted with `new_key`.
+///
+/// # Returns
+/// * `Result<Option<[u8; 12]>, Box<dyn std::error::Error>>` - The new nonce, or `None` if there is
+///   no file at `path`.
+fn reencrypt_file(
+    path: &Path,
+    staged_path: &Path,
+    nonce: [u8; 12],
+    old_key: &Argon2EncryptionKey,
+    new_key: &Argon2EncryptionKey,
+) -> Result<Option<[u8; 12]>, Box<dyn std::error::Error>> {
+    let encrypted = match std::fs::read(path) {
+        Ok(encrypted) => encrypted,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    let contents = Ciphertext(encrypted).decrypt_bytes(old_key, &nonce)?;
+    let mut new_nonce = [0u8; 12];
+    OsRng.try_fill_bytes(&mut new_nonce).map_err(|_| SecurityError::TryRngCore)?;
+    write_replacing(staged_path, &Ciphertext::encrypt_bytes(&contents, new_key, &new_nonce)?.0)?;
+    Ok(Some(new_nonce))
+}
+
+/// Looks up an attachment's metadata by UUID.
+///
+/// # Errors
+/// * `ServiceError::AttachmentNotFound` - If no such attachment exists.
+pub fn get_attachment(state: &ClearTextDBState, attachment_uuid: Uuid) -> Result<&Attachment, ServiceError> {
+    state
+        .attachments
+        .iter()
+        .find(|attachment| attachment.attachment_uuid == attachment_uuid)
+        .ok_or(ServiceError::AttachmentNotFound(attachment_uuid))
+}
+
+/// Writes a new file (creating parent folders), readable by the owner only on Unix.
+fn write_private_file(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)?.write_all(contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::models::stories::Story;
+    use crate::users::User;
+
+    #[test]
+    fn test_save_extract_delete() {
+        let databases_dir = std::env::temp_dir().join(format!("ironyyy-attachments-{}", Uuid::new_v4()));
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let story = Story::new("Story".to_string(), String::new());
+        let story_uuid = story.story_uuid;
+        state.stories.push(story);
+        let key = Argon2EncryptionKey([3; 32]);
+
+        let contents = b"\x00\x01 binary contents \xff";
+        let target = AttachmentTarget::Story(story_uuid);
+        let uuid = save_attachment(&mut state, &key, &databases_dir, target, "notes.bin".to_string(), contents, &SystemClock).unwrap();
+        assert_eq!(state.stories[0].attachments, vec![uuid]);
+        assert_eq!(get_attachment(&state, uuid).unwrap().size, contents.len() as u64);
+
+        let path = attachment_path(&databases_dir, state.user.user_uuid, uuid);
+        assert_ne!(std::fs::read(&path).unwrap(), contents);
+        assert_eq!(extract_attachment(&state, &key, &databases_dir, uuid).unwrap(), contents);
+        assert!(extract_attachment(&state, &Argon2EncryptionKey([4; 32]), &databases_dir, uuid).is_err());
+
+        delete_attachment(&mut state, &databases_dir, uuid, &SystemClock).unwrap();
+        assert!(state.stories[0].attachments.is_empty() && state.attachments.is_empty());
+        assert!(!path.exists());
+
+        // Deleting the story drops the metadata; the caller removes the files.
+        let uuid = save_attachment(&mut state, &key, &databases_dir, target, "notes.bin".to_string(), contents, &SystemClock).unwrap();
+        let (_, removed) = crate::service::stories::delete_story(&mut state, story_uuid, &SystemClock).unwrap();
+        assert_eq!(removed, vec![uuid]);
+        assert!(state.attachments.is_empty());
+        delete_attachment_files(&databases_dir, state.user.user_uuid, &removed).unwrap();
+        assert!(!attachment_path(&databases_dir, state.user.user_uuid, uuid).exists());
+
+        let missing = AttachmentTarget::Epic(Uuid::new_v4());
+        assert!(save_attachment(&mut state, &key, &databases_dir, missing, "x".to_string(), b"x", &SystemClock).is_err());
+        std::fs::remove_dir_all(&databases_dir).unwrap();
+    }
+}
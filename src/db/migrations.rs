@@ -0,0 +1,223 @@
+//! # Migrations
+//! Upgrades for databases saved with an older schema.
+//!
+//! A database records the schema version it was saved with. When it is opened, the decrypted JSON
+//! is upgraded one version at a time before it is turned into a [`ClearTextDBState`], so fields can
+//! be added or renamed without breaking older databases. Each step upgrades every model in the
+//! database through its own `*_from_vN` function.
+//!
+//! To change the schema, bump [`SCHEMA_VERSION`] and add a step to [`STEPS`].
+
+use super::ClearTextDBState;
+use crate::models::Status;
+use crate::models::statuses::{StatusDefinition, add_missing_builtins};
+use serde_json::{Map, Value, json};
+
+/// The schema version written by this build.
+pub const SCHEMA_VERSION: u32 = 4;
+
+/// The version assumed for databases saved before schema versions were recorded.
+const UNVERSIONED: u32 = 1;
+
+/// One upgrade step, taking a database from version `N` to `N + 1`.
+type Step = fn(&mut Map<String, Value>) -> Result<(), Box<dyn std::error::Error>>;
+
+/// The upgrade steps; `STEPS[i]` upgrades from version `i + 1`.
+const STEPS: [Step; (SCHEMA_VERSION - 1) as usize] = [db_from_v1, db_from_v2, db_from_v3];
+
+/// Upgrades a decrypted database to the current schema and parses it.
+///
+/// # Errors
+/// Returns an error if the JSON isn't an object, was saved by a newer version of the app, or
+/// doesn't match the schema after upgrading.
+pub fn upgrade(mut value: Value) -> Result<ClearTextDBState, Box<dyn std::error::Error>> {
+    let db = value.as_object_mut().ok_or("Database is not a JSON object")?;
+    let version = match db.get("schema_version") {
+        Some(version) => u32::try_from(version.as_u64().ok_or("Invalid schema version")?)?,
+        None => UNVERSIONED,
+    };
+    if version > SCHEMA_VERSION {
+        return Err(format!("Database schema version {version} is newer than this app supports ({SCHEMA_VERSION})").into());
+    }
+    for step in &STEPS[(version.max(UNVERSIONED) - 1) as usize..] {
+        step(db)?;
+    }
+    db.insert("schema_version".to_string(), json!(SCHEMA_VERSION));
+    let mut state: ClearTextDBState = serde_json::from_value(value)?;
+    // An upgraded database differs from what is on disk until it is saved again.
+    if version < SCHEMA_VERSION {
+        state.mark_dirty();
+    }
+    Ok(state)
+}
+
+/// Version 1 to 2: fills in every field added to epics, stories, and users before versioning
+/// existed, and adds the newer built-in statuses.
+fn db_from_v1(db: &mut Map<String, Value>) -> Result<(), Box<dyn std::error::Error>> {
+    for (key, upgrade) in [("epics", epic_from_v1 as fn(&mut Map<String, Value>)), ("stories", story_from_v1)] {
+        for item in db.get_mut(key).and_then(Value::as_array_mut).into_iter().flatten() {
+            upgrade(item.as_object_mut().ok_or("Invalid database item")?);
+        }
+    }
+    if let Some(user) = db.get_mut("user").and_then(Value::as_object_mut) {
+        user_from_v1(user);
+    }
+    let mut statuses: Vec<StatusDefinition> = match db.remove("statuses") {
+        Some(statuses) => serde_json::from_value(statuses)?,
+        None => Vec::new(),
+    };
+    add_missing_builtins(&mut statuses);
+    db.insert("statuses".to_string(), serde_json::to_value(statuses)?);
+    Ok(())
+}
+
+/// Version 2 to 3: numbers the existing epics and stories in their stored order, for their short
+/// IDs.
+fn db_from_v2(db: &mut Map<String, Value>) -> Result<(), Box<dyn std::error::Error>> {
+    for (key, counter) in [("epics", "next_epic_number"), ("stories", "next_story_number")] {
+        let mut next: u32 = 1;
+        for item in db.get_mut(key).and_then(Value::as_array_mut).into_iter().flatten() {
+            item.as_object_mut().ok_or("Invalid database item")?.insert("number".to_string(), json!(next));
+            next += 1;
+        }
+        db.insert(counter.to_string(), json!(next));
+    }
+    Ok(())
+}
+
+/// Version 3 to 4: drops the user's backup codes, which were hashed with SHA-256 and can't be
+/// rehashed with Argon2id without the plaintext. New ones are issued when the user next enrolls a
+/// second factor.
+fn db_from_v3(db: &mut Map<String, Value>) -> Result<(), Box<dyn std::error::Error>> {
+    let user = db.get_mut("user").and_then(Value::as_object_mut).ok_or("Invalid user")?;
+    user.insert("backup_codes".to_string(), json!([]));
+    Ok(())
+}
+
+/// Upgrades a version 1 epic.
+fn epic_from_v1(epic: &mut Map<String, Value>) {
+    add_defaults(
+        epic,
+        &[
+            ("status", json!(Status::Open)),
+            ("story_uuids", json!([])),
+            ("labels", json!([])),
+            ("start_date", Value::Null),
+            ("due_date", Value::Null),
+            ("attachments", json!([])),
+            ("archived", json!(false)),
+            ("rank", json!(0)),
+            ("custom_fields", json!({})),
+            ("color", Value::Null),
+            ("icon", Value::Null),
+        ],
+    );
+    add_timestamps(epic);
+}
+
+/// Upgrades a version 1 story.
+fn story_from_v1(story: &mut Map<String, Value>) {
+    add_defaults(
+        story,
+        &[
+            ("status", json!(Status::Open)),
+            ("task_uuids", json!([])),
+            ("labels", json!([])),
+            ("start_date", Value::Null),
+            ("due_date", Value::Null),
+            ("attachments", json!([])),
+            ("estimate", Value::Null),
+            ("assignee", Value::Null),
+            ("blocked_by", json!([])),
+            ("checklist", json!([])),
+            ("links", json!([])),
+            ("archived", json!(false)),
+            ("rank", json!(0)),
+            ("custom_fields", json!({})),
+        ],
+    );
+    add_timestamps(story);
+}
+
+/// Upgrades a version 1 user.
+fn user_from_v1(user: &mut Map<String, Value>) {
+    add_defaults(
+        user,
+        &[
+            ("hotp_instance", Value::Null),
+            ("security_keys", json!([])),
+            ("backup_codes", json!([])),
+            ("audit_log", json!([])),
+        ],
+    );
+    add_timestamps(user);
+}
+
+/// Inserts each field that is missing with its default value.
+fn add_defaults(object: &mut Map<String, Value>, defaults: &[(&str, Value)]) {
+    for (field, default) in defaults {
+        object.entry(*field).or_insert_with(|| default.clone());
+    }
+}
+
+/// Inserts missing creation and modification times as the Unix epoch, which sorts as oldest.
+fn add_timestamps(object: &mut Map<String, Value>) {
+    let epoch = json!(chrono::DateTime::<chrono::Utc>::default());
+    add_defaults(object, &[("created_at", epoch.clone()), ("updated_at", epoch)]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::users::User;
+
+    /// Serializes a fresh database and strips it back to a version 1 layout.
+    fn v1_database() -> Value {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        state.epics.push(crate::models::epics::Epic::new("Epic".to_string(), String::new()));
+        let mut value = serde_json::to_value(&state).unwrap();
+        let db = value.as_object_mut().unwrap();
+        db.remove("schema_version");
+        db.insert("statuses".to_string(), json!([{ "status": "Open", "name": "Todo", "category": "Todo", "order": 0 }]));
+        let epic = db["epics"][0].as_object_mut().unwrap();
+        for field in ["labels", "archived", "rank", "custom_fields", "color", "icon", "created_at", "updated_at"] {
+            epic.remove(field);
+        }
+        db["user"].as_object_mut().unwrap().remove("audit_log");
+        value
+    }
+
+    #[test]
+    fn test_upgrade_from_v1() {
+        let state = upgrade(v1_database()).unwrap();
+        assert_eq!(state.schema_version, SCHEMA_VERSION);
+        assert!(state.is_dirty());
+        assert!(!state.epics[0].archived);
+        assert_eq!(state.statuses[0].name, "Todo");
+        assert_eq!(state.statuses.len(), Status::BUILTINS.len());
+        assert_eq!(state.epics[0].key(), "EPIC-1");
+        assert_eq!(state.next_epic_number, 2);
+        assert_eq!(state.next_story_number, 1);
+    }
+
+    #[test]
+    fn test_upgrade_from_v3_drops_sha256_backup_codes() {
+        let mut user = User::new("alice".to_string(), "password").unwrap();
+        user.enable_totp(crate::security::totp::TotpConfig::default()).unwrap();
+        let mut value = serde_json::to_value(ClearTextDBState::new(user)).unwrap();
+        value["schema_version"] = json!(3);
+        let state = upgrade(value).unwrap();
+        assert!(state.user.backup_codes.is_empty() && state.user.totp_instance.is_some());
+    }
+
+    #[test]
+    fn test_current_and_future_versions() {
+        let state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let value = serde_json::to_value(&state).unwrap();
+        assert_eq!(upgrade(value.clone()).unwrap(), state);
+
+        let mut future = value;
+        future["schema_version"] = json!(SCHEMA_VERSION + 1);
+        assert!(upgrade(future).is_err());
+    }
+}
@@ -0,0 +1,398 @@
+//! # Shared Databases
+//! A shared database holds one set of projects that several users of the same machine open, each
+//! with their own password, so a family or small team can work on them together offline.
+//!
+//! * The data is encrypted with a random data key.
+//! * Each user has an X25519 key pair made from the random
+//!   [member secret](crate::users::User::member_secret) kept in their own database, so it survives
+//!   password changes. Each member has their own copy of the shared data key, wrapped
+//!   (encrypted) for their public key. A logged-in user can therefore open the shared databases
+//!   they belong to without typing their password again, and the data key can be wrapped for
+//!   every member without them being there.
+//! * Accounts aren't shared: the file holds no user, and each member opens it as themself.
+//! * Only the members' UUIDs and key copies are in clear. Their usernames and roles are kept in
+//!   the encrypted data as [`Membership`]s, so they can't be changed without the data key, and a
+//!   key copy without a membership opens nothing.
+//! * Shared database files are kept in the [`SHARED_FOLDER`] of the `databases` folder, named
+//!   after the shared database's UUID.
+//!
+//! Removing a member replaces the data key with a new one, wrapped for the remaining members only,
+//! so the removed member can no longer open the file once it is saved; a copy of the file they
+//! kept from before still opens for them.
+//!
+//! Each member has a [`Role`], which the database is opened with, so the service layer refuses
+//! what it doesn't allow. Managing the members and saving go through
+//! [`crate::service::shared`], which checks the role first. Every member holds the data key, so
+//! roles are enforced by the app rather than by the encryption.
+
+use super::storage::{Session, write_replacing};
+use super::{ClearTextDBState, CypherTextDBState, KeySlot};
+use crate::models::roles::{Membership, Role};
+use crate::security::{Argon2EncryptionKey, SecurityError};
+use crate::users::User;
+use rand_core::{OsRng, TryRngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+use x25519_dalek::{PublicKey, StaticSecret};
+
+/// The folder in the `databases` folder that holds the shared database files.
+pub const SHARED_FOLDER: &str = "shared";
+
+/// Context mixed into a user's member secret, so the X25519 secret isn't used anywhere else.
+const MEMBER_SECRET_CONTEXT: &[u8] = b"ironyyy shared database member key";
+
+/// Context mixed into the key a data key copy is wrapped with.
+const WRAPPING_KEY_CONTEXT: &[u8] = b"ironyyy shared database wrapping key";
+
+/// Returns the path of a shared database file, `<databases_dir>/shared/<database_uuid>.json`.
+#[must_use]
+pub fn shared_path(databases_dir: &Path, database_uuid: Uuid) -> PathBuf {
+    databases_dir.join(SHARED_FOLDER).join(format!("{database_uuid}.json"))
+}
+
+/// # Member Key struct
+/// A member's copy of a shared database's data key.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct MemberKey {
+    /// The member's UUID, in clear.
+    pub user_uuid: Uuid,
+    /// The member's X25519 public key, made from their member secret.
+    pub public_key: [u8; 32],
+    /// The public half of the one-time key pair the data key copy was wrapped with.
+    pub ephemeral_key: [u8; 32],
+    /// The data key, wrapped with the key agreed between `ephemeral_key` and `public_key`.
+    pub key_slot: KeySlot,
+}
+
+impl MemberKey {
+    /// Wraps `data_key` for the member's public key, with a fresh one-time key pair.
+    fn wrap(&mut self, data_key: &Argon2EncryptionKey) -> Result<(), SecurityError> {
+        let mut ephemeral = [0u8; 32];
+        OsRng.try_fill_bytes(&mut ephemeral).map_err(|_| SecurityError::TryRngCore)?;
+        let ephemeral = StaticSecret::from(ephemeral);
+        self.ephemeral_key = PublicKey::from(&ephemeral).to_bytes();
+        let shared_secret = ephemeral.diffie_hellman(&PublicKey::from(self.public_key));
+        self.key_slot = KeySlot::wrap(data_key, &wrapping_key(shared_secret.as_bytes(), self.ephemeral_key, self.public_key))?;
+        Ok(())
+    }
+
+    /// Unwraps the member's copy of the data key with their secret.
+    fn unwrap_key(&self, secret: &StaticSecret) -> Result<Argon2EncryptionKey, SecurityError> {
+        let shared_secret = secret.diffie_hellman(&PublicKey::from(self.ephemeral_key));
+        self.key_slot.unwrap_key(&wrapping_key(shared_secret.as_bytes(), self.ephemeral_key, self.public_key))
+    }
+}
+
+/// Derives a user's X25519 secret from the member secret kept in their own database.
+fn member_secret(session: &Session) -> Result<StaticSecret, SecurityError> {
+    let secret = session.state.user.member_secret.ok_or(SecurityError::MissingKey)?;
+    Ok(StaticSecret::from(<[u8; 32]>::from(Sha256::new().chain_update(MEMBER_SECRET_CONTEXT).chain_update(secret).finalize())))
+}
+
+/// Derives the key a data key copy is wrapped with from an X25519 shared secret and both public
+/// keys.
+fn wrapping_key(shared_secret: &[u8; 32], ephemeral_key: [u8; 32], public_key: [u8; 32]) -> Argon2EncryptionKey {
+    let digest = Sha256::new()
+        .chain_update(WRAPPING_KEY_CONTEXT)
+        .chain_update(shared_secret)
+        .chain_update(ephemeral_key)
+        .chain_update(public_key)
+        .finalize();
+    Argon2EncryptionKey(digest.into())
+}
+
+/// # Shared Database struct
+/// A shared database file: the members' key copies in clear and the encrypted data.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct SharedDatabase {
+    /// The shared database's UUID, in clear.
+    pub database_uuid: Uuid,
+    /// The shared database's name, in clear, e.g. `Family`.
+    pub name: String,
+    /// The members' copies of the data key.
+    pub member_keys: Vec<MemberKey>,
+    /// The data, encrypted with the data key, without a user but with the memberships.
+    pub data: CypherTextDBState,
+}
+
+/// Lists the shared databases `user_uuid` is a member of.
+///
+/// Files that can't be parsed as shared databases are skipped. A missing folder means none.
+/// # Returns
+/// * `std::io::Result<Vec<(Uuid, String)>>` - Each shared database's UUID and name, sorted by
+///   name.
+/// # Errors
+/// * `std::io::Error` - If the folder exists but can't be read.
+pub fn detect_shared(databases_dir: &Path, user_uuid: Uuid) -> std::io::Result<Vec<(Uuid, String)>> {
+    let entries = match std::fs::read_dir(databases_dir.join(SHARED_FOLDER)) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+    let mut databases = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().is_some_and(|extension| extension == "json")
+            && let Ok(contents) = std::fs::read_to_string(&path)
+            && let Ok(db) = serde_json::from_str::<SharedDatabase>(&contents)
+            && db.member_keys.iter().any(|member| member.user_uuid == user_uuid)
+        {
+            databases.push((db.database_uuid, db.name));
+        }
+    }
+    databases.sort_by(|a, b| a.1.cmp(&b.1));
+    Ok(databases)
+}
+
+/// Reads a shared database file.
+///
+/// # Errors
+/// * `std::io::Error` - If the file can't be read.
+/// * `serde_json::Error` - If it isn't a shared database file.
+pub fn read_shared(databases_dir: &Path, database_uuid: Uuid) -> Result<SharedDatabase, Box<dyn std::error::Error>> {
+    let contents = std::fs::read_to_string(shared_path(databases_dir, database_uuid))?;
+    Ok(serde_json::from_str(&contents)?)
+}
+
+/// # Shared Session struct
+/// A shared database opened by one of its members.
+#[derive(Clone, Debug)]
+pub struct SharedSession {
+    /// The shared database's UUID.
+    pub database_uuid: Uuid,
+    /// The shared database's name.
+    pub name: String,
+    /// The decrypted data, with the member who opened it as the user.
+    pub state: ClearTextDBState,
+    /// The members' copies of the data key.
+    member_keys: Vec<MemberKey>,
+    /// The key the data is encrypted with.
+    data_key: Argon2EncryptionKey,
+}
+
+impl SharedSession {
+    /// Creates a shared database, with the logged-in user of `owner` as its only member and owner.
+    /// It is written once [saved](crate::service::shared::save_shared).
+    ///
+    /// # Errors
+    /// * `SecurityError` - If the data key can't be generated or wrapped.
+    pub fn create(name: String, owner: &Session) -> Result<Self, SecurityError> {
+        let mut shared = Self {
+            database_uuid: Uuid::new_v4(),
+            name,
+            state: ClearTextDBState::new(owner.state.user.clone()),
+            member_keys: Vec::new(),
+            data_key: Argon2EncryptionKey::random()?,
+        };
+        shared.add_member(owner, Role::Owner)?;
+        Ok(shared)
+    }
+
+    /// Opens a shared database as the logged-in user of `session`, with the secret derived from
+    /// their member secret, and with the role their membership gives them.
+    ///
+    /// # Errors
+    /// * `std::io::Error` / `serde_json::Error` - If the file can't be read.
+    /// * `"Not a member of this shared database"` - If the user has no key copy or no membership.
+    /// * `SecurityError` or an indicator mismatch - If the data key or data can't be decrypted.
+    pub fn open(databases_dir: &Path, database_uuid: Uuid, session: &Session) -> Result<Self, Box<dyn std::error::Error>> {
+        let db = read_shared(databases_dir, database_uuid)?;
+        let user = &session.state.user;
+        let member = db.member_keys.iter().find(|member| member.user_uuid == user.user_uuid).ok_or("Not a member of this shared database")?;
+        let data_key = member.unwrap_key(&member_secret(session)?)?;
+        let mut state = db.data.to_clear_text(&data_key)?;
+        let role = state.members.iter().find(|membership| membership.user_uuid == user.user_uuid).ok_or("Not a member of this shared database")?.role;
+        state.user = user.clone();
+        state.set_role(role);
+        Ok(Self { database_uuid, name: db.name, state, member_keys: db.member_keys, data_key })
+    }
+
+    /// The members' UUIDs, usernames, and roles.
+    #[must_use]
+    pub fn members(&self) -> Vec<(Uuid, String, Role)> {
+        self.state.members.iter().map(|membership| (membership.user_uuid, membership.username.clone(), membership.role)).collect()
+    }
+
+    /// Whether `user_uuid` is the only owner.
+    #[must_use]
+    pub fn is_last_owner(&self, user_uuid: Uuid) -> bool {
+        let mut owners = self.state.members.iter().filter(|membership| membership.role == Role::Owner);
+        owners.clone().count() == 1 && owners.any(|membership| membership.user_uuid == user_uuid)
+    }
+
+    /// Gives the user of `member` a copy of the data key and a membership with `role`, replacing
+    /// any they had. Doesn't check who may add members; see
+    /// [`crate::service::shared::add_member`].
+    ///
+    /// # Errors
+    /// * `SecurityError` - If the data key can't be wrapped.
+    pub(crate) fn add_member(&mut self, member: &Session, role: Role) -> Result<(), SecurityError> {
+        let user = &member.state.user;
+        let public_key = PublicKey::from(&member_secret(member)?).to_bytes();
+        let mut key = MemberKey { user_uuid: user.user_uuid, public_key, ..MemberKey::default() };
+        key.wrap(&self.data_key)?;
+        self.member_keys.retain(|existing| existing.user_uuid != user.user_uuid);
+        self.member_keys.push(key);
+        self.state.members.retain(|existing| existing.user_uuid != user.user_uuid);
+        self.state.members.push(Membership { user_uuid: user.user_uuid, username: user.username.clone(), role });
+        if user.user_uuid == self.state.user.user_uuid {
+            self.state.set_role(role);
+        }
+        self.state.mark_dirty();
+        Ok(())
+    }
+
+    /// Changes a member's role. Doesn't check who may; see [`crate::service::shared::set_role`].
+    ///
+    /// # Returns
+    /// * `bool` - Whether the user is a member and their role was changed.
+    pub(crate) fn set_role(&mut self, user_uuid: Uuid, role: Role) -> bool {
+        let Some(membership) = self.state.members.iter_mut().find(|membership| membership.user_uuid == user_uuid) else {
+            return false;
+        };
+        membership.role = role;
+        if user_uuid == self.state.user.user_uuid {
+            self.state.set_role(role);
+        }
+        self.state.mark_dirty();
+        true
+    }
+
+    /// Changes the username in a member's membership, e.g. after they were renamed.
+    ///
+    /// # Returns
+    /// * `bool` - Whether the user is a member and their username was changed.
+    pub(crate) fn set_username(&mut self, user_uuid: Uuid, username: &str) -> bool {
+        match self.state.members.iter_mut().find(|membership| membership.user_uuid == user_uuid && membership.username != username) {
+            Some(membership) => {
+                membership.username = username.to_string();
+                self.state.mark_dirty();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Stops a user from opening the shared database, and replaces the data key with a new one
+    /// wrapped for the remaining members, so a copy of the old key doesn't open the file once it
+    /// is saved. Doesn't check who may; see [`crate::service::shared::remove_member`].
+    ///
+    /// # Returns
+    /// * `Result<bool, SecurityError>` - Whether the user was a member and was removed.
+    /// # Errors
+    /// * `SecurityError` - If the new data key can't be generated or wrapped.
+    pub(crate) fn remove_member(&mut self, user_uuid: Uuid) -> Result<bool, SecurityError> {
+        if !self.state.members.iter().any(|membership| membership.user_uuid == user_uuid) {
+            return Ok(false);
+        }
+        let data_key = Argon2EncryptionKey::random()?;
+        let mut member_keys = self.member_keys.clone();
+        member_keys.retain(|member| member.user_uuid != user_uuid);
+        for member in &mut member_keys {
+            member.wrap(&data_key)?;
+        }
+        self.member_keys = member_keys;
+        self.data_key = data_key;
+        self.state.members.retain(|membership| membership.user_uuid != user_uuid);
+        self.state.mark_dirty();
+        Ok(true)
+    }
+
+    /// Deletes the shared database file, for every member. Doesn't check who may; see
+    /// [`crate::service::shared::delete_shared`].
+    ///
+    /// # Errors
+    /// * `std::io::Error` - If the file exists but can't be removed.
+    pub(crate) fn delete(self, databases_dir: &Path) -> std::io::Result<()> {
+        match std::fs::remove_file(shared_path(databases_dir, self.database_uuid)) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+
+    /// Encrypts the data and memberships, without the user who opened it, and writes the shared
+    /// database file with the key copies of the members, then marks it saved. Doesn't check who
+    /// may; see [`crate::service::shared::save_shared`].
+    ///
+    /// # Errors
+    /// Same as [`ClearTextDBState::to_cypher_text`], and `std::io::Error` if the file can't be
+    /// written.
+    pub(crate) fn save(&mut self, databases_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let data = ClearTextDBState { user: User::default(), ..self.state.clone() }.to_cypher_text(&self.data_key)?;
+        let member_keys = self.member_keys.iter().filter(|member| self.state.members.iter().any(|membership| membership.user_uuid == member.user_uuid)).cloned().collect();
+        let db = SharedDatabase { database_uuid: self.database_uuid, name: self.name.clone(), member_keys, data };
+        write_replacing(&shared_path(databases_dir, self.database_uuid), serde_json::to_string(&db)?.as_bytes())?;
+        self.state.mark_saved();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::service::epics::create_epic;
+
+    /// Creates and saves a user's own database.
+    fn session_for(databases_dir: &Path, name: &str) -> Session {
+        let mut session = Session::new(ClearTextDBState::new(User::new(name.to_string(), "a long enough password").unwrap()), "a long enough password").unwrap();
+        session.save(databases_dir).unwrap();
+        session
+    }
+
+    #[test]
+    fn test_members_open_the_shared_database_with_their_own_keys() {
+        let databases_dir = std::env::temp_dir().join(format!("ironyyy-shared-{}", Uuid::new_v4()));
+        let (alice, bob, carol) = (session_for(&databases_dir, "alice"), session_for(&databases_dir, "bob"), session_for(&databases_dir, "carol"));
+
+        let mut shared = SharedSession::create("Family".to_string(), &alice).unwrap();
+        create_epic(&mut shared.state, "Holiday", String::new(), &SystemClock).unwrap();
+        shared.add_member(&Session::unlock(&databases_dir, bob.state.user.user_uuid, "a long enough password").unwrap(), Role::Editor).unwrap();
+        shared.save(&databases_dir).unwrap();
+        assert_eq!(detect_shared(&databases_dir, bob.state.user.user_uuid).unwrap(), vec![(shared.database_uuid, "Family".to_string())]);
+        assert!(detect_shared(&databases_dir, carol.state.user.user_uuid).unwrap().is_empty());
+        assert_eq!(crate::db::storage::detect_users(&databases_dir).unwrap().len(), 3);
+
+        let opened = SharedSession::open(&databases_dir, shared.database_uuid, &bob).unwrap();
+        assert_eq!(opened.state.user.username, "bob");
+        assert_eq!(opened.state.role(), Role::Editor);
+        assert_eq!(opened.state.epics[0].title, "Holiday");
+        assert!(SharedSession::open(&databases_dir, shared.database_uuid, &carol).is_err());
+
+        // Bob keeps a copy of the file, and so of the data key, from before he is removed.
+        let kept = read_shared(&databases_dir, shared.database_uuid).unwrap();
+        shared.add_member(&carol, Role::Viewer).unwrap();
+        assert!(shared.remove_member(bob.state.user.user_uuid).unwrap());
+        shared.save(&databases_dir).unwrap();
+        assert!(SharedSession::open(&databases_dir, shared.database_uuid, &bob).is_err());
+        let old_member = kept.member_keys.iter().find(|member| member.user_uuid == bob.state.user.user_uuid).unwrap();
+        let old_key = old_member.unwrap_key(&member_secret(&bob).unwrap()).unwrap();
+        assert!(read_shared(&databases_dir, shared.database_uuid).unwrap().data.to_clear_text(&old_key).is_err());
+        assert_eq!(SharedSession::open(&databases_dir, shared.database_uuid, &carol).unwrap().state.epics[0].title, "Holiday");
+        std::fs::remove_dir_all(&databases_dir).unwrap();
+    }
+
+    #[test]
+    fn test_roles_are_kept_out_of_reach_in_the_encrypted_data() {
+        let databases_dir = std::env::temp_dir().join(format!("ironyyy-roster-{}", Uuid::new_v4()));
+        let (alice, carol) = (session_for(&databases_dir, "alice"), session_for(&databases_dir, "carol"));
+        let mut shared = SharedSession::create("Team".to_string(), &alice).unwrap();
+        shared.add_member(&carol, Role::Viewer).unwrap();
+        shared.save(&databases_dir).unwrap();
+
+        let path = shared_path(&databases_dir, shared.database_uuid);
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(!contents.contains("Viewer") && !contents.contains("carol"));
+
+        // A key copy written in for someone without a membership opens nothing.
+        let mut db = read_shared(&databases_dir, shared.database_uuid).unwrap();
+        let mut forged = db.member_keys[1].clone();
+        forged.user_uuid = Uuid::new_v4();
+        db.member_keys.push(forged);
+        std::fs::write(&path, serde_json::to_string(&db).unwrap()).unwrap();
+        assert_eq!(SharedSession::open(&databases_dir, shared.database_uuid, &carol).unwrap().state.role(), Role::Viewer);
+        std::fs::remove_dir_all(&databases_dir).unwrap();
+    }
+}
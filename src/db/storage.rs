@@ -0,0 +1,355 @@
+//! # Storage
+//! Reading and writing the encrypted database files in the `databases` folder, and unlocking
+//! them into a [`Session`].
+
+use super::{ClearTextDBState, CypherTextDBState, KeySlot, SecurityKeySlot};
+use crate::security::fido2::combine_keys;
+#[cfg(feature = "fido2")]
+use crate::security::fido2::{HmacSecretAuthenticator, RELYING_PARTY_ID, register_security_key};
+use crate::security::{Argon2EncryptionKey, SecurityError};
+use crate::service::undo::UndoStack;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Returns the path of a user's database file, `<databases_dir>/<user_uuid>.json`.
+#[must_use]
+pub fn database_path(databases_dir: &Path, user_uuid: Uuid) -> PathBuf {
+    databases_dir.join(format!("{user_uuid}.json"))
+}
+
+/// Lists the users with a database file, from the clear-text part of each file.
+///
+/// Files that can't be parsed as databases are skipped. A missing folder means no users.
+/// # Returns
+/// * `std::io::Result<Vec<(Uuid, String)>>` - Each user's UUID and username, sorted by username.
+/// # Errors
+/// * `std::io::Error` - If the folder exists but can't be read.
+pub fn detect_users(databases_dir: &Path) -> std::io::Result<Vec<(Uuid, String)>> {
+    let entries = match std::fs::read_dir(databases_dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+    let mut users = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().is_some_and(|extension| extension == "json")
+            && let Ok(contents) = std::fs::read_to_string(&path)
+            && let Ok(db) = serde_json::from_str::<CypherTextDBState>(&contents)
+        {
+            users.push((db.user_uuid, db.username));
+        }
+    }
+    users.sort_by(|a, b| a.1.cmp(&b.1));
+    Ok(users)
+}
+
+/// Reads a user's encrypted database file.
+///
+/// # Errors
+/// * `std::io::Error` - If the file can't be read.
+/// * `serde_json::Error` - If it isn't a database file.
+pub fn read_database(databases_dir: &Path, user_uuid: Uuid) -> Result<CypherTextDBState, Box<dyn std::error::Error>> {
+    let contents = std::fs::read_to_string(database_path(databases_dir, user_uuid))?;
+    Ok(serde_json::from_str(&contents)?)
+}
+
+/// Writes a user's encrypted database file, replacing any previous one only once the new one is
+/// fully written. The file is readable by the owner only on Unix.
+///
+/// # Errors
+/// * `std::io::Error` - If the folder or file can't be written.
+/// * `serde_json::Error` - If the database can't be serialized.
+pub fn write_database(databases_dir: &Path, db: &CypherTextDBState) -> Result<(), Box<dyn std::error::Error>> {
+    write_replacing(&database_path(databases_dir, db.user_uuid), serde_json::to_string(db)?.as_bytes())?;
+    Ok(())
+}
+
+/// Writes a file next to `path` and then moves it over `path`, so a reader sees either the old
+/// contents or the new ones. Missing folders are created, and the file is readable by the owner
+/// only on Unix. A temporary file left over from an interrupted write is removed first, so the new
+/// one is always created with those permissions, and the file and its folder are flushed to disk
+/// before and after the move.
+///
+/// # Errors
+/// * `std::io::Error` - If the folder or file can't be written.
+pub(crate) fn write_replacing(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(".tmp");
+    match std::fs::remove_file(&temporary) {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err),
+        _ => {}
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&temporary)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    std::fs::rename(temporary, path)?;
+    sync_parent(path)
+}
+
+/// Flushes the folder holding `path` to disk, so a file moved into it stays there after a crash.
+/// Folders can't be opened as files on Windows, where this does nothing.
+fn sync_parent(path: &Path) -> std::io::Result<()> {
+    #[cfg(unix)]
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        std::fs::File::open(parent)?.sync_all()?;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
+
+/// # Session struct
+/// An unlocked database, with the keys to save it again.
+#[derive(Clone, Debug)]
+pub struct Session {
+    /// The decrypted database.
+    pub state: ClearTextDBState,
+    /// The data key the database and its attachments are encrypted with. A new one is made when
+    /// the password changes.
+    pub key: Argon2EncryptionKey,
+    /// The key derived from the user's password, which wraps the data key in the file.
+    password_key: Argon2EncryptionKey,
+    /// The secret the user's security keys unwrap, combined with the password key to wrap the data
+    /// key, once a security key is registered.
+    security_secret: Option<Argon2EncryptionKey>,
+    /// The security key secret, wrapped for each of the user's security keys.
+    security_key_slots: Vec<SecurityKeySlot>,
+    /// The changes made since the database was unlocked, to undo and redo them.
+    pub undo: UndoStack,
+}
+
+impl Session {
+    /// Starts a session for a new database with a random data key, wrapped with the key derived
+    /// from `password` once [saved](Session::save).
+    ///
+    /// # Errors
+    /// * `SecurityError` - If a key can't be derived or generated.
+    pub fn new(mut state: ClearTextDBState, password: &str) -> Result<Self, SecurityError> {
+        let password_key = Argon2EncryptionKey::new(password, state.user.user_uuid)?;
+        add_member_secret(&mut state)?;
+        Ok(Self { state, key: Argon2EncryptionKey::random()?, password_key, security_secret: None, security_key_slots: Vec::new(), undo: UndoStack::default() })
+    }
+
+    /// Derives the key from a password and decrypts a user's database file with it.
+    ///
+    /// # Errors
+    /// * `std::io::Error` / `serde_json::Error` - If the file can't be read.
+    /// * `SecurityError` or an indicator mismatch - If the password is wrong.
+    pub fn unlock(databases_dir: &Path, user_uuid: Uuid, password: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::decrypt(&read_database(databases_dir, user_uuid)?, password)
+    }
+
+    /// Derives the key from a password and decrypts an already read database with it.
+    ///
+    /// # Errors
+    /// * `SecurityError::SecurityKeyRequired` - If the user registered a security key; see
+    ///   `Session::decrypt_with_security_key`.
+    /// * `SecurityError` or an indicator mismatch - If the password is wrong.
+    pub fn decrypt(db: &CypherTextDBState, password: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        if !db.security_key_slots.is_empty() {
+            return Err(SecurityError::SecurityKeyRequired.into());
+        }
+        Self::open(db, password, None)
+    }
+
+    /// Derives the key from a password and decrypts an already read database with it and the
+    /// secret one of the user's security keys unwraps. Databases without security keys only need
+    /// the password.
+    ///
+    /// # Errors
+    /// * `SecurityError::SecurityKeyRequired` - If `authenticator` holds none of the user's keys.
+    /// * `SecurityError` or an indicator mismatch - If the password is wrong.
+    #[cfg(feature = "fido2")]
+    pub fn decrypt_with_security_key(db: &CypherTextDBState, password: &str, authenticator: &dyn HmacSecretAuthenticator) -> Result<Self, Box<dyn std::error::Error>> {
+        if db.security_key_slots.is_empty() {
+            return Self::open(db, password, None);
+        }
+        let secret = db
+            .security_key_slots
+            .iter()
+            .find_map(|slot| {
+                let output = authenticator.hmac_secret(RELYING_PARTY_ID, &slot.credential_id, &slot.salt).ok()?;
+                slot.key_slot.unwrap_key(&Argon2EncryptionKey(output)).ok()
+            })
+            .ok_or(SecurityError::SecurityKeyRequired)?;
+        Self::open(db, password, Some(secret))
+    }
+
+    /// Registers a FIDO2 security key for the user, from the next [save](Session::save) on
+    /// needed to unlock the database: the data key is wrapped with the password key combined with
+    /// a secret each of the user's security keys can unwrap. Backup codes can't unlock such a
+    /// database, so registering a second key is the way to keep a spare.
+    ///
+    /// # Errors
+    /// * `SecurityError::Fido2` - If the authenticator failed.
+    /// * `SecurityError::TryRngCore` - If the credential salt or the secret could not be generated.
+    #[cfg(feature = "fido2")]
+    pub fn add_security_key(&mut self, authenticator: &dyn HmacSecretAuthenticator, label: String) -> Result<(), SecurityError> {
+        let user = &self.state.user;
+        let (credential, output) = register_security_key(authenticator, user.user_uuid.as_bytes(), &user.username, label)?;
+        let secret = match &self.security_secret {
+            Some(secret) => secret.clone(),
+            None => Argon2EncryptionKey::random()?,
+        };
+        let key_slot = KeySlot::wrap(&secret, &Argon2EncryptionKey(output))?;
+        self.security_secret = Some(secret);
+        self.security_key_slots.push(SecurityKeySlot { credential_id: credential.credential_id.clone(), salt: credential.salt, key_slot });
+        self.state.user.security_keys.push(credential);
+        self.state.mark_dirty();
+        Ok(())
+    }
+
+    /// Encrypts the database and writes it to its file, then marks it saved.
+    ///
+    /// # Errors
+    /// Same as [`ClearTextDBState::to_cypher_text`] and [`write_database`].
+    pub fn save(&mut self, databases_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let mut db = self.state.to_cypher_text(&self.key)?;
+        let wrapping_key = match &self.security_secret {
+            Some(secret) => combine_keys(&self.password_key, secret),
+            None => self.password_key.clone(),
+        };
+        db.password_slot = Some(KeySlot::wrap(&self.key, &wrapping_key)?);
+        db.security_key_slots.clone_from(&self.security_key_slots);
+        write_database(databases_dir, &db)?;
+        self.state.mark_saved();
+        Ok(())
+    }
+
+    /// Switches to the key derived from a new password; the data key is wrapped with it from the
+    /// next [save](Session::save) on. A password change also replaces the data key itself (see
+    /// [`crate::users::change_password`]).
+    pub(crate) fn set_password_key(&mut self, password_key: Argon2EncryptionKey) {
+        self.password_key = password_key;
+    }
+
+    /// Unwraps the data key with the password key, combined with the security key secret if
+    /// there is one, and decrypts the database.
+    ///
+    /// # Errors
+    /// * `SecurityError` or an indicator mismatch - If the password or secret is wrong.
+    fn open(db: &CypherTextDBState, password: &str, security_secret: Option<Argon2EncryptionKey>) -> Result<Self, Box<dyn std::error::Error>> {
+        let password_key = Argon2EncryptionKey::new(password, db.user_uuid)?;
+        // Older files are encrypted with the password key itself, which stays their data key.
+        let key = match (&db.password_slot, &security_secret) {
+            (Some(slot), Some(secret)) => slot.unwrap_key(&combine_keys(&password_key, secret))?,
+            (Some(slot), None) => slot.unwrap_key(&password_key)?,
+            (None, _) => password_key.clone(),
+        };
+        let mut state = db.to_clear_text(&key)?;
+        add_member_secret(&mut state)?;
+        Ok(Self { state, key, password_key, security_secret, security_key_slots: db.security_key_slots.clone(), undo: UndoStack::default() })
+    }
+}
+
+/// Gives the user a [member secret](crate::users::User::member_secret) if their database was saved
+/// without one, marking it changed so the secret is kept.
+///
+/// # Errors
+/// * `SecurityError::TryRngCore` - If the secret can't be generated.
+fn add_member_secret(state: &mut ClearTextDBState) -> Result<(), SecurityError> {
+    if state.user.member_secret.is_none() {
+        state.user.member_secret = Some(Argon2EncryptionKey::random()?.0);
+        state.mark_dirty();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::users::User;
+
+    #[test]
+    fn test_save_detect_and_unlock() {
+        let databases_dir = std::env::temp_dir().join(format!("ironyyy-storage-{}", Uuid::new_v4()));
+        assert!(detect_users(&databases_dir).unwrap().is_empty());
+        let user = User::new("alice".to_string(), "password").unwrap();
+        let user_uuid = user.user_uuid;
+        let mut session = Session::new(ClearTextDBState::new(user), "password").unwrap();
+        session.state.mark_dirty();
+        session.save(&databases_dir).unwrap();
+        assert!(!session.state.is_dirty());
+        std::fs::write(databases_dir.join("notes.json"), "not a database").unwrap();
+
+        assert_eq!(detect_users(&databases_dir).unwrap(), vec![(user_uuid, "alice".to_string())]);
+        assert_eq!(Session::unlock(&databases_dir, user_uuid, "password").unwrap().state, session.state);
+        assert!(Session::unlock(&databases_dir, user_uuid, "wrong").is_err());
+        assert!(Session::unlock(&databases_dir, Uuid::new_v4(), "password").is_err());
+        std::fs::remove_dir_all(&databases_dir).unwrap();
+    }
+
+    #[test]
+    fn test_files_without_a_data_key_still_open() {
+        let databases_dir = std::env::temp_dir().join(format!("ironyyy-legacy-{}", Uuid::new_v4()));
+        let state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let user_uuid = state.user.user_uuid;
+        let password_key = Argon2EncryptionKey::new("password", user_uuid).unwrap();
+        write_database(&databases_dir, &state.to_cypher_text(&password_key).unwrap()).unwrap();
+
+        let mut session = Session::unlock(&databases_dir, user_uuid, "password").unwrap();
+        assert_eq!(session.key, password_key);
+        session.save(&databases_dir).unwrap();
+        assert!(read_database(&databases_dir, user_uuid).unwrap().password_slot.is_some());
+        assert_eq!(Session::unlock(&databases_dir, user_uuid, "password").unwrap().key, password_key);
+        std::fs::remove_dir_all(&databases_dir).unwrap();
+    }
+
+    #[cfg(feature = "fido2")]
+    #[test]
+    fn test_security_keys_are_needed_to_unlock() {
+        use crate::security::fido2::SoftwareAuthenticator;
+        let databases_dir = std::env::temp_dir().join(format!("ironyyy-fido2-{}", Uuid::new_v4()));
+        let (blue, spare, other) = (SoftwareAuthenticator([7; 32]), SoftwareAuthenticator([8; 32]), SoftwareAuthenticator([9; 32]));
+        let mut session = Session::new(ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap()), "password").unwrap();
+        session.add_security_key(&blue, "blue".to_string()).unwrap();
+        session.add_security_key(&spare, "spare".to_string()).unwrap();
+        session.save(&databases_dir).unwrap();
+        let user_uuid = session.state.user.user_uuid;
+
+        let db = read_database(&databases_dir, user_uuid).unwrap();
+        let required = SecurityError::SecurityKeyRequired.to_string();
+        assert_eq!(Session::decrypt(&db, "password").unwrap_err().to_string(), required);
+        assert_eq!(Session::decrypt_with_security_key(&db, "password", &other).unwrap_err().to_string(), required);
+        assert!(Session::decrypt_with_security_key(&db, "wrong", &blue).is_err());
+        // Neither half of the combined key opens the file alone.
+        let password_key = Argon2EncryptionKey::new("password", user_uuid).unwrap();
+        assert!(db.password_slot.as_ref().unwrap().unwrap_key(&password_key).is_err());
+
+        let mut unlocked = Session::decrypt_with_security_key(&db, "password", &spare).unwrap();
+        assert_eq!(unlocked.key, session.key);
+        unlocked.save(&databases_dir).unwrap();
+        assert!(Session::decrypt_with_security_key(&read_database(&databases_dir, user_uuid).unwrap(), "password", &blue).is_ok());
+        std::fs::remove_dir_all(&databases_dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_replacing_ignores_a_stale_temporary_file() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = std::env::temp_dir().join(format!("ironyyy-replace-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("db.json");
+        let temporary = dir.join("db.json.tmp");
+        std::fs::write(&temporary, "left over from a crash").unwrap();
+        std::fs::set_permissions(&temporary, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        write_replacing(&path, b"new").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "new");
+        assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        assert!(!temporary.exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
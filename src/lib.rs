@@ -5,21 +5,47 @@
 
 //! Ironyyy is a secure, offline-first project management application focused on epics and stories.
 
+pub mod app;
+pub mod audit;
+pub mod clock;
 pub mod db;
-mod models;
-mod nav;
-mod pages;
+pub mod models;
+pub mod nav;
+pub mod pages;
 pub mod security;
-mod users;
+pub mod service;
+pub mod terminal;
+#[cfg(feature = "tui")]
+pub mod tui;
+pub mod users;
 
-/// Runs the Ironyyy application.
-/// 
+/// Runs the Ironyyy application on the databases in [`app::DATABASES_DIR`], reading the keys
+/// typed from standard input, with its terminal in [raw mode](terminal::RawMode) while it runs. `--open <route>` opens a page at start-up; an error is printed and
+/// the process exits with status 1 if the route is invalid or a database can't be read or saved.
+///
 /// # Examples
-/// ```
+/// ```no_run
 /// use ironyyy::run_app;
 /// run_app();
 /// ```
 pub fn run_app() {
-    // Application entry point
-    todo!()
+    let args: Vec<String> = std::env::args().collect();
+    let raw_mode = terminal::RawMode::enable();
+    let mut keys: Box<dyn Iterator<Item = pages::line_editor::Key>> = if raw_mode.is_some() {
+        Box::new(terminal::keys(std::io::stdin()))
+    } else {
+        Box::new(std::io::stdin().lines().map_while(Result::ok).flat_map(|line| pages::line_editor::decode_keys(format!("{line}\n").as_bytes())))
+    };
+    let mut draw = |lines: Vec<String>| {
+        for line in lines {
+            println!("{line}");
+        }
+    };
+    let result = app::run(std::path::Path::new(app::DATABASES_DIR), &args, &mut keys, &mut draw);
+    // Exiting skips destructors, so the terminal is put back first.
+    drop(raw_mode);
+    if let Err(err) = result {
+        eprintln!("{err}");
+        std::process::exit(1);
+    }
 }
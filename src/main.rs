@@ -1,5 +1,5 @@
-use ironyyy::{ };
+use ironyyy::run_app;
 
 fn main() {
-    todo!()
+    run_app();
 }
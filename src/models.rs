@@ -1,16 +1,41 @@
 //! # Models Module
 //! This module contains data models used throughout the application.
 
+pub mod attachments;
+pub mod checklists;
+pub mod custom_fields;
 pub mod epics;
+pub mod estimates;
+pub mod events;
+pub mod goals;
+pub mod history;
+pub mod labels;
+pub mod links;
+pub mod projects;
+pub mod roles;
+pub mod settings;
+pub mod sprints;
+pub mod stats;
+pub mod statuses;
 pub mod stories;
+pub mod tasks;
+pub mod validation;
+pub mod work_logs;
+pub mod workflow;
 
+use self::statuses::{StatusCategory, StatusDefinition, find_definition};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 /// # Status struct 
 /// 
 /// (applies to Epics and Stories)
 /// 
-/// An epic or story can have one of the following statuses: `Open`, `InProgress`, or `Closed`.
+/// An epic or story can have one of the built-in statuses (`Open`, `InProgress`, `Blocked`,
+/// `InReview`, `Cancelled`, or `Closed`), or a custom status defined in the database (see
+/// [`statuses`]). The discriminants are stable: new built-ins get new values, existing ones never
+/// change.
 #[repr(u8)]
 #[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
 pub enum Status {
@@ -19,7 +44,119 @@ pub enum Status {
     Open = 0,
     /// The item is currently in progress.
     InProgress = 1,
+    /// Work has started but is held up by something.
+    Blocked = 2,
+    /// The work is done and waiting for review.
+    InReview = 3,
+    /// A user-defined status, identified by UUID.
+    Custom(Uuid) = 128,
+    /// The item was dropped without being completed. It counts as done, since no work remains.
+    Cancelled = 254,
     /// The item has been completed.
     Closed = 255,
 }
 
+impl Status {
+    /// The built-in statuses, in their default workflow order.
+    pub const BUILTINS: [Status; 6] = [
+        Status::Open,
+        Status::InProgress,
+        Status::Blocked,
+        Status::InReview,
+        Status::Cancelled,
+        Status::Closed,
+    ];
+
+    /// The default display name of a built-in status, or `None` for custom statuses.
+    #[must_use]
+    pub fn builtin_name(self) -> Option<&'static str> {
+        match self {
+            Status::Open => Some("Open"),
+            Status::InProgress => Some("In Progress"),
+            Status::Blocked => Some("Blocked"),
+            Status::InReview => Some("In Review"),
+            Status::Cancelled => Some("Cancelled"),
+            Status::Closed => Some("Closed"),
+            Status::Custom(_) => None,
+        }
+    }
+
+    /// The category of a built-in status, or `None` for custom statuses.
+    #[must_use]
+    pub fn builtin_category(self) -> Option<StatusCategory> {
+        match self {
+            Status::Open => Some(StatusCategory::Todo),
+            Status::InProgress | Status::Blocked | Status::InReview => Some(StatusCategory::InProgress),
+            Status::Cancelled | Status::Closed => Some(StatusCategory::Done),
+            Status::Custom(_) => None,
+        }
+    }
+
+    /// The category of this status according to a database's status definitions.
+    ///
+    /// Built-in statuses always keep their own category; unknown custom statuses count as to-do.
+    #[must_use]
+    pub fn category(self, statuses: &[StatusDefinition]) -> StatusCategory {
+        self.builtin_category()
+            .or_else(|| find_definition(statuses, self).map(|definition| definition.category))
+            .unwrap_or_default()
+    }
+
+    /// Whether this status means the work is finished (completed or cancelled).
+    #[must_use]
+    pub fn is_done(self, statuses: &[StatusDefinition]) -> bool {
+        self.category(statuses) == StatusCategory::Done
+    }
+
+    /// Whether this status means work remains, i.e. it is not done.
+    #[must_use]
+    pub fn is_open(self, statuses: &[StatusDefinition]) -> bool {
+        !self.is_done(statuses)
+    }
+
+    /// The display name of this status according to a database's status definitions.
+    #[must_use]
+    pub fn name(self, statuses: &[StatusDefinition]) -> String {
+        find_definition(statuses, self)
+            .map(|definition| definition.name.clone())
+            .or_else(|| self.builtin_name().map(str::to_string))
+            .unwrap_or_else(|| "Unknown".to_string())
+    }
+}
+
+/// # Scheduled trait
+///
+/// (applies to Epics and Stories)
+///
+/// Items with an optional start date and due date.
+pub trait Scheduled {
+    /// The day work is planned to start, if set.
+    fn start_date(&self) -> Option<NaiveDate>;
+
+    /// The day the item is due, if set.
+    fn due_date(&self) -> Option<NaiveDate>;
+
+    /// The item's current status.
+    fn status(&self) -> Status;
+
+    /// Whether the item is past its due date without being done.
+    ///
+    /// An item due today is not overdue yet.
+    fn is_overdue(&self, today: NaiveDate, statuses: &[StatusDefinition]) -> bool {
+        !self.status().is_done(statuses) && self.due_date().is_some_and(|due| due < today)
+    }
+}
+
+/// # Timestamped trait
+///
+/// (applies to Epics, Stories, and Users)
+///
+/// Items that remember when they were created and last modified. The timestamps are maintained by
+/// the service layer; items from databases saved before they existed report the Unix epoch.
+pub trait Timestamped {
+    /// When the item was created.
+    fn created_at(&self) -> DateTime<Utc>;
+
+    /// When the item was last modified.
+    fn updated_at(&self) -> DateTime<Utc>;
+}
@@ -0,0 +1,23 @@
+//! # Attachments
+//! The `Attachment` model: metadata for a file attached to an epic or story. The file contents
+//! are stored encrypted next to the user's database file.
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// # Attachment struct
+/// Metadata for an attached file.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[allow(clippy::struct_field_names)]
+pub struct Attachment {
+    /// The unique identifier of the attachment, also used to name the encrypted file.
+    pub attachment_uuid: Uuid,
+    /// The original file name.
+    pub name: String,
+    /// The size of the original file in bytes.
+    pub size: u64,
+    /// SHA-256 digest of the original file contents.
+    pub sha256: Vec<u8>,
+    /// AES-GCM nonce used to encrypt the stored file.
+    pub nonce: [u8; 12],
+}
@@ -0,0 +1,58 @@
+//! # Checklists
+//! Acceptance-criteria checklists attached to stories.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// # Checklist Item struct
+/// One acceptance criterion and whether it has been met.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct ChecklistItem {
+    /// The criterion.
+    pub text: String,
+    /// Whether the criterion has been met.
+    pub checked: bool,
+}
+
+impl ChecklistItem {
+    /// Creates an unchecked item.
+    #[must_use]
+    pub fn new(text: String) -> Self {
+        Self { text, checked: false }
+    }
+}
+
+impl fmt::Display for ChecklistItem {
+    /// Renders the item as a checklist line, e.g. `[x] Works offline`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let check = if self.checked { 'x' } else { ' ' };
+        write!(f, "[{check}] {}", self.text)
+    }
+}
+
+/// Returns the percentage (rounded down) of checked items, or `None` for an empty checklist.
+///
+/// # Examples
+/// ```rust
+/// use ironyyy::models::checklists::{completion_percent, ChecklistItem};
+/// let mut items = vec![ChecklistItem::new("a".to_string()), ChecklistItem::new("b".to_string()), ChecklistItem::new("c".to_string())];
+/// items[0].checked = true;
+/// assert_eq!(completion_percent(&items), Some(33));
+/// assert_eq!(completion_percent(&[]), None);
+/// ```
+#[must_use]
+pub fn completion_percent(items: &[ChecklistItem]) -> Option<u8> {
+    if items.is_empty() {
+        return None;
+    }
+    let checked = items.iter().filter(|item| item.checked).count();
+    u8::try_from(checked * 100 / items.len()).ok()
+}
+
+/// Renders a short checklist summary for list views, e.g. `1/3 (33%)`, or `None` for an empty checklist.
+#[must_use]
+pub fn completion_summary(items: &[ChecklistItem]) -> Option<String> {
+    let percent = completion_percent(items)?;
+    let checked = items.iter().filter(|item| item.checked).count();
+    Some(format!("{checked}/{} ({percent}%)", items.len()))
+}
@@ -0,0 +1,96 @@
+//! # Custom Fields
+//! User-defined fields that can be attached to epics and stories.
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use uuid::Uuid;
+
+/// # `FieldId` type alias
+/// The UUID of a custom field definition.
+pub type FieldId = Uuid;
+
+/// # Field Kind enum
+/// The type of value a custom field holds.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum FieldKind {
+    /// Free text.
+    #[default]
+    Text,
+    /// A whole number.
+    Number,
+    /// A calendar date.
+    Date,
+    /// One of a fixed list of options.
+    Enum(Vec<String>),
+}
+
+/// # Field Value enum
+/// The value of a custom field on an epic or story.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum FieldValue {
+    /// A text value.
+    Text(String),
+    /// A number value.
+    Number(i64),
+    /// A date value.
+    Date(NaiveDate),
+    /// The chosen option of an enum field.
+    Enum(String),
+}
+
+impl FieldValue {
+    /// Whether this value can be stored in a field of the given kind. Enum values must be one of the
+    /// field's options.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use ironyyy::models::custom_fields::{FieldKind, FieldValue};
+    /// let kind = FieldKind::Enum(vec!["Low".to_string(), "High".to_string()]);
+    /// assert!(FieldValue::Enum("High".to_string()).fits(&kind));
+    /// assert!(!FieldValue::Enum("Medium".to_string()).fits(&kind));
+    /// assert!(!FieldValue::Number(3).fits(&FieldKind::Text));
+    /// ```
+    #[must_use]
+    pub fn fits(&self, kind: &FieldKind) -> bool {
+        match (self, kind) {
+            (FieldValue::Text(_), FieldKind::Text) | (FieldValue::Number(_), FieldKind::Number) | (FieldValue::Date(_), FieldKind::Date) => true,
+            (FieldValue::Enum(option), FieldKind::Enum(options)) => options.contains(option),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for FieldValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldValue::Text(text) | FieldValue::Enum(text) => f.write_str(text),
+            FieldValue::Number(number) => write!(f, "{number}"),
+            FieldValue::Date(date) => write!(f, "{date}"),
+        }
+    }
+}
+
+/// # Field Definition struct
+/// A custom field available to every epic and story in a database.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct FieldDefinition {
+    /// The unique identifier of the field.
+    pub field_id: FieldId,
+    /// The name of the field, unique within a database (ignoring case).
+    pub name: String,
+    /// The type of value the field holds.
+    pub kind: FieldKind,
+}
+
+impl FieldDefinition {
+    /// Creates a new field definition with a fresh ID.
+    #[must_use]
+    pub fn new(name: String, kind: FieldKind) -> Self {
+        Self {
+            field_id: Uuid::new_v4(),
+            name,
+            kind,
+        }
+    }
+}
@@ -1,32 +1,341 @@
-use super::Status;
+//! # Epics
+//! The `Epic` model: a large body of work broken down into stories.
+
+use super::{Scheduled, Status, Timestamped, custom_fields::{FieldId, FieldValue}, statuses::StatusDefinition, stories::Story, validation::{ValidationError, validate_color, validate_date_range, validate_description, validate_title}};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
 use uuid::Uuid;
 
 /// # Epic struct
 /// Represents an epic in the project management system.
 #[derive(Clone, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[allow(clippy::struct_field_names)]
 pub struct Epic {
     /// The unique identifier of the epic.
     pub epic_uuid: Uuid,
+    /// The per-database number of this epic, shown as e.g. `EPIC-12` (set by the service layer).
+    #[serde(default)]
+    pub number: u32,
     /// The title of the epic.
     pub title: String,
     /// The description of the epic.
     pub description: String,
     /// The status of the epic.
-    pub status: Status,    
+    pub status: Status,
     /// The list of story UUIDs associated with this epic.
     pub story_uuids: Vec<Uuid>,
+    /// The UUID of the epic this one is nested under, if any.
+    #[serde(default)]
+    pub parent_epic: Option<Uuid>,
+    /// The UUIDs of the labels attached to this epic.
+    #[serde(default)]
+    pub labels: Vec<Uuid>,
+    /// The day work on this epic is planned to start.
+    #[serde(default)]
+    pub start_date: Option<NaiveDate>,
+    /// The day this epic is due.
+    #[serde(default)]
+    pub due_date: Option<NaiveDate>,
+    /// The UUIDs of the files attached to this epic.
+    #[serde(default)]
+    pub attachments: Vec<Uuid>,
+    /// Whether this epic is hidden from default views.
+    #[serde(default)]
+    pub archived: bool,
+    /// The position of this epic among the other epics, lowest first.
+    #[serde(default)]
+    pub rank: u32,
+    /// The values of this epic's custom fields.
+    #[serde(default)]
+    pub custom_fields: BTreeMap<FieldId, FieldValue>,
+    /// The UUIDs of the users who want to hear about changes to this epic. Not part of its
+    /// history, since watching changes nothing about the epic itself.
+    #[serde(default)]
+    pub watchers: Vec<Uuid>,
+    /// When this epic was moved to the trash, if it was. Deleted items are hidden everywhere
+    /// until they are restored or purged.
+    #[serde(default)]
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// The display color of this epic, as a hex string such as `#d73a4a`.
+    #[serde(default)]
+    pub color: Option<String>,
+    /// A short icon (usually an emoji) shown before the title.
+    #[serde(default)]
+    pub icon: Option<String>,
+    /// When this epic was created (set by the service layer).
+    #[serde(default)]
+    pub created_at: DateTime<Utc>,
+    /// When this epic was last modified (set by the service layer).
+    #[serde(default)]
+    pub updated_at: DateTime<Utc>,
 }
 
 impl Epic {
     /// Creates a new epic with the given title and description.
+    #[must_use]
     pub fn new(title: String, description: String) -> Self {
         Self {
             epic_uuid: Uuid::new_v4(),
+            number: 0,
             title,
             description,
             status: Status::Open,
             story_uuids: Vec::new(),
+            parent_epic: None,
+            labels: Vec::new(),
+            start_date: None,
+            due_date: None,
+            attachments: Vec::new(),
+            archived: false,
+            rank: 0,
+            custom_fields: BTreeMap::new(),
+            watchers: Vec::new(),
+            deleted_at: None,
+            color: None,
+            icon: None,
+            created_at: DateTime::default(),
+            updated_at: DateTime::default(),
+        }
+    }
+
+    /// Whether this epic is in the trash.
+    #[must_use]
+    pub fn is_deleted(&self) -> bool {
+        self.deleted_at.is_some()
+    }
+
+    /// The short, human-readable ID of this epic, e.g. `EPIC-12`.
+    #[must_use]
+    pub fn key(&self) -> String {
+        format!("EPIC-{}", self.number)
+    }
+
+    /// Checks the title, description, dates, and color against the same rules as
+    /// [`EpicBuilder::build`], for epics that didn't come through the builder or the service
+    /// layer, such as imported ones.
+    ///
+    /// # Errors
+    /// Same as [`EpicBuilder::build`].
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        validate_title(&self.title)?;
+        validate_description(&self.description)?;
+        validate_date_range(self.start_date, self.due_date)?;
+        if let Some(color) = &self.color {
+            validate_color(color)?;
+        }
+        Ok(())
+    }
+
+    /// Copies this epic, without its stories, as a template for repeated work.
+    ///
+    /// The copy gets a new UUID, a "(copy)" suffix on its title, and the `Open` status. It keeps
+    /// its place under the same parent epic, but leaves behind its stories, attachments, number,
+    /// rank, archived flag, watchers, and timestamps.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use ironyyy::models::{Status, epics::Epic};
+    /// let epic = Epic::builder().title("Quarterly release").status(Status::Closed).build().unwrap();
+    /// let copy = epic.duplicate();
+    /// assert_eq!((copy.title.as_str(), copy.status), ("Quarterly release (copy)", Status::Open));
+    /// assert_ne!(copy.epic_uuid, epic.epic_uuid);
+    /// ```
+    #[must_use]
+    pub fn duplicate(&self) -> Self {
+        Self {
+            title: format!("{} (copy)", self.title),
+            description: self.description.clone(),
+            parent_epic: self.parent_epic,
+            labels: self.labels.clone(),
+            start_date: self.start_date,
+            due_date: self.due_date,
+            custom_fields: self.custom_fields.clone(),
+            color: self.color.clone(),
+            icon: self.icon.clone(),
+            ..Self::new(String::new(), String::new())
+        }
+    }
+
+    /// Starts building an epic with more than a title and description.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use ironyyy::models::{Status, epics::Epic};
+    /// let epic = Epic::builder().title("Launch").status(Status::InProgress).build().unwrap();
+    /// assert_eq!(epic.status, Status::InProgress);
+    /// assert!(Epic::builder().description("No title").build().is_err());
+    /// ```
+    #[must_use]
+    pub fn builder() -> EpicBuilder {
+        EpicBuilder(Self::new(String::new(), String::new()))
+    }
+
+    /// The title with the icon in front, if there is one, e.g. `🚀 Launch`.
+    #[must_use]
+    pub fn badge(&self) -> String {
+        match &self.icon {
+            Some(icon) => format!("{icon} {}", self.title),
+            None => self.title.clone(),
         }
     }
-}
\ No newline at end of file
+
+    /// Counts how many of this epic's stories are done.
+    ///
+    /// # Arguments
+    /// * `stories` - The stories to look through; only those belonging to this epic are counted.
+    /// * `statuses` - The database's status definitions, to tell which statuses count as done.
+    #[must_use]
+    pub fn progress(&self, stories: &[Story], statuses: &[StatusDefinition]) -> Progress {
+        stories
+            .iter()
+            .filter(|story| self.story_uuids.contains(&story.story_uuid))
+            .fold(Progress::default(), |progress, story| Progress {
+                done: progress.done + usize::from(story.status.is_done(statuses)),
+                total: progress.total + 1,
+            })
+    }
+}
+
+/// # `EpicBuilder` struct
+/// Collects an epic's fields and checks them in [`EpicBuilder::build`].
+#[derive(Clone, Debug)]
+pub struct EpicBuilder(Epic);
+
+impl EpicBuilder {
+    /// Sets the title.
+    #[must_use]
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.0.title = title.into();
+        self
+    }
+
+    /// Sets the description.
+    #[must_use]
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.0.description = description.into();
+        self
+    }
+
+    /// Sets the status.
+    #[must_use]
+    pub fn status(mut self, status: Status) -> Self {
+        self.0.status = status;
+        self
+    }
+
+    /// Sets the labels.
+    #[must_use]
+    pub fn labels(mut self, labels: Vec<Uuid>) -> Self {
+        self.0.labels = labels;
+        self
+    }
+
+    /// Sets the start date.
+    #[must_use]
+    pub fn start_date(mut self, start_date: NaiveDate) -> Self {
+        self.0.start_date = Some(start_date);
+        self
+    }
+
+    /// Sets the due date.
+    #[must_use]
+    pub fn due_date(mut self, due_date: NaiveDate) -> Self {
+        self.0.due_date = Some(due_date);
+        self
+    }
+
+    /// Sets the display color, e.g. `#d73a4a`.
+    #[must_use]
+    pub fn color(mut self, color: impl Into<String>) -> Self {
+        self.0.color = Some(color.into());
+        self
+    }
+
+    /// Sets the icon.
+    #[must_use]
+    pub fn icon(mut self, icon: impl Into<String>) -> Self {
+        self.0.icon = Some(icon.into());
+        self
+    }
+
+    /// Checks the fields and returns the epic, with its title trimmed.
+    ///
+    /// # Errors
+    /// * `ValidationError::EmptyTitle` / `ValidationError::TitleTooLong` - If the title is blank or too long.
+    /// * `ValidationError::DescriptionTooLong` - If the description is too long.
+    /// * `ValidationError::InvalidDateRange` - If the start date falls after the due date.
+    /// * `ValidationError::InvalidColor` - If the color isn't written as `#rrggbb`.
+    pub fn build(self) -> Result<Epic, ValidationError> {
+        let mut built = self.0;
+        built.validate()?;
+        built.title = built.title.trim().to_string();
+        Ok(built)
+    }
+}
+
+/// # Progress struct
+/// How far along an epic is, by number of finished stories.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct Progress {
+    /// Number of done stories.
+    pub done: usize,
+    /// Number of stories in the epic.
+    pub total: usize,
+}
+
+impl Progress {
+    /// The share of done stories, rounded down; an epic without stories is at 0%.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use ironyyy::models::epics::Progress;
+    /// assert_eq!(Progress { done: 2, total: 3 }.percent(), 66);
+    /// assert_eq!(Progress::default().percent(), 0);
+    /// ```
+    #[must_use]
+    pub fn percent(&self) -> u8 {
+        if self.total == 0 {
+            return 0;
+        }
+        u8::try_from(self.done * 100 / self.total).unwrap_or(100)
+    }
+
+    /// Whether the epic has stories and all of them are done.
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.total > 0 && self.done == self.total
+    }
+}
+
+impl fmt::Display for Progress {
+    /// Renders the progress as e.g. `2/3 (66%)`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{} ({}%)", self.done, self.total, self.percent())
+    }
+}
+
+impl Scheduled for Epic {
+    fn start_date(&self) -> Option<NaiveDate> {
+        self.start_date
+    }
+
+    fn due_date(&self) -> Option<NaiveDate> {
+        self.due_date
+    }
+
+    fn status(&self) -> Status {
+        self.status
+    }
+}
+
+impl Timestamped for Epic {
+    fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    fn updated_at(&self) -> DateTime<Utc> {
+        self.updated_at
+    }
+}
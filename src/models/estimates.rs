@@ -0,0 +1,104 @@
+//! # Estimates
+//! Story-size estimates, either in abstract story points or in hours, their epic-level totals, and
+//! how hour estimates compare with the time actually logged.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use uuid::Uuid;
+
+/// # Estimate enum
+/// The estimated size of a story.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum Estimate {
+    /// Relative size in story points.
+    Points(u32),
+    /// Expected effort in hours.
+    Hours(u32),
+}
+
+impl fmt::Display for Estimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Estimate::Points(points) => write!(f, "{points} pt"),
+            Estimate::Hours(hours) => write!(f, "{hours} h"),
+        }
+    }
+}
+
+/// # Estimate Totals struct
+/// The sum of a set of stories' estimates. Points and hours are kept apart since they don't convert.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct EstimateTotals {
+    /// Total story points.
+    pub points: u32,
+    /// Total hours.
+    pub hours: u32,
+    /// Number of stories without an estimate.
+    pub unestimated: usize,
+}
+
+impl EstimateTotals {
+    /// Adds one story's estimate (or lack of one) to the totals.
+    pub fn add(&mut self, estimate: Option<Estimate>) {
+        match estimate {
+            Some(Estimate::Points(points)) => self.points = self.points.saturating_add(points),
+            Some(Estimate::Hours(hours)) => self.hours = self.hours.saturating_add(hours),
+            None => self.unestimated += 1,
+        }
+    }
+}
+
+impl FromIterator<Option<Estimate>> for EstimateTotals {
+    fn from_iter<I: IntoIterator<Item = Option<Estimate>>>(iter: I) -> Self {
+        let mut totals = Self::default();
+        for estimate in iter {
+            totals.add(estimate);
+        }
+        totals
+    }
+}
+
+/// # Effort Comparison struct
+/// An item's estimated hours next to the time actually logged against it.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct EffortComparison {
+    /// The epic or story being compared.
+    pub item_uuid: Uuid,
+    /// The estimated effort, in hours.
+    pub estimated_hours: u32,
+    /// The logged effort, in seconds.
+    pub actual_seconds: u64,
+}
+
+impl EffortComparison {
+    /// The actual effort as a percentage of the estimate, or `None` without an estimate.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use ironyyy::models::estimates::EffortComparison;
+    /// let comparison = EffortComparison { estimated_hours: 4, actual_seconds: 5 * 3600, ..Default::default() };
+    /// assert_eq!(comparison.percent(), Some(125));
+    /// ```
+    #[must_use]
+    pub fn percent(&self) -> Option<u64> {
+        let estimated_seconds = u64::from(self.estimated_hours) * 3600;
+        (estimated_seconds > 0).then(|| self.actual_seconds.saturating_mul(100) / estimated_seconds)
+    }
+
+    /// Whether the actual effort is more than `threshold_percent` over the estimate.
+    #[must_use]
+    pub fn exceeds(&self, threshold_percent: u32) -> bool {
+        self.percent().is_some_and(|percent| percent > 100 + u64::from(threshold_percent))
+    }
+}
+
+impl fmt::Display for EffortComparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let minutes = self.actual_seconds / 60;
+        write!(f, "{}h {:02}m of {}h", minutes / 60, minutes % 60, self.estimated_hours)?;
+        match self.percent() {
+            Some(percent) => write!(f, " ({percent}%)"),
+            None => Ok(()),
+        }
+    }
+}
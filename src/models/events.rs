@@ -0,0 +1,66 @@
+//! # Events
+//! Notifications of data changes, emitted by the service layer for other subsystems (autosave,
+//! audit log, notifications, script hooks) to react to.
+
+use super::Status;
+use uuid::Uuid;
+
+/// # Event enum
+/// A change made to an epic or story.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Event {
+    /// An epic was created.
+    EpicCreated {
+        /// The new epic.
+        epic_uuid: Uuid,
+    },
+    /// Some of an epic's fields changed.
+    EpicUpdated {
+        /// The changed epic.
+        epic_uuid: Uuid,
+        /// The names of the changed fields, as recorded in the history.
+        fields: Vec<String>,
+    },
+    /// An epic's status changed. Emitted alongside [`Event::EpicUpdated`].
+    EpicStatusChanged {
+        /// The changed epic.
+        epic_uuid: Uuid,
+        /// The status before the change.
+        from: Status,
+        /// The status after the change.
+        to: Status,
+    },
+    /// An epic was permanently deleted.
+    EpicDeleted {
+        /// The deleted epic.
+        epic_uuid: Uuid,
+    },
+    /// A story was created, or a copy of one was made.
+    StoryCreated {
+        /// The new story.
+        story_uuid: Uuid,
+        /// The epic it was added to, if any.
+        epic_uuid: Option<Uuid>,
+    },
+    /// Some of a story's fields changed.
+    StoryUpdated {
+        /// The changed story.
+        story_uuid: Uuid,
+        /// The names of the changed fields, as recorded in the history.
+        fields: Vec<String>,
+    },
+    /// A story's status changed. Emitted alongside [`Event::StoryUpdated`].
+    StoryStatusChanged {
+        /// The changed story.
+        story_uuid: Uuid,
+        /// The status before the change.
+        from: Status,
+        /// The status after the change.
+        to: Status,
+    },
+    /// A story was permanently deleted.
+    StoryDeleted {
+        /// The deleted story.
+        story_uuid: Uuid,
+    },
+}
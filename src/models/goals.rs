@@ -0,0 +1,37 @@
+//! # Goals
+//! The `Goal` model: an objective with key results, measured by the epics linked to it.
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// # Goal struct
+/// Represents an objective (OKR-style) that epics contribute to.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[allow(clippy::struct_field_names)]
+pub struct Goal {
+    /// The unique identifier of the goal.
+    pub goal_uuid: Uuid,
+    /// What the goal is meant to achieve.
+    pub objective: String,
+    /// The day the goal should be reached by, if set.
+    pub target_date: Option<NaiveDate>,
+    /// The measurable outcomes that show the objective was reached.
+    pub key_results: Vec<String>,
+    /// The UUIDs of the epics contributing to this goal.
+    pub epic_uuids: Vec<Uuid>,
+}
+
+impl Goal {
+    /// Creates a new goal without key results or epics.
+    #[must_use]
+    pub fn new(objective: String, target_date: Option<NaiveDate>) -> Self {
+        Self {
+            goal_uuid: Uuid::new_v4(),
+            objective,
+            target_date,
+            key_results: Vec::new(),
+            epic_uuids: Vec::new(),
+        }
+    }
+}
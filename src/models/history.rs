@@ -0,0 +1,129 @@
+//! # History
+//! Per-item change history: one `HistoryEntry` per changed field, recorded by the service layer
+//! whenever an epic or story is modified.
+
+use super::{custom_fields::{FieldId, FieldValue}, epics::Epic, stories::Story};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use uuid::Uuid;
+
+/// # History Entry struct
+/// A single field change on an epic or story.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct HistoryEntry {
+    /// The UUID of the changed epic or story.
+    pub item_uuid: Uuid,
+    /// The name of the changed field.
+    pub field: String,
+    /// The field's value before the change, as text.
+    pub old_value: String,
+    /// The field's value after the change, as text.
+    pub new_value: String,
+    /// When the change was made.
+    pub timestamp: DateTime<Utc>,
+}
+
+/// # Tracked trait
+///
+/// (applies to Epics and Stories)
+///
+/// Items whose fields are recorded in the change history. When a field is added to a tracked
+/// model, it should be added to `tracked_fields` too.
+pub trait Tracked {
+    /// The UUID history entries are filed under.
+    fn item_uuid(&self) -> Uuid;
+
+    /// The item's fields as `(name, value)` pairs, in a fixed order.
+    fn tracked_fields(&self) -> Vec<(&'static str, String)>;
+}
+
+impl Tracked for Epic {
+    fn item_uuid(&self) -> Uuid {
+        self.epic_uuid
+    }
+
+    fn tracked_fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("number", self.number.to_string()),
+            ("title", self.title.clone()),
+            ("description", self.description.clone()),
+            ("status", format!("{:?}", self.status)),
+            ("stories", join_uuids(&self.story_uuids)),
+            ("parent_epic", optional(self.parent_epic.as_ref())),
+            ("labels", join_uuids(&self.labels)),
+            ("start_date", optional(self.start_date.as_ref())),
+            ("due_date", optional(self.due_date.as_ref())),
+            ("attachments", join_uuids(&self.attachments)),
+            ("archived", self.archived.to_string()),
+            ("rank", self.rank.to_string()),
+            ("custom_fields", join_fields(&self.custom_fields)),
+            ("deleted_at", optional(self.deleted_at.as_ref())),
+            ("color", optional(self.color.as_ref())),
+            ("icon", optional(self.icon.as_ref())),
+        ]
+    }
+}
+
+impl Tracked for Story {
+    fn item_uuid(&self) -> Uuid {
+        self.story_uuid
+    }
+
+    fn tracked_fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("number", self.number.to_string()),
+            ("title", self.title.clone()),
+            ("description", self.description.clone()),
+            ("status", format!("{:?}", self.status)),
+            ("tasks", join_uuids(&self.task_uuids)),
+            ("labels", join_uuids(&self.labels)),
+            ("start_date", optional(self.start_date.as_ref())),
+            ("due_date", optional(self.due_date.as_ref())),
+            ("attachments", join_uuids(&self.attachments)),
+            ("estimate", optional(self.estimate.as_ref())),
+            ("assignee", optional(self.assignee.as_ref())),
+            ("blocked_by", join_uuids(&self.blocked_by)),
+            ("checklist", self.checklist.iter().map(ToString::to_string).collect::<Vec<_>>().join("; ")),
+            ("links", self.links.iter().map(ToString::to_string).collect::<Vec<_>>().join("; ")),
+            ("archived", self.archived.to_string()),
+            ("rank", self.rank.to_string()),
+            ("custom_fields", join_fields(&self.custom_fields)),
+            ("deleted_at", optional(self.deleted_at.as_ref())),
+        ]
+    }
+}
+
+/// Compares two versions of an item and returns an entry for every field that changed.
+#[must_use]
+pub fn diff<T: Tracked>(before: &T, after: &T, timestamp: DateTime<Utc>) -> Vec<HistoryEntry> {
+    before
+        .tracked_fields()
+        .into_iter()
+        .zip(after.tracked_fields())
+        .filter(|((_, old_value), (_, new_value))| old_value != new_value)
+        .map(|((field, old_value), (_, new_value))| HistoryEntry {
+            item_uuid: after.item_uuid(),
+            field: field.to_string(),
+            old_value,
+            new_value,
+            timestamp,
+        })
+        .collect()
+}
+
+/// Renders a UUID list as comma-separated text.
+fn join_uuids(uuids: &[Uuid]) -> String {
+    uuids.iter().map(Uuid::to_string).collect::<Vec<_>>().join(", ")
+}
+
+/// Renders custom field values as `field=value` pairs.
+fn join_fields(fields: &BTreeMap<FieldId, FieldValue>) -> String {
+    fields.iter().map(|(field_id, value)| format!("{field_id}={value}")).collect::<Vec<_>>().join(", ")
+}
+
+/// Renders an optional value, with `None` as empty text.
+fn optional<T: Display>(value: Option<&T>) -> String {
+    value.map(ToString::to_string).unwrap_or_default()
+}
@@ -0,0 +1,30 @@
+//! # Labels
+//! The `Label` model: a named, colored tag that can be attached to epics and stories.
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// # Label struct
+/// Represents a label (tag) defined in a user's database.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[allow(clippy::struct_field_names)]
+pub struct Label {
+    /// The unique identifier of the label.
+    pub label_uuid: Uuid,
+    /// The name of the label.
+    pub name: String,
+    /// The display color of the label, as a hex string such as `#d73a4a`.
+    pub color: String,
+}
+
+impl Label {
+    /// Creates a new label with the given name and color.
+    #[must_use]
+    pub fn new(name: String, color: String) -> Self {
+        Self {
+            label_uuid: Uuid::new_v4(),
+            name,
+            color,
+        }
+    }
+}
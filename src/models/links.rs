@@ -0,0 +1,74 @@
+//! # Links
+//! Typed links between two stories.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use uuid::Uuid;
+
+/// # Link Type enum
+/// How a story relates to the story it links to. Every type has an inverse, seen from the other end.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum LinkType {
+    /// The stories are related; the inverse of itself.
+    #[default]
+    RelatesTo,
+    /// This story duplicates the other one.
+    Duplicates,
+    /// The other story duplicates this one.
+    IsDuplicatedBy,
+    /// This story blocks the other one.
+    Blocks,
+    /// The other story blocks this one.
+    IsBlockedBy,
+}
+
+impl LinkType {
+    /// Returns the link type as seen from the other story.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use ironyyy::models::links::LinkType;
+    /// assert_eq!(LinkType::Duplicates.inverse(), LinkType::IsDuplicatedBy);
+    /// assert_eq!(LinkType::RelatesTo.inverse(), LinkType::RelatesTo);
+    /// ```
+    #[must_use]
+    pub fn inverse(self) -> Self {
+        match self {
+            LinkType::RelatesTo => LinkType::RelatesTo,
+            LinkType::Duplicates => LinkType::IsDuplicatedBy,
+            LinkType::IsDuplicatedBy => LinkType::Duplicates,
+            LinkType::Blocks => LinkType::IsBlockedBy,
+            LinkType::IsBlockedBy => LinkType::Blocks,
+        }
+    }
+}
+
+impl fmt::Display for LinkType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let phrase = match self {
+            LinkType::RelatesTo => "relates to",
+            LinkType::Duplicates => "duplicates",
+            LinkType::IsDuplicatedBy => "is duplicated by",
+            LinkType::Blocks => "blocks",
+            LinkType::IsBlockedBy => "is blocked by",
+        };
+        f.write_str(phrase)
+    }
+}
+
+/// # Link struct
+/// One end of a link between two stories, stored on the story it starts from.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct Link {
+    /// How this story relates to the other one.
+    pub link_type: LinkType,
+    /// The other story.
+    pub story_uuid: Uuid,
+}
+
+impl fmt::Display for Link {
+    /// Renders the link as e.g. `duplicates <uuid>`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.link_type, self.story_uuid)
+    }
+}
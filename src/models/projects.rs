@@ -0,0 +1,49 @@
+//! # Projects
+//! The `Project` model: a separate body of work within a user's database, grouping epics.
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// # Project struct
+/// Represents a project holding its own epics (and, through them, stories).
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[allow(clippy::struct_field_names)]
+pub struct Project {
+    /// The unique identifier of the project.
+    pub project_uuid: Uuid,
+    /// The name of the project.
+    pub name: String,
+    /// The description of the project.
+    pub description: String,
+    /// The UUIDs of the epics in this project. An epic belongs to at most one project.
+    pub epic_uuids: Vec<Uuid>,
+}
+
+impl Project {
+    /// Creates a new, empty project.
+    #[must_use]
+    pub fn new(name: String, description: String) -> Self {
+        Self {
+            project_uuid: Uuid::new_v4(),
+            name,
+            description,
+            epic_uuids: Vec::new(),
+        }
+    }
+}
+
+/// # Project Stats struct
+/// Summary numbers for a project.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct ProjectStats {
+    /// Number of epics in the project.
+    pub epics: usize,
+    /// Number of those epics that are done.
+    pub done_epics: usize,
+    /// Number of stories in the project's epics.
+    pub stories: usize,
+    /// Number of those stories that are done.
+    pub done_stories: usize,
+    /// Number of those stories past their due date without being done.
+    pub overdue_stories: usize,
+}
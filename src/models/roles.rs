@@ -0,0 +1,87 @@
+//! # Roles
+//! What a member of a shared database may do. A user's own database is always opened as its
+//! [`Role::Owner`]; the service layer checks the role before every change to the projects.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use uuid::Uuid;
+
+/// # Role enum
+/// A member's role in a shared database.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum Role {
+    /// Can do everything, including deleting projects and managing the members.
+    #[default]
+    Owner,
+    /// Can change the projects, but not delete them or manage the members.
+    Editor,
+    /// Can only read.
+    Viewer,
+}
+
+impl Role {
+    /// Every role, from the most to the least allowed.
+    pub const ALL: [Role; 3] = [Role::Owner, Role::Editor, Role::Viewer];
+
+    /// Whether the role may do something.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use ironyyy::models::roles::{Permission, Role};
+    /// assert!(Role::Editor.allows(Permission::Edit));
+    /// assert!(!Role::Editor.allows(Permission::DeleteProject));
+    /// assert!(!Role::Viewer.allows(Permission::Edit));
+    /// ```
+    #[must_use]
+    pub fn allows(self, permission: Permission) -> bool {
+        match self {
+            Role::Owner => true,
+            Role::Editor => permission == Permission::Edit,
+            Role::Viewer => false,
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Role::Owner => write!(f, "owner"),
+            Role::Editor => write!(f, "editor"),
+            Role::Viewer => write!(f, "viewer"),
+        }
+    }
+}
+
+/// # Permission enum
+/// Something only some roles may do.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum Permission {
+    /// Create, change, or delete epics, stories, and everything else in the projects.
+    Edit,
+    /// Delete a whole project.
+    DeleteProject,
+    /// Add or remove members of a shared database, or change their roles.
+    ManageMembers,
+}
+
+impl fmt::Display for Permission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Permission::Edit => write!(f, "make changes"),
+            Permission::DeleteProject => write!(f, "delete projects"),
+            Permission::ManageMembers => write!(f, "manage members"),
+        }
+    }
+}
+
+/// # Membership struct
+/// A member of a shared database and their role, kept in its encrypted data.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct Membership {
+    /// The member's UUID.
+    pub user_uuid: Uuid,
+    /// The member's username, for listing the members.
+    pub username: String,
+    /// What the member may do.
+    pub role: Role,
+}
@@ -0,0 +1,216 @@
+//! # Settings
+//! The user's interface preferences, saved with their database.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The command-line flag that starts the full-screen interface whatever the settings say.
+pub const TUI_FLAG: &str = "--tui";
+
+/// # Settings struct
+/// Interface preferences. Settings added later fall back to their defaults when an older
+/// database is loaded.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Whether to start in the full-screen interface instead of the line-based pages. Only has an
+    /// effect when built with the `tui` feature.
+    pub full_screen: bool,
+    /// The color theme of the line-based pages.
+    pub theme: ThemeName,
+    /// Minutes without input before the session locks; 0 never locks it.
+    pub lock_after_minutes: u32,
+    /// The language of the pages.
+    pub locale: Locale,
+    /// Whether to show the pages as plain labeled text for screen readers instead of themed.
+    pub accessible: bool,
+    /// The sort order and filter last used on each list page.
+    pub lists: BTreeMap<ListName, ListPreferences>,
+}
+
+/// # List Name enum
+/// The list pages whose sort order and filter are remembered.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum ListName {
+    /// The epic list.
+    Epics,
+    /// The archived items.
+    Archived,
+}
+
+/// # List Sort enum
+/// The orders a list page can be sorted in.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum ListSort {
+    /// The page's own order, e.g. the epic tree.
+    #[default]
+    Default,
+    /// By status, in workflow order.
+    Status,
+    /// By the [`Priority`](crate::pages::epic_wizard::PRIORITY_FIELD) field, in the order of its
+    /// options; items without one come last.
+    Priority,
+    /// By due date, earliest first; items without one come last.
+    DueDate,
+}
+
+impl ListSort {
+    /// Every sort order, in the order they are offered.
+    pub const ALL: [ListSort; 4] = [ListSort::Default, ListSort::Status, ListSort::Priority, ListSort::DueDate];
+
+    /// The sort order's name as shown and typed, e.g. `due`.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            ListSort::Default => "default",
+            ListSort::Status => "status",
+            ListSort::Priority => "priority",
+            ListSort::DueDate => "due",
+        }
+    }
+
+    /// Looks a sort order up by name, ignoring case; an empty name is the default order.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use ironyyy::models::settings::ListSort;
+    /// assert_eq!(ListSort::from_name("Due"), Some(ListSort::DueDate));
+    /// assert_eq!(ListSort::from_name(""), Some(ListSort::Default));
+    /// assert_eq!(ListSort::from_name("size"), None);
+    /// ```
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Some(ListSort::Default);
+        }
+        Self::ALL.into_iter().find(|sort| sort.name().eq_ignore_ascii_case(name))
+    }
+}
+
+/// # List Preferences struct
+/// How a list page was last sorted and filtered.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(default)]
+pub struct ListPreferences {
+    /// The sort order.
+    pub sort: ListSort,
+    /// The filter text; empty shows every row.
+    pub filter: String,
+}
+
+/// # Theme Name enum
+/// The color themes to choose from (see [`crate::pages::theme`]).
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum ThemeName {
+    /// Colors for dark terminal backgrounds.
+    #[default]
+    Default,
+    /// Colors for light terminal backgrounds.
+    Light,
+    /// Bright, bold colors without dimmed text.
+    HighContrast,
+    /// No colors or other styling at all.
+    Monochrome,
+}
+
+impl ThemeName {
+    /// Every theme, in the order they are offered.
+    pub const ALL: [ThemeName; 4] = [ThemeName::Default, ThemeName::Light, ThemeName::HighContrast, ThemeName::Monochrome];
+
+    /// The theme's name as shown and typed, e.g. `high-contrast`.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            ThemeName::Default => "default",
+            ThemeName::Light => "light",
+            ThemeName::HighContrast => "high-contrast",
+            ThemeName::Monochrome => "monochrome",
+        }
+    }
+
+    /// Looks a theme up by name, ignoring case; `no-color` is accepted for the monochrome theme.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use ironyyy::models::settings::ThemeName;
+    /// assert_eq!(ThemeName::from_name("High-Contrast"), Some(ThemeName::HighContrast));
+    /// assert_eq!(ThemeName::from_name("no-color"), Some(ThemeName::Monochrome));
+    /// assert_eq!(ThemeName::from_name("neon"), None);
+    /// ```
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim();
+        if name.eq_ignore_ascii_case("no-color") {
+            return Some(ThemeName::Monochrome);
+        }
+        Self::ALL.into_iter().find(|theme| theme.name().eq_ignore_ascii_case(name))
+    }
+}
+
+/// # Locale enum
+/// The languages the pages can be shown in (see [`crate::pages::i18n`]).
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum Locale {
+    /// English.
+    #[default]
+    English,
+    /// English with accented letters, to spot text that skips the translation catalog.
+    Pseudo,
+}
+
+impl Locale {
+    /// Every locale, in the order they are offered.
+    pub const ALL: [Locale; 2] = [Locale::English, Locale::Pseudo];
+
+    /// The locale's language tag as shown and typed, e.g. `en`.
+    #[must_use]
+    pub fn tag(self) -> &'static str {
+        match self {
+            Locale::English => "en",
+            Locale::Pseudo => "en-XA",
+        }
+    }
+
+    /// Looks a locale up by its language tag, ignoring case; `pseudo` is accepted for `en-XA`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use ironyyy::models::settings::Locale;
+    /// assert_eq!(Locale::from_tag("EN"), Some(Locale::English));
+    /// assert_eq!(Locale::from_tag("pseudo"), Some(Locale::Pseudo));
+    /// assert_eq!(Locale::from_tag("fr"), None);
+    /// ```
+    #[must_use]
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let tag = tag.trim();
+        if tag.eq_ignore_ascii_case("pseudo") {
+            return Some(Locale::Pseudo);
+        }
+        Self::ALL.into_iter().find(|locale| locale.tag().eq_ignore_ascii_case(tag))
+    }
+}
+
+impl Settings {
+    /// How a list page was last sorted and filtered; the defaults if it never was.
+    #[must_use]
+    pub fn list(&self, name: ListName) -> ListPreferences {
+        self.lists.get(&name).cloned().unwrap_or_default()
+    }
+
+    /// Whether to start in the full-screen interface: either the setting is on or [`TUI_FLAG`]
+    /// is among the command-line arguments.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use ironyyy::models::settings::Settings;
+    /// let settings = Settings::default();
+    /// assert!(settings.wants_full_screen(["ironyyy", "--tui"]));
+    /// assert!(!settings.wants_full_screen(["ironyyy"]));
+    /// assert!(Settings { full_screen: true, ..Settings::default() }.wants_full_screen(["ironyyy"]));
+    /// ```
+    #[must_use]
+    pub fn wants_full_screen(&self, args: impl IntoIterator<Item = impl AsRef<str>>) -> bool {
+        self.full_screen || args.into_iter().any(|arg| arg.as_ref() == TUI_FLAG)
+    }
+}
@@ -0,0 +1,62 @@
+//! # Sprints
+//! The `Sprint` model: a fixed-length iteration that a set of stories is planned into.
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// # Sprint struct
+/// Represents a sprint (iteration) in the project management system.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[allow(clippy::struct_field_names)]
+pub struct Sprint {
+    /// The unique identifier of the sprint.
+    pub sprint_uuid: Uuid,
+    /// The name of the sprint.
+    pub name: String,
+    /// The first day of the sprint.
+    pub start: NaiveDate,
+    /// The last day of the sprint.
+    pub end: NaiveDate,
+    /// The UUIDs of the stories planned into this sprint.
+    pub story_uuids: Vec<Uuid>,
+    /// Whether the sprint has been closed.
+    pub closed: bool,
+}
+
+impl Sprint {
+    /// Creates a new, open sprint with no stories.
+    #[must_use]
+    pub fn new(name: String, start: NaiveDate, end: NaiveDate) -> Self {
+        Self {
+            sprint_uuid: Uuid::new_v4(),
+            name,
+            start,
+            end,
+            story_uuids: Vec::new(),
+            closed: false,
+        }
+    }
+}
+
+/// # Burndown Point struct
+/// The story points left in a sprint at the end of one day.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct BurndownPoint {
+    /// The day.
+    pub date: NaiveDate,
+    /// The points of the sprint's stories that weren't done by the end of the day.
+    pub remaining_points: u32,
+}
+
+/// # Sprint Velocity struct
+/// The story points a sprint got done.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct SprintVelocity {
+    /// The sprint.
+    pub sprint_uuid: Uuid,
+    /// The sprint's name.
+    pub name: String,
+    /// The points of the sprint's stories that were done by its last day.
+    pub completed_points: u32,
+}
@@ -0,0 +1,71 @@
+//! # Stats
+//! Summary numbers about a database's epics and stories, for the dashboard and reports.
+
+use super::statuses::StatusCategory;
+use super::Status;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// # Category Counts struct
+/// How many items are in each status category.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct CategoryCounts {
+    /// Items whose work hasn't started.
+    pub todo: usize,
+    /// Items whose work is underway.
+    pub in_progress: usize,
+    /// Items that are finished.
+    pub done: usize,
+}
+
+impl CategoryCounts {
+    /// Counts one more item in `category`.
+    pub fn add(&mut self, category: StatusCategory) {
+        match category {
+            StatusCategory::Todo => self.todo += 1,
+            StatusCategory::InProgress => self.in_progress += 1,
+            StatusCategory::Done => self.done += 1,
+        }
+    }
+
+    /// The number of items counted.
+    #[must_use]
+    pub fn total(&self) -> usize {
+        self.todo + self.in_progress + self.done
+    }
+}
+
+/// # Epic Stats struct
+/// Summary numbers for one epic's stories.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct EpicStats {
+    /// The epic.
+    pub epic_uuid: Uuid,
+    /// Its stories, by status category.
+    pub stories: CategoryCounts,
+    /// Its stories past their due date without being done.
+    pub overdue_stories: usize,
+}
+
+/// # Stats struct
+/// Summary numbers for the epics and stories shown in default views (not archived or deleted).
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct Stats {
+    /// Epics by status category.
+    pub epics: CategoryCounts,
+    /// Stories by status category.
+    pub stories: CategoryCounts,
+    /// Stories by exact status.
+    pub stories_by_status: BTreeMap<Status, usize>,
+    /// Stories by label UUID. A story with several labels counts towards each.
+    pub stories_by_label: BTreeMap<Uuid, usize>,
+    /// Each epic's numbers, in rank order.
+    pub per_epic: Vec<EpicStats>,
+    /// Epics past their due date without being done.
+    pub overdue_epics: usize,
+    /// Stories past their due date without being done.
+    pub overdue_stories: usize,
+    /// Epics and stories modified in the last seven days.
+    pub touched_this_week: usize,
+}
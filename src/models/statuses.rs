@@ -0,0 +1,74 @@
+//! # Statuses
+//! Per-database workflow statuses. The built-in statuses ([`Status::BUILTINS`]) are always
+//! available; users can add their own, each belonging to a [`StatusCategory`].
+
+use super::Status;
+use serde::{Deserialize, Serialize};
+
+/// # Status Category enum
+/// The broad stage of work a status represents. Reports, filters, and rules look at the category
+/// rather than the individual status, so they keep working with custom statuses.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum StatusCategory {
+    /// Work that hasn't started.
+    #[default]
+    Todo,
+    /// Work that is underway.
+    InProgress,
+    /// Work that is finished.
+    Done,
+}
+
+/// # Status Definition struct
+/// The name, category, and position of a status in a database's workflow.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct StatusDefinition {
+    /// The status being defined.
+    pub status: Status,
+    /// The display name.
+    pub name: String,
+    /// The category the status belongs to.
+    pub category: StatusCategory,
+    /// Position in boards and pickers, lowest first.
+    pub order: u32,
+}
+
+/// Returns the definitions of the built-in statuses, used for new databases and for databases
+/// saved before statuses were configurable.
+#[must_use]
+pub fn default_statuses() -> Vec<StatusDefinition> {
+    Status::BUILTINS.into_iter().zip(0..).map(|(status, order)| builtin_definition(status, order)).collect()
+}
+
+/// Adds definitions for built-in statuses missing from a database's list (e.g. built-ins added
+/// after the database was saved), after the existing statuses.
+pub fn add_missing_builtins(statuses: &mut Vec<StatusDefinition>) {
+    for status in Status::BUILTINS {
+        if find_definition(statuses, status).is_none() {
+            let order = statuses.iter().map(|definition| definition.order + 1).max().unwrap_or(0);
+            statuses.push(builtin_definition(status, order));
+        }
+    }
+}
+
+/// Builds the default definition of a built-in status.
+fn builtin_definition(status: Status, order: u32) -> StatusDefinition {
+    StatusDefinition {
+        status,
+        name: status.builtin_name().unwrap_or_default().to_string(),
+        category: status.builtin_category().unwrap_or_default(),
+        order,
+    }
+}
+
+/// Finds the definition of a status.
+#[must_use]
+pub fn find_definition(statuses: &[StatusDefinition], status: Status) -> Option<&StatusDefinition> {
+    statuses.iter().find(|definition| definition.status == status)
+}
+
+/// Finds the definition of a status by its display name, ignoring case and surrounding spaces.
+#[must_use]
+pub fn find_definition_by_name<'a>(statuses: &'a [StatusDefinition], name: &str) -> Option<&'a StatusDefinition> {
+    statuses.iter().find(|definition| definition.name.eq_ignore_ascii_case(name.trim()))
+}
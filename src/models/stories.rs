@@ -1,29 +1,286 @@
-use super::Status;
+//! # Stories
+//! The `Story` model: a single unit of work belonging to an epic.
+
+use super::{Scheduled, Status, Timestamped, custom_fields::{FieldId, FieldValue}, checklists::ChecklistItem, estimates::Estimate, links::Link, validation::{ValidationError, validate_date_range, validate_description, validate_title}};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use uuid::Uuid;
 
 /// # Story struct
 /// Represents a story in the project management system.
 #[derive(Clone, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[allow(clippy::struct_field_names)]
 pub struct Story {
     /// The unique identifier of the story.
     pub story_uuid: Uuid,
+    /// The per-database number of this story, shown as e.g. `STORY-12` (set by the service layer).
+    #[serde(default)]
+    pub number: u32,
     /// The title of the story.
     pub title: String,
     /// The description of the story.
     pub description: String,
     /// The status of the story.
-    pub status: Status,    
+    pub status: Status,
+    /// The list of task UUIDs belonging to this story.
+    #[serde(default)]
+    pub task_uuids: Vec<Uuid>,
+    /// The UUIDs of the labels attached to this story.
+    #[serde(default)]
+    pub labels: Vec<Uuid>,
+    /// The day work on this story is planned to start.
+    #[serde(default)]
+    pub start_date: Option<NaiveDate>,
+    /// The day this story is due.
+    #[serde(default)]
+    pub due_date: Option<NaiveDate>,
+    /// The UUIDs of the files attached to this story.
+    #[serde(default)]
+    pub attachments: Vec<Uuid>,
+    /// The estimated size of this story.
+    #[serde(default)]
+    pub estimate: Option<Estimate>,
+    /// The UUID of the user this story is assigned to, if any.
+    #[serde(default)]
+    pub assignee: Option<Uuid>,
+    /// The UUIDs of the stories that must be closed before this one can be.
+    #[serde(default)]
+    pub blocked_by: Vec<Uuid>,
+    /// The story's acceptance criteria.
+    #[serde(default)]
+    pub checklist: Vec<ChecklistItem>,
+    /// Links to related and duplicate stories. Blocking links live in `blocked_by` instead.
+    #[serde(default)]
+    pub links: Vec<Link>,
+    /// Whether this story is hidden from default views.
+    #[serde(default)]
+    pub archived: bool,
+    /// The position of this story among the other stories in its epic, lowest first.
+    #[serde(default)]
+    pub rank: u32,
+    /// The values of this story's custom fields.
+    #[serde(default)]
+    pub custom_fields: BTreeMap<FieldId, FieldValue>,
+    /// The UUIDs of the users who want to hear about changes to this story. Not part of its
+    /// history, since watching changes nothing about the story itself.
+    #[serde(default)]
+    pub watchers: Vec<Uuid>,
+    /// When this story was moved to the trash, if it was. Deleted items are hidden everywhere
+    /// until they are restored or purged.
+    #[serde(default)]
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// When this story was created (set by the service layer).
+    #[serde(default)]
+    pub created_at: DateTime<Utc>,
+    /// When this story was last modified (set by the service layer).
+    #[serde(default)]
+    pub updated_at: DateTime<Utc>,
 }
 
 impl Story {
     /// Creates a new story with the given title and description.
+    #[must_use]
     pub fn new(title: String, description: String) -> Self {
         Self {
             story_uuid: Uuid::new_v4(),
+            number: 0,
             title,
             description,
             status: Status::Open,
+            task_uuids: Vec::new(),
+            labels: Vec::new(),
+            start_date: None,
+            due_date: None,
+            attachments: Vec::new(),
+            estimate: None,
+            assignee: None,
+            blocked_by: Vec::new(),
+            checklist: Vec::new(),
+            links: Vec::new(),
+            archived: false,
+            rank: 0,
+            custom_fields: BTreeMap::new(),
+            watchers: Vec::new(),
+            deleted_at: None,
+            created_at: DateTime::default(),
+            updated_at: DateTime::default(),
+        }
+    }
+
+    /// Whether this story is in the trash.
+    #[must_use]
+    pub fn is_deleted(&self) -> bool {
+        self.deleted_at.is_some()
+    }
+
+    /// The short, human-readable ID of this story, e.g. `STORY-12`.
+    #[must_use]
+    pub fn key(&self) -> String {
+        format!("STORY-{}", self.number)
+    }
+
+    /// Checks the title, description, and dates against the same rules as
+    /// [`StoryBuilder::build`], for stories that didn't come through the builder or the service
+    /// layer, such as imported ones.
+    ///
+    /// # Errors
+    /// Same as [`StoryBuilder::build`].
+    /// # Examples
+    /// ```rust
+    /// use ironyyy::models::{stories::Story, validation::ValidationError};
+    /// let mut story = Story::new("Imported".to_string(), String::new());
+    /// assert!(story.validate().is_ok());
+    /// story.title = " ".to_string();
+    /// assert_eq!(story.validate(), Err(ValidationError::EmptyTitle));
+    /// ```
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        validate_title(&self.title)?;
+        validate_description(&self.description)?;
+        validate_date_range(self.start_date, self.due_date)
+    }
+
+    /// Copies this story as a template for repeated work.
+    ///
+    /// The copy gets a new UUID, a "(copy)" suffix on its title, and the `Open` status, with its
+    /// checklist unchecked. Everything tying it to the original's history or to other items is
+    /// left behind: tasks, attachments, blockers, links, its number, rank, archived flag, and
+    /// timestamps.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use ironyyy::models::{Status, stories::Story};
+    /// let story = Story::builder().title("Release notes").status(Status::Closed).build().unwrap();
+    /// let copy = story.duplicate();
+    /// assert_eq!((copy.title.as_str(), copy.status), ("Release notes (copy)", Status::Open));
+    /// assert_ne!(copy.story_uuid, story.story_uuid);
+    /// ```
+    #[must_use]
+    pub fn duplicate(&self) -> Self {
+        Self {
+            title: format!("{} (copy)", self.title),
+            description: self.description.clone(),
+            labels: self.labels.clone(),
+            start_date: self.start_date,
+            due_date: self.due_date,
+            estimate: self.estimate,
+            assignee: self.assignee,
+            checklist: self.checklist.iter().map(|item| ChecklistItem::new(item.text.clone())).collect(),
+            custom_fields: self.custom_fields.clone(),
+            ..Self::new(String::new(), String::new())
         }
     }
-}
\ No newline at end of file
+
+    /// Starts building a story with more than a title and description.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use ironyyy::models::{estimates::Estimate, stories::Story};
+    /// let story = Story::builder().title("Login form").estimate(Estimate::Points(3)).build().unwrap();
+    /// assert_eq!(story.estimate, Some(Estimate::Points(3)));
+    /// ```
+    #[must_use]
+    pub fn builder() -> StoryBuilder {
+        StoryBuilder(Self::new(String::new(), String::new()))
+    }
+}
+
+/// # `StoryBuilder` struct
+/// Collects a story's fields and checks them in [`StoryBuilder::build`].
+#[derive(Clone, Debug)]
+pub struct StoryBuilder(Story);
+
+impl StoryBuilder {
+    /// Sets the title.
+    #[must_use]
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.0.title = title.into();
+        self
+    }
+
+    /// Sets the description.
+    #[must_use]
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.0.description = description.into();
+        self
+    }
+
+    /// Sets the status.
+    #[must_use]
+    pub fn status(mut self, status: Status) -> Self {
+        self.0.status = status;
+        self
+    }
+
+    /// Sets the labels.
+    #[must_use]
+    pub fn labels(mut self, labels: Vec<Uuid>) -> Self {
+        self.0.labels = labels;
+        self
+    }
+
+    /// Sets the start date.
+    #[must_use]
+    pub fn start_date(mut self, start_date: NaiveDate) -> Self {
+        self.0.start_date = Some(start_date);
+        self
+    }
+
+    /// Sets the due date.
+    #[must_use]
+    pub fn due_date(mut self, due_date: NaiveDate) -> Self {
+        self.0.due_date = Some(due_date);
+        self
+    }
+
+    /// Sets the estimate.
+    #[must_use]
+    pub fn estimate(mut self, estimate: Estimate) -> Self {
+        self.0.estimate = Some(estimate);
+        self
+    }
+
+    /// Sets the assignee.
+    #[must_use]
+    pub fn assignee(mut self, assignee: Uuid) -> Self {
+        self.0.assignee = Some(assignee);
+        self
+    }
+
+    /// Checks the fields and returns the story, with its title trimmed.
+    ///
+    /// # Errors
+    /// * `ValidationError::EmptyTitle` / `ValidationError::TitleTooLong` - If the title is blank or too long.
+    /// * `ValidationError::DescriptionTooLong` - If the description is too long.
+    /// * `ValidationError::InvalidDateRange` - If the start date falls after the due date.
+    pub fn build(self) -> Result<Story, ValidationError> {
+        let mut built = self.0;
+        built.validate()?;
+        built.title = built.title.trim().to_string();
+        Ok(built)
+    }
+}
+
+impl Scheduled for Story {
+    fn start_date(&self) -> Option<NaiveDate> {
+        self.start_date
+    }
+
+    fn due_date(&self) -> Option<NaiveDate> {
+        self.due_date
+    }
+
+    fn status(&self) -> Status {
+        self.status
+    }
+}
+
+impl Timestamped for Story {
+    fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    fn updated_at(&self) -> DateTime<Utc> {
+        self.updated_at
+    }
+}
@@ -0,0 +1,42 @@
+//! # Tasks
+//! The `Task` model: a checklist-sized piece of a story that doesn't warrant a story of its own.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use uuid::Uuid;
+
+/// # Task struct
+/// Represents a task (subtask) under a story.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[allow(clippy::struct_field_names)]
+pub struct Task {
+    /// The unique identifier of the task.
+    pub task_uuid: Uuid,
+    /// The title of the task.
+    pub title: String,
+    /// Whether the task is done.
+    pub done: bool,
+    /// The UUID of the user the task is assigned to, if any.
+    pub assignee: Option<Uuid>,
+}
+
+impl Task {
+    /// Creates a new, unassigned, not-done task with the given title.
+    #[must_use]
+    pub fn new(title: String) -> Self {
+        Self {
+            task_uuid: Uuid::new_v4(),
+            title,
+            done: false,
+            assignee: None,
+        }
+    }
+}
+
+impl fmt::Display for Task {
+    /// Renders the task as a checklist line, e.g. `[x] Write migration`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let check = if self.done { 'x' } else { ' ' };
+        write!(f, "[{check}] {}", self.title)
+    }
+}
@@ -0,0 +1,183 @@
+//! # Validation
+//! Checks for epic and story fields, and the errors they report. Pages, the service layer, and
+//! anything reading epics and stories from outside (see [`crate::models::epics::Epic::validate`])
+//! all use these, so every entry point enforces the same rules. Status changes are checked
+//! against the database's workflow by [`crate::service::check_transition`].
+//!
+//! Titles are trimmed, must not be empty, and are limited to [`MAX_TITLE_LENGTH`] characters;
+//! descriptions are limited to [`MAX_DESCRIPTION_LENGTH`] characters. Label and custom field
+//! names must be unique, ignoring case. Usernames must not be blank or taken, and passwords must
+//! be at least [`MIN_PASSWORD_LENGTH`] characters and not contain the username.
+
+use chrono::NaiveDate;
+
+/// The longest allowed title, in characters.
+pub const MAX_TITLE_LENGTH: usize = 200;
+
+/// The longest allowed description, in characters.
+pub const MAX_DESCRIPTION_LENGTH: usize = 10_000;
+
+/// The shortest allowed password, in characters.
+pub const MIN_PASSWORD_LENGTH: usize = 12;
+
+/// # Validation Error
+/// Why an epic, story, task, or account field was rejected.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ValidationError {
+    /// The title is empty.
+    EmptyTitle,
+    /// The title is longer than [`MAX_TITLE_LENGTH`] characters.
+    TitleTooLong,
+    /// The description is longer than [`MAX_DESCRIPTION_LENGTH`] characters.
+    DescriptionTooLong,
+    /// The start date falls after the due date.
+    InvalidDateRange,
+    /// The color isn't a `#rrggbb` hex color.
+    InvalidColor,
+    /// Something else already uses this name.
+    DuplicateName(String),
+    /// The name is empty.
+    EmptyName,
+    /// The password is shorter than [`MIN_PASSWORD_LENGTH`] characters.
+    PasswordTooShort,
+    /// The password contains the username.
+    PasswordContainsUsername,
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationError::EmptyTitle => write!(f, "The title must not be empty"),
+            ValidationError::TitleTooLong => write!(f, "The title must be at most {MAX_TITLE_LENGTH} characters"),
+            ValidationError::DescriptionTooLong => write!(f, "The description must be at most {MAX_DESCRIPTION_LENGTH} characters"),
+            ValidationError::InvalidDateRange => write!(f, "The start date must not be after the due date"),
+            ValidationError::InvalidColor => write!(f, "The color must be written as #rrggbb"),
+            ValidationError::DuplicateName(name) => write!(f, "The name {name:?} is already taken"),
+            ValidationError::EmptyName => write!(f, "The name must not be empty"),
+            ValidationError::PasswordTooShort => write!(f, "The password must be at least {MIN_PASSWORD_LENGTH} characters"),
+            ValidationError::PasswordContainsUsername => write!(f, "The password must not contain the username"),
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Trims a title and checks that it is neither blank nor too long.
+///
+/// # Returns
+/// * `Result<String, ValidationError>` - The trimmed title.
+/// # Errors
+/// * `ValidationError::EmptyTitle` - If the title is empty or only whitespace.
+/// * `ValidationError::TitleTooLong` - If the trimmed title is over [`MAX_TITLE_LENGTH`] characters.
+/// # Examples
+/// ```rust
+/// use ironyyy::models::validation::{validate_title, ValidationError};
+/// assert_eq!(validate_title("  Ship it \n").unwrap(), "Ship it");
+/// assert_eq!(validate_title("   "), Err(ValidationError::EmptyTitle));
+/// ```
+pub fn validate_title(title: &str) -> Result<String, ValidationError> {
+    let title = title.trim();
+    if title.is_empty() {
+        return Err(ValidationError::EmptyTitle);
+    }
+    if title.chars().count() > MAX_TITLE_LENGTH {
+        return Err(ValidationError::TitleTooLong);
+    }
+    Ok(title.to_string())
+}
+
+/// Checks that a description isn't too long.
+///
+/// # Errors
+/// * `ValidationError::DescriptionTooLong` - If it is over [`MAX_DESCRIPTION_LENGTH`] characters.
+pub fn validate_description(description: &str) -> Result<(), ValidationError> {
+    if description.chars().count() > MAX_DESCRIPTION_LENGTH {
+        return Err(ValidationError::DescriptionTooLong);
+    }
+    Ok(())
+}
+
+/// Checks that a start date, if any, doesn't fall after a due date, if any.
+///
+/// # Errors
+/// * `ValidationError::InvalidDateRange` - If `start` is after `due`.
+pub fn validate_date_range(start: Option<NaiveDate>, due: Option<NaiveDate>) -> Result<(), ValidationError> {
+    if let (Some(start), Some(due)) = (start, due)
+        && start > due
+    {
+        return Err(ValidationError::InvalidDateRange);
+    }
+    Ok(())
+}
+
+/// Checks that a color is written as `#rrggbb`.
+///
+/// # Errors
+/// * `ValidationError::InvalidColor` - If it isn't.
+/// # Examples
+/// ```rust
+/// use ironyyy::models::validation::validate_color;
+/// assert!(validate_color("#d73a4a").is_ok());
+/// assert!(validate_color("red").is_err());
+/// ```
+pub fn validate_color(color: &str) -> Result<(), ValidationError> {
+    match color.strip_prefix('#') {
+        Some(hex) if hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()) => Ok(()),
+        _ => Err(ValidationError::InvalidColor),
+    }
+}
+
+/// Checks that a name isn't already among `existing`, ignoring case.
+///
+/// # Errors
+/// * `ValidationError::DuplicateName` - If it is.
+/// # Examples
+/// ```rust
+/// use ironyyy::models::validation::{validate_unique_name, ValidationError};
+/// assert!(validate_unique_name("ux", ["bug", "docs"]).is_ok());
+/// assert_eq!(validate_unique_name("Bug", ["bug"]), Err(ValidationError::DuplicateName("Bug".to_string())));
+/// ```
+pub fn validate_unique_name<'a>(name: &str, existing: impl IntoIterator<Item = &'a str>) -> Result<(), ValidationError> {
+    if existing.into_iter().any(|other| other.eq_ignore_ascii_case(name)) {
+        return Err(ValidationError::DuplicateName(name.to_string()));
+    }
+    Ok(())
+}
+
+/// Trims a new username and checks that it is neither blank nor among `existing`, ignoring case.
+///
+/// # Returns
+/// * `Result<String, ValidationError>` - The trimmed username.
+/// # Errors
+/// * `ValidationError::EmptyName` - If the username is empty or only whitespace.
+/// * `ValidationError::DuplicateName` - If another user already has it.
+pub fn validate_username<'a>(username: &str, existing: impl IntoIterator<Item = &'a str>) -> Result<String, ValidationError> {
+    let username = username.trim();
+    if username.is_empty() {
+        return Err(ValidationError::EmptyName);
+    }
+    validate_unique_name(username, existing)?;
+    Ok(username.to_string())
+}
+
+/// Checks a new password against the password policy.
+///
+/// # Errors
+/// * `ValidationError::PasswordTooShort` - If it is under [`MIN_PASSWORD_LENGTH`] characters.
+/// * `ValidationError::PasswordContainsUsername` - If it contains the username, ignoring case.
+/// # Examples
+/// ```rust
+/// use ironyyy::models::validation::{validate_password, ValidationError};
+/// assert!(validate_password("correct horse battery", "alice").is_ok());
+/// assert_eq!(validate_password("hunter2", "alice"), Err(ValidationError::PasswordTooShort));
+/// assert_eq!(validate_password("Alice-is-the-best", "alice"), Err(ValidationError::PasswordContainsUsername));
+/// ```
+pub fn validate_password(password: &str, username: &str) -> Result<(), ValidationError> {
+    if password.chars().count() < MIN_PASSWORD_LENGTH {
+        return Err(ValidationError::PasswordTooShort);
+    }
+    if !username.is_empty() && password.to_lowercase().contains(&username.to_lowercase()) {
+        return Err(ValidationError::PasswordContainsUsername);
+    }
+    Ok(())
+}
@@ -0,0 +1,52 @@
+//! # Work Logs
+//! The `WorkLog` model: a span of time spent on a story, either timed live or entered by hand.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// # Work Log struct
+/// Time spent working on a story.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[allow(clippy::struct_field_names)]
+pub struct WorkLog {
+    /// The unique identifier of the work log entry.
+    pub work_log_uuid: Uuid,
+    /// The story the time was spent on.
+    pub story_uuid: Uuid,
+    /// When the work started.
+    pub started_at: DateTime<Utc>,
+    /// How long the work took, in seconds. `None` while the timer is still running.
+    pub duration_seconds: Option<u64>,
+    /// A free-form note on what was done.
+    pub note: String,
+}
+
+impl WorkLog {
+    /// Creates a running work log that started at `started_at`.
+    #[must_use]
+    pub fn start(story_uuid: Uuid, started_at: DateTime<Utc>) -> Self {
+        Self {
+            work_log_uuid: Uuid::new_v4(),
+            story_uuid,
+            started_at,
+            duration_seconds: None,
+            note: String::new(),
+        }
+    }
+
+    /// Whether the timer for this entry is still running.
+    #[must_use]
+    pub fn is_running(&self) -> bool {
+        self.duration_seconds.is_none()
+    }
+
+    /// The time spent, counting a running entry up to `now`.
+    #[must_use]
+    pub fn duration(&self, now: DateTime<Utc>) -> Duration {
+        match self.duration_seconds {
+            Some(seconds) => Duration::seconds(i64::try_from(seconds).unwrap_or(i64::MAX)),
+            None => (now - self.started_at).max(Duration::zero()),
+        }
+    }
+}
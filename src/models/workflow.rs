@@ -0,0 +1,134 @@
+//! # Workflow
+//! The status transition table: which moves between status categories are allowed, and which
+//! need a deliberate action such as reopening finished work.
+//!
+//! Rules are written in terms of [`StatusCategory`], so they apply to custom statuses as well as
+//! the built-ins. Moving between two statuses of the same category is always allowed.
+
+use super::{
+    Status,
+    statuses::{StatusCategory, StatusDefinition},
+};
+use serde::{Deserialize, Serialize};
+
+/// # Transition Action enum
+/// The kind of action that moves an item from one status to another.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum TransitionAction {
+    /// An ordinary status change.
+    #[default]
+    Move,
+    /// Taking finished work back up.
+    Reopen,
+}
+
+/// # Transition Rule struct
+/// Allows moving from one status category to another with a given action.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct TransitionRule {
+    /// The category being left.
+    pub from: StatusCategory,
+    /// The category being entered.
+    pub to: StatusCategory,
+    /// The action required for the move.
+    pub action: TransitionAction,
+}
+
+/// # Transition enum
+/// The verdict of the workflow on a requested status change.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Transition {
+    /// The change may go ahead.
+    Allowed,
+    /// The change is possible, but only with the given action.
+    Requires(TransitionAction),
+    /// No rule allows the change.
+    Forbidden,
+}
+
+/// # Workflow struct
+/// A database's configurable transition table.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct Workflow {
+    /// The allowed transitions between categories.
+    pub rules: Vec<TransitionRule>,
+}
+
+impl Default for Workflow {
+    /// Work may move forward or back freely, except that finished work has to be reopened.
+    fn default() -> Self {
+        use StatusCategory::{Done, InProgress, Todo};
+        use TransitionAction::{Move, Reopen};
+        let rules = [
+            (Todo, InProgress, Move),
+            (Todo, Done, Move),
+            (InProgress, Todo, Move),
+            (InProgress, Done, Move),
+            (Done, Todo, Reopen),
+            (Done, InProgress, Reopen),
+        ]
+        .into_iter()
+        .map(|(from, to, action)| TransitionRule { from, to, action })
+        .collect();
+        Self { rules }
+    }
+}
+
+impl Workflow {
+    /// Decides whether an item may move from `from` to `to` with `action`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use ironyyy::models::{Status, statuses::default_statuses};
+    /// use ironyyy::models::workflow::{Transition, TransitionAction, Workflow};
+    /// let workflow = Workflow::default();
+    /// let statuses = default_statuses();
+    /// assert_eq!(workflow.check(Status::Open, Status::Closed, TransitionAction::Move, &statuses), Transition::Allowed);
+    /// assert_eq!(
+    ///     workflow.check(Status::Closed, Status::Open, TransitionAction::Move, &statuses),
+    ///     Transition::Requires(TransitionAction::Reopen)
+    /// );
+    /// ```
+    #[must_use]
+    pub fn check(&self, from: Status, to: Status, action: TransitionAction, statuses: &[StatusDefinition]) -> Transition {
+        let (from, to) = (from.category(statuses), to.category(statuses));
+        if from == to {
+            return Transition::Allowed;
+        }
+        match self.rules.iter().find(|rule| rule.from == from && rule.to == to) {
+            Some(rule) if rule.action == action => Transition::Allowed,
+            Some(rule) => Transition::Requires(rule.action),
+            None => Transition::Forbidden,
+        }
+    }
+
+    /// Adds or replaces the rule for moving from one category to another.
+    pub fn allow(&mut self, from: StatusCategory, to: StatusCategory, action: TransitionAction) {
+        self.forbid(from, to);
+        self.rules.push(TransitionRule { from, to, action });
+    }
+
+    /// Removes the rule for moving from one category to another, so the move is refused.
+    pub fn forbid(&mut self, from: StatusCategory, to: StatusCategory) {
+        self.rules.retain(|rule| rule.from != from || rule.to != to);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::statuses::default_statuses;
+
+    #[test]
+    fn test_configurable_rules() {
+        let statuses = default_statuses();
+        let mut workflow = Workflow::default();
+        assert_eq!(workflow.check(Status::Closed, Status::InProgress, TransitionAction::Reopen, &statuses), Transition::Allowed);
+        assert_eq!(workflow.check(Status::Open, Status::Open, TransitionAction::Reopen, &statuses), Transition::Allowed);
+
+        workflow.forbid(StatusCategory::Todo, StatusCategory::Done);
+        assert_eq!(workflow.check(Status::Open, Status::Closed, TransitionAction::Move, &statuses), Transition::Forbidden);
+        workflow.allow(StatusCategory::Done, StatusCategory::Todo, TransitionAction::Move);
+        assert_eq!(workflow.check(Status::Closed, Status::Open, TransitionAction::Move, &statuses), Transition::Allowed);
+    }
+}
@@ -1,9 +1,662 @@
 //! # Navigation Module
 //! This module handles navigation between different pages/screens of the CLI application.
+//!
+//! The navigator owns its pages, so a page can be built whenever it is needed, such as the detail
+//! page of an epic that was just created: [`Navigator::open`] builds the page of a
+//! [route](routes) from the current state.
+//!
+//! Going back works like a browser's back button: the page closed is kept, with its selection,
+//! filter, and whatever it was showing, so going forward reopens it as it was. Opening a new page
+//! forgets the pages gone back from.
+//!
+//! [Modals](crate::pages::modal) opened by the current page sit on top of it: they get the input
+//! and are drawn over it until they close and hand their result back to it.
+//!
+//! Pages are told when they are shown and when they stop being shown through
+//! [`Page::on_enter`] and [`Page::on_exit`], so they can reload what they show or drop unsaved
+//! edits.
+//!
+//! The navigator also guards the session: while no one is logged in, only the pages that don't
+//! [need a session](Page::needs_session), such as the login and registration pages, can be
+//! shown, and pages that do are closed as soon as they would be. While the session is locked,
+//! showing such a page asks the owner for the [lock screen](crate::pages::lock) instead, which
+//! also happens on the first input after the session sat idle past its
+//! [`AutoLock`] timeout.
 
-use crate::pages::Page;
+pub mod routes;
+pub mod tabs;
 
-/// # Navigator type alias
-/// Manages the current page and navigation history.
-pub type Navigator<'a> = Vec<&'a dyn Page>;
+use crate::clock::Clock;
+use crate::db::ClearTextDBState;
+use crate::models::settings::Settings;
+use crate::pages::i18n::tr;
+use crate::pages::line_editor::{Key, LineEditor};
+use crate::pages::lock::AutoLock;
+use crate::pages::modal::{Modal, ModalOutcome, overlay};
+use crate::pages::{Page, PageAction, PageExit, Size};
+use crate::service::ServiceError;
+use routes::Route;
+use std::sync::Arc;
 
+/// # Session State enum
+/// Whether the pages that [need a session](Page::needs_session) may be shown.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum SessionState {
+    /// No one is logged in.
+    #[default]
+    SignedOut,
+    /// The user is logged in and the session is unlocked.
+    Unlocked,
+    /// The user is logged in but has to unlock the session first.
+    Locked,
+}
+
+/// # Navigator struct
+/// Owns the open pages as a stack: the last page pushed is the one shown and the one that gets
+/// the user's input.
+#[derive(Default)]
+pub struct Navigator {
+    /// The open pages, the shown one last.
+    pages: Vec<Box<dyn Page>>,
+    /// The pages gone back from, the most recent last, for going forward again.
+    forward: Vec<Box<dyn Page>>,
+    /// The modals open over the current page, the one shown last.
+    modals: Vec<Box<dyn Modal>>,
+    /// What the page last entered asked for from [`Page::on_enter`], not yet taken.
+    entered: Option<PageAction>,
+    /// Whether the pages that need a session may be shown.
+    session: SessionState,
+    /// Locks the session after a while without input, if the user set a timeout.
+    auto_lock: Option<AutoLock>,
+    /// Whether pages are rendered in reading order for the accessible mode.
+    linear: bool,
+}
+
+impl Navigator {
+    /// Creates a navigator for an unlocked session, showing `page`.
+    #[must_use]
+    pub fn new(page: Box<dyn Page>) -> Self {
+        Self::with_session(page, SessionState::Unlocked)
+    }
+
+    /// Creates a navigator before anyone has logged in, showing `page`, e.g. the login page.
+    #[must_use]
+    pub fn signed_out(page: Box<dyn Page>) -> Self {
+        Self::with_session(page, SessionState::SignedOut)
+    }
+
+    /// Creates a navigator in the given session state, showing `page`.
+    fn with_session(page: Box<dyn Page>, session: SessionState) -> Self {
+        let mut navigator = Self { pages: vec![page], forward: Vec::new(), modals: Vec::new(), entered: None, session, auto_lock: None, linear: false };
+        navigator.enter();
+        navigator
+    }
+
+    /// Whether the pages that need a session may be shown.
+    #[must_use]
+    pub fn session(&self) -> SessionState {
+        self.session
+    }
+
+    /// Starts the session once the user has logged in, locking it after a while without input
+    /// if `auto_lock` is given.
+    pub fn sign_in(&mut self, auto_lock: Option<AutoLock>) {
+        self.session = SessionState::Unlocked;
+        self.auto_lock = auto_lock;
+    }
+
+    /// Hands the session, its idle timer, and the rendering mode over to `other`, e.g. the
+    /// navigator of the [tab](tabs) switched to, which runs the session from now on.
+    fn hand_over(&mut self, other: &mut Navigator) {
+        other.session = self.session;
+        other.auto_lock = self.auto_lock.take();
+        other.linear = self.linear;
+    }
+
+    /// Locks the session after the time without input now given in `settings`, e.g. once the user
+    /// changed it, if it locks by itself at all.
+    pub fn update_auto_lock(&mut self, settings: &Settings) {
+        if let Some(auto_lock) = &mut self.auto_lock {
+            auto_lock.update(settings);
+        }
+    }
+
+    /// Locks the session, e.g. when the user asks to; the pages stay open behind the lock
+    /// screen.
+    pub fn lock(&mut self) {
+        if self.session == SessionState::Unlocked {
+            self.session = SessionState::Locked;
+        }
+    }
+
+    /// Unlocks the session once the user has proved it is still them, counting the idle time
+    /// from now.
+    pub fn unlock(&mut self, clock: &dyn Clock) {
+        if self.session == SessionState::Locked {
+            self.session = SessionState::Unlocked;
+        }
+        if let Some(auto_lock) = &mut self.auto_lock {
+            auto_lock.touch(clock);
+        }
+    }
+
+    /// Ends the session, closing every page that needs one, including those gone back from.
+    pub fn sign_out(&mut self) {
+        // A current page that needs a session is closed below, so it is only told once.
+        if self.pages.last().is_some_and(|page| !page.needs_session()) {
+            self.leave(PageExit::Hidden);
+        } else {
+            self.modals.clear();
+        }
+        self.session = SessionState::SignedOut;
+        self.auto_lock = None;
+        for mut page in self.pages.extract_if(.., |page| page.needs_session()).chain(self.forward.extract_if(.., |page| page.needs_session())) {
+            page.on_exit(PageExit::Closed);
+        }
+        self.enter();
+    }
+
+    /// Locks the session if it has sat idle past its timeout, and otherwise counts the idle time
+    /// from now. Call this when input arrives, before handing it on.
+    ///
+    /// # Returns
+    /// * `PageAction` - [`PageAction::Lock`] if the session was locked, for the owner to open the
+    ///   lock screen; otherwise `None`.
+    pub fn check_idle(&mut self, clock: &dyn Clock) -> PageAction {
+        let Some(auto_lock) = &mut self.auto_lock else { return PageAction::None };
+        if self.session == SessionState::Unlocked && matches!(auto_lock.check(clock), PageAction::Lock) {
+            self.session = SessionState::Locked;
+            return PageAction::Lock;
+        }
+        auto_lock.touch(clock);
+        PageAction::None
+    }
+
+    /// The number of open pages.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Whether no page is open.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.pages.is_empty()
+    }
+
+    /// Shows `page` on top of the current one, forgetting the pages gone back from.
+    pub fn push(&mut self, page: Box<dyn Page>) {
+        self.leave(PageExit::Hidden);
+        for mut forgotten in self.forward.drain(..) {
+            forgotten.on_exit(PageExit::Closed);
+        }
+        self.pages.push(page);
+        self.enter();
+    }
+
+    /// Closes the current page for good, e.g. a wizard that finished, going back to the one below
+    /// it.
+    ///
+    /// # Returns
+    /// * `Option<Box<dyn Page>>` - The closed page, or `None` if no page was open.
+    pub fn pop(&mut self) -> Option<Box<dyn Page>> {
+        self.leave(PageExit::Closed);
+        let closed = self.pages.pop();
+        self.enter();
+        closed
+    }
+
+    /// Builds the page of `route` from the current database state and shows it on top of the
+    /// current one, e.g. the detail page of an epic that was just created.
+    ///
+    /// # Errors
+    /// * `ServiceError::UnknownReference` - If the route names an epic or story that doesn't
+    ///   exist.
+    pub fn open(&mut self, route: &Route, state: &ClearTextDBState, clock: &Arc<dyn Clock>) -> Result<(), ServiceError> {
+        self.push(route.open(state, clock)?);
+        Ok(())
+    }
+
+    /// Goes back to the page below the current one, keeping the current one to go forward to.
+    pub fn back(&mut self) {
+        self.leave(PageExit::Hidden);
+        if let Some(page) = self.pages.pop() {
+            self.forward.push(page);
+        }
+        self.enter();
+    }
+
+    /// Goes forward to the page last gone back from, as it was left.
+    ///
+    /// # Returns
+    /// * `bool` - Whether there was a page to go forward to.
+    pub fn forward(&mut self) -> bool {
+        let Some(page) = self.forward.pop() else { return false };
+        self.leave(PageExit::Hidden);
+        self.pages.push(page);
+        self.enter();
+        true
+    }
+
+    /// Whether there is a page to go forward to.
+    #[must_use]
+    pub fn can_go_forward(&self) -> bool {
+        !self.forward.is_empty()
+    }
+
+    /// Swaps the current page for `page`, e.g. to rebuild it after the state it shows changed. If
+    /// no page is open, `page` is shown.
+    ///
+    /// # Returns
+    /// * `Option<Box<dyn Page>>` - The page replaced, if any.
+    pub fn replace(&mut self, page: Box<dyn Page>) -> Option<Box<dyn Page>> {
+        self.leave(PageExit::Closed);
+        let replaced = self.pages.pop();
+        self.pages.push(page);
+        self.enter();
+        replaced
+    }
+
+    /// Takes what the page last entered asked for when it was shown, such as
+    /// [`PageAction::Refresh`] to be rebuilt; [`Navigator::run`] handles it before drawing.
+    ///
+    /// # Returns
+    /// * `PageAction` - The action, or `None` if there is nothing to do.
+    pub fn take_entered(&mut self) -> PageAction {
+        self.entered.take().unwrap_or(PageAction::None)
+    }
+
+    /// Closes the modals and tells the current page it is no longer shown.
+    fn leave(&mut self, exit: PageExit) {
+        self.modals.clear();
+        if let Some(page) = self.pages.last_mut() {
+            page.on_exit(exit);
+        }
+    }
+
+    /// Tells the current page it is shown, keeping what it asks for until it is taken. While
+    /// signed out, pages that need a session are closed instead; while locked, the owner is asked
+    /// for the lock screen.
+    fn enter(&mut self) {
+        let mut refused = false;
+        while self.session == SessionState::SignedOut && self.pages.last().is_some_and(|page| page.needs_session()) {
+            if let Some(mut page) = self.pages.pop() {
+                page.on_exit(PageExit::Closed);
+            }
+            refused = true;
+        }
+        self.entered = match self.pages.last_mut() {
+            Some(page) if page.needs_session() && self.session == SessionState::Locked => Some(PageAction::Lock),
+            Some(page) => Some(page.on_enter()),
+            None => None,
+        }
+        .filter(|action| !matches!(action, PageAction::None));
+        if refused && self.entered.is_none() {
+            self.entered = Some(PageAction::ShowError(tr("nav-sign-in-first")));
+        }
+    }
+
+    /// The page shown, if any.
+    #[must_use]
+    pub fn current(&self) -> Option<&dyn Page> {
+        self.pages.last().map(AsRef::as_ref)
+    }
+
+    /// The page shown, to hand it input, if any.
+    #[must_use]
+    pub fn current_mut(&mut self) -> Option<&mut (dyn Page + 'static)> {
+        self.pages.last_mut().map(AsMut::as_mut)
+    }
+
+    /// Opens a modal over the current page. Modals close with their page: going to another page
+    /// or replacing this one closes them without a result.
+    pub fn open_modal(&mut self, modal: Box<dyn Modal>) {
+        self.modals.push(modal);
+    }
+
+    /// The modal shown, if any.
+    #[must_use]
+    pub fn modal(&self) -> Option<&dyn Modal> {
+        self.modals.last().map(AsRef::as_ref)
+    }
+
+    /// Renders the pages from now on with [`Page::render_linear`], for the
+    /// [accessible mode](crate::pages::accessible), or as usual.
+    pub fn set_linear(&mut self, linear: bool) {
+        self.linear = linear;
+    }
+
+    /// Renders the current page, with the modal shown over it if one is open, or after it when
+    /// rendering [linearly](Navigator::set_linear).
+    #[must_use]
+    pub fn render(&self, size: Size) -> Vec<String> {
+        let mut lines = self.current().map(|page| if self.linear { page.render_linear(size) } else { page.render(size) }).unwrap_or_default();
+        match self.modal() {
+            Some(modal) if self.linear => {
+                lines.extend(modal.render(size));
+                lines
+            }
+            Some(modal) => overlay(lines, &modal.render(size)),
+            None => lines,
+        }
+    }
+
+    /// Hands a line of input to the modal shown, or to the current page if no modal is open, and
+    /// dispatches the action that results. A modal that closes hands its result to the current
+    /// page.
+    ///
+    /// # Returns
+    /// * `PageAction` - What is left for the owner to handle, as from [`Navigator::dispatch`].
+    pub fn handle_input(&mut self, input: &str) -> PageAction {
+        let action = match self.modals.last_mut().map(|modal| modal.handle_input(input)) {
+            Some(ModalOutcome::Open) => PageAction::None,
+            Some(ModalOutcome::Error(error)) => PageAction::ShowError(error),
+            Some(ModalOutcome::Close(result)) => {
+                self.modals.pop();
+                self.current_mut().map_or(PageAction::None, |page| page.modal_closed(result))
+            }
+            None => self.current_mut().map_or(PageAction::None, |page| page.handle_input(input)),
+        };
+        self.dispatch(action)
+    }
+
+    /// Applies the navigation a page asked for: [`PageAction::Navigate`] pushes the new page,
+    /// [`PageAction::Pop`] goes back, [`PageAction::Forward`] goes forward again, and
+    /// [`PageAction::OpenModal`] opens the modal. Going back from the last page quits.
+    ///
+    /// # Returns
+    /// * `PageAction` - What is left for the owner to handle (`Quit`, `Refresh`, or a message for
+    ///   [`Toasts::handle`](crate::pages::toast::Toasts::handle)); navigation that was carried
+    ///   out comes back as `None`, and what the page entered asks for is left for
+    ///   [`Navigator::take_entered`].
+    pub fn dispatch(&mut self, action: PageAction) -> PageAction {
+        match action {
+            PageAction::Navigate(page) => {
+                self.push(page);
+                PageAction::None
+            }
+            PageAction::Pop => {
+                self.back();
+                if self.is_empty() { PageAction::Quit } else { PageAction::None }
+            }
+            PageAction::Forward => {
+                if self.forward() { PageAction::None } else { PageAction::ShowError(tr("nav-no-forward")) }
+            }
+            PageAction::OpenModal(modal) => {
+                self.open_modal(modal);
+                PageAction::None
+            }
+            PageAction::Lock => {
+                self.lock();
+                PageAction::Lock
+            }
+            other => other,
+        }
+    }
+
+    /// Reads the next input for the page being shown through the shared [`LineEditor`], hiding
+    /// it if the page asks for a secret and no modal is open.
+    ///
+    /// # Returns
+    /// * `Option<String>` - The line, or `None` if no page is shown or the input ended.
+    pub fn read_input(&self, editor: &mut LineEditor, keys: &mut dyn Iterator<Item = Key>) -> Option<String> {
+        editor.set_masked(self.current()?.masks_input() && self.modals.is_empty());
+        editor.read_line(keys)
+    }
+
+    /// Runs the pages until the user quits, the last page is closed, or the input ends: draws the
+    /// current page and its modal, reads a line for them, and dispatches the action that results.
+    /// Input that arrives after the session sat idle too long locks it instead of being handled.
+    ///
+    /// # Arguments
+    /// * `editor` - The line editor the input is read through.
+    /// * `keys` - The keys typed.
+    /// * `size` - The terminal's size, for rendering.
+    /// * `clock` - The clock the idle time is measured with.
+    /// * `draw` - Shows a page's rendered lines.
+    /// * `owner` - Handles what navigation leaves over, such as [`PageAction::Refresh`] or an
+    ///   error to show, and returns what to do next, e.g. [`PageAction::Navigate`] to open a
+    ///   page, [`PageAction::Quit`], or [`PageAction::None`] to carry on. On
+    ///   [`PageAction::Lock`] it opens the lock screen, and calls [`Navigator::unlock`] once the
+    ///   user unlocks it.
+    pub fn run(
+        &mut self,
+        editor: &mut LineEditor,
+        keys: &mut dyn Iterator<Item = Key>,
+        size: Size,
+        clock: &dyn Clock,
+        draw: &mut dyn FnMut(Vec<String>),
+        owner: &mut dyn FnMut(&mut Navigator, PageAction) -> PageAction,
+    ) {
+        while !self.is_empty() {
+            if !self.settle_entered(owner) {
+                return;
+            }
+            draw(self.render(size));
+            let Some(input) = self.read_input(editor, keys) else { return };
+            let action = match self.check_idle(clock) {
+                PageAction::None => self.handle_input(&input),
+                locked => locked,
+            };
+            if !self.settle(action, owner) {
+                return;
+            }
+        }
+    }
+
+    /// Handles what the pages entered asked for when they were shown, as [`Navigator::run`] does
+    /// before drawing, e.g. for an owner that reads the input itself.
+    ///
+    /// # Returns
+    /// * `bool` - Whether to keep running, as from [`Navigator::settle`].
+    pub fn settle_entered(&mut self, owner: &mut dyn FnMut(&mut Navigator, PageAction) -> PageAction) -> bool {
+        while let Some(entered) = self.entered.take() {
+            let action = self.dispatch(entered);
+            if !self.settle(action, owner) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Hands what dispatching left over to the owner and dispatches its answer, as
+    /// [`Navigator::run`] does after each input.
+    ///
+    /// # Returns
+    /// * `bool` - Whether to keep running: `false` once the action or the owner's answer is
+    ///   [`PageAction::Quit`].
+    pub fn settle(&mut self, action: PageAction, owner: &mut dyn FnMut(&mut Navigator, PageAction) -> PageAction) -> bool {
+        let action = match action {
+            PageAction::None => return true,
+            PageAction::Quit => return false,
+            other => {
+                let next = owner(self, other);
+                self.dispatch(next)
+            }
+        };
+        !matches!(action, PageAction::Quit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{FixedClock, SystemClock};
+    use crate::db::ClearTextDBState;
+    use crate::pages::lock::LockScreenPage;
+    use crate::pages::{archived::ArchivedItemsPage, edit::EditPage, epic_list::EpicListPage, help::HelpPage, keybindings::KeyMap, story_detail::StoryDetailPage};
+    use crate::service::{epics::create_epic, references::ItemRef, stories::create_story};
+    use crate::users::User;
+    use chrono::{DateTime, Duration};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// A page that records how it was left, into a log shared with the test.
+    struct ExitLog(&'static str, Rc<RefCell<Vec<(&'static str, PageExit)>>>);
+
+    impl Page for ExitLog {
+        fn render(&self, _size: Size) -> Vec<String> {
+            vec![self.0.to_string()]
+        }
+
+        fn handle_input(&mut self, _input: &str) -> PageAction {
+            PageAction::None
+        }
+
+        fn on_exit(&mut self, exit: PageExit) {
+            self.1.borrow_mut().push((self.0, exit));
+        }
+    }
+
+    #[test]
+    fn test_navigator() {
+        let mut navigator = Navigator::new(Box::new(HelpPage::new(&KeyMap::default(), None)));
+        let help = PageAction::Navigate(Box::new(HelpPage::new(&KeyMap::default(), None)));
+        assert!(matches!(navigator.dispatch(help), PageAction::None));
+        assert_eq!(navigator.len(), 2);
+        assert!(matches!(navigator.dispatch(PageAction::ShowError("oops".to_string())), PageAction::ShowError(_)));
+        let mut editor = LineEditor::new();
+        let input = navigator.read_input(&mut editor, &mut [Key::Char('x'), Key::Enter].into_iter()).unwrap();
+        assert_eq!(editor.history(), ["x"]);
+        let action = navigator.current_mut().unwrap().handle_input(&input);
+        assert!(matches!(navigator.dispatch(action), PageAction::None));
+        assert!(navigator.replace(Box::new(HelpPage::new(&KeyMap::default(), None))).is_some());
+        assert!(matches!(navigator.dispatch(PageAction::Pop), PageAction::Quit));
+        assert!(navigator.is_empty() && navigator.current().is_none());
+    }
+
+    #[test]
+    fn test_open_builds_the_page_of_a_route() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let mut navigator = Navigator::new(Box::new(EpicListPage::new(&state)));
+        let epic_uuid = create_epic(&mut state, "Launch", String::new(), &SystemClock).unwrap();
+        let clock: Arc<dyn Clock> = Arc::new(SystemClock);
+        navigator.open(&Route::Epic(epic_uuid.to_string()), &state, &clock).unwrap();
+        assert_eq!(navigator.current().unwrap().render(Size::default())[0], "=== EPIC-1 Launch ===");
+        assert!(matches!(navigator.open(&Route::Story("STORY-1".to_string()), &state, &clock), Err(ServiceError::UnknownReference(_))));
+        assert_eq!(navigator.len(), 2);
+    }
+
+    #[test]
+    fn test_back_and_forward_keep_page_state() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        create_epic(&mut state, "Launch", String::new(), &SystemClock).unwrap();
+        let mut navigator = Navigator::new(Box::new(ArchivedItemsPage::new(&state)));
+        assert!(matches!(navigator.dispatch(PageAction::Forward), PageAction::ShowError(error) if error == "There is no page to go forward to."));
+        navigator.dispatch(PageAction::Navigate(Box::new(EpicListPage::new(&state))));
+        navigator.current_mut().unwrap().handle_input("f zzz");
+        let action = navigator.current_mut().unwrap().handle_input("q");
+        assert!(matches!(navigator.dispatch(action), PageAction::None));
+        assert_eq!(navigator.current().unwrap().render(Size::default())[0], "=== Archived Items ===");
+        let action = navigator.current_mut().unwrap().handle_input("]");
+        assert!(matches!(navigator.dispatch(action), PageAction::None));
+        assert_eq!(navigator.current().unwrap().render(Size::default())[0..2], ["=== Epics ===", "Filter: zzz"]);
+        assert!(!navigator.can_go_forward());
+
+        navigator.back();
+        assert!(navigator.can_go_forward());
+        navigator.push(Box::new(HelpPage::new(&KeyMap::default(), None)));
+        assert!(!navigator.can_go_forward());
+    }
+
+    #[test]
+    fn test_modal_draws_over_the_page_and_returns_its_result() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        create_epic(&mut state, "Launch", String::new(), &SystemClock).unwrap();
+        let mut navigator = Navigator::new(Box::new(EpicListPage::new(&state)));
+        navigator.handle_input("f launch");
+        assert!(matches!(navigator.handle_input("d"), PageAction::None));
+        let lines = navigator.render(Size::default());
+        assert_eq!(lines[..5], ["=== Epics ===", "┌──────────────────────────────┐", "│ Move this epic to the trash? │", "│ [y] yes  [n] no              │", "└──────────────────────────────┘"]);
+        assert!(matches!(navigator.handle_input("maybe"), PageAction::ShowError(error) if error == "Type y or n."));
+        assert!(matches!(navigator.handle_input("n"), PageAction::None));
+        assert!(navigator.modal().is_none());
+        assert_eq!(navigator.render(Size::default())[1], "Filter: launch");
+        navigator.handle_input("d");
+        assert!(matches!(navigator.handle_input("y"), PageAction::Refresh));
+    }
+
+    #[test]
+    fn test_pages_are_told_when_they_are_entered_and_left() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let epic_uuid = create_epic(&mut state, "Launch", String::new(), &SystemClock).unwrap();
+        let story_uuid = create_story(&mut state, epic_uuid, "Login", String::new(), &SystemClock).unwrap();
+        let mut navigator = Navigator::new(Box::new(StoryDetailPage::new(&state, story_uuid).unwrap()));
+        assert!(matches!(navigator.take_entered(), PageAction::None));
+
+        navigator.push(Box::new(EditPage::new(&state, ItemRef::Story(story_uuid)).unwrap()));
+        navigator.handle_input("1");
+        assert!(matches!(navigator.dispatch(PageAction::Pop), PageAction::None));
+        assert!(matches!(navigator.take_entered(), PageAction::Refresh));
+        navigator.forward();
+        assert_eq!(navigator.current().unwrap().render(Size::default())[1], "Currently: Login");
+
+        let closed = navigator.pop().unwrap();
+        assert_eq!(closed.render(Size::default())[1], "Cancelled.");
+        assert!(matches!(navigator.take_entered(), PageAction::Refresh));
+        navigator.replace(Box::new(StoryDetailPage::new(&state, story_uuid).unwrap()));
+        assert!(matches!(navigator.take_entered(), PageAction::None));
+    }
+
+    #[test]
+    fn test_guards_keep_pages_behind_an_unlocked_session() {
+        let user = User::new("alice".to_string(), "password").unwrap();
+        let state = ClearTextDBState::new(user.clone());
+        let mut navigator = Navigator::signed_out(Box::new(LockScreenPage::new(user.clone(), Arc::new(SystemClock))));
+        assert!(matches!(navigator.dispatch(PageAction::Navigate(Box::new(EpicListPage::new(&state)))), PageAction::None));
+        assert!(matches!(navigator.take_entered(), PageAction::ShowError(error) if error == "Log in to open this page."));
+        assert_eq!(navigator.len(), 1);
+
+        let clock = FixedClock::new(DateTime::from_timestamp(1_700_000_000, 0).unwrap());
+        navigator.sign_in(Some(AutoLock::new(&Settings { lock_after_minutes: 5, ..Settings::default() }, &clock)));
+        navigator.push(Box::new(EpicListPage::new(&state)));
+        clock.advance(Duration::minutes(6));
+        assert!(matches!(navigator.check_idle(&clock), PageAction::Lock));
+        assert_eq!(navigator.session(), SessionState::Locked);
+        navigator.push(Box::new(LockScreenPage::new(user.clone(), Arc::new(SystemClock))));
+        navigator.pop();
+        assert!(matches!(navigator.take_entered(), PageAction::Lock));
+        navigator.unlock(&clock);
+        assert!(matches!(navigator.check_idle(&clock), PageAction::None));
+
+        navigator.sign_out();
+        assert_eq!(navigator.len(), 1);
+        assert_eq!(navigator.current().unwrap().render(Size::default())[0], "=== Locked ===");
+    }
+
+    #[test]
+    fn test_closed_pages_are_told_once() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let user = User::new("alice".to_string(), "password").unwrap();
+        let mut navigator = Navigator::signed_out(Box::new(LockScreenPage::new(user, Arc::new(SystemClock))));
+        navigator.sign_in(None);
+        navigator.push(Box::new(ExitLog("below", Rc::clone(&log))));
+        navigator.push(Box::new(ExitLog("top", Rc::clone(&log))));
+        navigator.sign_out();
+        assert_eq!(*log.borrow(), [("below", PageExit::Hidden), ("below", PageExit::Closed), ("top", PageExit::Closed)]);
+
+        log.borrow_mut().clear();
+        navigator.push(Box::new(ExitLog("refused", Rc::clone(&log))));
+        assert_eq!(*log.borrow(), [("refused", PageExit::Closed)]);
+        assert_eq!(navigator.len(), 1);
+    }
+
+    #[test]
+    fn test_run_dispatches_until_quit() {
+        let state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let mut navigator = Navigator::new(Box::new(EpicListPage::new(&state)));
+        let mut drawn = Vec::new();
+        let mut owned = Vec::new();
+        let keys = [Key::Char('z'), Key::Enter, Key::Char('x'), Key::Enter, Key::Char('Q'), Key::Enter, Key::Char('q'), Key::Enter];
+        navigator.run(&mut LineEditor::new(), &mut keys.into_iter(), Size::default(), &SystemClock, &mut |lines| drawn.push(lines[0].clone()), &mut |_, action| {
+            owned.push(format!("{action:?}"));
+            match action {
+                PageAction::ShowError(_) => PageAction::Navigate(Box::new(HelpPage::new(&KeyMap::default(), None))),
+                _ => PageAction::None,
+            }
+        });
+        assert_eq!(drawn, ["=== Epics ===", "=== Help ===", "=== Epics ==="]);
+        assert_eq!(owned, ["ShowError(\"Unknown command \\\"z\\\".\")", "Refresh"]);
+        assert_eq!(navigator.len(), 1);
+    }
+}
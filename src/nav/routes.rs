@@ -0,0 +1,308 @@
+//! # Routes
+//! Every page that can be opened directly has a route string, such as `epic/EPIC-3`,
+//! `story/<uuid>`, or `search?q=login+page`, so a command palette, the [`OPEN_FLAG`] command-line
+//! flag, or a saved view can jump straight to it. Routes may start with [`SCHEME`] to be used as
+//! links.
+//!
+//! Parsing only checks a route's form; [`Route::open`] looks up the items it names.
+
+use crate::clock::Clock;
+use crate::db::ClearTextDBState;
+use crate::pages::archived::ArchivedItemsPage;
+use crate::pages::calendar::CalendarPage;
+use crate::pages::dashboard::DashboardPage;
+use crate::pages::edit::EditPage;
+use crate::pages::epic_detail::EpicDetailPage;
+use crate::pages::epic_list::EpicListPage;
+use crate::pages::epic_wizard::EpicWizardPage;
+use crate::pages::external_editor::ExternalEditor;
+use crate::pages::help::HelpPage;
+use crate::pages::kanban::KanbanPage;
+use crate::pages::keybindings::KeyMap;
+use crate::pages::search::SearchPage;
+use crate::pages::settings::SettingsPage;
+use crate::pages::story_detail::StoryDetailPage;
+use crate::pages::story_wizard::StoryCreationPage;
+use crate::pages::Page;
+use crate::service::ServiceError;
+use crate::service::references::{ItemRef, find_epic, find_story, resolve};
+use std::fmt;
+use std::sync::Arc;
+
+/// The command-line flag that opens a route at start-up, e.g. `--open epic/EPIC-3`.
+pub const OPEN_FLAG: &str = "--open";
+
+/// The prefix that makes a route a link, e.g. `ironyyy://story/STORY-12`.
+pub const SCHEME: &str = "ironyyy://";
+
+/// # Route enum
+/// A page and what it shows.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Route {
+    /// `dashboard`
+    Dashboard,
+    /// `epics`
+    Epics,
+    /// `epic/<id>`: an epic's detail page, by short ID or UUID.
+    Epic(String),
+    /// `story/<id>`: a story's detail page, by short ID or UUID.
+    Story(String),
+    /// `search?q=<query>`: the search page with a query searched for.
+    Search(String),
+    /// `board`
+    Board,
+    /// `archived`
+    Archived,
+    /// `calendar`
+    Calendar,
+    /// `help`
+    Help,
+    /// `settings`
+    Settings,
+    /// `new-epic`: the wizard that creates an epic.
+    NewEpic,
+    /// `new-story` or `new-story/<epic id>`: the page that creates a story, in the epic given if
+    /// there is one.
+    NewStory(Option<String>),
+    /// `edit/<id>`: the page that edits an epic or story, by short ID or UUID.
+    Edit(String),
+}
+
+/// # Route Error enum
+/// Why a route couldn't be parsed.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum RouteError {
+    /// The route is empty.
+    Empty,
+    /// No page has this name.
+    UnknownPage(String),
+    /// The page needs an epic or story ID after a `/`.
+    MissingId(String),
+    /// The page doesn't take this parameter, or the parameter is malformed.
+    BadParameter(String),
+}
+
+impl fmt::Display for RouteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RouteError::Empty => write!(f, "No page given"),
+            RouteError::UnknownPage(page) => write!(f, "There is no page called {page:?}"),
+            RouteError::MissingId(page) if page == "edit" => write!(f, "Give the epic or story to edit, e.g. edit/STORY-1"),
+            RouteError::MissingId(page) => write!(f, "Give the {page} to open, e.g. {page}/{}-1", page.to_uppercase()),
+            RouteError::BadParameter(parameter) => write!(f, "Unexpected parameter {parameter:?}"),
+        }
+    }
+}
+
+impl std::error::Error for RouteError {}
+
+impl Route {
+    /// Parses a route, ignoring [`SCHEME`], surrounding slashes, and the case of the page name.
+    ///
+    /// # Errors
+    /// * `RouteError` - If the route isn't one of the forms listed on [`Route`].
+    ///
+    /// # Examples
+    /// ```rust
+    /// use ironyyy::nav::routes::Route;
+    /// assert_eq!(Route::parse("epic/EPIC-3"), Ok(Route::Epic("EPIC-3".to_string())));
+    /// assert_eq!(Route::parse("ironyyy://search?q=login+page%21"), Ok(Route::Search("login page!".to_string())));
+    /// assert_eq!(Route::parse("/Board/"), Ok(Route::Board));
+    /// assert!(Route::parse("story").is_err());
+    /// ```
+    pub fn parse(route: &str) -> Result<Self, RouteError> {
+        let route = route.trim();
+        let route = route.strip_prefix(SCHEME).unwrap_or(route).trim_matches('/');
+        if route.is_empty() {
+            return Err(RouteError::Empty);
+        }
+        let (path, query) = route.split_once('?').map_or((route, None), |(path, query)| (path, Some(query)));
+        let (page, id) = path.split_once('/').map_or((path, None), |(page, id)| (page, Some(id.trim_matches('/'))));
+        let page = page.to_lowercase();
+        let id = id.filter(|id| !id.is_empty()).map(decode);
+        if let Some(query) = query
+            && page != "search"
+        {
+            return Err(RouteError::BadParameter(query.to_string()));
+        }
+        let no_id = |route: Route| match &id {
+            Some(id) => Err(RouteError::BadParameter(id.clone())),
+            None => Ok(route),
+        };
+        match page.as_str() {
+            "dashboard" => no_id(Route::Dashboard),
+            "epics" => no_id(Route::Epics),
+            "board" => no_id(Route::Board),
+            "archived" => no_id(Route::Archived),
+            "calendar" => no_id(Route::Calendar),
+            "help" => no_id(Route::Help),
+            "settings" => no_id(Route::Settings),
+            "new-epic" => no_id(Route::NewEpic),
+            "new-story" => Ok(Route::NewStory(id)),
+            "edit" => id.map(Route::Edit).ok_or(RouteError::MissingId(page)),
+            "epic" => id.map(Route::Epic).ok_or(RouteError::MissingId(page)),
+            "story" => id.map(Route::Story).ok_or(RouteError::MissingId(page)),
+            "search" => {
+                let mut text = String::new();
+                for parameter in query.into_iter().flat_map(|query| query.split('&')).filter(|parameter| !parameter.is_empty()) {
+                    match parameter.split_once('=') {
+                        Some(("q", value)) => text = decode(value),
+                        _ => return Err(RouteError::BadParameter(parameter.to_string())),
+                    }
+                }
+                no_id(Route::Search(text))
+            }
+            _ => Err(RouteError::UnknownPage(page)),
+        }
+    }
+
+    /// Finds the route given with [`OPEN_FLAG`] among the command-line arguments, as
+    /// `--open <route>` or `--open=<route>`.
+    ///
+    /// # Returns
+    /// * `Option<Result<Route, RouteError>>` - The parsed route, or `None` if the flag isn't
+    ///   given.
+    #[must_use]
+    pub fn from_args(args: impl IntoIterator<Item = impl AsRef<str>>) -> Option<Result<Self, RouteError>> {
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let arg = arg.as_ref();
+            if arg == OPEN_FLAG {
+                return Some(args.next().map_or(Err(RouteError::Empty), |route| Self::parse(route.as_ref())));
+            }
+            if let Some(route) = arg.strip_prefix(OPEN_FLAG).and_then(|rest| rest.strip_prefix('=')) {
+                return Some(Self::parse(route));
+            }
+        }
+        None
+    }
+
+    /// Creates the route's page from the current database state.
+    ///
+    /// # Errors
+    /// * `ServiceError::UnknownReference` - If the epic or story doesn't exist.
+    pub fn open(&self, state: &ClearTextDBState, clock: &Arc<dyn Clock>) -> Result<Box<dyn Page>, ServiceError> {
+        Ok(match self {
+            Route::Dashboard => Box::new(DashboardPage::new(state, Arc::clone(clock))),
+            Route::Epics => Box::new(EpicListPage::new(state)),
+            Route::Epic(id) => Box::new(EpicDetailPage::new(state, find_epic(state, id)?.epic_uuid)?),
+            Route::Story(id) => Box::new(StoryDetailPage::new(state, find_story(state, id)?.story_uuid)?),
+            Route::Search(query) => Box::new(SearchPage::with_query(state, query)),
+            Route::Board => Box::new(KanbanPage::new(state, Arc::clone(clock))),
+            Route::Archived => Box::new(ArchivedItemsPage::new(state)),
+            Route::Calendar => Box::new(CalendarPage::new(state, clock.today())),
+            Route::Help => Box::new(HelpPage::new(&KeyMap::active(), None)),
+            Route::Settings => Box::new(SettingsPage::new(state)),
+            Route::NewEpic => Box::new(EpicWizardPage::new(state).with_external_editor(ExternalEditor::from_env())),
+            Route::NewStory(epic) => {
+                let epic_uuid = epic.as_deref().map(|id| find_epic(state, id).map(|epic| epic.epic_uuid)).transpose()?;
+                Box::new(StoryCreationPage::new(state, epic_uuid)?.with_external_editor(ExternalEditor::from_env()))
+            }
+            Route::Edit(id) => Box::new(EditPage::new(state, resolve(state, id)?)?.with_external_editor(ExternalEditor::from_env())),
+        })
+    }
+}
+
+impl From<ItemRef> for Route {
+    /// The detail page of an epic or story, by UUID.
+    fn from(item: ItemRef) -> Self {
+        match item {
+            ItemRef::Epic(epic_uuid) => Route::Epic(epic_uuid.to_string()),
+            ItemRef::Story(story_uuid) => Route::Story(story_uuid.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Route {
+    /// The route in the form [`Route::parse`] reads, e.g. `search?q=login+page`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Route::Dashboard => write!(f, "dashboard"),
+            Route::Epics => write!(f, "epics"),
+            Route::Epic(id) => write!(f, "epic/{}", encode(id)),
+            Route::Story(id) => write!(f, "story/{}", encode(id)),
+            Route::Search(query) => write!(f, "search?q={}", encode(query)),
+            Route::Board => write!(f, "board"),
+            Route::Archived => write!(f, "archived"),
+            Route::Calendar => write!(f, "calendar"),
+            Route::Help => write!(f, "help"),
+            Route::Settings => write!(f, "settings"),
+            Route::NewEpic => write!(f, "new-epic"),
+            Route::NewStory(None) => write!(f, "new-story"),
+            Route::NewStory(Some(epic)) => write!(f, "new-story/{}", encode(epic)),
+            Route::Edit(id) => write!(f, "edit/{}", encode(id)),
+        }
+    }
+}
+
+/// Decodes a route parameter: `+` is a space and `%XX` a byte. Malformed escapes are kept as
+/// typed.
+fn decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let escaped = (bytes[index] == b'%').then(|| text.get(index + 1..index + 3)).flatten().and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[index], escaped) {
+            (_, Some(byte)) => {
+                decoded.push(byte);
+                index += 3;
+                continue;
+            }
+            (b'+', None) => decoded.push(b' '),
+            (byte, None) => decoded.push(byte),
+        }
+        index += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Encodes a route parameter so [`decode`] gives it back: spaces become `+`, and anything but
+/// letters, digits, and `-_.~` becomes `%XX`.
+fn encode(text: &str) -> String {
+    text.bytes()
+        .map(|byte| match byte {
+            b' ' => "+".to_string(),
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => char::from(byte).to_string(),
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::pages::Size;
+    use crate::service::{epics::create_epic, stories::create_story};
+    use crate::users::User;
+
+    #[test]
+    fn test_routes_parse_round_trip_and_open() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let epic_uuid = create_epic(&mut state, "Launch", String::new(), &SystemClock).unwrap();
+        let story_uuid = create_story(&mut state, epic_uuid, "Login page", String::new(), &SystemClock).unwrap();
+
+        let clock: Arc<dyn Clock> = Arc::new(SystemClock);
+        let story = Route::parse(&format!("{SCHEME}story/{story_uuid}")).unwrap();
+        assert_eq!(story.open(&state, &clock).unwrap().render(Size::default())[0], "=== STORY-1 Login page ===");
+        let search = Route::Search("login & more".to_string());
+        assert_eq!(search.to_string(), "search?q=login+%26+more");
+        assert_eq!(Route::parse(&search.to_string()), Ok(search));
+        let lines = Route::parse("search?q=login").unwrap().open(&state, &clock).unwrap().render(Size::default());
+        assert!(lines.iter().any(|line| line.contains("STORY-1 Login page")));
+        assert!(matches!(Route::parse("epic/EPIC-9").unwrap().open(&state, &clock), Err(ServiceError::UnknownReference(_))));
+        let new_story = Route::parse("new-story/EPIC-1").unwrap();
+        assert_eq!(new_story.to_string(), "new-story/EPIC-1");
+        assert_eq!(new_story.open(&state, &clock).unwrap().render(Size::default())[2], "In EPIC-1 Launch");
+        assert_eq!(Route::parse("new-epic/EPIC-1"), Err(RouteError::BadParameter("EPIC-1".to_string())));
+
+        assert_eq!(Route::parse("edit/story-1").unwrap().open(&state, &clock).unwrap().render(Size::default())[0], "=== Edit STORY-1 Login page ===");
+        assert_eq!(Route::parse("epic/"), Err(RouteError::MissingId("epic".to_string())));
+        assert_eq!(Route::parse("board?q=x"), Err(RouteError::BadParameter("q=x".to_string())));
+        assert_eq!(Route::parse("sprints"), Err(RouteError::UnknownPage("sprints".to_string())));
+        assert_eq!(Route::from_args(["ironyyy", "--open", "calendar"]), Some(Ok(Route::Calendar)));
+        assert_eq!(Route::from_args(["ironyyy", "--open=epics"]), Some(Ok(Route::Epics)));
+        assert_eq!(Route::from_args(["ironyyy", "--tui"]), None);
+    }
+}
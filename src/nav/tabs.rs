@@ -0,0 +1,302 @@
+//! # Tabs
+//! The top-level sections, each shown in a tab with its own [`Navigator`], so switching to
+//! another tab and back finds the pages opened in it as they were left, reloaded in case another
+//! tab changed what they show. Typing `g` and a tab's number switches to it, as does the number
+//! alone on a page that doesn't [read text](crate::pages::Page::reads_text); a tab's first page is
+//! opened the first time it is switched to. The session, with its idle timer, goes with the tab
+//! shown.
+//!
+//! Going back from a tab's first page closes the tab and shows the dashboard; going back from
+//! the dashboard's first page quits.
+
+use super::Navigator;
+use super::routes::Route;
+use crate::clock::Clock;
+use crate::db::ClearTextDBState;
+use crate::pages::i18n::{tr, tr_with};
+use crate::pages::line_editor::{Key, LineEditor};
+use crate::pages::{PageAction, Size};
+use crate::service::ServiceError;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// # Tab enum
+/// A top-level section.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Tab {
+    /// The dashboard.
+    Dashboard,
+    /// The epic list.
+    Epics,
+    /// The kanban board.
+    Board,
+    /// The calendar of due dates.
+    Calendar,
+    /// The search page.
+    Search,
+    /// The user's settings.
+    Settings,
+}
+
+impl Tab {
+    /// Every tab, in the order of their numbers.
+    pub const ALL: [Tab; 6] = [Tab::Dashboard, Tab::Epics, Tab::Board, Tab::Calendar, Tab::Search, Tab::Settings];
+
+    /// The tab's number, from 1, as typed to switch to it.
+    #[must_use]
+    pub fn number(self) -> usize {
+        Self::ALL.iter().position(|tab| *tab == self).unwrap_or_default() + 1
+    }
+
+    /// Looks a tab up by its number.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use ironyyy::nav::tabs::Tab;
+    /// assert_eq!(Tab::from_number(2), Some(Tab::Epics));
+    /// assert_eq!(Tab::from_number(0), None);
+    /// ```
+    #[must_use]
+    pub fn from_number(number: usize) -> Option<Self> {
+        Self::ALL.get(number.checked_sub(1)?).copied()
+    }
+
+    /// The tab's name as shown in the tab bar.
+    #[must_use]
+    pub fn title(self) -> String {
+        tr(match self {
+            Tab::Dashboard => "tab-dashboard",
+            Tab::Epics => "tab-epics",
+            Tab::Board => "tab-board",
+            Tab::Calendar => "tab-calendar",
+            Tab::Search => "tab-search",
+            Tab::Settings => "tab-settings",
+        })
+    }
+
+    /// The route of the tab's first page.
+    #[must_use]
+    pub fn route(self) -> Route {
+        match self {
+            Tab::Dashboard => Route::Dashboard,
+            Tab::Epics => Route::Epics,
+            Tab::Board => Route::Board,
+            Tab::Calendar => Route::Calendar,
+            Tab::Search => Route::Search(String::new()),
+            Tab::Settings => Route::Settings,
+        }
+    }
+}
+
+/// # Tabs struct
+/// The open tabs and which one is shown.
+pub struct Tabs {
+    /// Each opened tab's pages.
+    navigators: BTreeMap<Tab, Navigator>,
+    /// The tab shown.
+    active: Tab,
+}
+
+impl Tabs {
+    /// Opens the dashboard tab.
+    ///
+    /// # Errors
+    /// * `ServiceError` - If the dashboard can't be built.
+    pub fn new(state: &ClearTextDBState, clock: &Arc<dyn Clock>) -> Result<Self, ServiceError> {
+        let mut tabs = Self { navigators: BTreeMap::new(), active: Tab::Dashboard };
+        tabs.switch(Tab::Dashboard, state, clock)?;
+        Ok(tabs)
+    }
+
+    /// The tab shown.
+    #[must_use]
+    pub fn active(&self) -> Tab {
+        self.active
+    }
+
+    /// Shows `tab`, with the pages it had when it was last shown, the current one reloaded from
+    /// `state`, or its first page if it isn't open. The session goes with it.
+    ///
+    /// # Errors
+    /// * `ServiceError` - If the tab's first page can't be built.
+    pub fn switch(&mut self, tab: Tab, state: &ClearTextDBState, clock: &Arc<dyn Clock>) -> Result<(), ServiceError> {
+        if tab == self.active && self.current().is_some_and(|navigator| !navigator.is_empty()) {
+            return Ok(());
+        }
+        let kept = if tab == self.active { None } else { self.navigators.remove(&tab).filter(|navigator| !navigator.is_empty()) };
+        let mut shown = match kept {
+            Some(mut navigator) => {
+                if let Some(page) = navigator.current_mut() {
+                    page.reload(state);
+                }
+                navigator
+            }
+            None => Navigator::new(tab.route().open(state, clock)?),
+        };
+        if let Some(hidden) = self.navigators.get_mut(&self.active) {
+            hidden.hand_over(&mut shown);
+        }
+        self.navigators.insert(tab, shown);
+        self.active = tab;
+        Ok(())
+    }
+
+    /// The pages of the tab shown.
+    #[must_use]
+    pub fn current(&self) -> Option<&Navigator> {
+        self.navigators.get(&self.active)
+    }
+
+    /// The pages of the tab shown, to open or close pages in it.
+    #[must_use]
+    pub fn current_mut(&mut self) -> Option<&mut Navigator> {
+        self.navigators.get_mut(&self.active)
+    }
+
+    /// The tab bar, e.g. `1 Dashboard  <2 Epics>  3 Board ...`, with the tab shown in `<>`.
+    #[must_use]
+    pub fn bar(&self) -> String {
+        Tab::ALL
+            .iter()
+            .map(|tab| {
+                let id = if *tab == self.active { "tab-active" } else { "tab-inactive" };
+                tr_with(id, &[("number", &tab.number()), ("title", &tab.title())])
+            })
+            .collect::<Vec<_>>()
+            .join("  ")
+    }
+
+    /// Renders the tab bar above the tab's current page.
+    #[must_use]
+    pub fn render(&self, size: Size) -> Vec<String> {
+        let page_size = Size { height: size.height.saturating_sub(1), ..size };
+        let mut lines = vec![self.bar()];
+        lines.extend(self.current().map(|navigator| navigator.render(page_size)).unwrap_or_default());
+        lines
+    }
+
+    /// Hands a line of input to the tab shown and switches tabs if asked to.
+    ///
+    /// # Returns
+    /// * `PageAction` - What is left for the owner to handle, as from [`Navigator::dispatch`].
+    pub fn handle_input(&mut self, input: &str, state: &ClearTextDBState, clock: &Arc<dyn Clock>) -> PageAction {
+        let action = self.route_input(input);
+        self.dispatch(action, state, clock)
+    }
+
+    /// Hands a line of input to the tab shown, unless it is a bare tab number typed on a page that
+    /// doesn't read text, which asks to switch to that tab.
+    fn route_input(&mut self, input: &str) -> PageAction {
+        let Some(navigator) = self.current_mut() else { return PageAction::None };
+        let tab = input.trim().parse().ok().and_then(Tab::from_number);
+        match tab {
+            Some(tab) if navigator.modal().is_none() && navigator.current().is_some_and(|page| !page.reads_text()) => PageAction::SwitchTab(tab),
+            _ => navigator.handle_input(input),
+        }
+    }
+
+    /// Runs the tabs until the user quits, the dashboard's last page is closed, or the input ends,
+    /// the way [`Navigator::run`] runs one tab's pages: draws the tab bar over the page shown,
+    /// reads a line for it, and dispatches the action that results.
+    ///
+    /// # Arguments
+    /// * `editor`, `keys`, `size`, `clock`, `draw` - As for [`Navigator::run`].
+    /// * `owner` - Handles what the tab shown leaves over, as for [`Navigator::run`], and also
+    ///   [`PageAction::SwitchTab`] and the [`PageAction::Quit`] of a tab whose last page closed,
+    ///   e.g. with [`Tabs::dispatch`].
+    pub fn run(
+        &mut self,
+        editor: &mut LineEditor,
+        keys: &mut dyn Iterator<Item = Key>,
+        size: Size,
+        clock: &dyn Clock,
+        draw: &mut dyn FnMut(Vec<String>),
+        owner: &mut dyn FnMut(&mut Tabs, PageAction) -> PageAction,
+    ) {
+        while let Some(navigator) = self.current_mut() {
+            let action = match navigator.take_entered() {
+                PageAction::None => {
+                    draw(self.render(size));
+                    let Some(navigator) = self.current_mut() else { return };
+                    let Some(input) = navigator.read_input(editor, keys) else { return };
+                    match navigator.check_idle(clock) {
+                        PageAction::None => self.route_input(&input),
+                        locked => locked,
+                    }
+                }
+                entered => navigator.dispatch(entered),
+            };
+            if !self.settle(action, owner) {
+                return;
+            }
+        }
+    }
+
+    /// Hands what dispatching left over to the owner and dispatches its answer in the tab shown.
+    ///
+    /// # Returns
+    /// * `bool` - Whether to keep running.
+    fn settle(&mut self, action: PageAction, owner: &mut dyn FnMut(&mut Tabs, PageAction) -> PageAction) -> bool {
+        if matches!(action, PageAction::None) {
+            return true;
+        }
+        let next = owner(self, action);
+        let next = match self.current_mut() {
+            Some(navigator) => navigator.dispatch(next),
+            None => next,
+        };
+        !matches!(next, PageAction::Quit)
+    }
+
+    /// Applies a switch of tabs, and closes the tab shown if its last page was closed.
+    ///
+    /// # Returns
+    /// * `PageAction` - What is left for the owner to handle; `Quit` only once the dashboard's
+    ///   last page is closed or the user quits.
+    pub fn dispatch(&mut self, action: PageAction, state: &ClearTextDBState, clock: &Arc<dyn Clock>) -> PageAction {
+        let closed = self.current().is_none_or(Navigator::is_empty);
+        let closing = self.active;
+        let next = match action {
+            PageAction::SwitchTab(tab) => tab,
+            PageAction::Quit if closed && self.active != Tab::Dashboard => Tab::Dashboard,
+            other => return other,
+        };
+        let result = self.switch(next, state, clock);
+        if closed && self.active != closing {
+            self.navigators.remove(&closing);
+        }
+        match result {
+            Ok(()) => PageAction::None,
+            Err(error) => PageAction::ShowError(error.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::service::epics::create_epic;
+    use crate::users::User;
+
+    #[test]
+    fn test_tabs_keep_their_own_pages() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        create_epic(&mut state, "Launch", String::new(), &SystemClock).unwrap();
+        let clock: Arc<dyn Clock> = Arc::new(SystemClock);
+        let mut tabs = Tabs::new(&state, &clock).unwrap();
+        assert!(matches!(tabs.handle_input("g 2", &state, &clock), PageAction::None));
+        tabs.handle_input("f launch", &state, &clock);
+        assert_eq!(tabs.render(Size::default())[..2], ["1 Dashboard  <2 Epics>  3 Board  4 Calendar  5 Search  6 Settings", "=== Epics ==="]);
+
+        tabs.handle_input("g 6", &state, &clock);
+        assert_eq!(tabs.render(Size::default())[1], "=== Settings ===");
+        assert!(matches!(tabs.handle_input("g 9", &state, &clock), PageAction::ShowError(error) if error.starts_with("There is no tab \"9\"")));
+        tabs.handle_input("g 2", &state, &clock);
+        assert_eq!(tabs.render(Size::default())[1..3], ["=== Epics ===", "Filter: launch"]);
+
+        assert!(matches!(tabs.handle_input("q", &state, &clock), PageAction::None));
+        assert_eq!(tabs.active(), Tab::Dashboard);
+        assert!(matches!(tabs.handle_input("q", &state, &clock), PageAction::Quit));
+    }
+}
@@ -1,22 +1,292 @@
 //! # Pages Module
 //! This module contains different pages/screens of the CLI application.
 
+pub mod accessible;
+pub mod archived;
+pub mod burndown;
+pub mod calendar;
+pub mod dashboard;
+pub mod edit;
+pub mod effort;
+pub mod epic_detail;
+pub mod epic_list;
+pub mod epic_wizard;
+pub mod external_editor;
+pub mod goals;
+pub mod help;
+pub mod history;
+pub mod i18n;
+pub mod inbox;
+pub mod kanban;
+pub mod keybindings;
+pub mod line_editor;
+pub mod list;
+pub mod lock;
+pub mod login;
+pub mod markdown;
+pub mod modal;
+pub mod register;
+pub mod picker;
+pub mod progress_bar;
+pub mod reminders;
+pub mod search;
+pub mod settings;
+pub mod story_detail;
+pub mod story_wizard;
+pub mod table;
+pub mod text_editor;
+pub mod theme;
+pub mod toast;
+pub mod totp_enrollment;
+pub mod totp_rotation;
+
+use self::help::HelpPage;
+use self::i18n::tr_with;
+use self::keybindings::{KeyAction, KeyContext, KeyMap};
+use self::modal::Modal;
+use self::settings::{PasswordChange, SettingChange, UsernameChange};
+use crate::db::ClearTextDBState;
+use crate::models::settings::{ListName, ListPreferences};
+use crate::nav::routes::Route;
+use crate::nav::tabs::Tab;
+use crate::service::undo::Command;
+use std::any::Any;
+use std::fmt;
 use uuid::Uuid;
 
 /// # Page trait
 /// Represents a page/screen in the CLI application.
 pub trait Page {
-    fn render(&self) -> Vec<String>;
-    fn handle_input(&self, input: &str);
+    /// Renders the page as a list of lines to print, laid out to fit `size`.
+    fn render(&self, size: Size) -> Vec<String>;
+    /// Handles a line of user input, updating the page's own state (selection, cursor, page
+    /// number, ...) as needed.
+    ///
+    /// # Returns
+    /// * `PageAction` - What the navigator or main loop should do next.
+    fn handle_input(&mut self, input: &str) -> PageAction;
+    /// Whether the next input is a secret, such as a password, that the prompt should hide and
+    /// leave out of its history.
+    fn masks_input(&self) -> bool {
+        false
+    }
+    /// Whether the page reads what is typed as text or numbers of its own, rather than only as
+    /// keys. On a page that doesn't, a bare [tab](crate::nav::tabs) number switches to that tab.
+    fn reads_text(&self) -> bool {
+        true
+    }
+    /// Renders the page in reading order for the [accessible mode](accessible). Pages that lay
+    /// content out side by side override this; the rest render as usual.
+    fn render_linear(&self, size: Size) -> Vec<String> {
+        self.render(size)
+    }
+    /// Takes the result of a [modal](modal) the page opened, once it is closed; `result` holds
+    /// the modal's result type.
+    ///
+    /// # Returns
+    /// * `PageAction` - What the navigator or main loop should do next.
+    fn modal_closed(&mut self, _result: Box<dyn Any>) -> PageAction {
+        PageAction::None
+    }
+    /// Called by the [navigator](crate::nav::Navigator) when the page becomes the one shown: when
+    /// it is opened, and when it is shown again after the page over it closes or the user goes
+    /// forward to it. A page showing data that may have changed meanwhile can ask to be rebuilt.
+    ///
+    /// # Returns
+    /// * `PageAction` - What the navigator or main loop should do next.
+    fn on_enter(&mut self) -> PageAction {
+        PageAction::None
+    }
+    /// Called by the [navigator](crate::nav::Navigator) when the page stops being the one shown,
+    /// before the next page is entered. A page with unsaved edits keeps them while it is
+    /// [hidden](PageExit::Hidden) and discards them when it is [closed](PageExit::Closed).
+    fn on_exit(&mut self, _exit: PageExit) {}
+    /// Whether the page shows the user's data and so may only be shown while the session is
+    /// unlocked. Only the pages that open or unlock a session, such as the login and lock
+    /// screens, return `false`.
+    fn needs_session(&self) -> bool {
+        true
+    }
+    /// Takes the oldest change the user asked for that the owner runs as a [`Command`], so it can
+    /// be [undone](crate::service::undo::UndoStack); other results stay for the page's own
+    /// `take_*` methods.
+    fn take_command(&mut self) -> Option<Command> {
+        None
+    }
+    /// Takes the oldest change to the user's own account or preferences that the owner saves,
+    /// such as a list's new sort order; unlike a [`Command`], undo leaves it alone.
+    fn take_account_change(&mut self) -> Option<AccountChange> {
+        None
+    }
+    /// Called by the owner after the database changed under the page, e.g. by an undo or a
+    /// command it ran, so the page shows `state` again.
+    fn reload(&mut self, _state: &ClearTextDBState) {}
+}
+
+/// # Account Change enum
+/// A change to the user's own account or preferences, asked for by a page with
+/// [`Page::take_account_change`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AccountChange {
+    /// How a list is sorted and filtered, saved with
+    /// [`set_list_preferences`](crate::service::settings::set_list_preferences).
+    ListPreferences(ListName, ListPreferences),
+    /// An interface preference, saved with [`SettingChange::apply`].
+    Setting(SettingChange),
+    /// A new password, which rewraps the database key with [`PasswordChange::apply`].
+    Password(PasswordChange),
+    /// A new username, which renames the account with [`UsernameChange::apply`].
+    Username(UsernameChange),
+}
+
+/// # Page Exit enum
+/// Why a page stopped being the one shown.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum PageExit {
+    /// Another page was opened over it, or the user went back from it; it may be shown again as
+    /// it was.
+    Hidden,
+    /// It was closed or replaced for good.
+    Closed,
+}
+
+/// # Size struct
+/// The dimensions of the terminal a page is rendered into, in characters.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Size {
+    /// The number of columns.
+    pub width: u16,
+    /// The number of rows.
+    pub height: u16,
+}
+
+impl Default for Size {
+    /// The classic 80 by 24 terminal.
+    fn default() -> Self {
+        Self { width: 80, height: 24 }
+    }
 }
 
-// TODO: Implement specific pages like LoginPage, DashboardPage, EpicCreationPage, StoryCreationPage, etc.
+impl Size {
+    /// Creates a size of `width` columns by `height` rows.
+    #[must_use]
+    pub fn new(width: u16, height: u16) -> Self {
+        Self { width, height }
+    }
+
+    /// Reads the terminal size from the `COLUMNS` and `LINES` environment variables, falling back
+    /// to the default for whichever is missing or invalid.
+    #[must_use]
+    pub fn from_env() -> Self {
+        let read = |name: &str| std::env::var(name).ok().and_then(|value| value.trim().parse().ok()).filter(|value| *value > 0);
+        let fallback = Self::default();
+        Self { width: read("COLUMNS").unwrap_or(fallback.width), height: read("LINES").unwrap_or(fallback.height) }
+    }
+
+    /// The width as a `usize`, for laying out text.
+    #[must_use]
+    pub fn columns(self) -> usize {
+        usize::from(self.width)
+    }
+}
+
+/// # Page Action enum
+/// What a page asks of the navigator or main loop after handling input.
+pub enum PageAction {
+    /// Open another page on top of this one.
+    Navigate(Box<dyn Page>),
+    /// Open the page of a [route](crate::nav::routes::Route) on top of this one; the owner, which
+    /// holds the database, builds it from the current state with
+    /// [`Navigator::open`](crate::nav::Navigator::open).
+    Open(Route),
+    /// Close this page and go back to the one below it.
+    Pop,
+    /// Go forward again to the page last closed with [`PageAction::Pop`].
+    Forward,
+    /// Open a [modal](modal) over this page; its result comes back through
+    /// [`Page::modal_closed`].
+    OpenModal(Box<dyn Modal>),
+    /// Leave the application.
+    Quit,
+    /// The page has a result for its owner to collect with [`Page::take_command`] or
+    /// [`Page::take_account_change`]; the owner applies it, saves if needed, and rebuilds the page
+    /// from the updated state.
+    Refresh,
+    /// Show an error message; the page stays as it was.
+    ShowError(String),
+    /// Show a short message such as `Saved` as a [toast](toast::Toasts); the page stays as it
+    /// was.
+    Notify(String),
+    /// Open the log of recent toasts, which the main loop keeps.
+    ShowNotifications,
+    /// Lock the session; the owner, which holds the user, opens the
+    /// [lock screen](lock::LockScreenPage).
+    Lock,
+    /// Undo the most recent change; the owner, which holds the
+    /// [undo stack](crate::service::undo::UndoStack), restores the data and reloads the page.
+    Undo,
+    /// Redo the most recently undone change, as for [`PageAction::Undo`].
+    Redo,
+    /// Switch to another of the [tabs](crate::nav::tabs::Tabs), each of which keeps its own pages.
+    SwitchTab(Tab),
+    /// Nothing to do beyond rendering the page again.
+    None,
+}
+
+impl fmt::Debug for PageAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PageAction::Navigate(page) => f.debug_tuple("Navigate").field(&page.render(Size::default()).first()).finish(),
+            PageAction::Open(route) => f.debug_tuple("Open").field(route).finish(),
+            PageAction::Pop => write!(f, "Pop"),
+            PageAction::Forward => write!(f, "Forward"),
+            PageAction::OpenModal(modal) => f.debug_tuple("OpenModal").field(&modal.render(Size::default()).first()).finish(),
+            PageAction::Quit => write!(f, "Quit"),
+            PageAction::Refresh => write!(f, "Refresh"),
+            PageAction::ShowError(error) => f.debug_tuple("ShowError").field(error).finish(),
+            PageAction::Notify(message) => f.debug_tuple("Notify").field(message).finish(),
+            PageAction::ShowNotifications => write!(f, "ShowNotifications"),
+            PageAction::Lock => write!(f, "Lock"),
+            PageAction::Undo => write!(f, "Undo"),
+            PageAction::Redo => write!(f, "Redo"),
+            PageAction::SwitchTab(tab) => f.debug_tuple("SwitchTab").field(tab).finish(),
+            PageAction::None => write!(f, "None"),
+        }
+    }
+}
+
+/// Handles the global keybindings: help opens the [`HelpPage`] for `context`, back closes the
+/// page and forward reopens it, quit leaves the application, notifications asks for the toast log, lock asks for the
+/// lock screen, undo and redo ask for the last change to be undone or redone, and switching tabs asks for the tab numbered by the argument.
+///
+/// # Returns
+/// * `Option<PageAction>` - The action, or `None` if `input` isn't a global key (or is bound to
+///   something else in `context`).
+#[must_use]
+pub fn global_action(keys: &KeyMap, context: KeyContext, input: &str) -> Option<PageAction> {
+    let (action, argument) = keys.lookup(context, input)?;
+    match action {
+        KeyAction::Help => Some(PageAction::Navigate(Box::new(HelpPage::new(keys, Some(context))))),
+        KeyAction::Back => Some(PageAction::Pop),
+        KeyAction::Forward => Some(PageAction::Forward),
+        KeyAction::Quit => Some(PageAction::Quit),
+        KeyAction::Notifications => Some(PageAction::ShowNotifications),
+        KeyAction::Lock => Some(PageAction::Lock),
+        KeyAction::Undo => Some(PageAction::Undo),
+        KeyAction::Redo => Some(PageAction::Redo),
+        KeyAction::SwitchTab => Some(argument.parse().ok().and_then(Tab::from_number).map_or_else(
+            || PageAction::ShowError(tr_with("nav-no-such-tab", &[("number", &argument)])),
+            PageAction::SwitchTab,
+        )),
+        _ => None,
+    }
+}
 
-/// # DetectedUsers type alias
+/// # `DetectedUsers` type alias
 /// A list of detected users represented by their UUID and username.
-type DetectedUsers = Vec<(Uuid, String)>;
+pub type DetectedUsers = Vec<(Uuid, String)>;
 
-/// # LoginOrRegisterPage struct
+/// # `LoginOrRegisterPage` struct
 /// Represents the page for the user to select whether to log into an existing account or register a new one.
-pub struct LoginOrRegisterPage(DetectedUsers);
+pub struct LoginOrRegisterPage(pub DetectedUsers);
 
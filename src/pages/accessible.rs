@@ -0,0 +1,161 @@
+//! # Accessible Output
+//! A plain mode for screen readers and braille displays. Instead of being themed, every page's
+//! lines are passed through [`linearize`], which spells out what the themed output shows by
+//! position, color, or drawing: headings and the selected row get a label, key hints become a
+//! list, and progress bars, chart bars, and box-drawing characters are dropped in favor of the
+//! numbers next to them. Pages laid out in two dimensions, such as the kanban board, list their
+//! content in reading order through [`Page::render_linear`].
+//!
+//! The mode is turned on in the settings, or by setting the [`ACCESSIBLE_ENV`] environment
+//! variable to anything but an empty string.
+
+use super::i18n::{tr, tr_with};
+use super::theme::{Role, strip_styles};
+use super::{Page, Size};
+use crate::models::settings::Settings;
+
+/// The environment variable that turns the accessible mode on whatever the settings say.
+pub const ACCESSIBLE_ENV: &str = "IRONYYY_ACCESSIBLE";
+
+/// Whether to use the accessible mode: either the setting is on or `forced` is set.
+///
+/// # Examples
+/// ```rust
+/// use ironyyy::models::settings::Settings;
+/// use ironyyy::pages::accessible::resolve;
+/// assert!(resolve(&Settings { accessible: true, ..Settings::default() }, false));
+/// assert!(resolve(&Settings::default(), true));
+/// assert!(!resolve(&Settings::default(), false));
+/// ```
+#[must_use]
+pub fn resolve(settings: &Settings, forced: bool) -> bool {
+    settings.accessible || forced
+}
+
+/// Whether to use the accessible mode, honoring the [`ACCESSIBLE_ENV`] environment variable.
+#[must_use]
+pub fn from_settings(settings: &Settings) -> bool {
+    resolve(settings, std::env::var_os(ACCESSIBLE_ENV).is_some_and(|value| !value.is_empty()))
+}
+
+/// Renders a page in the accessible mode.
+#[must_use]
+pub fn render(page: &dyn Page, size: Size) -> Vec<String> {
+    linearize(page.render_linear(size))
+}
+
+/// Turns a page's rendered lines into plain labeled text.
+///
+/// # Examples
+/// ```rust
+/// use ironyyy::pages::accessible::linearize;
+/// let lines = ["=== Epics ===", ">  1  EPIC-1 Launch  [#####-----] 1/2 (50%)", "[o] open  [f <text>] filter"].map(str::to_string);
+/// assert_eq!(linearize(lines.to_vec()), ["Page: Epics", "Selected: 1  EPIC-1 Launch  1/2 (50%)", "Keys: o: open; f <text>: filter"]);
+/// ```
+#[must_use]
+pub fn linearize(lines: Vec<String>) -> Vec<String> {
+    lines.into_iter().filter_map(|line| linearize_line(&strip_styles(&line))).collect()
+}
+
+/// Linearizes one line, or drops it if it was only drawing.
+fn linearize_line(line: &str) -> Option<String> {
+    if !line.trim().is_empty() && line.chars().all(|char| is_box_drawing(char) || char.is_whitespace() || (line.trim_start().starts_with('+') && "+-".contains(char))) {
+        return None;
+    }
+    let line = if line.chars().any(is_box_drawing) { line.split(is_box_drawing).map(str::trim).filter(|cell| !cell.is_empty()).collect::<Vec<_>>().join("  ") } else { line.to_string() };
+    let line = drop_bars(&line);
+    let trimmed = line.trim();
+    Some(match Role::of(&line) {
+        Some(Role::Heading) => tr_with("a11y-page", &[("title", &strip_marker(trimmed, "==="))]),
+        Some(Role::Section) => tr_with("a11y-section", &[("title", &strip_marker(trimmed, "---"))]),
+        Some(Role::Selected) => tr_with("a11y-selected", &[("row", &line[1..].trim_start())]),
+        Some(Role::Hint) => tr_with("a11y-keys", &[("keys", &keys(&line))]),
+        Some(Role::Error) => line,
+        None if trimmed == ">" => tr("a11y-cursor"),
+        None => {
+            if let Some(message) = line.strip_prefix("• ") {
+                tr_with("a11y-notice", &[("message", &message)])
+            } else if let Some(item) = trimmed.strip_prefix("[x] ") {
+                tr_with("a11y-done", &[("item", &item)])
+            } else if let Some(item) = trimmed.strip_prefix("[ ] ") {
+                tr_with("a11y-to-do", &[("item", &item)])
+            } else if let Some((number, text)) = trimmed.split_once(" | ").filter(|(number, _)| number.parse::<usize>().is_ok()) {
+                tr_with("a11y-line", &[("number", &number), ("text", &text)])
+            } else if let Some(text) = trimmed.strip_prefix("| ") {
+                text.to_string()
+            } else {
+                line
+            }
+        }
+    })
+}
+
+/// Whether `char` is a box-drawing character, e.g. `│` or `┼`.
+fn is_box_drawing(char: char) -> bool {
+    ('\u{2500}'..='\u{257f}').contains(&char)
+}
+
+/// Removes a heading's markers, e.g. `=== Epics ===` to `Epics`.
+fn strip_marker<'a>(line: &'a str, marker: &str) -> &'a str {
+    line.trim_start_matches(marker).trim_end_matches(marker).trim()
+}
+
+/// Removes progress bars (`[####------] `) and chart bars (words of two or more `#`), keeping
+/// the numbers that go with them.
+fn drop_bars(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(start) = rest.find('[') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        match after.find(']') {
+            Some(end) if end > 0 && after[..end].chars().all(|char| char == '#' || char == '-') => {
+                rest = after[end + 1..].strip_prefix(' ').unwrap_or(&after[end + 1..]);
+            }
+            _ => {
+                out.push('[');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out.split(' ').filter(|word| word.len() < 2 || !word.chars().all(|char| char == '#')).collect::<Vec<_>>().join(" ")
+}
+
+/// Reads a hint line such as `[e] edit  [t <status>] change status` into `e: edit; t <status>:
+/// change status`.
+fn keys(line: &str) -> String {
+    line.split('[')
+        .filter_map(|part| part.split_once(']'))
+        .map(|(key, action)| tr_with("a11y-key", &[("key", &key.trim()), ("action", &action.trim())]))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::db::ClearTextDBState;
+    use crate::pages::kanban::KanbanPage;
+    use crate::pages::table::{Align, Border, Table};
+    use crate::service::{epics::create_epic, stories::create_story};
+    use crate::users::User;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_linearize_labels_pages() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let launch = create_epic(&mut state, "Launch", String::new(), &SystemClock).unwrap();
+        create_story(&mut state, launch, "Login", String::new(), &SystemClock).unwrap();
+        let board = render(&KanbanPage::new(&state, Arc::new(SystemClock)), Size::default());
+        assert_eq!(board[..5], ["Page: Board", "Section: Open (1)", "EPIC-1 Launch", "Selected: STORY-1 Login", "Section: In Progress (0)"]);
+        assert!(board.last().unwrap().starts_with("Keys: h: previous column; l: next column;"));
+
+        let mut table = Table::new().column("Day", Align::Left).column("Left", Align::Right).border(Border::Unicode);
+        table.add_row(["2025-03-01", "7"]);
+        assert_eq!(linearize(table.render(40)), ["Day  Left", "2025-03-01  7"]);
+        let lines = ["2025-03-01  ####### 7", "• Saved", "  [x] Write docs", "  1 | Hello", "    >"].map(str::to_string);
+        assert_eq!(linearize(lines.to_vec()), ["2025-03-01  7", "Notice: Saved", "Done: Write docs", "Line 1: Hello", "Cursor"]);
+    }
+}
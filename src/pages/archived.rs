@@ -0,0 +1,134 @@
+//! # Archived Items Page
+//! Lists the epics and stories that have been archived, a page at a time, with a filter and sort
+//! orders to find one among many. Like the epic list, the page starts from the sort order and
+//! filter last used and hands changes to them to the caller with
+//! [`Page::take_account_change`].
+
+use super::i18n::tr;
+use super::keybindings::{KeyAction, KeyContext, KeyMap};
+use super::list::{ListView, RowFacets};
+use super::table::Align;
+use super::{AccountChange, Page, PageAction, Size, global_action};
+use crate::db::ClearTextDBState;
+use crate::models::settings::{ListName, ListPreferences};
+use crate::service::archive::{archived_epics, archived_stories};
+use crate::service::pagination::DEFAULT_PAGE_SIZE;
+use crate::service::references::ItemRef;
+
+/// # `ArchivedItemsPage` struct
+/// A read-only view of the archived epics and stories.
+pub struct ArchivedItemsPage {
+    /// The archived epics, then stories, each with its kind and its short ID and title.
+    list: ListView<ItemRef>,
+    /// The sort order and filter as last saved.
+    saved: ListPreferences,
+    /// The keybindings.
+    keys: KeyMap,
+}
+
+impl ArchivedItemsPage {
+    /// Creates the page from the current database state.
+    #[must_use]
+    pub fn new(state: &ClearTextDBState) -> Self {
+        let epics = archived_epics(state).into_iter().map(|epic| {
+            let row = (ItemRef::Epic(epic.epic_uuid), vec![tr("kind-epic"), format!("{} {}", epic.key(), epic.title)]);
+            (row, RowFacets::of(state, epic, &epic.labels, &epic.custom_fields))
+        });
+        let stories = archived_stories(state).into_iter().map(|story| {
+            let row = (ItemRef::Story(story.story_uuid), vec![tr("kind-story"), format!("{} {}", story.key(), story.title)]);
+            (row, RowFacets::of(state, story, &story.labels, &story.custom_fields))
+        });
+        let (rows, facets) = epics.chain(stories).unzip();
+        let saved = state.settings.list(ListName::Archived);
+        Self {
+            list: ListView::new(rows, DEFAULT_PAGE_SIZE).column(&tr("column-kind"), Align::Left).column(&tr("column-item"), Align::Left).facets(facets).with_preferences(&saved),
+            saved,
+            keys: KeyMap::active(),
+        }
+    }
+
+    /// The selected item, if anything has been archived.
+    #[must_use]
+    pub fn selected(&self) -> Option<ItemRef> {
+        self.list.selected().copied()
+    }
+
+    /// Asks the caller to save the sort order and filter if they changed since they were last
+    /// saved.
+    fn preferences_changed(&self) -> PageAction {
+        if self.list.preferences() == self.saved { PageAction::None } else { PageAction::Refresh }
+    }
+}
+
+impl Page for ArchivedItemsPage {
+    fn reads_text(&self) -> bool {
+        false
+    }
+
+    fn render(&self, size: Size) -> Vec<String> {
+        let mut lines = vec![tr("archived-title")];
+        if self.list.has_no_rows() {
+            lines.push(tr("archived-empty"));
+            return lines;
+        }
+        lines.extend(self.list.bar());
+        if self.list.is_empty() {
+            lines.push(tr("archived-no-match"));
+        } else {
+            lines.extend(self.list.render(size.columns()));
+        }
+        lines.push(self.list.page_footer());
+        lines.push(self.keys.hints(KeyContext::Archived));
+        lines
+    }
+
+    fn handle_input(&mut self, input: &str) -> PageAction {
+        match self.keys.lookup(KeyContext::Archived, input) {
+            Some((KeyAction::Filter, filter)) => {
+                self.list.set_filter(filter);
+                return self.preferences_changed();
+            }
+            Some((KeyAction::Sort, sort)) => return self.list.sort_by_name(sort).map_or_else(PageAction::ShowError, |()| self.preferences_changed()),
+            Some((action, _)) if self.list.navigate(action) => (),
+            _ => return global_action(&self.keys, KeyContext::Archived, input).unwrap_or(PageAction::None),
+        }
+        PageAction::None
+    }
+
+    fn take_account_change(&mut self) -> Option<AccountChange> {
+        let preferences = self.list.preferences();
+        (preferences != self.saved).then(|| {
+            self.saved = preferences.clone();
+            AccountChange::ListPreferences(ListName::Archived, preferences)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::service::{archive::archive_story, epics::create_epic, stories::create_story};
+    use crate::users::User;
+
+    #[test]
+    fn test_render_archived_items() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let epic_uuid = create_epic(&mut state, "Epic", String::new(), &SystemClock).unwrap();
+        let story_uuid = create_story(&mut state, epic_uuid, "Old story", String::new(), &SystemClock).unwrap();
+        assert_eq!(ArchivedItemsPage::new(&state).render(Size::default())[1], "Nothing has been archived.");
+
+        archive_story(&mut state, story_uuid, &SystemClock).unwrap();
+        let mut page = ArchivedItemsPage::new(&state);
+        assert_eq!(
+            page.render(Size::default()),
+            vec!["=== Archived Items ===", "   #  Kind   Item", ">  1  Story  STORY-1 Old story", "Page 1 of 1", "[j] move down  [k] move up  [>] next page  [<] previous page  [f <text>] filter  [s <status|priority|due>] sort"]
+        );
+        assert_eq!(page.selected(), Some(ItemRef::Story(story_uuid)));
+        assert!(matches!(page.handle_input("f epic"), PageAction::Refresh));
+        assert!(matches!(page.take_account_change(), Some(AccountChange::ListPreferences(ListName::Archived, preferences)) if preferences.filter == "epic"));
+        assert_eq!(page.take_account_change(), None);
+        assert_eq!(page.render(Size::default())[1..3], ["Filter: epic", "Nothing archived matches the filter."]);
+        assert!(matches!(page.handle_input("q"), PageAction::Pop));
+    }
+}
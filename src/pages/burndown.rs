@@ -0,0 +1,146 @@
+//! # Burndown Page
+//! Charts a sprint's burndown and the velocity of recent sprints as text bar charts.
+
+use super::i18n::{tr, tr_with};
+use super::keybindings::{KeyContext, KeyMap};
+use super::{Page, PageAction, Size, global_action};
+use crate::clock::Clock;
+use crate::db::ClearTextDBState;
+use crate::models::sprints::{BurndownPoint, SprintVelocity};
+use crate::service::{
+    ServiceError,
+    burndown::{burndown, rolling_velocity, velocity},
+    sprints::get_sprint,
+};
+use uuid::Uuid;
+
+/// The width of the longest bar on a wide terminal, in characters.
+const MAX_CHART_WIDTH: usize = 40;
+/// The width of the longest bar however narrow the terminal is.
+const MIN_CHART_WIDTH: usize = 10;
+/// How many recent sprints the average velocity covers.
+const VELOCITY_WINDOW: usize = 3;
+
+/// # `BurndownPage` struct
+/// A read-only report of one sprint's burndown and the velocity of closed sprints.
+pub struct BurndownPage {
+    /// The sprint's name.
+    sprint_name: String,
+    /// The sprint's burndown, one point per day so far.
+    burndown: Vec<BurndownPoint>,
+    /// The velocity of each closed sprint, oldest first.
+    velocity: Vec<SprintVelocity>,
+}
+
+impl BurndownPage {
+    /// Creates the report for a sprint from the current database state.
+    ///
+    /// # Errors
+    /// * `ServiceError::SprintNotFound` - If the sprint does not exist.
+    pub fn new(state: &ClearTextDBState, sprint_uuid: Uuid, clock: &dyn Clock) -> Result<Self, ServiceError> {
+        Ok(Self {
+            sprint_name: get_sprint(state, sprint_uuid)?.name.clone(),
+            burndown: burndown(state, sprint_uuid, clock)?,
+            velocity: velocity(state),
+        })
+    }
+}
+
+impl Page for BurndownPage {
+    fn reads_text(&self) -> bool {
+        false
+    }
+
+    fn render(&self, size: Size) -> Vec<String> {
+        let mut lines = vec![tr_with("burndown-title", &[("sprint", &self.sprint_name)])];
+        if self.burndown.is_empty() {
+            lines.push(tr("burndown-not-started"));
+        }
+        let max = self.burndown.iter().map(|point| point.remaining_points).max().unwrap_or_default();
+        let width = chart_width(size, "YYYY-MM-DD".len(), max);
+        lines.extend(self.burndown.iter().map(|point| format!("{}  {} {}", point.date, bar(point.remaining_points, max, width), point.remaining_points)));
+
+        lines.push(tr("velocity-title"));
+        if self.velocity.is_empty() {
+            lines.push(tr("velocity-empty"));
+        }
+        let max = self.velocity.iter().map(|velocity| velocity.completed_points).max().unwrap_or_default();
+        let name_width = self.velocity.iter().map(|velocity| velocity.name.chars().count()).max().unwrap_or_default();
+        let width = chart_width(size, name_width, max);
+        lines.extend(
+            self.velocity
+                .iter()
+                .map(|velocity| format!("{:name_width$}  {} {}", velocity.name, bar(velocity.completed_points, max, width), velocity.completed_points)),
+        );
+        if let Some(average) = rolling_velocity(&self.velocity, VELOCITY_WINDOW) {
+            lines.push(tr_with("velocity-average", &[("sprints", &self.velocity.len().min(VELOCITY_WINDOW)), ("average", &average)]));
+        }
+        lines
+    }
+
+    fn handle_input(&mut self, input: &str) -> PageAction {
+        global_action(&KeyMap::active(), KeyContext::Global, input).unwrap_or(PageAction::None)
+    }
+}
+
+/// The width of the longest bar that fits on a line after a `label_width` label and before the
+/// value `max`.
+fn chart_width(size: Size, label_width: usize, max: u32) -> usize {
+    let used = label_width + "  ".len() + " ".len() + max.to_string().len();
+    size.columns().saturating_sub(used).clamp(MIN_CHART_WIDTH, MAX_CHART_WIDTH)
+}
+
+/// Draws `value` as a bar of `#`, scaled so that `max` fills `width` characters.
+fn bar(value: u32, max: u32, width: usize) -> String {
+    let [value, max] = [value, max].map(|points| usize::try_from(points).unwrap_or_default());
+    "#".repeat(value.saturating_mul(width).checked_div(max).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+    use crate::models::{Status, estimates::Estimate};
+    use crate::service::{
+        epics::create_epic,
+        estimates::set_story_estimate,
+        sprints::{assign_story_to_sprint, close_sprint, create_sprint},
+        stories::{create_story, set_story_status},
+    };
+    use crate::users::User;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_render_burndown() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let day = |day| NaiveDate::from_ymd_opt(2024, 3, day).unwrap();
+        let clock = |day_of_month| FixedClock::new(day(day_of_month).and_hms_opt(12, 0, 0).unwrap().and_utc());
+        let epic_uuid = create_epic(&mut state, "Epic", String::new(), &clock(1)).unwrap();
+        let sprint_uuid = create_sprint(&mut state, "Sprint 1".to_string(), day(1), day(2)).unwrap();
+        for points in [2, 2] {
+            let story_uuid = create_story(&mut state, epic_uuid, "Story", String::new(), &clock(1)).unwrap();
+            set_story_estimate(&mut state, story_uuid, Some(Estimate::Points(points)), &clock(1)).unwrap();
+            assign_story_to_sprint(&mut state, story_uuid, sprint_uuid).unwrap();
+        }
+        let first = state.stories[0].story_uuid;
+        set_story_status(&mut state, first, Status::Closed, &clock(2)).unwrap();
+
+        let (full, half) = ("#".repeat(40), "#".repeat(20));
+        assert_eq!(
+            BurndownPage::new(&state, sprint_uuid, &clock(5)).unwrap().render(Size::default()),
+            vec![
+                "=== Burndown: Sprint 1 ===".to_string(),
+                format!("2024-03-01  {full} 4"),
+                format!("2024-03-02  {half} 2"),
+                "=== Velocity ===".to_string(),
+                "No closed sprints yet.".to_string(),
+            ]
+        );
+        let narrow = BurndownPage::new(&state, sprint_uuid, &clock(5)).unwrap().render(Size::new(30, 24));
+        assert_eq!(narrow[1..3], [format!("2024-03-01  {} 4", "#".repeat(16)), format!("2024-03-02  {} 2", "#".repeat(8))]);
+        close_sprint(&mut state, sprint_uuid, None).unwrap();
+        let lines = BurndownPage::new(&state, sprint_uuid, &clock(5)).unwrap().render(Size::default());
+        assert_eq!(lines[lines.len() - 2..], [format!("Sprint 1  {full} 2"), "Average velocity (last 1): 2 pt".to_string()]);
+        assert!(BurndownPage::new(&state, Uuid::new_v4(), &clock(5)).is_err());
+    }
+}
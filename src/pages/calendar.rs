@@ -0,0 +1,224 @@
+//! # Calendar Page
+//! Plots the epics and stories by due date on a month grid, or on a week with a line per day.
+//! Epics are shown as milestones, marked `◆`. Everything due in the period shown is numbered:
+//! the month grid shows the numbers in each day's cell, listed below the grid, and the week shows
+//! them next to the items. Typing a number opens that item's detail page by its [route](Route).
+
+use super::i18n::{tr, tr_with};
+use super::keybindings::{KeyAction, KeyContext, KeyMap};
+use super::{Page, PageAction, Size, global_action};
+use crate::db::ClearTextDBState;
+use crate::nav::routes::Route;
+use crate::service::calendar::{CalendarEntry, due_between, month_range, week_range};
+use chrono::{Datelike, Duration, Months, NaiveDate};
+
+/// The narrowest a day's cell on the month grid gets, in characters, including the gap after it.
+pub const MIN_CELL_WIDTH: usize = 6;
+
+/// # `CalendarPage` struct
+/// A month or week of due dates.
+pub struct CalendarPage {
+    /// A snapshot of the database state, to find what is due when the period changes.
+    state: ClearTextDBState,
+    /// Today's date, marked with a `*` and gone back to with [`KeyAction::Today`].
+    today: NaiveDate,
+    /// A day in the period shown.
+    date: NaiveDate,
+    /// Whether a week is shown instead of a month.
+    week: bool,
+    /// What is due in the period shown, in order; an item's number is its position plus one.
+    entries: Vec<CalendarEntry>,
+    /// The keybindings.
+    keys: KeyMap,
+}
+
+impl CalendarPage {
+    /// Creates the calendar showing the month holding `today`.
+    #[must_use]
+    pub fn new(state: &ClearTextDBState, today: NaiveDate) -> Self {
+        let mut page = Self { state: state.clone(), today, date: today, week: false, entries: Vec::new(), keys: KeyMap::active() };
+        page.show(today);
+        page
+    }
+
+    /// The first and last day of the period shown.
+    #[must_use]
+    pub fn period(&self) -> (NaiveDate, NaiveDate) {
+        if self.week { week_range(self.date) } else { month_range(self.date) }
+    }
+
+    /// Shows the period holding `date`.
+    fn show(&mut self, date: NaiveDate) {
+        self.date = date;
+        let (first, last) = self.period();
+        self.entries = due_between(&self.state, first, last);
+    }
+
+    /// Shows the next period, or the previous one if `forward` is false.
+    fn step(&mut self, forward: bool) {
+        let date = if self.week {
+            self.date + Duration::days(if forward { 7 } else { -7 })
+        } else {
+            let first = month_range(self.date).0;
+            let moved = if forward { first.checked_add_months(Months::new(1)) } else { first.checked_sub_months(Months::new(1)) };
+            moved.unwrap_or(first)
+        };
+        self.show(date);
+    }
+
+    /// The numbers of the items due on `day`, e.g. `1,2`.
+    fn numbers(&self, day: NaiveDate) -> String {
+        self.entries.iter().enumerate().filter(|(_, entry)| entry.due_date == day).map(|(index, _)| (index + 1).to_string()).collect::<Vec<_>>().join(",")
+    }
+
+    /// A day's date as shown, e.g. `Fri 14`, with a `*` if it is today.
+    fn day_label(&self, day: NaiveDate) -> String {
+        format!("{} {}{}", day.weekday(), day.day(), if day == self.today { "*" } else { "" })
+    }
+
+    /// An item as shown, with its number and, if it is a milestone, a `◆`.
+    fn item_label(index: usize, entry: &CalendarEntry) -> String {
+        let item = if entry.milestone { tr_with("calendar-milestone", &[("item", &entry.label)]) } else { entry.label.clone() };
+        format!("{} {item}", index + 1)
+    }
+
+    /// The title, e.g. `=== Calendar: March 2025 ===`.
+    fn title(&self) -> String {
+        let (first, _) = self.period();
+        if self.week { tr_with("calendar-week-title", &[("date", &first)]) } else { tr_with("calendar-month-title", &[("month", &first.format("%B %Y"))]) }
+    }
+
+    /// The month grid: a header of weekdays, then a row per week with each day's number and the
+    /// numbers of the items due on it.
+    fn month_grid(&self, size: Size) -> Vec<String> {
+        let width = (size.columns() / 7).max(MIN_CELL_WIDTH);
+        let (first, last) = self.period();
+        let monday = week_range(first).0;
+        let header: String = (0..7).map(|offset| fit(&(monday + Duration::days(offset)).weekday().to_string(), width)).collect();
+        let mut lines = vec![header.trim_end().to_string()];
+        let mut week_start = monday;
+        while week_start <= last {
+            let row: String = (0..7)
+                .map(|offset| {
+                    let day = week_start + Duration::days(offset);
+                    if day.month() != first.month() {
+                        return fit("", width);
+                    }
+                    let today = if day == self.today { "*" } else { "" };
+                    fit(format!("{}{today} {}", day.day(), self.numbers(day)).trim_end(), width)
+                })
+                .collect();
+            lines.push(row.trim_end().to_string());
+            week_start += Duration::days(7);
+        }
+        lines
+    }
+
+    /// The items due in the month, one per line with the day they are due.
+    fn month_list(&self) -> Vec<String> {
+        if self.entries.is_empty() {
+            return vec![tr("calendar-empty")];
+        }
+        self.entries.iter().enumerate().map(|(index, entry)| format!("{}  {}", self.day_label(entry.due_date), Self::item_label(index, entry))).collect()
+    }
+
+    /// The week, a line per day with the items due on it.
+    fn week_lines(&self) -> Vec<String> {
+        let (first, _) = self.period();
+        (0..7)
+            .map(|offset| {
+                let day = first + Duration::days(offset);
+                let items: Vec<String> = self.entries.iter().enumerate().filter(|(_, entry)| entry.due_date == day).map(|(index, entry)| Self::item_label(index, entry)).collect();
+                format!("{:<8}{}", self.day_label(day), items.join("  ")).trim_end().to_string()
+            })
+            .collect()
+    }
+}
+
+/// Cuts or pads text to exactly `width` characters, leaving the last one as a gap.
+fn fit(text: &str, width: usize) -> String {
+    let text: String = text.chars().take(width.saturating_sub(1)).collect();
+    format!("{text:<width$}")
+}
+
+impl Page for CalendarPage {
+    fn render(&self, size: Size) -> Vec<String> {
+        let mut lines = vec![self.title()];
+        if self.week {
+            lines.extend(self.week_lines());
+        } else {
+            lines.extend(self.month_grid(size));
+            lines.extend(self.month_list());
+        }
+        lines.push(tr_with("calendar-hint", &[("hints", &self.keys.hints(KeyContext::Calendar))]));
+        lines
+    }
+
+    fn render_linear(&self, _size: Size) -> Vec<String> {
+        let mut lines = vec![self.title()];
+        lines.extend(if self.week { self.week_lines() } else { self.month_list() });
+        lines.push(tr_with("calendar-hint", &[("hints", &self.keys.hints(KeyContext::Calendar))]));
+        lines
+    }
+
+    fn handle_input(&mut self, input: &str) -> PageAction {
+        match self.keys.lookup(KeyContext::Calendar, input).map(|(action, _)| action) {
+            Some(KeyAction::Earlier) => self.step(false),
+            Some(KeyAction::Later) => self.step(true),
+            Some(KeyAction::ToggleWeek) => {
+                self.week = !self.week;
+                let (first, last) = month_range(self.date);
+                self.show(if (first..=last).contains(&self.today) { self.today } else { self.date });
+            }
+            Some(KeyAction::Today) => self.show(self.today),
+            _ => {
+                if let Some(global) = global_action(&self.keys, KeyContext::Calendar, input) {
+                    return global;
+                }
+                let entry = input.trim().parse::<usize>().ok().and_then(|number| self.entries.get(number.checked_sub(1)?));
+                let Some(entry) = entry else { return PageAction::ShowError(tr_with("unknown-command", &[("input", &input.trim())])) };
+                return PageAction::Open(Route::from(entry.item));
+            }
+        }
+        PageAction::None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::service::{epics::create_epic, schedule::{set_epic_dates, set_story_dates}, stories::create_story};
+    use crate::users::User;
+
+    #[test]
+    fn test_calendar_plots_due_dates_by_month_and_week() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let date = |month, day| NaiveDate::from_ymd_opt(2025, month, day).unwrap();
+        let launch = create_epic(&mut state, "Launch", String::new(), &SystemClock).unwrap();
+        set_epic_dates(&mut state, launch, None, Some(date(3, 14)), &SystemClock).unwrap();
+        let login = create_story(&mut state, launch, "Login", String::new(), &SystemClock).unwrap();
+        let docs = create_story(&mut state, launch, "Docs", String::new(), &SystemClock).unwrap();
+        set_story_dates(&mut state, login, None, Some(date(3, 14)), &SystemClock).unwrap();
+        set_story_dates(&mut state, docs, None, Some(date(4, 2)), &SystemClock).unwrap();
+
+        let mut page = CalendarPage::new(&state, date(3, 12));
+        let lines = page.render(Size::new(56, 24));
+        assert_eq!(lines[..4], ["=== Calendar: March 2025 ===", "Mon     Tue     Wed     Thu     Fri     Sat     Sun", "                                        1       2", "3       4       5       6       7       8       9"]);
+        assert_eq!(lines[4], "10      11      12*     13      14 1,2  15      16");
+        assert_eq!(lines[8..10], ["Fri 14  1 ◆ EPIC-1 Launch", "Fri 14  2 STORY-1 Login"]);
+        assert!(matches!(page.handle_input("2"), PageAction::Open(Route::Story(id)) if id == login.to_string()));
+
+        page.handle_input(">");
+        assert_eq!(page.render(Size::new(56, 24))[0], "=== Calendar: April 2025 ===");
+        assert!(matches!(page.handle_input("1"), PageAction::Open(Route::Story(id)) if id == docs.to_string()));
+        assert!(matches!(page.handle_input("2"), PageAction::ShowError(error) if error == "Unknown command \"2\"."));
+
+        page.handle_input("t");
+        page.handle_input("w");
+        assert_eq!(page.period(), (date(3, 10), date(3, 16)));
+        assert_eq!(page.render(Size::default())[3..6], ["Wed 12*", "Thu 13", "Fri 14  1 ◆ EPIC-1 Launch  2 STORY-1 Login"]);
+        page.handle_input("<");
+        assert_eq!(page.period(), (date(3, 3), date(3, 9)));
+    }
+}
@@ -0,0 +1,197 @@
+//! # Dashboard Page
+//! The first page after logging in: how many epics and stories there are in each status, what is
+//! overdue, what changed lately, how far along each epic is, and what is due soon, with keys for
+//! the most common actions, which open the wizards or the search page by their
+//! [routes](Route). The counts come from [`crate::service::stats::stats`]; the page is rebuilt
+//! with [`PageAction::Refresh`] when it is shown again, since the pages opened over it change them.
+
+use super::i18n::{tr, tr_with};
+use super::{Page, PageAction, PageExit, Size, global_action};
+use super::keybindings::{KeyAction, KeyContext, KeyMap};
+use super::progress_bar::progress_bar;
+use super::reminders::{ReminderBanner, RemindersPanel};
+use super::table::{Align, Table};
+use crate::clock::Clock;
+use crate::db::ClearTextDBState;
+use crate::models::stats::CategoryCounts;
+use crate::nav::routes::Route;
+use crate::service::reminders::{DEFAULT_REMINDER_DAYS, reminders};
+use crate::service::{epics::epic_progress, stats::stats, statuses::ordered_statuses};
+use std::cmp::Reverse;
+use std::sync::Arc;
+
+/// The number of recently updated stories the dashboard lists.
+const RECENT_LIMIT: usize = 5;
+
+/// # `DashboardPage` struct
+/// A summary of the database, built when the page is opened.
+pub struct DashboardPage {
+    /// The summary lines above the reminders.
+    summary: Vec<String>,
+    /// Each active epic's short ID and title, with its progress bar.
+    epics: Vec<[String; 2]>,
+    /// The reminders panel.
+    panel: RemindersPanel,
+    /// The banner summarizing the reminders, until dismissed.
+    banner: ReminderBanner,
+    /// The keybindings.
+    keys: KeyMap,
+    /// The clock the summary and reminders are worked out with.
+    clock: Arc<dyn Clock>,
+    /// Whether another page was shown over this one, which may have changed the database.
+    hidden: bool,
+}
+
+impl DashboardPage {
+    /// Creates the dashboard from the current database state.
+    #[must_use]
+    pub fn new(state: &ClearTextDBState, clock: Arc<dyn Clock>) -> Self {
+        let totals = stats(state, clock.as_ref());
+        let describe = |counts: &CategoryCounts| {
+            tr_with("dashboard-counts", &[("total", &counts.total()), ("todo", &counts.todo), ("in-progress", &counts.in_progress), ("done", &counts.done)])
+        };
+        let mut summary = vec![
+            tr_with("dashboard-epics", &[("counts", &describe(&totals.epics))]),
+            tr_with("dashboard-stories", &[("counts", &describe(&totals.stories))]),
+            tr("dashboard-by-status"),
+        ];
+        summary.extend(
+            ordered_statuses(state)
+                .into_iter()
+                .map(|definition| format!("  {}: {}", definition.name, totals.stories_by_status.get(&definition.status).copied().unwrap_or_default())),
+        );
+        summary.push(tr_with("dashboard-overdue", &[("epics", &totals.overdue_epics), ("stories", &totals.overdue_stories)]));
+        summary.push(tr_with("dashboard-touched", &[("count", &totals.touched_this_week)]));
+        summary.push(tr("dashboard-recent"));
+        let mut recent: Vec<_> = state.stories.iter().filter(|story| !story.archived && !story.is_deleted()).collect();
+        recent.sort_by_key(|story| Reverse(story.updated_at));
+        if recent.is_empty() {
+            summary.push(tr("dashboard-no-stories"));
+        }
+        summary.extend(
+            recent
+                .into_iter()
+                .take(RECENT_LIMIT)
+                .map(|story| format!("  {} {}  {}", story.key(), story.title, story.updated_at.format("%Y-%m-%d %H:%M"))),
+        );
+        let epics = state
+            .epics
+            .iter()
+            .filter(|epic| !epic.archived && !epic.is_deleted())
+            .map(|epic| [format!("{} {}", epic.key(), epic.title), progress_bar(epic_progress(state, epic.epic_uuid).unwrap_or_default())])
+            .collect();
+        let found = reminders(state, DEFAULT_REMINDER_DAYS, clock.as_ref());
+        Self {
+            summary,
+            epics,
+            banner: ReminderBanner::new(&found),
+            panel: RemindersPanel::new(found),
+            keys: KeyMap::active(),
+            clock,
+            hidden: false,
+        }
+    }
+}
+
+impl Page for DashboardPage {
+    fn reads_text(&self) -> bool {
+        false
+    }
+
+    fn render(&self, size: Size) -> Vec<String> {
+        let mut lines = vec![tr("dashboard-title")];
+        lines.extend(self.banner.render());
+        lines.extend(self.summary.iter().cloned());
+        if !self.epics.is_empty() {
+            lines.push(tr("dashboard-epic-progress"));
+            let mut table = Table::new().column("", Align::Left).column("", Align::Left);
+            for row in &self.epics {
+                table.add_row(row);
+            }
+            lines.extend(table.render(size.columns().saturating_sub(2)).into_iter().map(|line| format!("  {line}")));
+        }
+        lines.extend(self.panel.render(size));
+        lines.push(tr("dashboard-quick-actions"));
+        lines.push(self.keys.hints(KeyContext::Dashboard));
+        lines
+    }
+
+    fn handle_input(&mut self, input: &str) -> PageAction {
+        let route = match self.keys.lookup(KeyContext::Dashboard, input) {
+            Some((KeyAction::NewEpic, _)) => Route::NewEpic,
+            Some((KeyAction::NewStory, _)) => Route::NewStory(None),
+            Some((KeyAction::Search, _)) => Route::Search(String::new()),
+            Some((KeyAction::DismissBanner, _)) => {
+                self.banner.dismiss();
+                return PageAction::None;
+            }
+            _ => return global_action(&self.keys, KeyContext::Dashboard, input).unwrap_or(PageAction::None),
+        };
+        PageAction::Open(route)
+    }
+
+    fn on_enter(&mut self) -> PageAction {
+        if std::mem::take(&mut self.hidden) { PageAction::Refresh } else { PageAction::None }
+    }
+
+    fn on_exit(&mut self, exit: PageExit) {
+        self.hidden = exit == PageExit::Hidden;
+    }
+
+    fn reload(&mut self, state: &ClearTextDBState) {
+        let dismissed = self.banner.render().is_none();
+        *self = Self::new(state, Arc::clone(&self.clock));
+        if dismissed {
+            self.banner.dismiss();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{FixedClock, SystemClock};
+    use crate::models::Status;
+    use crate::service::{epics::create_epic, schedule::set_story_dates, stories::{create_story, set_story_status}};
+    use crate::users::User;
+    use chrono::{DateTime, Duration};
+
+    #[test]
+    fn test_render_dashboard() {
+        let clock = FixedClock::new(DateTime::from_timestamp(1_700_000_000, 0).unwrap());
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        assert!(DashboardPage::new(&state, Arc::new(SystemClock)).render(Size::default()).contains(&"No stories yet.".to_string()));
+
+        let epic_uuid = create_epic(&mut state, "Launch", String::new(), &clock).unwrap();
+        let login = create_story(&mut state, epic_uuid, "Login", String::new(), &clock).unwrap();
+        clock.advance(Duration::hours(1));
+        let docs = create_story(&mut state, epic_uuid, "Docs", String::new(), &clock).unwrap();
+        set_story_status(&mut state, docs, Status::InProgress, &clock).unwrap();
+        set_story_dates(&mut state, login, None, Some(clock.today() - Duration::days(1)), &clock).unwrap();
+
+        // The page works out what is overdue by the real clock, which is long past the fixed one.
+        let mut page = DashboardPage::new(&state, Arc::new(SystemClock));
+        let lines = page.render(Size::default());
+        assert_eq!(lines[1], "! 1 item overdue (press x to dismiss)");
+        assert_eq!(lines[2..5], ["Epics: 1 (1 to do, 0 in progress, 0 done)", "Stories: 2 (1 to do, 1 in progress, 0 done)", "--- Stories by status ---"]);
+        assert!(lines.contains(&"  In Progress: 1".to_string()));
+        assert!(lines.contains(&"Overdue: 0 epics, 1 story".to_string()));
+        let recent = lines.iter().position(|line| line == "--- Recently updated ---").unwrap();
+        assert!(lines[recent + 1].starts_with("  STORY-1 Login"));
+        assert!(lines[recent + 2].starts_with("  STORY-2 Docs"));
+        let progress = lines.iter().position(|line| line == "--- Epic progress ---").unwrap();
+        assert_eq!(lines[progress + 1], "  EPIC-1 Launch  [----------] 0/2 (0%)");
+
+        page.handle_input("x");
+        assert!(!page.render(Size::default())[1].starts_with('!'));
+        assert!(matches!(page.handle_input("/"), PageAction::Open(Route::Search(query)) if query.is_empty()));
+        assert!(matches!(page.handle_input("e"), PageAction::Open(Route::NewEpic)));
+
+        page.on_exit(PageExit::Hidden);
+        assert!(matches!(page.on_enter(), PageAction::Refresh));
+        create_epic(&mut state, "Docs", String::new(), &clock).unwrap();
+        page.reload(&state);
+        let lines = page.render(Size::default());
+        assert_eq!(lines[1], "Epics: 2 (2 to do, 0 in progress, 0 done)");
+    }
+}
@@ -0,0 +1,366 @@
+//! # Edit Page
+//! Edits an existing epic or story field by field. The page starts from the item's current
+//! values; each new value is validated as soon as it is entered, and saving first shows the
+//! changes as a diff to confirm. The description is edited in the user's
+//! [`ExternalEditor`] if one is set, or otherwise in the multi-line [`TextEditor`].
+//!
+//! Once confirmed, the caller takes the changed fields as one [`Command::EditItem`] with
+//! [`Page::take_command`], so saving them is undone in one step. Closing the page before then
+//! discards the edits.
+
+use super::i18n::{tr, tr_with};
+use super::external_editor::ExternalEditor;
+use super::text_editor::{EditorOutcome, TextEditor};
+use super::{Page, PageAction, PageExit, Size};
+use crate::db::ClearTextDBState;
+use crate::models::validation::{validate_date_range, validate_description, validate_title};
+use crate::service::references::ItemRef;
+use crate::service::{ServiceError, get_epic, get_story, undo::Command};
+use chrono::NaiveDate;
+
+/// # Edit Field enum
+/// A field the edit page can change.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum EditField {
+    /// The title.
+    Title,
+    /// The description.
+    Description,
+    /// The start date.
+    StartDate,
+    /// The due date.
+    DueDate,
+}
+
+impl EditField {
+    /// Every field, in the order the page lists them.
+    pub const ALL: [EditField; 4] = [EditField::Title, EditField::Description, EditField::StartDate, EditField::DueDate];
+
+    /// The field's name as shown on the page.
+    #[must_use]
+    pub fn label(self) -> String {
+        tr(match self {
+            EditField::Title => "edit-field-title",
+            EditField::Description => "edit-field-description",
+            EditField::StartDate => "edit-field-start-date",
+            EditField::DueDate => "edit-field-due-date",
+        })
+    }
+}
+
+/// # Edit Mode enum
+/// What the edit page is doing.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum EditMode {
+    /// Listing the fields to pick from.
+    Fields,
+    /// Waiting for a new value for one field.
+    Editing(EditField),
+    /// Showing the changes to confirm.
+    Confirm,
+    /// Confirmed; the edits are ready.
+    Saved,
+    /// Abandoned.
+    Cancelled,
+}
+
+/// # `EditPage` struct
+/// Edits one epic or story.
+pub struct EditPage {
+    /// The item being edited.
+    item: ItemRef,
+    /// The item's short ID and title, for the header.
+    heading: String,
+    /// The current values, in [`EditField::ALL`] order, with dates as `YYYY-MM-DD` or empty.
+    original: [String; 4],
+    /// The edited values, in the same order.
+    edited: [String; 4],
+    /// What the page is doing.
+    mode: EditMode,
+    /// The description editor, while the description is being edited.
+    editor: Option<TextEditor>,
+    /// The user's own editor for the description, if one is set.
+    external: Option<ExternalEditor>,
+}
+
+impl EditPage {
+    /// Creates the edit page for an epic or story, loading its current values.
+    ///
+    /// # Errors
+    /// * `ServiceError::EpicNotFound` / `ServiceError::StoryNotFound` - If the item does not exist.
+    pub fn new(state: &ClearTextDBState, item: ItemRef) -> Result<Self, ServiceError> {
+        let date = |date: Option<NaiveDate>| date.map(|date| date.to_string()).unwrap_or_default();
+        let (heading, original) = match item {
+            ItemRef::Epic(epic_uuid) => {
+                let epic = get_epic(state, epic_uuid)?;
+                (format!("{} {}", epic.key(), epic.title), [epic.title.clone(), epic.description.clone(), date(epic.start_date), date(epic.due_date)])
+            }
+            ItemRef::Story(story_uuid) => {
+                let story = get_story(state, story_uuid)?;
+                (format!("{} {}", story.key(), story.title), [story.title.clone(), story.description.clone(), date(story.start_date), date(story.due_date)])
+            }
+        };
+        Ok(Self {
+            item,
+            heading,
+            edited: original.clone(),
+            original,
+            mode: EditMode::Fields,
+            editor: None,
+            external: None,
+        })
+    }
+
+    /// Edits the description in `external` instead of the in-app editor, e.g. with
+    /// [`ExternalEditor::from_env`].
+    #[must_use]
+    pub fn with_external_editor(mut self, external: Option<ExternalEditor>) -> Self {
+        self.external = external;
+        self
+    }
+
+    /// Returns what the page is doing.
+    #[must_use]
+    pub fn mode(&self) -> EditMode {
+        self.mode
+    }
+
+    /// Checks a new value for a field, returning it as it will be stored.
+    fn validate(&self, field: EditField, input: &str) -> Result<String, String> {
+        let edited = &self.edited;
+        match field {
+            EditField::Title => validate_title(input).map_err(|err| err.to_string()),
+            EditField::Description => validate_description(input).map(|()| input.to_string()).map_err(|err| err.to_string()),
+            EditField::StartDate | EditField::DueDate => {
+                let date = parse_date(input)?;
+                let (start, due) = if field == EditField::StartDate { (date, parse_date(&edited[3])?) } else { (parse_date(&edited[2])?, date) };
+                validate_date_range(start, due).map_err(|err| err.to_string())?;
+                Ok(date.map(|date| date.to_string()).unwrap_or_default())
+            }
+        }
+    }
+
+    /// The changed fields as diff lines.
+    fn diff(&self) -> Vec<String> {
+        let edited = &self.edited;
+        let shown = |value: &str| if value.is_empty() { tr("value-none") } else { value.to_string() };
+        EditField::ALL
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| edited[*index] != self.original[*index])
+            .flat_map(|(index, field)| {
+                [
+                    tr_with("edit-removed", &[("field", &field.label()), ("value", &shown(&self.original[index]))]),
+                    tr_with("edit-added", &[("field", &field.label()), ("value", &shown(&edited[index]))]),
+                ]
+            })
+            .collect()
+    }
+
+    /// Opens the description in the external editor and takes what it returns, or, without one,
+    /// in the in-app editor.
+    fn open_description(&mut self) -> Result<EditMode, String> {
+        let Some(external) = &self.external else {
+            self.editor = Some(TextEditor::new(&self.edited[1]));
+            return Ok(EditMode::Editing(EditField::Description));
+        };
+        let text = external.edit(&self.edited[1]).map_err(|err| tr_with("editor-failed", &[("error", &err)]))?;
+        self.edited[1] = self.validate(EditField::Description, &text)?;
+        Ok(EditMode::Fields)
+    }
+
+    /// Handles one input in the current mode, returning the next mode.
+    fn advance(&mut self, input: &str) -> Result<EditMode, String> {
+        let trimmed = input.trim();
+        match self.mode() {
+            EditMode::Fields => match trimmed {
+                "s" if self.diff().is_empty() => Err(tr("edit-unchanged")),
+                "s" => Ok(EditMode::Confirm),
+                "cancel" => Ok(EditMode::Cancelled),
+                _ => match trimmed.parse::<usize>().ok().and_then(|number| number.checked_sub(1)).and_then(|index| EditField::ALL.get(index)) {
+                    Some(EditField::Description) => self.open_description(),
+                    Some(field) => Ok(EditMode::Editing(*field)),
+                    None => Err(tr_with("unknown-command", &[("input", &trimmed)])),
+                },
+            },
+            EditMode::Editing(EditField::Description) => {
+                let Some(editor) = self.editor.as_mut() else { return Ok(EditMode::Fields) };
+                match editor.handle_input(input)? {
+                    EditorOutcome::Editing => return Ok(EditMode::Editing(EditField::Description)),
+                    EditorOutcome::Saved(text) => self.edited[1] = self.validate(EditField::Description, &text)?,
+                    EditorOutcome::Cancelled => (),
+                }
+                self.editor = None;
+                Ok(EditMode::Fields)
+            }
+            EditMode::Editing(field) => {
+                let value = self.validate(field, trimmed)?;
+                let index = EditField::ALL.iter().position(|candidate| *candidate == field).unwrap_or_default();
+                self.edited[index] = value;
+                Ok(EditMode::Fields)
+            }
+            EditMode::Confirm => match trimmed {
+                "y" | "yes" => Ok(EditMode::Saved),
+                _ => Ok(EditMode::Fields),
+            },
+            mode @ (EditMode::Saved | EditMode::Cancelled) => Ok(mode),
+        }
+    }
+}
+
+/// Parses an optional `YYYY-MM-DD` date; an empty string clears the date.
+fn parse_date(input: &str) -> Result<Option<NaiveDate>, String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Ok(None);
+    }
+    NaiveDate::parse_from_str(input, "%Y-%m-%d").map(Some).map_err(|_| tr("date-format"))
+}
+
+impl Page for EditPage {
+    fn render(&self, size: Size) -> Vec<String> {
+        let mut lines = vec![tr_with("edit-title", &[("item", &self.heading)])];
+        let edited = &self.edited;
+        match self.mode {
+            EditMode::Fields => {
+                lines.extend(EditField::ALL.iter().enumerate().map(|(index, field)| {
+                    let id = if edited[index] == self.original[index] { "edit-field-line" } else { "edit-field-changed" };
+                    tr_with(id, &[("number", &(index + 1)), ("field", &field.label()), ("value", &edited[index])])
+                }));
+                lines.push(tr("edit-fields-hint"));
+            }
+            EditMode::Editing(EditField::Description) => {
+                lines.push(tr("edit-description"));
+                lines.extend(self.editor.iter().flat_map(|editor| editor.render(size)));
+            }
+            EditMode::Editing(field) => {
+                let index = EditField::ALL.iter().position(|candidate| *candidate == field).unwrap_or_default();
+                lines.push(tr_with("edit-currently", &[("value", &edited[index])]));
+                lines.push(tr(match field {
+                    EditField::Title | EditField::Description => "edit-new-title",
+                    EditField::StartDate => "edit-new-start-date",
+                    EditField::DueDate => "edit-new-due-date",
+                }));
+            }
+            EditMode::Confirm => {
+                lines.push(tr("edit-changes"));
+                lines.extend(self.diff());
+                lines.push(tr("edit-confirm"));
+            }
+            EditMode::Saved => lines.push(tr("edit-saved")),
+            EditMode::Cancelled => lines.push(tr("cancelled")),
+        }
+        lines
+    }
+
+    fn handle_input(&mut self, input: &str) -> PageAction {
+        match self.advance(input) {
+            Ok(next) => {
+                self.mode = next;
+                match next {
+                    EditMode::Saved => PageAction::Refresh,
+                    EditMode::Cancelled => PageAction::Pop,
+                    _ => PageAction::None,
+                }
+            }
+            Err(error) => PageAction::ShowError(error),
+        }
+    }
+
+    fn on_exit(&mut self, exit: PageExit) {
+        if exit == PageExit::Closed && self.mode != EditMode::Saved {
+            self.mode = EditMode::Cancelled;
+            self.editor = None;
+            self.edited.clone_from(&self.original);
+        }
+    }
+
+    fn take_command(&mut self) -> Option<Command> {
+        if self.mode() != EditMode::Saved || self.edited == self.original {
+            return None;
+        }
+        let edited = &self.edited;
+        let changed = |index: usize| (edited[index] != self.original[index]).then(|| edited[index].clone());
+        let dates = (changed(2).is_some() || changed(3).is_some()).then(|| (parse_date(&edited[2]).ok().flatten(), parse_date(&edited[3]).ok().flatten()));
+        let command = Command::EditItem { item: self.item, title: changed(0), description: changed(1), dates };
+        self.original.clone_from(&self.edited);
+        Some(command)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::service::schedule::set_story_dates;
+    use crate::service::{epics::create_epic, stories::create_story};
+    use crate::users::User;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_edit_story() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let epic_uuid = create_epic(&mut state, "Launch", String::new(), &SystemClock).unwrap();
+        let story_uuid = create_story(&mut state, epic_uuid, "Login", "Old".to_string(), &SystemClock).unwrap();
+        set_story_dates(&mut state, story_uuid, None, NaiveDate::from_ymd_opt(2025, 3, 1), &SystemClock).unwrap();
+        let mut page = EditPage::new(&state, ItemRef::Story(story_uuid)).unwrap();
+
+        assert!(matches!(page.handle_input("s"), PageAction::ShowError(error) if error == "Nothing has changed."));
+        page.handle_input("1");
+        page.handle_input("  Sign in ");
+        page.handle_input("3");
+        let action = page.handle_input("2025-04-01");
+        assert!(matches!(action, PageAction::ShowError(error) if error == "The start date must not be after the due date"));
+        page.handle_input("2025-02-01");
+        assert_eq!(
+            page.render(Size::default())[1..5],
+            ["1. Title: Sign in *".to_string(), "2. Description: Old".to_string(), "3. Start date: 2025-02-01 *".to_string(), "4. Due date: 2025-03-01".to_string()]
+        );
+        page.handle_input("s");
+        assert_eq!(
+            page.render(Size::default())[1..7],
+            ["--- Changes ---", "- Title: Login", "+ Title: Sign in", "- Start date: (none)", "+ Start date: 2025-02-01", "Save these changes? (y/n)"].map(str::to_string)
+        );
+        assert_eq!(page.take_command(), None);
+        assert!(matches!(page.handle_input("y"), PageAction::Refresh));
+
+        let command = page.take_command().unwrap();
+        assert_eq!(command.to_string(), "edit story");
+        command.apply(&mut state, &SystemClock).unwrap();
+        assert_eq!(page.take_command(), None);
+        let story = get_story(&state, story_uuid).unwrap();
+        assert_eq!((story.title.as_str(), story.description.as_str()), ("Sign in", "Old"));
+        assert_eq!((story.start_date, story.due_date), (NaiveDate::from_ymd_opt(2025, 2, 1), NaiveDate::from_ymd_opt(2025, 3, 1)));
+    }
+
+    #[test]
+    fn test_edit_epic() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let epic_uuid = create_epic(&mut state, "Launch", "Old".to_string(), &SystemClock).unwrap();
+        let mut page = EditPage::new(&state, ItemRef::Epic(epic_uuid)).unwrap();
+        page.handle_input("2");
+        assert_eq!(page.render(Size::default())[1..4], ["Description:", "  1 | Old", "    >"]);
+        page.handle_input(":d 1");
+        assert!(matches!(page.handle_input(":w"), PageAction::None));
+        page.handle_input("s");
+        page.handle_input("y");
+        assert_eq!(page.take_command(), Some(Command::EditItem { item: ItemRef::Epic(epic_uuid), title: None, description: Some(String::new()), dates: None }));
+        let missing = Uuid::new_v4();
+        assert!(matches!(EditPage::new(&state, ItemRef::Epic(missing)), Err(ServiceError::EpicNotFound(uuid)) if uuid == missing));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_edit_description_externally() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let epic_uuid = create_epic(&mut state, "Launch", "Old".to_string(), &SystemClock).unwrap();
+        let external = ExternalEditor::new("sh", vec!["-c".to_string(), "printf 'New\\n' > \"$1\"".to_string(), "sh".to_string()]);
+        let mut page = EditPage::new(&state, ItemRef::Epic(epic_uuid)).unwrap().with_external_editor(Some(external));
+        assert!(matches!(page.handle_input("2"), PageAction::None));
+        assert_eq!((page.mode(), page.render(Size::default())[2].as_str()), (EditMode::Fields, "2. Description: New *"));
+
+        let mut page = EditPage::new(&state, ItemRef::Epic(epic_uuid)).unwrap().with_external_editor(Some(ExternalEditor::new("false", Vec::new())));
+        assert!(matches!(page.handle_input("2"), PageAction::ShowError(error) if error.starts_with("The editor failed")));
+        assert_eq!(page.mode(), EditMode::Fields);
+    }
+}
@@ -0,0 +1,79 @@
+//! # Effort Report Page
+//! Highlights the epics and stories whose logged time runs over their hour estimate.
+
+use super::i18n::{tr, tr_with};
+use super::keybindings::{KeyContext, KeyMap};
+use super::{Page, PageAction, Size, global_action};
+use crate::clock::Clock;
+use crate::db::ClearTextDBState;
+use crate::service::{estimates::effort_overruns, get_epic, get_story};
+
+/// # `EffortReportPage` struct
+/// A read-only report of effort overruns, worst first.
+pub struct EffortReportPage {
+    /// How far over its estimate an item must be to be listed, in percent.
+    threshold_percent: u32,
+    /// Each overrun item's short ID and title, with its comparison.
+    lines: Vec<String>,
+}
+
+impl EffortReportPage {
+    /// Creates the report from the current database state.
+    #[must_use]
+    pub fn new(state: &ClearTextDBState, threshold_percent: u32, clock: &dyn Clock) -> Self {
+        let lines = effort_overruns(state, threshold_percent, clock)
+            .into_iter()
+            .map(|comparison| {
+                let item = match get_epic(state, comparison.item_uuid) {
+                    Ok(epic) => format!("{} {}", epic.key(), epic.title),
+                    Err(_) => get_story(state, comparison.item_uuid).map(|story| format!("{} {}", story.key(), story.title)).unwrap_or_default(),
+                };
+                format!("{item}  {comparison}")
+            })
+            .collect();
+        Self { threshold_percent, lines }
+    }
+}
+
+impl Page for EffortReportPage {
+    fn reads_text(&self) -> bool {
+        false
+    }
+
+    fn render(&self, _size: Size) -> Vec<String> {
+        let mut lines = vec![tr_with("effort-title", &[("percent", &self.threshold_percent)])];
+        if self.lines.is_empty() {
+            lines.push(tr("effort-empty"));
+        }
+        lines.extend(self.lines.iter().cloned());
+        lines
+    }
+
+    fn handle_input(&mut self, input: &str) -> PageAction {
+        global_action(&KeyMap::active(), KeyContext::Global, input).unwrap_or(PageAction::None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::models::estimates::Estimate;
+    use crate::service::{epics::create_epic, estimates::set_story_estimate, stories::create_story, work_logs::log_work};
+    use crate::users::User;
+
+    #[test]
+    fn test_render_effort_report() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let epic_uuid = create_epic(&mut state, "Epic", String::new(), &SystemClock).unwrap();
+        let story_uuid = create_story(&mut state, epic_uuid, "Login", String::new(), &SystemClock).unwrap();
+        set_story_estimate(&mut state, story_uuid, Some(Estimate::Hours(2)), &SystemClock).unwrap();
+        assert_eq!(EffortReportPage::new(&state, 10, &SystemClock).render(Size::default())[1], "Everything is within its estimate.");
+
+        log_work(&mut state, story_uuid, SystemClock.now(), 3 * 3600, String::new()).unwrap();
+        assert_eq!(
+            EffortReportPage::new(&state, 10, &SystemClock).render(Size::default()),
+            vec!["=== Over Estimate by More Than 10% ===", "EPIC-1 Epic  3h 00m of 2h (150%)", "STORY-1 Login  3h 00m of 2h (150%)"]
+        );
+    }
+}
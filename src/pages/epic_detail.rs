@@ -0,0 +1,244 @@
+//! # Epic Detail Page
+//! Shows one epic: its status, progress, parent, labels, dates, custom fields, and description,
+//! followed by its stories in rank order. Picking a story or adding one opens its route; a status
+//! change is recorded for the caller to run as a command taken with [`Page::take_command`]. Typing
+//! `t` without a status opens a [`Picker`] over the statuses the workflow allows. When the page is shown again
+//! after another page was opened over it, it asks to be rebuilt with [`PageAction::Refresh`].
+
+use super::i18n::{tr, tr_with};
+use super::keybindings::{KeyAction, KeyContext, KeyMap};
+use super::picker::{Picked, Picker, status_picker};
+use super::progress_bar::progress_bar;
+use super::table::{Align, Table};
+use super::{Page, PageAction, PageExit, Size, global_action, markdown::render_styled};
+use crate::db::ClearTextDBState;
+use crate::nav::routes::Route;
+use crate::models::{Status, statuses::{StatusDefinition, find_definition_by_name}};
+use crate::service::{ServiceError, custom_fields::render_fields, epics::epic_progress, get_epic, labels::get_label, ranking::ranked_stories, undo::Command};
+use uuid::Uuid;
+
+/// # `EpicDetailPage` struct
+/// A view of one epic and its stories, built when the page is opened.
+pub struct EpicDetailPage {
+    /// The epic shown.
+    epic_uuid: Uuid,
+    /// The header and metadata lines.
+    lines: Vec<String>,
+    /// The epic's stories in rank order, each with its short ID, title, and status.
+    stories: Vec<(Uuid, [String; 3])>,
+    /// The database's statuses, to look up the status the user types.
+    statuses: Vec<StatusDefinition>,
+    /// The statuses the workflow lets the item move to, shown when `t` is typed on its own.
+    picker: Picker<Status>,
+    /// Whether the status picker is open.
+    picking: bool,
+    /// Whether another page was shown over this one, which may have changed the epic.
+    hidden: bool,
+    /// The status change chosen, not yet taken by the caller.
+    command: Option<Command>,
+    /// The keybindings.
+    keys: KeyMap,
+}
+
+impl EpicDetailPage {
+    /// Creates the detail page for an epic from the current database state.
+    ///
+    /// # Errors
+    /// * `ServiceError::EpicNotFound` - If the epic does not exist.
+    pub fn new(state: &ClearTextDBState, epic_uuid: Uuid) -> Result<Self, ServiceError> {
+        let epic = get_epic(state, epic_uuid)?;
+        let mut lines = vec![
+            tr_with("detail-title", &[("key", &epic.key()), ("title", &epic.badge())]),
+            tr_with("field-status", &[("value", &epic.status.name(&state.statuses))]),
+            tr_with("field-progress", &[("value", &progress_bar(epic_progress(state, epic_uuid)?))]),
+        ];
+        if let Some(parent) = epic.parent_epic.and_then(|parent| get_epic(state, parent).ok()) {
+            lines.push(tr_with("field-parent", &[("value", &format!("{} {}", parent.key(), parent.title))]));
+        }
+        let labels: Vec<&str> = epic.labels.iter().filter_map(|label_uuid| get_label(state, *label_uuid).ok()).map(|label| label.name.as_str()).collect();
+        if !labels.is_empty() {
+            lines.push(tr_with("field-labels", &[("value", &labels.join(", "))]));
+        }
+        lines.extend(epic.start_date.map(|date| tr_with("field-start", &[("value", &date)])));
+        lines.extend(epic.due_date.map(|date| tr_with("field-due", &[("value", &date)])));
+        lines.extend(render_fields(state, epic_uuid)?);
+        if !epic.description.is_empty() {
+            lines.push(tr("section-description"));
+            lines.extend(render_styled(&epic.description));
+        }
+        let stories = ranked_stories(state, epic_uuid)?
+            .into_iter()
+            .map(|story| (story.story_uuid, [story.key(), story.title.clone(), story.status.name(&state.statuses)]))
+            .collect();
+        Ok(Self {
+            epic_uuid,
+            lines,
+            stories,
+            statuses: state.statuses.clone(),
+            picker: status_picker(state, epic.status),
+            picking: false,
+            hidden: false,
+            command: None,
+            keys: KeyMap::active(),
+        })
+    }
+
+    /// Records a status change for the caller to run.
+    fn change_status(&mut self, status: Status) -> PageAction {
+        self.command = Some(Command::SetEpicStatus { epic_uuid: self.epic_uuid, status });
+        PageAction::Refresh
+    }
+
+    /// Reads one input into what the page does next.
+    fn parse(&mut self, input: &str) -> Result<PageAction, String> {
+        match self.keys.lookup(KeyContext::EpicDetail, input) {
+            Some((KeyAction::AddStory, _)) => Ok(PageAction::Open(Route::NewStory(Some(self.epic_uuid.to_string())))),
+            Some((KeyAction::ChangeStatus, name)) => find_definition_by_name(&self.statuses, name)
+                .map(|definition| definition.status)
+                .map(|status| self.change_status(status))
+                .ok_or_else(|| tr_with("no-such-status", &[("name", &name)])),
+            _ => input
+                .parse::<usize>()
+                .ok()
+                .and_then(|number| number.checked_sub(1))
+                .and_then(|index| self.stories.get(index))
+                .map(|(story_uuid, _)| PageAction::Open(Route::Story(story_uuid.to_string())))
+                .ok_or_else(|| tr_with("unknown-command", &[("input", &input)])),
+        }
+    }
+}
+
+impl Page for EpicDetailPage {
+    fn render(&self, size: Size) -> Vec<String> {
+        let mut lines = self.lines.clone();
+        lines.push(tr("section-stories"));
+        if self.stories.is_empty() {
+            lines.push(tr("epic-no-stories"));
+        } else {
+            let mut table = Table::new()
+                .column(&tr("column-number"), Align::Right)
+                .column(&tr("column-key"), Align::Left)
+                .column(&tr("column-title"), Align::Left)
+                .column(&tr("column-status"), Align::Left);
+            for (index, (_, cells)) in self.stories.iter().enumerate() {
+                table.add_row([(index + 1).to_string()].iter().chain(cells));
+            }
+            lines.extend(table.render(size.columns()));
+        }
+        if self.picking {
+            lines.push(tr("section-change-status"));
+            lines.extend(self.picker.render());
+            lines.push(tr("keep-status-hint"));
+        } else {
+            lines.push(tr_with("epic-detail-hint", &[("hints", &self.keys.hints(KeyContext::EpicDetail))]));
+        }
+        lines
+    }
+
+    fn handle_input(&mut self, input: &str) -> PageAction {
+        if self.picking {
+            return match self.picker.handle_input(input) {
+                Ok(Picked::Chosen(status)) => {
+                    self.picking = false;
+                    self.change_status(status)
+                }
+                Ok(Picked::Cancelled) => {
+                    self.picking = false;
+                    PageAction::None
+                }
+                Ok(Picked::Browsing) => PageAction::None,
+                Err(error) => PageAction::ShowError(error),
+            };
+        }
+        if let Some(global) = global_action(&self.keys, KeyContext::EpicDetail, input) {
+            return global;
+        }
+        match self.keys.lookup(KeyContext::EpicDetail, input.trim()) {
+            Some((KeyAction::ChangeStatus, "")) => {
+                self.picking = true;
+                return PageAction::None;
+            }
+            Some((KeyAction::Edit, _)) => return PageAction::Open(Route::Edit(self.epic_uuid.to_string())),
+            _ => (),
+        }
+        self.parse(input.trim()).unwrap_or_else(PageAction::ShowError)
+    }
+
+    fn on_enter(&mut self) -> PageAction {
+        if std::mem::take(&mut self.hidden) { PageAction::Refresh } else { PageAction::None }
+    }
+
+    fn on_exit(&mut self, exit: PageExit) {
+        self.hidden = exit == PageExit::Hidden;
+    }
+
+    fn take_command(&mut self) -> Option<Command> {
+        self.command.take()
+    }
+
+    fn reload(&mut self, state: &ClearTextDBState) {
+        if let Ok(page) = Self::new(state, self.epic_uuid) {
+            *self = page;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::service::{epics::create_epic, hierarchy::set_parent_epic, labels::{create_label, label_epic}, stories::{create_story, set_story_status}};
+    use crate::users::User;
+
+    #[test]
+    fn test_render_epic_detail() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let missing = Uuid::new_v4();
+        assert!(matches!(EpicDetailPage::new(&state, missing), Err(ServiceError::EpicNotFound(uuid)) if uuid == missing));
+        let platform = create_epic(&mut state, "Platform", String::new(), &SystemClock).unwrap();
+        let epic_uuid = create_epic(&mut state, "Launch", "Ship it".to_string(), &SystemClock).unwrap();
+        set_parent_epic(&mut state, epic_uuid, Some(platform), &SystemClock).unwrap();
+        let infra = create_label(&mut state, "infra".to_string(), "#0000ff".to_string()).unwrap();
+        label_epic(&mut state, epic_uuid, infra, &SystemClock).unwrap();
+        assert!(EpicDetailPage::new(&state, epic_uuid).unwrap().render(Size::default()).contains(&"No stories yet.".to_string()));
+        create_story(&mut state, epic_uuid, "Login", String::new(), &SystemClock).unwrap();
+        let docs = create_story(&mut state, epic_uuid, "Docs", String::new(), &SystemClock).unwrap();
+        set_story_status(&mut state, docs, Status::Closed, &SystemClock).unwrap();
+
+        let mut page = EpicDetailPage::new(&state, epic_uuid).unwrap();
+        assert_eq!(
+            page.render(Size::default()),
+            vec![
+                "=== EPIC-2 Launch ===",
+                "Status: Open",
+                "Progress: [#####-----] 1/2 (50%)",
+                "Parent: EPIC-1 Platform",
+                "Labels: infra",
+                "--- Description ---",
+                "Ship it",
+                "--- Stories ---",
+                "#  Key      Title  Status",
+                "1  STORY-1  Login  Open",
+                "2  STORY-2  Docs   Closed",
+                "[number] open story  [s] add story  [t <status>] change status  [e] edit",
+            ]
+        );
+        assert!(matches!(page.handle_input("2"), PageAction::Open(Route::Story(id)) if id == docs.to_string()));
+        assert!(matches!(page.handle_input("t in progress"), PageAction::Refresh));
+        assert_eq!(page.take_command(), Some(Command::SetEpicStatus { epic_uuid, status: Status::InProgress }));
+        assert!(matches!(page.handle_input("t done-ish"), PageAction::ShowError(error) if error == "There is no status \"done-ish\"."));
+        assert_eq!(page.take_command(), None);
+        assert!(matches!(page.handle_input("s"), PageAction::Open(Route::NewStory(Some(id))) if id == epic_uuid.to_string()));
+        assert!(matches!(page.handle_input("e"), PageAction::Open(Route::Edit(id)) if id == epic_uuid.to_string()));
+
+        page.handle_input("t");
+        assert_eq!(page.render(Size::default())[11..13], ["--- Change status ---", "> 1. Open"]);
+        assert!(matches!(page.handle_input("9"), PageAction::ShowError(error) if error == "There is no status \"9\"."));
+        page.handle_input("cancel");
+        assert_eq!(page.take_command(), None);
+        page.handle_input("t");
+        page.handle_input("j");
+        assert!(matches!(page.handle_input(""), PageAction::Refresh));
+        assert_eq!(page.take_command(), Some(Command::SetEpicStatus { epic_uuid, status: Status::InProgress }));
+    }
+}
@@ -0,0 +1,317 @@
+//! # Epic List Page
+//! Lists the active epics as a tree, a page at a time, with their short IDs, statuses, and
+//! rolled-up progress. The user moves a selection through the list, narrows it with a filter,
+//! sorts it, marks epics to act on several at once, and picks an action. Opening an epic or
+//! creating one goes to its [route](Route); archiving and moving to the trash are left for the
+//! caller to run as one command per epic, taken with [`Page::take_command`], after which it
+//! reloads the page. The list also asks to be reloaded with [`PageAction::Refresh`] when it is
+//! shown again after another page was opened over it.
+//!
+//! The sort order and filter start from those last used, saved in the settings; the caller
+//! saves changes to them, taken with [`Page::take_account_change`].
+
+use super::i18n::{tr, tr_with};
+use super::{AccountChange, Page, PageAction, PageExit, Size, global_action};
+use super::keybindings::{KeyAction, KeyContext, KeyMap};
+use super::list::{ListView, RowFacets};
+use super::modal::ConfirmModal;
+use super::progress_bar::progress_bar;
+use super::table::Align;
+use crate::db::ClearTextDBState;
+use crate::models::settings::{ListName, ListPreferences};
+use crate::nav::routes::Route;
+use crate::service::hierarchy::{epic_tree, subtree_progress};
+use crate::service::pagination::DEFAULT_PAGE_SIZE;
+use crate::service::undo::Command;
+use std::any::Any;
+use std::collections::VecDeque;
+use uuid::Uuid;
+
+/// # `EpicListPage` struct
+/// A paged, selectable list of the epics that are neither archived nor deleted.
+pub struct EpicListPage {
+    /// The epics in tree order, each with its indented short ID and title, status, and progress.
+    list: ListView<Uuid>,
+    /// The epics per page.
+    page_size: usize,
+    /// The archive and trash commands chosen, not yet taken by the caller.
+    commands: VecDeque<Command>,
+    /// The epics to delete once the user confirms it.
+    pending_delete: Option<Vec<Uuid>>,
+    /// The sort order and filter as last saved.
+    saved: ListPreferences,
+    /// Whether another page was shown over this one, which may have changed the epics.
+    hidden: bool,
+    /// The keybindings.
+    keys: KeyMap,
+}
+
+impl EpicListPage {
+    /// Creates the epic list from the current database state, showing [`DEFAULT_PAGE_SIZE`]
+    /// epics per page.
+    #[must_use]
+    pub fn new(state: &ClearTextDBState) -> Self {
+        Self::with_page_size(state, DEFAULT_PAGE_SIZE)
+    }
+
+    /// Creates the epic list with `page_size` epics per page.
+    #[must_use]
+    pub fn with_page_size(state: &ClearTextDBState, page_size: usize) -> Self {
+        let (rows, facets) = epic_tree(state)
+            .into_iter()
+            .filter(|(_, epic)| !epic.archived)
+            .map(|(depth, epic)| {
+                let progress = subtree_progress(state, epic.epic_uuid).unwrap_or_default();
+                let title = format!("{}{} {}", "  ".repeat(depth), epic.key(), epic.badge());
+                let facets = RowFacets::of(state, epic, &epic.labels, &epic.custom_fields);
+                ((epic.epic_uuid, vec![title, epic.status.name(&state.statuses), progress_bar(progress)]), facets)
+            })
+            .unzip();
+        let saved = state.settings.list(ListName::Epics);
+        Self {
+            list: ListView::new(rows, page_size)
+                .column(&tr("column-epic"), Align::Left)
+                .column(&tr("column-status"), Align::Left)
+                .column(&tr("column-progress"), Align::Left)
+                .facets(facets)
+                .with_preferences(&saved),
+            page_size,
+            commands: VecDeque::new(),
+            pending_delete: None,
+            saved,
+            hidden: false,
+            keys: KeyMap::active(),
+        }
+    }
+
+    /// The UUID of the selected epic, if the list isn't empty.
+    #[must_use]
+    pub fn selected(&self) -> Option<Uuid> {
+        self.list.selected().copied()
+    }
+
+    /// The UUIDs of the marked epics.
+    #[must_use]
+    pub fn marked(&self) -> Vec<Uuid> {
+        self.list.marked().into_iter().copied().collect()
+    }
+
+    /// Asks the caller to save the sort order and filter if they changed since they were last
+    /// saved.
+    fn preferences_changed(&self) -> PageAction {
+        if self.list.preferences() == self.saved { PageAction::None } else { PageAction::Refresh }
+    }
+
+    /// The marked epics, or the selected one if none are marked; `None` if that is no epics.
+    fn chosen(&self) -> Option<Vec<Uuid>> {
+        Some(self.list.chosen().into_iter().copied().collect()).filter(|chosen: &Vec<Uuid>| !chosen.is_empty())
+    }
+
+    /// Records a command for each of `epic_uuids` for the caller to run.
+    fn queue(&mut self, epic_uuids: Vec<Uuid>, command: fn(Uuid) -> Command) -> PageAction {
+        self.commands.extend(epic_uuids.into_iter().map(command));
+        PageAction::Refresh
+    }
+}
+
+impl Page for EpicListPage {
+    fn render(&self, size: Size) -> Vec<String> {
+        let mut lines = vec![tr("epics-title")];
+        lines.extend(self.list.bar());
+        if self.list.has_no_rows() {
+            lines.push(tr("epics-empty"));
+        } else if self.list.is_empty() {
+            lines.push(tr("epics-no-match"));
+        } else {
+            lines.extend(self.list.render(size.columns()));
+        }
+        lines.push(self.list.page_footer());
+        lines.push(tr_with("epics-hint", &[("hints", &self.keys.hints(KeyContext::EpicList))]));
+        lines
+    }
+
+    fn handle_input(&mut self, input: &str) -> PageAction {
+        let input = input.trim();
+        let (action, argument) = if input.is_empty() { (Some(KeyAction::Open), "") } else { self.keys.lookup(KeyContext::EpicList, input).map_or((None, ""), |(action, argument)| (Some(action), argument)) };
+        if let Some(action) = action
+            && self.list.navigate(action)
+        {
+            return PageAction::None;
+        }
+        match action {
+            Some(KeyAction::Filter) => {
+                self.list.set_filter(argument);
+                self.preferences_changed()
+            }
+            Some(KeyAction::Sort) => self.list.sort_by_name(argument).map_or_else(PageAction::ShowError, |()| self.preferences_changed()),
+            Some(KeyAction::ToggleMark) => {
+                self.list.toggle_mark();
+                PageAction::None
+            }
+            Some(KeyAction::NewEpic) => PageAction::Open(Route::NewEpic),
+            Some(KeyAction::Open) => self.selected().map_or(PageAction::None, |epic_uuid| PageAction::Open(Route::Epic(epic_uuid.to_string()))),
+            Some(KeyAction::Archive) => match self.chosen() {
+                Some(chosen) => self.queue(chosen, |epic_uuid| Command::ArchiveEpic { epic_uuid }),
+                None => PageAction::None,
+            },
+            Some(KeyAction::Delete) => {
+                let Some(chosen) = self.chosen() else { return PageAction::None };
+                let question = tr_with("epics-confirm-delete", &[("count", &chosen.len())]);
+                self.pending_delete = Some(chosen);
+                PageAction::OpenModal(Box::new(ConfirmModal::new(question)))
+            }
+            _ => {
+                if let Some(global) = global_action(&self.keys, KeyContext::EpicList, input) {
+                    return global;
+                }
+                match self.list.pick(input) {
+                    Some(epic_uuid) => PageAction::Open(Route::Epic(epic_uuid.to_string())),
+                    None => PageAction::ShowError(tr_with("unknown-command", &[("input", &input)])),
+                }
+            }
+        }
+    }
+
+    fn modal_closed(&mut self, result: Box<dyn Any>) -> PageAction {
+        let confirmed = result.downcast::<bool>().is_ok_and(|confirmed| *confirmed);
+        match self.pending_delete.take().filter(|_| confirmed) {
+            Some(chosen) => self.queue(chosen, |epic_uuid| Command::TrashEpic { epic_uuid }),
+            None => PageAction::None,
+        }
+    }
+
+    fn on_enter(&mut self) -> PageAction {
+        if std::mem::take(&mut self.hidden) { PageAction::Refresh } else { PageAction::None }
+    }
+
+    fn on_exit(&mut self, exit: PageExit) {
+        self.hidden = exit == PageExit::Hidden;
+    }
+
+    fn take_command(&mut self) -> Option<Command> {
+        self.commands.pop_front()
+    }
+
+    fn take_account_change(&mut self) -> Option<AccountChange> {
+        let preferences = self.list.preferences();
+        (preferences != self.saved).then(|| {
+            self.saved = preferences.clone();
+            AccountChange::ListPreferences(ListName::Epics, preferences)
+        })
+    }
+
+    fn reload(&mut self, state: &ClearTextDBState) {
+        let preferences = self.list.preferences();
+        let mut page = Self::with_page_size(state, self.page_size);
+        page.list.set_filter(&preferences.filter);
+        page.list.set_sort(preferences.sort);
+        page.saved = std::mem::take(&mut self.saved);
+        *self = page;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::models::Status;
+    use crate::models::settings::ListSort;
+    use crate::service::labels::{create_label, label_epic};
+    use crate::service::schedule::set_epic_dates;
+    use crate::service::settings::set_list_preferences;
+    use chrono::NaiveDate;
+    use crate::service::{archive::archive_epic, epics::create_epic, hierarchy::set_parent_epic, stories::{create_story, set_story_status}};
+    use crate::users::User;
+
+    #[test]
+    fn test_epic_list_paging_and_actions() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        assert_eq!(EpicListPage::new(&state).render(Size::default())[1], "No epics yet. Press n to create one.");
+        let launch = create_epic(&mut state, "Launch", String::new(), &SystemClock).unwrap();
+        let docs = create_epic(&mut state, "Docs", String::new(), &SystemClock).unwrap();
+        let site = create_epic(&mut state, "Site", String::new(), &SystemClock).unwrap();
+        let old = create_epic(&mut state, "Old", String::new(), &SystemClock).unwrap();
+        set_parent_epic(&mut state, docs, Some(launch), &SystemClock).unwrap();
+        let story_uuid = create_story(&mut state, docs, "Guide", String::new(), &SystemClock).unwrap();
+        set_story_status(&mut state, story_uuid, Status::Closed, &SystemClock).unwrap();
+        archive_epic(&mut state, old, &SystemClock).unwrap();
+
+        let mut page = EpicListPage::with_page_size(&state, 2);
+        assert_eq!(
+            page.render(Size::default()),
+            vec![
+                "=== Epics ===",
+                "   #  Epic           Status  Progress",
+                ">  1  EPIC-1 Launch  Open    [##########] 1/1 (100%)",
+                "   2    EPIC-2 Docs  Open    [##########] 1/1 (100%)",
+                "Page 1 of 2",
+                "[number] open  [o] open  [j] move down  [k] move up  [>] next page  [<] previous page  [n] new epic  [a] archive  [d] delete  [f <text>] filter  [x] mark  [s <status|priority|due>] sort",
+            ]
+        );
+        page.handle_input(">");
+        assert_eq!(page.render(Size::default())[2..4], [">  1  EPIC-3 Site  Open    [----------] 0/0 (0%)".to_string(), "Page 2 of 2".to_string()]);
+        assert_eq!(page.render(Size::new(30, 24))[2], ">  1  EPIC-3…  Open    [-----…");
+        page.handle_input(">");
+        assert_eq!(page.selected(), Some(site));
+        assert!(matches!(page.handle_input("d"), PageAction::OpenModal(_)));
+        assert!(matches!(page.modal_closed(Box::new(false)), PageAction::None));
+        assert_eq!(page.take_command(), None);
+        page.handle_input("d");
+        assert!(matches!(page.modal_closed(Box::new(true)), PageAction::Refresh));
+        assert_eq!(page.take_command(), Some(Command::TrashEpic { epic_uuid: site }));
+        page.handle_input("k");
+        assert_eq!(page.render(Size::default())[4], "Page 1 of 2");
+        assert!(matches!(page.handle_input("1"), PageAction::Open(Route::Epic(id)) if id == launch.to_string()));
+        assert!(matches!(page.handle_input("7"), PageAction::ShowError(error) if error == "Unknown command \"7\"."));
+        assert!(matches!(page.handle_input("q"), PageAction::Pop));
+        assert!(matches!(page.handle_input("n"), PageAction::Open(Route::NewEpic)));
+        assert_eq!(page.take_command(), None);
+
+        page.handle_input("f docs");
+        assert_eq!(page.render(Size::default())[1..4], ["Filter: docs", "   #  Epic           Status  Progress", ">  1    EPIC-2 Docs  Open    [##########] 1/1 (100%)"]);
+        page.handle_input("x");
+        page.handle_input("f");
+        page.handle_input("x");
+        assert_eq!(page.marked(), vec![launch, docs]);
+        page.handle_input("a");
+        assert_eq!(std::iter::from_fn(|| page.take_command()).collect::<Vec<_>>(), [Command::ArchiveEpic { epic_uuid: launch }, Command::ArchiveEpic { epic_uuid: docs }]);
+        page.handle_input("f zzz");
+        assert_eq!(page.render(Size::default())[2], "No epics match the filter.");
+
+        for epic_uuid in [launch, docs] {
+            Command::ArchiveEpic { epic_uuid }.apply(&mut state, &SystemClock).unwrap();
+        }
+        page.reload(&state);
+        assert_eq!(page.render(Size::default())[1..3], ["Filter: zzz", "No epics match the filter."]);
+        page.handle_input("f");
+        assert_eq!(page.render(Size::default())[2], ">  1  EPIC-3 Site  Open    [----------] 0/0 (0%)");
+    }
+
+    #[test]
+    fn test_epic_list_sorts_filters_by_label_and_remembers() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let launch = create_epic(&mut state, "Launch", String::new(), &SystemClock).unwrap();
+        let docs = create_epic(&mut state, "Docs", String::new(), &SystemClock).unwrap();
+        let site = create_epic(&mut state, "Site", String::new(), &SystemClock).unwrap();
+        set_epic_dates(&mut state, docs, None, NaiveDate::from_ymd_opt(2025, 3, 1), &SystemClock).unwrap();
+        set_epic_dates(&mut state, site, None, NaiveDate::from_ymd_opt(2025, 2, 1), &SystemClock).unwrap();
+        let ui = create_label(&mut state, "UI".to_string(), "blue".to_string()).unwrap();
+        label_epic(&mut state, launch, ui, &SystemClock).unwrap();
+        label_epic(&mut state, site, ui, &SystemClock).unwrap();
+
+        let mut page = EpicListPage::new(&state);
+        assert_eq!(page.take_account_change(), None);
+        assert!(matches!(page.handle_input("s due"), PageAction::Refresh));
+        assert_eq!(page.render(Size::default())[1..5], ["Sort: due", "   #  Epic           Status  Progress", ">  1  EPIC-3 Site    Open    [----------] 0/0 (0%)", "   2  EPIC-2 Docs    Open    [----------] 0/0 (0%)"]);
+        page.handle_input("f label:ui");
+        assert_eq!(page.render(Size::default())[4..7], [">  1  EPIC-3 Site    Open    [----------] 0/0 (0%)", "   2  EPIC-1 Launch  Open    [----------] 0/0 (0%)", "Page 1 of 1"]);
+        assert!(matches!(page.handle_input("s size"), PageAction::ShowError(error) if error.starts_with("Unknown sort order \"size\".")));
+
+        let Some(AccountChange::ListPreferences(list, preferences)) = page.take_account_change() else { panic!("expected the new list preferences") };
+        assert_eq!((list, &preferences), (ListName::Epics, &ListPreferences { sort: ListSort::DueDate, filter: "label:ui".to_string() }));
+        assert_eq!(page.take_account_change(), None);
+        assert!(matches!(page.handle_input("s due"), PageAction::None));
+        set_list_preferences(&mut state, list, preferences);
+        assert_eq!(EpicListPage::new(&state).render(Size::default())[1..3], ["Filter: label:ui", "Sort: due"]);
+    }
+}
@@ -0,0 +1,329 @@
+//! # Epic Wizard Page
+//! Creates an epic one step at a time: title, description, labels, and, if the database defines
+//! an enum custom field named [`PRIORITY_FIELD`], a priority, then a summary to confirm. Each
+//! answer is checked with the shared validation rules as soon as it is entered. Typing `<` goes
+//! back a step and `cancel` abandons the wizard. The description is written in the multi-line
+//! [`TextEditor`](super::text_editor::TextEditor).
+//!
+//! Once confirmed, the caller takes the answers as a [`Command::CreateEpic`] with
+//! [`Page::take_command`], runs it, and shows the new epic in place of the wizard.
+
+use super::i18n::{tr, tr_with};
+use super::external_editor::ExternalEditor;
+use super::picker::{Picked, Picker};
+use super::text_editor::{EditorOutcome, TextEditor};
+use super::{Page, PageAction, Size};
+use crate::db::ClearTextDBState;
+use crate::models::custom_fields::{FieldId, FieldKind, FieldValue};
+use crate::models::validation::{validate_description, validate_title};
+use crate::service::custom_fields::find_field_by_name;
+use crate::service::undo::Command;
+use uuid::Uuid;
+
+/// The name of the enum custom field the wizards offer as the priority.
+pub const PRIORITY_FIELD: &str = "Priority";
+
+/// The [`PRIORITY_FIELD`] and a picker over its options, led by `None`, if the database defines
+/// it as an enum field.
+pub(super) fn priority_field(state: &ClearTextDBState) -> Option<(FieldId, Picker<Option<String>>)> {
+    find_field_by_name(state, PRIORITY_FIELD).and_then(|field| match &field.kind {
+        FieldKind::Enum(options) => {
+            let options = options.iter().map(|option| (Some(option.clone()), option.clone()));
+            Some((field.field_id, Picker::new("priority", [(None, tr("wizard-priority-none"))].into_iter().chain(options).collect())))
+        }
+        _ => None,
+    })
+}
+
+/// # Wizard Step enum
+/// Where the user is in the epic wizard.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum WizardStep {
+    /// Entering the title.
+    Title,
+    /// Entering the description.
+    Description,
+    /// Choosing labels.
+    Labels,
+    /// Choosing a priority.
+    Priority,
+    /// Reviewing the answers.
+    Confirm,
+    /// Confirmed; the draft is ready.
+    Done,
+    /// Abandoned.
+    Cancelled,
+}
+
+/// # Epic Draft struct
+/// The answers collected by the epic wizard.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+struct EpicDraft {
+    /// The trimmed title; empty once the draft was taken.
+    title: String,
+    /// The description.
+    description: String,
+    /// The chosen labels.
+    labels: Vec<Uuid>,
+    /// The priority field and chosen option, if any.
+    priority: Option<(FieldId, FieldValue)>,
+}
+
+/// # `EpicWizardPage` struct
+/// Walks the user through creating an epic.
+pub struct EpicWizardPage {
+    /// The labels that can be chosen, by UUID and name.
+    labels: Vec<(Uuid, String)>,
+    /// The priority field and a picker over its options, if the database defines one.
+    priority_field: Option<(FieldId, Picker<Option<String>>)>,
+    /// Current step of the wizard.
+    step: WizardStep,
+    /// The answers so far.
+    draft: EpicDraft,
+    /// The description editor, once the user starts typing the description.
+    editor: Option<TextEditor>,
+    /// The user's own editor for the description, if one is set.
+    external: Option<ExternalEditor>,
+}
+
+impl EpicWizardPage {
+    /// Creates the wizard, offering the database's labels and priority field.
+    #[must_use]
+    pub fn new(state: &ClearTextDBState) -> Self {
+        Self {
+            labels: state.labels.iter().map(|label| (label.label_uuid, label.name.clone())).collect(),
+            priority_field: priority_field(state),
+            step: WizardStep::Title,
+            draft: EpicDraft::default(),
+            editor: None,
+            external: None,
+        }
+    }
+
+    /// Writes the description in `external` instead of the in-app editor, e.g. with
+    /// [`ExternalEditor::from_env`].
+    #[must_use]
+    pub fn with_external_editor(mut self, external: Option<ExternalEditor>) -> Self {
+        self.external = external;
+        self
+    }
+
+    /// Returns the current step of the wizard.
+    #[must_use]
+    pub fn step(&self) -> WizardStep {
+        self.step
+    }
+
+    /// The steps the wizard goes through, in order; the priority step only if there is a field.
+    fn steps(&self) -> Vec<WizardStep> {
+        let mut steps = vec![WizardStep::Title, WizardStep::Description, WizardStep::Labels];
+        if self.priority_field.is_some() {
+            steps.push(WizardStep::Priority);
+        }
+        steps.push(WizardStep::Confirm);
+        steps
+    }
+
+    /// The step before or after the current one.
+    fn neighbour(&self, forward: bool) -> WizardStep {
+        let steps = self.steps();
+        let step = self.step();
+        let index = steps.iter().position(|candidate| *candidate == step).unwrap_or_default();
+        let index = if forward { index + 1 } else { index.saturating_sub(1) };
+        steps.get(index).copied().unwrap_or(WizardStep::Done)
+    }
+
+    /// Writes the description in the external editor, if there is one, and moves past the step.
+    /// Without one, or if it fails, the step stays for the in-app editor.
+    fn open_description(&mut self) -> Result<WizardStep, String> {
+        self.step = WizardStep::Description;
+        let Some(external) = &self.external else { return Ok(WizardStep::Description) };
+        let text = external.edit(&self.draft.description).map_err(|err| tr_with("editor-failed", &[("error", &err)]))?;
+        validate_description(&text).map_err(|err| err.to_string())?;
+        self.draft.description = text;
+        Ok(self.neighbour(true))
+    }
+
+    /// Passes input to the description editor. Saving records the description and moves on;
+    /// cancelling throws the changes away and goes back a step.
+    fn edit_description(&mut self, input: &str) -> Result<WizardStep, String> {
+        let editor = self.editor.get_or_insert_with(|| TextEditor::new(&self.draft.description));
+        let forward = match editor.handle_input(input)? {
+            EditorOutcome::Editing => return Ok(WizardStep::Description),
+            EditorOutcome::Saved(text) => {
+                validate_description(&text).map_err(|err| err.to_string())?;
+                self.draft.description = text;
+                true
+            }
+            EditorOutcome::Cancelled => false,
+        };
+        Ok(self.neighbour(forward))
+    }
+
+    /// Records the answer for the current step.
+    fn answer(&mut self, input: &str) -> Result<WizardStep, String> {
+        let draft = &mut self.draft;
+        match self.step {
+            WizardStep::Title => draft.title = validate_title(input).map_err(|err| err.to_string())?,
+            WizardStep::Labels => {
+                draft.labels = input
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|name| !name.is_empty())
+                    .map(|name| {
+                        self.labels.iter().find(|(_, label)| label.eq_ignore_ascii_case(name)).map(|(label_uuid, _)| *label_uuid).ok_or_else(|| tr_with("no-such-label", &[("name", &name)]))
+                    })
+                    .collect::<Result<_, _>>()?;
+            }
+            WizardStep::Priority => {
+                let Some((field_id, picker)) = &mut self.priority_field else { return Ok(self.neighbour(true)) };
+                match picker.handle_input(input)? {
+                    Picked::Chosen(option) => draft.priority = option.map(|option| (*field_id, FieldValue::Enum(option))),
+                    Picked::Browsing => return Ok(WizardStep::Priority),
+                    Picked::Cancelled => return Ok(self.neighbour(false)),
+                }
+            }
+            WizardStep::Confirm => match input.trim() {
+                "y" | "yes" => (),
+                _ => return Err(tr("epic-wizard-confirm-error")),
+            },
+            WizardStep::Description | WizardStep::Done | WizardStep::Cancelled => return Ok(self.step()),
+        }
+        Ok(self.neighbour(true))
+    }
+
+    /// The summary of the answers shown at the confirmation step.
+    fn summary(&self) -> Vec<String> {
+        let draft = &self.draft;
+        let labels: Vec<&str> = draft.labels.iter().filter_map(|uuid| self.labels.iter().find(|(label_uuid, _)| label_uuid == uuid)).map(|(_, name)| name.as_str()).collect();
+        let mut lines = vec![
+            tr_with("summary-title", &[("value", &draft.title)]),
+            tr_with("summary-description", &[("value", &draft.description)]),
+            tr_with("summary-labels", &[("value", &labels.join(", "))]),
+        ];
+        if self.priority_field.is_some() {
+            lines.push(tr_with("summary-priority", &[("value", &draft.priority.as_ref().map(|(_, value)| value.to_string()).unwrap_or_default())]));
+        }
+        lines
+    }
+}
+
+impl Page for EpicWizardPage {
+    fn render(&self, size: Size) -> Vec<String> {
+        let mut lines = vec![tr("epic-wizard-title")];
+        let step = self.step();
+        let steps = self.steps();
+        if let Some(index) = steps.iter().position(|candidate| *candidate == step) {
+            lines.push(tr_with("wizard-step", &[("step", &(index + 1)), ("steps", &steps.len())]));
+        }
+        match step {
+            WizardStep::Title => lines.push(tr("wizard-title")),
+            WizardStep::Description => {
+                lines.push(tr("wizard-description"));
+                lines.extend(self.editor.as_ref().map_or_else(|| TextEditor::new(&self.draft.description).render(size), |editor| editor.render(size)));
+            }
+            WizardStep::Labels => {
+                let names: Vec<&str> = self.labels.iter().map(|(_, name)| name.as_str()).collect();
+                lines.push(tr_with("epic-wizard-labels", &[("labels", &if names.is_empty() { tr("epic-wizard-no-labels") } else { names.join(", ") })]));
+            }
+            WizardStep::Priority => {
+                lines.push(tr("wizard-priority"));
+                lines.extend(self.priority_field.iter().flat_map(|(_, picker)| picker.render()));
+            }
+            WizardStep::Confirm => {
+                lines.extend(self.summary());
+                lines.push(tr("epic-wizard-confirm"));
+            }
+            WizardStep::Done => lines.push(tr("epic-wizard-done")),
+            WizardStep::Cancelled => lines.push(tr("cancelled")),
+        }
+        if !matches!(step, WizardStep::Done | WizardStep::Cancelled) {
+            lines.push(tr("back-cancel-hint"));
+        }
+        lines
+    }
+
+    fn handle_input(&mut self, input: &str) -> PageAction {
+        if matches!(self.step(), WizardStep::Done | WizardStep::Cancelled) {
+            return PageAction::None;
+        }
+        let next = match input.trim() {
+            "<" => Ok(self.neighbour(false)),
+            "cancel" => Ok(WizardStep::Cancelled),
+            _ if self.step == WizardStep::Description => self.edit_description(input),
+            _ => self.answer(input),
+        }
+        .and_then(|next| if next == WizardStep::Description && self.step != next { self.open_description() } else { Ok(next) });
+        match next {
+            Ok(next) => {
+                if next != WizardStep::Description {
+                    self.editor = None;
+                }
+                self.step = next;
+                match next {
+                    WizardStep::Done => PageAction::Refresh,
+                    WizardStep::Cancelled => PageAction::Pop,
+                    _ => PageAction::None,
+                }
+            }
+            Err(error) => PageAction::ShowError(error),
+        }
+    }
+
+    fn take_command(&mut self) -> Option<Command> {
+        if self.step() != WizardStep::Done || self.draft.title.is_empty() {
+            return None;
+        }
+        let EpicDraft { title, description, labels, priority } = std::mem::take(&mut self.draft);
+        Some(Command::CreateEpic { title, description, labels, priority })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::service::{custom_fields::define_field, get_epic, labels::create_label};
+    use crate::users::User;
+
+    #[test]
+    fn test_epic_wizard() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let infra = create_label(&mut state, "infra".to_string(), "#0000ff".to_string()).unwrap();
+        let priority = define_field(&mut state, "priority".to_string(), FieldKind::Enum(vec!["Low".to_string(), "High".to_string()])).unwrap();
+        let mut page = EpicWizardPage::new(&state);
+
+        assert!(matches!(page.handle_input("   "), PageAction::ShowError(error) if error == "The title must not be empty"));
+        assert_eq!(page.render(Size::default())[1..3], ["Step 1 of 5".to_string(), "Title:".to_string()]);
+        page.handle_input(" Launch ");
+        page.handle_input("Ship v1");
+        page.handle_input(":w");
+        page.handle_input("<");
+        assert_eq!(page.step(), WizardStep::Description);
+        assert_eq!(page.render(Size::default())[2..5], ["Description (optional):", "  1 | Ship v1", "    >"]);
+        page.handle_input(":c 1 Ship v1.0");
+        page.handle_input(":w");
+        assert!(matches!(page.handle_input("infra, docs"), PageAction::ShowError(error) if error == "There is no label \"docs\"."));
+        page.handle_input("INFRA");
+        assert!(matches!(page.handle_input("urgent"), PageAction::ShowError(error) if error == "There is no priority \"urgent\"."));
+        assert_eq!(page.render(Size::default())[2..6], ["Priority:", "> 1. None", "  2. Low", "  3. High"]);
+        page.handle_input("j");
+        page.handle_input("j");
+        page.handle_input("");
+        assert_eq!(page.render(Size::default())[2..6], ["Title: Launch".to_string(), "Description: Ship v1.0".to_string(), "Labels: infra".to_string(), "Priority: High".to_string()]);
+        assert_eq!(page.take_command(), None);
+        assert!(matches!(page.handle_input("y"), PageAction::Refresh));
+
+        let epic_uuid = page.take_command().unwrap().apply(&mut state, &SystemClock).unwrap().unwrap();
+        assert_eq!(page.take_command(), None);
+        let epic = get_epic(&state, epic_uuid).unwrap();
+        assert_eq!((epic.title.as_str(), epic.description.as_str(), epic.labels.clone()), ("Launch", "Ship v1.0", vec![infra]));
+        assert_eq!(epic.custom_fields.get(&priority), Some(&FieldValue::Enum("High".to_string())));
+
+        let mut page = EpicWizardPage::new(&ClearTextDBState::new(User::new("bob".to_string(), "password").unwrap()));
+        page.handle_input("Docs");
+        assert!(matches!(page.handle_input("cancel"), PageAction::Pop));
+        assert_eq!(page.step(), WizardStep::Cancelled);
+        assert_eq!(page.take_command(), None);
+    }
+}
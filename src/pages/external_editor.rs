@@ -0,0 +1,143 @@
+//! # External Editor
+//! Lets long text, such as descriptions, be written in the user's own editor (`$VISUAL` or
+//! `$EDITOR`) instead of the in-app [`TextEditor`](super::text_editor::TextEditor), which pages
+//! fall back to when neither is set.
+//!
+//! The text is decrypted data, so it is handed over in a temporary file inside a new directory
+//! only the user can read. When the editor exits, every file in that directory (including any
+//! swap or backup files the editor left) is overwritten with zeros before it is deleted.
+
+use std::fs::{self, DirBuilder, File, OpenOptions};
+use std::io::{self, Write as _};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use uuid::Uuid;
+
+/// # `ExternalEditor` struct
+/// The command that opens an editor, e.g. `vim` or `code --wait`. The file to edit is passed as
+/// the last argument.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ExternalEditor {
+    /// The program to run.
+    program: String,
+    /// The arguments before the file name.
+    args: Vec<String>,
+}
+
+impl ExternalEditor {
+    /// Creates an editor command from a program and its arguments.
+    #[must_use]
+    pub fn new(program: impl Into<String>, args: Vec<String>) -> Self {
+        Self { program: program.into(), args }
+    }
+
+    /// Reads the editor from `$VISUAL`, then `$EDITOR`, splitting it at whitespace.
+    ///
+    /// # Returns
+    /// * `Option<ExternalEditor>` - The editor, or `None` if neither variable is set to anything,
+    ///   in which case the in-app editor is used.
+    #[must_use]
+    pub fn from_env() -> Option<Self> {
+        ["VISUAL", "EDITOR"].into_iter().find_map(|name| Self::parse(&std::env::var(name).ok()?))
+    }
+
+    /// Splits a command line such as `code --wait` into the program and its arguments.
+    fn parse(command: &str) -> Option<Self> {
+        let mut words = command.split_whitespace().map(str::to_string);
+        let program = words.next()?;
+        Some(Self { program, args: words.collect() })
+    }
+
+    /// Opens `text` in the editor and waits for it to exit.
+    ///
+    /// # Returns
+    /// * `io::Result<String>` - The edited text, without trailing whitespace.
+    ///
+    /// # Errors
+    /// * `io::Error` - If the temporary file can't be written or read, or the editor can't be
+    ///   started or exits with an error. The temporary files are shredded either way.
+    pub fn edit(&self, text: &str) -> io::Result<String> {
+        let scratch = Scratch::create()?;
+        let path = scratch.0.join("ironyyy-edit.md");
+        private_file(&path)?.write_all(text.as_bytes())?;
+        let status = Command::new(&self.program).args(&self.args).arg(&path).status()?;
+        if !status.success() {
+            return Err(io::Error::other(format!("{} exited with {status}", self.program)));
+        }
+        Ok(fs::read_to_string(&path)?.trim_end().to_string())
+    }
+}
+
+/// # Scratch struct
+/// A private temporary directory, shredded and removed when dropped.
+struct Scratch(PathBuf);
+
+impl Scratch {
+    /// Creates a new directory under the system's temporary directory that only the user can
+    /// open.
+    fn create() -> io::Result<Self> {
+        let path = std::env::temp_dir().join(format!("ironyyy-edit-{}", Uuid::new_v4()));
+        let mut builder = DirBuilder::new();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::DirBuilderExt;
+            builder.mode(0o700);
+        }
+        builder.create(&path)?;
+        Ok(Self(path))
+    }
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        if let Ok(entries) = fs::read_dir(&self.0) {
+            for entry in entries.flatten() {
+                let _ = shred(&entry.path());
+            }
+        }
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Creates a file only the user can read or write, failing if it already exists.
+fn private_file(path: &Path) -> io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)
+}
+
+/// Overwrites a file with zeros, flushes it to disk, and deletes it.
+fn shred(path: &Path) -> io::Result<()> {
+    if path.is_file() {
+        let mut file = OpenOptions::new().write(true).open(path)?;
+        let length = usize::try_from(file.metadata()?.len()).unwrap_or(usize::MAX);
+        file.write_all(&vec![0; length])?;
+        file.sync_all()?;
+    }
+    fs::remove_file(path)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_shreds_the_temporary_file() {
+        let log = std::env::temp_dir().join(format!("ironyyy-edit-log-{}", Uuid::new_v4()));
+        let script = format!("printf ' and signup\\n' >> \"$1\"; echo \"$1\" > {}", log.display());
+        let editor = ExternalEditor::new("sh", vec!["-c".to_string(), script, "sh".to_string()]);
+        assert_eq!(editor.edit("Login").unwrap(), "Login and signup");
+        let path = PathBuf::from(fs::read_to_string(&log).unwrap().trim());
+        assert!(!path.exists() && !path.parent().unwrap().exists());
+        fs::remove_file(&log).unwrap();
+
+        assert!(ExternalEditor::new("false", Vec::new()).edit("Login").is_err());
+        assert_eq!(ExternalEditor::parse("code  --wait"), Some(ExternalEditor::new("code", vec!["--wait".to_string()])));
+        assert_eq!(ExternalEditor::parse("  "), None);
+    }
+}
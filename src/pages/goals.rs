@@ -0,0 +1,81 @@
+//! # Goals Page
+//! Lists the database's goals with their key results and progress.
+
+use super::i18n::{tr, tr_with};
+use super::keybindings::{KeyContext, KeyMap};
+use super::{Page, PageAction, Size, global_action};
+use crate::db::ClearTextDBState;
+use crate::models::epics::Progress;
+use crate::models::goals::Goal;
+use crate::service::goals::goal_progress;
+
+/// # `GoalsPage` struct
+/// A read-only overview of every goal, with progress derived from its linked epics.
+pub struct GoalsPage {
+    /// Each goal with its progress.
+    goals: Vec<(Goal, Progress)>,
+}
+
+impl GoalsPage {
+    /// Creates the page from the current database state.
+    #[must_use]
+    pub fn new(state: &ClearTextDBState) -> Self {
+        let goals = state
+            .goals
+            .iter()
+            .map(|goal| (goal.clone(), goal_progress(state, goal.goal_uuid).unwrap_or_default()))
+            .collect();
+        Self { goals }
+    }
+}
+
+impl Page for GoalsPage {
+    fn reads_text(&self) -> bool {
+        false
+    }
+
+    fn render(&self, _size: Size) -> Vec<String> {
+        let mut lines = vec![tr("goals-title")];
+        if self.goals.is_empty() {
+            lines.push(tr("goals-empty"));
+        }
+        for (goal, progress) in &self.goals {
+            let goal_line = match goal.target_date {
+                Some(date) => tr_with("goal-target", &[("objective", &goal.objective), ("date", &date)]),
+                None => goal.objective.clone(),
+            };
+            lines.push(tr_with("goal-progress", &[("goal", &goal_line), ("progress", progress)]));
+            lines.extend(goal.key_results.iter().map(|key_result| format!("  - {key_result}")));
+        }
+        lines
+    }
+
+    fn handle_input(&mut self, input: &str) -> PageAction {
+        global_action(&KeyMap::active(), KeyContext::Global, input).unwrap_or(PageAction::None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::service::epics::create_epic;
+    use crate::service::goals::{add_key_result, create_goal, link_epic_to_goal};
+    use crate::users::User;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_render_goals() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        assert_eq!(GoalsPage::new(&state).render(Size::default())[1], "No goals yet.");
+
+        let goal_uuid = create_goal(&mut state, "Grow signups", NaiveDate::from_ymd_opt(2025, 6, 30)).unwrap();
+        add_key_result(&mut state, goal_uuid, "1,000 new accounts").unwrap();
+        let epic_uuid = create_epic(&mut state, "Launch", String::new(), &SystemClock).unwrap();
+        link_epic_to_goal(&mut state, goal_uuid, epic_uuid).unwrap();
+        assert_eq!(
+            GoalsPage::new(&state).render(Size::default()),
+            ["=== Goals ===", "Grow signups (by 2025-06-30)  0/1 (0%) epics done", "  - 1,000 new accounts"]
+        );
+    }
+}
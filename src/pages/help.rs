@@ -0,0 +1,85 @@
+//! # Help Page
+//! Lists the keybindings, generated from the active [`KeyMap`]: the global ones, then those of
+//! the page the help was opened from, then every other page's.
+
+use super::i18n::{tr, tr_with};
+use super::{Page, PageAction, Size};
+use super::keybindings::{KeyContext, KeyMap};
+
+/// # `HelpPage` struct
+/// A read-only list of keybindings.
+pub struct HelpPage {
+    /// The formatted lines.
+    lines: Vec<String>,
+}
+
+impl HelpPage {
+    /// Creates the help page for the page it was opened from, if any.
+    #[must_use]
+    pub fn new(keys: &KeyMap, current: Option<KeyContext>) -> Self {
+        let first = [Some(KeyContext::Global), current.filter(|context| *context != KeyContext::Global)];
+        let rest = KeyContext::ALL.into_iter().filter(|context| !first.contains(&Some(*context)));
+        let contexts: Vec<KeyContext> = first.into_iter().flatten().chain(rest).collect();
+        let mut lines = Vec::new();
+        for context in contexts {
+            let bindings: Vec<String> = keys
+                .bindings(context)
+                .map(|binding| {
+                    let key = match binding.action.argument() {
+                        Some(argument) => format!("{} {argument}", binding.key),
+                        None => binding.key.clone(),
+                    };
+                    format!("  {key:<14} {}", binding.action.description())
+                })
+                .collect();
+            if bindings.is_empty() {
+                continue;
+            }
+            let title = if Some(context) == current { tr_with("help-this-page", &[("context", &context.title())]) } else { context.title() };
+            lines.push(format!("--- {title} ---"));
+            lines.extend(bindings);
+        }
+        Self { lines }
+    }
+}
+
+impl Page for HelpPage {
+    fn render(&self, _size: Size) -> Vec<String> {
+        let mut lines = vec![tr("help-title")];
+        lines.extend(self.lines.iter().cloned());
+        lines.push(tr("help-numbers"));
+        lines
+    }
+
+    fn handle_input(&mut self, _input: &str) -> PageAction {
+        PageAction::Pop
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_help_lists_current_page_first() {
+        let lines = HelpPage::new(&KeyMap::default(), Some(KeyContext::StoryDetail)).render(Size::default());
+        assert_eq!(
+            lines[1..11],
+            [
+                "--- Everywhere ---",
+                "  ?              help",
+                "  q              back",
+                "  ]              forward",
+                "  Q              quit",
+                "  !              notifications",
+                "  Z              lock",
+                "  ^Z             undo",
+                "  ^Y             redo",
+                "  g <1-6>        switch tab",
+            ]
+            .map(str::to_string)
+        );
+        assert_eq!(lines[11..13], ["--- Story detail (this page) ---", "  t <status>     change status"].map(str::to_string));
+        assert_eq!(lines.iter().filter(|line| line.starts_with("---")).count(), KeyContext::ALL.len());
+    }
+}
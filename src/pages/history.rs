@@ -0,0 +1,90 @@
+//! # History Page
+//! Shows the timeline of changes made to an epic or story.
+
+use super::i18n::{tr, tr_with};
+use super::keybindings::{KeyContext, KeyMap};
+use super::{Page, PageAction, Size, global_action};
+use crate::db::ClearTextDBState;
+use crate::models::history::HistoryEntry;
+use crate::service::{ServiceError, get_epic, get_story, history::timeline};
+use uuid::Uuid;
+
+/// # `HistoryPage` struct
+/// A read-only view of one item's change history, oldest change first.
+pub struct HistoryPage {
+    /// The title of the epic or story.
+    title: String,
+    /// The item's history entries.
+    entries: Vec<HistoryEntry>,
+}
+
+impl HistoryPage {
+    /// Creates a history page for an epic or story.
+    ///
+    /// # Errors
+    /// * `ServiceError::StoryNotFound` - If no epic or story has the given UUID.
+    pub fn new(state: &ClearTextDBState, item_uuid: Uuid) -> Result<Self, ServiceError> {
+        let title = match get_epic(state, item_uuid) {
+            Ok(epic) => epic.title.clone(),
+            Err(_) => get_story(state, item_uuid)?.title.clone(),
+        };
+        let entries = timeline(state, item_uuid).into_iter().cloned().collect();
+        Ok(Self { title, entries })
+    }
+}
+
+impl Page for HistoryPage {
+    fn reads_text(&self) -> bool {
+        false
+    }
+
+    fn render(&self, _size: Size) -> Vec<String> {
+        let mut lines = vec![tr_with("history-title", &[("title", &self.title)])];
+        if self.entries.is_empty() {
+            lines.push(tr("history-empty"));
+        }
+        for entry in &self.entries {
+            lines.push(format!(
+                "{}  {}: {} -> {}",
+                entry.timestamp.format("%Y-%m-%d %H:%M:%S"),
+                entry.field,
+                display_value(&entry.old_value),
+                display_value(&entry.new_value),
+            ));
+        }
+        lines
+    }
+
+    fn handle_input(&mut self, input: &str) -> PageAction {
+        global_action(&KeyMap::active(), KeyContext::Global, input).unwrap_or(PageAction::None)
+    }
+}
+
+/// Shows empty values as `(none)` so they stand out in the timeline.
+pub(super) fn display_value(value: &str) -> String {
+    if value.is_empty() { tr("value-none") } else { value.to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::models::{Status, stories::Story};
+    use crate::service::stories::set_story_status;
+    use crate::users::User;
+
+    #[test]
+    fn test_render_timeline() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let story = Story::new("Write docs".to_string(), String::new());
+        let story_uuid = story.story_uuid;
+        state.stories.push(story);
+        assert_eq!(HistoryPage::new(&state, story_uuid).unwrap().render(Size::default())[1], "No changes recorded yet.");
+
+        set_story_status(&mut state, story_uuid, Status::Closed, &SystemClock).unwrap();
+        let lines = HistoryPage::new(&state, story_uuid).unwrap().render(Size::default());
+        assert_eq!(lines[0], "=== History: Write docs ===");
+        assert!(lines[1].ends_with("status: Open -> Closed"));
+        assert!(HistoryPage::new(&state, Uuid::new_v4()).is_err());
+    }
+}
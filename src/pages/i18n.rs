@@ -0,0 +1,384 @@
+//! # Localization
+//! Every user-facing string of the pages comes from a message catalog rather than the code, so
+//! the pages can be shown in the language chosen in the [settings](crate::models::settings::Locale).
+//! Catalogs are written in a subset of [Fluent](https://projectfluent.org) syntax, one file per
+//! language under `src/pages/i18n/`:
+//!
+//! ```text
+//! # Comments start with a hash.
+//! page-footer = Page { $page } of { $pages }
+//! epics-marked =
+//!     { $count ->
+//!         [0] No epics marked
+//!         [one] One epic marked
+//!        *[other] { $count } epics marked
+//!     }
+//! ```
+//!
+//! A message is text with `{ $name }` placeholders, or a choice between variants on a variable:
+//! an exact match of the value comes first, then, for numbers, the locale's plural category
+//! (`zero`, `one`, `two`, `few`, `many`, or `other`), then the variant marked with `*`.
+//!
+//! The locale is kept per thread: the main loop calls [`set_locale`] when the database is opened
+//! and whenever the setting changes, and pages call [`tr`] and [`tr_with`] as they render.
+
+use crate::models::settings::Locale;
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+/// The English catalog, which every locale falls back to.
+const ENGLISH: &str = include_str!("i18n/en.ftl");
+
+thread_local! {
+    /// The locale the pages on this thread are shown in.
+    static LOCALE: Cell<Locale> = const { Cell::new(Locale::English) };
+}
+
+/// Shows the pages rendered on this thread from now on in `locale`.
+pub fn set_locale(locale: Locale) {
+    LOCALE.with(|current| current.set(locale));
+}
+
+/// The locale the pages on this thread are shown in.
+#[must_use]
+pub fn locale() -> Locale {
+    LOCALE.with(Cell::get)
+}
+
+/// The message `id` in the current locale.
+///
+/// # Examples
+/// ```rust
+/// use ironyyy::pages::i18n::tr;
+/// assert_eq!(tr("cancelled"), "Cancelled.");
+/// assert_eq!(tr("no-such-message"), "no-such-message");
+/// ```
+#[must_use]
+pub fn tr(id: &str) -> String {
+    tr_with(id, &[])
+}
+
+/// The message `id` in the current locale, with its placeholders filled in from `args`.
+///
+/// # Examples
+/// ```rust
+/// use ironyyy::pages::i18n::tr_with;
+/// assert_eq!(tr_with("page-footer", &[("page", &1), ("pages", &2)]), "Page 1 of 2");
+/// assert_eq!(tr_with("reminders-overdue", &[("count", &1)]), "1 item overdue");
+/// assert_eq!(tr_with("reminders-overdue", &[("count", &3)]), "3 items overdue");
+/// ```
+#[must_use]
+pub fn tr_with(id: &str, args: &[(&str, &dyn Display)]) -> String {
+    let locale = locale();
+    catalog(locale).format(locale, id, args)
+}
+
+/// The catalog of `locale`.
+///
+/// # Panics
+/// If the built-in English catalog can't be parsed, which the tests rule out.
+#[must_use]
+pub fn catalog(locale: Locale) -> &'static Catalog {
+    /// The parsed English catalog.
+    static CATALOG: OnceLock<Catalog> = OnceLock::new();
+    match locale {
+        Locale::English | Locale::Pseudo => CATALOG.get_or_init(|| Catalog::parse(ENGLISH).unwrap_or_else(|error| panic!("The English catalog is invalid: {error}"))),
+    }
+}
+
+/// # Plural Category enum
+/// The [CLDR plural categories](https://cldr.unicode.org/index/cldr-spec/plural-rules) a number
+/// can fall into; each language uses some of them.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum PluralCategory {
+    /// E.g. 0 in Arabic.
+    Zero,
+    /// E.g. 1 in English.
+    One,
+    /// E.g. 2 in Welsh.
+    Two,
+    /// E.g. 2 to 4 in Polish.
+    Few,
+    /// E.g. 5 and up in Polish.
+    Many,
+    /// Every other number.
+    Other,
+}
+
+impl PluralCategory {
+    /// The category of `number` in `locale`.
+    #[must_use]
+    pub fn of(locale: Locale, number: i64) -> Self {
+        match locale {
+            Locale::English | Locale::Pseudo if number == 1 => PluralCategory::One,
+            Locale::English | Locale::Pseudo => PluralCategory::Other,
+        }
+    }
+
+    /// The category's name as written in variant keys, e.g. `one`.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            PluralCategory::Zero => "zero",
+            PluralCategory::One => "one",
+            PluralCategory::Two => "two",
+            PluralCategory::Few => "few",
+            PluralCategory::Many => "many",
+            PluralCategory::Other => "other",
+        }
+    }
+}
+
+/// # Element enum
+/// A piece of a message.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+enum Element {
+    /// Text shown as it is.
+    Text(String),
+    /// A `{ $name }` placeholder.
+    Variable(String),
+    /// A choice between variants on a variable.
+    Select {
+        /// The variable chosen on.
+        variable: String,
+        /// The variants' keys and messages.
+        variants: Vec<(String, Vec<Element>)>,
+        /// The index of the variant marked with `*`.
+        default: usize,
+    },
+}
+
+/// # Catalog struct
+/// The messages of one language, by ID.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Catalog {
+    /// The parsed messages.
+    messages: HashMap<String, Vec<Element>>,
+}
+
+impl Catalog {
+    /// Parses a catalog.
+    ///
+    /// # Errors
+    /// * `String` - The line and problem of the first message that can't be parsed.
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut messages = HashMap::new();
+        let mut lines = source.lines().enumerate().peekable();
+        while let Some((number, line)) = lines.next() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |problem: &str| format!("line {}: {problem}", number + 1);
+            let (id, value) = line.split_once('=').ok_or_else(|| error("expected `id = message`"))?;
+            let mut body = vec![value.trim().to_string()];
+            while let Some((_, next)) = lines.next_if(|(_, next)| next.starts_with(char::is_whitespace) && !next.trim().is_empty()) {
+                body.push(next.trim().to_string());
+            }
+            let body = body.join("\n");
+            let elements = Parser { chars: body.trim_start().chars().collect(), position: 0 }.pattern(false).map_err(|problem| error(&problem))?;
+            messages.insert(id.trim().to_string(), elements);
+        }
+        Ok(Self { messages })
+    }
+
+    /// Whether the catalog has the message `id`.
+    #[must_use]
+    pub fn contains(&self, id: &str) -> bool {
+        self.messages.contains_key(id)
+    }
+
+    /// Formats the message `id` for `locale` with `args`. A missing message comes out as its ID
+    /// and a missing argument as its placeholder, so a gap in a catalog shows rather than hides.
+    #[must_use]
+    pub fn format(&self, locale: Locale, id: &str, args: &[(&str, &dyn Display)]) -> String {
+        let mut text = String::new();
+        match self.messages.get(id) {
+            Some(elements) => format_elements(&mut text, elements, locale, args),
+            None => text.push_str(id),
+        }
+        text
+    }
+}
+
+/// Appends `elements` to `text`.
+fn format_elements(text: &mut String, elements: &[Element], locale: Locale, args: &[(&str, &dyn Display)]) {
+    let argument = |name: &str| args.iter().find(|(arg, _)| *arg == name).map(|(_, value)| value.to_string());
+    for element in elements {
+        match element {
+            Element::Text(part) if locale == Locale::Pseudo => text.push_str(&pseudo(part)),
+            Element::Text(part) => text.push_str(part),
+            Element::Variable(name) => text.push_str(&argument(name).unwrap_or_else(|| format!("{{${name}}}"))),
+            Element::Select { variable, variants, default } => {
+                let value = argument(variable).unwrap_or_default();
+                let category = value.parse::<i64>().ok().map(|number| PluralCategory::of(locale, number).name());
+                let variant = variants
+                    .iter()
+                    .find(|(key, _)| *key == value)
+                    .or_else(|| variants.iter().find(|(key, _)| Some(key.as_str()) == category))
+                    .unwrap_or(&variants[*default]);
+                format_elements(text, &variant.1, locale, args);
+            }
+        }
+    }
+}
+
+/// Accents the vowels of `text`, leaving what is in square brackets (keys to type) alone.
+fn pseudo(text: &str) -> String {
+    let mut depth = 0_usize;
+    text.chars()
+        .map(|char| {
+            match char {
+                '[' => depth += 1,
+                ']' => depth = depth.saturating_sub(1),
+                _ => (),
+            }
+            if depth > 0 {
+                return char;
+            }
+            match char {
+                'a' => 'á',
+                'e' => 'é',
+                'i' => 'í',
+                'o' => 'ó',
+                'u' => 'ú',
+                'A' => 'Á',
+                'E' => 'É',
+                'I' => 'Í',
+                'O' => 'Ó',
+                'U' => 'Ú',
+                other => other,
+            }
+        })
+        .collect()
+}
+
+/// # Parser struct
+/// Reads the body of one message.
+struct Parser {
+    /// The body's characters.
+    chars: Vec<char>,
+    /// The index of the next character.
+    position: usize,
+}
+
+impl Parser {
+    /// The next character, if any.
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    /// Skips `expected` if it comes next.
+    fn eat(&mut self, expected: &str) -> bool {
+        let matches = expected.chars().enumerate().all(|(offset, char)| self.chars.get(self.position + offset) == Some(&char));
+        if matches {
+            self.position += expected.chars().count();
+        }
+        matches
+    }
+
+    /// Skips spaces and line breaks.
+    fn skip_blank(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.position += 1;
+        }
+    }
+
+    /// Reads text and placeables up to the end of the body, or of the line if `line` is set.
+    fn pattern(&mut self, line: bool) -> Result<Vec<Element>, String> {
+        let mut elements = Vec::new();
+        let mut text = String::new();
+        while let Some(char) = self.peek() {
+            match char {
+                '\n' if line => break,
+                '{' => {
+                    self.position += 1;
+                    if !text.is_empty() {
+                        elements.push(Element::Text(std::mem::take(&mut text)));
+                    }
+                    elements.push(self.placeable()?);
+                }
+                '}' => return Err("unexpected `}`".to_string()),
+                _ => {
+                    text.push(char);
+                    self.position += 1;
+                }
+            }
+        }
+        if !text.trim_end().is_empty() {
+            elements.push(Element::Text(text.trim_end().to_string()));
+        }
+        Ok(elements)
+    }
+
+    /// Reads a placeable after its `{`: a variable, or a choice between variants on one.
+    fn placeable(&mut self) -> Result<Element, String> {
+        self.skip_blank();
+        if !self.eat("$") {
+            return Err("expected `$` and a variable name".to_string());
+        }
+        let start = self.position;
+        while self.peek().is_some_and(|char| char.is_alphanumeric() || char == '-' || char == '_') {
+            self.position += 1;
+        }
+        let variable: String = self.chars[start..self.position].iter().collect();
+        self.skip_blank();
+        if self.eat("}") {
+            return Ok(Element::Variable(variable));
+        }
+        if !self.eat("->") {
+            return Err(format!("expected `}}` or `->` after ${variable}"));
+        }
+        let mut variants = Vec::new();
+        let mut default = None;
+        loop {
+            self.skip_blank();
+            if self.eat("}") {
+                break;
+            }
+            if self.eat("*") {
+                default = Some(variants.len());
+            }
+            if !self.eat("[") {
+                return Err(format!("expected a variant of ${variable}"));
+            }
+            let start = self.position;
+            while self.peek().is_some_and(|char| char != ']' && char != '\n') {
+                self.position += 1;
+            }
+            let key: String = self.chars[start..self.position].iter().collect();
+            if !self.eat("]") {
+                return Err(format!("unclosed variant key `[{key}`"));
+            }
+            while self.peek() == Some(' ') {
+                self.position += 1;
+            }
+            variants.push((key.trim().to_string(), self.pattern(true)?));
+        }
+        let default = default.ok_or_else(|| format!("the variants of ${variable} need a default marked with `*`"))?;
+        Ok(Element::Select { variable, variants, default })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catalog_selects_variants_and_pseudo_localizes() {
+        let stories = Catalog::parse("# Stories\nstories =\n    { $count ->\n        [0] No stories\n        [one] One story in { $epic }\n       *[other] { $count } stories in { $epic }\n    }\nkeys = Press [enter] to go on.\n").unwrap();
+        let epic = "EPIC-1";
+        let format = |locale, count: i64| stories.format(locale, "stories", &[("count", &count), ("epic", &epic)]);
+        assert_eq!([format(Locale::English, 0), format(Locale::English, 1), format(Locale::English, 12)], ["No stories", "One story in EPIC-1", "12 stories in EPIC-1"]);
+        assert_eq!(format(Locale::Pseudo, 2), "2 stóríés ín EPIC-1");
+        assert_eq!(stories.format(Locale::Pseudo, "keys", &[]), "Préss [enter] tó gó ón.");
+        assert_eq!(stories.format(Locale::English, "stories", &[]), "{$count} stories in {$epic}");
+        assert_eq!(Catalog::parse("broken =\n    { $count ->\n        [one] One\n    }\n"), Err("line 1: the variants of $count need a default marked with `*`".to_string()));
+
+        set_locale(Locale::Pseudo);
+        assert_eq!(tr("cancelled"), "Cáncélléd.");
+        set_locale(Locale::English);
+        assert!(catalog(Locale::Pseudo).contains("page-footer"));
+    }
+}
@@ -0,0 +1,426 @@
+# English messages of the pages; every other locale falls back to these.
+# Keep the markers the theme colors lines by (`=== `, `--- `, `[key]`) where they are, and
+# leave text in square brackets alone: it is what the user types.
+
+## Shared
+
+cancelled = Cancelled.
+page-footer = Page { $page } of { $pages }
+unknown-command = Unknown command "{ $input }".
+back-cancel-hint = [<] back  [cancel] cancel
+editor-failed = The editor failed: { $error }
+value-none = (none)
+date-format = Write the date as YYYY-MM-DD.
+no-such-status = There is no status "{ $name }".
+no-such-priority = There is no priority "{ $name }".
+no-such-epic = There is no epic "{ $name }".
+no-such-label = There is no label "{ $name }".
+no-such-user = There is no user "{ $name }".
+no-such-line = There is no line "{ $name }".
+
+## Keybindings
+
+context-global = Everywhere
+context-dashboard = Dashboard
+context-epic-list = Epic list
+context-epic-detail = Epic detail
+context-story-detail = Story detail
+context-search = Search
+context-board = Board
+context-archived = Archived items
+context-calendar = Calendar
+action-help = help
+action-back = back
+action-forward = forward
+action-quit = quit
+action-notifications = notifications
+action-lock = lock
+action-undo = undo
+action-redo = redo
+action-new-epic = new epic
+action-new-story = new story
+action-search = search
+action-dismiss-banner = dismiss reminders
+action-move-down = move down
+action-move-up = move up
+action-next-page = next page
+action-previous-page = previous page
+action-open = open
+action-archive = archive
+action-delete = delete
+action-add-story = add story
+action-change-status = change status
+action-edit = edit
+action-move-to-epic = move to epic
+action-previous-column = previous column
+action-next-column = next column
+action-move-card-left = move card left
+action-move-card-right = move card right
+action-filter = filter
+action-toggle-mark = mark
+action-sort = sort
+action-earlier = earlier
+action-later = later
+action-toggle-week = week/month
+action-today = today
+action-switch-tab = switch tab
+argument-status = <status>
+argument-epic = <EPIC-n>
+argument-text = <text>
+argument-sort = <status|priority|due>
+argument-tab = <1-6>
+
+## Help
+
+help-title = === Help ===
+help-this-page = { $context } (this page)
+help-numbers = Lists also accept an item's number to open it.
+
+## Picker
+
+picker-hint = [number] choose  [j] move down  [k] move up  [Enter] choose selected
+picker-reopen = { $status } (reopen)
+
+## Navigation
+
+nav-no-forward = There is no page to go forward to.
+nav-sign-in-first = Log in to open this page.
+nav-no-such-tab = There is no tab "{ $number }"; type g and a number from 1 to 6.
+tab-dashboard = Dashboard
+tab-epics = Epics
+tab-board = Board
+tab-calendar = Calendar
+tab-search = Search
+tab-settings = Settings
+tab-active = <{ $number } { $title }>
+tab-inactive = { $number } { $title }
+
+## Modals
+
+modal-confirm-hint = [y] yes  [n] no
+modal-confirm-error = Type y or n.
+modal-dismiss-hint = [Enter] close
+
+## Toasts
+
+notifications-title = === Notifications ===
+notifications-empty = No notifications yet.
+
+## Undo
+
+undo-done = Undid { $change }.
+redo-done = Redid { $change }.
+undo-nothing = There is nothing to undo.
+redo-nothing = There is nothing to redo.
+
+## Lock screen
+
+lock-title = === Locked ===
+lock-user = Logged in as { $username }
+lock-password = Password:
+second-factor-prompt = Enter the code from your authenticator, or a backup code:
+lock-unlocked = Unlocked.
+lock-security-key = Connect one of your security keys, then enter your password again.
+wrong-password = Wrong password.
+security-key-required = Connect one of your security keys to unlock this database.
+invalid-code = That code is not valid.
+
+## Text editor
+
+editor-range-reversed = { $first } comes after { $last }.
+editor-unknown-command = Unknown command ":{ $name }".
+editor-hint = [text] insert line  [:w] save  [:q] cancel  [:g N] go to  [:d N] delete  [:c N text] change
+
+## Lists
+
+filter-line = Filter: { $filter }
+sort-line = Sort: { $sort }
+unknown-sort = Unknown sort order "{ $sort }". Sort by status, priority, or due, or leave it empty for the default order.
+kind-epic = Epic
+kind-story = Story
+column-kind = Kind
+column-item = Item
+column-epic = Epic
+column-status = Status
+column-progress = Progress
+column-result = Result
+
+## Archived items
+
+archived-title = === Archived Items ===
+archived-empty = Nothing has been archived.
+archived-no-match = Nothing archived matches the filter.
+
+## Burndown
+
+burndown-title = === Burndown: { $sprint } ===
+burndown-not-started = The sprint hasn't started yet.
+velocity-title = === Velocity ===
+velocity-empty = No closed sprints yet.
+velocity-average = Average velocity (last { $sprints }): { $average } pt
+
+## Calendar
+
+calendar-month-title = === Calendar: { $month } ===
+calendar-week-title = === Calendar: week of { $date } ===
+calendar-empty = Nothing is due this month.
+calendar-milestone = ◆ { $item }
+calendar-hint = [number] open  { $hints }
+
+## Dashboard
+
+dashboard-title = === Dashboard ===
+dashboard-counts = { $total } ({ $todo } to do, { $in-progress } in progress, { $done } done)
+dashboard-epics = Epics: { $counts }
+dashboard-stories = Stories: { $counts }
+dashboard-by-status = --- Stories by status ---
+dashboard-overdue =
+    Overdue: { $epics ->
+        [one] { $epics } epic
+       *[other] { $epics } epics
+    }, { $stories ->
+        [one] { $stories } story
+       *[other] { $stories } stories
+    }
+dashboard-touched = Touched this week: { $count }
+dashboard-recent = --- Recently updated ---
+dashboard-no-stories = No stories yet.
+dashboard-epic-progress = --- Epic progress ---
+dashboard-quick-actions = --- Quick actions ---
+
+## Effort
+
+effort-title = === Over Estimate by More Than { $percent }% ===
+effort-empty = Everything is within its estimate.
+
+## Goals
+
+goals-title = === Goals ===
+goals-empty = No goals yet.
+goal-target = { $objective } (by { $date })
+goal-progress = { $goal }  { $progress } epics done
+
+## History and inbox
+
+history-title = === History: { $title } ===
+history-empty = No changes recorded yet.
+inbox-title = === Inbox ===
+inbox-empty = Nothing new on the items you watch.
+
+## Board
+
+board-title = === Board ===
+board-no-card = There is no card to move.
+board-no-column = There is no column there.
+board-no-epic = No epic
+
+## Reminders
+
+reminders-title = --- Reminders ---
+reminders-empty = Nothing is due soon.
+reminder-overdue = { $item }  overdue since { $date }
+reminder-due-today = { $item }  due today, { $date }
+reminder-due-soon = { $item }  due { $date }
+reminders-due-soon =
+    { $count ->
+        [one] { $count } item due soon
+       *[other] { $count } items due soon
+    }
+reminders-overdue =
+    { $count ->
+        [one] { $count } item overdue
+       *[other] { $count } items overdue
+    }
+reminders-overdue-and-due-soon =
+    { $overdue ->
+        [one] { $overdue } item overdue
+       *[other] { $overdue } items overdue
+    }, { $upcoming } due soon
+reminders-dismiss = ! { $text } (press x to dismiss)
+
+## Search
+
+search-title = === Search ===
+search-query = Search: { $query }
+search-prompt = Type to search epics and stories by ID, title, or text.
+search-no-matches = No matches.
+search-hint = [text] search  [number] open  [enter] open selected  { $hints }
+
+## Epic list
+
+epics-title = === Epics ===
+epics-empty = No epics yet. Press n to create one.
+epics-no-match = No epics match the filter.
+epics-confirm-delete =
+    Move { $count ->
+        [one] this epic
+       *[other] these { $count } epics
+    } to the trash?
+epics-hint = [number] open  { $hints }
+
+## Detail pages
+
+detail-title = === { $key } { $title } ===
+field-status = Status: { $value }
+field-progress = Progress: { $value }
+field-parent = Parent: { $value }
+field-epic = Epic: { $value }
+field-labels = Labels: { $value }
+field-start = Start: { $value }
+field-due = Due: { $value }
+field-assignee = Assignee: { $value }
+field-estimate = Estimate: { $value }
+section-description = --- Description ---
+section-stories = --- Stories ---
+section-links = --- Links ---
+section-checklist = --- Checklist { $summary } ---
+section-change-status = --- Change status ---
+epic-no-stories = No stories yet.
+column-number = #
+column-key = Key
+column-title = Title
+keep-status-hint = [cancel] keep the current status
+epic-detail-hint = [number] open story  { $hints }
+
+## Edit page
+
+edit-title = === Edit { $item } ===
+edit-field-title = Title
+edit-field-description = Description
+edit-field-start-date = Start date
+edit-field-due-date = Due date
+edit-field-line = { $number }. { $field }: { $value }
+edit-field-changed = { $number }. { $field }: { $value } *
+edit-removed = - { $field }: { $value }
+edit-added = + { $field }: { $value }
+edit-fields-hint = [number] edit field  [s] save  [cancel] cancel
+edit-description = Description:
+edit-currently = Currently: { $value }
+edit-new-title = New title:
+edit-new-start-date = New start date (YYYY-MM-DD, empty to clear):
+edit-new-due-date = New due date (YYYY-MM-DD, empty to clear):
+edit-changes = --- Changes ---
+edit-confirm = Save these changes? (y/n)
+edit-saved = Changes ready to save.
+edit-unchanged = Nothing has changed.
+
+## Wizards
+
+wizard-step = Step { $step } of { $steps }
+wizard-title = Title:
+wizard-description = Description (optional):
+wizard-priority = Priority:
+wizard-priority-none = None
+wizard-status = Status:
+wizard-epic = Epic:
+wizard-due-date = Due date (YYYY-MM-DD, optional):
+summary-title = Title: { $value }
+summary-description = Description: { $value }
+summary-labels = Labels: { $value }
+summary-priority = Priority: { $value }
+summary-epic = Epic: { $value }
+summary-status = Status: { $value }
+summary-due = Due: { $value }
+epic-wizard-title = === New Epic ===
+epic-wizard-labels = Labels, separated by commas (available: { $labels }):
+epic-wizard-no-labels = none
+epic-wizard-confirm = Create this epic? (y)
+epic-wizard-confirm-error = Type y to create the epic, < to go back, or cancel.
+epic-wizard-done = Epic ready to create.
+story-wizard-title = === New Story ===
+story-wizard-in-epic = In { $epic }
+story-wizard-no-epics = There are no epics yet. Create one first.
+story-wizard-choose-epic = Choose an epic by its number.
+story-wizard-confirm = Create this story? (y)
+story-wizard-confirm-error = Type y to create the story, < to go back, or cancel.
+story-wizard-done = Story ready to create.
+
+## Log in and register
+
+login-title = === Log In ===
+login-no-users = No users found. Register a new user first.
+login-choose-user = Choose a user by number or name:
+login-password = Password for { $username }:
+login-welcome = Welcome back, { $username }!
+read-database-failed = Could not read the database: { $error }
+write-database-failed = Could not write the database: { $error }
+register-title = === Register ===
+register-username = Choose a username:
+register-password = Choose a password (at least { $length } characters):
+register-confirm-password = Type the password again:
+register-passwords-differ = The passwords did not match. Choose a password again.
+register-offer-totp = Set up an authenticator app for two-factor login? (y/n)
+register-answer-yes-no = Please answer y or n.
+register-code-mismatch = That code did not match. Try again, or type `skip`.
+register-enter-code = Enter the 6-digit code from your authenticator to confirm, or `skip`:
+register-not-created = The account was not created.
+register-welcome = Welcome, { $username }! Your database has been created.
+backup-codes-intro = Write down these backup codes; each works once if you lose your authenticator:
+
+## Authenticator
+
+enrollment-title = === Set Up Authenticator ===
+enrollment-on = Two-factor login is on.
+enrollment-code-mismatch = That code did not match. Try again.
+enrollment-replaces = This replaces your current second factor and backup codes.
+enrollment-scan = Scan the QR code with your authenticator app, then enter the 6-digit code it shows:
+rotation-title = === Re-enroll Authenticator ===
+rotation-active = Your new authenticator is active. The old secret no longer works.
+rotation-code-mismatch = That code did not match the new secret. Try again.
+rotation-exported = QR code written to { $path }
+rotation-save-hint = Type `save <file.png|file.svg>` to write the QR code to an image file.
+rotation-enter-code = Enter the 6-digit code shown for the NEW entry to confirm:
+rotation-bad-extension = the file name must end in .png or .svg
+
+## Settings
+
+settings-title = === Settings ===
+settings-theme = Theme
+settings-locale = Language
+settings-full-screen = Full screen
+settings-accessible = Accessible mode
+settings-lock-after = Lock after
+settings-on = on
+settings-off = off
+settings-never = never
+settings-minutes-value =
+    { $minutes ->
+        [one] 1 minute
+       *[other] { $minutes } minutes
+    }
+settings-hint = Type a setting's number and a new value, e.g. [1 light]; on/off settings switch with the number alone.
+settings-on-off = Type on or off.
+settings-minutes = Give the minutes as a whole number; 0 never locks.
+settings-no-such-theme = There is no theme "{ $name }".
+settings-no-such-locale = There is no language "{ $name }".
+settings-password = Password
+settings-password-value = type [6] to change it
+settings-current-password = Current password:
+settings-new-password = New password (at least { $length } characters):
+settings-password-hint = Type cancel to keep the current password.
+settings-username = Username
+settings-password-changed = The password has been changed.
+settings-username-changed = You are now { $name }.
+
+## Full-screen interface
+
+tui-epics = Epics
+tui-stories = Stories
+tui-details = Details
+tui-no-epics = No epics yet.
+tui-hints = [Enter] open  [n] new  [e] edit  [Tab] next panel  [q] quit
+tui-page-hints = [Enter] send the line  [Esc] back
+tui-unavailable = This build has no full-screen interface; build it with the tui feature to use one.
+
+## Accessible output
+
+a11y-page = Page: { $title }
+a11y-section = Section: { $title }
+a11y-selected = Selected: { $row }
+a11y-keys = Keys: { $keys }
+a11y-key = { $key }: { $action }
+a11y-notice = Notice: { $message }
+a11y-cursor = Cursor
+a11y-line = Line { $number }: { $text }
+a11y-done = Done: { $item }
+a11y-to-do = To do: { $item }
@@ -0,0 +1,86 @@
+//! # Inbox Page
+//! Shows the latest changes to the epics and stories the user watches.
+
+use super::i18n::tr;
+use super::keybindings::{KeyContext, KeyMap};
+use super::{Page, PageAction, Size, global_action, history::display_value};
+use crate::db::ClearTextDBState;
+use crate::service::{get_epic, get_story, watchers::inbox};
+use uuid::Uuid;
+
+/// The number of changes the inbox shows.
+const INBOX_LIMIT: usize = 50;
+
+/// # `InboxPage` struct
+/// A read-only view of a user's inbox, newest change first.
+pub struct InboxPage {
+    /// Each change, already formatted as a line.
+    lines: Vec<String>,
+}
+
+impl InboxPage {
+    /// Creates the inbox page for a user from the current database state.
+    #[must_use]
+    pub fn new(state: &ClearTextDBState, user_uuid: Uuid) -> Self {
+        let lines = inbox(state, user_uuid, INBOX_LIMIT)
+            .into_iter()
+            .map(|entry| {
+                let item = match get_epic(state, entry.item_uuid) {
+                    Ok(epic) => format!("{} {}", epic.key(), epic.title),
+                    Err(_) => get_story(state, entry.item_uuid).map(|story| format!("{} {}", story.key(), story.title)).unwrap_or_default(),
+                };
+                format!(
+                    "{}  {item}  {}: {} -> {}",
+                    entry.timestamp.format("%Y-%m-%d %H:%M:%S"),
+                    entry.field,
+                    display_value(&entry.old_value),
+                    display_value(&entry.new_value),
+                )
+            })
+            .collect();
+        Self { lines }
+    }
+}
+
+impl Page for InboxPage {
+    fn reads_text(&self) -> bool {
+        false
+    }
+
+    fn render(&self, _size: Size) -> Vec<String> {
+        let mut lines = vec![tr("inbox-title")];
+        if self.lines.is_empty() {
+            lines.push(tr("inbox-empty"));
+        }
+        lines.extend(self.lines.iter().cloned());
+        lines
+    }
+
+    fn handle_input(&mut self, input: &str) -> PageAction {
+        global_action(&KeyMap::active(), KeyContext::Global, input).unwrap_or(PageAction::None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::models::Status;
+    use crate::service::{epics::create_epic, stories::{create_story, set_story_status}, watchers::watch_story};
+    use crate::users::User;
+
+    #[test]
+    fn test_render_inbox() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let me = state.user.user_uuid;
+        let epic_uuid = create_epic(&mut state, "Epic", String::new(), &SystemClock).unwrap();
+        let story_uuid = create_story(&mut state, epic_uuid, "Login", String::new(), &SystemClock).unwrap();
+        assert_eq!(InboxPage::new(&state, me).render(Size::default())[1], "Nothing new on the items you watch.");
+
+        watch_story(&mut state, story_uuid, me).unwrap();
+        set_story_status(&mut state, story_uuid, Status::Closed, &SystemClock).unwrap();
+        let lines = InboxPage::new(&state, me).render(Size::default());
+        assert_eq!(lines.len(), 2);
+        assert!(lines[1].ends_with("STORY-1 Login  status: Open -> Closed"));
+    }
+}
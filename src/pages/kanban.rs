@@ -0,0 +1,295 @@
+//! # Kanban Board Page
+//! Shows the active stories as cards in one column per status, in workflow order, grouped by
+//! epic within each column. The user moves a selection between cards and moves the selected card
+//! to the neighbouring column; each move is tried against the page's own copy of the database
+//! with [`set_story_status`], so the workflow and blockers are enforced and the board updates
+//! straight away. The caller applies the recorded moves to the real database as commands taken
+//! with [`Page::take_command`]; opening a card opens the story's route, and the board is rebuilt
+//! with [`PageAction::Refresh`] when it is shown again.
+
+use super::i18n::{tr, tr_with};
+use super::{Page, PageAction, PageExit, Size, global_action};
+use super::keybindings::{KeyAction, KeyContext, KeyMap};
+use crate::clock::Clock;
+use crate::db::ClearTextDBState;
+use crate::models::Status;
+use crate::nav::routes::Route;
+use crate::service::statuses::ordered_statuses;
+use crate::service::stories::set_story_status;
+use crate::service::undo::Command;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// The narrowest a column gets, in characters, including the gap after it; on a narrow terminal
+/// the board runs off the screen rather than becoming unreadable.
+pub const MIN_COLUMN_WIDTH: usize = 12;
+
+/// # Card struct
+/// A story on the board.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Card {
+    /// The story shown.
+    story_uuid: Uuid,
+    /// The epic the story belongs to, e.g. `EPIC-1 Launch`, which cards are grouped under.
+    group: String,
+    /// The card's text, e.g. `STORY-3 Login`.
+    label: String,
+}
+
+/// # Column struct
+/// The cards with one status.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Column {
+    /// The column's status.
+    status: Status,
+    /// The status's display name.
+    name: String,
+    /// The cards, grouped by epic in epic order, then in rank order.
+    cards: Vec<Card>,
+}
+
+/// # `KanbanPage` struct
+/// A board of the stories that are neither archived nor deleted.
+pub struct KanbanPage {
+    /// The page's copy of the database, with the moves made so far applied.
+    state: ClearTextDBState,
+    /// The columns, rebuilt after every move.
+    columns: Vec<Column>,
+    /// The index of the selected column.
+    column: usize,
+    /// The index of the selected card in its column.
+    card: usize,
+    /// The status changes made, oldest first, not yet taken by the caller.
+    moves: Vec<Command>,
+    /// The keybindings.
+    keys: KeyMap,
+    /// The clock moves are tried with.
+    clock: Arc<dyn Clock>,
+    /// Whether another page was shown over this one, which may have changed the stories.
+    hidden: bool,
+}
+
+impl KanbanPage {
+    /// Creates the board from the current database state.
+    #[must_use]
+    pub fn new(state: &ClearTextDBState, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            columns: build_columns(state),
+            state: state.clone(),
+            column: 0,
+            card: 0,
+            moves: Vec::new(),
+            keys: KeyMap::active(),
+            clock,
+            hidden: false,
+        }
+    }
+
+    /// The UUID of the selected story, if the selected column has any cards.
+    #[must_use]
+    pub fn selected(&self) -> Option<Uuid> {
+        self.columns.get(self.column)?.cards.get(self.card).map(|card| card.story_uuid)
+    }
+
+    /// Selects a column, keeping the card selection inside it.
+    fn select_column(&mut self, index: usize) {
+        let columns = &self.columns;
+        let index = index.min(columns.len().saturating_sub(1));
+        self.column = index;
+        self.card = self.card.min(columns.get(index).map_or(0, |column| column.cards.len().saturating_sub(1)));
+    }
+
+    /// Selects a card in the selected column.
+    fn select_card(&mut self, index: usize) {
+        let count = self.columns.get(self.column).map_or(0, |column| column.cards.len());
+        self.card = index.min(count.saturating_sub(1));
+    }
+
+    /// Moves the selected card to the column `offset` places away, if the workflow allows it.
+    fn move_card(&mut self, offset: isize) -> Result<(), String> {
+        let story_uuid = self.selected().ok_or_else(|| tr("board-no-card"))?;
+        let target = self.column.checked_add_signed(offset).filter(|target| *target < self.columns.len()).ok_or_else(|| tr("board-no-column"))?;
+        let status = self.columns[target].status;
+        let mut trial = self.state.clone();
+        set_story_status(&mut trial, story_uuid, status, self.clock.as_ref()).map_err(|err| err.to_string())?;
+        self.columns = build_columns(&trial);
+        self.state = trial;
+        self.column = target;
+        let position = self.columns[target].cards.iter().position(|card| card.story_uuid == story_uuid).unwrap_or(0);
+        self.card = position;
+        self.moves.push(Command::SetStoryStatus { story_uuid, status });
+        Ok(())
+    }
+}
+
+/// Lays the active stories out in one column per status, in workflow order.
+fn build_columns(state: &ClearTextDBState) -> Vec<Column> {
+    let mut placed = Vec::new();
+    for epic in state.epics.iter().filter(|epic| !epic.is_deleted()) {
+        let group = format!("{} {}", epic.key(), epic.title);
+        placed.extend(epic.story_uuids.iter().map(|story_uuid| (*story_uuid, group.clone())));
+    }
+    let orphans = state.stories.iter().filter(|story| !placed.iter().any(|(story_uuid, _)| *story_uuid == story.story_uuid));
+    let orphans: Vec<(Uuid, String)> = orphans.map(|story| (story.story_uuid, tr("board-no-epic"))).collect();
+    placed.extend(orphans);
+    let cards: Vec<(Status, Card)> = placed
+        .into_iter()
+        .filter_map(|(story_uuid, group)| {
+            let story = state.stories.iter().find(|story| story.story_uuid == story_uuid && !story.archived && !story.is_deleted())?;
+            Some((story.status, Card { story_uuid, group, label: format!("{} {}", story.key(), story.title) }))
+        })
+        .collect();
+    ordered_statuses(state)
+        .into_iter()
+        .map(|definition| Column {
+            status: definition.status,
+            name: definition.name.clone(),
+            cards: cards.iter().filter(|(status, _)| *status == definition.status).map(|(_, card)| card.clone()).collect(),
+        })
+        .collect()
+}
+
+/// The width of each column, including the gap after it, sharing the terminal's width equally.
+fn column_width(size: Size, count: usize) -> usize {
+    (size.columns() / count.max(1)).max(MIN_COLUMN_WIDTH)
+}
+
+/// Cuts or pads text to exactly `width` characters.
+fn fit(text: &str, width: usize) -> String {
+    let text: String = text.chars().take(width).collect();
+    format!("{text:<width$}")
+}
+
+impl KanbanPage {
+    /// Each column's heading, e.g. `Open (2)`, and its epic headings and cards, with a `>`
+    /// before the selected card.
+    fn column_lines(&self) -> Vec<(String, Vec<String>)> {
+        self.columns
+            .iter()
+            .enumerate()
+            .map(|(column_index, column)| {
+                let mut cells = Vec::new();
+                let mut group = None;
+                for (card_index, card) in column.cards.iter().enumerate() {
+                    if group != Some(&card.group) {
+                        cells.push(card.group.clone());
+                        group = Some(&card.group);
+                    }
+                    let selected = column_index == self.column && card_index == self.card;
+                    cells.push(format!("{} {}", if selected { '>' } else { ' ' }, card.label));
+                }
+                (format!("{} ({})", column.name, column.cards.len()), cells)
+            })
+            .collect()
+    }
+}
+
+impl Page for KanbanPage {
+    fn reads_text(&self) -> bool {
+        false
+    }
+
+    fn render(&self, size: Size) -> Vec<String> {
+        let width = column_width(size, self.columns.len());
+        let cells: Vec<Vec<String>> = self.column_lines().into_iter().map(|(heading, cards)| [heading, "-".repeat(width - 2)].into_iter().chain(cards).collect()).collect();
+        let height = cells.iter().map(Vec::len).max().unwrap_or(0);
+        let mut lines = vec![tr("board-title")];
+        lines.extend((0..height).map(|row| {
+            let line: String = cells.iter().map(|column| fit(column.get(row).map_or("", String::as_str), width - 1) + " ").collect();
+            line.trim_end().to_string()
+        }));
+        lines.push(self.keys.hints(KeyContext::Board));
+        lines
+    }
+
+    fn render_linear(&self, _size: Size) -> Vec<String> {
+        let mut lines = vec![tr("board-title")];
+        for (heading, cards) in self.column_lines() {
+            lines.push(format!("--- {heading} ---"));
+            lines.extend(cards);
+        }
+        lines.push(self.keys.hints(KeyContext::Board));
+        lines
+    }
+
+    fn handle_input(&mut self, input: &str) -> PageAction {
+        match self.keys.lookup(KeyContext::Board, input).map(|(action, _)| action) {
+            Some(KeyAction::PreviousColumn) => self.select_column(self.column.saturating_sub(1)),
+            Some(KeyAction::NextColumn) => self.select_column(self.column.saturating_add(1)),
+            Some(KeyAction::MoveDown) => self.select_card(self.card.saturating_add(1)),
+            Some(KeyAction::MoveUp) => self.select_card(self.card.saturating_sub(1)),
+            Some(KeyAction::MoveCardLeft) => return self.move_card(-1).map_or_else(PageAction::ShowError, |()| PageAction::Refresh),
+            Some(KeyAction::MoveCardRight) => return self.move_card(1).map_or_else(PageAction::ShowError, |()| PageAction::Refresh),
+            Some(KeyAction::Open) => return self.selected().map_or(PageAction::None, |story_uuid| PageAction::Open(Route::Story(story_uuid.to_string()))),
+            _ => return global_action(&self.keys, KeyContext::Board, input).unwrap_or_else(|| PageAction::ShowError(tr_with("unknown-command", &[("input", &input.trim())]))),
+        }
+        PageAction::None
+    }
+
+    fn on_enter(&mut self) -> PageAction {
+        if std::mem::take(&mut self.hidden) { PageAction::Refresh } else { PageAction::None }
+    }
+
+    fn on_exit(&mut self, exit: PageExit) {
+        self.hidden = exit == PageExit::Hidden;
+    }
+
+    /// Takes the oldest move, since moves must be applied in order.
+    fn take_command(&mut self) -> Option<Command> {
+        (!self.moves.is_empty()).then(|| self.moves.remove(0))
+    }
+
+    fn reload(&mut self, state: &ClearTextDBState) {
+        let selected = self.selected();
+        self.state = state.clone();
+        self.columns = build_columns(state);
+        let found = self.columns.iter().enumerate().find_map(|(index, column)| column.cards.iter().position(|card| Some(card.story_uuid) == selected).map(|card| (index, card)));
+        match found {
+            Some((column, card)) => (self.column, self.card) = (column, card),
+            None => self.select_column(self.column),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::service::{epics::create_epic, stories::create_story};
+    use crate::users::User;
+
+    #[test]
+    fn test_board_moves_cards_through_the_workflow() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let launch = create_epic(&mut state, "Launch", String::new(), &SystemClock).unwrap();
+        let docs = create_epic(&mut state, "Docs", String::new(), &SystemClock).unwrap();
+        let login = create_story(&mut state, launch, "Login", String::new(), &SystemClock).unwrap();
+        let guide = create_story(&mut state, docs, "Guide", String::new(), &SystemClock).unwrap();
+        let mut page = KanbanPage::new(&state, Arc::new(SystemClock));
+        let wide = Size::new(160, 40);
+        let lines = page.render(wide);
+        assert!(lines[1].starts_with(&format!("{}In Progress (0)", fit("Open (2)", column_width(wide, ordered_statuses(&state).len())))));
+        assert_eq!(lines[3..7], ["EPIC-1 Launch", "> STORY-1 Login", "EPIC-2 Docs", "  STORY-2 Guide"]);
+
+        assert!(matches!(page.handle_input("L"), PageAction::Refresh));
+        assert_eq!(page.take_command(), Some(Command::SetStoryStatus { story_uuid: login, status: Status::InProgress }));
+        assert_eq!(page.selected(), Some(login));
+        let narrow = page.render(Size::new(20, 10));
+        assert!(narrow[1].starts_with(&format!("{}In Progress ", fit("Open (1)", MIN_COLUMN_WIDTH))));
+        assert!(narrow[4].starts_with(&fit("  STORY-2 Guide", MIN_COLUMN_WIDTH - 1)));
+        for _ in 0..4 {
+            page.handle_input("L");
+        }
+        assert_eq!(std::iter::from_fn(|| page.take_command()).last(), Some(Command::SetStoryStatus { story_uuid: login, status: Status::Closed }));
+        page.handle_input("H");
+        assert_eq!(page.take_command(), Some(Command::SetStoryStatus { story_uuid: login, status: Status::Cancelled }));
+        assert!(matches!(page.handle_input("H"), PageAction::ShowError(error) if error == "This status change requires the Reopen action"));
+        assert_eq!(page.take_command(), None);
+        assert_eq!(page.selected(), Some(login));
+
+        for _ in 0..4 {
+            page.handle_input("h");
+        }
+        assert!(matches!(page.handle_input("o"), PageAction::Open(Route::Story(id)) if id == guide.to_string()));
+    }
+}
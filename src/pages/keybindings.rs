@@ -0,0 +1,542 @@
+//! # Keybindings
+//! The registry of single-key commands: which key does what on which page. Pages look their input
+//! up here instead of matching on literal keys, and their hint lines and the
+//! [help page](super::help::HelpPage) are generated from the same bindings, so what is shown can't
+//! drift from what the keys do.
+//!
+//! A command may take an argument after the key, separated by a space, e.g. `t in progress`.
+//!
+//! The bindings start from a [preset](KeyPreset), vim-style or emacs-style, and can be remapped
+//! in [`KEYBINDINGS_FILE`]:
+//!
+//! ```json
+//! { "preset": "Emacs", "bindings": [{ "context": "EpicList", "key": "m", "action": "ToggleMark" }] }
+//! ```
+//!
+//! Each binding there replaces the key of its action in its context. A map where a key does two
+//! things on one page, or where a page's key hides a global one, is refused.
+
+use super::i18n::tr;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::fmt;
+use std::path::Path;
+
+/// The file, in the databases folder, that the keybindings are loaded from.
+pub const KEYBINDINGS_FILE: &str = "keybindings.json";
+
+thread_local! {
+    /// The keybindings the pages on this thread use, if they were loaded.
+    static ACTIVE: RefCell<Option<KeyMap>> = const { RefCell::new(None) };
+}
+
+/// # Key Context enum
+/// Where a keybinding applies.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum KeyContext {
+    /// On every page.
+    Global,
+    /// On the dashboard.
+    Dashboard,
+    /// On the epic list.
+    EpicList,
+    /// On an epic's detail page.
+    EpicDetail,
+    /// On a story's detail page.
+    StoryDetail,
+    /// On the search page, where any other line is a query.
+    Search,
+    /// On the kanban board.
+    Board,
+    /// On the archived items page.
+    Archived,
+    /// On the calendar.
+    Calendar,
+}
+
+impl KeyContext {
+    /// Every context, in the order the help page lists them.
+    pub const ALL: [KeyContext; 9] = [
+        KeyContext::Global,
+        KeyContext::Dashboard,
+        KeyContext::EpicList,
+        KeyContext::EpicDetail,
+        KeyContext::StoryDetail,
+        KeyContext::Search,
+        KeyContext::Board,
+        KeyContext::Archived,
+        KeyContext::Calendar,
+    ];
+
+    /// The context's name as shown on the help page.
+    #[must_use]
+    pub fn title(self) -> String {
+        tr(match self {
+            KeyContext::Global => "context-global",
+            KeyContext::Dashboard => "context-dashboard",
+            KeyContext::EpicList => "context-epic-list",
+            KeyContext::EpicDetail => "context-epic-detail",
+            KeyContext::StoryDetail => "context-story-detail",
+            KeyContext::Search => "context-search",
+            KeyContext::Board => "context-board",
+            KeyContext::Archived => "context-archived",
+            KeyContext::Calendar => "context-calendar",
+        })
+    }
+}
+
+/// # Key Action enum
+/// A command a key can be bound to.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum KeyAction {
+    /// Show the help page.
+    Help,
+    /// Go back to the previous page.
+    Back,
+    /// Go forward to the page last gone back from.
+    Forward,
+    /// Leave the application.
+    Quit,
+    /// Show the recent notifications.
+    Notifications,
+    /// Lock the session.
+    Lock,
+    /// Undo the most recent change.
+    Undo,
+    /// Redo the most recently undone change.
+    Redo,
+    /// Create a new epic.
+    NewEpic,
+    /// Create a new story.
+    NewStory,
+    /// Open the search page.
+    Search,
+    /// Dismiss the reminder banner.
+    DismissBanner,
+    /// Move the selection down.
+    MoveDown,
+    /// Move the selection up.
+    MoveUp,
+    /// Show the next page of a list.
+    NextPage,
+    /// Show the previous page of a list.
+    PreviousPage,
+    /// Open the selected item.
+    Open,
+    /// Archive the selected item.
+    Archive,
+    /// Delete the selected or shown item.
+    Delete,
+    /// Add a story to the shown epic.
+    AddStory,
+    /// Move the shown item to the status given as the argument, or picked from a list without one.
+    ChangeStatus,
+    /// Edit the shown item.
+    Edit,
+    /// Move the shown story to the epic given as the argument.
+    MoveToEpic,
+    /// Select the column to the left.
+    PreviousColumn,
+    /// Select the column to the right.
+    NextColumn,
+    /// Move the selected card to the column on the left.
+    MoveCardLeft,
+    /// Move the selected card to the column on the right.
+    MoveCardRight,
+    /// Show only the rows matching the text given as the argument, or every row without one.
+    Filter,
+    /// Mark or unmark the selected row.
+    ToggleMark,
+    /// Sort the rows by the field given as the argument, or in the list's own order without one.
+    Sort,
+    /// Show the previous month or week.
+    Earlier,
+    /// Show the next month or week.
+    Later,
+    /// Switch between showing a month and a week.
+    ToggleWeek,
+    /// Go back to the month or week holding today.
+    Today,
+    /// Switch to the tab numbered by the argument.
+    SwitchTab,
+}
+
+impl KeyAction {
+    /// What the command does, as shown in hints and on the help page.
+    #[must_use]
+    pub fn description(self) -> String {
+        tr(match self {
+            KeyAction::Help => "action-help",
+            KeyAction::Back => "action-back",
+            KeyAction::Forward => "action-forward",
+            KeyAction::Quit => "action-quit",
+            KeyAction::Notifications => "action-notifications",
+            KeyAction::Lock => "action-lock",
+            KeyAction::Undo => "action-undo",
+            KeyAction::Redo => "action-redo",
+            KeyAction::NewEpic => "action-new-epic",
+            KeyAction::NewStory => "action-new-story",
+            KeyAction::Search => "action-search",
+            KeyAction::DismissBanner => "action-dismiss-banner",
+            KeyAction::MoveDown => "action-move-down",
+            KeyAction::MoveUp => "action-move-up",
+            KeyAction::NextPage => "action-next-page",
+            KeyAction::PreviousPage => "action-previous-page",
+            KeyAction::Open => "action-open",
+            KeyAction::Archive => "action-archive",
+            KeyAction::Delete => "action-delete",
+            KeyAction::AddStory => "action-add-story",
+            KeyAction::ChangeStatus => "action-change-status",
+            KeyAction::Edit => "action-edit",
+            KeyAction::MoveToEpic => "action-move-to-epic",
+            KeyAction::PreviousColumn => "action-previous-column",
+            KeyAction::NextColumn => "action-next-column",
+            KeyAction::MoveCardLeft => "action-move-card-left",
+            KeyAction::MoveCardRight => "action-move-card-right",
+            KeyAction::Filter => "action-filter",
+            KeyAction::ToggleMark => "action-toggle-mark",
+            KeyAction::Sort => "action-sort",
+            KeyAction::Earlier => "action-earlier",
+            KeyAction::Later => "action-later",
+            KeyAction::ToggleWeek => "action-toggle-week",
+            KeyAction::Today => "action-today",
+            KeyAction::SwitchTab => "action-switch-tab",
+        })
+    }
+
+    /// The placeholder for the command's argument, if it takes one.
+    #[must_use]
+    pub fn argument(self) -> Option<String> {
+        match self {
+            KeyAction::ChangeStatus => Some(tr("argument-status")),
+            KeyAction::MoveToEpic => Some(tr("argument-epic")),
+            KeyAction::Filter => Some(tr("argument-text")),
+            KeyAction::Sort => Some(tr("argument-sort")),
+            KeyAction::SwitchTab => Some(tr("argument-tab")),
+            _ => None,
+        }
+    }
+}
+
+/// # Keybinding struct
+/// One key bound to a command in a context.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct Keybinding {
+    /// Where the binding applies.
+    pub context: KeyContext,
+    /// The key, as typed.
+    pub key: String,
+    /// The command it runs.
+    pub action: KeyAction,
+}
+
+impl Keybinding {
+    /// Creates a binding.
+    #[must_use]
+    pub fn new(context: KeyContext, key: &str, action: KeyAction) -> Self {
+        Self { context, key: key.to_string(), action }
+    }
+
+    /// The binding as shown in hints, e.g. `[t <status>] change status`.
+    #[must_use]
+    pub fn hint(&self) -> String {
+        match self.action.argument() {
+            Some(argument) => format!("[{} {argument}] {}", self.key, self.action.description()),
+            None => format!("[{}] {}", self.key, self.action.description()),
+        }
+    }
+}
+
+/// # Key Preset enum
+/// A starting set of keybindings.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum KeyPreset {
+    /// `j`/`k` to move, `h`/`l` between board columns, `>`/`<` between pages.
+    #[default]
+    Vim,
+    /// `n`/`p` to move, `b`/`f` between board columns, `v`/`V` between pages.
+    Emacs,
+}
+
+/// # `KeymapConfig` struct
+/// The contents of [`KEYBINDINGS_FILE`]: a preset and the bindings that change it.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default)]
+pub struct KeymapConfig {
+    /// The preset to start from.
+    pub preset: KeyPreset,
+    /// The bindings that replace the preset's keys for their actions.
+    pub bindings: Vec<Keybinding>,
+}
+
+/// # Conflict struct
+/// Two bindings of one key that can't both work: they are on the same page, or one is global and
+/// the page's binding hides it.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Conflict {
+    /// The key bound twice.
+    pub key: String,
+    /// The first binding's context and command.
+    pub first: (KeyContext, KeyAction),
+    /// The second binding's context and command.
+    pub second: (KeyContext, KeyAction),
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let describe = |(context, action): (KeyContext, KeyAction)| format!("{} ({})", action.description(), context.title());
+        write!(f, "{:?} is bound to both {} and {}", self.key, describe(self.first), describe(self.second))
+    }
+}
+
+/// # Keymap Error enum
+/// Why the keybindings file couldn't be used.
+#[derive(Debug)]
+pub enum KeymapError {
+    /// The file couldn't be read.
+    Io(std::io::Error),
+    /// The file isn't a valid [`KeymapConfig`].
+    Parse(serde_json::Error),
+    /// The resulting bindings conflict.
+    Conflicts(Vec<Conflict>),
+}
+
+impl fmt::Display for KeymapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeymapError::Io(err) => write!(f, "Could not read the keybindings: {err}"),
+            KeymapError::Parse(err) => write!(f, "The keybindings file is invalid: {err}"),
+            KeymapError::Conflicts(conflicts) => {
+                write!(f, "The keybindings conflict: {}", conflicts.iter().map(Conflict::to_string).collect::<Vec<_>>().join("; "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for KeymapError {}
+
+/// # Key Map struct
+/// The active keybindings.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KeyMap {
+    /// Every binding, in the order hints list them.
+    bindings: Vec<Keybinding>,
+}
+
+impl Default for KeyMap {
+    /// The bindings of the default preset.
+    fn default() -> Self {
+        Self::preset(KeyPreset::default())
+    }
+}
+
+impl KeyMap {
+    /// The bindings of a preset.
+    #[must_use]
+    pub fn preset(preset: KeyPreset) -> Self {
+        use KeyAction as A;
+        use KeyContext as C;
+        let bindings = [
+            (C::Global, "?", A::Help),
+            (C::Global, "q", A::Back),
+            (C::Global, "]", A::Forward),
+            (C::Global, "Q", A::Quit),
+            (C::Global, "!", A::Notifications),
+            (C::Global, "Z", A::Lock),
+            (C::Global, "^Z", A::Undo),
+            (C::Global, "^Y", A::Redo),
+            (C::Global, "g", A::SwitchTab),
+            (C::Dashboard, "e", A::NewEpic),
+            (C::Dashboard, "s", A::NewStory),
+            (C::Dashboard, "/", A::Search),
+            (C::Dashboard, "x", A::DismissBanner),
+            (C::EpicList, "o", A::Open),
+            (C::EpicList, "j", A::MoveDown),
+            (C::EpicList, "k", A::MoveUp),
+            (C::EpicList, ">", A::NextPage),
+            (C::EpicList, "<", A::PreviousPage),
+            (C::EpicList, "n", A::NewEpic),
+            (C::EpicList, "a", A::Archive),
+            (C::EpicList, "d", A::Delete),
+            (C::EpicList, "f", A::Filter),
+            (C::EpicList, "x", A::ToggleMark),
+            (C::EpicList, "s", A::Sort),
+            (C::EpicDetail, "s", A::AddStory),
+            (C::EpicDetail, "t", A::ChangeStatus),
+            (C::EpicDetail, "e", A::Edit),
+            (C::StoryDetail, "t", A::ChangeStatus),
+            (C::StoryDetail, "e", A::Edit),
+            (C::StoryDetail, "m", A::MoveToEpic),
+            (C::StoryDetail, "d", A::Delete),
+            (C::Search, "+", A::MoveDown),
+            (C::Search, "-", A::MoveUp),
+            (C::Board, "h", A::PreviousColumn),
+            (C::Board, "l", A::NextColumn),
+            (C::Board, "j", A::MoveDown),
+            (C::Board, "k", A::MoveUp),
+            (C::Board, "H", A::MoveCardLeft),
+            (C::Board, "L", A::MoveCardRight),
+            (C::Board, "o", A::Open),
+            (C::Archived, "j", A::MoveDown),
+            (C::Archived, "k", A::MoveUp),
+            (C::Archived, ">", A::NextPage),
+            (C::Archived, "<", A::PreviousPage),
+            (C::Archived, "f", A::Filter),
+            (C::Archived, "s", A::Sort),
+            (C::Calendar, "<", A::Earlier),
+            (C::Calendar, ">", A::Later),
+            (C::Calendar, "w", A::ToggleWeek),
+            (C::Calendar, "t", A::Today),
+        ];
+        let mut keys = Self { bindings: bindings.into_iter().map(|(context, key, action)| Keybinding::new(context, key, action)).collect() };
+        let changes: &[(KeyContext, &str, KeyAction)] = match preset {
+            KeyPreset::Vim => &[],
+            KeyPreset::Emacs => &[
+                (C::EpicList, "c", A::NewEpic),
+                (C::EpicList, "n", A::MoveDown),
+                (C::EpicList, "p", A::MoveUp),
+                (C::EpicList, "v", A::NextPage),
+                (C::EpicList, "V", A::PreviousPage),
+                (C::Board, "b", A::PreviousColumn),
+                (C::Board, "f", A::NextColumn),
+                (C::Board, "n", A::MoveDown),
+                (C::Board, "p", A::MoveUp),
+                (C::Board, "B", A::MoveCardLeft),
+                (C::Board, "F", A::MoveCardRight),
+                (C::Archived, "n", A::MoveDown),
+                (C::Archived, "p", A::MoveUp),
+                (C::Archived, "v", A::NextPage),
+                (C::Archived, "V", A::PreviousPage),
+            ],
+        };
+        for (context, key, action) in changes {
+            keys.bind(Keybinding::new(*context, key, *action));
+        }
+        keys
+    }
+
+    /// Builds the bindings a config asks for: its preset with its bindings applied.
+    ///
+    /// # Errors
+    /// * `KeymapError::Conflicts` - If the result binds a key twice.
+    pub fn from_config(config: &KeymapConfig) -> Result<Self, KeymapError> {
+        let mut keys = Self::preset(config.preset);
+        for binding in &config.bindings {
+            keys.bind(binding.clone());
+        }
+        let conflicts = keys.conflicts();
+        if conflicts.is_empty() { Ok(keys) } else { Err(KeymapError::Conflicts(conflicts)) }
+    }
+
+    /// Loads the bindings from a [`KEYBINDINGS_FILE`]; without one, the default preset applies.
+    ///
+    /// # Errors
+    /// * `KeymapError::Io` - If the file exists but can't be read.
+    /// * `KeymapError::Parse` - If the file isn't a valid config.
+    /// * `KeymapError::Conflicts` - If the result binds a key twice.
+    pub fn load(path: &Path) -> Result<Self, KeymapError> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => return Err(KeymapError::Io(err)),
+        };
+        Self::from_config(&serde_json::from_str(&contents).map_err(KeymapError::Parse)?)
+    }
+
+    /// The bindings the pages on this thread use: those passed to [`KeyMap::set_active`], or the
+    /// default ones.
+    #[must_use]
+    pub fn active() -> Self {
+        ACTIVE.with(|active| active.borrow().clone()).unwrap_or_default()
+    }
+
+    /// Makes `keys` the bindings of the pages created on this thread from now on.
+    pub fn set_active(keys: Self) {
+        ACTIVE.with(|active| *active.borrow_mut() = Some(keys));
+    }
+
+    /// Binds a key, replacing the key its action had in its context, or adding it if the action
+    /// had none there.
+    pub fn bind(&mut self, binding: Keybinding) {
+        match self.bindings.iter_mut().find(|existing| existing.context == binding.context && existing.action == binding.action) {
+            Some(existing) => *existing = binding,
+            None => self.bindings.push(binding),
+        }
+    }
+
+    /// The keys bound twice on one page, counting global bindings on every page.
+    #[must_use]
+    pub fn conflicts(&self) -> Vec<Conflict> {
+        let mut conflicts = Vec::new();
+        for (index, first) in self.bindings.iter().enumerate() {
+            for second in &self.bindings[index + 1..] {
+                let overlap = first.context == second.context || first.context == KeyContext::Global || second.context == KeyContext::Global;
+                if first.key == second.key && overlap && first.action != second.action {
+                    conflicts.push(Conflict { key: first.key.clone(), first: (first.context, first.action), second: (second.context, second.action) });
+                }
+            }
+        }
+        conflicts
+    }
+
+    /// The bindings of one context, in order.
+    pub fn bindings(&self, context: KeyContext) -> impl Iterator<Item = &Keybinding> {
+        self.bindings.iter().filter(move |binding| binding.context == context)
+    }
+
+    /// Looks up a typed line in a context: the first word is the key and the rest, trimmed, is the
+    /// argument. Page bindings win over global ones.
+    ///
+    /// # Returns
+    /// * `Option<(KeyAction, &str)>` - The command and its argument (empty if none), or `None`
+    ///   if the key isn't bound.
+    /// # Examples
+    /// ```rust
+    /// use ironyyy::pages::keybindings::{KeyAction, KeyContext, KeyMap};
+    /// let keys = KeyMap::default();
+    /// assert_eq!(keys.lookup(KeyContext::StoryDetail, "t in progress"), Some((KeyAction::ChangeStatus, "in progress")));
+    /// assert_eq!(keys.lookup(KeyContext::StoryDetail, "?"), Some((KeyAction::Help, "")));
+    /// assert_eq!(keys.lookup(KeyContext::StoryDetail, "z"), None);
+    /// ```
+    #[must_use]
+    pub fn lookup<'a>(&self, context: KeyContext, input: &'a str) -> Option<(KeyAction, &'a str)> {
+        let input = input.trim();
+        let (key, argument) = input.split_once(' ').unwrap_or((input, ""));
+        let find = |context| self.bindings(context).find(|binding| binding.key == key).map(|binding| (binding.action, argument.trim()));
+        find(context).or_else(|| find(KeyContext::Global))
+    }
+
+    /// The hint line listing a context's bindings, e.g. `[e] new epic  [s] new story`.
+    #[must_use]
+    pub fn hints(&self, context: KeyContext) -> String {
+        self.bindings(context).map(Keybinding::hint).collect::<Vec<_>>().join("  ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pages::help::HelpPage;
+    use crate::pages::{Page, Size};
+
+    #[test]
+    fn test_config_remaps_presets_and_refuses_conflicts() {
+        assert!(KeyMap::preset(KeyPreset::Vim).conflicts().is_empty());
+        let emacs = KeyMap::preset(KeyPreset::Emacs);
+        assert!(emacs.conflicts().is_empty());
+        assert_eq!(emacs.lookup(KeyContext::Board, "f"), Some((KeyAction::NextColumn, "")));
+        assert_eq!(emacs.lookup(KeyContext::EpicList, "n"), Some((KeyAction::MoveDown, "")));
+
+        let config: KeymapConfig = serde_json::from_str(r#"{ "preset": "Emacs", "bindings": [{ "context": "EpicList", "key": "m", "action": "ToggleMark" }] }"#).unwrap();
+        let keys = KeyMap::from_config(&config).unwrap();
+        assert_eq!(keys.lookup(KeyContext::EpicList, "m"), Some((KeyAction::ToggleMark, "")));
+        assert_eq!(keys.lookup(KeyContext::EpicList, "x"), None);
+        let help = HelpPage::new(&keys, Some(KeyContext::EpicList)).render(Size::default());
+        assert!(help.contains(&format!("  {:<14} {}", "m", KeyAction::ToggleMark.description())));
+
+        let config: KeymapConfig = serde_json::from_str(r#"{ "bindings": [{ "context": "Board", "key": "q", "action": "Open" }] }"#).unwrap();
+        let Err(KeymapError::Conflicts(conflicts)) = KeyMap::from_config(&config) else { panic!("expected a conflict") };
+        assert_eq!(conflicts, [Conflict { key: "q".to_string(), first: (KeyContext::Global, KeyAction::Back), second: (KeyContext::Board, KeyAction::Open) }]);
+        assert!(KeyMap::load(Path::new("/nonexistent/keybindings.json")).is_ok_and(|keys| keys == KeyMap::default()));
+    }
+}
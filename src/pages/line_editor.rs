@@ -0,0 +1,455 @@
+//! # Line Editor
+//! A readline-style prompt: the line being typed can be edited anywhere (cursor movement,
+//! backspace and delete, word and line kills), and the up and down arrows recall earlier lines.
+//! Every prompt reads its line through a [`LineEditor`] instead of a raw `stdin` line read, so
+//! the editing keys work the same everywhere; pages still receive the finished line.
+//!
+//! Keys come in as [`Key`]s, decoded from the terminal's bytes with [`decode_keys`], or with a
+//! [`KeyDecoder`] as they are read a few at a time. When the terminal is in
+//! [raw mode](crate::terminal::RawMode) it doesn't echo what is typed, so the editor
+//! [echoes](LineEditor::set_echo) the line itself, masked while a page asks for a secret.
+
+use std::io::Write;
+
+/// The number of lines the history keeps; older ones are dropped.
+pub const HISTORY_LIMIT: usize = 100;
+
+/// # Key enum
+/// A key press, as far as line editing is concerned.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Key {
+    /// A printable character.
+    Char(char),
+    /// Enter: submit the line.
+    Enter,
+    /// Backspace: delete the character before the cursor.
+    Backspace,
+    /// Delete: delete the character under the cursor.
+    Delete,
+    /// Left arrow or Ctrl+B: move back one character.
+    Left,
+    /// Right arrow or Ctrl+F: move forward one character.
+    Right,
+    /// Alt+B or Ctrl+Left: move back one word.
+    WordLeft,
+    /// Alt+F or Ctrl+Right: move forward one word.
+    WordRight,
+    /// Home or Ctrl+A: move to the start of the line.
+    Home,
+    /// End or Ctrl+E: move to the end of the line.
+    End,
+    /// Up arrow or Ctrl+P: recall the previous line.
+    Up,
+    /// Down arrow or Ctrl+N: recall the next line.
+    Down,
+    /// Ctrl+W: delete the word before the cursor.
+    DeleteWord,
+    /// Ctrl+U: delete everything before the cursor.
+    KillToStart,
+    /// Ctrl+K: delete everything from the cursor on.
+    KillToEnd,
+    /// Ctrl+D: end of input when the line is empty, otherwise delete.
+    EndOfInput,
+    /// Ctrl+C: end of input, whatever has been typed.
+    Interrupt,
+    /// Escape, on its own.
+    Escape,
+    /// Ctrl+Z or Ctrl+Y, holding `Z` or `Y`: a shortcut submitted straight away as `^Z` or `^Y`,
+    /// leaving the line as it is.
+    Ctrl(char),
+}
+
+/// # Decode Keys
+/// Decodes the bytes read from a terminal in raw mode into key presses, understanding the usual
+/// control characters and the ANSI escape sequences for arrows, Home, End, and Delete. Unknown
+/// sequences are skipped.
+///
+/// # Examples
+/// ```
+/// use ironyyy::pages::line_editor::{Key, decode_keys};
+/// assert_eq!(decode_keys("hé\x1b[D\x7f\r".as_bytes()), vec![Key::Char('h'), Key::Char('é'), Key::Left, Key::Backspace, Key::Enter]);
+/// ```
+#[must_use]
+pub fn decode_keys(bytes: &[u8]) -> Vec<Key> {
+    let text = String::from_utf8_lossy(bytes);
+    let mut chars = text.chars().peekable();
+    let mut keys = Vec::new();
+    while let Some(c) = chars.next() {
+        let key = match c {
+            '\x1b' => match chars.next() {
+                Some('[' | 'O') => {
+                    let mut sequence = String::new();
+                    while let Some(&next) = chars.peek() {
+                        chars.next();
+                        sequence.push(next);
+                        if next.is_ascii_alphabetic() || next == '~' {
+                            break;
+                        }
+                    }
+                    match sequence.as_str() {
+                        "A" => Key::Up,
+                        "B" => Key::Down,
+                        "C" => Key::Right,
+                        "D" => Key::Left,
+                        "H" | "1~" | "7~" => Key::Home,
+                        "F" | "4~" | "8~" => Key::End,
+                        "3~" => Key::Delete,
+                        "1;5C" | "1;3C" => Key::WordRight,
+                        "1;5D" | "1;3D" => Key::WordLeft,
+                        _ => continue,
+                    }
+                }
+                Some('b') => Key::WordLeft,
+                Some('f') => Key::WordRight,
+                None => Key::Escape,
+                Some(_) => continue,
+            },
+            '\r' | '\n' => Key::Enter,
+            '\x7f' | '\x08' => Key::Backspace,
+            '\x01' => Key::Home,
+            '\x02' => Key::Left,
+            '\x03' => Key::Interrupt,
+            '\x04' => Key::EndOfInput,
+            '\x05' => Key::End,
+            '\x06' => Key::Right,
+            '\x0b' => Key::KillToEnd,
+            '\x0e' => Key::Down,
+            '\x10' => Key::Up,
+            '\x15' => Key::KillToStart,
+            '\x17' => Key::DeleteWord,
+            '\x19' => Key::Ctrl('Y'),
+            '\x1a' => Key::Ctrl('Z'),
+            c if c.is_control() => continue,
+            c => Key::Char(c),
+        };
+        keys.push(key);
+    }
+    keys
+}
+
+/// # `KeyDecoder` struct
+/// Decodes a raw-mode terminal's bytes into key presses as they are read, a few at a time. A
+/// character or escape sequence split between two reads is held back until the rest of it
+/// arrives, where [`decode_keys`] would mangle or drop it; an Escape read on its own is the
+/// Escape key.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct KeyDecoder {
+    /// The bytes of an unfinished character or escape sequence, from the end of the last read.
+    pending: Vec<u8>,
+}
+
+impl KeyDecoder {
+    /// Decodes the bytes just read, after any held back from the last read.
+    pub fn feed(&mut self, bytes: &[u8]) -> Vec<Key> {
+        self.pending.extend_from_slice(bytes);
+        let complete = complete_len(&self.pending);
+        let keys = decode_keys(&self.pending[..complete]);
+        self.pending.drain(..complete);
+        keys
+    }
+}
+
+/// How many of `bytes` can be decoded: all but an unfinished UTF-8 character or escape sequence
+/// at the end.
+fn complete_len(bytes: &[u8]) -> usize {
+    if let Some(start) = bytes.iter().rposition(|&byte| byte == 0x1b)
+        && let [b'[' | b'O', parameters @ ..] = &bytes[start + 1..]
+        && !parameters.iter().any(|byte| byte.is_ascii_alphabetic() || *byte == b'~')
+    {
+        return start;
+    }
+    match std::str::from_utf8(bytes) {
+        Err(error) if error.error_len().is_none() => error.valid_up_to(),
+        _ => bytes.len(),
+    }
+}
+
+/// # Line Edit enum
+/// What a key press did to the line.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum LineEdit {
+    /// The line changed or the cursor moved; keep reading.
+    Editing,
+    /// The line was submitted.
+    Submitted(String),
+    /// The input ended (Ctrl+D on an empty line, or Ctrl+C).
+    Closed,
+}
+
+/// # `LineEditor` struct
+/// The line being typed, its cursor, and the history of submitted lines.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct LineEditor {
+    /// The characters of the line.
+    buffer: Vec<char>,
+    /// The cursor's position, in characters from the start of the line.
+    cursor: usize,
+    /// Submitted lines, oldest first.
+    history: Vec<String>,
+    /// The history entry being shown, if the user has gone back through the history.
+    recalled: Option<usize>,
+    /// The line as it was before going back through the history, restored by going past the
+    /// newest entry.
+    draft: Vec<char>,
+    /// Whether the line is a secret: it is shown as `*`s and never kept in the history.
+    masked: bool,
+    /// Whether to draw the line on standard output as it is edited.
+    echo: bool,
+}
+
+impl LineEditor {
+    /// Creates an empty editor with no history.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Masks the next lines, e.g. while a page asks for a password, or stops masking them.
+    pub fn set_masked(&mut self, masked: bool) {
+        self.masked = masked;
+    }
+
+    /// Draws the line on standard output as it is edited, for a terminal that doesn't echo the
+    /// keys typed, or stops drawing it.
+    pub fn set_echo(&mut self, echo: bool) {
+        self.echo = echo;
+    }
+
+    /// The line typed so far.
+    #[must_use]
+    pub fn line(&self) -> String {
+        self.buffer.iter().collect()
+    }
+
+    /// The cursor's position, in characters from the start of the line.
+    #[must_use]
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// The submitted lines, oldest first.
+    #[must_use]
+    pub fn history(&self) -> &[String] {
+        &self.history
+    }
+
+    /// The prompt and line as they should be drawn, with masked lines shown as `*`s. The
+    /// terminal cursor belongs `prompt.chars().count() + cursor()` columns in.
+    #[must_use]
+    pub fn render(&self, prompt: &str) -> String {
+        let line = if self.masked { "*".repeat(self.buffer.len()) } else { self.line() };
+        format!("{prompt}{line}")
+    }
+
+    /// Applies one key press.
+    pub fn handle_key(&mut self, key: Key) -> LineEdit {
+        match key {
+            Key::Char(c) => {
+                self.buffer.insert(self.cursor, c);
+                self.cursor += 1;
+            }
+            Key::Enter => return LineEdit::Submitted(self.submit()),
+            Key::Backspace => {
+                if self.cursor > 0 {
+                    self.cursor -= 1;
+                    self.buffer.remove(self.cursor);
+                }
+            }
+            Key::EndOfInput if self.buffer.is_empty() => return LineEdit::Closed,
+            Key::Interrupt => return LineEdit::Closed,
+            Key::Delete | Key::EndOfInput => {
+                if self.cursor < self.buffer.len() {
+                    self.buffer.remove(self.cursor);
+                }
+            }
+            Key::Left => self.cursor = self.cursor.saturating_sub(1),
+            Key::Right => self.cursor = (self.cursor + 1).min(self.buffer.len()),
+            Key::WordLeft => self.cursor = self.word_start(),
+            Key::WordRight => self.cursor = self.word_end(),
+            Key::Home => self.cursor = 0,
+            Key::End => self.cursor = self.buffer.len(),
+            Key::Up => self.recall_previous(),
+            Key::Down => self.recall_next(),
+            Key::DeleteWord => {
+                let start = self.word_start();
+                self.buffer.drain(start..self.cursor);
+                self.cursor = start;
+            }
+            Key::KillToStart => {
+                self.buffer.drain(..self.cursor);
+                self.cursor = 0;
+            }
+            Key::KillToEnd => self.buffer.truncate(self.cursor),
+            Key::Escape => (),
+            Key::Ctrl(c) => return LineEdit::Submitted(format!("^{c}")),
+        }
+        LineEdit::Editing
+    }
+
+    /// Reads one line from `keys`, the way a prompt does.
+    ///
+    /// # Returns
+    /// * `Option<String>` - The submitted line, or `None` if the keys ran out or the input was
+    ///   closed before a line was submitted.
+    pub fn read_line(&mut self, keys: &mut dyn Iterator<Item = Key>) -> Option<String> {
+        for key in keys {
+            let edit = self.handle_key(key);
+            if self.echo {
+                let mut out = std::io::stdout().lock();
+                // Best effort: a line that can't be drawn is still read.
+                let _ = self.echo_to(&mut out, &edit).and_then(|()| out.flush());
+            }
+            match edit {
+                LineEdit::Editing => (),
+                LineEdit::Submitted(line) => return Some(line),
+                LineEdit::Closed => return None,
+            }
+        }
+        None
+    }
+
+    /// Writes what a key press did to the line to `out`: the whole line again, with the cursor
+    /// put back in it, or a line break once it is submitted or closed.
+    ///
+    /// # Errors
+    /// * `std::io::Error` - If `out` can't be written.
+    fn echo_to(&self, out: &mut dyn Write, edit: &LineEdit) -> std::io::Result<()> {
+        if *edit != LineEdit::Editing {
+            return writeln!(out);
+        }
+        write!(out, "\r\x1b[K{}\r", self.render(""))?;
+        if self.cursor > 0 {
+            write!(out, "\x1b[{}C", self.cursor)?;
+        }
+        Ok(())
+    }
+
+    /// Empties the line, adding it to the history unless it is blank, masked, or the same as
+    /// the newest entry.
+    fn submit(&mut self) -> String {
+        let line: String = self.buffer.drain(..).collect();
+        self.cursor = 0;
+        self.recalled = None;
+        self.draft.clear();
+        if !self.masked && !line.trim().is_empty() && self.history.last() != Some(&line) {
+            self.history.push(line.clone());
+            if self.history.len() > HISTORY_LIMIT {
+                self.history.remove(0);
+            }
+        }
+        line
+    }
+
+    /// Replaces the line with the previous history entry.
+    fn recall_previous(&mut self) {
+        if self.masked {
+            return;
+        }
+        let index = match self.recalled {
+            Some(index) => index.saturating_sub(1),
+            None if self.history.is_empty() => return,
+            None => {
+                self.draft = std::mem::take(&mut self.buffer);
+                self.history.len() - 1
+            }
+        };
+        self.show(index);
+    }
+
+    /// Replaces the line with the next history entry, or with the draft after the newest one.
+    fn recall_next(&mut self) {
+        let Some(index) = self.recalled else { return };
+        if index + 1 < self.history.len() {
+            self.show(index + 1);
+        } else {
+            self.recalled = None;
+            self.buffer = std::mem::take(&mut self.draft);
+            self.cursor = self.buffer.len();
+        }
+    }
+
+    /// Shows a history entry, with the cursor at its end.
+    fn show(&mut self, index: usize) {
+        self.recalled = Some(index);
+        self.buffer = self.history[index].chars().collect();
+        self.cursor = self.buffer.len();
+    }
+
+    /// Where the word before the cursor starts, skipping any spaces just before it.
+    fn word_start(&self) -> usize {
+        let before = &self.buffer[..self.cursor];
+        let end = before.iter().rposition(|c| !c.is_whitespace()).map_or(0, |index| index + 1);
+        before[..end].iter().rposition(|c| c.is_whitespace()).map_or(0, |index| index + 1)
+    }
+
+    /// Where the word after the cursor ends, skipping any spaces just after it.
+    fn word_end(&self) -> usize {
+        let after = &self.buffer[self.cursor..];
+        let start = after.iter().position(|c| !c.is_whitespace()).unwrap_or(after.len());
+        self.cursor + after[start..].iter().position(|c| c.is_whitespace()).map_or(after.len(), |index| start + index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Turns text into key presses.
+    fn typed(text: &str) -> impl Iterator<Item = Key> + '_ {
+        text.chars().map(Key::Char)
+    }
+
+    #[test]
+    fn test_line_editing() {
+        let mut editor = LineEditor::new();
+        let mut keys = typed("fix login bug").chain([Key::WordLeft, Key::DeleteWord, Key::End, Key::Left, Key::Left, Key::Backspace, Key::Home, Key::Delete, Key::Enter]);
+        assert_eq!(editor.read_line(&mut keys), Some("ix ug".to_string()));
+
+        let mut keys = typed("one two three").chain([Key::WordLeft, Key::WordLeft, Key::KillToEnd, Key::Char('!'), Key::Home, Key::WordRight]);
+        assert_eq!(editor.read_line(&mut keys), None);
+        assert_eq!((editor.line().as_str(), editor.cursor()), ("one !", 3));
+        editor.handle_key(Key::KillToStart);
+        assert_eq!(editor.render("> "), ">  !");
+
+        assert_eq!(decode_keys(b"\x1a\x19"), [Key::Ctrl('Z'), Key::Ctrl('Y')]);
+        let mut decoder = KeyDecoder::default();
+        assert_eq!(decoder.feed(b"h\xc3"), [Key::Char('h')]);
+        assert_eq!(decoder.feed(b"\xa9\x1b[1;"), [Key::Char('é')]);
+        assert_eq!(decoder.feed(b"5D\x1b"), [Key::WordLeft, Key::Escape]);
+        assert_eq!(editor.handle_key(Key::Ctrl('Z')), LineEdit::Submitted("^Z".to_string()));
+        assert_eq!(editor.line(), " !");
+    }
+
+    #[test]
+    fn test_history() {
+        let mut editor = LineEditor::new();
+        for line in ["first", "second", "second", "  "] {
+            editor.read_line(&mut typed(line).chain([Key::Enter]));
+        }
+        assert_eq!(editor.history(), ["first", "second"]);
+
+        editor.read_line(&mut typed("dra"));
+        editor.handle_key(Key::Up);
+        editor.handle_key(Key::Up);
+        editor.handle_key(Key::Up);
+        assert_eq!(editor.line(), "first");
+        editor.handle_key(Key::Down);
+        assert_eq!(editor.line(), "second");
+        editor.handle_key(Key::Down);
+        assert_eq!((editor.line().as_str(), editor.cursor()), ("dra", 3));
+
+        editor.handle_key(Key::KillToStart);
+        editor.set_masked(true);
+        assert_eq!(editor.read_line(&mut typed("hunter2").chain([Key::Up])), None);
+        assert_eq!(editor.render("Password: "), "Password: *******");
+        let mut echoed = Vec::new();
+        editor.handle_key(Key::Left);
+        editor.echo_to(&mut echoed, &LineEdit::Editing).unwrap();
+        editor.echo_to(&mut echoed, &LineEdit::Submitted(String::new())).unwrap();
+        assert_eq!(String::from_utf8(echoed).unwrap(), "\r\x1b[K*******\r\x1b[6C\n");
+        assert_eq!(editor.handle_key(Key::Enter), LineEdit::Submitted("hunter2".to_string()));
+        assert_eq!(editor.history().len(), 2);
+        assert_eq!(editor.handle_key(Key::EndOfInput), LineEdit::Closed);
+        assert_eq!(editor.read_line(&mut decode_keys(b"half-typed\x03").into_iter()), None);
+    }
+}
@@ -0,0 +1,335 @@
+//! # List View
+//! The behaviour the list pages share: a [`table`](super::table) shown a page at a time, with a
+//! selection that moves with the list's keys, a filter that narrows the rows as the user types,
+//! a sort order, and marks for acting on several rows at once.
+//!
+//! Rows keep the value they stand for (a UUID or an [`ItemRef`](crate::service::references::ItemRef))
+//! next to their cells, so pages get back values rather than indices, and may carry
+//! [`RowFacets`]: what they are sorted by, and their labels for `label:<name>` filters.
+
+use super::epic_wizard::PRIORITY_FIELD;
+use super::i18n::tr_with;
+use super::keybindings::KeyAction;
+use super::table::{Align, Table};
+use crate::db::ClearTextDBState;
+use crate::models::custom_fields::{FieldId, FieldKind, FieldValue};
+use crate::models::settings::{ListPreferences, ListSort};
+use crate::models::Scheduled;
+use crate::service::custom_fields::find_field_by_name;
+use crate::service::labels::get_label;
+use crate::service::pagination::{PageRequest, paginate};
+use crate::service::statuses::ordered_statuses;
+use chrono::NaiveDate;
+use std::collections::{BTreeMap, BTreeSet};
+use uuid::Uuid;
+
+/// The prefix of a filter word that matches a label's name instead of the cells, e.g. `label:ui`.
+pub const LABEL_FILTER: &str = "label:";
+
+/// # `RowFacets` struct
+/// What a row is sorted by and filtered on besides its cells.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct RowFacets {
+    /// The position of the row's status in workflow order.
+    pub status: usize,
+    /// The position of the row's priority among the priority field's options, if it has one.
+    pub priority: Option<usize>,
+    /// The row's due date, if it has one.
+    pub due_date: Option<NaiveDate>,
+    /// The names of the row's labels, in lowercase.
+    pub labels: Vec<String>,
+}
+
+impl RowFacets {
+    /// The facets of an epic or story.
+    #[must_use]
+    pub fn of(state: &ClearTextDBState, item: &impl Scheduled, labels: &[Uuid], custom_fields: &BTreeMap<FieldId, FieldValue>) -> Self {
+        let priority = find_field_by_name(state, PRIORITY_FIELD).and_then(|field| match (&field.kind, custom_fields.get(&field.field_id)) {
+            (FieldKind::Enum(options), Some(FieldValue::Enum(value))) => options.iter().position(|option| option == value),
+            _ => None,
+        });
+        Self {
+            status: ordered_statuses(state).iter().position(|definition| definition.status == item.status()).unwrap_or(usize::MAX),
+            priority,
+            due_date: item.due_date(),
+            labels: labels.iter().filter_map(|label_uuid| get_label(state, *label_uuid).ok()).map(|label| label.name.to_lowercase()).collect(),
+        }
+    }
+}
+
+/// # `ListView` struct
+/// Rows of cells, each standing for a value, with paging, filtering, selection, and marks.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ListView<T> {
+    /// The headers and alignments of the columns after the selection marker and row number.
+    columns: Vec<(String, Align)>,
+    /// Every row's value and cells, in order.
+    rows: Vec<(T, Vec<String>)>,
+    /// Every row's facets, in the order of `rows`; empty if the rows have none.
+    facets: Vec<RowFacets>,
+    /// The current filter; empty shows every row.
+    filter: String,
+    /// The current sort order.
+    sort: ListSort,
+    /// The indices in `rows` of the rows that match the filter, in sort order.
+    visible: Vec<usize>,
+    /// How many rows are shown per page.
+    page_size: usize,
+    /// The position in `visible` of the selected row.
+    selected: usize,
+    /// The indices in `rows` of the marked rows.
+    marked: BTreeSet<usize>,
+}
+
+impl<T> ListView<T> {
+    /// Creates a list of `rows`, showing `page_size` at a time, with the first row selected.
+    #[must_use]
+    pub fn new(rows: Vec<(T, Vec<String>)>, page_size: usize) -> Self {
+        Self {
+            columns: Vec::new(),
+            visible: (0..rows.len()).collect(),
+            rows,
+            facets: Vec::new(),
+            filter: String::new(),
+            sort: ListSort::Default,
+            page_size: page_size.max(1),
+            selected: 0,
+            marked: BTreeSet::new(),
+        }
+    }
+
+    /// Adds a column for the next cell of each row.
+    #[must_use]
+    pub fn column(mut self, header: &str, align: Align) -> Self {
+        self.columns.push((header.to_string(), align));
+        self
+    }
+
+    /// Sets the rows' facets, given in the order of the rows, so they can be sorted and filtered
+    /// by label.
+    #[must_use]
+    pub fn facets(mut self, facets: Vec<RowFacets>) -> Self {
+        self.facets = facets;
+        self
+    }
+
+    /// The number of rows that match the filter.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.visible.len()
+    }
+
+    /// Whether no rows match the filter.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.visible.is_empty()
+    }
+
+    /// Whether the list has no rows at all, whatever the filter.
+    #[must_use]
+    pub fn has_no_rows(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// The current filter.
+    #[must_use]
+    pub fn filter(&self) -> &str {
+        &self.filter
+    }
+
+    /// Shows only the rows containing every word of `filter` in one of their cells, ignoring
+    /// case, and selects the first of them. A word starting with [`LABEL_FILTER`] matches the
+    /// rows with a label whose name contains the rest. An empty filter shows every row.
+    pub fn set_filter(&mut self, filter: &str) {
+        self.filter = filter.trim().to_string();
+        self.update();
+    }
+
+    /// The current sort order.
+    #[must_use]
+    pub fn sort(&self) -> ListSort {
+        self.sort
+    }
+
+    /// The current sort order and filter, to remember for next time.
+    #[must_use]
+    pub fn preferences(&self) -> ListPreferences {
+        ListPreferences { sort: self.sort, filter: self.filter.clone() }
+    }
+
+    /// Applies a remembered sort order and filter.
+    #[must_use]
+    pub fn with_preferences(mut self, preferences: &ListPreferences) -> Self {
+        self.sort = preferences.sort;
+        self.set_filter(&preferences.filter);
+        self
+    }
+
+    /// Sorts the rows by the sort order named `name`, as typed after the sort key.
+    ///
+    /// # Errors
+    /// * `String` - The message to show if `name` isn't a sort order.
+    pub fn sort_by_name(&mut self, name: &str) -> Result<(), String> {
+        let sort = ListSort::from_name(name).ok_or_else(|| tr_with("unknown-sort", &[("sort", &name)]))?;
+        self.set_sort(sort);
+        Ok(())
+    }
+
+    /// Sorts the rows, keeping the rows' own order among equals, and selects the first.
+    pub fn set_sort(&mut self, sort: ListSort) {
+        self.sort = sort;
+        self.update();
+    }
+
+    /// The selected row's value, if any rows match the filter.
+    #[must_use]
+    pub fn selected(&self) -> Option<&T> {
+        self.visible.get(self.selected).map(|index| &self.rows[*index].0)
+    }
+
+    /// The marked rows' values, in list order, whether or not they match the filter.
+    #[must_use]
+    pub fn marked(&self) -> Vec<&T> {
+        self.marked.iter().map(|index| &self.rows[*index].0).collect()
+    }
+
+    /// The rows an action applies to: the marked rows if there are any, otherwise the selected
+    /// row.
+    #[must_use]
+    pub fn chosen(&self) -> Vec<&T> {
+        if self.marked.is_empty() { self.selected().into_iter().collect() } else { self.marked() }
+    }
+
+    /// Marks the selected row, or unmarks it if it is marked.
+    pub fn toggle_mark(&mut self) {
+        if let Some(index) = self.visible.get(self.selected)
+            && !self.marked.remove(index)
+        {
+            self.marked.insert(*index);
+        }
+    }
+
+    /// Selects the row at `position` among those matching the filter, turning to its page.
+    pub fn select(&mut self, position: usize) {
+        self.selected = position.min(self.visible.len().saturating_sub(1));
+    }
+
+    /// Applies a navigation command: moving the selection, or turning the page.
+    ///
+    /// # Returns
+    /// * `bool` - Whether `action` was a navigation command.
+    pub fn navigate(&mut self, action: KeyAction) -> bool {
+        let request = self.request();
+        match action {
+            KeyAction::MoveDown => self.select(self.selected.saturating_add(1)),
+            KeyAction::MoveUp => self.select(self.selected.saturating_sub(1)),
+            KeyAction::NextPage if request.next().offset < self.visible.len() => self.select(request.next().offset),
+            KeyAction::NextPage => (),
+            KeyAction::PreviousPage => self.select(request.previous().offset),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Selects the row with the 1-based number `input` on the current page.
+    ///
+    /// # Returns
+    /// * `Option<&T>` - The row's value, or `None` if `input` isn't the number of a row shown.
+    pub fn pick(&mut self, input: &str) -> Option<&T> {
+        let request = self.request();
+        let position = input.trim().parse::<usize>().ok()?.checked_sub(1)? + request.offset;
+        if position >= self.visible.len().min(request.offset + request.limit) {
+            return None;
+        }
+        self.select(position);
+        self.selected()
+    }
+
+    /// The filter bar: the current filter and sort order, each on a line if set.
+    #[must_use]
+    pub fn bar(&self) -> Vec<String> {
+        let filter = (!self.filter.is_empty()).then(|| tr_with("filter-line", &[("filter", &self.filter)]));
+        let sort = (self.sort != ListSort::Default).then(|| tr_with("sort-line", &[("sort", &self.sort.name())]));
+        filter.into_iter().chain(sort).collect()
+    }
+
+    /// The page position, e.g. `Page 1 of 2`.
+    #[must_use]
+    pub fn page_footer(&self) -> String {
+        let page = paginate(self.visible.clone(), self.request());
+        tr_with("page-footer", &[("page", &page.page_number()), ("pages", &page.page_count())])
+    }
+
+    /// Renders the current page as a table fitting `width`: a `>` before the selected row, then
+    /// each row's number on the page (with a `*` if it is marked) and its cells.
+    #[must_use]
+    pub fn render(&self, width: usize) -> Vec<String> {
+        let table = self.columns.iter().fold(Table::new().column("", Align::Left).column("#", Align::Right), |table, (header, align)| table.column(header, *align));
+        let page = paginate(self.visible.iter().enumerate().collect(), self.request());
+        let table = page.items.iter().fold(table, |mut table, (position, index)| {
+            let marker = if *position == self.selected { ">" } else { "" };
+            let number = format!("{}{}", if self.marked.contains(index) { "*" } else { "" }, position - page.request.offset + 1);
+            table.add_row([marker.to_string(), number].into_iter().chain(self.rows[**index].1.iter().cloned()));
+            table
+        });
+        table.render(width)
+    }
+
+    /// Applies the filter and sort order to the rows, and selects the first.
+    fn update(&mut self) {
+        let words: Vec<String> = self.filter.split_whitespace().map(str::to_lowercase).collect();
+        let no_facets = RowFacets::default();
+        let facets = |index: usize| self.facets.get(index).unwrap_or(&no_facets);
+        let mut visible: Vec<usize> = (0..self.rows.len())
+            .filter(|index| {
+                let text = self.rows[*index].1.join(" ").to_lowercase();
+                words.iter().all(|word| match word.strip_prefix(LABEL_FILTER) {
+                    Some(label) => facets(*index).labels.iter().any(|name| name.contains(label)),
+                    None => text.contains(word.as_str()),
+                })
+            })
+            .collect();
+        match self.sort {
+            ListSort::Default => (),
+            ListSort::Status => visible.sort_by_key(|index| facets(*index).status),
+            ListSort::Priority => visible.sort_by_key(|index| facets(*index).priority.map_or((1, 0), |priority| (0, priority))),
+            ListSort::DueDate => visible.sort_by_key(|index| (facets(*index).due_date.is_none(), facets(*index).due_date)),
+        }
+        self.visible = visible;
+        self.selected = 0;
+    }
+
+    /// The page holding the selected row.
+    fn request(&self) -> PageRequest {
+        PageRequest { offset: self.selected - self.selected % self.page_size, limit: self.page_size }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_view_filters_pages_and_marks() {
+        let rows = ["Launch", "Docs", "Site", "Launch docs"].into_iter().enumerate().map(|(index, title)| (index, vec![title.to_string()])).collect();
+        let mut list = ListView::new(rows, 2).column("Title", Align::Left);
+        assert_eq!(list.render(80), vec!["   #  Title", ">  1  Launch", "   2  Docs"]);
+        assert!(list.navigate(KeyAction::NextPage));
+        assert_eq!((list.selected(), list.page_footer()), (Some(&2), "Page 2 of 2".to_string()));
+        list.toggle_mark();
+        assert_eq!(list.pick("2"), Some(&3));
+        list.toggle_mark();
+        assert_eq!(list.render(80)[1..], ["   *1  Site", ">  *2  Launch docs"]);
+        assert_eq!(list.chosen(), vec![&2, &3]);
+
+        list.set_filter("DOCS");
+        assert_eq!(list.pick("3"), None);
+        assert_eq!((list.len(), list.selected()), (2, Some(&1)));
+        assert!(!list.navigate(KeyAction::Open));
+        list.set_filter("zzz");
+        assert!(list.is_empty() && !list.has_no_rows());
+        assert_eq!(list.chosen(), vec![&2, &3]);
+        list.toggle_mark();
+        assert_eq!(list.page_footer(), "Page 1 of 1");
+    }
+}
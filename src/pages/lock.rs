@@ -0,0 +1,280 @@
+//! # Lock Screen Page
+//! Hides the session until the user proves it is still them. The lock screen is opened on top of
+//! whatever page was showing, either by the lock key or by [`AutoLock`] after a while without
+//! input, so unlocking goes back to exactly where the user was.
+//!
+//! The page shows only the username and asks for the password, then a code from the second
+//! factor if the user has one, unless one of their security keys is connected. A user whose only
+//! second factor is a security key is asked to connect it and enter the password again. Once
+//! unlocked, the caller takes the user back with [`LockScreenPage::take_user`] (a backup code may
+//! have been used up) and closes the page.
+
+use super::i18n::{tr, tr_with};
+use super::{Page, PageAction, Size};
+use crate::clock::Clock;
+use crate::models::settings::Settings;
+#[cfg(feature = "fido2")]
+use crate::security::fido2::HmacSecretAuthenticator;
+use crate::users::User;
+use chrono::{DateTime, Duration, Utc};
+use std::sync::Arc;
+
+/// # Lock Step enum
+/// Where the user is in unlocking the session.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum LockStep {
+    /// Entering the password.
+    Password,
+    /// Entering a code from the second factor.
+    SecondFactor,
+    /// Unlocked; the user is ready to be taken back.
+    Unlocked,
+}
+
+/// # `LockScreenPage` struct
+/// The lock screen for the logged-in user.
+pub struct LockScreenPage {
+    /// A copy of the logged-in user, to check the password and code against.
+    user: User,
+    /// Current step of the flow.
+    step: LockStep,
+    /// The clock second-factor codes are checked against.
+    clock: Arc<dyn Clock>,
+    /// The connected security key, which unlocks without a code.
+    #[cfg(feature = "fido2")]
+    authenticator: Option<Arc<dyn HmacSecretAuthenticator>>,
+}
+
+impl LockScreenPage {
+    /// Locks the session of `user`.
+    #[must_use]
+    pub fn new(user: User, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            user,
+            step: LockStep::Password,
+            clock,
+            #[cfg(feature = "fido2")]
+            authenticator: None,
+        }
+    }
+
+    /// Accepts one of the user's security keys, if `authenticator` holds it, instead of a code.
+    #[cfg(feature = "fido2")]
+    #[must_use]
+    pub fn with_security_key(mut self, authenticator: Arc<dyn HmacSecretAuthenticator>) -> Self {
+        self.authenticator = Some(authenticator);
+        self
+    }
+
+    /// Whether the connected security key is one of the user's.
+    #[cfg(feature = "fido2")]
+    fn security_key_present(&self) -> bool {
+        self.authenticator.as_ref().is_some_and(|authenticator| matches!(self.user.verify_security_key(authenticator.as_ref()), Ok(Some(_))))
+    }
+
+    /// Whether the user can unlock with a code, from TOTP or HOTP or one of their backup codes.
+    fn takes_codes(&self) -> bool {
+        self.user.totp_instance.is_some() || self.user.hotp_instance.is_some() || self.user.remaining_backup_codes() > 0
+    }
+
+    /// Returns the current step of the flow.
+    #[must_use]
+    pub fn step(&self) -> LockStep {
+        self.step
+    }
+
+    /// Takes the user back once the session is unlocked, so the caller can store it in the
+    /// database state (and save it if a backup code or HOTP counter was used up).
+    #[must_use]
+    pub fn take_user(&mut self) -> Option<User> {
+        (self.step == LockStep::Unlocked).then(|| self.user.clone())
+    }
+}
+
+impl Page for LockScreenPage {
+    fn masks_input(&self) -> bool {
+        self.step == LockStep::Password
+    }
+
+    fn needs_session(&self) -> bool {
+        false
+    }
+
+    fn render(&self, _size: Size) -> Vec<String> {
+        let mut lines = vec![tr("lock-title"), tr_with("lock-user", &[("username", &self.user.username)])];
+        match self.step {
+            LockStep::Password => lines.push(tr("lock-password")),
+            LockStep::SecondFactor => lines.push(tr("second-factor-prompt")),
+            LockStep::Unlocked => lines.push(tr("lock-unlocked")),
+        }
+        lines
+    }
+
+    fn handle_input(&mut self, input: &str) -> PageAction {
+        let result = match self.step {
+            LockStep::Password => match self.user.verify_password(input) {
+                #[cfg(feature = "fido2")]
+                Ok(true) if self.security_key_present() => Ok(LockStep::Unlocked),
+                Ok(true) if self.takes_codes() => Ok(LockStep::SecondFactor),
+                Ok(true) if !self.user.security_keys.is_empty() => Err(tr("lock-security-key")),
+                Ok(true) => Ok(LockStep::Unlocked),
+                Ok(false) => Err(tr("wrong-password")),
+                Err(err) => Err(err.to_string()),
+            },
+            LockStep::SecondFactor => match self.user.verify_second_factor(input.trim(), self.clock.as_ref()) {
+                Ok(true) => Ok(LockStep::Unlocked),
+                Ok(false) => Err(tr("invalid-code")),
+                Err(err) => Err(err.to_string()),
+            },
+            LockStep::Unlocked => return PageAction::None,
+        };
+        match result {
+            Ok(next) => {
+                self.step = next;
+                if next == LockStep::Unlocked { PageAction::Refresh } else { PageAction::None }
+            }
+            Err(error) => PageAction::ShowError(error),
+        }
+    }
+}
+
+/// # `AutoLock` struct
+/// Tracks input to lock the session after [`Settings::lock_after_minutes`] without any.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct AutoLock {
+    /// How long the session may sit idle, or `None` if it never locks by itself.
+    timeout: Option<Duration>,
+    /// When the last input arrived.
+    last_input: DateTime<Utc>,
+}
+
+impl AutoLock {
+    /// Starts tracking input with the user's setting, counting from now.
+    #[must_use]
+    pub fn new(settings: &Settings, clock: &dyn Clock) -> Self {
+        Self { timeout: timeout(settings), last_input: clock.now() }
+    }
+
+    /// Switches to the user's setting after they changed it, keeping the idle time counted so far.
+    pub fn update(&mut self, settings: &Settings) {
+        self.timeout = timeout(settings);
+    }
+
+    /// Records that input arrived.
+    pub fn touch(&mut self, clock: &dyn Clock) {
+        self.last_input = clock.now();
+    }
+
+    /// Asks for the lock screen if the session has been idle too long.
+    ///
+    /// # Returns
+    /// * `PageAction` - [`PageAction::Lock`] if it is time to lock, otherwise `None`.
+    #[must_use]
+    pub fn check(&self, clock: &dyn Clock) -> PageAction {
+        match self.timeout {
+            Some(timeout) if clock.now() - self.last_input >= timeout => PageAction::Lock,
+            _ => PageAction::None,
+        }
+    }
+}
+
+/// How long the session may sit idle under `settings`, or `None` if it never locks by itself.
+fn timeout(settings: &Settings) -> Option<Duration> {
+    (settings.lock_after_minutes > 0).then(|| Duration::minutes(i64::from(settings.lock_after_minutes)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{FixedClock, SystemClock};
+    use crate::security::totp::{TotpConfig, generate_token_at};
+
+    /// A clock stopped at one time that, unlike [`FixedClock`], can be shared with a page.
+    struct StoppedClock(DateTime<Utc>);
+
+    impl Clock for StoppedClock {
+        fn now(&self) -> DateTime<Utc> {
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_unlock_with_backup_code() {
+        let mut user = User::new("alice".to_string(), "password").unwrap();
+        let codes = user.enable_hotp().unwrap();
+        let mut page = LockScreenPage::new(user, Arc::new(SystemClock));
+        assert_eq!(page.render(Size::default()), vec!["=== Locked ===", "Logged in as alice", "Password:"]);
+        assert!(page.masks_input());
+        assert!(matches!(page.handle_input("wrong"), PageAction::ShowError(error) if error == "Wrong password."));
+        page.handle_input("password");
+        assert_eq!(page.step(), LockStep::SecondFactor);
+        assert!(matches!(page.handle_input("000000000"), PageAction::ShowError(_)));
+        assert_eq!(page.take_user(), None);
+        assert!(matches!(page.handle_input(&codes[0]), PageAction::Refresh));
+        assert_eq!(page.take_user().unwrap().remaining_backup_codes(), codes.len() - 1);
+    }
+
+    /// Whichever security key is plugged in, which the test can swap.
+    #[cfg(feature = "fido2")]
+    struct ConnectedKey(std::sync::Mutex<crate::security::fido2::SoftwareAuthenticator>);
+
+    #[cfg(feature = "fido2")]
+    impl HmacSecretAuthenticator for ConnectedKey {
+        fn make_credential(&self, rp_id: &str, user_id: &[u8], user_name: &str) -> Result<Vec<u8>, crate::security::SecurityError> {
+            self.0.lock().unwrap().make_credential(rp_id, user_id, user_name)
+        }
+
+        fn hmac_secret(&self, rp_id: &str, credential_id: &[u8], salt: &[u8; 32]) -> Result<[u8; 32], crate::security::SecurityError> {
+            self.0.lock().unwrap().hmac_secret(rp_id, credential_id, salt)
+        }
+    }
+
+    #[cfg(feature = "fido2")]
+    #[test]
+    fn test_unlock_with_security_key() {
+        use crate::db::{ClearTextDBState, storage::Session};
+        use crate::security::fido2::SoftwareAuthenticator;
+        use std::sync::Mutex;
+        let mut session = Session::new(ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap()), "password").unwrap();
+        session.add_security_key(&SoftwareAuthenticator([7; 32]), "blue".to_string()).unwrap();
+        let user = session.state.user;
+
+        // The key is the user's only second factor, so without it there is no code to ask for.
+        let connected = Arc::new(ConnectedKey(Mutex::new(SoftwareAuthenticator([8; 32]))));
+        let mut page = LockScreenPage::new(user, Arc::new(SystemClock)).with_security_key(Arc::clone(&connected) as Arc<dyn HmacSecretAuthenticator>);
+        let action = page.handle_input("password");
+        assert!(matches!(action, PageAction::ShowError(error) if error == "Connect one of your security keys, then enter your password again."));
+        assert_eq!(page.step(), LockStep::Password);
+        *connected.0.lock().unwrap() = SoftwareAuthenticator([7; 32]);
+        assert!(matches!(page.handle_input("password"), PageAction::Refresh));
+        assert_eq!(page.step(), LockStep::Unlocked);
+    }
+
+    #[test]
+    fn test_unlock_checks_codes_against_the_given_clock() {
+        let mut user = User::new("alice".to_string(), "password").unwrap();
+        user.enable_totp(TotpConfig::default()).unwrap();
+        let totp = user.totp_instance.clone().unwrap();
+        let now = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let mut page = LockScreenPage::new(user, Arc::new(StoppedClock(now)));
+        page.handle_input("password");
+
+        let later = FixedClock::new(now + Duration::hours(1));
+        assert!(matches!(page.handle_input(&generate_token_at(&totp, &later).unwrap()), PageAction::ShowError(_)));
+        assert!(matches!(page.handle_input(&generate_token_at(&totp, &StoppedClock(now)).unwrap()), PageAction::Refresh));
+        assert_eq!(page.step(), LockStep::Unlocked);
+    }
+
+    #[test]
+    fn test_auto_lock_after_idle_minutes() {
+        let clock = FixedClock::new(DateTime::from_timestamp(1_700_000_000, 0).unwrap());
+        let mut lock = AutoLock::new(&Settings { lock_after_minutes: 5, ..Settings::default() }, &clock);
+        clock.advance(Duration::minutes(4));
+        lock.touch(&clock);
+        clock.advance(Duration::minutes(4));
+        assert!(matches!(lock.check(&clock), PageAction::None));
+        clock.advance(Duration::minutes(1));
+        assert!(matches!(lock.check(&clock), PageAction::Lock));
+        assert!(matches!(AutoLock::new(&Settings::default(), &clock).check(&clock), PageAction::None));
+    }
+}
@@ -0,0 +1,239 @@
+//! # Login Page
+//! Unlocks a user's database: pick one of the detected users, enter the password (the caller
+//! hides the typed characters while [`LoginPage::masks_input`] is true), then a TOTP, HOTP, or
+//! backup code if the user has a second factor. A database with security keys is unlocked with
+//! the password and the connected key instead, with no code. Once [`LoginPage::step`] is
+//! [`LoginStep::LoggedIn`], the caller takes the session with [`LoginPage::into_session`] and
+//! shows the dashboard.
+
+use super::i18n::{tr, tr_with};
+use super::{DetectedUsers, Page, PageAction, Size};
+use crate::clock::Clock;
+use crate::db::storage::{Session, read_database};
+#[cfg(feature = "fido2")]
+use crate::security::fido2::HmacSecretAuthenticator;
+use crate::security::SecurityError;
+use std::path::PathBuf;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// # Login Step
+/// Where the user is in the login flow.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum LoginStep {
+    /// Choosing which user to log in as.
+    ChooseUser,
+    /// Entering the chosen user's password.
+    Password,
+    /// Entering a code from the chosen user's second factor.
+    SecondFactor,
+    /// Logged in; the session is ready.
+    LoggedIn,
+}
+
+/// # `LoginPage` struct
+/// Walks a user through logging in to one of the detected databases.
+pub struct LoginPage {
+    /// The users with a database file.
+    users: DetectedUsers,
+    /// The folder holding the database files.
+    databases_dir: PathBuf,
+    /// Current step of the flow.
+    step: LoginStep,
+    /// The user being logged in, once chosen.
+    chosen: Option<(Uuid, String)>,
+    /// The unlocked database, once the password was accepted.
+    session: Option<Session>,
+    /// The clock second-factor codes are checked against.
+    clock: Arc<dyn Clock>,
+    /// The connected security key, for databases that need one to be unlocked.
+    #[cfg(feature = "fido2")]
+    authenticator: Option<Arc<dyn HmacSecretAuthenticator>>,
+}
+
+impl LoginPage {
+    /// Creates a login page for the users detected in `databases_dir`.
+    #[must_use]
+    pub fn new(users: DetectedUsers, databases_dir: PathBuf, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            users,
+            databases_dir,
+            step: LoginStep::ChooseUser,
+            chosen: None,
+            session: None,
+            clock,
+            #[cfg(feature = "fido2")]
+            authenticator: None,
+        }
+    }
+
+    /// Unlocks databases that need a security key with `authenticator`.
+    #[cfg(feature = "fido2")]
+    #[must_use]
+    pub fn with_security_key(mut self, authenticator: Arc<dyn HmacSecretAuthenticator>) -> Self {
+        self.authenticator = Some(authenticator);
+        self
+    }
+
+    /// Returns the current step of the flow.
+    #[must_use]
+    pub fn step(&self) -> LoginStep {
+        self.step
+    }
+
+    /// Consumes the page and returns the unlocked session, if the user logged in.
+    #[must_use]
+    pub fn into_session(self) -> Option<Session> {
+        (self.step == LoginStep::LoggedIn).then_some(self.session).flatten()
+    }
+
+    /// Finds a detected user by 1-based number or by username, ignoring case.
+    fn find_user(&self, input: &str) -> Option<(Uuid, String)> {
+        let by_number = input.parse::<usize>().ok().and_then(|number| number.checked_sub(1)).and_then(|index| self.users.get(index));
+        by_number.or_else(|| self.users.iter().find(|(_, username)| username.eq_ignore_ascii_case(input))).cloned()
+    }
+
+    /// Reads the chosen user's database and decrypts it with the password, and the security key
+    /// if it needs one.
+    fn unlock(&self, user_uuid: Uuid, password: &str) -> Result<Session, String> {
+        let db = read_database(&self.databases_dir, user_uuid).map_err(|err| tr_with("read-database-failed", &[("error", &err)]))?;
+        #[cfg(feature = "fido2")]
+        if let Some(authenticator) = &self.authenticator {
+            return Session::decrypt_with_security_key(&db, password, authenticator.as_ref()).map_err(|err| unlock_error(err.as_ref()));
+        }
+        Session::decrypt(&db, password).map_err(|err| unlock_error(err.as_ref()))
+    }
+}
+
+/// The message for a database that didn't unlock.
+fn unlock_error(err: &(dyn std::error::Error + 'static)) -> String {
+    match err.downcast_ref::<SecurityError>() {
+        Some(SecurityError::SecurityKeyRequired) => tr("security-key-required"),
+        _ => tr("wrong-password"),
+    }
+}
+
+impl Page for LoginPage {
+    fn masks_input(&self) -> bool {
+        self.step() == LoginStep::Password
+    }
+
+    fn needs_session(&self) -> bool {
+        false
+    }
+
+    fn render(&self, _size: Size) -> Vec<String> {
+        let mut lines = vec![tr("login-title")];
+        let username = self.chosen.as_ref().map(|(_, username)| username.clone()).unwrap_or_default();
+        match self.step() {
+            LoginStep::ChooseUser if self.users.is_empty() => lines.push(tr("login-no-users")),
+            LoginStep::ChooseUser => {
+                lines.extend(self.users.iter().enumerate().map(|(index, (_, username))| format!("{}. {username}", index + 1)));
+                lines.push(tr("login-choose-user"));
+            }
+            LoginStep::Password => lines.push(tr_with("login-password", &[("username", &username)])),
+            LoginStep::SecondFactor => lines.push(tr("second-factor-prompt")),
+            LoginStep::LoggedIn => lines.push(tr_with("login-welcome", &[("username", &username)])),
+        }
+        lines
+    }
+
+    fn handle_input(&mut self, input: &str) -> PageAction {
+        let step = self.step();
+        let result = match step {
+            LoginStep::ChooseUser => match self.find_user(input.trim()) {
+                Some(user) => {
+                    self.chosen = Some(user);
+                    Ok(LoginStep::Password)
+                }
+                None => Err(tr_with("no-such-user", &[("name", &input.trim())])),
+            },
+            LoginStep::Password => {
+                let Some((user_uuid, _)) = self.chosen else { return PageAction::None };
+                self.unlock(user_uuid, input).map(|session| {
+                    // A database with security keys only opens with one, which is the second factor.
+                    let user = &session.state.user;
+                    let next = if user.has_second_factor() && user.security_keys.is_empty() { LoginStep::SecondFactor } else { LoginStep::LoggedIn };
+                    self.session = Some(session);
+                    next
+                })
+            }
+            LoginStep::SecondFactor => {
+                let Some(session) = self.session.as_mut() else { return PageAction::None };
+                match session.state.user.verify_second_factor(input, self.clock.as_ref()) {
+                    Ok(true) => {
+                        // A backup code was consumed or the HOTP counter moved on.
+                        session.state.mark_dirty();
+                        Ok(LoginStep::LoggedIn)
+                    }
+                    Ok(false) => Err(tr("invalid-code")),
+                    Err(err) => Err(err.to_string()),
+                }
+            }
+            LoginStep::LoggedIn => return PageAction::None,
+        };
+        match result {
+            Ok(next) => {
+                self.step = next;
+                if next == LoginStep::LoggedIn { PageAction::Refresh } else { PageAction::None }
+            }
+            Err(error) => PageAction::ShowError(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::db::ClearTextDBState;
+    use crate::db::storage::detect_users;
+    use crate::users::User;
+
+    #[test]
+    fn test_login_with_backup_code() {
+        let databases_dir = std::env::temp_dir().join(format!("ironyyy-login-{}", Uuid::new_v4()));
+        let mut user = User::new("alice".to_string(), "password").unwrap();
+        let codes = user.enable_hotp().unwrap();
+        Session::new(ClearTextDBState::new(user), "password").unwrap().save(&databases_dir).unwrap();
+
+        let mut page = LoginPage::new(detect_users(&databases_dir).unwrap(), databases_dir.clone(), Arc::new(SystemClock));
+        assert_eq!(page.render(Size::default())[1..], ["1. alice".to_string(), "Choose a user by number or name:".to_string()]);
+        assert!(matches!(page.handle_input("bob"), PageAction::ShowError(error) if error == "There is no user \"bob\"."));
+        page.handle_input("1");
+        assert!(page.masks_input());
+        assert!(matches!(page.handle_input("wrong"), PageAction::ShowError(error) if error == "Wrong password."));
+        assert_eq!(page.render(Size::default())[1..], ["Password for alice:".to_string()]);
+        page.handle_input("password");
+        assert_eq!(page.step(), LoginStep::SecondFactor);
+        page.handle_input("000000000");
+        assert_eq!(page.step(), LoginStep::SecondFactor);
+        assert!(matches!(page.handle_input(&codes[0]), PageAction::Refresh));
+        assert_eq!(page.render(Size::default())[1], "Welcome back, alice!");
+
+        let session = page.into_session().unwrap();
+        assert_eq!(session.state.user.remaining_backup_codes(), codes.len() - 1);
+        assert!(session.state.is_dirty());
+        std::fs::remove_dir_all(&databases_dir).unwrap();
+    }
+
+    #[cfg(feature = "fido2")]
+    #[test]
+    fn test_login_with_security_key() {
+        use crate::security::fido2::SoftwareAuthenticator;
+        let databases_dir = std::env::temp_dir().join(format!("ironyyy-login-key-{}", Uuid::new_v4()));
+        let mut session = Session::new(ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap()), "password").unwrap();
+        session.add_security_key(&SoftwareAuthenticator([7; 32]), "blue".to_string()).unwrap();
+        session.save(&databases_dir).unwrap();
+
+        let mut page = LoginPage::new(detect_users(&databases_dir).unwrap(), databases_dir.clone(), Arc::new(SystemClock));
+        page.handle_input("alice");
+        assert!(matches!(page.handle_input("password"), PageAction::ShowError(error) if error == "Connect one of your security keys to unlock this database."));
+
+        let mut page = LoginPage::new(detect_users(&databases_dir).unwrap(), databases_dir.clone(), Arc::new(SystemClock)).with_security_key(Arc::new(SoftwareAuthenticator([7; 32])));
+        page.handle_input("alice");
+        assert!(matches!(page.handle_input("password"), PageAction::Refresh));
+        assert_eq!(page.step(), LoginStep::LoggedIn);
+        std::fs::remove_dir_all(&databases_dir).unwrap();
+    }
+}
@@ -0,0 +1,155 @@
+//! # Markdown Module
+//! Renders Markdown descriptions for the detail pages, either styled with ANSI escape codes or as
+//! plain text for terminals that can't show them.
+//!
+//! Only the subset that descriptions need is understood: ATX headings (`# Title`), bullet and
+//! numbered lists, fenced code blocks, and inline `**bold**` and `` `code` ``. Anything else is
+//! shown as written.
+
+/// ANSI escape code for bold text.
+const BOLD: &str = "\x1b[1m";
+/// ANSI escape code for underlined text.
+const UNDERLINE: &str = "\x1b[4m";
+/// ANSI escape code for dimmed text.
+const DIM: &str = "\x1b[2m";
+/// ANSI escape code that clears all styling.
+const RESET: &str = "\x1b[0m";
+
+/// # Render Styled
+/// Renders Markdown as terminal lines styled with ANSI escape codes.
+///
+/// # Arguments
+/// * `markdown` - The Markdown source, e.g. an epic or story description.
+///
+/// # Returns
+/// * `Vec<String>` - The lines to print.
+///
+/// # Examples
+/// ```
+/// use ironyyy::pages::markdown::render_styled;
+/// assert_eq!(render_styled("# Goal"), vec!["\x1b[1m\x1b[4mGoal\x1b[0m"]);
+/// ```
+#[must_use]
+pub fn render_styled(markdown: &str) -> Vec<String> {
+    render(markdown, true)
+}
+
+/// # Render Plain
+/// Renders Markdown as plain text, with the markup removed but the structure kept.
+///
+/// # Arguments
+/// * `markdown` - The Markdown source, e.g. an epic or story description.
+///
+/// # Returns
+/// * `Vec<String>` - The lines to print.
+///
+/// # Examples
+/// ```
+/// use ironyyy::pages::markdown::render_plain;
+/// assert_eq!(render_plain("* **Fast** login"), vec!["  - Fast login"]);
+/// ```
+#[must_use]
+pub fn render_plain(markdown: &str) -> Vec<String> {
+    render(markdown, false)
+}
+
+/// Renders Markdown line by line, styled or plain.
+fn render(markdown: &str, styled: bool) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut in_code_block = false;
+    for line in markdown.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            lines.push(if styled { format!("    {DIM}{line}{RESET}") } else { format!("    {line}") });
+            continue;
+        }
+
+        let trimmed = line.trim_start();
+        let level = trimmed.chars().take_while(|&c| c == '#').count();
+        if (1..=6).contains(&level) && trimmed[level..].starts_with(' ') {
+            let text = render_inline(trimmed[level..].trim(), false);
+            lines.push(match (styled, level) {
+                (true, 1) => format!("{BOLD}{UNDERLINE}{text}{RESET}"),
+                (true, _) => format!("{BOLD}{text}{RESET}"),
+                (false, _) => text,
+            });
+        } else if let Some(item) = ["- ", "* ", "+ "].iter().find_map(|bullet| trimmed.strip_prefix(bullet)) {
+            let bullet = if styled { '•' } else { '-' };
+            lines.push(format!("  {bullet} {}", render_inline(item, styled)));
+        } else if let Some((number, item)) = trimmed.split_once(". ")
+            && !number.is_empty()
+            && number.chars().all(|c| c.is_ascii_digit())
+        {
+            lines.push(format!("  {number}. {}", render_inline(item, styled)));
+        } else {
+            lines.push(render_inline(line, styled));
+        }
+    }
+    lines
+}
+
+/// Renders the inline `**bold**` and `` `code` `` spans of a line; unmatched markers are kept.
+fn render_inline(text: &str, styled: bool) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    loop {
+        let next = [("**", BOLD), ("`", DIM)]
+            .into_iter()
+            .filter_map(|(marker, style)| {
+                let start = rest.find(marker)?;
+                let len = rest[start + marker.len()..].find(marker)?;
+                Some((start, marker, style, len))
+            })
+            .min_by_key(|&(start, ..)| start);
+        let Some((start, marker, style, len)) = next else {
+            out.push_str(rest);
+            return out;
+        };
+        let inner = &rest[start + marker.len()..start + marker.len() + len];
+        out.push_str(&rest[..start]);
+        if styled {
+            out.extend([style, inner, RESET]);
+        } else {
+            out.push_str(inner);
+        }
+        rest = &rest[start + 2 * marker.len() + len..];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A description using every supported element.
+    const DESCRIPTION: &str = "# Login\nUsers sign in with **TOTP**.\n\n## Steps\n- Open `ironyyy`\n2. Enter code\n```\nlet x = 1;\n```\nA lone ** marker";
+
+    #[test]
+    fn test_render_plain() {
+        assert_eq!(
+            render_plain(DESCRIPTION),
+            vec![
+                "Login",
+                "Users sign in with TOTP.",
+                "",
+                "Steps",
+                "  - Open ironyyy",
+                "  2. Enter code",
+                "    let x = 1;",
+                "A lone ** marker",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_styled() {
+        let lines = render_styled(DESCRIPTION);
+        assert_eq!(lines[1], format!("Users sign in with {BOLD}TOTP{RESET}."));
+        assert_eq!(lines[3], format!("{BOLD}Steps{RESET}"));
+        assert_eq!(lines[4], format!("  • Open {DIM}ironyyy{RESET}"));
+        assert_eq!(lines[6], format!("    {DIM}let x = 1;{RESET}"));
+        assert_eq!(lines.len(), 8);
+    }
+}
@@ -0,0 +1,140 @@
+//! # Modals
+//! Small dialogs, such as a confirmation, a picker, or an error, that a page opens over itself
+//! with [`PageAction::OpenModal`](super::PageAction::OpenModal). While a modal is open it gets
+//! the input and is drawn in a box over the page, which stays as it was underneath. When the
+//! modal closes, its result goes back to the page through [`Page::modal_closed`](super::Page::modal_closed)
+//! as a `Box<dyn Any>` holding the modal's result type, which the page downcasts:
+//!
+//! * [`ConfirmModal`] gives a `bool`: whether the user said yes.
+//! * [`PickerModal<T>`] gives an `Option<T>`: the value chosen, or `None` if the user cancelled.
+//! * [`ErrorDialog`] gives `()`.
+
+use super::i18n::tr;
+use super::picker::{Picked, Picker};
+use super::Size;
+use std::any::Any;
+
+/// # Modal Outcome enum
+/// What an input did to a modal.
+pub enum ModalOutcome {
+    /// Keep the modal open.
+    Open,
+    /// Keep the modal open and show this error.
+    Error(String),
+    /// Close the modal, handing this result to the page below.
+    Close(Box<dyn Any>),
+}
+
+/// # Modal trait
+/// A dialog shown over a page.
+pub trait Modal {
+    /// Renders the dialog's lines, without the box drawn around them.
+    fn render(&self, size: Size) -> Vec<String>;
+    /// Handles a line of user input.
+    fn handle_input(&mut self, input: &str) -> ModalOutcome;
+}
+
+/// # `ConfirmModal` struct
+/// A yes-or-no question.
+pub struct ConfirmModal {
+    /// The question, e.g. `Delete EPIC-1 Launch?`.
+    question: String,
+}
+
+impl ConfirmModal {
+    /// Creates the confirmation.
+    #[must_use]
+    pub fn new(question: String) -> Self {
+        Self { question }
+    }
+}
+
+impl Modal for ConfirmModal {
+    fn render(&self, _size: Size) -> Vec<String> {
+        vec![self.question.clone(), tr("modal-confirm-hint")]
+    }
+
+    fn handle_input(&mut self, input: &str) -> ModalOutcome {
+        match input.trim().to_lowercase().as_str() {
+            "y" | "yes" => ModalOutcome::Close(Box::new(true)),
+            "n" | "no" | "cancel" => ModalOutcome::Close(Box::new(false)),
+            _ => ModalOutcome::Error(tr("modal-confirm-error")),
+        }
+    }
+}
+
+/// # `PickerModal` struct
+/// A [`Picker`] in a dialog.
+pub struct PickerModal<T> {
+    /// The prompt shown above the options, e.g. `New status:`.
+    prompt: String,
+    /// The options.
+    picker: Picker<T>,
+}
+
+impl<T> PickerModal<T> {
+    /// Creates the dialog.
+    #[must_use]
+    pub fn new(prompt: String, picker: Picker<T>) -> Self {
+        Self { prompt, picker }
+    }
+}
+
+impl<T: Clone + PartialEq + 'static> Modal for PickerModal<T> {
+    fn render(&self, _size: Size) -> Vec<String> {
+        let mut lines = vec![self.prompt.clone()];
+        lines.extend(self.picker.render());
+        lines
+    }
+
+    fn handle_input(&mut self, input: &str) -> ModalOutcome {
+        match self.picker.handle_input(input) {
+            Ok(Picked::Browsing) => ModalOutcome::Open,
+            Ok(Picked::Chosen(value)) => ModalOutcome::Close(Box::new(Some(value))),
+            Ok(Picked::Cancelled) => ModalOutcome::Close(Box::new(None::<T>)),
+            Err(error) => ModalOutcome::Error(error),
+        }
+    }
+}
+
+/// # `ErrorDialog` struct
+/// An error the user has to acknowledge.
+pub struct ErrorDialog {
+    /// The message.
+    message: String,
+}
+
+impl ErrorDialog {
+    /// Creates the dialog.
+    #[must_use]
+    pub fn new(message: String) -> Self {
+        Self { message }
+    }
+}
+
+impl Modal for ErrorDialog {
+    fn render(&self, _size: Size) -> Vec<String> {
+        vec![format!("Error: {}", self.message), tr("modal-dismiss-hint")]
+    }
+
+    fn handle_input(&mut self, _input: &str) -> ModalOutcome {
+        ModalOutcome::Close(Box::new(()))
+    }
+}
+
+/// Draws a modal's lines in a box over the page's lines, from the line below the page's title.
+#[must_use]
+pub fn overlay(page: Vec<String>, modal: &[String]) -> Vec<String> {
+    let width = modal.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+    let mut boxed = vec![format!("┌{}┐", "─".repeat(width + 2))];
+    boxed.extend(modal.iter().map(|line| format!("│ {line:<width$} │")));
+    boxed.push(format!("└{}┘", "─".repeat(width + 2)));
+    let mut lines = page;
+    for (offset, line) in boxed.into_iter().enumerate() {
+        match lines.get_mut(offset + 1) {
+            Some(existing) => *existing = line,
+            None => lines.push(line),
+        }
+    }
+    lines
+}
@@ -0,0 +1,172 @@
+//! # Picker
+//! A small selector for choosing one of a few values, such as a status or a priority. Options are
+//! numbered; the user types a number or an option's name, or moves the selection with `j`/`k` (or
+//! the arrow keys, through [`Picker::handle_key`]) and presses Enter.
+//!
+//! [`status_picker`] builds the options from the database's workflow, so custom statuses show up
+//! without the pages knowing about them.
+
+use super::i18n::{tr, tr_with};
+use super::line_editor::Key;
+use crate::db::ClearTextDBState;
+use crate::models::{Status, workflow::{Transition, TransitionAction}};
+use crate::service::statuses::ordered_statuses;
+
+/// # Picked enum
+/// What an input did to a picker.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Picked<T> {
+    /// The selection moved, or nothing happened; keep picking.
+    Browsing,
+    /// The user chose this value.
+    Chosen(T),
+    /// The user backed out without choosing.
+    Cancelled,
+}
+
+/// # Picker struct
+/// Numbered options, each a value and the name shown for it, with one selected.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Picker<T> {
+    /// What is being chosen, e.g. `status`; input matching no option is reported with the
+    /// `no-such-<noun>` message.
+    noun: &'static str,
+    /// The values and their names, in the order shown.
+    options: Vec<(T, String)>,
+    /// The index of the selected option.
+    selected: usize,
+}
+
+impl<T: Clone + PartialEq> Picker<T> {
+    /// Creates a picker over `options`, with the first one selected.
+    #[must_use]
+    pub fn new(noun: &'static str, options: Vec<(T, String)>) -> Self {
+        Self { noun, options, selected: 0 }
+    }
+
+    /// Selects the option holding `value`, if there is one.
+    #[must_use]
+    pub fn selecting(mut self, value: &T) -> Self {
+        self.selected = self.options.iter().position(|(option, _)| option == value).unwrap_or(self.selected);
+        self
+    }
+
+    /// The selected value, if there are any options.
+    #[must_use]
+    pub fn selected(&self) -> Option<&T> {
+        self.options.get(self.selected).map(|(value, _)| value)
+    }
+
+    /// The options' values and names, in the order shown.
+    #[must_use]
+    pub fn options(&self) -> &[(T, String)] {
+        &self.options
+    }
+
+    /// Handles a line of input: a number or name chooses that option, `j` and `k` move the
+    /// selection, an empty line chooses the selected option, and `cancel` backs out.
+    ///
+    /// # Errors
+    /// * `String` - If the input matches no option.
+    pub fn handle_input(&mut self, input: &str) -> Result<Picked<T>, String> {
+        let input = input.trim();
+        let index = match input {
+            "" => self.selected,
+            "j" => return Ok(self.step(true)),
+            "k" => return Ok(self.step(false)),
+            "cancel" => return Ok(Picked::Cancelled),
+            _ => input
+                .parse::<usize>()
+                .ok()
+                .and_then(|number| number.checked_sub(1))
+                .filter(|index| *index < self.options.len())
+                .or_else(|| self.options.iter().position(|(_, name)| name.eq_ignore_ascii_case(input)))
+                .ok_or_else(|| tr_with(&format!("no-such-{}", self.noun), &[("name", &input)]))?,
+        };
+        Ok(self.choose(index))
+    }
+
+    /// Handles a key: the arrow keys move the selection, a digit chooses that option, Enter
+    /// chooses the selected option, and Escape backs out.
+    pub fn handle_key(&mut self, key: Key) -> Picked<T> {
+        match key {
+            Key::Down => self.step(true),
+            Key::Up => self.step(false),
+            Key::Enter => self.choose(self.selected),
+            Key::Escape => Picked::Cancelled,
+            Key::Char(digit) => match digit.to_digit(10).and_then(|number| usize::try_from(number).ok()?.checked_sub(1)) {
+                Some(index) if index < self.options.len() => self.choose(index),
+                _ => Picked::Browsing,
+            },
+            _ => Picked::Browsing,
+        }
+    }
+
+    /// Renders the numbered options, with a `>` before the selected one.
+    #[must_use]
+    pub fn render(&self) -> Vec<String> {
+        let mut lines: Vec<String> = self
+            .options
+            .iter()
+            .enumerate()
+            .map(|(index, (_, name))| format!("{} {}. {name}", if index == self.selected { '>' } else { ' ' }, index + 1))
+            .collect();
+        lines.push(tr("picker-hint"));
+        lines
+    }
+
+    /// Moves the selection one option down or up, stopping at the ends.
+    fn step(&mut self, down: bool) -> Picked<T> {
+        self.selected = if down { (self.selected + 1).min(self.options.len().saturating_sub(1)) } else { self.selected.saturating_sub(1) };
+        Picked::Browsing
+    }
+
+    /// Selects and chooses an option.
+    fn choose(&mut self, index: usize) -> Picked<T> {
+        self.selected = index;
+        self.options.get(index).map_or(Picked::Browsing, |(value, _)| Picked::Chosen(value.clone()))
+    }
+}
+
+/// A picker over the statuses an item in `current` may move to under the database's workflow,
+/// in the workflow's order, with `current` selected. Statuses that are only reachable by
+/// reopening are marked as such.
+#[must_use]
+pub fn status_picker(state: &ClearTextDBState, current: Status) -> Picker<Status> {
+    let options = ordered_statuses(state)
+        .into_iter()
+        .filter_map(|definition| match state.workflow.check(current, definition.status, TransitionAction::Move, &state.statuses) {
+            Transition::Allowed => Some((definition.status, definition.name.clone())),
+            Transition::Requires(TransitionAction::Reopen) => Some((definition.status, tr_with("picker-reopen", &[("status", &definition.name)]))),
+            Transition::Requires(TransitionAction::Move) | Transition::Forbidden => None,
+        })
+        .collect();
+    Picker::new("status", options).selecting(&current)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::statuses::StatusCategory;
+    use crate::service::statuses::create_status;
+    use crate::users::User;
+
+    #[test]
+    fn test_status_picker_follows_workflow() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let review = create_status(&mut state, "Review".to_string(), StatusCategory::InProgress).unwrap();
+        state.workflow.forbid(StatusCategory::InProgress, StatusCategory::Todo);
+        let mut picker = status_picker(&state, Status::InProgress);
+        assert_eq!(picker.render()[..6], ["> 1. In Progress", "  2. Blocked", "  3. In Review", "  4. Cancelled", "  5. Closed", "  6. Review"]);
+        assert_eq!(picker.handle_key(Key::Down), Picked::Browsing);
+        assert_eq!(picker.handle_key(Key::Enter), Picked::Chosen(Status::Blocked));
+        assert_eq!(picker.handle_input("review"), Ok(Picked::Chosen(review)));
+        assert_eq!(picker.handle_input("7"), Err("There is no status \"7\".".to_string()));
+        assert_eq!(picker.handle_key(Key::Escape), Picked::Cancelled);
+
+        let mut picker = status_picker(&state, Status::Closed);
+        assert_eq!(picker.options()[0].1, "Open (reopen)");
+        picker.handle_input("k").unwrap();
+        assert_eq!(picker.handle_input(""), Ok(Picked::Chosen(Status::Cancelled)));
+    }
+}
@@ -0,0 +1,22 @@
+//! # Progress Bar
+//! Draws an epic's [`Progress`] as a bar followed by its counts, for the epic list, the epic
+//! detail page, and the dashboard.
+//!
+//! ```rust
+//! use ironyyy::models::epics::Progress;
+//! use ironyyy::pages::progress_bar::progress_bar;
+//! assert_eq!(progress_bar(Progress { done: 1, total: 2 }), "[#####-----] 1/2 (50%)");
+//! assert_eq!(progress_bar(Progress::default()), "[----------] 0/0 (0%)");
+//! ```
+
+use crate::models::epics::Progress;
+
+/// The number of characters between the brackets of a bar.
+pub const BAR_WIDTH: usize = 10;
+
+/// Draws `progress` as a bar of [`BAR_WIDTH`] characters followed by e.g. `1/2 (50%)`.
+#[must_use]
+pub fn progress_bar(progress: Progress) -> String {
+    let filled = usize::from(progress.percent()) * BAR_WIDTH / 100;
+    format!("[{}{}] {progress}", "#".repeat(filled), "-".repeat(BAR_WIDTH - filled))
+}
@@ -0,0 +1,246 @@
+//! # Register Page
+//! Creates a new user: a username that no detected user has, a password meeting the password
+//! policy (typed twice, hidden while [`RegisterPage::masks_input`] is true), and optionally an
+//! authenticator app confirmed with a code. The user's encrypted database is then written and
+//! the user is logged straight in; once [`RegisterPage::step`] is [`RegisterStep::Registered`],
+//! the caller takes the session with [`RegisterPage::into_session`] and shows the dashboard.
+
+use super::i18n::{tr, tr_with};
+use super::{DetectedUsers, Page, PageAction, Size};
+use crate::clock::Clock;
+use crate::db::ClearTextDBState;
+use crate::db::storage::Session;
+use crate::models::validation::{MIN_PASSWORD_LENGTH, validate_password, validate_username};
+use crate::security::totp::{TotpConfig, onboard_totp, verify_totp};
+use crate::service::users::register_user;
+use crate::users::User;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// # Register Step
+/// Where the user is in the registration flow.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum RegisterStep {
+    /// Choosing a username.
+    Username,
+    /// Choosing a password.
+    Password,
+    /// Typing the password again.
+    ConfirmPassword,
+    /// Deciding whether to set up an authenticator app.
+    OfferTotp,
+    /// Scanning the QR code and confirming it with a code.
+    EnrollTotp,
+    /// Registered and logged in; the session is ready.
+    Registered,
+}
+
+/// # TOTP Enrollment struct
+/// An authenticator app being set up, before it is confirmed.
+struct TotpEnrollment {
+    /// The user with the new secret and backup codes.
+    user: User,
+    /// The onboarding QR code lines.
+    qr_lines: Vec<String>,
+    /// The plaintext backup codes, shown once.
+    backup_codes: Vec<String>,
+}
+
+/// # `RegisterPage` struct
+/// Walks a new user through creating an account and database.
+pub struct RegisterPage {
+    /// The users with a database file, whose names are taken.
+    users: DetectedUsers,
+    /// The folder holding the database files.
+    databases_dir: PathBuf,
+    /// Current step of the flow.
+    step: RegisterStep,
+    /// The chosen username.
+    username: String,
+    /// The chosen password, until the account is created.
+    password: String,
+    /// The new user, once the password is confirmed.
+    user: Option<User>,
+    /// The authenticator app being set up, if the user asked for one.
+    enrollment: Option<TotpEnrollment>,
+    /// The new, logged-in database.
+    session: Option<Session>,
+    /// The clock the account is stamped with and codes are checked against.
+    clock: Arc<dyn Clock>,
+}
+
+impl RegisterPage {
+    /// Creates a registration page, given the users already detected in `databases_dir`.
+    #[must_use]
+    pub fn new(users: DetectedUsers, databases_dir: PathBuf, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            users,
+            databases_dir,
+            step: RegisterStep::Username,
+            username: String::new(),
+            password: String::new(),
+            user: None,
+            enrollment: None,
+            session: None,
+            clock,
+        }
+    }
+
+    /// Returns the current step of the flow.
+    #[must_use]
+    pub fn step(&self) -> RegisterStep {
+        self.step
+    }
+
+    /// Consumes the page and returns the new user's session, if registration finished.
+    #[must_use]
+    pub fn into_session(self) -> Option<Session> {
+        (self.step == RegisterStep::Registered).then_some(self.session).flatten()
+    }
+
+    /// Handles one input for the current step, returning the next step.
+    fn advance(&mut self, input: &str) -> Result<RegisterStep, String> {
+        match self.step() {
+            RegisterStep::Username => {
+                let username = validate_username(input, self.users.iter().map(|(_, username)| username.as_str())).map_err(|err| err.to_string())?;
+                self.username = username;
+                Ok(RegisterStep::Password)
+            }
+            RegisterStep::Password => {
+                validate_password(input, &self.username).map_err(|err| err.to_string())?;
+                self.password = input.to_string();
+                Ok(RegisterStep::ConfirmPassword)
+            }
+            RegisterStep::ConfirmPassword => {
+                if input != self.password {
+                    self.password.clear();
+                    self.step = RegisterStep::Password;
+                    return Err(tr("register-passwords-differ"));
+                }
+                let user = register_user(self.username.clone(), input, self.clock.as_ref()).map_err(|err| err.to_string())?;
+                self.user = Some(user);
+                Ok(RegisterStep::OfferTotp)
+            }
+            RegisterStep::OfferTotp => match input.trim().to_lowercase().as_str() {
+                "y" | "yes" => {
+                    let mut user = self.user.clone().unwrap_or_default();
+                    let backup_codes = user.enable_totp(TotpConfig::default()).map_err(|err| err.to_string())?;
+                    let qr_lines = user.totp_instance.as_ref().map(onboard_totp).transpose().map_err(|err| err.to_string())?.unwrap_or_default();
+                    self.enrollment = Some(TotpEnrollment { user, qr_lines, backup_codes });
+                    Ok(RegisterStep::EnrollTotp)
+                }
+                "n" | "no" => self.finish(),
+                _ => Err(tr("register-answer-yes-no")),
+            },
+            RegisterStep::EnrollTotp => {
+                if input.trim() == "skip" {
+                    self.enrollment = None;
+                    return self.finish();
+                }
+                let confirmed = match &mut self.enrollment {
+                    Some(TotpEnrollment { user: User { totp_instance: Some(totp), .. }, .. }) => verify_totp(totp, input.trim(), self.clock.as_ref()).map_err(|err| err.to_string())?,
+                    _ => false,
+                };
+                if !confirmed {
+                    return Err(tr("register-code-mismatch"));
+                }
+                if let Some(enrollment) = self.enrollment.take() {
+                    self.user = Some(enrollment.user);
+                }
+                self.finish()
+            }
+            RegisterStep::Registered => Ok(RegisterStep::Registered),
+        }
+    }
+
+    /// Derives the key, writes the new user's encrypted database, and logs them in.
+    fn finish(&mut self) -> Result<RegisterStep, String> {
+        let Some(user) = self.user.clone() else {
+            return Err(tr("register-not-created"));
+        };
+        let mut session = Session::new(ClearTextDBState::new(user), &self.password).map_err(|err| err.to_string())?;
+        session.save(&self.databases_dir).map_err(|err| tr_with("write-database-failed", &[("error", &err)]))?;
+        self.password.clear();
+        self.session = Some(session);
+        Ok(RegisterStep::Registered)
+    }
+}
+
+impl Page for RegisterPage {
+    fn masks_input(&self) -> bool {
+        matches!(self.step(), RegisterStep::Password | RegisterStep::ConfirmPassword)
+    }
+
+    fn needs_session(&self) -> bool {
+        false
+    }
+
+    fn render(&self, _size: Size) -> Vec<String> {
+        let mut lines = vec![tr("register-title")];
+        match self.step() {
+            RegisterStep::Username => lines.push(tr("register-username")),
+            RegisterStep::Password => lines.push(tr_with("register-password", &[("length", &MIN_PASSWORD_LENGTH)])),
+            RegisterStep::ConfirmPassword => lines.push(tr("register-confirm-password")),
+            RegisterStep::OfferTotp => lines.push(tr("register-offer-totp")),
+            RegisterStep::EnrollTotp => {
+                if let Some(enrollment) = &self.enrollment {
+                    lines.extend(enrollment.qr_lines.iter().cloned());
+                    lines.push(tr("backup-codes-intro"));
+                    lines.extend(enrollment.backup_codes.iter().map(|code| format!("  {code}")));
+                }
+                lines.push(tr("register-enter-code"));
+            }
+            RegisterStep::Registered => lines.push(tr_with("register-welcome", &[("username", &self.username)])),
+        }
+        lines
+    }
+
+    fn handle_input(&mut self, input: &str) -> PageAction {
+        match self.advance(input) {
+            Ok(next) => {
+                self.step = next;
+                if next == RegisterStep::Registered { PageAction::Refresh } else { PageAction::None }
+            }
+            Err(error) => PageAction::ShowError(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::db::storage::detect_users;
+    use crate::security::totp::generate_token_at;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_register_with_totp() {
+        let databases_dir = std::env::temp_dir().join(format!("ironyyy-register-{}", Uuid::new_v4()));
+        let mut page = RegisterPage::new(vec![(Uuid::new_v4(), "bob".to_string())], databases_dir.clone(), Arc::new(SystemClock));
+        assert!(matches!(page.handle_input("Bob"), PageAction::ShowError(error) if error == "The name \"Bob\" is already taken"));
+        page.handle_input("  alice ");
+        assert!(matches!(page.handle_input("short"), PageAction::ShowError(error) if error == "The password must be at least 12 characters"));
+        page.handle_input("correct horse battery");
+        assert!(page.masks_input());
+        page.handle_input("correct horse batter");
+        assert_eq!(page.step(), RegisterStep::Password);
+        page.handle_input("correct horse battery");
+        page.handle_input("correct horse battery");
+        page.handle_input("maybe");
+        assert_eq!(page.step(), RegisterStep::OfferTotp);
+        page.handle_input("y");
+        assert!(page.render(Size::default()).iter().any(|line| line.starts_with("Write down these backup codes")));
+        page.handle_input("not-a-code");
+        assert_eq!(page.step(), RegisterStep::EnrollTotp);
+        let code = page.enrollment.as_ref().and_then(|enrollment| enrollment.user.totp_instance.clone()).map(|totp| generate_token_at(&totp, &SystemClock).unwrap()).unwrap();
+        page.handle_input(&code);
+        assert_eq!(page.render(Size::default())[1], "Welcome, alice! Your database has been created.");
+
+        let session = page.into_session().unwrap();
+        assert!(session.state.user.has_second_factor());
+        assert_eq!(detect_users(&databases_dir).unwrap(), vec![(session.state.user.user_uuid, "alice".to_string())]);
+        assert_eq!(Session::unlock(&databases_dir, session.state.user.user_uuid, "correct horse battery").unwrap().state.user, session.state.user);
+        std::fs::remove_dir_all(&databases_dir).unwrap();
+    }
+}
@@ -0,0 +1,109 @@
+//! # Reminders
+//! The reminders panel shown on the dashboard and the banner shown once a database is unlocked,
+//! both listing overdue and upcoming items.
+
+use super::i18n::{tr, tr_with};
+use super::keybindings::{KeyContext, KeyMap};
+use super::{Page, PageAction, Size, global_action};
+use crate::service::reminders::{Reminder, Urgency};
+
+/// # `RemindersPanel` struct
+/// A read-only list of reminders, most pressing first.
+pub struct RemindersPanel {
+    /// The reminders, in the order given.
+    reminders: Vec<Reminder>,
+}
+
+impl RemindersPanel {
+    /// Creates the panel from reminders found by [`crate::service::reminders::reminders`].
+    #[must_use]
+    pub fn new(reminders: Vec<Reminder>) -> Self {
+        Self { reminders }
+    }
+}
+
+impl Page for RemindersPanel {
+    fn reads_text(&self) -> bool {
+        false
+    }
+
+    fn render(&self, _size: Size) -> Vec<String> {
+        let mut lines = vec![tr("reminders-title")];
+        if self.reminders.is_empty() {
+            lines.push(tr("reminders-empty"));
+        }
+        lines.extend(self.reminders.iter().map(|reminder| {
+            let id = match reminder.urgency {
+                Urgency::Overdue => "reminder-overdue",
+                Urgency::DueToday => "reminder-due-today",
+                Urgency::DueSoon => "reminder-due-soon",
+            };
+            tr_with(id, &[("item", &reminder.label), ("date", &reminder.due_date)])
+        }));
+        lines
+    }
+
+    fn handle_input(&mut self, input: &str) -> PageAction {
+        global_action(&KeyMap::active(), KeyContext::Global, input).unwrap_or(PageAction::None)
+    }
+}
+
+/// # `ReminderBanner` struct
+/// A one-line summary of the reminders, shown until the user dismisses it.
+pub struct ReminderBanner {
+    /// The banner text, or `None` once dismissed or if nothing is due.
+    text: Option<String>,
+}
+
+impl ReminderBanner {
+    /// Creates the banner from reminders found by [`crate::service::reminders::reminders`].
+    #[must_use]
+    pub fn new(reminders: &[Reminder]) -> Self {
+        let overdue = reminders.iter().filter(|reminder| reminder.urgency == Urgency::Overdue).count();
+        let upcoming = reminders.len() - overdue;
+        let text = match (overdue, upcoming) {
+            (0, 0) => None,
+            (0, upcoming) => Some(tr_with("reminders-due-soon", &[("count", &upcoming)])),
+            (overdue, 0) => Some(tr_with("reminders-overdue", &[("count", &overdue)])),
+            (overdue, upcoming) => Some(tr_with("reminders-overdue-and-due-soon", &[("overdue", &overdue), ("upcoming", &upcoming)])),
+        };
+        Self { text }
+    }
+
+    /// The banner line, unless it was dismissed or nothing is due.
+    #[must_use]
+    pub fn render(&self) -> Option<String> {
+        self.text.as_ref().map(|text| tr_with("reminders-dismiss", &[("text", text)]))
+    }
+
+    /// Hides the banner for the rest of the session.
+    pub fn dismiss(&mut self) {
+        self.text = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::references::ItemRef;
+    use chrono::NaiveDate;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_render_reminders() {
+        let due_date = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let reminder = |label: &str, urgency| Reminder { item: ItemRef::Story(Uuid::new_v4()), label: label.to_string(), due_date, urgency };
+        let found = vec![reminder("STORY-1 Login", Urgency::Overdue), reminder("STORY-2 Docs", Urgency::DueSoon)];
+        assert_eq!(RemindersPanel::new(Vec::new()).render(Size::default())[1], "Nothing is due soon.");
+        assert_eq!(
+            RemindersPanel::new(found.clone()).render(Size::default()),
+            vec!["--- Reminders ---", "STORY-1 Login  overdue since 2024-03-01", "STORY-2 Docs  due 2024-03-01"]
+        );
+
+        let mut banner = ReminderBanner::new(&found);
+        assert_eq!(banner.render().as_deref(), Some("! 1 item overdue, 1 due soon (press x to dismiss)"));
+        banner.dismiss();
+        assert_eq!(banner.render(), None);
+        assert_eq!(ReminderBanner::new(&[]).render(), None);
+    }
+}
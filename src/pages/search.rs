@@ -0,0 +1,166 @@
+//! # Search Page
+//! Finds epics and stories as the user types: every line that isn't a navigation key becomes the
+//! new query, and the results are refreshed straight away. Short IDs and titles are matched
+//! fuzzily with [`quick_open`], then anything else mentioning the words is added from the
+//! full-text [`SearchIndex`]. Choosing a result opens its detail page by its [route](Route).
+
+use super::i18n::{tr, tr_with};
+use super::{Page, PageAction, Size, global_action};
+use super::keybindings::{KeyAction, KeyContext, KeyMap};
+use super::list::ListView;
+use super::table::Align;
+use crate::db::ClearTextDBState;
+use crate::nav::routes::Route;
+use crate::service::quick_open::quick_open;
+use crate::service::references::ItemRef;
+use crate::service::search::SearchIndex;
+use crate::service::{get_epic, get_story};
+
+/// The maximum number of results shown.
+pub const RESULT_LIMIT: usize = 10;
+
+/// # `SearchPage` struct
+/// A query line with live results across epics and stories.
+pub struct SearchPage {
+    /// A snapshot of the database state to search.
+    state: ClearTextDBState,
+    /// The full-text index over `state`.
+    index: SearchIndex,
+    /// The current query.
+    query: String,
+    /// The results for the current query, each with its short ID and title, and status.
+    results: ListView<ItemRef>,
+    /// The keybindings.
+    keys: KeyMap,
+}
+
+impl SearchPage {
+    /// Creates the search page over the current database state, with an empty query.
+    #[must_use]
+    pub fn new(state: &ClearTextDBState) -> Self {
+        Self {
+            state: state.clone(),
+            index: SearchIndex::build(state),
+            query: String::new(),
+            results: results_list(Vec::new()),
+            keys: KeyMap::active(),
+        }
+    }
+
+    /// Creates the search page with `query` already searched for.
+    #[must_use]
+    pub fn with_query(state: &ClearTextDBState, query: &str) -> Self {
+        let mut page = Self::new(state);
+        page.set_query(query.trim());
+        page
+    }
+
+    /// The selected result, if there are any results.
+    #[must_use]
+    pub fn selected(&self) -> Option<ItemRef> {
+        self.results.selected().copied()
+    }
+
+    /// Replaces the query and refreshes the results, selecting the first.
+    fn set_query(&mut self, query: &str) {
+        let mut results: Vec<(ItemRef, Vec<String>)> = Vec::new();
+        let fuzzy = quick_open(&self.state, query, RESULT_LIMIT).into_iter().map(|hit| hit.item);
+        let full_text = self.index.search(&self.state, query).into_iter().map(|hit| {
+            if get_epic(&self.state, hit.item_uuid).is_ok() { ItemRef::Epic(hit.item_uuid) } else { ItemRef::Story(hit.item_uuid) }
+        });
+        for item in fuzzy.chain(full_text) {
+            if results.len() == RESULT_LIMIT {
+                break;
+            }
+            if !results.iter().any(|(existing, _)| *existing == item)
+                && let Some(line) = self.describe(item)
+            {
+                results.push((item, line));
+            }
+        }
+        self.query = query.to_string();
+        self.results = results_list(results);
+    }
+
+    /// The cells of a result's row, e.g. `STORY-3 Login form` and `Open`.
+    fn describe(&self, item: ItemRef) -> Option<Vec<String>> {
+        let (key, title, status) = match item {
+            ItemRef::Epic(epic_uuid) => get_epic(&self.state, epic_uuid).map(|epic| (epic.key(), epic.title.clone(), epic.status)).ok()?,
+            ItemRef::Story(story_uuid) => get_story(&self.state, story_uuid).map(|story| (story.key(), story.title.clone(), story.status)).ok()?,
+        };
+        Some(vec![format!("{key} {title}"), status.name(&self.state.statuses)])
+    }
+}
+
+/// Lays out search results, all on one page.
+fn results_list(results: Vec<(ItemRef, Vec<String>)>) -> ListView<ItemRef> {
+    ListView::new(results, RESULT_LIMIT).column(&tr("column-result"), Align::Left).column(&tr("column-status"), Align::Left)
+}
+
+impl Page for SearchPage {
+    fn render(&self, size: Size) -> Vec<String> {
+        let mut lines = vec![tr("search-title"), tr_with("search-query", &[("query", &self.query)])];
+        if self.query.trim().is_empty() {
+            lines.push(tr("search-prompt"));
+        } else if self.results.is_empty() {
+            lines.push(tr("search-no-matches"));
+        } else {
+            lines.extend(self.results.render(size.columns()));
+        }
+        lines.push(tr_with("search-hint", &[("hints", &self.keys.hints(KeyContext::Search))]));
+        lines
+    }
+
+    fn handle_input(&mut self, input: &str) -> PageAction {
+        match self.keys.lookup(KeyContext::Search, input).map(|(action, _)| action) {
+            Some(action @ (KeyAction::MoveDown | KeyAction::MoveUp)) => {
+                self.results.navigate(action);
+            }
+            Some(KeyAction::Help | KeyAction::Back | KeyAction::Quit) => return global_action(&self.keys, KeyContext::Search, input).unwrap_or(PageAction::None),
+            _ if input.trim().is_empty() => return self.selected().map_or(PageAction::None, |item| PageAction::Open(Route::from(item))),
+            _ => match self.results.pick(input).copied() {
+                Some(item) => return PageAction::Open(Route::from(item)),
+                None => self.set_query(input.trim()),
+            },
+        }
+        PageAction::None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::service::{epics::create_epic, stories::create_story};
+    use crate::users::User;
+
+    #[test]
+    fn test_search_updates_results_and_opens_selection() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let epic_uuid = create_epic(&mut state, "Accounts", "Signup and recovery".to_string(), &SystemClock).unwrap();
+        let login = create_story(&mut state, epic_uuid, "Login form", String::new(), &SystemClock).unwrap();
+        let reset = create_story(&mut state, epic_uuid, "Password reset", "Email a login link".to_string(), &SystemClock).unwrap();
+        let mut page = SearchPage::new(&state);
+        assert_eq!(page.render(Size::default())[2], "Type to search epics and stories by ID, title, or text.");
+
+        page.handle_input("login");
+        assert_eq!(
+            page.render(Size::default())[1..5],
+            ["Search: login", "   #  Result                  Status", ">  1  STORY-1 Login form      Open", "   2  STORY-2 Password reset  Open"]
+        );
+        page.handle_input("recovery");
+        assert_eq!(page.render(Size::default())[3], ">  1  EPIC-1 Accounts  Open");
+        page.handle_input("login");
+        page.handle_input("+");
+        assert_eq!(page.selected(), Some(ItemRef::Story(reset)));
+        assert!(matches!(page.handle_input(""), PageAction::Open(Route::Story(id)) if id == reset.to_string()));
+        assert!(matches!(page.handle_input("1"), PageAction::Open(Route::Story(id)) if id == login.to_string()));
+
+        page.handle_input("story-2");
+        assert_eq!(page.selected(), Some(ItemRef::Story(reset)));
+        page.handle_input("zzz");
+        assert_eq!(page.render(Size::default())[2], "No matches.");
+        assert!(matches!(page.handle_input(""), PageAction::None));
+        assert!(matches!(page.handle_input("q"), PageAction::Pop));
+    }
+}
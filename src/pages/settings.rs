@@ -0,0 +1,320 @@
+//! # Settings Page
+//! Lists the user's interface preferences, numbered. Typing a number and a value changes one,
+//! e.g. `1 light` for the light theme or `5 10` to lock after ten minutes; on/off settings switch
+//! with the number alone, and the theme and language go to the next choice. The page only records
+//! the change; the caller takes it with [`Page::take_account_change`] and saves it with
+//! [`SettingChange::apply`].
+//!
+//! The password is changed from here too: the page asks for the current password and the new one
+//! twice, hidden, and the caller rewraps the database key with [`PasswordChange::apply`]. Typing
+//! `7` and a name renames the account, which the caller saves with [`UsernameChange::apply`]. If
+//! the caller can't apply a change, it reloads the page from the session.
+
+use super::i18n::{tr, tr_with};
+use super::keybindings::{KeyContext, KeyMap};
+use super::{AccountChange, Page, PageAction, Size, global_action};
+use crate::clock::Clock;
+use crate::db::ClearTextDBState;
+use crate::db::storage::Session;
+use crate::models::settings::{Locale, Settings, ThemeName};
+use crate::models::validation::{MIN_PASSWORD_LENGTH, validate_password, validate_username};
+use crate::service::settings::{set_accessible, set_full_screen, set_locale, set_lock_after, set_theme};
+use crate::users::{AccountChangeError, change_password, change_username};
+use std::collections::VecDeque;
+use std::path::Path;
+use uuid::Uuid;
+
+/// # Setting Change enum
+/// A preference changed on the settings page.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum SettingChange {
+    /// The color theme.
+    Theme(ThemeName),
+    /// The language.
+    Locale(Locale),
+    /// Whether to start in the full-screen interface.
+    FullScreen(bool),
+    /// Whether to show the screen-reader-friendly output.
+    Accessible(bool),
+    /// Minutes without input before the session locks; 0 never locks it.
+    LockAfter(u32),
+}
+
+impl SettingChange {
+    /// Saves the change to the user's settings through the service layer.
+    pub fn apply(self, state: &mut ClearTextDBState) {
+        match self {
+            SettingChange::Theme(theme) => set_theme(state, theme),
+            SettingChange::Locale(locale) => set_locale(state, locale),
+            SettingChange::FullScreen(full_screen) => set_full_screen(state, full_screen),
+            SettingChange::Accessible(accessible) => set_accessible(state, accessible),
+            SettingChange::LockAfter(minutes) => set_lock_after(state, minutes),
+        }
+    }
+}
+
+/// # `PasswordChange` struct
+/// A new password the user confirmed on the settings page.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PasswordChange {
+    /// The current password, as typed.
+    pub old: String,
+    /// The new password.
+    pub new: String,
+}
+
+impl PasswordChange {
+    /// Changes the password and rewraps the database key with [`change_password`].
+    ///
+    /// # Errors
+    /// * `AccountChangeError` - If the current password is wrong or the database couldn't be
+    ///   written; the old password still works.
+    pub fn apply(self, session: &mut Session, databases_dir: &Path, clock: &dyn Clock) -> Result<(), AccountChangeError> {
+        change_password(session, databases_dir, &self.old, &self.new, clock)
+    }
+}
+
+/// # `UsernameChange` struct
+/// A new username the user chose on the settings page.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UsernameChange(pub String);
+
+impl UsernameChange {
+    /// Renames the account with [`change_username`].
+    ///
+    /// # Returns
+    /// * `Result<Vec<(Uuid, String)>, AccountChangeError>` - The detected users, for the login
+    ///   page.
+    ///
+    /// # Errors
+    /// * `AccountChangeError` - If another user has the name or the database couldn't be written.
+    pub fn apply(self, session: &mut Session, databases_dir: &Path, clock: &dyn Clock) -> Result<Vec<(Uuid, String)>, AccountChangeError> {
+        change_username(session, databases_dir, &self.0, clock)
+    }
+}
+
+/// # Password Step enum
+/// Where the user is in changing the password.
+enum PasswordStep {
+    /// Typing the current password.
+    Current,
+    /// Typing the new password.
+    New {
+        /// The current password.
+        old: String,
+    },
+    /// Typing the new password again.
+    Confirm {
+        /// The current password.
+        old: String,
+        /// The new password.
+        new: String,
+    },
+}
+
+/// # `SettingsPage` struct
+/// The user's preferences, with the change the user made applied to the copy shown.
+pub struct SettingsPage {
+    /// The settings shown.
+    settings: Settings,
+    /// The logged-in user's name, which the new password must not contain.
+    username: String,
+    /// The changes made, not yet taken by the caller.
+    changes: VecDeque<AccountChange>,
+    /// Where the user is in changing the password, if they are.
+    password: Option<PasswordStep>,
+    /// The keybindings.
+    keys: KeyMap,
+}
+
+impl SettingsPage {
+    /// Creates the settings page showing the user's settings from the current database state.
+    #[must_use]
+    pub fn new(state: &ClearTextDBState) -> Self {
+        Self {
+            settings: state.settings.clone(),
+            username: state.user.username.clone(),
+            changes: VecDeque::new(),
+            password: None,
+            keys: KeyMap::active(),
+        }
+    }
+
+    /// Handles a line typed while changing the password.
+    fn change_password(&mut self, step: PasswordStep, input: &str) -> PageAction {
+        if input.trim() == "cancel" {
+            return PageAction::Notify(tr("cancelled"));
+        }
+        let (next, action) = match step {
+            PasswordStep::Current => (Some(PasswordStep::New { old: input.to_string() }), PageAction::None),
+            PasswordStep::New { old } => match validate_password(input, &self.username) {
+                Ok(()) => (Some(PasswordStep::Confirm { old, new: input.to_string() }), PageAction::None),
+                Err(error) => (Some(PasswordStep::New { old }), PageAction::ShowError(error.to_string())),
+            },
+            PasswordStep::Confirm { old, new } if input == new => {
+                self.changes.push_back(AccountChange::Password(PasswordChange { old, new }));
+                (None, PageAction::Refresh)
+            }
+            PasswordStep::Confirm { old, .. } => (Some(PasswordStep::New { old }), PageAction::ShowError(tr("register-passwords-differ"))),
+        };
+        self.password = next;
+        action
+    }
+
+    /// Reads the change asked for by setting number `number` to `value`, which may be empty.
+    fn parse(&self, number: &str, value: &str) -> Result<SettingChange, String> {
+        let on_off = |current: bool| match value.to_lowercase().as_str() {
+            "" => Ok(!current),
+            "on" => Ok(true),
+            "off" => Ok(false),
+            _ => Err(tr("settings-on-off")),
+        };
+        match number {
+            "1" if value.is_empty() => {
+                let next = ThemeName::ALL.iter().position(|theme| *theme == self.settings.theme).map_or(0, |index| index + 1);
+                Ok(SettingChange::Theme(ThemeName::ALL[next % ThemeName::ALL.len()]))
+            }
+            "1" => ThemeName::from_name(value).map(SettingChange::Theme).ok_or_else(|| tr_with("settings-no-such-theme", &[("name", &value)])),
+            "2" if value.is_empty() => {
+                let next = Locale::ALL.iter().position(|locale| *locale == self.settings.locale).map_or(0, |index| index + 1);
+                Ok(SettingChange::Locale(Locale::ALL[next % Locale::ALL.len()]))
+            }
+            "2" => Locale::from_tag(value).map(SettingChange::Locale).ok_or_else(|| tr_with("settings-no-such-locale", &[("name", &value)])),
+            "3" => on_off(self.settings.full_screen).map(SettingChange::FullScreen),
+            "4" => on_off(self.settings.accessible).map(SettingChange::Accessible),
+            "5" => value.parse().map(SettingChange::LockAfter).map_err(|_| tr("settings-minutes")),
+            _ => Err(tr_with("unknown-command", &[("input", &format!("{number} {value}").trim())])),
+        }
+    }
+}
+
+/// Shows an on/off setting's value.
+fn on_off(value: bool) -> String {
+    tr(if value { "settings-on" } else { "settings-off" })
+}
+
+impl Page for SettingsPage {
+    fn masks_input(&self) -> bool {
+        self.password.is_some()
+    }
+
+    fn render(&self, _size: Size) -> Vec<String> {
+        let mut lines = vec![tr("settings-title")];
+        if let Some(step) = &self.password {
+            lines.push(match step {
+                PasswordStep::Current => tr("settings-current-password"),
+                PasswordStep::New { .. } => tr_with("settings-new-password", &[("length", &MIN_PASSWORD_LENGTH)]),
+                PasswordStep::Confirm { .. } => tr("register-confirm-password"),
+            });
+            lines.push(tr("settings-password-hint"));
+            return lines;
+        }
+        let settings = &self.settings;
+        let lock_after = if settings.lock_after_minutes == 0 { tr("settings-never") } else { tr_with("settings-minutes-value", &[("minutes", &settings.lock_after_minutes)]) };
+        let rows = [
+            ("settings-theme", settings.theme.name().to_string()),
+            ("settings-locale", settings.locale.tag().to_string()),
+            ("settings-full-screen", on_off(settings.full_screen)),
+            ("settings-accessible", on_off(settings.accessible)),
+            ("settings-lock-after", lock_after),
+            ("settings-password", tr("settings-password-value")),
+            ("settings-username", self.username.clone()),
+        ];
+        lines.extend(rows.iter().enumerate().map(|(index, (id, value))| format!("{}. {}: {value}", index + 1, tr(id))));
+        lines.push(tr("settings-hint"));
+        lines
+    }
+
+    fn handle_input(&mut self, input: &str) -> PageAction {
+        if let Some(step) = self.password.take() {
+            return self.change_password(step, input);
+        }
+        if let Some(global) = global_action(&self.keys, KeyContext::Global, input) {
+            return global;
+        }
+        let input = input.trim();
+        let (number, value) = input.split_once(' ').unwrap_or((input, ""));
+        if number == "6" && value.is_empty() {
+            self.password = Some(PasswordStep::Current);
+            return PageAction::None;
+        }
+        if number == "7" {
+            return match validate_username(value, []) {
+                Ok(username) => {
+                    self.username.clone_from(&username);
+                    self.changes.push_back(AccountChange::Username(UsernameChange(username)));
+                    PageAction::Refresh
+                }
+                Err(error) => PageAction::ShowError(error.to_string()),
+            };
+        }
+        match self.parse(number, value.trim()) {
+            Ok(change) => {
+                match change {
+                    SettingChange::Theme(theme) => self.settings.theme = theme,
+                    SettingChange::Locale(locale) => self.settings.locale = locale,
+                    SettingChange::FullScreen(full_screen) => self.settings.full_screen = full_screen,
+                    SettingChange::Accessible(accessible) => self.settings.accessible = accessible,
+                    SettingChange::LockAfter(minutes) => self.settings.lock_after_minutes = minutes,
+                }
+                self.changes.push_back(AccountChange::Setting(change));
+                PageAction::Refresh
+            }
+            Err(error) => PageAction::ShowError(error),
+        }
+    }
+
+    fn take_account_change(&mut self) -> Option<AccountChange> {
+        self.changes.pop_front()
+    }
+
+    fn reload(&mut self, state: &ClearTextDBState) {
+        *self = Self::new(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::users::User;
+
+    #[test]
+    fn test_settings_change_one_at_a_time() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let mut page = SettingsPage::new(&state);
+        assert_eq!(page.render(Size::default())[1..6], ["1. Theme: default", "2. Language: en", "3. Full screen: off", "4. Accessible mode: off", "5. Lock after: never"]);
+
+        assert!(matches!(page.handle_input("1"), PageAction::Refresh));
+        assert_eq!(page.take_account_change(), Some(AccountChange::Setting(SettingChange::Theme(ThemeName::Light))));
+        page.handle_input("4");
+        let Some(AccountChange::Setting(change)) = page.take_account_change() else { panic!("expected a setting change") };
+        change.apply(&mut state);
+        assert!(state.settings.accessible);
+        page.handle_input("5 10");
+        assert_eq!(page.render(Size::default())[5], "5. Lock after: 10 minutes");
+        assert!(matches!(page.handle_input("1 neon"), PageAction::ShowError(error) if error == "There is no theme \"neon\"."));
+        assert!(matches!(page.handle_input("3 maybe"), PageAction::ShowError(error) if error == "Type on or off."));
+        assert!(matches!(page.handle_input("8"), PageAction::ShowError(error) if error == "Unknown command \"8\"."));
+    }
+
+    #[test]
+    fn test_change_password_from_settings() {
+        let state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let mut page = SettingsPage::new(&state);
+        assert_eq!(page.render(Size::default())[6..8], ["6. Password: type [6] to change it", "7. Username: alice"]);
+        page.handle_input("6");
+        assert!(page.masks_input());
+        page.handle_input("password");
+        assert!(matches!(page.handle_input("alice-is-the-best"), PageAction::ShowError(error) if error == "The password must not contain the username"));
+        page.handle_input("correct horse battery");
+        assert!(matches!(page.handle_input("correct horse"), PageAction::ShowError(error) if error.starts_with("The passwords did not match")));
+        page.handle_input("correct horse battery");
+        assert!(matches!(page.handle_input("correct horse battery"), PageAction::Refresh));
+        assert!(!page.masks_input());
+        assert_eq!(page.take_account_change(), Some(AccountChange::Password(PasswordChange { old: "password".to_string(), new: "correct horse battery".to_string() })));
+
+        assert!(matches!(page.handle_input("7 alicia"), PageAction::Refresh));
+        assert_eq!(page.take_account_change(), Some(AccountChange::Username(UsernameChange("alicia".to_string()))));
+        assert_eq!(page.render(Size::default())[7], "7. Username: alicia");
+    }
+}
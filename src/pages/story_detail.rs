@@ -0,0 +1,243 @@
+//! # Story Detail Page
+//! Shows one story: its epic, status, assignee, estimate, labels, dates, custom fields, links,
+//! checklist, and description. Single-key actions move it to another status or epic, edit it, or
+//! delete it; as on the other detail pages, each change is recorded for the caller to run as a
+//! command taken with [`Page::take_command`]. Typing `t` without a status opens a
+//! [`Picker`] over the statuses the workflow allows. When the page is shown again after another page
+//! was opened over it, it asks to be rebuilt with [`PageAction::Refresh`], since the story may have
+//! changed.
+
+use super::i18n::{tr, tr_with};
+use super::keybindings::{KeyAction, KeyContext, KeyMap};
+use super::picker::{Picked, Picker, status_picker};
+use super::{Page, PageAction, PageExit, Size, global_action, markdown::render_styled};
+use crate::db::ClearTextDBState;
+use crate::nav::routes::Route;
+use crate::models::checklists::completion_summary;
+use crate::models::{Status, statuses::{StatusDefinition, find_definition_by_name}};
+use crate::service::{ServiceError, custom_fields::render_fields, get_story, labels::get_label, links::render_links, undo::Command};
+use uuid::Uuid;
+
+/// # `StoryDetailPage` struct
+/// A view of one story, built when the page is opened.
+pub struct StoryDetailPage {
+    /// The story shown.
+    story_uuid: Uuid,
+    /// The formatted lines.
+    lines: Vec<String>,
+    /// The database's statuses, to look up the status the user types.
+    statuses: Vec<StatusDefinition>,
+    /// The short ID and UUID of every epic the story could move to.
+    epics: Vec<(String, Uuid)>,
+    /// The statuses the workflow lets the item move to, shown when `t` is typed on its own.
+    picker: Picker<Status>,
+    /// Whether the status picker is open.
+    picking: bool,
+    /// Whether another page was shown over this one, which may have changed the story.
+    hidden: bool,
+    /// The change chosen, not yet taken by the caller.
+    command: Option<Command>,
+    /// The keybindings.
+    keys: KeyMap,
+}
+
+impl StoryDetailPage {
+    /// Creates the detail page for a story from the current database state.
+    ///
+    /// # Errors
+    /// * `ServiceError::StoryNotFound` - If the story does not exist.
+    pub fn new(state: &ClearTextDBState, story_uuid: Uuid) -> Result<Self, ServiceError> {
+        let story = get_story(state, story_uuid)?;
+        let mut lines = vec![tr_with("detail-title", &[("key", &story.key()), ("title", &story.title)])];
+        if let Some(epic) = state.epics.iter().find(|epic| epic.story_uuids.contains(&story_uuid)) {
+            lines.push(tr_with("field-epic", &[("value", &format!("{} {}", epic.key(), epic.title))]));
+        }
+        lines.push(tr_with("field-status", &[("value", &story.status.name(&state.statuses))]));
+        if let Some(assignee) = story.assignee {
+            let name = if assignee == state.user.user_uuid { state.user.username.clone() } else { assignee.to_string() };
+            lines.push(tr_with("field-assignee", &[("value", &name)]));
+        }
+        lines.extend(story.estimate.map(|estimate| tr_with("field-estimate", &[("value", &estimate)])));
+        let labels: Vec<&str> = story.labels.iter().filter_map(|label_uuid| get_label(state, *label_uuid).ok()).map(|label| label.name.as_str()).collect();
+        if !labels.is_empty() {
+            lines.push(tr_with("field-labels", &[("value", &labels.join(", "))]));
+        }
+        lines.extend(story.start_date.map(|date| tr_with("field-start", &[("value", &date)])));
+        lines.extend(story.due_date.map(|date| tr_with("field-due", &[("value", &date)])));
+        lines.extend(render_fields(state, story_uuid)?);
+        let linked = render_links(state, story_uuid)?;
+        if !linked.is_empty() {
+            lines.push(tr("section-links"));
+            lines.extend(linked.into_iter().map(|link| format!("  {link}")));
+        }
+        if let Some(summary) = completion_summary(&story.checklist) {
+            lines.push(tr_with("section-checklist", &[("summary", &summary)]));
+            lines.extend(story.checklist.iter().map(|item| format!("  [{}] {}", if item.checked { 'x' } else { ' ' }, item.text)));
+        }
+        if !story.description.is_empty() {
+            lines.push(tr("section-description"));
+            lines.extend(render_styled(&story.description));
+        }
+        let epics = state.epics.iter().filter(|epic| !epic.is_deleted()).map(|epic| (epic.key(), epic.epic_uuid)).collect();
+        Ok(Self {
+            story_uuid,
+            lines,
+            statuses: state.statuses.clone(),
+            picker: status_picker(state, story.status),
+            picking: false,
+            hidden: false,
+            epics,
+            command: None,
+            keys: KeyMap::active(),
+        })
+    }
+
+    /// Reads one input into a command on the story.
+    fn parse(&self, input: &str) -> Result<Command, String> {
+        let story_uuid = self.story_uuid;
+        match self.keys.lookup(KeyContext::StoryDetail, input) {
+            Some((KeyAction::ChangeStatus, name)) => find_definition_by_name(&self.statuses, name)
+                .map(|definition| Command::SetStoryStatus { story_uuid, status: definition.status })
+                .ok_or_else(|| tr_with("no-such-status", &[("name", &name)])),
+            Some((KeyAction::MoveToEpic, key)) => self
+                .epics
+                .iter()
+                .find(|(epic_key, _)| epic_key.eq_ignore_ascii_case(key))
+                .map(|(_, epic_uuid)| Command::MoveStory { story_uuid, epic_uuid: *epic_uuid })
+                .ok_or_else(|| tr_with("no-such-epic", &[("name", &key)])),
+            Some((KeyAction::Delete, _)) => Ok(Command::DeleteStory { story_uuid }),
+            _ => Err(tr_with("unknown-command", &[("input", &input)])),
+        }
+    }
+}
+
+impl Page for StoryDetailPage {
+    fn render(&self, _size: Size) -> Vec<String> {
+        let mut lines = self.lines.clone();
+        if self.picking {
+            lines.push(tr("section-change-status"));
+            lines.extend(self.picker.render());
+            lines.push(tr("keep-status-hint"));
+        } else {
+            lines.push(self.keys.hints(KeyContext::StoryDetail));
+        }
+        lines
+    }
+
+    fn handle_input(&mut self, input: &str) -> PageAction {
+        if self.picking {
+            return match self.picker.handle_input(input) {
+                Ok(Picked::Chosen(status)) => {
+                    self.picking = false;
+                    self.command = Some(Command::SetStoryStatus { story_uuid: self.story_uuid, status });
+                    PageAction::Refresh
+                }
+                Ok(Picked::Cancelled) => {
+                    self.picking = false;
+                    PageAction::None
+                }
+                Ok(Picked::Browsing) => PageAction::None,
+                Err(error) => PageAction::ShowError(error),
+            };
+        }
+        if let Some(global) = global_action(&self.keys, KeyContext::StoryDetail, input) {
+            return global;
+        }
+        match self.keys.lookup(KeyContext::StoryDetail, input.trim()) {
+            Some((KeyAction::ChangeStatus, "")) => {
+                self.picking = true;
+                return PageAction::None;
+            }
+            Some((KeyAction::Edit, _)) => return PageAction::Open(Route::Edit(self.story_uuid.to_string())),
+            _ => (),
+        }
+        match self.parse(input.trim()) {
+            Ok(command) => {
+                self.command = Some(command);
+                PageAction::Refresh
+            }
+            Err(error) => PageAction::ShowError(error),
+        }
+    }
+
+    fn on_enter(&mut self) -> PageAction {
+        if std::mem::take(&mut self.hidden) { PageAction::Refresh } else { PageAction::None }
+    }
+
+    fn on_exit(&mut self, exit: PageExit) {
+        self.hidden = exit == PageExit::Hidden;
+    }
+
+    fn take_command(&mut self) -> Option<Command> {
+        self.command.take()
+    }
+
+    fn reload(&mut self, state: &ClearTextDBState) {
+        if let Ok(page) = Self::new(state, self.story_uuid) {
+            *self = page;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::models::estimates::Estimate;
+    use crate::service::{
+        checklists::{add_checklist_item, toggle_checklist_item},
+        dependencies::add_blocker,
+        epics::create_epic,
+        estimates::set_story_estimate,
+        stories::create_story,
+    };
+    use crate::users::User;
+
+    #[test]
+    fn test_render_story_detail() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let epic_uuid = create_epic(&mut state, "Launch", String::new(), &SystemClock).unwrap();
+        let other_epic = create_epic(&mut state, "Later", String::new(), &SystemClock).unwrap();
+        let story_uuid = create_story(&mut state, epic_uuid, "Login", "Use **TOTP**".to_string(), &SystemClock).unwrap();
+        let design = create_story(&mut state, epic_uuid, "Design", String::new(), &SystemClock).unwrap();
+        add_blocker(&mut state, story_uuid, design, &SystemClock).unwrap();
+        set_story_estimate(&mut state, story_uuid, Some(Estimate::Points(3)), &SystemClock).unwrap();
+        add_checklist_item(&mut state, story_uuid, "Form".to_string(), &SystemClock).unwrap();
+        add_checklist_item(&mut state, story_uuid, "Errors".to_string(), &SystemClock).unwrap();
+        toggle_checklist_item(&mut state, story_uuid, 0, &SystemClock).unwrap();
+
+        let mut page = StoryDetailPage::new(&state, story_uuid).unwrap();
+        assert_eq!(
+            page.render(Size::default()),
+            vec![
+                "=== STORY-1 Login ===",
+                "Epic: EPIC-1 Launch",
+                "Status: Open",
+                "Estimate: 3 pt",
+                "--- Links ---",
+                "  is blocked by: Design",
+                "--- Checklist 1/2 (50%) ---",
+                "  [x] Form",
+                "  [ ] Errors",
+                "--- Description ---",
+                "Use \x1b[1mTOTP\x1b[0m",
+                "[t <status>] change status  [e] edit  [m <EPIC-n>] move to epic  [d] delete",
+            ]
+        );
+        assert!(matches!(page.handle_input("m epic-2"), PageAction::Refresh));
+        assert_eq!(page.take_command(), Some(Command::MoveStory { story_uuid, epic_uuid: other_epic }));
+        page.handle_input("t closed");
+        assert_eq!(page.take_command(), Some(Command::SetStoryStatus { story_uuid, status: Status::Closed }));
+        page.handle_input("t");
+        assert_eq!(page.render(Size::default())[11..13], ["--- Change status ---", "> 1. Open"]);
+        page.handle_input("blocked");
+        assert_eq!(page.take_command(), Some(Command::SetStoryStatus { story_uuid, status: Status::Blocked }));
+        assert!(matches!(page.handle_input("m EPIC-9"), PageAction::ShowError(error) if error == "There is no epic \"EPIC-9\"."));
+        assert!(matches!(page.handle_input("?"), PageAction::Navigate(_)));
+        assert!(matches!(page.handle_input("e"), PageAction::Open(Route::Edit(id)) if id == story_uuid.to_string()));
+        page.handle_input("d");
+        assert_eq!(page.take_command(), Some(Command::DeleteStory { story_uuid }));
+        let missing = Uuid::new_v4();
+        assert!(matches!(StoryDetailPage::new(&state, missing), Err(ServiceError::StoryNotFound(uuid)) if uuid == missing));
+    }
+}
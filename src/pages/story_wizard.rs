@@ -0,0 +1,378 @@
+//! # Story Creation Page
+//! Creates a story in the same step-by-step way as the
+//! [epic wizard](super::epic_wizard::EpicWizardPage): the epic (skipped when the page is opened
+//! from an epic's detail page), title, description, status, priority (if the database defines
+//! one), and due date, then a summary to confirm. `<` goes back a step and `cancel` abandons the
+//! page.
+//!
+//! Once confirmed, the caller takes the answers as a [`Command::CreateStory`] with
+//! [`Page::take_command`], runs it, and shows the new story in place of the page.
+
+use super::i18n::{tr, tr_with};
+use super::external_editor::ExternalEditor;
+use super::picker::{Picked, Picker};
+use super::text_editor::{EditorOutcome, TextEditor};
+use super::{Page, PageAction, Size};
+use super::epic_wizard::priority_field;
+use crate::db::ClearTextDBState;
+use crate::models::Status;
+use crate::models::custom_fields::{FieldId, FieldValue};
+use crate::models::statuses::StatusDefinition;
+use crate::models::validation::{validate_description, validate_title};
+use crate::service::statuses::ordered_statuses;
+use crate::service::{ServiceError, get_epic, hierarchy::epic_tree, undo::Command};
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+/// # Story Step enum
+/// Where the user is in the story creation flow.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum StoryStep {
+    /// Picking the epic.
+    Epic,
+    /// Entering the title.
+    Title,
+    /// Entering the description.
+    Description,
+    /// Choosing the initial status.
+    Status,
+    /// Choosing a priority.
+    Priority,
+    /// Entering the due date.
+    DueDate,
+    /// Reviewing the answers.
+    Confirm,
+    /// Confirmed; the draft is ready.
+    Done,
+    /// Abandoned.
+    Cancelled,
+}
+
+/// # Story Draft struct
+/// The answers collected by the story creation page.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+struct StoryDraft {
+    /// The epic the story goes into.
+    epic_uuid: Uuid,
+    /// The trimmed title; empty once the draft was taken.
+    title: String,
+    /// The description.
+    description: String,
+    /// The initial status.
+    status: Status,
+    /// The priority field and chosen option, if any.
+    priority: Option<(FieldId, FieldValue)>,
+    /// The due date, if any.
+    due_date: Option<NaiveDate>,
+}
+
+/// # `StoryCreationPage` struct
+/// Walks the user through creating a story.
+pub struct StoryCreationPage {
+    /// The epics to pick from, by UUID and formatted line; empty when the epic was pre-selected.
+    epics: Vec<(Uuid, String)>,
+    /// The pre-selected epic's line, if the page was opened from an epic.
+    preselected: Option<String>,
+    /// The database's statuses.
+    statuses: Vec<StatusDefinition>,
+    /// The priority field and a picker over its options, if the database defines one.
+    priority_field: Option<(FieldId, Picker<Option<String>>)>,
+    /// The picker for the new story's status.
+    status_picker: Picker<Status>,
+    /// Current step of the flow.
+    step: StoryStep,
+    /// The answers so far.
+    draft: StoryDraft,
+    /// The description editor, once the user starts typing the description.
+    editor: Option<TextEditor>,
+    /// The user's own editor for the description, if one is set.
+    external: Option<ExternalEditor>,
+}
+
+impl StoryCreationPage {
+    /// Creates the page. With `epic_uuid`, the story goes into that epic and the epic step is
+    /// skipped; otherwise the user picks one of the active epics.
+    ///
+    /// # Errors
+    /// * `ServiceError::EpicNotFound` - If the pre-selected epic does not exist.
+    pub fn new(state: &ClearTextDBState, epic_uuid: Option<Uuid>) -> Result<Self, ServiceError> {
+        let preselected = epic_uuid.map(|epic_uuid| get_epic(state, epic_uuid).map(|epic| format!("{} {}", epic.key(), epic.title))).transpose()?;
+        let epics = if preselected.is_some() {
+            Vec::new()
+        } else {
+            epic_tree(state)
+                .into_iter()
+                .filter(|(_, epic)| !epic.archived)
+                .map(|(depth, epic)| (epic.epic_uuid, format!("{}{} {}", "  ".repeat(depth), epic.key(), epic.title)))
+                .collect()
+        };
+        Ok(Self {
+            epics,
+            preselected,
+            statuses: state.statuses.clone(),
+            priority_field: priority_field(state),
+            status_picker: Picker::new("status", ordered_statuses(state).into_iter().map(|definition| (definition.status, definition.name.clone())).collect()).selecting(&Status::Open),
+            step: if epic_uuid.is_some() { StoryStep::Title } else { StoryStep::Epic },
+            draft: StoryDraft { epic_uuid: epic_uuid.unwrap_or_default(), ..StoryDraft::default() },
+            editor: None,
+            external: None,
+        })
+    }
+
+    /// Writes the description in `external` instead of the in-app editor, e.g. with
+    /// [`ExternalEditor::from_env`].
+    #[must_use]
+    pub fn with_external_editor(mut self, external: Option<ExternalEditor>) -> Self {
+        self.external = external;
+        self
+    }
+
+    /// Returns the current step of the flow.
+    #[must_use]
+    pub fn step(&self) -> StoryStep {
+        self.step
+    }
+
+    /// The steps the page goes through, in order.
+    fn steps(&self) -> Vec<StoryStep> {
+        let mut steps = Vec::new();
+        if self.preselected.is_none() {
+            steps.push(StoryStep::Epic);
+        }
+        steps.extend([StoryStep::Title, StoryStep::Description, StoryStep::Status]);
+        if self.priority_field.is_some() {
+            steps.push(StoryStep::Priority);
+        }
+        steps.extend([StoryStep::DueDate, StoryStep::Confirm]);
+        steps
+    }
+
+    /// The step before or after the current one.
+    fn neighbour(&self, forward: bool) -> StoryStep {
+        let steps = self.steps();
+        let step = self.step();
+        let index = steps.iter().position(|candidate| *candidate == step).unwrap_or_default();
+        let index = if forward { index + 1 } else { index.saturating_sub(1) };
+        steps.get(index).copied().unwrap_or(StoryStep::Done)
+    }
+
+    /// Writes the description in the external editor, if there is one, and moves past the step.
+    /// Without one, or if it fails, the step stays for the in-app editor.
+    fn open_description(&mut self) -> Result<StoryStep, String> {
+        self.step = StoryStep::Description;
+        let Some(external) = &self.external else { return Ok(StoryStep::Description) };
+        let text = external.edit(&self.draft.description).map_err(|err| tr_with("editor-failed", &[("error", &err)]))?;
+        validate_description(&text).map_err(|err| err.to_string())?;
+        self.draft.description = text;
+        Ok(self.neighbour(true))
+    }
+
+    /// Passes input to the description editor. Saving records the description and moves on;
+    /// cancelling throws the changes away and goes back a step.
+    fn edit_description(&mut self, input: &str) -> Result<StoryStep, String> {
+        let editor = self.editor.get_or_insert_with(|| TextEditor::new(&self.draft.description));
+        let forward = match editor.handle_input(input)? {
+            EditorOutcome::Editing => return Ok(StoryStep::Description),
+            EditorOutcome::Saved(text) => {
+                validate_description(&text).map_err(|err| err.to_string())?;
+                self.draft.description = text;
+                true
+            }
+            EditorOutcome::Cancelled => false,
+        };
+        Ok(self.neighbour(forward))
+    }
+
+    /// Records the answer for the current step.
+    fn answer(&mut self, input: &str) -> Result<StoryStep, String> {
+        let draft = &mut self.draft;
+        let input = input.trim();
+        match self.step {
+            StoryStep::Epic => {
+                draft.epic_uuid = input
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|number| number.checked_sub(1))
+                    .and_then(|index| self.epics.get(index))
+                    .map(|(epic_uuid, _)| *epic_uuid)
+                    .ok_or_else(|| tr("story-wizard-choose-epic"))?;
+            }
+            StoryStep::Title => draft.title = validate_title(input).map_err(|err| err.to_string())?,
+            StoryStep::Status => match self.status_picker.handle_input(input)? {
+                Picked::Chosen(status) => draft.status = status,
+                Picked::Browsing => return Ok(StoryStep::Status),
+                Picked::Cancelled => return Ok(self.neighbour(false)),
+            },
+            StoryStep::Priority => {
+                let Some((field_id, picker)) = &mut self.priority_field else { return Ok(self.neighbour(true)) };
+                match picker.handle_input(input)? {
+                    Picked::Chosen(option) => draft.priority = option.map(|option| (*field_id, FieldValue::Enum(option))),
+                    Picked::Browsing => return Ok(StoryStep::Priority),
+                    Picked::Cancelled => return Ok(self.neighbour(false)),
+                }
+            }
+            StoryStep::DueDate => {
+                draft.due_date = if input.is_empty() {
+                    None
+                } else {
+                    Some(NaiveDate::parse_from_str(input, "%Y-%m-%d").map_err(|_| tr("date-format"))?)
+                };
+            }
+            StoryStep::Confirm => match input {
+                "y" | "yes" => (),
+                _ => return Err(tr("story-wizard-confirm-error")),
+            },
+            StoryStep::Description | StoryStep::Done | StoryStep::Cancelled => return Ok(self.step()),
+        }
+        Ok(self.neighbour(true))
+    }
+
+    /// The summary of the answers shown at the confirmation step.
+    fn summary(&self) -> Vec<String> {
+        let draft = &self.draft;
+        let epic = self.preselected.clone().or_else(|| self.epics.iter().find(|(epic_uuid, _)| *epic_uuid == draft.epic_uuid).map(|(_, line)| line.trim().to_string()));
+        let status = self.statuses.iter().find(|definition| definition.status == draft.status).map(|definition| definition.name.clone()).unwrap_or_default();
+        let mut lines = vec![
+            tr_with("summary-epic", &[("value", &epic.unwrap_or_default())]),
+            tr_with("summary-title", &[("value", &draft.title)]),
+            tr_with("summary-description", &[("value", &draft.description)]),
+            tr_with("summary-status", &[("value", &status)]),
+        ];
+        if self.priority_field.is_some() {
+            lines.push(tr_with("summary-priority", &[("value", &draft.priority.as_ref().map(|(_, value)| value.to_string()).unwrap_or_default())]));
+        }
+        lines.push(tr_with("summary-due", &[("value", &draft.due_date.map(|date| date.to_string()).unwrap_or_default())]));
+        lines
+    }
+}
+
+impl Page for StoryCreationPage {
+    fn render(&self, size: Size) -> Vec<String> {
+        let mut lines = vec![tr("story-wizard-title")];
+        let step = self.step();
+        let steps = self.steps();
+        if let Some(index) = steps.iter().position(|candidate| *candidate == step) {
+            lines.push(tr_with("wizard-step", &[("step", &(index + 1)), ("steps", &steps.len())]));
+        }
+        if let Some(epic) = &self.preselected {
+            lines.push(tr_with("story-wizard-in-epic", &[("epic", &epic)]));
+        }
+        match step {
+            StoryStep::Epic if self.epics.is_empty() => lines.push(tr("story-wizard-no-epics")),
+            StoryStep::Epic => {
+                lines.extend(self.epics.iter().enumerate().map(|(index, (_, line))| format!("{}. {line}", index + 1)));
+                lines.push(tr("wizard-epic"));
+            }
+            StoryStep::Title => lines.push(tr("wizard-title")),
+            StoryStep::Description => {
+                lines.push(tr("wizard-description"));
+                lines.extend(self.editor.as_ref().map_or_else(|| TextEditor::new(&self.draft.description).render(size), |editor| editor.render(size)));
+            }
+            StoryStep::Status => {
+                lines.push(tr("wizard-status"));
+                lines.extend(self.status_picker.render());
+            }
+            StoryStep::Priority => {
+                lines.push(tr("wizard-priority"));
+                lines.extend(self.priority_field.iter().flat_map(|(_, picker)| picker.render()));
+            }
+            StoryStep::DueDate => lines.push(tr("wizard-due-date")),
+            StoryStep::Confirm => {
+                lines.extend(self.summary());
+                lines.push(tr("story-wizard-confirm"));
+            }
+            StoryStep::Done => lines.push(tr("story-wizard-done")),
+            StoryStep::Cancelled => lines.push(tr("cancelled")),
+        }
+        if !matches!(step, StoryStep::Done | StoryStep::Cancelled) {
+            lines.push(tr("back-cancel-hint"));
+        }
+        lines
+    }
+
+    fn handle_input(&mut self, input: &str) -> PageAction {
+        if matches!(self.step(), StoryStep::Done | StoryStep::Cancelled) {
+            return PageAction::None;
+        }
+        let next = match input.trim() {
+            "<" => Ok(self.neighbour(false)),
+            "cancel" => Ok(StoryStep::Cancelled),
+            _ if self.step == StoryStep::Description => self.edit_description(input),
+            _ => self.answer(input),
+        }
+        .and_then(|next| if next == StoryStep::Description && self.step != next { self.open_description() } else { Ok(next) });
+        match next {
+            Ok(next) => {
+                if next != StoryStep::Description {
+                    self.editor = None;
+                }
+                self.step = next;
+                match next {
+                    StoryStep::Done => PageAction::Refresh,
+                    StoryStep::Cancelled => PageAction::Pop,
+                    _ => PageAction::None,
+                }
+            }
+            Err(error) => PageAction::ShowError(error),
+        }
+    }
+
+    fn take_command(&mut self) -> Option<Command> {
+        if self.step() != StoryStep::Done || self.draft.title.is_empty() {
+            return None;
+        }
+        let StoryDraft { epic_uuid, title, description, status, priority, due_date } = std::mem::take(&mut self.draft);
+        Some(Command::CreateStory { epic_uuid, title, description, status, priority, due_date })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::service::{epics::create_epic, get_story};
+    use crate::users::User;
+
+    #[test]
+    fn test_create_story_in_preselected_epic() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let epic_uuid = create_epic(&mut state, "Launch", String::new(), &SystemClock).unwrap();
+        let mut page = StoryCreationPage::new(&state, Some(epic_uuid)).unwrap();
+        assert_eq!(page.render(Size::default())[1..4], ["Step 1 of 5".to_string(), "In EPIC-1 Launch".to_string(), "Title:".to_string()]);
+        page.handle_input("Login");
+        page.handle_input(":w");
+        assert!(matches!(page.handle_input("started"), PageAction::ShowError(error) if error == "There is no status \"started\"."));
+        page.handle_input("in progress");
+        assert!(matches!(page.handle_input("March 1st"), PageAction::ShowError(error) if error == "Write the date as YYYY-MM-DD."));
+        page.handle_input("2025-03-01");
+        assert!(page.render(Size::default()).contains(&"Status: In Progress".to_string()));
+        page.handle_input("y");
+
+        let story_uuid = page.take_command().unwrap().apply(&mut state, &SystemClock).unwrap().unwrap();
+        assert_eq!(page.take_command(), None);
+        let story = get_story(&state, story_uuid).unwrap();
+        assert_eq!((story.title.as_str(), story.status, story.due_date), ("Login", Status::InProgress, NaiveDate::from_ymd_opt(2025, 3, 1)));
+        assert_eq!(get_epic(&state, epic_uuid).unwrap().story_uuids, vec![story_uuid]);
+        let missing = Uuid::new_v4();
+        assert!(matches!(StoryCreationPage::new(&state, Some(missing)), Err(ServiceError::EpicNotFound(uuid)) if uuid == missing));
+    }
+
+    #[test]
+    fn test_pick_epic() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        create_epic(&mut state, "Launch", String::new(), &SystemClock).unwrap();
+        let docs = create_epic(&mut state, "Docs", String::new(), &SystemClock).unwrap();
+        let mut page = StoryCreationPage::new(&state, None).unwrap();
+        assert_eq!(page.render(Size::default())[2..5], ["1. EPIC-1 Launch".to_string(), "2. EPIC-2 Docs".to_string(), "Epic:".to_string()]);
+        page.handle_input("3");
+        assert_eq!(page.step(), StoryStep::Epic);
+        page.handle_input("2");
+        page.handle_input("Guide");
+        page.handle_input("<");
+        page.handle_input("<");
+        assert_eq!(page.step(), StoryStep::Epic);
+        page.handle_input("cancel");
+        assert_eq!(page.take_command(), None);
+        assert_eq!(page.draft.epic_uuid, docs);
+    }
+}
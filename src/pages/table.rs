@@ -0,0 +1,205 @@
+//! # Table
+//! Lays rows of cells out in aligned columns for the list pages, so they don't format their own
+//! padding. Columns are as wide as their widest cell; when the table is wider than the terminal,
+//! the widest columns give way first and their cells are cut short with an ellipsis.
+//!
+//! ```rust
+//! use ironyyy::pages::table::{Align, Border, Table};
+//! let mut table = Table::new().column("Key", Align::Left).column("Points", Align::Right).border(Border::Ascii);
+//! table.add_row(["STORY-1", "3"]);
+//! assert_eq!(
+//!     table.render(80),
+//!     vec!["+---------+--------+", "| Key     | Points |", "+---------+--------+", "| STORY-1 |      3 |", "+---------+--------+"]
+//! );
+//! ```
+
+/// The narrowest a column is squeezed to when the table doesn't fit.
+const MIN_COLUMN_WIDTH: usize = 4;
+
+/// # Align enum
+/// Which side of its column a cell's text sits on.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Align {
+    /// Against the left edge, for text.
+    #[default]
+    Left,
+    /// Against the right edge, for numbers.
+    Right,
+}
+
+/// # Border enum
+/// How the table is framed.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Border {
+    /// No frame; columns are separated by two spaces.
+    #[default]
+    None,
+    /// A frame of `+`, `-`, and `|`.
+    Ascii,
+    /// A frame of box-drawing characters.
+    Unicode,
+}
+
+impl Border {
+    /// The characters the frame is drawn with: the vertical line, the horizontal line, and the
+    /// left, middle, and right joints of the top, middle, and bottom rules.
+    fn glyphs(self) -> Option<(char, char, [[char; 3]; 3])> {
+        match self {
+            Border::None => None,
+            Border::Ascii => Some(('|', '-', [['+'; 3]; 3])),
+            Border::Unicode => Some(('│', '─', [['┌', '┬', '┐'], ['├', '┼', '┤'], ['└', '┴', '┘']])),
+        }
+    }
+}
+
+/// # Column struct
+/// A column's header and alignment.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+struct Column {
+    /// The header; the header row is left out if every header is empty.
+    header: String,
+    /// How the column's cells are aligned.
+    align: Align,
+}
+
+/// # Table struct
+/// Columns and rows of text, rendered to fit a width.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Table {
+    /// The columns, left to right.
+    columns: Vec<Column>,
+    /// The rows' cells; missing cells are blank and extra ones are ignored.
+    rows: Vec<Vec<String>>,
+    /// How the table is framed.
+    border: Border,
+}
+
+impl Table {
+    /// Creates a table with no columns, rows, or border.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a column on the right.
+    #[must_use]
+    pub fn column(mut self, header: &str, align: Align) -> Self {
+        self.columns.push(Column { header: header.to_string(), align });
+        self
+    }
+
+    /// Frames the table.
+    #[must_use]
+    pub fn border(mut self, border: Border) -> Self {
+        self.border = border;
+        self
+    }
+
+    /// Adds a row at the bottom.
+    pub fn add_row<I: IntoIterator<Item = S>, S: Into<String>>(&mut self, cells: I) {
+        self.rows.push(cells.into_iter().map(Into::into).collect());
+    }
+
+    /// Whether the table has no rows.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Renders the table in at most `width` characters per line, if the columns can be squeezed
+    /// that far.
+    #[must_use]
+    pub fn render(&self, width: usize) -> Vec<String> {
+        let widths = self.widths(width);
+        let glyphs = self.border.glyphs();
+        let row = |cells: &[String]| {
+            let cells: Vec<String> = self
+                .columns
+                .iter()
+                .zip(&widths)
+                .enumerate()
+                .map(|(index, (column, width))| fit(cells.get(index).map_or("", String::as_str), *width, column.align))
+                .collect();
+            match glyphs {
+                None => cells.join("  ").trim_end().to_string(),
+                Some((vertical, ..)) => format!("{vertical} {} {vertical}", cells.join(&format!(" {vertical} "))),
+            }
+        };
+        let rule = |position: usize| {
+            glyphs.map(|(_, horizontal, joints)| {
+                let [left, middle, right] = joints[position];
+                let segments: Vec<String> = widths.iter().map(|width| horizontal.to_string().repeat(width + 2)).collect();
+                format!("{left}{}{right}", segments.join(&middle.to_string()))
+            })
+        };
+        let mut lines: Vec<String> = rule(0).into_iter().collect();
+        if self.columns.iter().any(|column| !column.header.is_empty()) {
+            let headers: Vec<String> = self.columns.iter().map(|column| column.header.clone()).collect();
+            lines.push(row(&headers));
+            lines.extend(rule(1));
+        }
+        lines.extend(self.rows.iter().map(|cells| row(cells)));
+        lines.extend(rule(2));
+        lines
+    }
+
+    /// Works out each column's width: its widest cell or header, with the widest columns
+    /// narrowed one character at a time until the table fits `width`.
+    fn widths(&self, width: usize) -> Vec<usize> {
+        let mut widths: Vec<usize> = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(index, column)| {
+                let cells = self.rows.iter().filter_map(|cells| cells.get(index)).map(|cell| cell.chars().count());
+                cells.chain([column.header.chars().count()]).max().unwrap_or_default()
+            })
+            .collect();
+        let frame = match self.border {
+            Border::None => 2 * widths.len().saturating_sub(1),
+            Border::Ascii | Border::Unicode => 3 * widths.len() + 1,
+        };
+        while widths.iter().sum::<usize>() + frame > width {
+            let Some(widest) = widths.iter_mut().max_by_key(|width| **width).filter(|width| **width > MIN_COLUMN_WIDTH) else { break };
+            *widest -= 1;
+        }
+        widths
+    }
+}
+
+/// Pads `text` to exactly `width` characters, or cuts it short with an ellipsis if it is longer.
+#[must_use]
+pub fn fit(text: &str, width: usize, align: Align) -> String {
+    let text: String = if text.chars().count() > width {
+        text.chars().take(width.saturating_sub(1)).chain((width > 0).then_some('…')).collect()
+    } else {
+        text.to_string()
+    };
+    match align {
+        Align::Left => format!("{text:<width$}"),
+        Align::Right => format!("{text:>width$}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_fits_width() {
+        let mut table = Table::new().column("", Align::Left).column("#", Align::Right).column("Title", Align::Left).column("Status", Align::Left);
+        table.add_row([">", "1", "EPIC-1 Launch the new website", "In Progress"]);
+        table.add_row(["", "12", "EPIC-12 Docs"]);
+        assert_eq!(
+            table.render(80),
+            vec!["    #  Title                          Status", ">   1  EPIC-1 Launch the new website  In Progress", "   12  EPIC-12 Docs"]
+        );
+        assert_eq!(table.render(30)[1], ">   1  EPIC-1 Lau…  In Progre…");
+        assert!(table.render(0).iter().all(|line| line.chars().count() <= 27));
+
+        let unicode = Table::new().column("", Align::Left).border(Border::Unicode);
+        assert!(unicode.is_empty());
+        assert_eq!(unicode.render(80), vec!["┌──┐", "└──┘"]);
+        assert_eq!(fit("Login", 3, Align::Right), "Lo…");
+    }
+}
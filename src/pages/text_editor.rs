@@ -0,0 +1,190 @@
+//! # Text Editor
+//! A multi-line editor for epic and story descriptions, used by the wizards and the edit page.
+//! Pages get their input a line at a time, so the editor works like `ed`: each line typed is
+//! inserted at the cursor, and lines starting with `:` are commands.
+//!
+//! | Command          | Effect                                              |
+//! |------------------|-----------------------------------------------------|
+//! | `:w`, `:save`    | Save the text.                                      |
+//! | `:q`, `:cancel`  | Throw the changes away.                             |
+//! | `:g N`           | Move the cursor after line `N` (`:g 0` is the top). |
+//! | `:d N`, `:d N-M` | Delete line `N`, or lines `N` to `M`.               |
+//! | `:c N text`      | Change line `N` to `text`.                          |
+//! | `::text`         | Insert a line starting with `:`.                    |
+//!
+//! Long lines are wrapped to the terminal's width when shown, but stored as typed.
+
+use super::Size;
+use super::i18n::{tr, tr_with};
+
+/// The width taken by the line numbers in front of each line, e.g. ` 12 | `.
+const GUTTER_WIDTH: usize = 6;
+/// The narrowest the text is wrapped to, however narrow the terminal is.
+const MIN_WRAP_WIDTH: usize = 20;
+
+/// # Editor Outcome enum
+/// What an input did to the editor.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum EditorOutcome {
+    /// The text changed or the cursor moved; keep editing.
+    Editing,
+    /// The user saved; this is the text, without trailing blank lines.
+    Saved(String),
+    /// The user threw the changes away.
+    Cancelled,
+}
+
+/// # `TextEditor` struct
+/// The lines being edited and where the next typed line goes.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct TextEditor {
+    /// The lines of the text.
+    lines: Vec<String>,
+    /// How many lines come before the cursor; typed lines are inserted here.
+    cursor: usize,
+}
+
+impl TextEditor {
+    /// Opens `text` for editing, with the cursor after the last line.
+    #[must_use]
+    pub fn new(text: &str) -> Self {
+        let lines: Vec<String> = text.lines().map(str::to_string).collect();
+        Self { cursor: lines.len(), lines }
+    }
+
+    /// The lines of the text.
+    #[must_use]
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    /// How many lines come before the cursor.
+    #[must_use]
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// The text as it would be saved, without trailing blank lines.
+    #[must_use]
+    pub fn text(&self) -> String {
+        self.lines.join("\n").trim_end().to_string()
+    }
+
+    /// Handles one line of input: a command, or a line to insert at the cursor.
+    ///
+    /// # Errors
+    /// * `String` - If the command is unknown or names a line that doesn't exist.
+    pub fn handle_input(&mut self, input: &str) -> Result<EditorOutcome, String> {
+        let Some(command) = input.strip_prefix(':').filter(|command| !command.starts_with(':')) else {
+            let line = input.strip_prefix(':').unwrap_or(input);
+            self.lines.insert(self.cursor, line.to_string());
+            self.cursor += 1;
+            return Ok(EditorOutcome::Editing);
+        };
+        let (name, argument) = command.trim_start().split_once(' ').unwrap_or((command.trim(), ""));
+        match name {
+            "w" | "save" => return Ok(EditorOutcome::Saved(self.text())),
+            "q" | "cancel" => return Ok(EditorOutcome::Cancelled),
+            "g" | "goto" => self.cursor = self.line_number(argument, 0)?,
+            "d" | "delete" => {
+                let (first, last) = argument.split_once('-').unwrap_or((argument, argument));
+                let (first, last) = (self.line_number(first, 1)?, self.line_number(last, 1)?);
+                if last < first {
+                    return Err(tr_with("editor-range-reversed", &[("first", &first), ("last", &last)]));
+                }
+                self.lines.drain(first - 1..last);
+                self.cursor = if self.cursor >= last { self.cursor - (last - first + 1) } else { self.cursor.min(first - 1) };
+            }
+            "c" | "change" => {
+                let (number, text) = argument.trim_start().split_once(' ').unwrap_or((argument, ""));
+                let number = self.line_number(number, 1)?;
+                self.lines[number - 1] = text.to_string();
+            }
+            _ => return Err(tr_with("editor-unknown-command", &[("name", &name)])),
+        }
+        Ok(EditorOutcome::Editing)
+    }
+
+    /// Renders the numbered lines, wrapped to fit `size`, with a `>` where the next line goes and
+    /// a reminder of the commands.
+    #[must_use]
+    pub fn render(&self, size: Size) -> Vec<String> {
+        let width = size.columns().saturating_sub(GUTTER_WIDTH).max(MIN_WRAP_WIDTH);
+        let mut rendered = Vec::new();
+        for (index, line) in self.lines.iter().enumerate() {
+            if index == self.cursor {
+                rendered.push("    >".to_string());
+            }
+            for (row_index, row) in wrap(line, width).into_iter().enumerate() {
+                rendered.push(if row_index == 0 { format!("{:>3} | {row}", index + 1) } else { format!("    | {row}") });
+            }
+        }
+        if self.cursor == self.lines.len() {
+            rendered.push("    >".to_string());
+        }
+        rendered.push(tr("editor-hint"));
+        rendered
+    }
+
+    /// Parses a 1-based line number, accepting `lowest` (0 or 1) up to the number of lines.
+    fn line_number(&self, text: &str, lowest: usize) -> Result<usize, String> {
+        let text = text.trim();
+        text.parse::<usize>().ok().filter(|number| (lowest..=self.lines.len()).contains(number)).ok_or_else(|| tr_with("no-such-line", &[("name", &text)]))
+    }
+}
+
+/// Wraps a line at spaces to rows of at most `width` characters, splitting words that are longer
+/// than a whole row.
+fn wrap(line: &str, width: usize) -> Vec<String> {
+    let mut rows = Vec::new();
+    let mut row: Vec<char> = Vec::new();
+    for word in line.split(' ') {
+        let mut word: Vec<char> = word.chars().collect();
+        if !row.is_empty() && row.len() + 1 + word.len() > width {
+            rows.push(row.drain(..).collect());
+        } else if !row.is_empty() {
+            row.push(' ');
+        }
+        while word.len() > width {
+            let rest = word.split_off(width);
+            rows.push(word.into_iter().collect());
+            word = rest;
+        }
+        row.extend(word);
+    }
+    rows.push(row.into_iter().collect());
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_editor() {
+        let mut editor = TextEditor::new("Ship the login page.\nThen the signup page.");
+        for input in [":g 1", "", ":: not a command", ":g 4", "Done."] {
+            assert_eq!(editor.handle_input(input), Ok(EditorOutcome::Editing));
+        }
+        assert_eq!(editor.lines(), ["Ship the login page.", "", ": not a command", "Then the signup page.", "Done."]);
+        editor.handle_input(":d 2-3").unwrap();
+        editor.handle_input(":c 2 Then signup.").unwrap();
+        assert_eq!((editor.lines(), editor.cursor()), (["Ship the login page.", "Then signup.", "Done."].map(str::to_string).as_slice(), 3));
+        assert_eq!(editor.handle_input(":d 4"), Err("There is no line \"4\".".to_string()));
+        assert_eq!(editor.handle_input(":x"), Err("Unknown command \":x\".".to_string()));
+        editor.handle_input(":g 0").unwrap();
+        editor.handle_input("").unwrap();
+        assert_eq!(editor.handle_input(":w"), Ok(EditorOutcome::Saved("\nShip the login page.\nThen signup.\nDone.".to_string())));
+        assert_eq!(editor.handle_input(":cancel"), Ok(EditorOutcome::Cancelled));
+    }
+
+    #[test]
+    fn test_render_wraps_to_width() {
+        let mut editor = TextEditor::new("The quick brown fox jumps over the lazy dog");
+        editor.handle_input(":g 0").unwrap();
+        let lines = editor.render(Size::new(26, 24));
+        assert_eq!(lines[..4], ["    >", "  1 | The quick brown fox", "    | jumps over the lazy", "    | dog"]);
+        assert_eq!(wrap("abcdefghij klm", 4), ["abcd", "efgh", "ij", "klm"]);
+        assert_eq!(TextEditor::new("").render(Size::default())[0], "    >");
+    }
+}
@@ -0,0 +1,166 @@
+//! # Themes
+//! Colors the lines pages render, by the role each line plays: page headings (`=== Title ===`),
+//! section headings (`--- Links ---`), the selected row (`> ...`), errors (`Error: ...`), and key
+//! hints (`[e] edit ...`). Because the theme is applied to the output of
+//! [`Page::render`](super::Page::render) rather than inside each page, every page is themed the
+//! same way.
+//!
+//! Setting the `NO_COLOR` environment variable to anything but an empty string forces the
+//! monochrome theme, which also strips the styling detail pages give Markdown descriptions.
+
+use crate::models::settings::{Settings, ThemeName};
+
+/// The role a rendered line plays, which decides how it is colored.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Role {
+    /// A page heading, e.g. `=== Epics ===`.
+    Heading,
+    /// A section heading, e.g. `--- Stories ---`.
+    Section,
+    /// The selected row of a list, e.g. `>  1  EPIC-1 Launch`.
+    Selected,
+    /// An error message, e.g. `Error: Wrong password.`.
+    Error,
+    /// A key hint line, e.g. `[e] edit  [d] delete`.
+    Hint,
+}
+
+impl Role {
+    /// Works out the role of a rendered line from its prefix, or `None` for ordinary text.
+    #[must_use]
+    pub fn of(line: &str) -> Option<Self> {
+        [("=== ", Role::Heading), ("--- ", Role::Section), ("> ", Role::Selected), ("Error: ", Role::Error), ("[", Role::Hint)]
+            .into_iter()
+            .find_map(|(prefix, role)| line.starts_with(prefix).then_some(role))
+    }
+}
+
+/// # Theme struct
+/// The ANSI SGR parameters (e.g. `1;36` for bold cyan) used for each [`Role`]; empty means
+/// unstyled.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Theme {
+    /// Which theme this is.
+    pub name: ThemeName,
+    /// The style of page headings.
+    heading: &'static str,
+    /// The style of section headings.
+    section: &'static str,
+    /// The style of the selected row.
+    selected: &'static str,
+    /// The style of error messages.
+    error: &'static str,
+    /// The style of key hints.
+    hint: &'static str,
+}
+
+impl Theme {
+    /// The palette of a theme.
+    #[must_use]
+    pub fn new(name: ThemeName) -> Self {
+        let (heading, section, selected, error, hint) = match name {
+            ThemeName::Default => ("1;36", "1", "33", "31", "2"),
+            ThemeName::Light => ("1;34", "1", "35", "31", "90"),
+            ThemeName::HighContrast => ("1;4;97", "1;97", "7", "1;91", "97"),
+            ThemeName::Monochrome => ("", "", "", "", ""),
+        };
+        Self { name, heading, section, selected, error, hint }
+    }
+
+    /// The theme chosen in the settings, unless `no_color` forces the monochrome theme.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use ironyyy::models::settings::{Settings, ThemeName};
+    /// use ironyyy::pages::theme::Theme;
+    /// let settings = Settings { theme: ThemeName::Light, ..Settings::default() };
+    /// assert_eq!(Theme::resolve(&settings, false).name, ThemeName::Light);
+    /// assert_eq!(Theme::resolve(&settings, true).name, ThemeName::Monochrome);
+    /// ```
+    #[must_use]
+    pub fn resolve(settings: &Settings, no_color: bool) -> Self {
+        Self::new(if no_color { ThemeName::Monochrome } else { settings.theme })
+    }
+
+    /// The theme chosen in the settings, honoring the `NO_COLOR` environment variable.
+    #[must_use]
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self::resolve(settings, std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()))
+    }
+
+    /// Styles text for a role.
+    #[must_use]
+    pub fn paint(&self, role: Role, text: &str) -> String {
+        let style = match role {
+            Role::Heading => self.heading,
+            Role::Section => self.section,
+            Role::Selected => self.selected,
+            Role::Error => self.error,
+            Role::Hint => self.hint,
+        };
+        if style.is_empty() { text.to_string() } else { format!("\x1b[{style}m{text}\x1b[0m") }
+    }
+
+    /// Styles a page's rendered lines by their roles. The monochrome theme instead removes any
+    /// styling the lines already carry.
+    #[must_use]
+    pub fn apply(&self, lines: Vec<String>) -> Vec<String> {
+        lines
+            .into_iter()
+            .map(|line| match Role::of(&line) {
+                _ if self.name == ThemeName::Monochrome => strip_styles(&line),
+                Some(role) => self.paint(role, &line),
+                None => line,
+            })
+            .collect()
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::new(ThemeName::Default)
+    }
+}
+
+/// Removes ANSI SGR escape sequences (`ESC [ ... m`) from a line.
+pub(super) fn strip_styles(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            chars.by_ref().find(|&c| c == 'm');
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::db::ClearTextDBState;
+    use crate::pages::{Page, Size, epic_list::EpicListPage, markdown::render_styled};
+    use crate::service::{epics::create_epic, settings::set_theme};
+    use crate::users::User;
+
+    #[test]
+    fn test_themes_style_pages_by_role() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        create_epic(&mut state, "Launch", String::new(), &SystemClock).unwrap();
+        let lines = EpicListPage::new(&state).render(Size::default());
+        let themed = Theme::resolve(&state.settings, false).apply(lines.clone());
+        assert_eq!(themed[0], "\x1b[1;36m=== Epics ===\x1b[0m");
+        assert_eq!(themed[2], "\x1b[33m>  1  EPIC-1 Launch  Open    [----------] 0/0 (0%)\x1b[0m");
+        assert_eq!(themed[3], lines[3]);
+
+        set_theme(&mut state, ThemeName::HighContrast);
+        assert!(state.is_dirty());
+        let high_contrast = Theme::resolve(&state.settings, false);
+        assert_eq!(high_contrast.paint(Role::Error, "Error: Wrong password."), "\x1b[1;91mError: Wrong password.\x1b[0m");
+        let monochrome = Theme::resolve(&state.settings, true);
+        assert_eq!(monochrome.apply(render_styled("Use **TOTP**")), vec!["Use TOTP"]);
+        assert_eq!(monochrome.apply(lines.clone()), lines);
+    }
+}
@@ -0,0 +1,167 @@
+//! # Toasts
+//! Short messages such as `Saved` or a non-fatal error, shown under the current page for a few
+//! seconds instead of replacing it. Pages ask for one with [`PageAction::Notify`] (or
+//! [`PageAction::ShowError`]); the main loop passes every action through [`Toasts::handle`], which
+//! keeps the message and returns what is left to do.
+//!
+//! The most recent messages are kept in a log that the [`NotificationLogPage`] shows, so one that
+//! disappeared too soon can still be read.
+
+use super::i18n::tr;
+use super::keybindings::{KeyContext, KeyMap};
+use super::{Page, PageAction, Size, global_action};
+use crate::clock::Clock;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::VecDeque;
+
+/// How long a toast stays on screen, in seconds.
+pub const TOAST_SECONDS: i64 = 4;
+/// The number of messages the log keeps.
+pub const LOG_LIMIT: usize = 50;
+
+/// # Toast Kind enum
+/// Whether a toast reports something done or something that went wrong.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ToastKind {
+    /// An action finished, e.g. `Story moved`.
+    Info,
+    /// An action failed without leaving the page.
+    Error,
+}
+
+/// # Toast struct
+/// One message and when it was shown.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Toast {
+    /// The message.
+    pub message: String,
+    /// Whether it is news or an error.
+    pub kind: ToastKind,
+    /// When it was shown.
+    pub at: DateTime<Utc>,
+}
+
+impl Toast {
+    /// The message as shown, e.g. `• Saved` or `Error: Wrong password.`.
+    #[must_use]
+    pub fn line(&self) -> String {
+        match self.kind {
+            ToastKind::Info => format!("• {}", self.message),
+            ToastKind::Error => format!("Error: {}", self.message),
+        }
+    }
+}
+
+/// # Toasts struct
+/// The log of recent messages, newest last.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Toasts {
+    /// The last [`LOG_LIMIT`] messages, oldest first.
+    log: VecDeque<Toast>,
+}
+
+impl Toasts {
+    /// Creates an empty log.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Shows a message, dropping the oldest one from the log if it is full.
+    pub fn push(&mut self, kind: ToastKind, message: impl Into<String>, clock: &dyn Clock) {
+        if self.log.len() == LOG_LIMIT {
+            self.log.pop_front();
+        }
+        self.log.push_back(Toast { message: message.into(), kind, at: clock.now() });
+    }
+
+    /// The logged messages, oldest first.
+    #[must_use]
+    pub fn log(&self) -> impl DoubleEndedIterator<Item = &Toast> {
+        self.log.iter()
+    }
+
+    /// Takes the messages out of a page's action: notifications and errors become toasts, and
+    /// [`PageAction::ShowNotifications`] opens the log.
+    ///
+    /// # Returns
+    /// * `PageAction` - What is left for the navigator to do; messages come back as `None`.
+    pub fn handle(&mut self, action: PageAction, clock: &dyn Clock) -> PageAction {
+        match action {
+            PageAction::Notify(message) => self.push(ToastKind::Info, message, clock),
+            PageAction::ShowError(error) => self.push(ToastKind::Error, error, clock),
+            PageAction::ShowNotifications => return PageAction::Navigate(Box::new(NotificationLogPage::new(self))),
+            other => return other,
+        }
+        PageAction::None
+    }
+
+    /// The lines for the messages shown in the last [`TOAST_SECONDS`], oldest first.
+    #[must_use]
+    pub fn render(&self, clock: &dyn Clock) -> Vec<String> {
+        let since = clock.now() - Duration::seconds(TOAST_SECONDS);
+        self.log.iter().filter(|toast| toast.at > since).map(Toast::line).collect()
+    }
+}
+
+/// # `NotificationLogPage` struct
+/// The recent messages, newest first, taken when the page is opened.
+pub struct NotificationLogPage {
+    /// The formatted messages, newest first.
+    lines: Vec<String>,
+    /// The keybindings.
+    keys: KeyMap,
+}
+
+impl NotificationLogPage {
+    /// Creates the page from the log.
+    #[must_use]
+    pub fn new(toasts: &Toasts) -> Self {
+        Self {
+            lines: toasts.log().rev().map(|toast| format!("{}  {}", toast.at.format("%H:%M:%S"), toast.line())).collect(),
+            keys: KeyMap::active(),
+        }
+    }
+}
+
+impl Page for NotificationLogPage {
+    fn render(&self, _size: Size) -> Vec<String> {
+        let mut lines = vec![tr("notifications-title")];
+        if self.lines.is_empty() {
+            lines.push(tr("notifications-empty"));
+        }
+        lines.extend(self.lines.iter().cloned());
+        lines
+    }
+
+    fn handle_input(&mut self, input: &str) -> PageAction {
+        global_action(&self.keys, KeyContext::Global, input).unwrap_or(PageAction::None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+
+    #[test]
+    fn test_toasts_expire_and_stay_in_the_log() {
+        let clock = FixedClock::new(DateTime::from_timestamp(1_700_000_000, 0).unwrap());
+        let mut toasts = Toasts::new();
+        assert!(matches!(toasts.handle(PageAction::Notify("Saved".to_string()), &clock), PageAction::None));
+        clock.advance(Duration::seconds(3));
+        assert!(matches!(toasts.handle(PageAction::ShowError("There is no epic \"EPIC-9\".".to_string()), &clock), PageAction::None));
+        assert!(matches!(toasts.handle(PageAction::Refresh, &clock), PageAction::Refresh));
+        assert_eq!(toasts.render(&clock), vec!["• Saved", "Error: There is no epic \"EPIC-9\"."]);
+        clock.advance(Duration::seconds(2));
+        assert_eq!(toasts.render(&clock), vec!["Error: There is no epic \"EPIC-9\"."]);
+
+        let PageAction::Navigate(page) = toasts.handle(PageAction::ShowNotifications, &clock) else { panic!("expected the log page") };
+        assert_eq!(page.render(Size::default()), vec!["=== Notifications ===", "22:13:23  Error: There is no epic \"EPIC-9\".", "22:13:20  • Saved"]);
+        for number in 0..LOG_LIMIT {
+            toasts.push(ToastKind::Info, number.to_string(), &clock);
+        }
+        assert_eq!(toasts.log().next().map(|toast| toast.message.as_str()), Some("0"));
+        assert_eq!(NotificationLogPage::new(&Toasts::new()).render(Size::default())[1], "No notifications yet.");
+    }
+}
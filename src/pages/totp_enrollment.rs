@@ -0,0 +1,137 @@
+//! # TOTP Enrollment Page
+//! Turns on two-factor login for a user: shows the onboarding QR code for a new TOTP secret,
+//! waits for a code from it to prove the authenticator was set up, and then shows the backup
+//! codes once.
+
+use super::i18n::tr;
+use super::{Page, PageAction, Size};
+use crate::clock::Clock;
+use crate::security::SecurityError;
+use crate::security::totp::{TotpInstance, generate_totp, onboard_totp};
+use crate::users::User;
+use std::sync::Arc;
+
+/// # Enrollment Status
+/// Where the user is in the enrollment flow.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum EnrollmentStatus {
+    /// Waiting for a code from the new secret.
+    AwaitingCode,
+    /// The last code entered did not match the new secret.
+    InvalidCode,
+    /// The secret was confirmed and is now the user's second factor.
+    Enrolled,
+    /// Verification failed with an error.
+    Failed(String),
+}
+
+/// # `TotpEnrollmentPage` struct
+/// Shows the onboarding QR code for a new TOTP secret and waits for a confirming code.
+pub struct TotpEnrollmentPage {
+    /// The user being enrolled.
+    user: User,
+    /// The new TOTP secret awaiting confirmation.
+    pending: TotpInstance,
+    /// Pre-rendered onboarding QR code lines for the pending secret.
+    qr_lines: Vec<String>,
+    /// The backup codes issued on confirmation, shown once.
+    backup_codes: Vec<String>,
+    /// The clock confirming codes are checked against.
+    clock: Arc<dyn Clock>,
+    /// Current state of the flow.
+    status: EnrollmentStatus,
+}
+
+impl TotpEnrollmentPage {
+    /// Creates a new enrollment page for the given user, generating a pending TOTP secret.
+    ///
+    /// # Errors
+    /// * `SecurityError::TryRngCore` - If the new secret could not be generated.
+    /// * `SecurityError::Totp` - If the onboarding QR code could not be rendered.
+    pub fn new(user: User, clock: Arc<dyn Clock>) -> Result<Self, SecurityError> {
+        let pending = generate_totp(&user.username)?;
+        let qr_lines = onboard_totp(&pending)?;
+        Ok(Self {
+            user,
+            pending,
+            qr_lines,
+            clock,
+            backup_codes: Vec::new(),
+            status: EnrollmentStatus::AwaitingCode,
+        })
+    }
+
+    /// Returns the current state of the flow.
+    #[must_use]
+    pub fn status(&self) -> EnrollmentStatus {
+        self.status.clone()
+    }
+
+    /// Consumes the page and returns the (possibly updated) user for persisting.
+    #[must_use]
+    pub fn into_user(self) -> User {
+        self.user
+    }
+}
+
+impl Page for TotpEnrollmentPage {
+    fn render(&self, _size: Size) -> Vec<String> {
+        let mut lines = vec![tr("enrollment-title")];
+        match &self.status {
+            EnrollmentStatus::Enrolled => {
+                lines.push(tr("enrollment-on"));
+                lines.push(tr("backup-codes-intro"));
+                lines.extend(self.backup_codes.iter().map(|code| format!("  {code}")));
+                return lines;
+            }
+            EnrollmentStatus::InvalidCode => lines.push(tr("enrollment-code-mismatch")),
+            EnrollmentStatus::Failed(err) => lines.push(format!("Error: {err}")),
+            EnrollmentStatus::AwaitingCode => {}
+        }
+        if self.user.has_second_factor() {
+            lines.push(tr("enrollment-replaces"));
+        }
+        lines.extend(self.qr_lines.iter().cloned());
+        lines.push(tr("enrollment-scan"));
+        lines
+    }
+
+    fn handle_input(&mut self, input: &str) -> PageAction {
+        if self.status == EnrollmentStatus::Enrolled {
+            return PageAction::None;
+        }
+        let result = self.user.confirm_totp_enrollment(self.pending.clone(), input, self.clock.as_ref());
+        self.status = match result {
+            Ok(Some(codes)) => {
+                self.backup_codes = codes;
+                EnrollmentStatus::Enrolled
+            }
+            Ok(None) => EnrollmentStatus::InvalidCode,
+            Err(err) => EnrollmentStatus::Failed(err.to_string()),
+        };
+        if self.status() == EnrollmentStatus::Enrolled { PageAction::Refresh } else { PageAction::None }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::security::totp::generate_token_at;
+
+    #[test]
+    fn test_enrollment_shows_backup_codes_once_confirmed() {
+        let mut page = TotpEnrollmentPage::new(User::new("alice".to_string(), "password").unwrap(), Arc::new(SystemClock)).unwrap();
+        assert!(page.render(Size::default()).last().unwrap().starts_with("Scan the QR code"));
+        page.handle_input("000000");
+        assert_eq!(page.status(), EnrollmentStatus::InvalidCode);
+
+        assert!(matches!(page.handle_input(&generate_token_at(&page.pending, &SystemClock).unwrap()), PageAction::Refresh));
+        let lines = page.render(Size::default());
+        assert_eq!(page.status(), EnrollmentStatus::Enrolled);
+        assert_eq!(lines[1], "Two-factor login is on.");
+        let user = page.into_user();
+        assert_eq!(lines.len() - 3, user.remaining_backup_codes());
+        assert!(user.has_second_factor());
+    }
+}
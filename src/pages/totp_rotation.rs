@@ -0,0 +1,119 @@
+//! # TOTP Rotation Page
+//! Lets a user re-enroll their authenticator (e.g. after switching phones) by scanning a new
+//! secret and confirming it with a code before the old secret is invalidated.
+
+use super::i18n::{tr, tr_with};
+use super::{Page, PageAction, Size};
+use crate::clock::Clock;
+use crate::security::totp::{export_qr_image, onboard_totp, QrImageFormat, TotpInstance};
+use crate::security::SecurityError;
+use crate::users::User;
+use std::path::Path;
+use std::sync::Arc;
+
+/// # Rotation Status
+/// Where the user is in the re-enrollment flow.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RotationStatus {
+    /// Waiting for a code from the new secret.
+    AwaitingCode,
+    /// The last code entered did not match the new secret.
+    InvalidCode,
+    /// The new secret was confirmed and has replaced the old one.
+    Confirmed,
+    /// The QR code was written to the given image file.
+    Exported(String),
+    /// Verification failed with an error.
+    Failed(String),
+}
+
+/// # `TotpRotationPage` struct
+/// Shows the onboarding QR code for a new TOTP secret and waits for a confirming code.
+pub struct TotpRotationPage {
+    /// The user being re-enrolled.
+    user: User,
+    /// The new TOTP secret awaiting confirmation.
+    pending: TotpInstance,
+    /// Pre-rendered onboarding QR code lines for the pending secret.
+    qr_lines: Vec<String>,
+    /// The clock confirming codes are checked against.
+    clock: Arc<dyn Clock>,
+    /// Current state of the flow.
+    status: RotationStatus,
+}
+
+impl TotpRotationPage {
+    /// Creates a new rotation page for the given user, generating a pending TOTP secret.
+    ///
+    /// # Errors
+    /// * `SecurityError::TryRngCore` - If the new secret could not be generated.
+    /// * `SecurityError::Totp` - If the onboarding QR code could not be rendered.
+    pub fn new(user: User, clock: Arc<dyn Clock>) -> Result<Self, SecurityError> {
+        let pending = user.begin_totp_rotation()?;
+        let qr_lines = onboard_totp(&pending)?;
+        Ok(Self {
+            user,
+            pending,
+            qr_lines,
+            clock,
+            status: RotationStatus::AwaitingCode,
+        })
+    }
+
+    /// Returns the current state of the flow.
+    #[must_use]
+    pub fn status(&self) -> RotationStatus {
+        self.status.clone()
+    }
+
+    /// Consumes the page and returns the (possibly updated) user for persisting.
+    #[must_use]
+    pub fn into_user(self) -> User {
+        self.user
+    }
+}
+
+impl Page for TotpRotationPage {
+    fn render(&self, _size: Size) -> Vec<String> {
+        let mut lines = vec![tr("rotation-title")];
+        match &self.status {
+            RotationStatus::Confirmed => {
+                lines.push(tr("rotation-active"));
+                return lines;
+            }
+            RotationStatus::InvalidCode => lines.push(tr("rotation-code-mismatch")),
+            RotationStatus::Failed(err) => lines.push(format!("Error: {err}")),
+            RotationStatus::Exported(path) => lines.push(tr_with("rotation-exported", &[("path", &path)])),
+            RotationStatus::AwaitingCode => {}
+        }
+        lines.extend(self.qr_lines.iter().cloned());
+        lines.push(tr("rotation-save-hint"));
+        lines.push(tr("rotation-enter-code"));
+        lines
+    }
+
+    fn handle_input(&mut self, input: &str) -> PageAction {
+        if self.status == RotationStatus::Confirmed {
+            return PageAction::None;
+        }
+        if let Some(path) = input.trim().strip_prefix("save ") {
+            let path = Path::new(path.trim());
+            let new_status = match QrImageFormat::from_path(path) {
+                Some(format) => match export_qr_image(&self.pending, path, format) {
+                    Ok(()) => RotationStatus::Exported(path.display().to_string()),
+                    Err(err) => RotationStatus::Failed(err.to_string()),
+                },
+                None => RotationStatus::Failed(tr("rotation-bad-extension")),
+            };
+            self.status = new_status;
+            return PageAction::None;
+        }
+        let result = self.user.confirm_totp_rotation(self.pending.clone(), input, self.clock.as_ref());
+        self.status = match result {
+            Ok(true) => RotationStatus::Confirmed,
+            Ok(false) => RotationStatus::InvalidCode,
+            Err(err) => RotationStatus::Failed(err.to_string()),
+        };
+        if self.status == RotationStatus::Confirmed { PageAction::Refresh } else { PageAction::None }
+    }
+}
@@ -1,41 +1,43 @@
 //! # Security Module
 //! This module handles security features such as password hashing and encryption
 
+pub mod backup_codes;
 mod errors;
+pub mod fido2;
 mod helpers;
+pub mod hotp;
 pub mod totp;
 
-use crate::security::errors::SecurityError;
+pub use self::errors::SecurityError;
 use self::helpers::{ argon2_instance };
 
 use aes_gcm::{
-    aead::{Aead, AeadCore, KeyInit, OsRng},
-    Aes256Gcm, Nonce, Key
+    aead::{Aead, KeyInit},
+    Aes256Gcm, Key
 };
-use argon2::{
-    Params,
-    password_hash::{
-        PasswordHasher, SaltString
-    },
-    Argon2
+use argon2::password_hash::{
+    PasswordHasher, SaltString
 };
+use rand_core::{OsRng, TryRngCore};
 use serde::{ Deserialize, Serialize };
 use uuid::Uuid;
 
 /// # Argon2 Hash
 /// 
-/// Represents a hashed password using the Argon2id algorithm.
+/// Represents a hashed password using the `Argon2id` algorithm.
 #[derive(Clone, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
 pub struct Argon2Hash(pub Vec<u8>);
 
 impl Argon2Hash {
-    /// Creates a new Argon2Hash from a plaintext password and a salt.
+    /// Creates a new `Argon2Hash` from a plaintext password and a salt.
     /// 
     /// # Arguments
     /// * `password` - The plaintext password to hash.
     /// * `salt` - A UUID used as the salt for hashing.
     /// # Returns
-    /// * `Result<Argon2Hash, SecurityError>` - The resulting Argon2Hash or an error.
+    /// * `Result<Argon2Hash, SecurityError>` - The resulting `Argon2Hash` or an error.
+    /// # Errors
+    /// * `SecurityError::Argon2` / `SecurityError::PasswordHash` - If Argon2 fails to hash the password.
     /// 
     /// # Examples
     /// ```rust
@@ -60,6 +62,8 @@ impl Argon2Hash {
     /// * `salt` - The UUID salt used during hashing.
     /// # Returns
     /// * `Result<bool, SecurityError>` - True if the password matches, false otherwise.
+    /// # Errors
+    /// * `SecurityError::Argon2` / `SecurityError::PasswordHash` - If Argon2 fails to hash the password.
     /// # Examples
     /// ```rust
     /// use ironyyy::security::Argon2Hash;
@@ -68,10 +72,11 @@ impl Argon2Hash {
     /// let hash = Argon2Hash::new("my_secure_password", salt).unwrap();
     /// let is_valid = hash.verify_password("my_secure_password", salt).unwrap();
     /// assert!(is_valid);
+    /// ```
     pub fn verify_password(&self, password: &str, salt: Uuid) -> Result<bool, SecurityError> {
         let reference_hash = self.0.clone();
         let argon2 = argon2_instance()?;
-        let salt_string = SaltString::encode_b64(salt.as_bytes()).unwrap();
+        let salt_string = SaltString::encode_b64(salt.as_bytes())?;
         let password_hash = argon2.hash_password(password.as_bytes(), &salt_string)?.hash.ok_or(SecurityError::Hash)?.as_bytes().to_vec();
 
         Ok(reference_hash == password_hash)
@@ -79,18 +84,20 @@ impl Argon2Hash {
 }
 
 /// # Argon2 password-derived encryption key
-/// Represents an encryption key derived from a password using Argon2id.
+/// Represents an encryption key derived from a password using `Argon2id`.
 #[derive(Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
 pub struct Argon2EncryptionKey(pub [u8; 32]);
 
 impl Argon2EncryptionKey {
-    /// Creates a new Argon2EncryptionKey from a plaintext password and a salt.
+    /// Creates a new `Argon2EncryptionKey` from a plaintext password and a salt.
     /// 
     /// # Arguments
     /// * `password` - The plaintext password to derive the key from.
     /// * `salt` - A UUID used as the salt for key derivation.
     /// # Returns
-    /// * `Result<Argon2EncryptionKey, SecurityError>` - The resulting Argon2EncryptionKey or an error.
+    /// * `Result<Argon2EncryptionKey, SecurityError>` - The resulting `Argon2EncryptionKey` or an error.
+    /// # Errors
+    /// * `SecurityError::Argon2` - If Argon2 fails to derive the key.
     /// # Examples
     /// ```rust
     /// use ironyyy::security::Argon2EncryptionKey;
@@ -105,6 +112,17 @@ impl Argon2EncryptionKey {
         argon2_instance()?.hash_password_into(password.as_bytes(), salt.as_bytes(), &mut output_key_material)?;
         Ok(Argon2EncryptionKey(output_key_material))
     }
+
+    /// Creates a random key, e.g. a database's data key, which is stored wrapped with a
+    /// password-derived key rather than derived itself.
+    ///
+    /// # Errors
+    /// * `SecurityError::TryRngCore` - If the random number generator fails.
+    pub fn random() -> Result<Self, SecurityError> {
+        let mut key = [0u8; 32];
+        OsRng.try_fill_bytes(&mut key).map_err(|_| SecurityError::TryRngCore)?;
+        Ok(Argon2EncryptionKey(key))
+    }
 }
 
 /// # Ciphertext
@@ -113,14 +131,16 @@ impl Argon2EncryptionKey {
 pub struct Ciphertext(pub Vec<u8>);
 
 impl Ciphertext {
-    /// Encrypts plaintext using the provided Argon2EncryptionKey and nonce.
+    /// Encrypts plaintext using the provided `Argon2EncryptionKey` and nonce.
     /// 
     /// # Arguments
     /// * `plaintext` - The plaintext string to encrypt.
-    /// * `encryption_key` - The Argon2EncryptionKey used for encryption.
+    /// * `encryption_key` - The `Argon2EncryptionKey` used for encryption.
     /// * `nonce` - A 12-byte nonce for AES-GCM encryption. CHANGE THIS FOR EVERY CALL TO ENCRYPT, but also store it alongside the ciphertext for decryption.
     /// # Returns
-    /// * `Result<Ciphertext, SecurityError>` - The resulting Ciphertext or an error.
+    /// * `Result<Ciphertext, SecurityError>` - The resulting `Ciphertext` or an error.
+    /// # Errors
+    /// * `SecurityError::AesGcm` - If encryption fails.
     /// # Examples
     /// ```rust
     /// use ironyyy::security::{ Argon2EncryptionKey, Ciphertext };
@@ -134,23 +154,35 @@ impl Ciphertext {
     /// assert_eq!(ciphertext, Ciphertext::encrypt("Sensitive data", &key, &nonce).unwrap());
     /// ```
     pub fn encrypt(plaintext: &str, encryption_key: &Argon2EncryptionKey, nonce: &[u8; 12]) -> Result<Self, SecurityError> {
+        Self::encrypt_bytes(plaintext.as_bytes(), encryption_key, nonce)
+    }
+
+    /// Encrypts arbitrary bytes (e.g. an attachment) using the provided `Argon2EncryptionKey` and nonce.
+    ///
+    /// The same nonce rules as for [`Ciphertext::encrypt`] apply.
+    /// # Errors
+    /// * `SecurityError::AesGcm` - If encryption fails.
+    pub fn encrypt_bytes(plaintext: &[u8], encryption_key: &Argon2EncryptionKey, nonce: &[u8; 12]) -> Result<Self, SecurityError> {
         let key: &Key<Aes256Gcm> = &encryption_key.0.into();
 
-        let cipher = Aes256Gcm::new(&key);
+        let cipher = Aes256Gcm::new(key);
         let ciphertext = cipher.encrypt(
             &(*nonce).into(),
-            plaintext.as_bytes(),
+            plaintext,
         )?;
         Ok(Ciphertext(ciphertext))
     }
 
-    /// Decrypts the ciphertext using the provided Argon2EncryptionKey and nonce.
+    /// Decrypts the ciphertext using the provided `Argon2EncryptionKey` and nonce.
     /// 
     /// # Arguments
-    /// * `encryption_key` - The Argon2EncryptionKey used for decryption.
+    /// * `encryption_key` - The `Argon2EncryptionKey` used for decryption.
     /// * `nonce` - The 12-byte nonce used during encryption.
     /// # Returns
     /// * `Result<String, SecurityError>` - The resulting plaintext string or an error.
+    /// # Errors
+    /// * `SecurityError::AesGcm` - If the key or nonce is wrong, or the ciphertext was tampered with.
+    /// * `SecurityError::FromUtf8Error` - If the decrypted bytes are not valid UTF-8.
     /// # Examples
     /// ```rust
     /// use ironyyy::security::{ Argon2EncryptionKey, Ciphertext };
@@ -165,14 +197,22 @@ impl Ciphertext {
     /// assert_eq!(plaintext, "Sensitive data");
     /// ```
     pub fn decrypt(&self, encryption_key: &Argon2EncryptionKey, nonce: &[u8; 12]) -> Result<String, SecurityError> {
+        let plaintext = String::from_utf8(self.decrypt_bytes(encryption_key, nonce)?)?;
+        Ok(plaintext)
+    }
+
+    /// Decrypts the ciphertext into raw bytes using the provided `Argon2EncryptionKey` and nonce.
+    ///
+    /// # Errors
+    /// * `SecurityError::AesGcm` - If the key or nonce is wrong, or the ciphertext was tampered with.
+    pub fn decrypt_bytes(&self, encryption_key: &Argon2EncryptionKey, nonce: &[u8; 12]) -> Result<Vec<u8>, SecurityError> {
         let key: &Key<Aes256Gcm> = &encryption_key.0.into();
-        let cipher = Aes256Gcm::new(&key);
+        let cipher = Aes256Gcm::new(key);
         let plaintext_bytes = cipher.decrypt(
             &(*nonce).into(),
             self.0.as_ref(),
         )?;
-        let plaintext = String::from_utf8(plaintext_bytes)?;
-        Ok(plaintext)
+        Ok(plaintext_bytes)
     }
 }
 
@@ -0,0 +1,171 @@
+//! One-time backup codes for 2FA recovery
+//!
+//! Backup codes are generated alongside a TOTP instance so that a user who loses access to their
+//! authenticator can still log in. Only an Argon2id hash of each code is stored, made with the
+//! same parameters and salt as the user's password hash, so guessing the codes from a stolen file
+//! is as slow as guessing the password. The plaintext codes are shown to the user exactly once.
+
+use super::{Argon2Hash, SecurityError};
+use rand_core::{OsRng, TryRngCore};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Number of backup codes generated per enrollment.
+pub const BACKUP_CODE_COUNT: usize = 10;
+
+/// Number of characters in a backup code (excluding the separator).
+const BACKUP_CODE_LENGTH: usize = 10;
+
+/// Alphabet used for backup codes. Ambiguous characters (`0`/`O`, `1`/`I`) are left out.
+/// Its length is 32, so `byte % 32` maps random bytes onto it without bias.
+const BACKUP_CODE_ALPHABET: &[u8; 32] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+
+/// # Backup Code
+/// A single hashed, single-use backup code.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct BackupCode {
+    /// Argon2id hash of the normalized code, salted with the user's UUID.
+    pub hash: Vec<u8>,
+    /// Whether this code has already been used.
+    pub consumed: bool,
+}
+
+impl BackupCode {
+    /// Creates a new, unconsumed `BackupCode` from a plaintext code.
+    ///
+    /// # Arguments
+    /// * `code` - The plaintext backup code.
+    /// * `salt` - The user's UUID, used as a salt.
+    /// # Errors
+    /// * `SecurityError::Argon2` / `SecurityError::PasswordHash` - If Argon2 fails to hash the code.
+    /// # Examples
+    /// ```rust
+    /// use ironyyy::security::backup_codes::BackupCode;
+    /// use uuid::Uuid;
+    /// let salt = Uuid::new_v4();
+    /// let code = BackupCode::new("ABCDE-FGHJK", salt).unwrap();
+    /// assert!(code.matches("abcde fghjk", salt).unwrap());
+    /// ```
+    pub fn new(code: &str, salt: Uuid) -> Result<Self, SecurityError> {
+        Ok(Self {
+            hash: hash_backup_code(code, salt)?,
+            consumed: false,
+        })
+    }
+
+    /// Checks whether a plaintext code matches this backup code, ignoring case, spaces, and dashes.
+    /// Consumed codes never match.
+    ///
+    /// # Errors
+    /// * `SecurityError::Argon2` / `SecurityError::PasswordHash` - If Argon2 fails to hash the code.
+    pub fn matches(&self, code: &str, salt: Uuid) -> Result<bool, SecurityError> {
+        Ok(!self.consumed && self.hash == hash_backup_code(code, salt)?)
+    }
+}
+
+/// Finds the unused backup code matching a plaintext code and marks it as consumed. The typed code
+/// is hashed once and compared with every stored hash.
+///
+/// # Returns
+/// * `Result<bool, SecurityError>` - Whether a code matched and has now been consumed.
+/// # Errors
+/// * `SecurityError::Argon2` / `SecurityError::PasswordHash` - If Argon2 fails to hash the code.
+pub fn redeem_backup_code(codes: &mut [BackupCode], code: &str, salt: Uuid) -> Result<bool, SecurityError> {
+    let hash = hash_backup_code(code, salt)?;
+    match codes.iter_mut().find(|backup_code| !backup_code.consumed && backup_code.hash == hash) {
+        Some(backup_code) => {
+            backup_code.consumed = true;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Normalizes a backup code: uppercase, with separators and whitespace removed.
+fn normalize_backup_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+/// Hashes a normalized backup code with Argon2id, the way passwords are hashed.
+fn hash_backup_code(code: &str, salt: Uuid) -> Result<Vec<u8>, SecurityError> {
+    Ok(Argon2Hash::new(&normalize_backup_code(code), salt)?.0)
+}
+
+/// Generates a fresh set of backup codes.
+///
+/// # Arguments
+/// * `salt` - The user's UUID, used as a salt for the stored hashes.
+/// # Returns
+/// * `Result<(Vec<String>, Vec<BackupCode>), SecurityError>` - The plaintext codes (to show the user once)
+///   and their hashed counterparts (to store), or an error.
+/// # Errors
+/// * `SecurityError::TryRngCore` - If the random number generator fails.
+/// * `SecurityError::Argon2` / `SecurityError::PasswordHash` - If Argon2 fails to hash a code.
+/// # Examples
+/// ```rust
+/// use ironyyy::security::backup_codes::{generate_backup_codes, BACKUP_CODE_COUNT};
+/// use uuid::Uuid;
+/// let salt = Uuid::new_v4();
+/// let (plaintext, hashed) = generate_backup_codes(salt).unwrap();
+/// assert_eq!(plaintext.len(), BACKUP_CODE_COUNT);
+/// assert!(hashed[0].matches(&plaintext[0], salt).unwrap());
+/// ```
+pub fn generate_backup_codes(salt: Uuid) -> Result<(Vec<String>, Vec<BackupCode>), SecurityError> {
+    let mut plaintext_codes = Vec::with_capacity(BACKUP_CODE_COUNT);
+    let mut hashed_codes = Vec::with_capacity(BACKUP_CODE_COUNT);
+
+    for _ in 0..BACKUP_CODE_COUNT {
+        let mut random_bytes = [0u8; BACKUP_CODE_LENGTH];
+        OsRng.try_fill_bytes(&mut random_bytes).map_err(|_| SecurityError::TryRngCore)?;
+
+        let mut code = String::with_capacity(BACKUP_CODE_LENGTH + 1);
+        for (i, byte) in random_bytes.iter().enumerate() {
+            if i == BACKUP_CODE_LENGTH / 2 {
+                code.push('-');
+            }
+            code.push(char::from(BACKUP_CODE_ALPHABET[usize::from(*byte) % BACKUP_CODE_ALPHABET.len()]));
+        }
+
+        hashed_codes.push(BackupCode::new(&code, salt)?);
+        plaintext_codes.push(code);
+    }
+
+    Ok((plaintext_codes, hashed_codes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_codes_are_unique_and_formatted() {
+        let salt = Uuid::new_v4();
+        let (plaintext, hashed) = generate_backup_codes(salt).unwrap();
+        assert_eq!(plaintext.len(), BACKUP_CODE_COUNT);
+        assert_eq!(hashed.len(), BACKUP_CODE_COUNT);
+        for code in &plaintext {
+            assert_eq!(code.len(), BACKUP_CODE_LENGTH + 1);
+            assert_eq!(code.chars().nth(BACKUP_CODE_LENGTH / 2), Some('-'));
+        }
+        let mut deduped = plaintext.clone();
+        deduped.sort();
+        deduped.dedup();
+        assert_eq!(deduped.len(), plaintext.len());
+    }
+
+    #[test]
+    fn test_backup_code_matching() {
+        let salt = Uuid::new_v4();
+        let mut code = BackupCode::new("ABCDE-FGHJK", salt).unwrap();
+        assert_eq!(code.hash, Argon2Hash::new("ABCDEFGHJK", salt).unwrap().0);
+        assert!(code.matches("ABCDEFGHJK", salt).unwrap());
+        assert!(code.matches(" abcde-fghjk ", salt).unwrap());
+        assert!(!code.matches("ABCDE-FGHJL", salt).unwrap());
+        assert!(!code.matches("ABCDE-FGHJK", Uuid::new_v4()).unwrap());
+        code.consumed = true;
+        assert!(!code.matches("ABCDE-FGHJK", salt).unwrap());
+    }
+}
@@ -1,14 +1,30 @@
-use super::*;
-use rand_core::{ OsRng, TryRngCore };
+//! Error types for the security module
 
+/// # Security Error
+/// Errors that can occur while hashing, deriving keys, encrypting, or handling 2FA.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum SecurityError {
+    /// AES-GCM encryption or decryption failed.
     AesGcm(aes_gcm::Error),
+    /// Argon2 parameter or key derivation failure.
     Argon2(argon2::Error),
+    /// Decrypted bytes were not valid UTF-8.
     FromUtf8Error(std::string::FromUtf8Error),
+    /// FIDO2 security key failure.
+    Fido2,
+    /// Argon2 produced no hash output.
     Hash,
+    /// A key the operation needs hasn't been generated.
+    MissingKey,
+    /// HOTP generation or verification failure.
+    Hotp,
+    /// Password hashing failure.
     PasswordHash(argon2::password_hash::Error),
+    /// The database can only be unlocked with one of the user's security keys, and none answered.
+    SecurityKeyRequired,
+    /// TOTP generation or verification failure.
     Totp,
+    /// The operating system random number generator failed.
     TryRngCore,
 }
 
@@ -39,15 +55,19 @@ impl From<std::string::FromUtf8Error> for SecurityError {
 impl std::fmt::Display for SecurityError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            SecurityError::AesGcm(err) => write!(f, "AES-GCM error: {}", err),
-            SecurityError::Argon2(err) => write!(f, "Argon2 error: {}", err),
-            SecurityError::FromUtf8Error(err) => write!(f, "UTF-8 conversion error: {}", err),
+            SecurityError::AesGcm(err) => write!(f, "AES-GCM error: {err}"),
+            SecurityError::Argon2(err) => write!(f, "Argon2 error: {err}"),
+            SecurityError::Fido2 => write!(f, "FIDO2 security key error"),
+            SecurityError::FromUtf8Error(err) => write!(f, "UTF-8 conversion error: {err}"),
             SecurityError::Hash => write!(f, "Hash error"), // This is due to a distinct error case from argon2's `hash`
-            SecurityError::PasswordHash(err) => write!(f, "Password hash error: {}", err),
+            SecurityError::Hotp => write!(f, "HOTP error"),
+            SecurityError::MissingKey => write!(f, "Missing key"),
+            SecurityError::PasswordHash(err) => write!(f, "Password hash error: {err}"),
+            SecurityError::SecurityKeyRequired => write!(f, "A security key is needed to unlock this database"),
             SecurityError::Totp => write!(f, "TOTP error"),
             SecurityError::TryRngCore => write!(f, "Random number generation error"),
         }
     }
 }
 
-impl std::error::Error for SecurityError {}
\ No newline at end of file
+impl std::error::Error for SecurityError {}
@@ -0,0 +1,195 @@
+//! FIDO2 security keys as a second factor
+//!
+//! A registered security key is asked for its `hmac-secret` extension output over a per-credential
+//! random salt. The output proves possession of the key (a hash of it is stored as a verifier) and
+//! unwraps a secret that is [combined](combine_keys) with the password key, so the database can't
+//! be unlocked without one of the user's keys present (see [`crate::db::storage::Session`]).
+//!
+//! Talking to the authenticator itself is behind the `fido2` feature: the transport is provided by
+//! an [`HmacSecretAuthenticator`] implementation, such as `usb::UsbSecurityKey` for keys plugged
+//! in over USB.
+
+use super::Argon2EncryptionKey;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+#[cfg(feature = "fido2")]
+use super::SecurityError;
+#[cfg(feature = "fido2")]
+use rand_core::{OsRng, TryRngCore};
+
+#[cfg(feature = "fido2")]
+pub mod usb;
+
+/// Relying party identifier that credentials are scoped to.
+pub const RELYING_PARTY_ID: &str = "ironyyy.local";
+
+/// Domain separator for the verifier hash.
+const VERIFIER_CONTEXT: &[u8] = b"ironyyy fido2 verifier v1";
+
+/// Domain separator for combining the password key with the security key secret.
+const DATABASE_KEY_CONTEXT: &[u8] = b"ironyyy fido2 database key v1";
+
+/// # Security Key Credential
+/// A registered FIDO2 credential, as stored in the user record.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct SecurityKeyCredential {
+    /// User-chosen name for the key (e.g. "blue key").
+    pub label: String,
+    /// Credential ID returned by the authenticator at registration.
+    pub credential_id: Vec<u8>,
+    /// Random salt sent with every `hmac-secret` request.
+    pub salt: [u8; 32],
+    /// SHA-256 of the `hmac-secret` output, used to recognize the right key.
+    pub verifier: Vec<u8>,
+}
+
+impl SecurityKeyCredential {
+    /// Creates a credential record from the registration results.
+    #[must_use]
+    pub fn new(label: String, credential_id: Vec<u8>, salt: [u8; 32], hmac_output: &[u8; 32]) -> Self {
+        Self {
+            label,
+            credential_id,
+            salt,
+            verifier: verifier_hash(hmac_output),
+        }
+    }
+
+    /// Checks an `hmac-secret` output against the stored verifier.
+    #[must_use]
+    pub fn verify_output(&self, hmac_output: &[u8; 32]) -> bool {
+        self.verifier == verifier_hash(hmac_output)
+    }
+}
+
+/// Combines the password-derived key with the secret the user's security keys unwrap, so that both
+/// are required to unwrap the data key.
+#[must_use]
+pub fn combine_keys(password_key: &Argon2EncryptionKey, secret: &Argon2EncryptionKey) -> Argon2EncryptionKey {
+    let mut hasher = Sha256::new();
+    hasher.update(DATABASE_KEY_CONTEXT);
+    hasher.update(password_key.0);
+    hasher.update(secret.0);
+    Argon2EncryptionKey(hasher.finalize().into())
+}
+
+/// Hashes an `hmac-secret` output into a verifier.
+fn verifier_hash(hmac_output: &[u8; 32]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update(VERIFIER_CONTEXT);
+    hasher.update(hmac_output);
+    hasher.finalize().to_vec()
+}
+
+/// # HMAC Secret Authenticator trait
+/// A FIDO2 authenticator that supports the `hmac-secret` extension.
+#[cfg(feature = "fido2")]
+pub trait HmacSecretAuthenticator {
+    /// Creates a new credential with the `hmac-secret` extension enabled and returns its ID.
+    ///
+    /// # Errors
+    /// * `SecurityError::Fido2` - If the authenticator refused or could not be reached.
+    fn make_credential(&self, rp_id: &str, user_id: &[u8], user_name: &str) -> Result<Vec<u8>, SecurityError>;
+
+    /// Asks the authenticator for the `hmac-secret` output of a credential over a salt.
+    ///
+    /// # Errors
+    /// * `SecurityError::Fido2` - If the credential is unknown to this authenticator or the user
+    ///   did not confirm presence.
+    fn hmac_secret(&self, rp_id: &str, credential_id: &[u8], salt: &[u8; 32]) -> Result<[u8; 32], SecurityError>;
+}
+
+/// Registers a security key: creates a credential and records its verifier.
+///
+/// # Returns
+/// * `Result<(SecurityKeyCredential, [u8; 32]), SecurityError>` - The credential and its first
+///   `hmac-secret` output.
+///
+/// # Arguments
+/// * `authenticator` - The connected security key.
+/// * `user_id` - The user's UUID bytes.
+/// * `user_name` - The username shown by the authenticator.
+/// * `label` - A name for the key.
+/// # Errors
+/// * `SecurityError::TryRngCore` - If the salt could not be generated.
+/// * `SecurityError::Fido2` - If the authenticator failed.
+#[cfg(feature = "fido2")]
+pub fn register_security_key(
+    authenticator: &dyn HmacSecretAuthenticator,
+    user_id: &[u8],
+    user_name: &str,
+    label: String,
+) -> Result<(SecurityKeyCredential, [u8; 32]), SecurityError> {
+    let credential_id = authenticator.make_credential(RELYING_PARTY_ID, user_id, user_name)?;
+    let mut salt = [0u8; 32];
+    OsRng.try_fill_bytes(&mut salt).map_err(|_| SecurityError::TryRngCore)?;
+    let hmac_output = authenticator.hmac_secret(RELYING_PARTY_ID, &credential_id, &salt)?;
+    Ok((SecurityKeyCredential::new(label, credential_id, salt, &hmac_output), hmac_output))
+}
+
+/// Asks the authenticator to prove possession of any of the given credentials.
+///
+/// # Returns
+/// * `Result<Option<(usize, [u8; 32])>, SecurityError>` - The index of the matching credential and
+///   its `hmac-secret` output, or `None` if this authenticator holds none of them.
+/// # Errors
+/// * `SecurityError::Fido2` - If the authenticator failed for every credential.
+#[cfg(feature = "fido2")]
+pub fn authenticate_security_key(
+    authenticator: &dyn HmacSecretAuthenticator,
+    credentials: &[SecurityKeyCredential],
+) -> Result<Option<(usize, [u8; 32])>, SecurityError> {
+    let mut last_error = None;
+    let mut any_answered = false;
+    for (index, credential) in credentials.iter().enumerate() {
+        match authenticator.hmac_secret(RELYING_PARTY_ID, &credential.credential_id, &credential.salt) {
+            Ok(output) if credential.verify_output(&output) => return Ok(Some((index, output))),
+            Ok(_) => any_answered = true,
+            Err(err) => last_error = Some(err),
+        }
+    }
+    match last_error {
+        Some(err) if !any_answered => Err(err),
+        _ => Ok(None),
+    }
+}
+
+/// A software authenticator deriving outputs from a device secret, for tests.
+#[cfg(all(test, feature = "fido2"))]
+pub(crate) struct SoftwareAuthenticator(pub [u8; 32]);
+
+#[cfg(all(test, feature = "fido2"))]
+impl HmacSecretAuthenticator for SoftwareAuthenticator {
+    fn make_credential(&self, rp_id: &str, user_id: &[u8], _user_name: &str) -> Result<Vec<u8>, SecurityError> {
+        Ok([rp_id.as_bytes(), user_id].concat())
+    }
+
+    fn hmac_secret(&self, _rp_id: &str, credential_id: &[u8], salt: &[u8; 32]) -> Result<[u8; 32], SecurityError> {
+        Ok(Sha256::new().chain_update(self.0).chain_update(credential_id).chain_update(salt).finalize().into())
+    }
+}
+
+#[cfg(all(test, feature = "fido2"))]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_register_and_authenticate() {
+        let key = SoftwareAuthenticator([7; 32]);
+        let other_key = SoftwareAuthenticator([8; 32]);
+        let user_id = Uuid::new_v4();
+        let (credential, registered) = register_security_key(&key, user_id.as_bytes(), "alice", "blue key".to_string()).unwrap();
+        let credentials = vec![credential];
+
+        let (index, output) = authenticate_security_key(&key, &credentials).unwrap().unwrap();
+        assert_eq!((index, output), (0, registered));
+        assert_eq!(authenticate_security_key(&other_key, &credentials).unwrap(), None);
+
+        let password_key = Argon2EncryptionKey([1; 32]);
+        let combined = combine_keys(&password_key, &Argon2EncryptionKey(output));
+        assert_ne!(combined, password_key);
+        assert_eq!(combined, combine_keys(&password_key, &Argon2EncryptionKey(output)));
+    }
+}
@@ -0,0 +1,588 @@
+//! FIDO2 security keys over USB
+//!
+//! [`UsbSecurityKey`] is the [`HmacSecretAuthenticator`] for keys plugged in over USB. For each
+//! request it looks through the Linux `hidraw` devices for ones with the FIDO usage page, so a key
+//! plugged in after start-up is found, and speaks CTAPHID to them in turn until one answers.
+//!
+//! Credentials are made with CTAP2 `authenticatorMakeCredential` and the `hmac-secret` extension
+//! turned on. The output is asked for with `authenticatorGetAssertion`: the salt is sent encrypted
+//! under a secret agreed with the key over P-256 (PIN/UV auth protocol one), and the output comes
+//! back encrypted under the same secret. Keys that need their PIN to make a credential refuse, and
+//! the request fails with `SecurityError::Fido2`.
+
+use super::HmacSecretAuthenticator;
+use crate::security::SecurityError;
+use aes::Aes256;
+use cbc::cipher::{BlockDecryptMut, BlockEncryptMut, KeyIvInit, block_padding::NoPadding};
+use ciborium::Value;
+use hmac::{Hmac, Mac};
+use p256::elliptic_curve::sec1::{FromEncodedPoint, ToEncodedPoint};
+use p256::{EncodedPoint, FieldBytes, PublicKey, SecretKey};
+use rand_core::{OsRng, TryRngCore};
+use sha2::{Digest, Sha256};
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Size of a CTAPHID report, in bytes.
+const REPORT_SIZE: usize = 64;
+
+/// Payload bytes in the first packet of a message, after the channel, command, and length.
+const INIT_DATA: usize = REPORT_SIZE - 7;
+
+/// Payload bytes in each continuation packet, after the channel and sequence number.
+const CONT_DATA: usize = REPORT_SIZE - 5;
+
+/// The channel a new channel is asked for on.
+const BROADCAST: [u8; 4] = [0xff; 4];
+
+/// CTAPHID command opening a channel.
+const CTAPHID_INIT: u8 = 0x86;
+
+/// CTAPHID command carrying a CTAP2 request or response.
+const CTAPHID_CBOR: u8 = 0x90;
+
+/// CTAPHID message sent while the key waits for the user to touch it.
+const CTAPHID_KEEPALIVE: u8 = 0xbb;
+
+/// CTAP2 `authenticatorMakeCredential`.
+const MAKE_CREDENTIAL: u8 = 0x01;
+
+/// CTAP2 `authenticatorGetAssertion`.
+const GET_ASSERTION: u8 = 0x02;
+
+/// CTAP2 `authenticatorClientPIN`, whose `getKeyAgreement` subcommand starts the agreement.
+const CLIENT_PIN: u8 = 0x06;
+
+/// COSE algorithm of the credentials made: ES256.
+const ES256: i32 = -7;
+
+/// COSE algorithm the agreement keys are labelled with: ECDH-ES with HKDF-256.
+const ECDH_ES_HKDF_256: i32 = -25;
+
+/// Authenticator data flag: attested credential data follows.
+const ATTESTED_CREDENTIAL_DATA: u8 = 0x40;
+
+/// Authenticator data flag: extension outputs follow.
+const EXTENSION_DATA: u8 = 0x80;
+
+/// Domain separator for the client data hash, which nothing checks since no signature is kept.
+const CLIENT_DATA_CONTEXT: &[u8] = b"ironyyy fido2 client data v1";
+
+/// # USB Security Key struct
+/// The FIDO2 security keys plugged in over USB, looked up afresh for every request.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct UsbSecurityKey;
+
+impl UsbSecurityKey {
+    /// Runs `request` on each connected key in turn, returning the first answer.
+    ///
+    /// # Errors
+    /// * `SecurityError::Fido2` - If no key is connected, or none of them answered.
+    fn ask<R>(request: impl Fn(&mut CtapHid<File>) -> Result<R, SecurityError>) -> Result<R, SecurityError> {
+        let mut result = Err(SecurityError::Fido2);
+        for path in fido_devices() {
+            let Ok(device) = OpenOptions::new().read(true).write(true).open(&path) else { continue };
+            result = CtapHid::open(device).and_then(|mut hid| request(&mut hid));
+            if result.is_ok() {
+                break;
+            }
+        }
+        result
+    }
+}
+
+impl HmacSecretAuthenticator for UsbSecurityKey {
+    fn make_credential(&self, rp_id: &str, user_id: &[u8], user_name: &str) -> Result<Vec<u8>, SecurityError> {
+        Self::ask(|hid| make_credential(hid, rp_id, user_id, user_name))
+    }
+
+    fn hmac_secret(&self, rp_id: &str, credential_id: &[u8], salt: &[u8; 32]) -> Result<[u8; 32], SecurityError> {
+        Self::ask(|hid| hmac_secret(hid, rp_id, credential_id, salt))
+    }
+}
+
+/// The `hidraw` devices whose report descriptor declares the FIDO usage page, in name order.
+fn fido_devices() -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir("/sys/class/hidraw") else { return Vec::new() };
+    let mut devices: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .filter(|entry| std::fs::read(entry.path().join("device/report_descriptor")).is_ok_and(|descriptor| is_fido(&descriptor)))
+        .map(|entry| Path::new("/dev").join(entry.file_name()))
+        .collect();
+    devices.sort();
+    devices
+}
+
+/// Whether a HID report descriptor sets the FIDO Alliance usage page, `0xF1D0`.
+fn is_fido(descriptor: &[u8]) -> bool {
+    descriptor.windows(3).any(|item| item == [0x06, 0xd0, 0xf1])
+}
+
+/// # CTAPHID Channel struct
+/// A channel to a key over HID reports.
+struct CtapHid<D> {
+    /// The open device.
+    device: D,
+    /// The channel the key gave us.
+    channel: [u8; 4],
+}
+
+impl<D: Read + Write> CtapHid<D> {
+    /// Asks the key for a channel of our own.
+    ///
+    /// # Errors
+    /// * `SecurityError::TryRngCore` - If the nonce could not be generated.
+    /// * `SecurityError::Fido2` - If the device failed.
+    fn open(device: D) -> Result<Self, SecurityError> {
+        let mut hid = Self { device, channel: BROADCAST };
+        let mut nonce = [0u8; 8];
+        OsRng.try_fill_bytes(&mut nonce).map_err(|_| SecurityError::TryRngCore)?;
+        hid.send(CTAPHID_INIT, &nonce)?;
+        loop {
+            // Answers to other programs' requests on the broadcast channel carry their nonces.
+            let response = hid.receive(CTAPHID_INIT)?;
+            if response.len() >= 12 && response[..8] == nonce {
+                hid.channel.copy_from_slice(&response[8..12]);
+                return Ok(hid);
+            }
+        }
+    }
+
+    /// A report on our channel, led by the report ID `hidraw` expects.
+    fn report(&self) -> [u8; REPORT_SIZE + 1] {
+        let mut report = [0u8; REPORT_SIZE + 1];
+        report[1..5].copy_from_slice(&self.channel);
+        report
+    }
+
+    /// Sends a message, split over as many packets as it takes.
+    fn send(&mut self, command: u8, data: &[u8]) -> Result<(), SecurityError> {
+        let length = u16::try_from(data.len()).map_err(|_| SecurityError::Fido2)?;
+        let (first, rest) = data.split_at(data.len().min(INIT_DATA));
+        let mut report = self.report();
+        report[5] = command;
+        report[6..8].copy_from_slice(&length.to_be_bytes());
+        report[8..8 + first.len()].copy_from_slice(first);
+        self.device.write_all(&report).map_err(|_| SecurityError::Fido2)?;
+        for (sequence, chunk) in rest.chunks(CONT_DATA).enumerate() {
+            let mut report = self.report();
+            report[5] = u8::try_from(sequence).map_err(|_| SecurityError::Fido2)?;
+            report[6..6 + chunk.len()].copy_from_slice(chunk);
+            self.device.write_all(&report).map_err(|_| SecurityError::Fido2)?;
+        }
+        Ok(())
+    }
+
+    /// Reads one report.
+    fn read_packet(&mut self) -> Result<[u8; REPORT_SIZE], SecurityError> {
+        let mut packet = [0u8; REPORT_SIZE];
+        match self.device.read(&mut packet) {
+            Ok(read) if read >= 7 => Ok(packet),
+            _ => Err(SecurityError::Fido2),
+        }
+    }
+
+    /// Receives the answer to `command` on our channel, skipping keep-alive messages.
+    ///
+    /// # Errors
+    /// * `SecurityError::Fido2` - If the key reported an error or answered out of order.
+    fn receive(&mut self, command: u8) -> Result<Vec<u8>, SecurityError> {
+        let packet = loop {
+            let packet = self.read_packet()?;
+            if packet[..4] != self.channel {
+                continue;
+            }
+            match packet[4] {
+                CTAPHID_KEEPALIVE => (),
+                found if found == command => break packet,
+                // `CTAPHID_ERROR`, or an answer to something we didn't ask.
+                _ => return Err(SecurityError::Fido2),
+            }
+        };
+        let length = usize::from(u16::from_be_bytes([packet[5], packet[6]]));
+        let mut data = packet[7..].to_vec();
+        let mut sequence = 0u8;
+        while data.len() < length {
+            let packet = self.read_packet()?;
+            if packet[..4] != self.channel {
+                continue;
+            }
+            if packet[4] != sequence {
+                return Err(SecurityError::Fido2);
+            }
+            data.extend_from_slice(&packet[5..]);
+            sequence = sequence.wrapping_add(1);
+        }
+        data.truncate(length);
+        Ok(data)
+    }
+
+    /// Sends a CTAP2 request and returns the response, or `Null` if it had none.
+    ///
+    /// # Errors
+    /// * `SecurityError::Fido2` - If the key answered with an error status, e.g. because it
+    ///   doesn't hold the credential.
+    fn request(&mut self, command: u8, request: &Value) -> Result<Value, SecurityError> {
+        let mut message = vec![command];
+        ciborium::into_writer(request, &mut message).map_err(|_| SecurityError::Fido2)?;
+        self.send(CTAPHID_CBOR, &message)?;
+        match self.receive(CTAPHID_CBOR)?.split_first() {
+            Some((0, [])) => Ok(Value::Null),
+            Some((0, body)) => ciborium::from_reader(body).map_err(|_| SecurityError::Fido2),
+            _ => Err(SecurityError::Fido2),
+        }
+    }
+}
+
+/// Looks up a map entry by its key.
+fn field(map: &Value, key: impl Into<Value>) -> Option<&Value> {
+    let key = key.into();
+    map.as_map()?.iter().find(|(found, _)| *found == key).map(|(_, value)| value)
+}
+
+/// A map entry's byte string.
+fn bytes_field(map: &Value, key: impl Into<Value>) -> Result<&[u8], SecurityError> {
+    field(map, key).and_then(Value::as_bytes).map(Vec::as_slice).ok_or(SecurityError::Fido2)
+}
+
+/// The client data hash sent with each request.
+fn client_data_hash(rp_id: &str) -> Vec<u8> {
+    Sha256::new().chain_update(CLIENT_DATA_CONTEXT).chain_update(rp_id).finalize().to_vec()
+}
+
+/// A `{"id": ..., "type": "public-key"}` credential descriptor.
+fn credential_descriptor(credential_id: &[u8]) -> Value {
+    Value::Map(vec![("id".into(), credential_id.into()), ("type".into(), "public-key".into())])
+}
+
+/// Makes a credential with `hmac-secret` turned on, waiting for the user to touch the key.
+///
+/// # Returns
+/// * `Result<Vec<u8>, SecurityError>` - The credential ID.
+fn make_credential<D: Read + Write>(hid: &mut CtapHid<D>, rp_id: &str, user_id: &[u8], user_name: &str) -> Result<Vec<u8>, SecurityError> {
+    let request = Value::Map(vec![
+        (1.into(), client_data_hash(rp_id).into()),
+        (2.into(), Value::Map(vec![("id".into(), rp_id.into()), ("name".into(), "ironyyy".into())])),
+        (3.into(), Value::Map(vec![("id".into(), user_id.into()), ("name".into(), user_name.into()), ("displayName".into(), user_name.into())])),
+        (4.into(), Value::Array(vec![Value::Map(vec![("alg".into(), ES256.into()), ("type".into(), "public-key".into())])])),
+        (6.into(), Value::Map(vec![("hmac-secret".into(), true.into())])),
+    ]);
+    let response = hid.request(MAKE_CREDENTIAL, &request)?;
+    credential_id(bytes_field(&response, 2)?)
+}
+
+/// Reads the credential ID out of the authenticator data of a new credential: the RP ID hash (32
+/// bytes), flags (1), signature count (4), AAGUID (16), and the ID's length (2) come before it.
+fn credential_id(auth_data: &[u8]) -> Result<Vec<u8>, SecurityError> {
+    if auth_data.get(32).is_none_or(|flags| flags & ATTESTED_CREDENTIAL_DATA == 0) {
+        return Err(SecurityError::Fido2);
+    }
+    let length = auth_data.get(53..55).map(|length| usize::from(u16::from_be_bytes([length[0], length[1]]))).ok_or(SecurityError::Fido2)?;
+    auth_data.get(55..55 + length).map(<[u8]>::to_vec).ok_or(SecurityError::Fido2)
+}
+
+/// Asks the key for the `hmac-secret` output of a credential over a salt, waiting for the user to
+/// touch the key.
+fn hmac_secret<D: Read + Write>(hid: &mut CtapHid<D>, rp_id: &str, credential_id: &[u8], salt: &[u8; 32]) -> Result<[u8; 32], SecurityError> {
+    let agreement = KeyAgreement::with(hid)?;
+    let salt_enc = encrypt(&agreement.shared, salt)?;
+    let extension = Value::Map(vec![(1.into(), agreement.platform_key), (2.into(), salt_enc.as_slice().into()), (3.into(), authenticate(&agreement.shared, &salt_enc)?.into())]);
+    let request = Value::Map(vec![
+        (1.into(), rp_id.into()),
+        (2.into(), client_data_hash(rp_id).into()),
+        (3.into(), Value::Array(vec![credential_descriptor(credential_id)])),
+        (4.into(), Value::Map(vec![("hmac-secret".into(), extension)])),
+    ]);
+    let response = hid.request(GET_ASSERTION, &request)?;
+    // Without attested credential data, the extension outputs follow the first 37 bytes.
+    let auth_data = bytes_field(&response, 2)?;
+    let extensions = match auth_data.get(37..) {
+        Some(extensions) if auth_data[32] & EXTENSION_DATA != 0 => ciborium::from_reader::<Value, _>(extensions).map_err(|_| SecurityError::Fido2)?,
+        _ => return Err(SecurityError::Fido2),
+    };
+    let output = <&[u8; 32]>::try_from(bytes_field(&extensions, "hmac-secret")?).map_err(|_| SecurityError::Fido2)?;
+    decrypt(&agreement.shared, output)
+}
+
+/// # Key Agreement struct
+/// A secret agreed with the key for one request, with our half of the agreement to send along.
+struct KeyAgreement {
+    /// Our public key, as a COSE key.
+    platform_key: Value,
+    /// SHA-256 of the shared point's x-coordinate.
+    shared: [u8; 32],
+}
+
+impl KeyAgreement {
+    /// Agrees a secret with the key, using a new key pair of our own.
+    fn with<D: Read + Write>(hid: &mut CtapHid<D>) -> Result<Self, SecurityError> {
+        let response = hid.request(CLIENT_PIN, &Value::Map(vec![(1.into(), 1.into()), (2.into(), 2.into())]))?;
+        let authenticator_key = from_cose(field(&response, 1).ok_or(SecurityError::Fido2)?)?;
+        let secret = random_secret_key()?;
+        Ok(Self { platform_key: to_cose(&secret.public_key()), shared: shared_secret(&secret, &authenticator_key) })
+    }
+}
+
+/// Generates a P-256 secret key.
+fn random_secret_key() -> Result<SecretKey, SecurityError> {
+    loop {
+        let mut bytes = [0u8; 32];
+        OsRng.try_fill_bytes(&mut bytes).map_err(|_| SecurityError::TryRngCore)?;
+        // Almost every 32 bytes are a valid scalar; the rest are drawn again.
+        if let Ok(secret) = SecretKey::from_slice(&bytes) {
+            return Ok(secret);
+        }
+    }
+}
+
+/// The secret two sides agree on: SHA-256 of the x-coordinate of the shared point.
+fn shared_secret(secret: &SecretKey, public_key: &PublicKey) -> [u8; 32] {
+    let shared = p256::ecdh::diffie_hellman(secret.to_nonzero_scalar(), public_key.as_affine());
+    Sha256::digest(shared.raw_secret_bytes()).into()
+}
+
+/// A P-256 public key as the COSE key CTAP2 exchanges.
+fn to_cose(public_key: &PublicKey) -> Value {
+    let point = public_key.to_encoded_point(false);
+    let coordinate = |coordinate: Option<&FieldBytes>| Value::from(coordinate.map(|bytes| bytes.to_vec()).unwrap_or_default());
+    Value::Map(vec![
+        (1.into(), 2.into()),
+        (3.into(), ECDH_ES_HKDF_256.into()),
+        ((-1).into(), 1.into()),
+        ((-2).into(), coordinate(point.x())),
+        ((-3).into(), coordinate(point.y())),
+    ])
+}
+
+/// Reads a P-256 public key from a COSE key.
+fn from_cose(key: &Value) -> Result<PublicKey, SecurityError> {
+    let coordinate = |label: i32| bytes_field(key, label).and_then(|bytes| <[u8; 32]>::try_from(bytes).map(FieldBytes::from).map_err(|_| SecurityError::Fido2));
+    let point = EncodedPoint::from_affine_coordinates(&coordinate(-2)?, &coordinate(-3)?, false);
+    Option::from(PublicKey::from_encoded_point(&point)).ok_or(SecurityError::Fido2)
+}
+
+/// Encrypts 32 bytes with AES-256-CBC under the shared secret, with a zero IV and no padding.
+fn encrypt(shared: &[u8; 32], block: &[u8; 32]) -> Result<[u8; 32], SecurityError> {
+    let mut encrypted = [0u8; 32];
+    cbc::Encryptor::<Aes256>::new(shared.into(), &[0u8; 16].into()).encrypt_padded_b2b_mut::<NoPadding>(block, &mut encrypted).map_err(|_| SecurityError::Fido2)?;
+    Ok(encrypted)
+}
+
+/// Decrypts what [`encrypt`] encrypted.
+fn decrypt(shared: &[u8; 32], block: &[u8; 32]) -> Result<[u8; 32], SecurityError> {
+    let mut decrypted = [0u8; 32];
+    cbc::Decryptor::<Aes256>::new(shared.into(), &[0u8; 16].into()).decrypt_padded_b2b_mut::<NoPadding>(block, &mut decrypted).map_err(|_| SecurityError::Fido2)?;
+    Ok(decrypted)
+}
+
+/// The first 16 bytes of HMAC-SHA-256 over `data` under the shared secret, proving we hold it.
+fn authenticate(shared: &[u8; 32], data: &[u8]) -> Result<Vec<u8>, SecurityError> {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(shared).map_err(|_| SecurityError::Fido2)?;
+    mac.update(data);
+    Ok(mac.finalize().into_bytes()[..16].to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::fido2::{RELYING_PARTY_ID, authenticate_security_key, register_security_key};
+    use std::collections::VecDeque;
+    use uuid::Uuid;
+
+    /// A key on the other end of the HID reports: it answers CTAPHID and the three CTAP2 requests
+    /// the platform sends, deriving each credential's `hmac-secret` from its own secret.
+    struct SoftwareKey {
+        /// The secret credentials are derived from.
+        secret: [u8; 32],
+        /// The key's half of the key agreement.
+        agreement: SecretKey,
+        /// The message being received: its command, length, and the data so far.
+        incoming: Option<(u8, usize, Vec<u8>)>,
+        /// The reports waiting to be read.
+        outgoing: VecDeque<[u8; REPORT_SIZE]>,
+    }
+
+    /// The channel the software key hands out.
+    const CHANNEL: [u8; 4] = [0, 0, 0, 7];
+
+    /// CTAPHID command the key echoes back.
+    const CTAPHID_PING: u8 = 0x81;
+
+    /// CTAPHID message reporting a transport error.
+    const CTAPHID_ERROR: u8 = 0xbf;
+
+    impl SoftwareKey {
+        fn new(secret: [u8; 32]) -> Self {
+            Self { secret, agreement: random_secret_key().unwrap(), incoming: None, outgoing: VecDeque::new() }
+        }
+
+        /// Queues a message, split into reports.
+        fn reply(&mut self, channel: [u8; 4], command: u8, data: &[u8]) {
+            let mut report = [0u8; REPORT_SIZE];
+            report[..4].copy_from_slice(&channel);
+            report[4] = command;
+            report[5..7].copy_from_slice(&u16::try_from(data.len()).unwrap().to_be_bytes());
+            let (first, rest) = data.split_at(data.len().min(INIT_DATA));
+            report[7..7 + first.len()].copy_from_slice(first);
+            self.outgoing.push_back(report);
+            for (sequence, chunk) in rest.chunks(CONT_DATA).enumerate() {
+                let mut report = [0u8; REPORT_SIZE];
+                report[..4].copy_from_slice(&channel);
+                report[4] = u8::try_from(sequence).unwrap();
+                report[5..5 + chunk.len()].copy_from_slice(chunk);
+                self.outgoing.push_back(report);
+            }
+        }
+
+        /// The random value a credential's outputs are made with, which only this key can work out.
+        fn credential_random(&self, credential_id: &[u8]) -> [u8; 32] {
+            Sha256::new().chain_update(self.secret).chain_update(credential_id).finalize().into()
+        }
+
+        /// Answers a CTAP2 request with a status byte and the CBOR response.
+        fn answer(&mut self, request: &[u8]) -> Vec<u8> {
+            let (command, body) = request.split_first().unwrap();
+            let request: Value = ciborium::from_reader(body).unwrap();
+            let response = match *command {
+                CLIENT_PIN => Value::Map(vec![(1.into(), to_cose(&self.agreement.public_key()))]),
+                MAKE_CREDENTIAL => {
+                    let user_id = bytes_field(field(&request, 3).unwrap(), "id").unwrap();
+                    let credential_id = [b"soft".as_slice(), &Sha256::digest(user_id)].concat();
+                    let mut auth_data = Sha256::digest(RELYING_PARTY_ID).to_vec();
+                    auth_data.push(0x01 | ATTESTED_CREDENTIAL_DATA);
+                    auth_data.extend([0; 4 + 16]);
+                    auth_data.extend(u16::try_from(credential_id.len()).unwrap().to_be_bytes());
+                    auth_data.extend(&credential_id);
+                    ciborium::into_writer(&to_cose(&self.agreement.public_key()), &mut auth_data).unwrap();
+                    Value::Map(vec![(1.into(), "none".into()), (2.into(), auth_data.into()), (3.into(), Value::Map(Vec::new()))])
+                }
+                GET_ASSERTION => {
+                    let credential_id = bytes_field(&field(&request, 3).unwrap().as_array().unwrap()[0], "id").unwrap().to_vec();
+                    if !credential_id.starts_with(b"soft") {
+                        // CTAP2_ERR_NO_CREDENTIALS
+                        return vec![0x2e];
+                    }
+                    let extension = field(field(&request, 4).unwrap(), "hmac-secret").unwrap();
+                    let shared = shared_secret(&self.agreement, &from_cose(field(extension, 1).unwrap()).unwrap());
+                    let salt_enc: &[u8; 32] = bytes_field(extension, 2).unwrap().try_into().unwrap();
+                    if authenticate(&shared, salt_enc).unwrap() != bytes_field(extension, 3).unwrap() {
+                        // CTAP2_ERR_EXTENSION_FIRST
+                        return vec![0xe0];
+                    }
+                    let salt = decrypt(&shared, salt_enc).unwrap();
+                    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(&self.credential_random(&credential_id)).unwrap();
+                    mac.update(&salt);
+                    let output: [u8; 32] = mac.finalize().into_bytes().into();
+                    let output = encrypt(&shared, &output).unwrap();
+                    let mut auth_data = Sha256::digest(RELYING_PARTY_ID).to_vec();
+                    auth_data.push(0x01 | EXTENSION_DATA);
+                    auth_data.extend([0; 4]);
+                    ciborium::into_writer(&Value::Map(vec![("hmac-secret".into(), output.as_slice().into())]), &mut auth_data).unwrap();
+                    Value::Map(vec![(1.into(), credential_descriptor(&credential_id)), (2.into(), auth_data.into()), (3.into(), vec![0u8; 70].into())])
+                }
+                _ => return vec![0x01],
+            };
+            let mut answer = vec![0];
+            ciborium::into_writer(&response, &mut answer).unwrap();
+            answer
+        }
+    }
+
+    impl Write for SoftwareKey {
+        fn write(&mut self, report: &[u8]) -> std::io::Result<usize> {
+            assert_eq!((report.len(), report[0]), (REPORT_SIZE + 1, 0));
+            let packet = &report[1..];
+            let channel: [u8; 4] = packet[..4].try_into().unwrap();
+            match self.incoming.take() {
+                None => {
+                    let length = usize::from(u16::from_be_bytes([packet[5], packet[6]]));
+                    self.incoming = Some((packet[4], length, packet[7..].to_vec()));
+                }
+                Some((command, length, mut data)) => {
+                    data.extend_from_slice(&packet[5..]);
+                    self.incoming = Some((command, length, data));
+                }
+            }
+            if let Some((command, length, mut data)) = self.incoming.take_if(|(_, length, data)| data.len() >= *length) {
+                data.truncate(length);
+                match command {
+                    CTAPHID_INIT => self.reply(BROADCAST, CTAPHID_INIT, &[&data[..8], &CHANNEL, &[2, 1, 0, 0, 0x04]].concat()),
+                    CTAPHID_PING => self.reply(channel, CTAPHID_PING, &data),
+                    CTAPHID_CBOR => {
+                        assert_eq!(channel, CHANNEL);
+                        // The key waits for a touch before answering.
+                        self.reply(CHANNEL, CTAPHID_KEEPALIVE, &[2]);
+                        let answer = self.answer(&data);
+                        self.reply(CHANNEL, CTAPHID_CBOR, &answer);
+                    }
+                    _ => self.reply(channel, CTAPHID_ERROR, &[1]),
+                }
+            }
+            Ok(report.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Read for SoftwareKey {
+        fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+            let report = self.outgoing.pop_front().ok_or(std::io::ErrorKind::UnexpectedEof)?;
+            buffer[..REPORT_SIZE].copy_from_slice(&report);
+            Ok(REPORT_SIZE)
+        }
+    }
+
+    /// Hands the requests to a software key, as [`UsbSecurityKey`] hands them to a USB one.
+    struct Plugged(std::sync::Mutex<SoftwareKey>);
+
+    impl HmacSecretAuthenticator for Plugged {
+        fn make_credential(&self, rp_id: &str, user_id: &[u8], user_name: &str) -> Result<Vec<u8>, SecurityError> {
+            make_credential(&mut CtapHid::open(&mut *self.0.lock().unwrap())?, rp_id, user_id, user_name)
+        }
+
+        fn hmac_secret(&self, rp_id: &str, credential_id: &[u8], salt: &[u8; 32]) -> Result<[u8; 32], SecurityError> {
+            hmac_secret(&mut CtapHid::open(&mut *self.0.lock().unwrap())?, rp_id, credential_id, salt)
+        }
+    }
+
+    #[test]
+    fn test_hmac_secret_over_ctaphid() {
+        let key = Plugged(std::sync::Mutex::new(SoftwareKey::new([7; 32])));
+        let user_id = Uuid::new_v4();
+        let (credential, registered) = register_security_key(&key, user_id.as_bytes(), "alice", "blue key".to_string()).unwrap();
+        assert_eq!(credential.credential_id, [b"soft".as_slice(), &Sha256::digest(user_id.as_bytes())].concat());
+
+        // Each request agrees a new secret, yet the output only depends on the credential and salt.
+        assert_eq!(authenticate_security_key(&key, std::slice::from_ref(&credential)).unwrap(), Some((0, registered)));
+        assert_ne!(key.hmac_secret(RELYING_PARTY_ID, &credential.credential_id, &[1; 32]).unwrap(), registered);
+        let other = Plugged(std::sync::Mutex::new(SoftwareKey::new([8; 32])));
+        assert_eq!(authenticate_security_key(&other, std::slice::from_ref(&credential)).unwrap(), None);
+        assert_eq!(key.hmac_secret(RELYING_PARTY_ID, b"not this key's", &[1; 32]), Err(SecurityError::Fido2));
+    }
+
+    #[test]
+    fn test_messages_are_split_into_reports() {
+        let mut key = SoftwareKey::new([7; 32]);
+        let mut hid = CtapHid::open(&mut key).unwrap();
+        assert_eq!(hid.channel, CHANNEL);
+        let data: Vec<u8> = (0..=200).collect();
+        hid.send(CTAPHID_PING, &data).unwrap();
+        assert_eq!(hid.receive(CTAPHID_PING).unwrap(), data);
+
+        // Reports on other channels are answers to other programs.
+        hid.device.reply(BROADCAST, CTAPHID_INIT, &[0; 17]);
+        hid.device.reply(CHANNEL, CTAPHID_PING, &[1, 2]);
+        assert_eq!(hid.receive(CTAPHID_PING).unwrap(), [1, 2]);
+        hid.device.reply(CHANNEL, CTAPHID_ERROR, &[1]);
+        assert_eq!(hid.receive(CTAPHID_PING), Err(SecurityError::Fido2));
+    }
+
+    #[test]
+    fn test_fido_usage_page_is_recognized() {
+        assert!(is_fido(&[0x06, 0xd0, 0xf1, 0x09, 0x01, 0xa1, 0x01]));
+        assert!(!is_fido(&[0x05, 0x01, 0x09, 0x06, 0xa1, 0x01]));
+    }
+}
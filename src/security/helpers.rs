@@ -1,24 +1,31 @@
-use super::*;
+//! Internal helpers shared by the security module
 
+use super::SecurityError;
+use argon2::{Argon2, Params};
+
+/// Argon2 memory cost in KiB.
 #[cfg(test)]
 const ARGON2_MEMORY_COST: u32 = 1024; // smaller for faster tests
+/// Argon2 memory cost in KiB.
 #[cfg(not(test))]
 const ARGON2_MEMORY_COST: u32 = 65536; // larger for enhanced security
 
+/// Argon2 time cost (number of passes).
 #[cfg(test)]
 const ARGON2_TIME_COST: u32 = 1; // smaller for faster tests
+/// Argon2 time cost (number of passes).
 #[cfg(not(test))]
 const ARGON2_TIME_COST: u32 = 8; // larger for enhanced security
 
 /// # Argon2 Parameters
 /// Returns Argon2 parameters configured for secure password hashing and key derivation.
 fn argon2_params() -> Result<Params, argon2::Error> {
-    Ok(Params::new(
+    Params::new(
         ARGON2_MEMORY_COST, // memory cost in KiB
         ARGON2_TIME_COST,    // time cost
         1,    // parallelism
         None, // output length (default is 32 bytes)
-    )?)
+    )
 }
 
 /// # Argon Instance
@@ -30,4 +37,4 @@ pub fn argon2_instance<'a>() -> Result<Argon2<'a>, SecurityError> {
         argon2::Version::V0x13,
         params,
     ))
-}
\ No newline at end of file
+}
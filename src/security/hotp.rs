@@ -0,0 +1,193 @@
+//! HMAC-based One-Time Password (HOTP) utilities
+//!
+//! HOTP (RFC 4226) is offered as a fallback second factor for users whose devices have unreliable
+//! clocks. Instead of the current time, a counter shared between the app and the authenticator is
+//! advanced each time a code is accepted.
+
+use super::SecurityError;
+use rand_core::{OsRng, TryRngCore};
+use serde::{Deserialize, Serialize};
+use totp_rs::{Algorithm, TOTP};
+
+/// Number of digits in an HOTP code.
+const HOTP_DIGITS: usize = 6;
+
+/// Length of a freshly generated HOTP secret in bytes.
+const HOTP_SECRET_LENGTH: usize = 20;
+
+/// How many counter values past the stored one are accepted, to tolerate codes that were
+/// generated on the device but never submitted.
+pub const HOTP_LOOK_AHEAD: u64 = 10;
+
+/// # HOTP Instance
+/// A counter-based one-time password secret, along with the next expected counter value.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct HotpInstance {
+    /// The raw shared secret.
+    secret: Vec<u8>,
+    /// Issuer shown in authenticator apps.
+    issuer: Option<String>,
+    /// Account name shown in authenticator apps.
+    account_name: String,
+    /// The next counter value the authenticator is expected to use.
+    pub counter: u64,
+}
+
+impl HotpInstance {
+    /// Generates a new HOTP instance with a random secret and a counter of zero.
+    ///
+    /// # Arguments
+    /// * `username` - The account name shown in the authenticator app.
+    /// # Errors
+    /// * `SecurityError::TryRngCore` - If the secret could not be generated.
+    /// # Examples
+    /// ```rust
+    /// use ironyyy::security::hotp::HotpInstance;
+    /// let hotp = HotpInstance::new("example_user").unwrap();
+    /// assert_eq!(hotp.counter, 0);
+    /// ```
+    pub fn new(username: &str) -> Result<Self, SecurityError> {
+        let mut secret = vec![0u8; HOTP_SECRET_LENGTH];
+        OsRng.try_fill_bytes(&mut secret).map_err(|_| SecurityError::TryRngCore)?;
+        Ok(Self {
+            secret,
+            issuer: Some("Ironyyy".to_string()),
+            account_name: username.to_string(),
+            counter: 0,
+        })
+    }
+
+    /// Sets the account name shown in authenticator apps, e.g. after the user is renamed. The
+    /// codes stay the same.
+    pub fn set_account_name(&mut self, account_name: String) {
+        self.account_name = account_name;
+    }
+
+    /// Builds a `totp_rs::TOTP` with a one-second step, so that "time" is simply the counter.
+    /// With that step, TOTP generation is exactly HOTP as defined in RFC 4226.
+    fn counter_generator(&self) -> Result<TOTP, SecurityError> {
+        TOTP::new(
+            Algorithm::SHA1,
+            HOTP_DIGITS,
+            0,
+            1,
+            self.secret.clone(),
+            self.issuer.clone(),
+            self.account_name.clone(),
+        )
+        .map_err(|_| SecurityError::Hotp)
+    }
+
+    /// Generates the code for a specific counter value.
+    ///
+    /// # Errors
+    /// * `SecurityError::Hotp` - If the secret is invalid.
+    pub fn generate(&self, counter: u64) -> Result<String, SecurityError> {
+        Ok(self.counter_generator()?.generate(counter))
+    }
+
+    /// Verifies a code against the stored counter and the look-ahead window.
+    ///
+    /// On success the counter is moved past the matched value, so the same code can't be reused.
+    /// # Arguments
+    /// * `code` - The code entered by the user.
+    /// * `look_ahead` - How many counter values past the stored one to accept.
+    /// # Returns
+    /// * `Result<bool, SecurityError>` - Whether the code was valid.
+    /// # Errors
+    /// * `SecurityError::Hotp` - If the secret is invalid.
+    /// # Examples
+    /// ```rust
+    /// use ironyyy::security::hotp::{HotpInstance, HOTP_LOOK_AHEAD};
+    /// let mut hotp = HotpInstance::new("example_user").unwrap();
+    /// let code = hotp.generate(3).unwrap();
+    /// assert!(hotp.verify(&code, HOTP_LOOK_AHEAD).unwrap());
+    /// assert_eq!(hotp.counter, 4);
+    /// ```
+    pub fn verify(&mut self, code: &str, look_ahead: u64) -> Result<bool, SecurityError> {
+        let generator = self.counter_generator()?;
+        let code = code.trim();
+        for counter in self.counter..=self.counter.saturating_add(look_ahead) {
+            if generator.generate(counter) == code {
+                self.counter = counter + 1;
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Returns the `otpauth://hotp/...` provisioning URI for authenticator apps.
+    ///
+    /// BEWARE: the URI contains the secret!
+    /// # Errors
+    /// * `SecurityError::Hotp` - If the secret is invalid.
+    pub fn provisioning_uri(&self) -> Result<String, SecurityError> {
+        let secret = self.counter_generator()?.get_secret_base32();
+        let account_name = percent_encode(&self.account_name);
+        Ok(match &self.issuer {
+            Some(issuer) => {
+                let issuer = percent_encode(issuer);
+                format!("otpauth://hotp/{issuer}:{account_name}?secret={secret}&issuer={issuer}&counter={}", self.counter)
+            }
+            None => format!("otpauth://hotp/{account_name}?secret={secret}&counter={}", self.counter),
+        })
+    }
+}
+
+/// Percent-encodes everything except RFC 3986 unreserved characters.
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| {
+            if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+                char::from(byte).to_string()
+            } else {
+                format!("%{byte:02X}")
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Instance using the RFC 4226 appendix D test secret.
+    fn rfc_instance() -> HotpInstance {
+        HotpInstance {
+            secret: b"12345678901234567890".to_vec(),
+            issuer: None,
+            account_name: "test".to_string(),
+            counter: 0,
+        }
+    }
+
+    #[test]
+    fn test_rfc4226_vectors() {
+        let hotp = rfc_instance();
+        let expected = ["755224", "287082", "359152", "969429", "338314", "254676", "287922", "162583", "399871", "520489"];
+        for (counter, code) in expected.iter().enumerate() {
+            assert_eq!(hotp.generate(counter as u64).unwrap(), *code);
+        }
+    }
+
+    #[test]
+    fn test_look_ahead_window_and_replay() {
+        let mut hotp = rfc_instance();
+        assert!(!hotp.verify("969429", 2).unwrap()); // counter 3 is outside 0..=2
+        assert!(hotp.verify("969429", 3).unwrap());
+        assert_eq!(hotp.counter, 4);
+        assert!(!hotp.verify("969429", HOTP_LOOK_AHEAD).unwrap()); // no replay
+    }
+
+    #[test]
+    fn test_provisioning_uri() {
+        let mut hotp = rfc_instance();
+        hotp.account_name = "a b".to_string();
+        hotp.counter = 7;
+        assert_eq!(
+            hotp.provisioning_uri().unwrap(),
+            "otpauth://hotp/a%20b?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&counter=7"
+        );
+    }
+}
@@ -1,14 +1,177 @@
 //! Time-based One-Time Password (TOTP) utilities
 
-use super::*;
-use easy_totp::{EasyTotp, QRColorMode, TerminalQRSize};
+use super::SecurityError;
+use crate::clock::Clock;
+use qrcodegen::{QrCode, QrCodeEcc};
+use rand_core::{OsRng, TryRngCore};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::path::Path;
+use totp_rs::{Algorithm, Secret, TOTP};
 
-/// Generate a TOTP instance for a given username
-/// 
+/// Quiet-zone width (in modules) around exported QR codes.
+const QR_BORDER: i32 = 4;
+
+/// Number of random bytes in a new secret (160 bits, as RFC 4226 recommends).
+const SECRET_LENGTH: usize = 20;
+
+/// # TOTP Algorithm
+/// The HMAC hash function used to derive codes.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum TotpAlgorithm {
+    /// HMAC-SHA-1, the most widely supported option.
+    #[serde(rename = "SHA1")]
+    Sha1,
+    /// HMAC-SHA-256.
+    #[serde(rename = "SHA256")]
+    Sha256,
+    /// HMAC-SHA-512, used by secrets enrolled before the algorithm was configurable.
+    #[default]
+    #[serde(rename = "SHA512")]
+    Sha512,
+}
+
+impl From<TotpAlgorithm> for Algorithm {
+    fn from(algorithm: TotpAlgorithm) -> Self {
+        match algorithm {
+            TotpAlgorithm::Sha1 => Algorithm::SHA1,
+            TotpAlgorithm::Sha256 => Algorithm::SHA256,
+            TotpAlgorithm::Sha512 => Algorithm::SHA512,
+        }
+    }
+}
+
+/// # TOTP Config
+/// Code length, time step, and hash algorithm for a TOTP secret.
+///
+/// The defaults (6 digits, 30 seconds, SHA-512) match secrets enrolled before these settings were
+/// configurable, so older user records keep working.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(default)]
+pub struct TotpConfig {
+    /// Number of digits per code (6 or 8).
+    pub digits: u8,
+    /// Length of a time step in seconds (30 or 60).
+    pub period: u64,
+    /// The HMAC hash function.
+    pub algorithm: TotpAlgorithm,
+}
+
+impl Default for TotpConfig {
+    fn default() -> Self {
+        Self {
+            digits: 6,
+            period: 30,
+            algorithm: TotpAlgorithm::Sha512,
+        }
+    }
+}
+
+impl TotpConfig {
+    /// Creates a validated TOTP configuration.
+    ///
+    /// # Arguments
+    /// * `digits` - Number of digits per code; must be 6 or 8.
+    /// * `period` - Time step in seconds; must be 30 or 60.
+    /// * `algorithm` - The HMAC hash function.
+    /// # Errors
+    /// * `SecurityError::Totp` - If `digits` or `period` is not supported.
+    /// # Examples
+    /// ```rust
+    /// use ironyyy::security::totp::{TotpAlgorithm, TotpConfig};
+    /// assert!(TotpConfig::new(8, 60, TotpAlgorithm::Sha256).is_ok());
+    /// assert!(TotpConfig::new(7, 30, TotpAlgorithm::Sha1).is_err());
+    /// ```
+    pub fn new(digits: u8, period: u64, algorithm: TotpAlgorithm) -> Result<Self, SecurityError> {
+        if !matches!(digits, 6 | 8) || !matches!(period, 30 | 60) {
+            return Err(SecurityError::Totp);
+        }
+        Ok(Self { digits, period, algorithm })
+    }
+}
+
+/// # TOTP Instance
+/// A TOTP secret together with its account label and configuration.
+///
+/// Serializes compatibly with `easy_totp::EasyTotp`, so records enrolled with it still load. The
+/// key is the bytes of `raw_secret`; new secrets are the Base32 encoding of random bytes.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct TotpInstance {
+    /// The raw secret string.
+    raw_secret: String,
+    /// Optional issuer shown in authenticator apps.
+    issuer: Option<String>,
+    /// The account name shown in authenticator apps.
+    account_name: String,
+    /// Code length, time step, and hash algorithm.
+    #[serde(flatten)]
+    pub config: TotpConfig,
+    /// The time step of the last accepted code; codes for it or earlier steps are rejected so a
+    /// code can't be used twice.
+    #[serde(default)]
+    last_used_step: u64,
+}
+
+impl TotpInstance {
+    /// Sets the account name shown in authenticator apps, e.g. after the user is renamed. The
+    /// codes stay the same.
+    pub fn set_account_name(&mut self, account_name: String) {
+        self.account_name = account_name;
+    }
+
+    /// Builds the `totp_rs::TOTP` that generates and checks this instance's codes.
+    fn totp_rs(&self) -> Result<TOTP, SecurityError> {
+        let secret = Secret::Raw(self.raw_secret.as_bytes().to_vec()).to_bytes().map_err(|_| SecurityError::Totp)?;
+        TOTP::new(
+            self.config.algorithm.into(),
+            usize::from(self.config.digits),
+            0,
+            self.config.period,
+            secret,
+            self.issuer.clone(),
+            self.account_name.clone(),
+        )
+        .map_err(|_| SecurityError::Totp)
+    }
+}
+
+/// # QR Image Format
+/// File formats the onboarding QR code can be exported as.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum QrImageFormat {
+    /// Portable Network Graphics raster image.
+    Png,
+    /// Scalable Vector Graphics document.
+    Svg,
+}
+
+impl QrImageFormat {
+    /// Picks the image format from a file extension (`.png` or `.svg`, case-insensitive).
+    ///
+    /// # Examples
+    /// ```rust
+    /// use ironyyy::security::totp::QrImageFormat;
+    /// use std::path::Path;
+    /// assert_eq!(QrImageFormat::from_path(Path::new("qr.SVG")), Some(QrImageFormat::Svg));
+    /// assert_eq!(QrImageFormat::from_path(Path::new("qr.txt")), None);
+    /// ```
+    #[must_use]
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "png" => Some(QrImageFormat::Png),
+            "svg" => Some(QrImageFormat::Svg),
+            _ => None,
+        }
+    }
+}
+
+/// Generate a TOTP instance for a given username with the default configuration
+///
 /// # Arguments
 /// * `username` - The username for which to generate the TOTP
 /// # Returns
-/// * `Result<EasyTotp, SecurityError>` - The generated TOTP instance or an error
+/// * `Result<TotpInstance, SecurityError>` - The generated TOTP instance or an error
 /// # Errors
 /// * `SecurityError::TryRngCore` - If there was an error generating the TOTP
 /// # Examples
@@ -16,14 +179,43 @@ use easy_totp::{EasyTotp, QRColorMode, TerminalQRSize};
 /// use ironyyy::security::totp::generate_totp;
 /// let totp = generate_totp("example_user").unwrap();
 /// ```
-pub fn generate_totp(username: &str) -> Result<EasyTotp, SecurityError> {
-    let totp = EasyTotp::new(Some("Ironyyy".to_string()), username.to_string()).map_err(|_| SecurityError::TryRngCore)?;
-    Ok(totp)
+pub fn generate_totp(username: &str) -> Result<TotpInstance, SecurityError> {
+    generate_totp_with_config(username, TotpConfig::default())
+}
+
+/// Generate a TOTP instance for a given username with a specific configuration
+///
+/// # Arguments
+/// * `username` - The username for which to generate the TOTP
+/// * `config` - The digits, period, and algorithm to enroll with
+/// # Returns
+/// * `Result<TotpInstance, SecurityError>` - The generated TOTP instance or an error
+/// # Errors
+/// * `SecurityError::TryRngCore` - If there was an error generating the secret
+/// # Examples
+/// ```rust
+/// use ironyyy::security::totp::{generate_totp_with_config, TotpAlgorithm, TotpConfig};
+/// let config = TotpConfig::new(8, 60, TotpAlgorithm::Sha1).unwrap();
+/// let totp = generate_totp_with_config("example_user", config).unwrap();
+/// assert_eq!(totp.config, config);
+/// ```
+pub fn generate_totp_with_config(username: &str, config: TotpConfig) -> Result<TotpInstance, SecurityError> {
+    let mut secret = [0u8; SECRET_LENGTH];
+    OsRng.try_fill_bytes(&mut secret).map_err(|_| SecurityError::TryRngCore)?;
+    Ok(TotpInstance {
+        raw_secret: Secret::Raw(secret.to_vec()).to_encoded().to_string(),
+        issuer: Some("Ironyyy".to_string()),
+        account_name: username.to_string(),
+        config,
+        last_used_step: 0,
+    })
 }
 
 /// Generate the onboarding QR code for a given TOTP instance
+///
+/// The QR code is drawn with half-block characters in light-on-dark colors.
 /// # Arguments
-/// * `et` - The TOTP instance for which to generate the QR code
+/// * `totp` - The TOTP instance for which to generate the QR code
 /// # Returns
 /// * `Result<Vec<String>, SecurityError>` - The generated QR code lines or an error
 /// # Errors
@@ -37,27 +229,263 @@ pub fn generate_totp(username: &str) -> Result<EasyTotp, SecurityError> {
 ///     println!("{}", line);
 /// }
 /// ```
-pub fn onboard_totp(et: &EasyTotp) -> Result<Vec<String>, SecurityError> {
-    Ok(et.qr_text(TerminalQRSize::Full, QRColorMode::Inverted).map_err(|_| SecurityError::Totp)?)
+pub fn onboard_totp(totp: &TotpInstance) -> Result<Vec<String>, SecurityError> {
+    let uri = provisioning_uri(totp)?;
+    let qr = QrCode::encode_text(&uri, QrCodeEcc::Medium).map_err(|_| SecurityError::Totp)?;
+    // Light modules are drawn filled, which reads correctly on dark terminal backgrounds.
+    let is_light = |x: i32, y: i32| !qr.get_module(x, y);
+
+    let mut lines = Vec::new();
+    for y in (-QR_BORDER..qr.size() + QR_BORDER).step_by(2) {
+        let line = (-QR_BORDER..qr.size() + QR_BORDER)
+            .map(|x| match (is_light(x, y), is_light(x, y + 1)) {
+                (true, true) => '█',
+                (true, false) => '▀',
+                (false, true) => '▄',
+                (false, false) => ' ',
+            })
+            .collect();
+        lines.push(line);
+    }
+    lines.push(String::from("Scan the above QR code with your authenticator app to set up TOTP."));
+    lines.push(String::from("BEWARE: this QR code contains your secret key! Handle with care."));
+    lines.push(String::from("If your terminal mangles the QR code, import this URI into your authenticator instead:"));
+    lines.push(uri);
+    Ok(lines)
+}
+
+/// Get the `otpauth://` provisioning URI for a given TOTP instance
+///
+/// BEWARE: the URI contains the secret!
+/// # Arguments
+/// * `totp` - The TOTP instance to describe
+/// # Returns
+/// * `Result<String, SecurityError>` - The provisioning URI or an error
+/// # Errors
+/// * `SecurityError::Totp` - If the TOTP instance is invalid
+/// # Examples
+/// ```rust
+/// use ironyyy::security::totp::{generate_totp, provisioning_uri};
+/// let totp = generate_totp("example_user").unwrap();
+/// let uri = provisioning_uri(&totp).unwrap();
+/// assert!(uri.starts_with("otpauth://totp/Ironyyy:example_user?"));
+/// ```
+pub fn provisioning_uri(totp: &TotpInstance) -> Result<String, SecurityError> {
+    Ok(totp.totp_rs()?.get_url())
+}
+
+/// Render the onboarding QR code for a given TOTP instance as an image
+/// # Arguments
+/// * `totp` - The TOTP instance to encode
+/// * `format` - The image format to produce
+/// # Returns
+/// * `Result<Vec<u8>, SecurityError>` - The encoded image bytes or an error
+/// # Errors
+/// * `SecurityError::Totp` - If the QR code could not be generated
+/// # Examples
+/// ```rust
+/// use ironyyy::security::totp::{generate_totp, qr_image, QrImageFormat};
+/// let totp = generate_totp("example_user").unwrap();
+/// let svg = qr_image(&totp, QrImageFormat::Svg).unwrap();
+/// assert!(String::from_utf8(svg).unwrap().contains("<svg"));
+/// ```
+pub fn qr_image(totp: &TotpInstance, format: QrImageFormat) -> Result<Vec<u8>, SecurityError> {
+    match format {
+        QrImageFormat::Png => totp.totp_rs()?.get_qr_png().map_err(|_| SecurityError::Totp),
+        QrImageFormat::Svg => {
+            let qr = QrCode::encode_text(&provisioning_uri(totp)?, QrCodeEcc::Medium).map_err(|_| SecurityError::Totp)?;
+            let dimension = qr.size() + QR_BORDER * 2;
+            let mut svg = format!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+                 <svg xmlns=\"http://www.w3.org/2000/svg\" version=\"1.1\" viewBox=\"0 0 {dimension} {dimension}\" stroke=\"none\">\n\
+                 <rect width=\"100%\" height=\"100%\" fill=\"#FFFFFF\"/>\n<path d=\""
+            );
+            for y in 0..qr.size() {
+                for x in 0..qr.size() {
+                    if qr.get_module(x, y) {
+                        write!(svg, "M{},{}h1v1h-1z ", x + QR_BORDER, y + QR_BORDER).map_err(|_| SecurityError::Totp)?;
+                    }
+                }
+            }
+            svg.push_str("\" fill=\"#000000\"/>\n</svg>\n");
+            Ok(svg.into_bytes())
+        }
+    }
+}
 
+/// Write the onboarding QR code for a given TOTP instance to an image file
+///
+/// On Unix the file is readable by the owner only, since it contains the secret. An existing file
+/// is replaced rather than rewritten, so it doesn't keep looser permissions.
+/// # Arguments
+/// * `totp` - The TOTP instance to encode
+/// * `path` - Where to write the image
+/// * `format` - The image format to write
+/// # Errors
+/// * `SecurityError::Totp` - If the QR code could not be generated
+/// * `std::io::Error` - If the file could not be written
+pub fn export_qr_image(totp: &TotpInstance, path: &Path, format: QrImageFormat) -> Result<(), Box<dyn std::error::Error>> {
+    let image = qr_image(totp, format)?;
+    crate::db::storage::write_replacing(path, &image)?;
+    Ok(())
+}
+
+/// Generate the TOTP code that is valid at the clock's current time
+/// # Arguments
+/// * `totp` - The TOTP instance to generate a code for
+/// * `clock` - The source of the current time
+/// # Returns
+/// * `Result<String, SecurityError>` - The code or an error
+/// # Errors
+/// * `SecurityError::Totp` - If there was an error generating the token
+/// # Examples
+/// ```rust
+/// use ironyyy::clock::SystemClock;
+/// use ironyyy::security::totp::{generate_totp, generate_token_at};
+/// let totp = generate_totp("example_user").unwrap();
+/// let code = generate_token_at(&totp, &SystemClock).unwrap();
+/// assert_eq!(code.len(), 6);
+/// ```
+pub fn generate_token_at(totp: &TotpInstance, clock: &dyn Clock) -> Result<String, SecurityError> {
+    Ok(totp.totp_rs()?.generate(clock.unix_seconds()))
 }
 
 /// Verify a TOTP code against a given TOTP instance
+///
+/// Codes from the adjacent time step on either side are also accepted, to tolerate small clock drift.
+/// An accepted code's time step is remembered, and codes for that step or any earlier one are
+/// rejected afterwards, so an observed code can't be replayed. The instance must be saved after a
+/// successful check.
 /// # Arguments
-/// * `et` - The TOTP instance to verify against
+/// * `totp` - The TOTP instance to verify against
 /// * `code` - The TOTP code to verify
+/// * `clock` - The source of the current time
 /// # Returns
 /// * `Result<bool, SecurityError>` - Whether the code is valid or an error
 /// # Errors
 /// * `SecurityError::Totp` - If there was an error generating the token
 /// # Examples
 /// ```rust
-/// use ironyyy::security::totp::{generate_totp, verify_totp};
-/// let totp = generate_totp("example_user").unwrap();
-/// let code = totp.generate_token().unwrap();
-/// let is_valid = verify_totp(&totp, &code).unwrap();
-/// assert!(is_valid);
+/// use ironyyy::clock::SystemClock;
+/// use ironyyy::security::totp::{generate_totp, generate_token_at, verify_totp};
+/// let mut totp = generate_totp("example_user").unwrap();
+/// let code = generate_token_at(&totp, &SystemClock).unwrap();
+/// assert!(verify_totp(&mut totp, &code, &SystemClock).unwrap());
+/// assert!(!verify_totp(&mut totp, &code, &SystemClock).unwrap());
 /// ```
-pub fn verify_totp(et: &EasyTotp, code: &str) -> Result<bool, SecurityError> {
-    Ok(et.generate_token().map_err(|_| SecurityError::Totp)? == code)
-}
\ No newline at end of file
+pub fn verify_totp(totp: &mut TotpInstance, code: &str, clock: &dyn Clock) -> Result<bool, SecurityError> {
+    let generator = totp.totp_rs()?;
+    let period = totp.config.period;
+    let current = clock.unix_seconds() / period;
+    let accepted = (current.saturating_sub(1)..=current + 1)
+        .filter(|step| *step > totp.last_used_step)
+        .find(|step| generator.check(code, step * period));
+    if let Some(step) = accepted {
+        totp.last_used_step = step;
+    }
+    Ok(accepted.is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+    use chrono::{DateTime, Duration};
+    use easy_totp::EasyTotp;
+
+    #[test]
+    fn test_default_instance_matches_easy_totp() {
+        let et = EasyTotp::new(Some("Ironyyy".to_string()), "example_user".to_string()).unwrap();
+        let totp: TotpInstance = serde_json::from_value(serde_json::to_value(&et).unwrap()).unwrap();
+        assert_eq!(totp.config, TotpConfig::default());
+        let token = et.generate_token().unwrap();
+        assert_eq!(totp.totp_rs().unwrap().generate_current().unwrap(), token);
+    }
+
+    #[test]
+    fn test_new_secrets_are_random() {
+        let totp = generate_totp("example_user").unwrap();
+        assert_eq!(totp.raw_secret.len(), 32);
+        assert!(totp.raw_secret.chars().all(|c| c.is_ascii_uppercase() || ('2'..='7').contains(&c)));
+        assert_ne!(generate_totp("example_user").unwrap().raw_secret, totp.raw_secret);
+    }
+
+    #[test]
+    fn test_custom_config_is_used() {
+        let config = TotpConfig::new(8, 60, TotpAlgorithm::Sha256).unwrap();
+        let totp = generate_totp_with_config("example_user", config).unwrap();
+        let clock = FixedClock::new(DateTime::from_timestamp(1_700_000_040, 0).unwrap());
+        let code = generate_token_at(&totp, &clock).unwrap();
+        assert_eq!(code.len(), 8);
+        // A 60 second step means the code is unchanged 59 seconds later.
+        clock.advance(Duration::seconds(59));
+        assert_eq!(generate_token_at(&totp, &clock).unwrap(), code);
+
+        let uri = provisioning_uri(&totp).unwrap();
+        assert!(uri.contains("digits=8") && uri.contains("algorithm=SHA256") && uri.contains("period=60"));
+
+        let round_trip: TotpInstance = serde_json::from_str(&serde_json::to_string(&totp).unwrap()).unwrap();
+        assert_eq!(round_trip, totp);
+    }
+
+    #[test]
+    fn test_verify_totp_step_boundaries() {
+        let totp = generate_totp("example_user").unwrap();
+        // The last second of the step starting at 1_700_000_010 (a multiple of 30).
+        let clock = FixedClock::new(DateTime::from_timestamp(1_700_000_039, 0).unwrap());
+        let code = generate_token_at(&totp, &clock).unwrap();
+        assert!(verify_totp(&mut totp.clone(), &code, &clock).unwrap());
+
+        // One step later the previous code is still tolerated...
+        clock.advance(Duration::seconds(30));
+        assert!(verify_totp(&mut totp.clone(), &code, &clock).unwrap());
+
+        // ...but two steps later it is rejected.
+        clock.advance(Duration::seconds(1));
+        assert!(!verify_totp(&mut totp.clone(), &code, &clock).unwrap());
+    }
+
+    #[test]
+    fn test_verify_totp_rejects_replays() {
+        let mut totp = generate_totp("example_user").unwrap();
+        let clock = FixedClock::new(DateTime::from_timestamp(1_700_000_039, 0).unwrap());
+        let code = generate_token_at(&totp, &clock).unwrap();
+        assert!(verify_totp(&mut totp, &code, &clock).unwrap());
+        assert!(!verify_totp(&mut totp, &code, &clock).unwrap());
+
+        // A code for an earlier step than the one last used is rejected as well.
+        clock.advance(Duration::seconds(30));
+        let next = generate_token_at(&totp, &clock).unwrap();
+        assert!(verify_totp(&mut totp, &next, &clock).unwrap());
+        clock.set(DateTime::from_timestamp(1_700_000_039, 0).unwrap());
+        assert!(!verify_totp(&mut totp, &code, &clock).unwrap());
+
+        let round_trip: TotpInstance = serde_json::from_str(&serde_json::to_string(&totp).unwrap()).unwrap();
+        assert!(!verify_totp(&mut round_trip.clone(), &next, &clock).unwrap());
+    }
+
+    #[test]
+    fn test_qr_image_formats() {
+        let totp = generate_totp("example_user").unwrap();
+        let png = qr_image(&totp, QrImageFormat::Png).unwrap();
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        let svg = String::from_utf8(qr_image(&totp, QrImageFormat::Svg).unwrap()).unwrap();
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_export_replaces_loose_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = std::env::temp_dir().join(format!("ironyyy-qr-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("qr.svg");
+        std::fs::write(&path, "old").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        export_qr_image(&generate_totp("example_user").unwrap(), &path, QrImageFormat::Svg).unwrap();
+        assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        assert!(std::fs::read_to_string(&path).unwrap().contains("<svg"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
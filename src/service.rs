@@ -0,0 +1,223 @@
+//! # Service Module
+//! Operations that read and mutate a user's `ClearTextDBState`, so that pages and other entry
+//! points share one mutation path instead of editing struct fields directly.
+//!
+//! Every mutation marks the state dirty (see [`ClearTextDBState::is_dirty`]), so callers know when
+//! it needs saving.
+//!
+//! Every change to the projects first checks the role the database was opened with (see
+//! [`require`]) and fails with `ServiceError::NotAllowed` if it doesn't allow it: viewers of a
+//! shared database can't change anything, and editors can't delete projects. Only the user's
+//! preferences and which project is shown can be changed whatever the role.
+
+pub mod archive;
+pub mod assignees;
+pub mod burndown;
+pub mod calendar;
+pub mod checklists;
+pub mod custom_fields;
+pub mod dependencies;
+pub mod epics;
+mod errors;
+pub mod estimates;
+pub mod events;
+pub mod goals;
+pub mod hierarchy;
+pub mod history;
+pub mod integrity;
+pub mod labels;
+pub mod links;
+pub mod pagination;
+pub mod projects;
+pub mod query;
+pub mod quick_open;
+pub mod ranking;
+pub mod references;
+pub mod reminders;
+pub mod repair;
+pub mod schedule;
+pub mod search;
+pub mod settings;
+pub mod shared;
+pub mod sprints;
+pub mod stats;
+pub mod statuses;
+pub mod stories;
+pub mod tasks;
+pub mod trash;
+pub mod undo;
+pub mod users;
+pub mod watchers;
+pub mod work_logs;
+
+pub use self::errors::ServiceError;
+pub use self::integrity::check_integrity;
+
+use crate::clock::Clock;
+use crate::db::ClearTextDBState;
+use crate::models::{
+    Status,
+    epics::Epic,
+    events::Event,
+    history::diff,
+    roles::Permission,
+    stories::Story,
+    workflow::{Transition, TransitionAction},
+};
+use uuid::Uuid;
+
+/// Checks that the role the database was opened with allows `permission`.
+///
+/// # Errors
+/// * `ServiceError::NotAllowed` - If it doesn't, e.g. for a viewer of a shared database.
+pub fn require(state: &ClearTextDBState, permission: Permission) -> Result<(), ServiceError> {
+    let role = state.role();
+    if role.allows(permission) { Ok(()) } else { Err(ServiceError::NotAllowed(role, permission)) }
+}
+
+/// Looks up an epic by UUID.
+///
+/// # Errors
+/// * `ServiceError::EpicNotFound` - If no such epic exists.
+pub fn get_epic(state: &ClearTextDBState, epic_uuid: Uuid) -> Result<&Epic, ServiceError> {
+    state
+        .epics
+        .iter()
+        .find(|epic| epic.epic_uuid == epic_uuid)
+        .ok_or(ServiceError::EpicNotFound(epic_uuid))
+}
+
+/// Looks up an epic by UUID for modification, marking the database dirty. Changes made through
+/// this reference are not recorded in the history; use [`update_epic`] instead.
+fn get_epic_mut(state: &mut ClearTextDBState, epic_uuid: Uuid) -> Result<&mut Epic, ServiceError> {
+    state.mark_dirty();
+    state
+        .epics
+        .iter_mut()
+        .find(|epic| epic.epic_uuid == epic_uuid)
+        .ok_or(ServiceError::EpicNotFound(epic_uuid))
+}
+
+/// Looks up a story by UUID.
+///
+/// # Errors
+/// * `ServiceError::StoryNotFound` - If no such story exists.
+pub fn get_story(state: &ClearTextDBState, story_uuid: Uuid) -> Result<&Story, ServiceError> {
+    state
+        .stories
+        .iter()
+        .find(|story| story.story_uuid == story_uuid)
+        .ok_or(ServiceError::StoryNotFound(story_uuid))
+}
+
+/// Looks up a story by UUID for modification, marking the database dirty. Changes made through
+/// this reference are not recorded in the history; use [`update_story`] instead.
+fn get_story_mut(state: &mut ClearTextDBState, story_uuid: Uuid) -> Result<&mut Story, ServiceError> {
+    state.mark_dirty();
+    state
+        .stories
+        .iter_mut()
+        .find(|story| story.story_uuid == story_uuid)
+        .ok_or(ServiceError::StoryNotFound(story_uuid))
+}
+
+/// Checks a status change against the database's workflow.
+///
+/// # Errors
+/// * `ServiceError::StatusNotFound` - If `to` is a custom status the database doesn't define.
+/// * `ServiceError::ActionRequired` - If the change needs a different action.
+/// * `ServiceError::InvalidTransition` - If no rule allows the change.
+pub fn check_transition(state: &ClearTextDBState, from: Status, to: Status, action: TransitionAction) -> Result<(), ServiceError> {
+    statuses::ensure_status_defined(state, to)?;
+    match state.workflow.check(from, to, action, &state.statuses) {
+        Transition::Allowed => Ok(()),
+        Transition::Requires(action) => Err(ServiceError::ActionRequired(action)),
+        Transition::Forbidden => Err(ServiceError::InvalidTransition(from, to)),
+    }
+}
+
+/// Applies a change to an epic, records every modified field in the history, and bumps its
+/// `updated_at` timestamp and emits [`Event::EpicUpdated`] if anything changed.
+///
+/// # Arguments
+/// * `state` - The database state to modify.
+/// * `epic_uuid` - The epic to change.
+/// * `clock` - The source of the history timestamps.
+/// * `change` - The modification to make.
+/// # Returns
+/// * `Result<R, ServiceError>` - Whatever `change` returned.
+/// # Errors
+/// * `ServiceError::EpicNotFound` - If no such epic exists.
+pub fn update_epic<R>(
+    state: &mut ClearTextDBState,
+    epic_uuid: Uuid,
+    clock: &dyn Clock,
+    change: impl FnOnce(&mut Epic) -> R,
+) -> Result<R, ServiceError> {
+    require(state, Permission::Edit)?;
+    let epic = get_epic_mut(state, epic_uuid)?;
+    let before = epic.clone();
+    let result = change(epic);
+    let now = clock.now();
+    let entries = diff(&before, epic, now);
+    if entries.is_empty() {
+        return Ok(result);
+    }
+    epic.updated_at = now;
+    let status = (epic.status != before.status).then_some((before.status, epic.status));
+    let fields = entries.iter().map(|entry| entry.field.clone()).collect();
+    state.history.extend(entries);
+    state.emit(Event::EpicUpdated { epic_uuid, fields });
+    if let Some((from, to)) = status {
+        state.emit(Event::EpicStatusChanged { epic_uuid, from, to });
+    }
+    Ok(result)
+}
+
+/// Applies a change to a story, records every modified field in the history, and bumps its
+/// `updated_at` timestamp and emits [`Event::StoryUpdated`] if anything changed.
+///
+/// # Arguments
+/// * `state` - The database state to modify.
+/// * `story_uuid` - The story to change.
+/// * `clock` - The source of the history timestamps.
+/// * `change` - The modification to make.
+/// # Returns
+/// * `Result<R, ServiceError>` - Whatever `change` returned.
+/// # Errors
+/// * `ServiceError::StoryNotFound` - If no such story exists.
+pub fn update_story<R>(
+    state: &mut ClearTextDBState,
+    story_uuid: Uuid,
+    clock: &dyn Clock,
+    change: impl FnOnce(&mut Story) -> R,
+) -> Result<R, ServiceError> {
+    require(state, Permission::Edit)?;
+    let story = get_story_mut(state, story_uuid)?;
+    let before = story.clone();
+    let result = change(story);
+    let now = clock.now();
+    let entries = diff(&before, story, now);
+    if entries.is_empty() {
+        return Ok(result);
+    }
+    story.updated_at = now;
+    let status = (story.status != before.status).then_some((before.status, story.status));
+    let fields = entries.iter().map(|entry| entry.field.clone()).collect();
+    state.history.extend(entries);
+    state.emit(Event::StoryUpdated { story_uuid, fields });
+    if let Some((from, to)) = status {
+        state.emit(Event::StoryStatusChanged { story_uuid, from, to });
+    }
+    Ok(result)
+}
+
+/// Lists the UUIDs of the epics matching a predicate, for cascading updates.
+fn epic_uuids_where(state: &ClearTextDBState, predicate: impl Fn(&Epic) -> bool) -> Vec<Uuid> {
+    state.epics.iter().filter(|epic| predicate(epic)).map(|epic| epic.epic_uuid).collect()
+}
+
+/// Lists the UUIDs of the stories matching a predicate, for cascading updates.
+fn story_uuids_where(state: &ClearTextDBState, predicate: impl Fn(&Story) -> bool) -> Vec<Uuid> {
+    state.stories.iter().filter(|story| predicate(story)).map(|story| story.story_uuid).collect()
+}
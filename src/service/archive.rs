@@ -0,0 +1,112 @@
+//! Archive operations: hiding finished epics and stories from default views without deleting them.
+
+use super::{ServiceError, get_epic, require, story_uuids_where, update_epic, update_story};
+use crate::clock::Clock;
+use crate::db::ClearTextDBState;
+use crate::models::roles::Permission;
+use crate::models::{epics::Epic, stories::Story};
+use uuid::Uuid;
+
+/// Archives an epic together with all of its stories.
+///
+/// # Errors
+/// * `ServiceError::EpicNotFound` - If the epic does not exist.
+pub fn archive_epic(state: &mut ClearTextDBState, epic_uuid: Uuid, clock: &dyn Clock) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    set_epic_archived(state, epic_uuid, true, clock)
+}
+
+/// Restores an archived epic together with all of its stories.
+///
+/// # Errors
+/// * `ServiceError::EpicNotFound` - If the epic does not exist.
+pub fn unarchive_epic(state: &mut ClearTextDBState, epic_uuid: Uuid, clock: &dyn Clock) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    set_epic_archived(state, epic_uuid, false, clock)
+}
+
+/// Archives a single story.
+///
+/// # Errors
+/// * `ServiceError::StoryNotFound` - If the story does not exist.
+pub fn archive_story(state: &mut ClearTextDBState, story_uuid: Uuid, clock: &dyn Clock) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    update_story(state, story_uuid, clock, |story| story.archived = true)
+}
+
+/// Restores a single archived story.
+///
+/// # Errors
+/// * `ServiceError::StoryNotFound` - If the story does not exist.
+pub fn unarchive_story(state: &mut ClearTextDBState, story_uuid: Uuid, clock: &dyn Clock) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    update_story(state, story_uuid, clock, |story| story.archived = false)
+}
+
+/// Lists the epics shown in default views.
+#[must_use]
+pub fn active_epics(state: &ClearTextDBState) -> Vec<&Epic> {
+    state.epics.iter().filter(|epic| !epic.archived && !epic.is_deleted()).collect()
+}
+
+/// Lists the stories shown in default views.
+#[must_use]
+pub fn active_stories(state: &ClearTextDBState) -> Vec<&Story> {
+    state.stories.iter().filter(|story| !story.archived && !story.is_deleted()).collect()
+}
+
+/// Lists the archived epics, leaving out deleted ones.
+#[must_use]
+pub fn archived_epics(state: &ClearTextDBState) -> Vec<&Epic> {
+    state.epics.iter().filter(|epic| epic.archived && !epic.is_deleted()).collect()
+}
+
+/// Lists the archived stories, leaving out deleted ones.
+#[must_use]
+pub fn archived_stories(state: &ClearTextDBState) -> Vec<&Story> {
+    state.stories.iter().filter(|story| story.archived && !story.is_deleted()).collect()
+}
+
+/// Sets the archived flag on an epic and its stories.
+fn set_epic_archived(state: &mut ClearTextDBState, epic_uuid: Uuid, archived: bool, clock: &dyn Clock) -> Result<(), ServiceError> {
+    let child_uuids = get_epic(state, epic_uuid)?.story_uuids.clone();
+    update_epic(state, epic_uuid, clock, |epic| epic.archived = archived)?;
+    for story_uuid in story_uuids_where(state, |story| child_uuids.contains(&story.story_uuid) && story.archived != archived) {
+        update_story(state, story_uuid, clock, |story| story.archived = archived)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::users::User;
+
+    #[test]
+    fn test_archive_and_unarchive() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let mut epic = Epic::new("Epic".to_string(), String::new());
+        let epic_uuid = epic.epic_uuid;
+        let story = Story::new("Story".to_string(), String::new());
+        let loose = Story::new("Loose".to_string(), String::new());
+        let (story_uuid, loose_uuid) = (story.story_uuid, loose.story_uuid);
+        epic.story_uuids.push(story_uuid);
+        state.epics.push(epic);
+        state.stories.extend([story, loose]);
+
+        archive_epic(&mut state, epic_uuid, &SystemClock).unwrap();
+        assert!(active_epics(&state).is_empty());
+        assert_eq!(archived_stories(&state)[0].story_uuid, story_uuid);
+        assert_eq!(active_stories(&state)[0].story_uuid, loose_uuid);
+
+        archive_story(&mut state, loose_uuid, &SystemClock).unwrap();
+        unarchive_epic(&mut state, epic_uuid, &SystemClock).unwrap();
+        assert_eq!(archived_epics(&state).len(), 0);
+        assert_eq!(archived_stories(&state)[0].story_uuid, loose_uuid);
+        unarchive_story(&mut state, loose_uuid, &SystemClock).unwrap();
+        assert_eq!(active_stories(&state).len(), 2);
+        let missing = Uuid::new_v4();
+        assert_eq!(archive_epic(&mut state, missing, &SystemClock), Err(ServiceError::EpicNotFound(missing)));
+    }
+}
@@ -0,0 +1,75 @@
+//! Assignment operations: assigning stories to users and finding who is working on what.
+
+use super::{ServiceError, require, update_story};
+use crate::clock::Clock;
+use crate::db::ClearTextDBState;
+use crate::models::roles::Permission;
+use crate::models::{stories::Story, tasks::Task};
+use uuid::Uuid;
+
+/// Assigns a story to a user, or unassigns it with `None`.
+///
+/// # Errors
+/// * `ServiceError::StoryNotFound` - If the story does not exist.
+pub fn assign_story(state: &mut ClearTextDBState, story_uuid: Uuid, assignee: Option<Uuid>, clock: &dyn Clock) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    update_story(state, story_uuid, clock, |story| story.assignee = assignee)
+}
+
+/// Lists the stories assigned to a user.
+#[must_use]
+pub fn stories_assigned_to(state: &ClearTextDBState, user_uuid: Uuid) -> Vec<&Story> {
+    state.stories.iter().filter(|story| story.assignee == Some(user_uuid)).collect()
+}
+
+/// Lists the tasks assigned to a user.
+#[must_use]
+pub fn tasks_assigned_to(state: &ClearTextDBState, user_uuid: Uuid) -> Vec<&Task> {
+    state.tasks.iter().filter(|task| task.assignee == Some(user_uuid)).collect()
+}
+
+/// Lists the stories assigned to the database's own user ("assigned to me").
+#[must_use]
+pub fn stories_assigned_to_me(state: &ClearTextDBState) -> Vec<&Story> {
+    stories_assigned_to(state, state.user.user_uuid)
+}
+
+/// Lists the stories nobody is assigned to.
+#[must_use]
+pub fn unassigned_stories(state: &ClearTextDBState) -> Vec<&Story> {
+    state.stories.iter().filter(|story| story.assignee.is_none()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::users::User;
+
+    #[test]
+    fn test_assigned_to_me() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let me = state.user.user_uuid;
+        let someone_else = Uuid::new_v4();
+        let uuids: Vec<Uuid> = (0..3)
+            .map(|_| {
+                let story = Story::new("Story".to_string(), String::new());
+                let uuid = story.story_uuid;
+                state.stories.push(story);
+                uuid
+            })
+            .collect();
+        assign_story(&mut state, uuids[0], Some(me), &SystemClock).unwrap();
+        assign_story(&mut state, uuids[1], Some(someone_else), &SystemClock).unwrap();
+
+        let mine: Vec<Uuid> = stories_assigned_to_me(&state).iter().map(|story| story.story_uuid).collect();
+        assert_eq!(mine, vec![uuids[0]]);
+        assert_eq!(stories_assigned_to(&state, someone_else).len(), 1);
+        assert_eq!(unassigned_stories(&state).len(), 1);
+
+        assign_story(&mut state, uuids[0], None, &SystemClock).unwrap();
+        assert!(stories_assigned_to_me(&state).is_empty());
+        let missing = Uuid::new_v4();
+        assert_eq!(assign_story(&mut state, missing, Some(me), &SystemClock), Err(ServiceError::StoryNotFound(missing)));
+    }
+}
@@ -0,0 +1,210 @@
+//! Burndown operations: replaying status changes from the history to chart how a sprint's story
+//! points burned down, and how many points each sprint closed (its velocity).
+//!
+//! Only stories estimated in points count; hour estimates are left out. Closing a sprint removes
+//! its unfinished stories, so a closed sprint's burndown only covers the stories it finished.
+
+use super::{ServiceError, sprints::get_sprint};
+use crate::clock::Clock;
+use crate::db::ClearTextDBState;
+use crate::models::{
+    Status,
+    estimates::Estimate,
+    sprints::{BurndownPoint, SprintVelocity},
+    stories::Story,
+};
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use std::fmt::Write;
+use uuid::Uuid;
+
+/// # Burndown
+/// Charts the points remaining in a sprint at the end of each of its days, up to today.
+///
+/// # Arguments
+/// * `state` - The database state to read.
+/// * `sprint_uuid` - The sprint to chart.
+/// * `clock` - Gives today's date; days after it are left out.
+///
+/// # Returns
+/// * `Result<Vec<BurndownPoint>, ServiceError>` - One point per day, oldest first. Empty if the
+///   sprint hasn't started.
+/// # Errors
+/// * `ServiceError::SprintNotFound` - If the sprint does not exist.
+pub fn burndown(state: &ClearTextDBState, sprint_uuid: Uuid, clock: &dyn Clock) -> Result<Vec<BurndownPoint>, ServiceError> {
+    let sprint = get_sprint(state, sprint_uuid)?;
+    let last_day = sprint.end.min(clock.now().date_naive());
+    let stories = pointed_stories(state, &sprint.story_uuids);
+    Ok(sprint
+        .start
+        .iter_days()
+        .take_while(|date| *date <= last_day)
+        .map(|date| {
+            let end_of_day = end_of(date);
+            let remaining_points = stories.iter().filter(|(story, _)| !done_at(state, story, end_of_day)).map(|(_, points)| points).sum();
+            BurndownPoint { date, remaining_points }
+        })
+        .collect())
+}
+
+/// # Velocity
+/// Counts the points each closed sprint got done by its last day.
+///
+/// # Returns
+/// * `Vec<SprintVelocity>` - One entry per closed sprint, oldest first.
+#[must_use]
+pub fn velocity(state: &ClearTextDBState) -> Vec<SprintVelocity> {
+    let mut sprints: Vec<_> = state.sprints.iter().filter(|sprint| sprint.closed).collect();
+    sprints.sort_by_key(|sprint| (sprint.end, sprint.start));
+    sprints
+        .into_iter()
+        .map(|sprint| {
+            let end_of_sprint = end_of(sprint.end);
+            let completed_points = pointed_stories(state, &sprint.story_uuids)
+                .into_iter()
+                .filter(|(story, _)| done_at(state, story, end_of_sprint))
+                .map(|(_, points)| points)
+                .sum();
+            SprintVelocity { sprint_uuid: sprint.sprint_uuid, name: sprint.name.clone(), completed_points }
+        })
+        .collect()
+}
+
+/// # Rolling Velocity
+/// Averages the velocity of the most recent sprints.
+///
+/// # Arguments
+/// * `velocities` - Sprint velocities, oldest first, as returned by [`velocity`].
+/// * `window` - How many of the most recent sprints to average.
+///
+/// # Returns
+/// * `Option<u32>` - The average, rounded down, or `None` if there are no sprints to average.
+///
+/// # Examples
+/// ```
+/// use ironyyy::models::sprints::SprintVelocity;
+/// use ironyyy::service::burndown::rolling_velocity;
+/// let velocities: Vec<SprintVelocity> = [8, 10, 14].into_iter().map(|completed_points| SprintVelocity { completed_points, ..Default::default() }).collect();
+/// assert_eq!(rolling_velocity(&velocities, 2), Some(12));
+/// assert_eq!(rolling_velocity(&[], 3), None);
+/// ```
+#[must_use]
+pub fn rolling_velocity(velocities: &[SprintVelocity], window: usize) -> Option<u32> {
+    let recent = &velocities[velocities.len().saturating_sub(window)..];
+    let count = u32::try_from(recent.len()).ok().filter(|count| *count > 0)?;
+    Some(recent.iter().map(|velocity| velocity.completed_points).sum::<u32>() / count)
+}
+
+/// Exports a burndown as CSV, with a `date,remaining_points` header.
+#[must_use]
+pub fn burndown_csv(points: &[BurndownPoint]) -> String {
+    let mut csv = String::from("date,remaining_points\n");
+    for point in points {
+        let _ = writeln!(csv, "{},{}", point.date, point.remaining_points);
+    }
+    csv
+}
+
+/// Exports sprint velocities as CSV, with a `sprint,completed_points` header.
+#[must_use]
+pub fn velocity_csv(velocities: &[SprintVelocity]) -> String {
+    let mut csv = String::from("sprint,completed_points\n");
+    for velocity in velocities {
+        let _ = writeln!(csv, "{},{}", csv_field(&velocity.name), velocity.completed_points);
+    }
+    csv
+}
+
+/// Quotes a CSV field if it contains a comma, quote, or line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// The stories among `story_uuids` estimated in points, with their points. Stories in the trash
+/// are left out.
+fn pointed_stories<'a>(state: &'a ClearTextDBState, story_uuids: &[Uuid]) -> Vec<(&'a Story, u32)> {
+    state
+        .stories
+        .iter()
+        .filter(|story| story_uuids.contains(&story.story_uuid) && !story.is_deleted())
+        .filter_map(|story| match story.estimate {
+            Some(Estimate::Points(points)) => Some((story, points)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The first instant after `date`.
+fn end_of(date: NaiveDate) -> DateTime<Utc> {
+    date.succ_opt().unwrap_or(date).and_time(NaiveTime::MIN).and_utc()
+}
+
+/// Whether a story was done just before `at`, according to its status history.
+fn done_at(state: &ClearTextDBState, story: &Story, at: DateTime<Utc>) -> bool {
+    let changes: Vec<_> = state.history.iter().filter(|entry| entry.item_uuid == story.story_uuid && entry.field == "status").collect();
+    let status = match changes.iter().rev().find(|entry| entry.timestamp < at) {
+        Some(entry) => parse_status(state, &entry.new_value),
+        None => match changes.first() {
+            Some(entry) => parse_status(state, &entry.old_value),
+            None => Some(story.status),
+        },
+    };
+    status.is_some_and(|status| status.is_done(&state.statuses))
+}
+
+/// Reads back a status as written to the history.
+fn parse_status(state: &ClearTextDBState, value: &str) -> Option<Status> {
+    Status::BUILTINS
+        .into_iter()
+        .chain(state.statuses.iter().map(|definition| definition.status))
+        .find(|status| format!("{status:?}") == value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+    use crate::service::{
+        epics::create_epic,
+        estimates::set_story_estimate,
+        sprints::{assign_story_to_sprint, close_sprint, create_sprint},
+        stories::{create_story, set_story_status},
+    };
+    use crate::users::User;
+
+    fn at(day: u32, hour: u32) -> FixedClock {
+        FixedClock::new(NaiveDate::from_ymd_opt(2024, 3, day).unwrap().and_hms_opt(hour, 0, 0).unwrap().and_utc())
+    }
+
+    #[test]
+    fn test_burndown_and_velocity() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let epic_uuid = create_epic(&mut state, "Epic", String::new(), &at(1, 9)).unwrap();
+        let sprint_uuid = create_sprint(&mut state, "Sprint 1, March".to_string(), at(1, 0).now().date_naive(), at(4, 0).now().date_naive()).unwrap();
+        for (title, points) in [("Login", 3), ("Logout", 5), ("Docs", 0)] {
+            let story_uuid = create_story(&mut state, epic_uuid, title, String::new(), &at(1, 9)).unwrap();
+            let estimate = if points > 0 { Estimate::Points(points) } else { Estimate::Hours(4) };
+            set_story_estimate(&mut state, story_uuid, Some(estimate), &at(1, 9)).unwrap();
+            assign_story_to_sprint(&mut state, story_uuid, sprint_uuid).unwrap();
+        }
+        let (login, logout) = (state.stories[0].story_uuid, state.stories[1].story_uuid);
+        set_story_status(&mut state, login, Status::Closed, &at(2, 15)).unwrap();
+        set_story_status(&mut state, logout, Status::InReview, &at(3, 10)).unwrap();
+
+        let remaining = |state: &ClearTextDBState, clock: &FixedClock| burndown(state, sprint_uuid, clock).unwrap().iter().map(|point| point.remaining_points).collect::<Vec<_>>();
+        assert_eq!(remaining(&state, &at(3, 12)), vec![8, 5, 5]);
+        set_story_status(&mut state, logout, Status::Closed, &at(9, 10)).unwrap();
+        assert_eq!(remaining(&state, &at(9, 12)), vec![8, 5, 5, 5]);
+        assert_eq!(burndown_csv(&burndown(&state, sprint_uuid, &at(1, 12)).unwrap()), "date,remaining_points\n2024-03-01,8\n");
+
+        assert!(velocity(&state).is_empty());
+        close_sprint(&mut state, sprint_uuid, None).unwrap();
+        let velocities = velocity(&state);
+        assert_eq!(velocities[0].completed_points, 3);
+        assert_eq!(rolling_velocity(&velocities, 3), Some(3));
+        assert_eq!(velocity_csv(&velocities), "sprint,completed_points\n\"Sprint 1, March\",3\n");
+    }
+}
@@ -0,0 +1,103 @@
+//! Calendar operations: laying out months and weeks, and finding the epics and stories due on each
+//! day, for the calendar page. An epic's due date is shown as a milestone.
+
+use super::{archive::{active_epics, active_stories}, references::ItemRef};
+use crate::db::ClearTextDBState;
+use crate::models::Scheduled;
+use chrono::{Datelike, Duration, NaiveDate};
+
+/// # Calendar Entry struct
+/// An epic or story on its due date.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct CalendarEntry {
+    /// The item.
+    pub item: ItemRef,
+    /// The item's short ID and title, e.g. `STORY-12 Login page`.
+    pub label: String,
+    /// When the item is due.
+    pub due_date: NaiveDate,
+    /// Whether the item is a milestone, i.e. an epic.
+    pub milestone: bool,
+}
+
+/// The first and last day of the month holding `date`.
+///
+/// # Examples
+/// ```rust
+/// use chrono::NaiveDate;
+/// use ironyyy::service::calendar::month_range;
+/// let date = |day| NaiveDate::from_ymd_opt(2024, 2, day).unwrap();
+/// assert_eq!(month_range(date(14)), (date(1), date(29)));
+/// ```
+#[must_use]
+pub fn month_range(date: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let first = date.with_day(1).unwrap_or(date);
+    let next = first.checked_add_months(chrono::Months::new(1)).unwrap_or(first);
+    (first, next.pred_opt().unwrap_or(first))
+}
+
+/// The Monday and Sunday of the week holding `date`.
+#[must_use]
+pub fn week_range(date: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let monday = date - Duration::days(i64::from(date.weekday().num_days_from_monday()));
+    (monday, monday + Duration::days(6))
+}
+
+/// # Due Between
+/// Lists the epics and stories that aren't archived or deleted and are due from `first` to `last`,
+/// done or not.
+///
+/// # Returns
+/// * `Vec<CalendarEntry>` - The entries, earliest due date first, milestones before stories on the
+///   same day.
+#[must_use]
+pub fn due_between(state: &ClearTextDBState, first: NaiveDate, last: NaiveDate) -> Vec<CalendarEntry> {
+    let entry = |item: ItemRef, label: String, scheduled: &dyn Scheduled| {
+        let due_date = scheduled.due_date().filter(|due| (first..=last).contains(due))?;
+        Some(CalendarEntry { item, label, due_date, milestone: matches!(item, ItemRef::Epic(_)) })
+    };
+    let epics = active_epics(state)
+        .into_iter()
+        .filter_map(|epic| entry(ItemRef::Epic(epic.epic_uuid), format!("{} {}", epic.key(), epic.title), epic));
+    let stories = active_stories(state)
+        .into_iter()
+        .filter_map(|story| entry(ItemRef::Story(story.story_uuid), format!("{} {}", story.key(), story.title), story));
+    let mut found: Vec<CalendarEntry> = epics.chain(stories).collect();
+    found.sort_by_key(|entry| entry.due_date);
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::service::{
+        archive::archive_story,
+        epics::create_epic,
+        schedule::{set_epic_dates, set_story_dates},
+        stories::create_story,
+    };
+    use crate::users::User;
+
+    #[test]
+    fn test_due_between() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let date = |day| NaiveDate::from_ymd_opt(2025, 3, day).unwrap();
+        let launch = create_epic(&mut state, "Launch", String::new(), &SystemClock).unwrap();
+        set_epic_dates(&mut state, launch, None, Some(date(14)), &SystemClock).unwrap();
+        let login = create_story(&mut state, launch, "Login", String::new(), &SystemClock).unwrap();
+        let old = create_story(&mut state, launch, "Old", String::new(), &SystemClock).unwrap();
+        let later = create_story(&mut state, launch, "Later", String::new(), &SystemClock).unwrap();
+        set_story_dates(&mut state, login, None, Some(date(14)), &SystemClock).unwrap();
+        set_story_dates(&mut state, old, None, Some(date(3)), &SystemClock).unwrap();
+        set_story_dates(&mut state, later, None, NaiveDate::from_ymd_opt(2025, 4, 1), &SystemClock).unwrap();
+        archive_story(&mut state, old, &SystemClock).unwrap();
+
+        let (first, last) = month_range(date(20));
+        let found = due_between(&state, first, last);
+        assert_eq!(found.iter().map(|entry| (entry.item, entry.milestone)).collect::<Vec<_>>(), [(ItemRef::Epic(launch), true), (ItemRef::Story(login), false)]);
+        assert_eq!(found[1].label, "STORY-1 Login");
+        assert_eq!(week_range(date(14)), (date(10), date(16)));
+        assert!(due_between(&state, date(15), date(31)).is_empty());
+    }
+}
@@ -0,0 +1,114 @@
+//! Checklist operations: editing and ticking off a story's acceptance criteria.
+
+use super::{ServiceError, get_story, require, update_story};
+use crate::clock::Clock;
+use crate::db::ClearTextDBState;
+use crate::models::roles::Permission;
+use crate::models::checklists::{ChecklistItem, completion_percent};
+use uuid::Uuid;
+
+/// Appends an unchecked item to a story's checklist.
+///
+/// # Returns
+/// * `Result<usize, ServiceError>` - The index of the new item.
+/// # Errors
+/// * `ServiceError::StoryNotFound` - If the story does not exist.
+pub fn add_checklist_item(state: &mut ClearTextDBState, story_uuid: Uuid, text: String, clock: &dyn Clock) -> Result<usize, ServiceError> {
+    require(state, Permission::Edit)?;
+    update_story(state, story_uuid, clock, |story| {
+        story.checklist.push(ChecklistItem::new(text));
+        story.checklist.len() - 1
+    })
+}
+
+/// Flips a checklist item between checked and unchecked.
+///
+/// # Returns
+/// * `Result<bool, ServiceError>` - Whether the item is now checked.
+/// # Errors
+/// * `ServiceError::StoryNotFound` - If the story does not exist.
+/// * `ServiceError::ChecklistItemNotFound` - If there is no item at `index`.
+pub fn toggle_checklist_item(state: &mut ClearTextDBState, story_uuid: Uuid, index: usize, clock: &dyn Clock) -> Result<bool, ServiceError> {
+    require(state, Permission::Edit)?;
+    ensure_item_exists(state, story_uuid, index)?;
+    update_story(state, story_uuid, clock, |story| {
+        let item = &mut story.checklist[index];
+        item.checked = !item.checked;
+        item.checked
+    })
+}
+
+/// Changes the text of a checklist item.
+///
+/// # Errors
+/// * `ServiceError::StoryNotFound` - If the story does not exist.
+/// * `ServiceError::ChecklistItemNotFound` - If there is no item at `index`.
+pub fn edit_checklist_item(
+    state: &mut ClearTextDBState,
+    story_uuid: Uuid,
+    index: usize,
+    text: String,
+    clock: &dyn Clock,
+) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    ensure_item_exists(state, story_uuid, index)?;
+    update_story(state, story_uuid, clock, |story| story.checklist[index].text = text)
+}
+
+/// Removes a checklist item.
+///
+/// # Errors
+/// * `ServiceError::StoryNotFound` - If the story does not exist.
+/// * `ServiceError::ChecklistItemNotFound` - If there is no item at `index`.
+pub fn remove_checklist_item(state: &mut ClearTextDBState, story_uuid: Uuid, index: usize, clock: &dyn Clock) -> Result<ChecklistItem, ServiceError> {
+    require(state, Permission::Edit)?;
+    ensure_item_exists(state, story_uuid, index)?;
+    update_story(state, story_uuid, clock, |story| story.checklist.remove(index))
+}
+
+/// Returns the percentage of a story's checklist that is checked, or `None` if it has no checklist.
+///
+/// # Errors
+/// * `ServiceError::StoryNotFound` - If the story does not exist.
+pub fn checklist_completion(state: &ClearTextDBState, story_uuid: Uuid) -> Result<Option<u8>, ServiceError> {
+    Ok(completion_percent(&get_story(state, story_uuid)?.checklist))
+}
+
+/// Fails if the story has no checklist item at `index`.
+fn ensure_item_exists(state: &ClearTextDBState, story_uuid: Uuid, index: usize) -> Result<(), ServiceError> {
+    if index >= get_story(state, story_uuid)?.checklist.len() {
+        return Err(ServiceError::ChecklistItemNotFound(index));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::models::{checklists::completion_summary, stories::Story};
+    use crate::users::User;
+
+    #[test]
+    fn test_checklist_editing() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let story = Story::new("Story".to_string(), String::new());
+        let story_uuid = story.story_uuid;
+        state.stories.push(story);
+        assert_eq!(checklist_completion(&state, story_uuid).unwrap(), None);
+
+        let first = add_checklist_item(&mut state, story_uuid, "Works offline".to_string(), &SystemClock).unwrap();
+        let second = add_checklist_item(&mut state, story_uuid, "Has docs".to_string(), &SystemClock).unwrap();
+        assert!(toggle_checklist_item(&mut state, story_uuid, first, &SystemClock).unwrap());
+        assert_eq!(checklist_completion(&state, story_uuid).unwrap(), Some(50));
+        assert_eq!(completion_summary(&state.stories[0].checklist).unwrap(), "1/2 (50%)");
+
+        edit_checklist_item(&mut state, story_uuid, second, "Has user docs".to_string(), &SystemClock).unwrap();
+        assert_eq!(state.stories[0].checklist[second].to_string(), "[ ] Has user docs");
+        assert_eq!(remove_checklist_item(&mut state, story_uuid, first, &SystemClock).unwrap().text, "Works offline");
+        assert_eq!(
+            toggle_checklist_item(&mut state, story_uuid, 1, &SystemClock),
+            Err(ServiceError::ChecklistItemNotFound(1))
+        );
+    }
+}
@@ -0,0 +1,177 @@
+//! Custom field operations: defining a database's custom fields, setting their values on epics and
+//! stories, and filtering by them.
+
+use super::{ServiceError, epic_uuids_where, get_epic, get_story, require, story_uuids_where, update_epic, update_story};
+use crate::clock::Clock;
+use crate::db::ClearTextDBState;
+use crate::models::roles::Permission;
+use crate::models::custom_fields::{FieldDefinition, FieldId, FieldKind, FieldValue};
+use crate::models::validation::validate_unique_name;
+use crate::models::{epics::Epic, stories::Story};
+use uuid::Uuid;
+
+/// Defines a new custom field.
+///
+/// Field names are unique within a database, ignoring case.
+/// # Returns
+/// * `Result<FieldId, ServiceError>` - The ID of the new field.
+/// # Errors
+/// * `ServiceError::Validation` - If a field with that name already exists.
+pub fn define_field(state: &mut ClearTextDBState, name: String, kind: FieldKind) -> Result<FieldId, ServiceError> {
+    require(state, Permission::Edit)?;
+    validate_unique_name(&name, state.custom_fields.iter().map(|field| field.name.as_str()))?;
+    let field = FieldDefinition::new(name, kind);
+    let field_id = field.field_id;
+    state.mark_dirty();
+    state.custom_fields.push(field);
+    Ok(field_id)
+}
+
+/// Looks up a custom field definition by ID.
+///
+/// # Errors
+/// * `ServiceError::FieldNotFound` - If no such field exists.
+pub fn get_field(state: &ClearTextDBState, field_id: FieldId) -> Result<&FieldDefinition, ServiceError> {
+    state
+        .custom_fields
+        .iter()
+        .find(|field| field.field_id == field_id)
+        .ok_or(ServiceError::FieldNotFound(field_id))
+}
+
+/// Looks up a custom field definition by name, ignoring case.
+#[must_use]
+pub fn find_field_by_name<'a>(state: &'a ClearTextDBState, name: &str) -> Option<&'a FieldDefinition> {
+    state.custom_fields.iter().find(|field| field.name.eq_ignore_ascii_case(name))
+}
+
+/// Deletes a custom field and clears its values from every epic and story.
+///
+/// # Returns
+/// * `Result<FieldDefinition, ServiceError>` - The removed definition.
+/// # Errors
+/// * `ServiceError::FieldNotFound` - If the field does not exist.
+pub fn delete_field(state: &mut ClearTextDBState, field_id: FieldId, clock: &dyn Clock) -> Result<FieldDefinition, ServiceError> {
+    require(state, Permission::Edit)?;
+    let index = state
+        .custom_fields
+        .iter()
+        .position(|field| field.field_id == field_id)
+        .ok_or(ServiceError::FieldNotFound(field_id))?;
+    for epic_uuid in epic_uuids_where(state, |epic| epic.custom_fields.contains_key(&field_id)) {
+        update_epic(state, epic_uuid, clock, |epic| epic.custom_fields.remove(&field_id))?;
+    }
+    for story_uuid in story_uuids_where(state, |story| story.custom_fields.contains_key(&field_id)) {
+        update_story(state, story_uuid, clock, |story| story.custom_fields.remove(&field_id))?;
+    }
+    state.mark_dirty();
+    Ok(state.custom_fields.remove(index))
+}
+
+/// Sets (or, with `None`, clears) a custom field on an epic.
+///
+/// # Errors
+/// * `ServiceError::FieldNotFound` - If the field does not exist.
+/// * `ServiceError::FieldTypeMismatch` - If the value doesn't fit the field's kind.
+/// * `ServiceError::EpicNotFound` - If the epic does not exist.
+pub fn set_epic_field(state: &mut ClearTextDBState, epic_uuid: Uuid, field_id: FieldId, value: Option<FieldValue>, clock: &dyn Clock) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    check_value(state, field_id, value.as_ref())?;
+    update_epic(state, epic_uuid, clock, |epic| match value {
+        Some(value) => epic.custom_fields.insert(field_id, value),
+        None => epic.custom_fields.remove(&field_id),
+    })?;
+    Ok(())
+}
+
+/// Sets (or, with `None`, clears) a custom field on a story.
+///
+/// # Errors
+/// * `ServiceError::FieldNotFound` - If the field does not exist.
+/// * `ServiceError::FieldTypeMismatch` - If the value doesn't fit the field's kind.
+/// * `ServiceError::StoryNotFound` - If the story does not exist.
+pub fn set_story_field(state: &mut ClearTextDBState, story_uuid: Uuid, field_id: FieldId, value: Option<FieldValue>, clock: &dyn Clock) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    check_value(state, field_id, value.as_ref())?;
+    update_story(state, story_uuid, clock, |story| match value {
+        Some(value) => story.custom_fields.insert(field_id, value),
+        None => story.custom_fields.remove(&field_id),
+    })?;
+    Ok(())
+}
+
+/// Lists the epics whose value for a custom field matches a predicate.
+#[must_use]
+pub fn epics_where_field(state: &ClearTextDBState, field_id: FieldId, predicate: impl Fn(&FieldValue) -> bool) -> Vec<&Epic> {
+    state.epics.iter().filter(|epic| !epic.is_deleted() && epic.custom_fields.get(&field_id).is_some_and(&predicate)).collect()
+}
+
+/// Lists the stories whose value for a custom field matches a predicate.
+#[must_use]
+pub fn stories_where_field(state: &ClearTextDBState, field_id: FieldId, predicate: impl Fn(&FieldValue) -> bool) -> Vec<&Story> {
+    state.stories.iter().filter(|story| !story.is_deleted() && story.custom_fields.get(&field_id).is_some_and(&predicate)).collect()
+}
+
+/// Renders an epic's or story's custom fields as `name: value` lines, in definition order.
+///
+/// # Errors
+/// * `ServiceError::StoryNotFound` - If no epic or story has the given UUID.
+pub fn render_fields(state: &ClearTextDBState, item_uuid: Uuid) -> Result<Vec<String>, ServiceError> {
+    let values = match get_epic(state, item_uuid) {
+        Ok(epic) => &epic.custom_fields,
+        Err(_) => &get_story(state, item_uuid)?.custom_fields,
+    };
+    Ok(state
+        .custom_fields
+        .iter()
+        .filter_map(|field| values.get(&field.field_id).map(|value| format!("{}: {value}", field.name)))
+        .collect())
+}
+
+/// Fails if the field doesn't exist or the value doesn't fit it.
+fn check_value(state: &ClearTextDBState, field_id: FieldId, value: Option<&FieldValue>) -> Result<(), ServiceError> {
+    let field = get_field(state, field_id)?;
+    match value {
+        Some(value) if !value.fits(&field.kind) => Err(ServiceError::FieldTypeMismatch(field.name.clone())),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::models::validation::ValidationError;
+    use crate::users::User;
+
+    #[test]
+    fn test_define_set_filter_delete() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let story = Story::new("Story".to_string(), String::new());
+        let story_uuid = story.story_uuid;
+        state.stories.push(story);
+
+        let options = vec!["Low".to_string(), "High".to_string()];
+        let priority = define_field(&mut state, "Priority".to_string(), FieldKind::Enum(options)).unwrap();
+        let points = define_field(&mut state, "Customer count".to_string(), FieldKind::Number).unwrap();
+        assert_eq!(
+            define_field(&mut state, "priority".to_string(), FieldKind::Text),
+            Err(ValidationError::DuplicateName("priority".to_string()).into())
+        );
+
+        set_story_field(&mut state, story_uuid, priority, Some(FieldValue::Enum("High".to_string())), &SystemClock).unwrap();
+        set_story_field(&mut state, story_uuid, points, Some(FieldValue::Number(12)), &SystemClock).unwrap();
+        assert_eq!(
+            set_story_field(&mut state, story_uuid, points, Some(FieldValue::Text("many".to_string())), &SystemClock),
+            Err(ServiceError::FieldTypeMismatch("Customer count".to_string()))
+        );
+        assert_eq!(render_fields(&state, story_uuid).unwrap(), ["Priority: High", "Customer count: 12"]);
+        assert_eq!(stories_where_field(&state, points, |value| *value > FieldValue::Number(10)).len(), 1);
+
+        delete_field(&mut state, priority, &SystemClock).unwrap();
+        assert_eq!(state.stories[0].custom_fields.len(), 1);
+        set_story_field(&mut state, story_uuid, points, None, &SystemClock).unwrap();
+        assert!(state.stories[0].custom_fields.is_empty());
+        assert!(epics_where_field(&state, points, |_| true).is_empty());
+    }
+}
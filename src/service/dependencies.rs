@@ -0,0 +1,110 @@
+//! Dependency operations: recording which stories block which, without allowing cycles.
+
+use super::{ServiceError, get_story, require, update_story};
+use crate::clock::Clock;
+use crate::db::ClearTextDBState;
+use crate::models::roles::Permission;
+use crate::models::stories::Story;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+/// Records that `story_uuid` is blocked by `blocker_uuid`. Adding an existing blocker has no effect.
+///
+/// # Errors
+/// * `ServiceError::StoryNotFound` - If either story does not exist.
+/// * `ServiceError::DependencyCycle` - If `blocker_uuid` is (directly or indirectly) blocked by
+///   `story_uuid`, or the two are the same story.
+pub fn add_blocker(state: &mut ClearTextDBState, story_uuid: Uuid, blocker_uuid: Uuid, clock: &dyn Clock) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    get_story(state, story_uuid)?;
+    get_story(state, blocker_uuid)?;
+    if depends_on(state, blocker_uuid, story_uuid) {
+        return Err(ServiceError::DependencyCycle);
+    }
+    update_story(state, story_uuid, clock, |story| {
+        if !story.blocked_by.contains(&blocker_uuid) {
+            story.blocked_by.push(blocker_uuid);
+        }
+    })
+}
+
+/// Removes `blocker_uuid` from the stories blocking `story_uuid`.
+///
+/// # Errors
+/// * `ServiceError::StoryNotFound` - If the story does not exist.
+pub fn remove_blocker(state: &mut ClearTextDBState, story_uuid: Uuid, blocker_uuid: Uuid, clock: &dyn Clock) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    update_story(state, story_uuid, clock, |story| story.blocked_by.retain(|uuid| *uuid != blocker_uuid))
+}
+
+/// Lists a story's direct blockers that aren't done yet.
+///
+/// Blockers that no longer exist are ignored.
+/// # Errors
+/// * `ServiceError::StoryNotFound` - If the story does not exist.
+pub fn open_blockers(state: &ClearTextDBState, story_uuid: Uuid) -> Result<Vec<&Story>, ServiceError> {
+    Ok(get_story(state, story_uuid)?
+        .blocked_by
+        .iter()
+        .filter_map(|blocker_uuid| get_story(state, *blocker_uuid).ok())
+        .filter(|blocker| !blocker.status.is_done(&state.statuses))
+        .collect())
+}
+
+/// Lists the stories that `story_uuid` directly blocks.
+#[must_use]
+pub fn blocking(state: &ClearTextDBState, story_uuid: Uuid) -> Vec<&Story> {
+    state.stories.iter().filter(|story| story.blocked_by.contains(&story_uuid)).collect()
+}
+
+/// Whether `from` is blocked, directly or through a chain of blockers, by `to` (or is `to`).
+fn depends_on(state: &ClearTextDBState, from: Uuid, to: Uuid) -> bool {
+    let mut seen = HashSet::new();
+    let mut pending = vec![from];
+    while let Some(current) = pending.pop() {
+        if current == to {
+            return true;
+        }
+        if seen.insert(current)
+            && let Ok(story) = get_story(state, current)
+        {
+            pending.extend(&story.blocked_by);
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::models::Status;
+    use crate::service::stories::set_story_status;
+    use crate::users::User;
+
+    #[test]
+    fn test_cycles_and_closing() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let [a, b, c]: [Uuid; 3] = std::array::from_fn(|_| {
+            let story = Story::new("Story".to_string(), String::new());
+            let uuid = story.story_uuid;
+            state.stories.push(story);
+            uuid
+        });
+
+        add_blocker(&mut state, a, b, &SystemClock).unwrap();
+        add_blocker(&mut state, b, c, &SystemClock).unwrap();
+        assert_eq!(add_blocker(&mut state, c, a, &SystemClock), Err(ServiceError::DependencyCycle));
+        assert_eq!(add_blocker(&mut state, a, a, &SystemClock), Err(ServiceError::DependencyCycle));
+        assert_eq!(blocking(&state, b).len(), 1);
+
+        assert_eq!(set_story_status(&mut state, a, Status::Closed, &SystemClock), Err(ServiceError::BlockedBy(vec![b])));
+        set_story_status(&mut state, c, Status::Closed, &SystemClock).unwrap();
+        set_story_status(&mut state, b, Status::Closed, &SystemClock).unwrap();
+        set_story_status(&mut state, a, Status::Closed, &SystemClock).unwrap();
+        assert!(open_blockers(&state, a).unwrap().is_empty());
+
+        remove_blocker(&mut state, a, b, &SystemClock).unwrap();
+        add_blocker(&mut state, c, a, &SystemClock).unwrap();
+    }
+}
@@ -0,0 +1,427 @@
+//! Epic operations.
+
+use super::{
+    ServiceError, check_transition, epic_uuids_where, get_epic, get_story,
+    projects::{move_epic_to_project, project_of},
+    ranking::next_epic_rank,
+    require,
+    stories::delete_story,
+    update_epic,
+};
+use crate::clock::Clock;
+use crate::db::ClearTextDBState;
+use crate::models::roles::Permission;
+use crate::models::validation::{validate_color, validate_description, validate_title};
+use crate::models::{Status, epics::{Epic, Progress}, events::Event, stories::Story, workflow::TransitionAction};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// # Delete Policy enum
+/// What happens to an epic's stories when the epic is deleted.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum DeletePolicy {
+    /// Refuse to delete an epic that still has stories.
+    #[default]
+    Refuse,
+    /// Keep the stories in the backlog, outside any epic.
+    Detach,
+    /// Delete the stories too (see [`delete_story`]).
+    Cascade,
+}
+
+/// Creates a new epic in the current project, numbered, stamped with the current time, and ranked
+/// after all existing epics.
+///
+/// # Returns
+/// * `Result<Uuid, ServiceError>` - The UUID of the new epic.
+/// # Errors
+/// * `ServiceError::Validation` - If the title or description is rejected.
+pub fn create_epic(state: &mut ClearTextDBState, title: &str, description: String, clock: &dyn Clock) -> Result<Uuid, ServiceError> {
+    require(state, Permission::Edit)?;
+    validate_description(&description)?;
+    let mut epic = Epic::new(validate_title(title)?, description);
+    epic.created_at = clock.now();
+    epic.updated_at = epic.created_at;
+    epic.rank = next_epic_rank(state);
+    epic.number = state.next_epic_number;
+    state.next_epic_number += 1;
+    let epic_uuid = epic.epic_uuid;
+    state.mark_dirty();
+    state.epics.push(epic);
+    state.emit(Event::EpicCreated { epic_uuid });
+    if let Some(project_uuid) = state.current_project {
+        move_epic_to_project(state, epic_uuid, Some(project_uuid))?;
+    }
+    Ok(epic_uuid)
+}
+
+/// Changes an epic's title, trimming it.
+///
+/// # Errors
+/// * `ServiceError::Validation` - If the title is blank or too long.
+/// * `ServiceError::EpicNotFound` - If the epic does not exist.
+pub fn rename_epic(state: &mut ClearTextDBState, epic_uuid: Uuid, title: &str, clock: &dyn Clock) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    let title = validate_title(title)?;
+    update_epic(state, epic_uuid, clock, |epic| epic.title = title)
+}
+
+/// Changes an epic's description.
+///
+/// # Errors
+/// * `ServiceError::Validation` - If the description is too long.
+/// * `ServiceError::EpicNotFound` - If the epic does not exist.
+pub fn set_epic_description(state: &mut ClearTextDBState, epic_uuid: Uuid, description: String, clock: &dyn Clock) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    validate_description(&description)?;
+    update_epic(state, epic_uuid, clock, |epic| epic.description = description)
+}
+
+/// Sets (or, with `None`, clears) an epic's display color and icon.
+///
+/// # Errors
+/// * `ServiceError::Validation` - If the color isn't written as `#rrggbb`.
+/// * `ServiceError::EpicNotFound` - If the epic does not exist.
+pub fn set_epic_appearance(
+    state: &mut ClearTextDBState,
+    epic_uuid: Uuid,
+    color: Option<String>,
+    icon: Option<String>,
+    clock: &dyn Clock,
+) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    if let Some(color) = &color {
+        validate_color(color)?;
+    }
+    let icon = icon.map(|icon| icon.trim().to_string()).filter(|icon| !icon.is_empty());
+    update_epic(state, epic_uuid, clock, |epic| {
+        epic.color = color;
+        epic.icon = icon;
+    })
+}
+
+/// Changes an epic's status with an ordinary move.
+///
+/// # Errors
+/// * `ServiceError::EpicNotFound` - If the epic does not exist.
+/// * `ServiceError::StatusNotFound` - If `status` is a custom status the database doesn't define.
+/// * `ServiceError::ActionRequired` / `ServiceError::InvalidTransition` - If the workflow refuses the move.
+pub fn set_epic_status(state: &mut ClearTextDBState, epic_uuid: Uuid, status: Status, clock: &dyn Clock) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    transition_epic(state, epic_uuid, status, TransitionAction::Move, clock)
+}
+
+/// Reopens a finished epic, moving it to `status`.
+///
+/// # Errors
+/// Same as [`set_epic_status`].
+pub fn reopen_epic(state: &mut ClearTextDBState, epic_uuid: Uuid, status: Status, clock: &dyn Clock) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    transition_epic(state, epic_uuid, status, TransitionAction::Reopen, clock)
+}
+
+/// Moves an epic to `status` with the given action, enforcing the workflow.
+///
+/// # Errors
+/// Same as [`set_epic_status`].
+pub fn transition_epic(state: &mut ClearTextDBState, epic_uuid: Uuid, status: Status, action: TransitionAction, clock: &dyn Clock) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    let current = get_epic(state, epic_uuid)?.status;
+    check_transition(state, current, status, action)?;
+    update_epic(state, epic_uuid, clock, |epic| epic.status = status)
+}
+
+/// Computes how many of an epic's stories are done.
+///
+/// # Errors
+/// * `ServiceError::EpicNotFound` - If the epic does not exist.
+pub fn epic_progress(state: &ClearTextDBState, epic_uuid: Uuid) -> Result<Progress, ServiceError> {
+    Ok(get_epic(state, epic_uuid)?.progress(&state.stories, &state.statuses))
+}
+
+/// Lists the epics that are still open although all of their stories are done, so the user can be
+/// prompted to close them.
+#[must_use]
+pub fn epics_ready_to_close(state: &ClearTextDBState) -> Vec<Uuid> {
+    epic_uuids_where(state, |epic| {
+        !epic.status.is_done(&state.statuses) && epic.progress(&state.stories, &state.statuses).is_complete()
+    })
+}
+
+/// Closes the epics containing a story if all of their stories are now done and the database has
+/// `auto_close_epics` turned on. Epics the workflow won't let close are left for the user.
+///
+/// # Returns
+/// * `Result<Vec<Uuid>, ServiceError>` - The epics that were closed.
+/// # Errors
+/// * `ServiceError::EpicNotFound` - Never in practice; the epics are looked up from the state.
+pub fn auto_close_epics_of(state: &mut ClearTextDBState, story_uuid: Uuid, clock: &dyn Clock) -> Result<Vec<Uuid>, ServiceError> {
+    require(state, Permission::Edit)?;
+    if !state.auto_close_epics {
+        return Ok(Vec::new());
+    }
+    let mut closed = Vec::new();
+    for epic_uuid in epics_ready_to_close(state) {
+        if get_epic(state, epic_uuid)?.story_uuids.contains(&story_uuid) && set_epic_status(state, epic_uuid, Status::Closed, clock).is_ok() {
+            closed.push(epic_uuid);
+        }
+    }
+    Ok(closed)
+}
+
+/// Copies an epic with [`Epic::duplicate`] together with its stories, for repeating a known-good
+/// structure. The copy goes into the same project, ranked after all existing epics.
+///
+/// Each story is copied with [`Story::duplicate`] but keeps its title and its blockers on other
+/// stories of the epic, which point at their copies. Stories in the trash are left out.
+///
+/// # Arguments
+/// * `state` - The database state to modify.
+/// * `epic_uuid` - The epic to copy.
+/// * `reset_statuses` - Whether the copies start `Open` rather than keeping the originals'
+///   statuses.
+/// * `clock` - The source of the copies' timestamps.
+/// # Returns
+/// * `Result<Uuid, ServiceError>` - The UUID of the copied epic.
+/// # Errors
+/// * `ServiceError::EpicNotFound` - If the epic does not exist.
+/// * `ServiceError::Validation` - If the suffixed title is too long.
+pub fn duplicate_epic(state: &mut ClearTextDBState, epic_uuid: Uuid, reset_statuses: bool, clock: &dyn Clock) -> Result<Uuid, ServiceError> {
+    require(state, Permission::Edit)?;
+    let original = get_epic(state, epic_uuid)?;
+    let mut copy = original.duplicate();
+    validate_title(&copy.title)?;
+    if !reset_statuses {
+        copy.status = original.status;
+    }
+    let stories: Vec<Story> = original
+        .story_uuids
+        .iter()
+        .filter_map(|story_uuid| get_story(state, *story_uuid).ok())
+        .filter(|story| !story.is_deleted())
+        .cloned()
+        .collect();
+    let project_uuid = project_of(state, epic_uuid).map(|project| project.project_uuid);
+    let now = clock.now();
+
+    let copied: HashMap<Uuid, Uuid> = stories.iter().map(|story| (story.story_uuid, Uuid::new_v4())).collect();
+    let mut story_copies = Vec::with_capacity(stories.len());
+    for story in &stories {
+        let mut story_copy = Story {
+            story_uuid: copied[&story.story_uuid],
+            title: story.title.clone(),
+            blocked_by: story.blocked_by.iter().filter_map(|blocker| copied.get(blocker).copied()).collect(),
+            rank: story.rank,
+            number: state.next_story_number,
+            created_at: now,
+            updated_at: now,
+            ..story.duplicate()
+        };
+        if !reset_statuses {
+            story_copy.status = story.status;
+        }
+        state.next_story_number += 1;
+        story_copies.push(story_copy);
+    }
+
+    copy.story_uuids = story_copies.iter().map(|story| story.story_uuid).collect();
+    copy.rank = next_epic_rank(state);
+    copy.number = state.next_epic_number;
+    state.next_epic_number += 1;
+    copy.created_at = now;
+    copy.updated_at = now;
+    let copy_uuid = copy.epic_uuid;
+    state.mark_dirty();
+    state.epics.push(copy);
+    state.emit(Event::EpicCreated { epic_uuid: copy_uuid });
+    for story_copy in story_copies {
+        state.emit(Event::StoryCreated { story_uuid: story_copy.story_uuid, epic_uuid: Some(copy_uuid) });
+        state.stories.push(story_copy);
+    }
+    move_epic_to_project(state, copy_uuid, project_uuid)?;
+    Ok(copy_uuid)
+}
+
+/// Deletes an epic, handling its stories according to `policy`, and removes the metadata of its
+/// attachments (and those of cascaded stories).
+///
+/// The encrypted attachment files are left on disk; pass the returned UUIDs to
+/// [`crate::db::attachments::delete_attachment_files`] once the change is saved.
+/// # Returns
+/// * `Result<(Epic, Vec<Uuid>), ServiceError>` - The removed epic and the removed attachments.
+/// # Errors
+/// * `ServiceError::EpicNotFound` - If the epic does not exist.
+/// * `ServiceError::EpicHasStories` - If `policy` is [`DeletePolicy::Refuse`] and the epic has stories.
+pub fn delete_epic(state: &mut ClearTextDBState, epic_uuid: Uuid, policy: DeletePolicy, clock: &dyn Clock) -> Result<(Epic, Vec<Uuid>), ServiceError> {
+    require(state, Permission::Edit)?;
+    let index = state
+        .epics
+        .iter()
+        .position(|epic| epic.epic_uuid == epic_uuid)
+        .ok_or(ServiceError::EpicNotFound(epic_uuid))?;
+    // Ignore UUIDs of stories that no longer exist.
+    let story_uuids: Vec<Uuid> = state.epics[index]
+        .story_uuids
+        .iter()
+        .copied()
+        .filter(|&story_uuid| state.stories.iter().any(|story| story.story_uuid == story_uuid))
+        .collect();
+    let mut attachment_uuids = state.epics[index].attachments.clone();
+    match policy {
+        DeletePolicy::Refuse if !story_uuids.is_empty() => return Err(ServiceError::EpicHasStories(story_uuids.len())),
+        DeletePolicy::Cascade => {
+            for story_uuid in story_uuids {
+                attachment_uuids.extend(delete_story(state, story_uuid, clock)?.1);
+            }
+        }
+        DeletePolicy::Refuse | DeletePolicy::Detach => (),
+    }
+    // Sub-epics move up to the deleted epic's parent. Like deleting stories, this only edits
+    // epics, so `index` is still valid.
+    let parent = state.epics[index].parent_epic;
+    for child_uuid in epic_uuids_where(state, |epic| epic.parent_epic == Some(epic_uuid)) {
+        update_epic(state, child_uuid, clock, |epic| epic.parent_epic = parent)?;
+    }
+    for project in &mut state.projects {
+        project.epic_uuids.retain(|uuid| *uuid != epic_uuid);
+    }
+    for goal in &mut state.goals {
+        goal.epic_uuids.retain(|uuid| *uuid != epic_uuid);
+    }
+    state.attachments.retain(|attachment| !attachment_uuids.contains(&attachment.attachment_uuid));
+    state.mark_dirty();
+    state.emit(Event::EpicDeleted { epic_uuid });
+    Ok((state.epics.remove(index), attachment_uuids))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::models::attachments::Attachment;
+    use crate::models::validation::ValidationError;
+    use crate::service::stories::{create_story, reopen_story, set_story_status};
+    use crate::service::{dependencies::add_blocker, projects::{create_project, switch_project}, tasks::add_task};
+    use crate::users::User;
+
+    #[test]
+    fn test_progress_and_closing() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let epic_uuid = create_epic(&mut state, "Epic", String::new(), &SystemClock).unwrap();
+        let first = create_story(&mut state, epic_uuid, "First", String::new(), &SystemClock).unwrap();
+        let second = create_story(&mut state, epic_uuid, "Second", String::new(), &SystemClock).unwrap();
+        assert!(epics_ready_to_close(&state).is_empty());
+
+        set_story_status(&mut state, first, Status::Closed, &SystemClock).unwrap();
+        assert_eq!(epic_progress(&state, epic_uuid).unwrap().to_string(), "1/2 (50%)");
+        set_story_status(&mut state, second, Status::Closed, &SystemClock).unwrap();
+        assert!(epic_progress(&state, epic_uuid).unwrap().is_complete());
+        assert_eq!(epics_ready_to_close(&state), vec![epic_uuid]);
+        assert_eq!(state.epics[0].status, Status::Open);
+
+        state.auto_close_epics = true;
+        reopen_story(&mut state, second, Status::Open, &SystemClock).unwrap();
+        set_story_status(&mut state, second, Status::Closed, &SystemClock).unwrap();
+        assert_eq!(state.epics[0].status, Status::Closed);
+        assert!(epics_ready_to_close(&state).is_empty());
+    }
+
+    #[test]
+    fn test_delete_policies() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let epic_uuid = create_epic(&mut state, "Epic", String::new(), &SystemClock).unwrap();
+        let story_uuid = create_story(&mut state, epic_uuid, "Story", String::new(), &SystemClock).unwrap();
+        let other_uuid = create_story(&mut state, epic_uuid, "Other", String::new(), &SystemClock).unwrap();
+        add_blocker(&mut state, other_uuid, story_uuid, &SystemClock).unwrap();
+        add_task(&mut state, story_uuid, "Task", &SystemClock).unwrap();
+        // Attachment metadata only; the files are the caller's to remove.
+        let attach = |state: &mut ClearTextDBState| {
+            let attachment = Attachment { attachment_uuid: Uuid::new_v4(), ..Attachment::default() };
+            state.attachments.push(attachment.clone());
+            attachment.attachment_uuid
+        };
+        let story_attachment = attach(&mut state);
+        state.stories[0].attachments.push(story_attachment);
+
+        assert_eq!(delete_epic(&mut state, epic_uuid, DeletePolicy::Refuse, &SystemClock), Err(ServiceError::EpicHasStories(2)));
+        assert_eq!(delete_story(&mut state, story_uuid, &SystemClock).unwrap().1, vec![story_attachment]);
+        assert!(state.tasks.is_empty() && state.attachments.is_empty());
+        assert_eq!(state.epics[0].story_uuids, vec![other_uuid]);
+        assert!(state.stories[0].blocked_by.is_empty());
+
+        let second_epic = create_epic(&mut state, "Second", String::new(), &SystemClock).unwrap();
+        let doomed = create_story(&mut state, second_epic, "Doomed", String::new(), &SystemClock).unwrap();
+        let epic_attachment = attach(&mut state);
+        state.epics[1].attachments.push(epic_attachment);
+        let doomed_attachment = attach(&mut state);
+        state.stories[1].attachments.push(doomed_attachment);
+        let kept_attachment = attach(&mut state);
+        state.stories[0].attachments.push(kept_attachment);
+        assert_eq!(delete_epic(&mut state, epic_uuid, DeletePolicy::Detach, &SystemClock).unwrap().1, Vec::<Uuid>::new());
+        assert_eq!(state.stories.len(), 2);
+        let removed = delete_epic(&mut state, second_epic, DeletePolicy::Cascade, &SystemClock).unwrap().1;
+        assert_eq!(removed, vec![epic_attachment, doomed_attachment]);
+        assert_eq!(state.attachments.len(), 1);
+        assert_eq!(state.attachments[0].attachment_uuid, kept_attachment);
+        assert!(state.epics.is_empty());
+        assert!(get_story(&state, doomed).is_err());
+        assert_eq!(state.stories[0].story_uuid, other_uuid);
+    }
+    #[test]
+    fn test_epic_appearance() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let epic_uuid = create_epic(&mut state, "Launch", String::new(), &SystemClock).unwrap();
+        set_epic_appearance(&mut state, epic_uuid, Some("#ff8800".to_string()), Some(" 🚀 ".to_string()), &SystemClock).unwrap();
+        assert_eq!(get_epic(&state, epic_uuid).unwrap().badge(), "🚀 Launch");
+        let invalid = set_epic_appearance(&mut state, epic_uuid, Some("orange".to_string()), None, &SystemClock);
+        assert_eq!(invalid, Err(ValidationError::InvalidColor.into()));
+        set_epic_appearance(&mut state, epic_uuid, None, None, &SystemClock).unwrap();
+        assert_eq!(get_epic(&state, epic_uuid).unwrap().badge(), "Launch");
+    }
+
+    #[test]
+    fn test_mutations_mark_dirty() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        assert!(!state.is_dirty());
+        let epic_uuid = create_epic(&mut state, "Epic", String::new(), &SystemClock).unwrap();
+        assert!(state.is_dirty());
+
+        state.mark_saved();
+        get_epic(&state, epic_uuid).unwrap();
+        assert!(!state.is_dirty());
+        rename_epic(&mut state, epic_uuid, "Renamed", &SystemClock).unwrap();
+        assert!(state.is_dirty());
+
+        state.mark_saved();
+        delete_epic(&mut state, epic_uuid, DeletePolicy::Refuse, &SystemClock).unwrap();
+        assert!(state.is_dirty());
+    }
+
+    #[test]
+    fn test_duplicate_epic_with_stories() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let project_uuid = create_project(&mut state, "Website", String::new()).unwrap();
+        switch_project(&mut state, Some(project_uuid)).unwrap();
+        let epic_uuid = create_epic(&mut state, "Release", "Checklist".to_string(), &SystemClock).unwrap();
+        let build = create_story(&mut state, epic_uuid, "Build", String::new(), &SystemClock).unwrap();
+        let ship = create_story(&mut state, epic_uuid, "Ship", String::new(), &SystemClock).unwrap();
+        add_blocker(&mut state, ship, build, &SystemClock).unwrap();
+        set_story_status(&mut state, build, Status::Closed, &SystemClock).unwrap();
+        switch_project(&mut state, None).unwrap();
+
+        let copy_uuid = duplicate_epic(&mut state, epic_uuid, false, &SystemClock).unwrap();
+        let copy = get_epic(&state, copy_uuid).unwrap();
+        assert_eq!((copy.title.as_str(), copy.key(), copy.story_uuids.len()), ("Release (copy)", "EPIC-2".to_string(), 2));
+        assert_eq!(project_of(&state, copy_uuid).map(|project| project.project_uuid), Some(project_uuid));
+        let (build_copy, ship_copy) = (get_story(&state, copy.story_uuids[0]).unwrap(), get_story(&state, copy.story_uuids[1]).unwrap());
+        assert_eq!((build_copy.title.as_str(), build_copy.status, build_copy.key()), ("Build", Status::Closed, "STORY-3".to_string()));
+        assert_eq!(ship_copy.blocked_by, vec![build_copy.story_uuid]);
+        assert_eq!(get_epic(&state, epic_uuid).unwrap().story_uuids, vec![build, ship]);
+
+        let reset = duplicate_epic(&mut state, epic_uuid, true, &SystemClock).unwrap();
+        let reset_story = get_epic(&state, reset).unwrap().story_uuids[0];
+        assert_eq!(get_story(&state, reset_story).unwrap().status, Status::Open);
+        let missing = Uuid::new_v4();
+        assert_eq!(duplicate_epic(&mut state, missing, true, &SystemClock), Err(ServiceError::EpicNotFound(missing)));
+    }
+}
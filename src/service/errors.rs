@@ -0,0 +1,106 @@
+//! Error types for the service layer
+
+use crate::models::{Status, roles::{Permission, Role}, validation::ValidationError, workflow::TransitionAction};
+use uuid::Uuid;
+
+/// # Service Error
+/// Errors returned by service-layer operations on the database state.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ServiceError {
+    /// No epic with the given UUID exists.
+    EpicNotFound(Uuid),
+    /// No story with the given UUID exists.
+    StoryNotFound(Uuid),
+    /// No task with the given UUID exists.
+    TaskNotFound(Uuid),
+    /// No label with the given UUID exists.
+    LabelNotFound(Uuid),
+    /// No sprint with the given UUID exists.
+    SprintNotFound(Uuid),
+    /// The sprint with the given UUID is already closed.
+    SprintClosed(Uuid),
+    /// No attachment with the given UUID exists.
+    AttachmentNotFound(Uuid),
+    /// Adding the dependency would make a story (indirectly) block itself.
+    DependencyCycle,
+    /// Nesting the epic would make it (indirectly) its own parent.
+    EpicCycle,
+    /// The story can't be closed while these blocking stories are still open.
+    BlockedBy(Vec<Uuid>),
+    /// No custom status with the given UUID is defined.
+    StatusNotFound(Uuid),
+    /// Built-in statuses can't be deleted.
+    BuiltinStatus,
+    /// The workflow doesn't allow moving from the first status to the second.
+    InvalidTransition(Status, Status),
+    /// The status change needs a specific action (e.g. reopening).
+    ActionRequired(TransitionAction),
+    /// A timer is already running, for the work log with the given UUID.
+    TimerRunning(Uuid),
+    /// No timer is running.
+    NoTimerRunning,
+    /// No work log with the given UUID exists.
+    WorkLogNotFound(Uuid),
+    /// The story's checklist has no item at the given index.
+    ChecklistItemNotFound(usize),
+    /// A story can't be linked to itself.
+    SelfLink,
+    /// The epic still has stories and the delete policy refuses to remove it.
+    EpicHasStories(usize),
+    /// A title, description, date range, color, or name was rejected.
+    Validation(ValidationError),
+    /// No custom field with the given ID exists.
+    FieldNotFound(Uuid),
+    /// The value doesn't match the custom field's kind (or isn't one of its options).
+    FieldTypeMismatch(String),
+    /// No project with the given UUID exists.
+    ProjectNotFound(Uuid),
+    /// No goal with the given UUID exists.
+    GoalNotFound(Uuid),
+    /// The text is neither a UUID nor a short ID like `EPIC-12`, or names no such item.
+    UnknownReference(String),
+    /// The role the database was opened with doesn't allow this.
+    NotAllowed(Role, Permission),
+}
+
+impl std::fmt::Display for ServiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ServiceError::EpicNotFound(uuid) => write!(f, "Epic not found: {uuid}"),
+            ServiceError::StoryNotFound(uuid) => write!(f, "Story not found: {uuid}"),
+            ServiceError::TaskNotFound(uuid) => write!(f, "Task not found: {uuid}"),
+            ServiceError::LabelNotFound(uuid) => write!(f, "Label not found: {uuid}"),
+            ServiceError::SprintNotFound(uuid) => write!(f, "Sprint not found: {uuid}"),
+            ServiceError::SprintClosed(uuid) => write!(f, "Sprint is already closed: {uuid}"),
+            ServiceError::AttachmentNotFound(uuid) => write!(f, "Attachment not found: {uuid}"),
+            ServiceError::DependencyCycle => write!(f, "That dependency would create a cycle"),
+            ServiceError::EpicCycle => write!(f, "An epic can't be nested inside itself"),
+            ServiceError::BlockedBy(uuids) => write!(f, "Blocked by {} open stories", uuids.len()),
+            ServiceError::StatusNotFound(uuid) => write!(f, "Status not found: {uuid}"),
+            ServiceError::BuiltinStatus => write!(f, "Built-in statuses can't be deleted"),
+            ServiceError::InvalidTransition(from, to) => write!(f, "Can't move from {from:?} to {to:?}"),
+            ServiceError::ActionRequired(action) => write!(f, "This status change requires the {action:?} action"),
+            ServiceError::TimerRunning(uuid) => write!(f, "A timer is already running: {uuid}"),
+            ServiceError::NoTimerRunning => write!(f, "No timer is running"),
+            ServiceError::WorkLogNotFound(uuid) => write!(f, "Work log not found: {uuid}"),
+            ServiceError::ChecklistItemNotFound(index) => write!(f, "Checklist item not found: {index}"),
+            ServiceError::SelfLink => write!(f, "A story can't be linked to itself"),
+            ServiceError::EpicHasStories(count) => write!(f, "Epic still has {count} stories"),
+            ServiceError::Validation(err) => write!(f, "{err}"),
+            ServiceError::FieldNotFound(uuid) => write!(f, "Custom field not found: {uuid}"),
+            ServiceError::FieldTypeMismatch(name) => write!(f, "That value doesn't fit the custom field {name:?}"),
+            ServiceError::ProjectNotFound(uuid) => write!(f, "Project not found: {uuid}"),
+            ServiceError::GoalNotFound(uuid) => write!(f, "Goal not found: {uuid}"),
+            ServiceError::UnknownReference(reference) => write!(f, "No epic or story matches {reference:?}"),
+            ServiceError::NotAllowed(role, permission) => write!(f, "A {role} can't {permission}"),
+        }
+    }
+}
+
+impl std::error::Error for ServiceError {}
+
+impl From<ValidationError> for ServiceError {
+    fn from(err: ValidationError) -> Self {
+        ServiceError::Validation(err)
+    }
+}
@@ -0,0 +1,133 @@
+//! Estimate operations: sizing stories, rolling their estimates up to epics, and comparing hour
+//! estimates with the time actually logged.
+
+use super::{ServiceError, get_epic, get_story, require, update_story, work_logs::story_time_spent};
+use crate::clock::Clock;
+use crate::db::ClearTextDBState;
+use crate::models::roles::Permission;
+use crate::models::estimates::{EffortComparison, Estimate, EstimateTotals};
+use uuid::Uuid;
+
+/// Sets (or clears) a story's estimate.
+///
+/// # Errors
+/// * `ServiceError::StoryNotFound` - If the story does not exist.
+pub fn set_story_estimate(
+    state: &mut ClearTextDBState,
+    story_uuid: Uuid,
+    estimate: Option<Estimate>,
+    clock: &dyn Clock,
+) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    update_story(state, story_uuid, clock, |story| story.estimate = estimate)
+}
+
+/// Sums the estimates of an epic's stories.
+///
+/// # Errors
+/// * `ServiceError::EpicNotFound` - If the epic does not exist.
+/// * `ServiceError::StoryNotFound` - If the epic refers to a missing story.
+pub fn epic_estimate_totals(state: &ClearTextDBState, epic_uuid: Uuid) -> Result<EstimateTotals, ServiceError> {
+    get_epic(state, epic_uuid)?
+        .story_uuids
+        .iter()
+        .map(|story_uuid| get_story(state, *story_uuid).map(|story| story.estimate))
+        .collect()
+}
+
+/// Compares a story's hour estimate with the time logged against it, including a running timer.
+///
+/// A story estimated in points, or not at all, compares against an estimate of 0 hours.
+/// # Errors
+/// * `ServiceError::StoryNotFound` - If the story does not exist.
+pub fn story_effort(state: &ClearTextDBState, story_uuid: Uuid, clock: &dyn Clock) -> Result<EffortComparison, ServiceError> {
+    let estimated_hours = match get_story(state, story_uuid)?.estimate {
+        Some(Estimate::Hours(hours)) => hours,
+        _ => 0,
+    };
+    let actual_seconds = u64::try_from(story_time_spent(state, story_uuid, clock).num_seconds()).unwrap_or(0);
+    Ok(EffortComparison { item_uuid: story_uuid, estimated_hours, actual_seconds })
+}
+
+/// Compares the hour estimates of an epic's stories with the time logged against them.
+///
+/// Only stories estimated in hours count, since time spent on the others has nothing to compare
+/// against.
+/// # Errors
+/// * `ServiceError::EpicNotFound` - If the epic does not exist.
+/// * `ServiceError::StoryNotFound` - If the epic refers to a missing story.
+pub fn epic_effort(state: &ClearTextDBState, epic_uuid: Uuid, clock: &dyn Clock) -> Result<EffortComparison, ServiceError> {
+    let mut total = EffortComparison { item_uuid: epic_uuid, ..EffortComparison::default() };
+    for story_uuid in &get_epic(state, epic_uuid)?.story_uuids {
+        let story = story_effort(state, *story_uuid, clock)?;
+        if story.estimated_hours > 0 {
+            total.estimated_hours = total.estimated_hours.saturating_add(story.estimated_hours);
+            total.actual_seconds = total.actual_seconds.saturating_add(story.actual_seconds);
+        }
+    }
+    Ok(total)
+}
+
+/// Lists the epics and stories whose logged time is more than `threshold_percent` over their hour
+/// estimate, worst first.
+#[must_use]
+pub fn effort_overruns(state: &ClearTextDBState, threshold_percent: u32, clock: &dyn Clock) -> Vec<EffortComparison> {
+    let epics = state.epics.iter().filter_map(|epic| epic_effort(state, epic.epic_uuid, clock).ok());
+    let stories = state.stories.iter().filter_map(|story| story_effort(state, story.story_uuid, clock).ok());
+    let mut overruns: Vec<EffortComparison> = epics.chain(stories).filter(|comparison| comparison.exceeds(threshold_percent)).collect();
+    overruns.sort_by_key(|comparison| std::cmp::Reverse(comparison.percent()));
+    overruns
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{FixedClock, SystemClock};
+    use crate::models::{epics::Epic, stories::Story};
+    use crate::service::{epics::create_epic, stories::create_story, work_logs::log_work};
+    use crate::users::User;
+    use chrono::DateTime;
+
+    #[test]
+    fn test_epic_rollup() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let mut epic = Epic::new("Epic".to_string(), String::new());
+        for estimate in [Some(Estimate::Points(3)), Some(Estimate::Points(5)), Some(Estimate::Hours(4)), None] {
+            let story = Story::new("Story".to_string(), String::new());
+            epic.story_uuids.push(story.story_uuid);
+            state.stories.push(story);
+            let story_uuid = state.stories.last().unwrap().story_uuid;
+            set_story_estimate(&mut state, story_uuid, estimate, &SystemClock).unwrap();
+        }
+        let epic_uuid = epic.epic_uuid;
+        state.epics.push(epic);
+
+        let totals = epic_estimate_totals(&state, epic_uuid).unwrap();
+        assert_eq!(totals, EstimateTotals { points: 8, hours: 4, unestimated: 1 });
+        assert_eq!(state.stories[0].estimate.unwrap().to_string(), "3 pt");
+    }
+
+    #[test]
+    fn test_effort_overruns() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let clock = FixedClock::new(DateTime::from_timestamp(1_700_000_000, 0).unwrap());
+        let epic_uuid = create_epic(&mut state, "Epic", String::new(), &clock).unwrap();
+        let over = create_story(&mut state, epic_uuid, "Over", String::new(), &clock).unwrap();
+        let under = create_story(&mut state, epic_uuid, "Under", String::new(), &clock).unwrap();
+        let points = create_story(&mut state, epic_uuid, "Points", String::new(), &clock).unwrap();
+        set_story_estimate(&mut state, over, Some(Estimate::Hours(2)), &clock).unwrap();
+        set_story_estimate(&mut state, under, Some(Estimate::Hours(4)), &clock).unwrap();
+        set_story_estimate(&mut state, points, Some(Estimate::Points(3)), &clock).unwrap();
+        log_work(&mut state, over, clock.now(), 3 * 3600, String::new()).unwrap();
+        log_work(&mut state, under, clock.now(), 3600, String::new()).unwrap();
+        log_work(&mut state, points, clock.now(), 10 * 3600, String::new()).unwrap();
+
+        let epic = epic_effort(&state, epic_uuid, &clock).unwrap();
+        assert_eq!((epic.estimated_hours, epic.actual_seconds, epic.percent()), (6, 4 * 3600, Some(66)));
+        assert_eq!(story_effort(&state, over, &clock).unwrap().to_string(), "3h 00m of 2h (150%)");
+
+        let overruns = effort_overruns(&state, 20, &clock);
+        assert_eq!(overruns.iter().map(|comparison| comparison.item_uuid).collect::<Vec<_>>(), vec![over]);
+        assert!(effort_overruns(&state, 50, &clock).is_empty());
+    }
+}
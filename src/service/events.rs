@@ -0,0 +1,102 @@
+//! Event operations: delivering the [`Event`]s the service layer emits to subscribed subsystems
+//! such as autosave, the audit log, notifications, and script hooks.
+//!
+//! Service operations queue events on the state as they go; the caller hands them to an
+//! [`EventBus`] once the operation has finished, so subscribers never see a half-applied change.
+
+use crate::db::ClearTextDBState;
+use crate::models::events::Event;
+
+/// A subscriber's callback, run once per published event.
+type Handler = Box<dyn FnMut(&Event)>;
+
+/// # Event Bus struct
+/// The subscribers to data-change events.
+#[derive(Default)]
+pub struct EventBus {
+    /// The subscribers, in the order they subscribed.
+    handlers: Vec<Handler>,
+}
+
+impl EventBus {
+    /// Creates an event bus with no subscribers.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Subscribes to every event. Handlers pick out the events they care about by matching on
+    /// them.
+    pub fn subscribe(&mut self, handler: impl FnMut(&Event) + 'static) {
+        self.handlers.push(Box::new(handler));
+    }
+
+    /// Delivers an event to every subscriber, in the order they subscribed.
+    pub fn publish(&mut self, event: &Event) {
+        for handler in &mut self.handlers {
+            handler(event);
+        }
+    }
+
+    /// Delivers the events queued on the state since the last call, oldest first.
+    ///
+    /// # Returns
+    /// * `usize` - How many events were delivered.
+    pub fn publish_pending(&mut self, state: &mut ClearTextDBState) -> usize {
+        let events = state.take_events();
+        for event in &events {
+            self.publish(event);
+        }
+        events.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::models::Status;
+    use crate::service::{
+        epics::create_epic,
+        stories::{create_story, delete_story, set_story_status},
+    };
+    use crate::users::User;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_publish_service_events() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let mut bus = EventBus::new();
+        let status_changes = Rc::new(RefCell::new(Vec::new()));
+        let seen = Rc::clone(&status_changes);
+        bus.subscribe(move |event| {
+            if let Event::StoryStatusChanged { from, to, .. } = event {
+                seen.borrow_mut().push((*from, *to));
+            }
+        });
+        let all = Rc::new(RefCell::new(Vec::new()));
+        let seen = Rc::clone(&all);
+        bus.subscribe(move |event| seen.borrow_mut().push(event.clone()));
+
+        let epic_uuid = create_epic(&mut state, "Epic", String::new(), &SystemClock).unwrap();
+        let story_uuid = create_story(&mut state, epic_uuid, "Login", String::new(), &SystemClock).unwrap();
+        assert_eq!(bus.publish_pending(&mut state), 3);
+        assert_eq!(
+            *all.borrow(),
+            vec![
+                Event::EpicCreated { epic_uuid },
+                Event::StoryCreated { story_uuid, epic_uuid: Some(epic_uuid) },
+                Event::EpicUpdated { epic_uuid, fields: vec!["stories".to_string()] },
+            ]
+        );
+
+        set_story_status(&mut state, story_uuid, Status::InProgress, &SystemClock).unwrap();
+        set_story_status(&mut state, story_uuid, Status::InProgress, &SystemClock).unwrap();
+        delete_story(&mut state, story_uuid, &SystemClock).unwrap();
+        bus.publish_pending(&mut state);
+        assert_eq!(*status_changes.borrow(), vec![(Status::Open, Status::InProgress)]);
+        assert!(all.borrow().contains(&Event::StoryDeleted { story_uuid }));
+        assert_eq!(bus.publish_pending(&mut state), 0);
+    }
+}
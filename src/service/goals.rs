@@ -0,0 +1,149 @@
+//! Goal operations: managing goals and their key results, linking epics to them, and measuring
+//! their progress.
+
+use super::{ServiceError, get_epic, require};
+use crate::db::ClearTextDBState;
+use crate::models::roles::Permission;
+use crate::models::epics::Progress;
+use crate::models::goals::Goal;
+use crate::models::validation::validate_title;
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+/// Creates a new goal.
+///
+/// # Returns
+/// * `Result<Uuid, ServiceError>` - The UUID of the new goal.
+/// # Errors
+/// * `ServiceError::Validation` - If the objective is blank or too long.
+pub fn create_goal(state: &mut ClearTextDBState, objective: &str, target_date: Option<NaiveDate>) -> Result<Uuid, ServiceError> {
+    require(state, Permission::Edit)?;
+    let goal = Goal::new(validate_title(objective)?, target_date);
+    let goal_uuid = goal.goal_uuid;
+    state.mark_dirty();
+    state.goals.push(goal);
+    Ok(goal_uuid)
+}
+
+/// Looks up a goal by UUID.
+///
+/// # Errors
+/// * `ServiceError::GoalNotFound` - If no such goal exists.
+pub fn get_goal(state: &ClearTextDBState, goal_uuid: Uuid) -> Result<&Goal, ServiceError> {
+    state
+        .goals
+        .iter()
+        .find(|goal| goal.goal_uuid == goal_uuid)
+        .ok_or(ServiceError::GoalNotFound(goal_uuid))
+}
+
+/// Looks up a goal by UUID for modification, marking the database dirty.
+///
+/// # Errors
+/// * `ServiceError::GoalNotFound` - If no such goal exists.
+pub fn get_goal_mut(state: &mut ClearTextDBState, goal_uuid: Uuid) -> Result<&mut Goal, ServiceError> {
+    require(state, Permission::Edit)?;
+    state.mark_dirty();
+    state
+        .goals
+        .iter_mut()
+        .find(|goal| goal.goal_uuid == goal_uuid)
+        .ok_or(ServiceError::GoalNotFound(goal_uuid))
+}
+
+/// Adds a key result to a goal.
+///
+/// # Errors
+/// * `ServiceError::Validation` - If the key result is blank or too long.
+/// * `ServiceError::GoalNotFound` - If the goal does not exist.
+pub fn add_key_result(state: &mut ClearTextDBState, goal_uuid: Uuid, key_result: &str) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    let key_result = validate_title(key_result)?;
+    get_goal_mut(state, goal_uuid)?.key_results.push(key_result);
+    Ok(())
+}
+
+/// Deletes a goal. Its epics are kept.
+///
+/// # Returns
+/// * `Result<Goal, ServiceError>` - The removed goal.
+/// # Errors
+/// * `ServiceError::GoalNotFound` - If the goal does not exist.
+pub fn delete_goal(state: &mut ClearTextDBState, goal_uuid: Uuid) -> Result<Goal, ServiceError> {
+    require(state, Permission::Edit)?;
+    let index = state
+        .goals
+        .iter()
+        .position(|goal| goal.goal_uuid == goal_uuid)
+        .ok_or(ServiceError::GoalNotFound(goal_uuid))?;
+    state.mark_dirty();
+    Ok(state.goals.remove(index))
+}
+
+/// Links an epic to a goal. Linking an epic twice has no effect.
+///
+/// # Errors
+/// * `ServiceError::EpicNotFound` - If the epic does not exist.
+/// * `ServiceError::GoalNotFound` - If the goal does not exist.
+pub fn link_epic_to_goal(state: &mut ClearTextDBState, goal_uuid: Uuid, epic_uuid: Uuid) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    get_epic(state, epic_uuid)?;
+    let goal = get_goal_mut(state, goal_uuid)?;
+    if !goal.epic_uuids.contains(&epic_uuid) {
+        goal.epic_uuids.push(epic_uuid);
+    }
+    Ok(())
+}
+
+/// Unlinks an epic from a goal.
+///
+/// # Errors
+/// * `ServiceError::GoalNotFound` - If the goal does not exist.
+pub fn unlink_epic_from_goal(state: &mut ClearTextDBState, goal_uuid: Uuid, epic_uuid: Uuid) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    get_goal_mut(state, goal_uuid)?.epic_uuids.retain(|uuid| *uuid != epic_uuid);
+    Ok(())
+}
+
+/// Measures a goal by how many of its linked epics are done.
+///
+/// # Errors
+/// * `ServiceError::GoalNotFound` - If the goal does not exist.
+pub fn goal_progress(state: &ClearTextDBState, goal_uuid: Uuid) -> Result<Progress, ServiceError> {
+    let goal = get_goal(state, goal_uuid)?;
+    let epics: Vec<_> = state.epics.iter().filter(|epic| goal.epic_uuids.contains(&epic.epic_uuid)).collect();
+    Ok(Progress {
+        done: epics.iter().filter(|epic| epic.status.is_done(&state.statuses)).count(),
+        total: epics.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::models::Status;
+    use crate::service::epics::{DeletePolicy, create_epic, delete_epic, set_epic_status};
+    use crate::users::User;
+
+    #[test]
+    fn test_goal_progress_follows_epics() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let goal_uuid = create_goal(&mut state, "Grow signups", NaiveDate::from_ymd_opt(2025, 6, 30)).unwrap();
+        add_key_result(&mut state, goal_uuid, "1,000 new accounts").unwrap();
+        let launch = create_epic(&mut state, "Launch", String::new(), &SystemClock).unwrap();
+        let referrals = create_epic(&mut state, "Referrals", String::new(), &SystemClock).unwrap();
+        link_epic_to_goal(&mut state, goal_uuid, launch).unwrap();
+        link_epic_to_goal(&mut state, goal_uuid, referrals).unwrap();
+        link_epic_to_goal(&mut state, goal_uuid, referrals).unwrap();
+
+        set_epic_status(&mut state, launch, Status::Closed, &SystemClock).unwrap();
+        assert_eq!(goal_progress(&state, goal_uuid).unwrap(), Progress { done: 1, total: 2 });
+        delete_epic(&mut state, referrals, DeletePolicy::Refuse, &SystemClock).unwrap();
+        assert_eq!(get_goal(&state, goal_uuid).unwrap().epic_uuids, vec![launch]);
+        unlink_epic_from_goal(&mut state, goal_uuid, launch).unwrap();
+        assert_eq!(goal_progress(&state, goal_uuid).unwrap().percent(), 0);
+        delete_goal(&mut state, goal_uuid).unwrap();
+        assert!(get_goal(&state, goal_uuid).is_err());
+    }
+}
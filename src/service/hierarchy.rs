@@ -0,0 +1,130 @@
+//! Hierarchy operations: nesting epics under other epics, without allowing cycles, and rolling
+//! progress up across a subtree.
+
+use super::{ServiceError, get_epic, ranking::ranked_epics, require, update_epic};
+use crate::clock::Clock;
+use crate::db::ClearTextDBState;
+use crate::models::roles::Permission;
+use crate::models::epics::{Epic, Progress};
+use uuid::Uuid;
+
+/// Nests an epic under `parent`, or with `None`, makes it a top-level epic.
+///
+/// # Errors
+/// * `ServiceError::EpicNotFound` - If either epic does not exist.
+/// * `ServiceError::EpicCycle` - If `parent` is the epic itself or one of its sub-epics.
+pub fn set_parent_epic(state: &mut ClearTextDBState, epic_uuid: Uuid, parent: Option<Uuid>, clock: &dyn Clock) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    get_epic(state, epic_uuid)?;
+    if let Some(parent_uuid) = parent {
+        get_epic(state, parent_uuid)?;
+        if subtree(state, epic_uuid).contains(&parent_uuid) {
+            return Err(ServiceError::EpicCycle);
+        }
+    }
+    update_epic(state, epic_uuid, clock, |epic| epic.parent_epic = parent)
+}
+
+/// Lists an epic's direct sub-epics in rank order.
+#[must_use]
+pub fn child_epics(state: &ClearTextDBState, epic_uuid: Uuid) -> Vec<&Epic> {
+    ranked_epics(state).into_iter().filter(|epic| epic.parent_epic == Some(epic_uuid)).collect()
+}
+
+/// Lists the UUIDs of an epic and all of its sub-epics, at any depth.
+#[must_use]
+pub fn subtree(state: &ClearTextDBState, epic_uuid: Uuid) -> Vec<Uuid> {
+    let mut uuids = vec![epic_uuid];
+    let mut next = 0;
+    while let Some(&uuid) = uuids.get(next) {
+        for epic in &state.epics {
+            // The `contains` check keeps a corrupted, cyclic hierarchy from looping forever.
+            if epic.parent_epic == Some(uuid) && !uuids.contains(&epic.epic_uuid) {
+                uuids.push(epic.epic_uuid);
+            }
+        }
+        next += 1;
+    }
+    uuids
+}
+
+/// Counts how many stories are done across an epic and all of its sub-epics.
+///
+/// # Errors
+/// * `ServiceError::EpicNotFound` - If the epic does not exist.
+pub fn subtree_progress(state: &ClearTextDBState, epic_uuid: Uuid) -> Result<Progress, ServiceError> {
+    get_epic(state, epic_uuid)?;
+    Ok(subtree(state, epic_uuid)
+        .into_iter()
+        .filter_map(|uuid| get_epic(state, uuid).ok())
+        .map(|epic| epic.progress(&state.stories, &state.statuses))
+        .fold(Progress::default(), |total, progress| Progress { done: total.done + progress.done, total: total.total + progress.total }))
+}
+
+/// Lists the epics not in the trash as a tree: each top-level epic in rank order, followed by its
+/// sub-epics, depth first, along with their depth (0 for top level).
+///
+/// Epics whose parent is missing or deleted are shown at the top level.
+#[must_use]
+pub fn epic_tree(state: &ClearTextDBState) -> Vec<(usize, &Epic)> {
+    let epics = ranked_epics(state);
+    let is_root = |epic: &Epic| epic.parent_epic.is_none_or(|parent| !epics.iter().any(|other| other.epic_uuid == parent));
+    let mut tree = Vec::new();
+    let mut pending: Vec<(usize, &Epic)> = epics.iter().rev().filter(|epic| is_root(epic)).map(|epic| (0, *epic)).collect();
+    while let Some((depth, epic)) = pending.pop() {
+        if tree.iter().any(|(_, seen): &(usize, &Epic)| seen.epic_uuid == epic.epic_uuid) {
+            continue;
+        }
+        tree.push((depth, epic));
+        pending.extend(epics.iter().rev().filter(|child| child.parent_epic == Some(epic.epic_uuid)).map(|child| (depth + 1, *child)));
+    }
+    tree
+}
+
+/// Renders the epic tree as indented lines with rolled-up progress, e.g. `  EPIC-3 Login  1/2 (50%)`.
+#[must_use]
+pub fn render_epic_tree(state: &ClearTextDBState) -> Vec<String> {
+    epic_tree(state)
+        .into_iter()
+        .map(|(depth, epic)| {
+            let progress = subtree_progress(state, epic.epic_uuid).unwrap_or_default();
+            format!("{}{} {}  {progress}", "  ".repeat(depth), epic.key(), epic.badge())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::models::Status;
+    use crate::service::epics::{DeletePolicy, create_epic, delete_epic};
+    use crate::service::stories::{create_story, set_story_status};
+    use crate::users::User;
+
+    #[test]
+    fn test_nesting_and_rollup() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let platform = create_epic(&mut state, "Platform", String::new(), &SystemClock).unwrap();
+        let auth = create_epic(&mut state, "Auth", String::new(), &SystemClock).unwrap();
+        let totp = create_epic(&mut state, "TOTP", String::new(), &SystemClock).unwrap();
+        set_parent_epic(&mut state, auth, Some(platform), &SystemClock).unwrap();
+        set_parent_epic(&mut state, totp, Some(auth), &SystemClock).unwrap();
+        assert_eq!(set_parent_epic(&mut state, platform, Some(totp), &SystemClock), Err(ServiceError::EpicCycle));
+        assert_eq!(set_parent_epic(&mut state, auth, Some(auth), &SystemClock), Err(ServiceError::EpicCycle));
+
+        create_story(&mut state, platform, "Deploy", String::new(), &SystemClock).unwrap();
+        let story_uuid = create_story(&mut state, totp, "Enroll", String::new(), &SystemClock).unwrap();
+        set_story_status(&mut state, story_uuid, Status::Closed, &SystemClock).unwrap();
+        assert_eq!(subtree_progress(&state, platform).unwrap(), Progress { done: 1, total: 2 });
+        assert_eq!(child_epics(&state, platform).len(), 1);
+        assert_eq!(
+            render_epic_tree(&state),
+            vec!["EPIC-1 Platform  1/2 (50%)", "  EPIC-2 Auth  1/1 (100%)", "    EPIC-3 TOTP  1/1 (100%)"]
+        );
+
+        // Deleting a middle epic moves its sub-epics up a level.
+        delete_epic(&mut state, auth, DeletePolicy::Refuse, &SystemClock).unwrap();
+        assert_eq!(get_epic(&state, totp).unwrap().parent_epic, Some(platform));
+    }
+}
@@ -0,0 +1,91 @@
+//! History operations: reading an item's timeline of changes, the database-wide activity feed, and
+//! recently updated items.
+
+use crate::db::ClearTextDBState;
+use crate::models::{Timestamped, history::HistoryEntry};
+use uuid::Uuid;
+
+/// Returns an epic's or story's change history, oldest first.
+#[must_use]
+pub fn timeline(state: &ClearTextDBState, item_uuid: Uuid) -> Vec<&HistoryEntry> {
+    let mut entries: Vec<&HistoryEntry> = state.history.iter().filter(|entry| entry.item_uuid == item_uuid).collect();
+    entries.sort_by_key(|entry| entry.timestamp);
+    entries
+}
+
+/// Returns the most recent changes across all epics and stories, newest first.
+///
+/// # Arguments
+/// * `state` - The database state to read.
+/// * `limit` - The maximum number of entries to return.
+#[must_use]
+pub fn activity_feed(state: &ClearTextDBState, limit: usize) -> Vec<&HistoryEntry> {
+    let mut entries: Vec<&HistoryEntry> = state.history.iter().collect();
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.timestamp));
+    entries.truncate(limit);
+    entries
+}
+
+/// Sorts items by their last modification, most recently updated first.
+pub fn sort_by_recently_updated<T: Timestamped>(items: &mut [&T]) {
+    items.sort_by_key(|item| std::cmp::Reverse(item.updated_at()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+    use crate::models::{Status, stories::Story};
+    use crate::service::{assignees::assign_story, stories::set_story_status};
+    use crate::users::User;
+    use chrono::{DateTime, Duration};
+
+    #[test]
+    fn test_mutations_are_recorded() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let story = Story::new("Story".to_string(), String::new());
+        let story_uuid = story.story_uuid;
+        state.stories.push(story);
+        let clock = FixedClock::new(DateTime::from_timestamp(1_700_000_000, 0).unwrap());
+
+        set_story_status(&mut state, story_uuid, Status::InProgress, &clock).unwrap();
+        clock.advance(Duration::minutes(5));
+        let assignee = state.user.user_uuid;
+        assign_story(&mut state, story_uuid, Some(assignee), &clock).unwrap();
+        // Re-assigning to the same user changes nothing and records nothing.
+        assign_story(&mut state, story_uuid, Some(assignee), &clock).unwrap();
+
+        let entries = timeline(&state, story_uuid);
+        assert_eq!(entries.len(), 2);
+        assert_eq!((entries[0].field.as_str(), entries[0].old_value.as_str(), entries[0].new_value.as_str()), ("status", "Open", "InProgress"));
+        assert_eq!(entries[1].field, "assignee");
+        assert_eq!(entries[1].new_value, assignee.to_string());
+        assert_eq!(entries[1].timestamp - entries[0].timestamp, Duration::minutes(5));
+    }
+    #[test]
+    fn test_activity_feed_and_recently_updated() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let clock = FixedClock::new(DateTime::from_timestamp(1_700_000_000, 0).unwrap());
+        let first = Story::new("First".to_string(), String::new());
+        let second = Story::new("Second".to_string(), String::new());
+        let (first_uuid, second_uuid) = (first.story_uuid, second.story_uuid);
+        state.stories.extend([first, second]);
+
+        set_story_status(&mut state, second_uuid, Status::InProgress, &clock).unwrap();
+        clock.advance(Duration::minutes(1));
+        set_story_status(&mut state, first_uuid, Status::InProgress, &clock).unwrap();
+
+        let feed = activity_feed(&state, 1);
+        assert_eq!(feed.len(), 1);
+        assert_eq!(feed[0].item_uuid, first_uuid);
+        let mut stories: Vec<&Story> = state.stories.iter().collect();
+        sort_by_recently_updated(&mut stories);
+        assert_eq!(stories[0].story_uuid, first_uuid);
+        clock.advance(Duration::minutes(1));
+        let assignee = state.user.user_uuid;
+        assign_story(&mut state, second_uuid, Some(assignee), &clock).unwrap();
+        let mut stories: Vec<&Story> = state.stories.iter().collect();
+        sort_by_recently_updated(&mut stories);
+        assert_eq!(stories[0].story_uuid, second_uuid);
+    }
+}
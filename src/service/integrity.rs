@@ -0,0 +1,243 @@
+//! Integrity operations: checking a whole database for broken references, impossible timestamps,
+//! and states the workflow shouldn't allow, for a `doctor`-style command to report on.
+
+use super::repair::{Inconsistency, find_inconsistencies};
+use crate::clock::Clock;
+use crate::db::{ClearTextDBState, migrations::SCHEMA_VERSION};
+use crate::models::{Status, Timestamped, statuses::find_definition, validation::ValidationError};
+use std::collections::HashSet;
+use std::fmt;
+use uuid::Uuid;
+
+/// # Integrity Problem enum
+/// One thing wrong with a database.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum IntegrityProblem {
+    /// The state wasn't upgraded to the current schema version.
+    SchemaVersion {
+        /// The version the state reports.
+        found: u32,
+        /// The version this app writes.
+        expected: u32,
+    },
+    /// Two epics or stories share a UUID.
+    DuplicateUuid(Uuid),
+    /// An item refers to something that doesn't exist.
+    MissingReference {
+        /// The referring item.
+        item_uuid: Uuid,
+        /// The field holding the reference.
+        field: &'static str,
+        /// The UUID that matches nothing.
+        missing: Uuid,
+    },
+    /// An epic's story list doesn't match the stories (see [`find_inconsistencies`]).
+    Membership(Inconsistency),
+    /// An epic is (indirectly) its own parent.
+    EpicCycle(Uuid),
+    /// An item was created after the current time.
+    CreatedInFuture(Uuid),
+    /// An item was last modified before it was created.
+    UpdatedBeforeCreated(Uuid),
+    /// An item has a custom status the database doesn't define.
+    UndefinedStatus {
+        /// The item.
+        item_uuid: Uuid,
+        /// Its status.
+        status: Status,
+    },
+    /// A built-in status is missing from the database's status definitions.
+    MissingBuiltinStatus(Status),
+    /// An item's field breaks a validation rule.
+    InvalidField {
+        /// The item.
+        item_uuid: Uuid,
+        /// The broken rule.
+        error: ValidationError,
+    },
+}
+
+impl fmt::Display for IntegrityProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IntegrityProblem::SchemaVersion { found, expected } => write!(f, "schema version is {found}, expected {expected}"),
+            IntegrityProblem::DuplicateUuid(uuid) => write!(f, "UUID {uuid} is used more than once"),
+            IntegrityProblem::MissingReference { item_uuid, field, missing } => write!(f, "{item_uuid} refers to missing {missing} in {field}"),
+            IntegrityProblem::Membership(inconsistency) => write!(f, "{inconsistency}"),
+            IntegrityProblem::EpicCycle(uuid) => write!(f, "epic {uuid} is nested inside itself"),
+            IntegrityProblem::CreatedInFuture(uuid) => write!(f, "{uuid} was created in the future"),
+            IntegrityProblem::UpdatedBeforeCreated(uuid) => write!(f, "{uuid} was modified before it was created"),
+            IntegrityProblem::UndefinedStatus { item_uuid, status } => write!(f, "{item_uuid} has undefined status {status:?}"),
+            IntegrityProblem::MissingBuiltinStatus(status) => write!(f, "built-in status {status:?} is not defined"),
+            IntegrityProblem::InvalidField { item_uuid, error } => write!(f, "{item_uuid}: {error}"),
+        }
+    }
+}
+
+/// # Integrity Report struct
+/// Everything [`check_integrity`] found wrong with a database.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct IntegrityReport {
+    /// The problems, grouped by kind of check.
+    pub problems: Vec<IntegrityProblem>,
+}
+
+impl IntegrityReport {
+    /// Whether no problems were found.
+    #[must_use]
+    pub fn is_healthy(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// # Check Integrity
+/// Checks the whole database: the schema version, UUID references between items, epic and story
+/// membership, epic nesting, timestamps, statuses, and field validation. Nothing is changed.
+///
+/// # Arguments
+/// * `state` - The database state to check.
+/// * `clock` - Gives the current time, which no item may be created after.
+///
+/// # Returns
+/// * `IntegrityReport` - Every problem found.
+#[must_use]
+pub fn check_integrity(state: &ClearTextDBState, clock: &dyn Clock) -> IntegrityReport {
+    let mut problems = Vec::new();
+    if state.schema_version != SCHEMA_VERSION {
+        problems.push(IntegrityProblem::SchemaVersion { found: state.schema_version, expected: SCHEMA_VERSION });
+    }
+
+    let mut seen = HashSet::new();
+    for uuid in state.epics.iter().map(|epic| epic.epic_uuid).chain(state.stories.iter().map(|story| story.story_uuid)) {
+        if !seen.insert(uuid) {
+            problems.push(IntegrityProblem::DuplicateUuid(uuid));
+        }
+    }
+
+    check_references(state, &mut problems);
+    problems.extend(find_inconsistencies(state).into_iter().map(IntegrityProblem::Membership));
+    for epic in &state.epics {
+        let mut parent = epic.parent_epic;
+        for _ in 0..state.epics.len() {
+            let Some(parent_uuid) = parent else { break };
+            if parent_uuid == epic.epic_uuid {
+                problems.push(IntegrityProblem::EpicCycle(epic.epic_uuid));
+                break;
+            }
+            parent = state.epics.iter().find(|other| other.epic_uuid == parent_uuid).and_then(|other| other.parent_epic);
+        }
+    }
+
+    let now = clock.now();
+    let epics = state.epics.iter().map(|epic| (epic.epic_uuid, epic as &dyn Timestamped, epic.status, epic.validate()));
+    let stories = state.stories.iter().map(|story| (story.story_uuid, story as &dyn Timestamped, story.status, story.validate()));
+    for (item_uuid, timestamps, status, validation) in epics.chain(stories) {
+        if timestamps.created_at() > now {
+            problems.push(IntegrityProblem::CreatedInFuture(item_uuid));
+        }
+        if timestamps.updated_at() < timestamps.created_at() {
+            problems.push(IntegrityProblem::UpdatedBeforeCreated(item_uuid));
+        }
+        if matches!(status, Status::Custom(_)) && find_definition(&state.statuses, status).is_none() {
+            problems.push(IntegrityProblem::UndefinedStatus { item_uuid, status });
+        }
+        if let Err(error) = validation {
+            problems.push(IntegrityProblem::InvalidField { item_uuid, error });
+        }
+    }
+    problems.extend(
+        Status::BUILTINS
+            .into_iter()
+            .filter(|status| find_definition(&state.statuses, *status).is_none())
+            .map(IntegrityProblem::MissingBuiltinStatus),
+    );
+    IntegrityReport { problems }
+}
+
+/// Reports every UUID reference (other than epic story lists) that matches nothing.
+fn check_references(state: &ClearTextDBState, problems: &mut Vec<IntegrityProblem>) {
+    let epics: HashSet<Uuid> = state.epics.iter().map(|epic| epic.epic_uuid).collect();
+    let stories: HashSet<Uuid> = state.stories.iter().map(|story| story.story_uuid).collect();
+    let tasks: HashSet<Uuid> = state.tasks.iter().map(|task| task.task_uuid).collect();
+    let labels: HashSet<Uuid> = state.labels.iter().map(|label| label.label_uuid).collect();
+    let attachments: HashSet<Uuid> = state.attachments.iter().map(|attachment| attachment.attachment_uuid).collect();
+    let fields: HashSet<Uuid> = state.custom_fields.iter().map(|field| field.field_id).collect();
+    let projects: HashSet<Uuid> = state.projects.iter().map(|project| project.project_uuid).collect();
+    let mut check = |item_uuid: Uuid, field: &'static str, references: Vec<Uuid>, known: &HashSet<Uuid>| {
+        problems.extend(
+            references
+                .into_iter()
+                .filter(|reference| !known.contains(reference))
+                .map(|missing| IntegrityProblem::MissingReference { item_uuid, field, missing }),
+        );
+    };
+
+    for epic in &state.epics {
+        check(epic.epic_uuid, "parent_epic", epic.parent_epic.into_iter().collect(), &epics);
+        check(epic.epic_uuid, "labels", epic.labels.clone(), &labels);
+        check(epic.epic_uuid, "attachments", epic.attachments.clone(), &attachments);
+        check(epic.epic_uuid, "custom_fields", epic.custom_fields.keys().copied().collect(), &fields);
+    }
+    for story in &state.stories {
+        check(story.story_uuid, "tasks", story.task_uuids.clone(), &tasks);
+        check(story.story_uuid, "blocked_by", story.blocked_by.clone(), &stories);
+        check(story.story_uuid, "links", story.links.iter().map(|link| link.story_uuid).collect(), &stories);
+        check(story.story_uuid, "labels", story.labels.clone(), &labels);
+        check(story.story_uuid, "attachments", story.attachments.clone(), &attachments);
+        check(story.story_uuid, "custom_fields", story.custom_fields.keys().copied().collect(), &fields);
+    }
+    for sprint in &state.sprints {
+        check(sprint.sprint_uuid, "stories", sprint.story_uuids.clone(), &stories);
+    }
+    for project in &state.projects {
+        check(project.project_uuid, "epics", project.epic_uuids.clone(), &epics);
+    }
+    for goal in &state.goals {
+        check(goal.goal_uuid, "epics", goal.epic_uuids.clone(), &epics);
+    }
+    for work_log in &state.work_logs {
+        check(work_log.work_log_uuid, "story", vec![work_log.story_uuid], &stories);
+    }
+    check(state.user.user_uuid, "current_project", state.current_project.into_iter().collect(), &projects);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{FixedClock, SystemClock};
+    use crate::service::{epics::create_epic, stories::create_story};
+    use crate::users::User;
+    use chrono::{DateTime, Duration};
+
+    #[test]
+    fn test_check_integrity() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let epic_uuid = create_epic(&mut state, "Epic", String::new(), &SystemClock).unwrap();
+        let story_uuid = create_story(&mut state, epic_uuid, "Story", String::new(), &SystemClock).unwrap();
+        assert!(check_integrity(&state, &SystemClock).is_healthy());
+
+        let missing = Uuid::new_v4();
+        state.stories[0].blocked_by.push(missing);
+        state.stories[0].title = String::new();
+        state.epics[0].parent_epic = Some(epic_uuid);
+        state.epics[0].updated_at = state.epics[0].created_at - Duration::seconds(1);
+        state.statuses.retain(|definition| definition.status != Status::Blocked);
+        state.schema_version = 1;
+        let report = check_integrity(&state, &SystemClock);
+        assert_eq!(
+            report.problems,
+            vec![
+                IntegrityProblem::SchemaVersion { found: 1, expected: SCHEMA_VERSION },
+                IntegrityProblem::MissingReference { item_uuid: story_uuid, field: "blocked_by", missing },
+                IntegrityProblem::EpicCycle(epic_uuid),
+                IntegrityProblem::UpdatedBeforeCreated(epic_uuid),
+                IntegrityProblem::InvalidField { item_uuid: story_uuid, error: ValidationError::EmptyTitle },
+                IntegrityProblem::MissingBuiltinStatus(Status::Blocked),
+            ]
+        );
+        assert_eq!(report.problems[2].to_string(), format!("epic {epic_uuid} is nested inside itself"));
+
+        let past = FixedClock::new(DateTime::from_timestamp(1_000_000_000, 0).unwrap());
+        assert!(check_integrity(&state, &past).problems.contains(&IntegrityProblem::CreatedInFuture(story_uuid)));
+    }
+}
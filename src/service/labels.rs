@@ -0,0 +1,208 @@
+//! Label operations: managing a database's labels, attaching them to epics and stories, and
+//! filtering by them.
+
+use super::{ServiceError, epic_uuids_where, require, story_uuids_where, update_epic, update_story};
+use crate::clock::Clock;
+use crate::db::ClearTextDBState;
+use crate::models::roles::Permission;
+use crate::models::{epics::Epic, labels::Label, stories::Story, validation::validate_unique_name};
+use uuid::Uuid;
+
+/// Creates a new label.
+///
+/// Label names are unique within a database, ignoring case.
+/// # Arguments
+/// * `state` - The database state to modify.
+/// * `name` - The name of the label.
+/// * `color` - The display color, e.g. `#d73a4a`.
+/// # Returns
+/// * `Result<Uuid, ServiceError>` - The UUID of the new label.
+/// # Errors
+/// * `ServiceError::Validation` - If a label with that name already exists.
+pub fn create_label(state: &mut ClearTextDBState, name: String, color: String) -> Result<Uuid, ServiceError> {
+    require(state, Permission::Edit)?;
+    ensure_unique_name(state, &name, None)?;
+    let label = Label::new(name, color);
+    let label_uuid = label.label_uuid;
+    state.mark_dirty();
+    state.labels.push(label);
+    Ok(label_uuid)
+}
+
+/// Looks up a label by UUID.
+///
+/// # Errors
+/// * `ServiceError::LabelNotFound` - If no such label exists.
+pub fn get_label(state: &ClearTextDBState, label_uuid: Uuid) -> Result<&Label, ServiceError> {
+    state
+        .labels
+        .iter()
+        .find(|label| label.label_uuid == label_uuid)
+        .ok_or(ServiceError::LabelNotFound(label_uuid))
+}
+
+/// Looks up a label by UUID for modification, marking the database dirty.
+///
+/// # Errors
+/// * `ServiceError::LabelNotFound` - If no such label exists.
+pub fn get_label_mut(state: &mut ClearTextDBState, label_uuid: Uuid) -> Result<&mut Label, ServiceError> {
+    require(state, Permission::Edit)?;
+    state.mark_dirty();
+    state
+        .labels
+        .iter_mut()
+        .find(|label| label.label_uuid == label_uuid)
+        .ok_or(ServiceError::LabelNotFound(label_uuid))
+}
+
+/// Renames a label.
+///
+/// # Errors
+/// * `ServiceError::LabelNotFound` - If the label does not exist.
+/// * `ServiceError::Validation` - If another label already has that name.
+pub fn rename_label(state: &mut ClearTextDBState, label_uuid: Uuid, name: String) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    ensure_unique_name(state, &name, Some(label_uuid))?;
+    get_label_mut(state, label_uuid)?.name = name;
+    Ok(())
+}
+
+/// Changes a label's display color.
+///
+/// # Errors
+/// * `ServiceError::LabelNotFound` - If the label does not exist.
+pub fn recolor_label(state: &mut ClearTextDBState, label_uuid: Uuid, color: String) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    get_label_mut(state, label_uuid)?.color = color;
+    Ok(())
+}
+
+/// Deletes a label and detaches it from every epic and story.
+///
+/// # Returns
+/// * `Result<Label, ServiceError>` - The removed label.
+/// # Errors
+/// * `ServiceError::LabelNotFound` - If the label does not exist.
+pub fn delete_label(state: &mut ClearTextDBState, label_uuid: Uuid, clock: &dyn Clock) -> Result<Label, ServiceError> {
+    require(state, Permission::Edit)?;
+    let index = state
+        .labels
+        .iter()
+        .position(|label| label.label_uuid == label_uuid)
+        .ok_or(ServiceError::LabelNotFound(label_uuid))?;
+    for epic_uuid in epic_uuids_where(state, |epic| epic.labels.contains(&label_uuid)) {
+        unlabel_epic(state, epic_uuid, label_uuid, clock)?;
+    }
+    for story_uuid in story_uuids_where(state, |story| story.labels.contains(&label_uuid)) {
+        unlabel_story(state, story_uuid, label_uuid, clock)?;
+    }
+    state.mark_dirty();
+    Ok(state.labels.remove(index))
+}
+
+/// Attaches a label to an epic. Attaching a label twice has no effect.
+///
+/// # Errors
+/// * `ServiceError::LabelNotFound` - If the label does not exist.
+/// * `ServiceError::EpicNotFound` - If the epic does not exist.
+pub fn label_epic(state: &mut ClearTextDBState, epic_uuid: Uuid, label_uuid: Uuid, clock: &dyn Clock) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    get_label(state, label_uuid)?;
+    update_epic(state, epic_uuid, clock, |epic| {
+        if !epic.labels.contains(&label_uuid) {
+            epic.labels.push(label_uuid);
+        }
+    })
+}
+
+/// Detaches a label from an epic.
+///
+/// # Errors
+/// * `ServiceError::EpicNotFound` - If the epic does not exist.
+pub fn unlabel_epic(state: &mut ClearTextDBState, epic_uuid: Uuid, label_uuid: Uuid, clock: &dyn Clock) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    update_epic(state, epic_uuid, clock, |epic| epic.labels.retain(|uuid| *uuid != label_uuid))
+}
+
+/// Attaches a label to a story. Attaching a label twice has no effect.
+///
+/// # Errors
+/// * `ServiceError::LabelNotFound` - If the label does not exist.
+/// * `ServiceError::StoryNotFound` - If the story does not exist.
+pub fn label_story(state: &mut ClearTextDBState, story_uuid: Uuid, label_uuid: Uuid, clock: &dyn Clock) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    get_label(state, label_uuid)?;
+    update_story(state, story_uuid, clock, |story| {
+        if !story.labels.contains(&label_uuid) {
+            story.labels.push(label_uuid);
+        }
+    })
+}
+
+/// Detaches a label from a story.
+///
+/// # Errors
+/// * `ServiceError::StoryNotFound` - If the story does not exist.
+pub fn unlabel_story(state: &mut ClearTextDBState, story_uuid: Uuid, label_uuid: Uuid, clock: &dyn Clock) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    update_story(state, story_uuid, clock, |story| story.labels.retain(|uuid| *uuid != label_uuid))
+}
+
+/// Lists the epics carrying a label, for filtered list views.
+#[must_use]
+pub fn epics_with_label(state: &ClearTextDBState, label_uuid: Uuid) -> Vec<&Epic> {
+    state.epics.iter().filter(|epic| !epic.is_deleted() && epic.labels.contains(&label_uuid)).collect()
+}
+
+/// Lists the stories carrying a label, for filtered list views.
+#[must_use]
+pub fn stories_with_label(state: &ClearTextDBState, label_uuid: Uuid) -> Vec<&Story> {
+    state.stories.iter().filter(|story| !story.is_deleted() && story.labels.contains(&label_uuid)).collect()
+}
+
+/// Fails if a label other than `except` already uses `name` (ignoring case).
+fn ensure_unique_name(state: &ClearTextDBState, name: &str, except: Option<Uuid>) -> Result<(), ServiceError> {
+    let others = state.labels.iter().filter(|label| Some(label.label_uuid) != except);
+    Ok(validate_unique_name(name, others.map(|label| label.name.as_str()))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::models::validation::ValidationError;
+    use crate::users::User;
+
+    #[test]
+    fn test_label_lifecycle() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let epic = Epic::new("Epic".to_string(), String::new());
+        let epic_uuid = epic.epic_uuid;
+        state.epics.push(epic);
+        let story = Story::new("Story".to_string(), String::new());
+        let story_uuid = story.story_uuid;
+        state.stories.push(story);
+
+        let bug = create_label(&mut state, "bug".to_string(), "#d73a4a".to_string()).unwrap();
+        let ux = create_label(&mut state, "ux".to_string(), "#0075ca".to_string()).unwrap();
+        assert_eq!(
+            create_label(&mut state, "Bug".to_string(), "#000000".to_string()),
+            Err(ValidationError::DuplicateName("Bug".to_string()).into())
+        );
+        assert!(rename_label(&mut state, ux, "BUG".to_string()).is_err());
+        rename_label(&mut state, ux, "design".to_string()).unwrap();
+
+        label_epic(&mut state, epic_uuid, bug, &SystemClock).unwrap();
+        label_story(&mut state, story_uuid, bug, &SystemClock).unwrap();
+        label_story(&mut state, story_uuid, bug, &SystemClock).unwrap();
+        label_story(&mut state, story_uuid, ux, &SystemClock).unwrap();
+        assert_eq!(state.stories[0].labels, vec![bug, ux]);
+        assert_eq!(epics_with_label(&state, bug).len(), 1);
+        assert_eq!(stories_with_label(&state, ux).len(), 1);
+
+        delete_label(&mut state, bug, &SystemClock).unwrap();
+        assert!(state.epics[0].labels.is_empty());
+        assert_eq!(state.stories[0].labels, vec![ux]);
+        assert_eq!(label_epic(&mut state, epic_uuid, bug, &SystemClock), Err(ServiceError::LabelNotFound(bug)));
+    }
+}
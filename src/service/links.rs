@@ -0,0 +1,118 @@
+//! Link operations: connecting stories with typed links that are kept in sync on both ends.
+//!
+//! Blocking links are stored as the blocked story's `blocked_by` list, so they share the cycle
+//! checks of [`super::dependencies`]; every other link type is stored on both stories.
+
+use super::dependencies::{add_blocker, blocking, remove_blocker};
+use super::{ServiceError, get_story, require, update_story};
+use crate::clock::Clock;
+use crate::db::ClearTextDBState;
+use crate::models::roles::Permission;
+use crate::models::links::{Link, LinkType};
+use uuid::Uuid;
+
+/// Links `story_uuid` to `other_uuid`, e.g. "`story_uuid` duplicates `other_uuid`". The inverse
+/// link is added to the other story. Adding an existing link has no effect.
+///
+/// # Errors
+/// * `ServiceError::StoryNotFound` - If either story does not exist.
+/// * `ServiceError::SelfLink` - If both UUIDs are the same story.
+/// * `ServiceError::DependencyCycle` - If a blocking link would create a cycle.
+pub fn add_link(state: &mut ClearTextDBState, story_uuid: Uuid, link_type: LinkType, other_uuid: Uuid, clock: &dyn Clock) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    get_story(state, story_uuid)?;
+    get_story(state, other_uuid)?;
+    if story_uuid == other_uuid {
+        return Err(ServiceError::SelfLink);
+    }
+    match link_type {
+        LinkType::Blocks => add_blocker(state, other_uuid, story_uuid, clock),
+        LinkType::IsBlockedBy => add_blocker(state, story_uuid, other_uuid, clock),
+        _ => {
+            push_link(state, story_uuid, Link { link_type, story_uuid: other_uuid }, clock)?;
+            push_link(state, other_uuid, Link { link_type: link_type.inverse(), story_uuid }, clock)
+        }
+    }
+}
+
+/// Removes a link and its inverse. Removing a missing link has no effect.
+///
+/// # Errors
+/// * `ServiceError::StoryNotFound` - If either story does not exist.
+pub fn remove_link(state: &mut ClearTextDBState, story_uuid: Uuid, link_type: LinkType, other_uuid: Uuid, clock: &dyn Clock) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    get_story(state, other_uuid)?;
+    match link_type {
+        LinkType::Blocks => remove_blocker(state, other_uuid, story_uuid, clock),
+        LinkType::IsBlockedBy => remove_blocker(state, story_uuid, other_uuid, clock),
+        _ => {
+            let link = Link { link_type, story_uuid: other_uuid };
+            let inverse = Link { link_type: link_type.inverse(), story_uuid };
+            update_story(state, story_uuid, clock, |story| story.links.retain(|existing| *existing != link))?;
+            update_story(state, other_uuid, clock, |story| story.links.retain(|existing| *existing != inverse))
+        }
+    }
+}
+
+/// Lists every link of a story, including blocking links in both directions, sorted by type.
+///
+/// # Errors
+/// * `ServiceError::StoryNotFound` - If the story does not exist.
+pub fn story_links(state: &ClearTextDBState, story_uuid: Uuid) -> Result<Vec<Link>, ServiceError> {
+    let story = get_story(state, story_uuid)?;
+    let mut links = story.links.clone();
+    links.extend(blocking(state, story_uuid).iter().map(|blocked| Link { link_type: LinkType::Blocks, story_uuid: blocked.story_uuid }));
+    links.extend(story.blocked_by.iter().map(|&blocker| Link { link_type: LinkType::IsBlockedBy, story_uuid: blocker }));
+    links.sort();
+    Ok(links)
+}
+
+/// Renders a story's links for its detail page, e.g. `duplicates: Login page`.
+///
+/// # Errors
+/// * `ServiceError::StoryNotFound` - If the story does not exist.
+pub fn render_links(state: &ClearTextDBState, story_uuid: Uuid) -> Result<Vec<String>, ServiceError> {
+    story_links(state, story_uuid)?
+        .into_iter()
+        .map(|link| Ok(format!("{}: {}", link.link_type, get_story(state, link.story_uuid)?.title)))
+        .collect()
+}
+
+/// Adds a link to one story unless it is already there.
+fn push_link(state: &mut ClearTextDBState, story_uuid: Uuid, link: Link, clock: &dyn Clock) -> Result<(), ServiceError> {
+    update_story(state, story_uuid, clock, |story| {
+        if !story.links.contains(&link) {
+            story.links.push(link);
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::models::stories::Story;
+    use crate::users::User;
+
+    #[test]
+    fn test_links_are_bidirectional() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let login = Story::new("Login page".to_string(), String::new());
+        let signin = Story::new("Sign-in screen".to_string(), String::new());
+        let api = Story::new("Auth API".to_string(), String::new());
+        let (login_uuid, signin_uuid, api_uuid) = (login.story_uuid, signin.story_uuid, api.story_uuid);
+        state.stories.extend([login, signin, api]);
+
+        add_link(&mut state, signin_uuid, LinkType::Duplicates, login_uuid, &SystemClock).unwrap();
+        add_link(&mut state, api_uuid, LinkType::Blocks, login_uuid, &SystemClock).unwrap();
+        assert_eq!(render_links(&state, login_uuid).unwrap(), ["is duplicated by: Sign-in screen", "is blocked by: Auth API"]);
+        assert_eq!(render_links(&state, api_uuid).unwrap(), ["blocks: Login page"]);
+        assert_eq!(add_link(&mut state, login_uuid, LinkType::Blocks, api_uuid, &SystemClock), Err(ServiceError::DependencyCycle));
+        assert_eq!(add_link(&mut state, login_uuid, LinkType::RelatesTo, login_uuid, &SystemClock), Err(ServiceError::SelfLink));
+
+        remove_link(&mut state, login_uuid, LinkType::IsDuplicatedBy, signin_uuid, &SystemClock).unwrap();
+        assert!(story_links(&state, signin_uuid).unwrap().is_empty());
+        remove_link(&mut state, api_uuid, LinkType::Blocks, login_uuid, &SystemClock).unwrap();
+        assert!(story_links(&state, login_uuid).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,139 @@
+//! Pagination operations: cutting long lists of epics and stories into pages, so large backlogs
+//! can be shown a page at a time.
+
+/// The number of items per page when none is chosen.
+pub const DEFAULT_PAGE_SIZE: usize = 20;
+
+/// # Page Request struct
+/// Which slice of a list to show.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct PageRequest {
+    /// How many items to skip.
+    pub offset: usize,
+    /// The maximum number of items to show.
+    pub limit: usize,
+}
+
+impl Default for PageRequest {
+    fn default() -> Self {
+        Self { offset: 0, limit: DEFAULT_PAGE_SIZE }
+    }
+}
+
+impl PageRequest {
+    /// Requests a 1-based page of `page_size` items. Page 0 is treated as page 1.
+    ///
+    /// # Examples
+    /// ```
+    /// use ironyyy::service::pagination::PageRequest;
+    /// assert_eq!(PageRequest::page(3, 10), PageRequest { offset: 20, limit: 10 });
+    /// ```
+    #[must_use]
+    pub fn page(number: usize, page_size: usize) -> Self {
+        Self { offset: number.saturating_sub(1).saturating_mul(page_size), limit: page_size }
+    }
+
+    /// The request for the page after this one.
+    #[must_use]
+    pub fn next(self) -> Self {
+        Self { offset: self.offset.saturating_add(self.limit), ..self }
+    }
+
+    /// The request for the page before this one, stopping at the first page.
+    #[must_use]
+    pub fn previous(self) -> Self {
+        Self { offset: self.offset.saturating_sub(self.limit), ..self }
+    }
+}
+
+/// # Paginated struct
+/// One page of a list, with enough context to move between pages.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Paginated<T> {
+    /// The items on this page.
+    pub items: Vec<T>,
+    /// The request that produced this page.
+    pub request: PageRequest,
+    /// The length of the whole list.
+    pub total: usize,
+}
+
+impl<T> Paginated<T> {
+    /// Whether there are items after this page.
+    #[must_use]
+    pub fn has_next(&self) -> bool {
+        self.request.offset.saturating_add(self.items.len()) < self.total
+    }
+
+    /// Whether there are items before this page.
+    #[must_use]
+    pub fn has_previous(&self) -> bool {
+        self.request.offset > 0
+    }
+
+    /// This page's 1-based number.
+    #[must_use]
+    pub fn page_number(&self) -> usize {
+        self.request.offset / self.request.limit.max(1) + 1
+    }
+
+    /// The number of pages in the whole list; an empty list has one, empty page.
+    #[must_use]
+    pub fn page_count(&self) -> usize {
+        self.total.div_ceil(self.request.limit.max(1)).max(1)
+    }
+}
+
+/// # Paginate
+/// Takes one page out of a list.
+///
+/// # Arguments
+/// * `items` - The whole list, already filtered and sorted.
+/// * `request` - Which page to take.
+///
+/// # Returns
+/// * `Paginated<T>` - The page, empty if the offset is past the end of the list.
+///
+/// # Examples
+/// ```
+/// use ironyyy::service::pagination::{PageRequest, paginate};
+/// let page = paginate((1..=25).collect(), PageRequest::page(3, 10));
+/// assert_eq!(page.items, vec![21, 22, 23, 24, 25]);
+/// assert_eq!((page.page_number(), page.page_count(), page.has_next()), (3, 3, false));
+/// ```
+#[must_use]
+pub fn paginate<T>(items: Vec<T>, request: PageRequest) -> Paginated<T> {
+    let total = items.len();
+    let items = items.into_iter().skip(request.offset).take(request.limit).collect();
+    Paginated { items, request, total }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::db::ClearTextDBState;
+    use crate::service::{epics::create_epic, query::Query, stories::create_story};
+    use crate::users::User;
+
+    #[test]
+    fn test_paginate_stories() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let epic_uuid = create_epic(&mut state, "Epic", String::new(), &SystemClock).unwrap();
+        for number in 1..=5 {
+            create_story(&mut state, epic_uuid, &format!("Story {number}"), String::new(), &SystemClock).unwrap();
+        }
+
+        let first = Query::new().stories_page(&state, PageRequest::page(1, 2));
+        assert_eq!(first.items.iter().map(|story| story.title.as_str()).collect::<Vec<_>>(), vec!["Story 1", "Story 2"]);
+        assert!(first.has_next() && !first.has_previous());
+        assert_eq!(first.page_count(), 3);
+
+        let last = Query::new().stories_page(&state, first.request.next().next());
+        assert_eq!(last.items.len(), 1);
+        assert!(!last.has_next() && last.has_previous());
+        assert_eq!(last.request.previous(), PageRequest::page(2, 2));
+        assert!(Query::new().stories_page(&state, PageRequest::page(9, 2)).items.is_empty());
+        assert_eq!(Query::new().epics_page(&state, PageRequest::default()).total, 1);
+    }
+}
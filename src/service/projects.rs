@@ -0,0 +1,198 @@
+//! Project operations: managing projects, moving epics between them, switching the current
+//! project, and per-project statistics.
+
+use super::{ServiceError, get_epic, require};
+use crate::db::ClearTextDBState;
+use crate::models::roles::Permission;
+use crate::models::projects::{Project, ProjectStats};
+use crate::models::validation::{validate_description, validate_title};
+use crate::models::{Scheduled, epics::Epic, stories::Story};
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+/// Creates a new, empty project.
+///
+/// # Returns
+/// * `Result<Uuid, ServiceError>` - The UUID of the new project.
+/// # Errors
+/// * `ServiceError::Validation` - If the name is blank or too long, or the description too long.
+pub fn create_project(state: &mut ClearTextDBState, name: &str, description: String) -> Result<Uuid, ServiceError> {
+    require(state, Permission::Edit)?;
+    validate_description(&description)?;
+    let project = Project::new(validate_title(name)?, description);
+    let project_uuid = project.project_uuid;
+    state.mark_dirty();
+    state.projects.push(project);
+    Ok(project_uuid)
+}
+
+/// Looks up a project by UUID.
+///
+/// # Errors
+/// * `ServiceError::ProjectNotFound` - If no such project exists.
+pub fn get_project(state: &ClearTextDBState, project_uuid: Uuid) -> Result<&Project, ServiceError> {
+    state
+        .projects
+        .iter()
+        .find(|project| project.project_uuid == project_uuid)
+        .ok_or(ServiceError::ProjectNotFound(project_uuid))
+}
+
+/// Looks up a project by UUID for modification, marking the database dirty.
+///
+/// # Errors
+/// * `ServiceError::ProjectNotFound` - If no such project exists.
+pub fn get_project_mut(state: &mut ClearTextDBState, project_uuid: Uuid) -> Result<&mut Project, ServiceError> {
+    require(state, Permission::Edit)?;
+    state.mark_dirty();
+    state
+        .projects
+        .iter_mut()
+        .find(|project| project.project_uuid == project_uuid)
+        .ok_or(ServiceError::ProjectNotFound(project_uuid))
+}
+
+/// Renames a project.
+///
+/// # Errors
+/// * `ServiceError::Validation` - If the name is blank or too long.
+/// * `ServiceError::ProjectNotFound` - If the project does not exist.
+pub fn rename_project(state: &mut ClearTextDBState, project_uuid: Uuid, name: &str) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    let name = validate_title(name)?;
+    get_project_mut(state, project_uuid)?.name = name;
+    Ok(())
+}
+
+/// Deletes a project. Its epics are kept, outside any project.
+///
+/// # Returns
+/// * `Result<Project, ServiceError>` - The removed project.
+/// # Errors
+/// * `ServiceError::ProjectNotFound` - If the project does not exist.
+/// * `ServiceError::NotAllowed` - If the database wasn't opened by an owner.
+pub fn delete_project(state: &mut ClearTextDBState, project_uuid: Uuid) -> Result<Project, ServiceError> {
+    require(state, Permission::DeleteProject)?;
+    let index = state
+        .projects
+        .iter()
+        .position(|project| project.project_uuid == project_uuid)
+        .ok_or(ServiceError::ProjectNotFound(project_uuid))?;
+    if state.current_project == Some(project_uuid) {
+        state.current_project = None;
+    }
+    state.mark_dirty();
+    Ok(state.projects.remove(index))
+}
+
+/// Moves an epic into a project (or, with `None`, out of every project).
+///
+/// # Errors
+/// * `ServiceError::EpicNotFound` - If the epic does not exist.
+/// * `ServiceError::ProjectNotFound` - If the project does not exist.
+pub fn move_epic_to_project(state: &mut ClearTextDBState, epic_uuid: Uuid, project_uuid: Option<Uuid>) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    get_epic(state, epic_uuid)?;
+    if let Some(project_uuid) = project_uuid {
+        get_project(state, project_uuid)?;
+    }
+    state.mark_dirty();
+    for project in &mut state.projects {
+        project.epic_uuids.retain(|uuid| *uuid != epic_uuid);
+    }
+    if let Some(project_uuid) = project_uuid {
+        get_project_mut(state, project_uuid)?.epic_uuids.push(epic_uuid);
+    }
+    Ok(())
+}
+
+/// Returns the project an epic belongs to, if any.
+#[must_use]
+pub fn project_of(state: &ClearTextDBState, epic_uuid: Uuid) -> Option<&Project> {
+    state.projects.iter().find(|project| project.epic_uuids.contains(&epic_uuid))
+}
+
+/// Switches to a project (or, with `None`, to the epics outside every project). Any role may
+/// switch, since it only changes what is shown.
+///
+/// # Errors
+/// * `ServiceError::ProjectNotFound` - If the project does not exist.
+pub fn switch_project(state: &mut ClearTextDBState, project_uuid: Option<Uuid>) -> Result<(), ServiceError> {
+    if let Some(project_uuid) = project_uuid {
+        get_project(state, project_uuid)?;
+    }
+    state.mark_dirty();
+    state.current_project = project_uuid;
+    Ok(())
+}
+
+/// Lists the epics of a project, or with `None`, the epics outside every project, leaving out
+/// deleted ones.
+#[must_use]
+pub fn project_epics(state: &ClearTextDBState, project_uuid: Option<Uuid>) -> Vec<&Epic> {
+    state
+        .epics
+        .iter()
+        .filter(|epic| !epic.is_deleted() && project_of(state, epic.epic_uuid).map(|project| project.project_uuid) == project_uuid)
+        .collect()
+}
+
+/// Lists the stories in a project's epics, or with `None`, in the epics outside every project,
+/// leaving out deleted ones.
+#[must_use]
+pub fn project_stories(state: &ClearTextDBState, project_uuid: Option<Uuid>) -> Vec<&Story> {
+    let epics = project_epics(state, project_uuid);
+    state
+        .stories
+        .iter()
+        .filter(|story| !story.is_deleted() && epics.iter().any(|epic| epic.story_uuids.contains(&story.story_uuid)))
+        .collect()
+}
+
+/// Computes summary numbers for a project, or with `None`, for the epics outside every project.
+#[must_use]
+pub fn project_stats(state: &ClearTextDBState, project_uuid: Option<Uuid>, today: NaiveDate) -> ProjectStats {
+    let epics = project_epics(state, project_uuid);
+    let stories = project_stories(state, project_uuid);
+    ProjectStats {
+        epics: epics.len(),
+        done_epics: epics.iter().filter(|epic| epic.status.is_done(&state.statuses)).count(),
+        stories: stories.len(),
+        done_stories: stories.iter().filter(|story| story.status.is_done(&state.statuses)).count(),
+        overdue_stories: stories.iter().filter(|story| story.is_overdue(today, &state.statuses)).count(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::models::Status;
+    use crate::service::{epics::create_epic, stories::{create_story, set_story_status}};
+    use crate::users::User;
+
+    #[test]
+    fn test_projects_separate_epics() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let loose = create_epic(&mut state, "Loose", String::new(), &SystemClock).unwrap();
+        let website = create_project(&mut state, "Website", String::new()).unwrap();
+        switch_project(&mut state, Some(website)).unwrap();
+        let redesign = create_epic(&mut state, "Redesign", String::new(), &SystemClock).unwrap();
+        let story = create_story(&mut state, redesign, "Header", String::new(), &SystemClock).unwrap();
+        create_story(&mut state, redesign, "Footer", String::new(), &SystemClock).unwrap();
+        set_story_status(&mut state, story, Status::Closed, &SystemClock).unwrap();
+
+        assert_eq!(project_epics(&state, Some(website)).len(), 1);
+        assert_eq!(project_epics(&state, None)[0].epic_uuid, loose);
+        let today = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let totals = project_stats(&state, Some(website), today);
+        assert_eq!((totals.epics, totals.stories, totals.done_stories), (1, 2, 1));
+
+        move_epic_to_project(&mut state, loose, Some(website)).unwrap();
+        assert_eq!(project_stats(&state, Some(website), today).epics, 2);
+        delete_project(&mut state, website).unwrap();
+        assert_eq!(state.current_project, None);
+        assert_eq!(project_epics(&state, None).len(), 2);
+        assert!(switch_project(&mut state, Some(website)).is_err());
+    }
+}
@@ -0,0 +1,253 @@
+//! Query operations: a typed, combinable filter over epics and stories, shared by list pages,
+//! search, and saved filters.
+//!
+//! ```rust
+//! use ironyyy::models::Status;
+//! use ironyyy::service::query::Query;
+//! let query = Query::new().status(Status::InProgress).label("infra").text("login");
+//! ```
+
+use super::pagination::{PageRequest, Paginated, paginate};
+use crate::db::ClearTextDBState;
+use crate::models::{Scheduled, Status, epics::Epic, stories::Story};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// # Filter enum
+/// One condition an epic or story must meet.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum Filter {
+    /// Has exactly this status.
+    Status(Status),
+    /// Carries a label with this name, ignoring case.
+    Label(String),
+    /// Is assigned to this user. Epics have no assignee, so they never match.
+    Assignee(Uuid),
+    /// Is due before this day.
+    DueBefore(NaiveDate),
+    /// Is due after this day.
+    DueAfter(NaiveDate),
+    /// Has this text in its title or description, ignoring case.
+    Text(String),
+    /// Meets every one of these filters.
+    All(Vec<Filter>),
+    /// Meets at least one of these filters.
+    Any(Vec<Filter>),
+    /// Doesn't meet this filter.
+    Not(Box<Filter>),
+}
+
+/// # Query struct
+/// A set of filters that all have to match, built up with chained calls. Archived and deleted
+/// items are left out unless asked for.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct Query {
+    /// The filters, all of which have to match.
+    filters: Vec<Filter>,
+    /// Whether archived items are included.
+    include_archived: bool,
+}
+
+impl Query {
+    /// Creates a query matching every active epic or story.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds any filter, such as an [`Filter::Any`] or [`Filter::Not`] combination.
+    #[must_use]
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
+    /// Only matches items with this status.
+    #[must_use]
+    pub fn status(self, status: Status) -> Self {
+        self.filter(Filter::Status(status))
+    }
+
+    /// Only matches items carrying the label with this name, ignoring case.
+    #[must_use]
+    pub fn label(self, name: impl Into<String>) -> Self {
+        self.filter(Filter::Label(name.into()))
+    }
+
+    /// Only matches stories assigned to this user.
+    #[must_use]
+    pub fn assignee(self, user_uuid: Uuid) -> Self {
+        self.filter(Filter::Assignee(user_uuid))
+    }
+
+    /// Only matches items due before this day.
+    #[must_use]
+    pub fn due_before(self, date: NaiveDate) -> Self {
+        self.filter(Filter::DueBefore(date))
+    }
+
+    /// Only matches items due after this day.
+    #[must_use]
+    pub fn due_after(self, date: NaiveDate) -> Self {
+        self.filter(Filter::DueAfter(date))
+    }
+
+    /// Only matches items with this text in their title or description, ignoring case.
+    #[must_use]
+    pub fn text(self, text: impl Into<String>) -> Self {
+        self.filter(Filter::Text(text.into()))
+    }
+
+    /// Also matches archived items.
+    #[must_use]
+    pub fn include_archived(mut self) -> Self {
+        self.include_archived = true;
+        self
+    }
+
+    /// Lists the epics matching this query, in stored order.
+    #[must_use]
+    pub fn epics<'a>(&self, state: &'a ClearTextDBState) -> Vec<&'a Epic> {
+        state.epics.iter().filter(|epic| self.matches(state, *epic)).collect()
+    }
+
+    /// Lists the stories matching this query, in stored order.
+    #[must_use]
+    pub fn stories<'a>(&self, state: &'a ClearTextDBState) -> Vec<&'a Story> {
+        state.stories.iter().filter(|story| self.matches(state, *story)).collect()
+    }
+
+    /// Takes one page of the epics matching this query.
+    #[must_use]
+    pub fn epics_page<'a>(&self, state: &'a ClearTextDBState, request: PageRequest) -> Paginated<&'a Epic> {
+        paginate(self.epics(state), request)
+    }
+
+    /// Takes one page of the stories matching this query.
+    #[must_use]
+    pub fn stories_page<'a>(&self, state: &'a ClearTextDBState, request: PageRequest) -> Paginated<&'a Story> {
+        paginate(self.stories(state), request)
+    }
+
+    /// Whether an item is visible to this query and meets all of its filters.
+    fn matches(&self, state: &ClearTextDBState, item: &impl Queryable) -> bool {
+        !item.is_deleted() && (self.include_archived || !item.is_archived()) && self.filters.iter().all(|filter| filter.matches(state, item))
+    }
+}
+
+impl Filter {
+    /// Whether an item meets this filter.
+    fn matches(&self, state: &ClearTextDBState, item: &impl Queryable) -> bool {
+        match self {
+            Filter::Status(status) => item.status() == *status,
+            Filter::Label(name) => state
+                .labels
+                .iter()
+                .any(|label| label.name.eq_ignore_ascii_case(name) && item.labels().contains(&label.label_uuid)),
+            Filter::Assignee(user_uuid) => item.assignee() == Some(*user_uuid),
+            Filter::DueBefore(date) => item.due_date().is_some_and(|due| due < *date),
+            Filter::DueAfter(date) => item.due_date().is_some_and(|due| due > *date),
+            Filter::Text(text) => {
+                let text = text.to_lowercase();
+                item.text().iter().any(|field| field.to_lowercase().contains(&text))
+            }
+            Filter::All(filters) => filters.iter().all(|filter| filter.matches(state, item)),
+            Filter::Any(filters) => filters.iter().any(|filter| filter.matches(state, item)),
+            Filter::Not(filter) => !filter.matches(state, item),
+        }
+    }
+}
+
+/// The fields of epics and stories that queries look at.
+trait Queryable: Scheduled {
+    /// The UUIDs of the item's labels.
+    fn labels(&self) -> &[Uuid];
+    /// The user the item is assigned to, if any.
+    fn assignee(&self) -> Option<Uuid>;
+    /// The item's searchable text: its title and description.
+    fn text(&self) -> [&str; 2];
+    /// Whether the item is archived.
+    fn is_archived(&self) -> bool;
+    /// Whether the item is in the trash.
+    fn is_deleted(&self) -> bool;
+}
+
+impl Queryable for Epic {
+    fn labels(&self) -> &[Uuid] {
+        &self.labels
+    }
+
+    fn assignee(&self) -> Option<Uuid> {
+        None
+    }
+
+    fn text(&self) -> [&str; 2] {
+        [&self.title, &self.description]
+    }
+
+    fn is_archived(&self) -> bool {
+        self.archived
+    }
+
+    fn is_deleted(&self) -> bool {
+        Epic::is_deleted(self)
+    }
+}
+
+impl Queryable for Story {
+    fn labels(&self) -> &[Uuid] {
+        &self.labels
+    }
+
+    fn assignee(&self) -> Option<Uuid> {
+        self.assignee
+    }
+
+    fn text(&self) -> [&str; 2] {
+        [&self.title, &self.description]
+    }
+
+    fn is_archived(&self) -> bool {
+        self.archived
+    }
+
+    fn is_deleted(&self) -> bool {
+        Story::is_deleted(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::service::{archive::archive_story, epics::create_epic, labels::{create_label, label_story}, schedule::set_story_dates, stories::{create_story, set_story_status}};
+    use crate::users::User;
+
+    #[test]
+    fn test_query_stories() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let epic_uuid = create_epic(&mut state, "Platform", String::new(), &SystemClock).unwrap();
+        let login = create_story(&mut state, epic_uuid, "Login page", String::new(), &SystemClock).unwrap();
+        let deploy = create_story(&mut state, epic_uuid, "Deploy", "Fix the LOGIN redirect".to_string(), &SystemClock).unwrap();
+        let docs = create_story(&mut state, epic_uuid, "Docs", String::new(), &SystemClock).unwrap();
+        let infra = create_label(&mut state, "infra".to_string(), "#0000ff".to_string()).unwrap();
+        label_story(&mut state, deploy, infra, &SystemClock).unwrap();
+        set_story_status(&mut state, deploy, Status::InProgress, &SystemClock).unwrap();
+        let due = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+        set_story_dates(&mut state, deploy, None, Some(due), &SystemClock).unwrap();
+
+        let uuids = |state: &ClearTextDBState, query: Query| query.stories(state).iter().map(|story| story.story_uuid).collect::<Vec<_>>();
+        assert_eq!(uuids(&state, Query::new().text("login")), vec![login, deploy]);
+        assert_eq!(uuids(&state, Query::new().status(Status::InProgress).label("INFRA").due_before(due.succ_opt().unwrap())), vec![deploy]);
+        assert!(uuids(&state, Query::new().label("infra").due_after(due)).is_empty());
+        let either = Filter::Any(vec![Filter::Text("docs".to_string()), Filter::Label("infra".to_string())]);
+        assert_eq!(uuids(&state, Query::new().filter(either.clone())), vec![deploy, docs]);
+        assert_eq!(uuids(&state, Query::new().filter(Filter::Not(Box::new(either)))), vec![login]);
+
+        archive_story(&mut state, docs, &SystemClock).unwrap();
+        assert_eq!(uuids(&state, Query::new().text("docs")), Vec::<Uuid>::new());
+        assert_eq!(uuids(&state, Query::new().text("docs").include_archived()), vec![docs]);
+        assert_eq!(Query::new().text("platform").epics(&state).len(), 1);
+    }
+}
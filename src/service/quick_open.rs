@@ -0,0 +1,118 @@
+//! Quick-open operations: fzf-style fuzzy matching over the short IDs and titles of epics and
+//! stories, so typing `lgn pg` finds "Login page redesign".
+
+use super::references::ItemRef;
+use crate::db::ClearTextDBState;
+
+/// Score for each matched character.
+const MATCH_SCORE: i32 = 1;
+/// Bonus for a match at the start of a word.
+const WORD_START_BONUS: i32 = 8;
+/// Bonus for a match right after the previous one.
+const CONSECUTIVE_BONUS: i32 = 5;
+/// Penalty for each character skipped between two matches.
+const GAP_PENALTY: i32 = 1;
+
+/// # Quick Open Hit struct
+/// An epic or story matching a quick-open pattern.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct QuickOpenHit {
+    /// The matching item.
+    pub item: ItemRef,
+    /// The text that was matched, e.g. `STORY-12 Login page redesign`.
+    pub label: String,
+    /// How well it matched; higher is better.
+    pub score: i32,
+}
+
+/// # Fuzzy Score
+/// Scores how well `pattern` matches `text` as a case-insensitive subsequence, favouring matches
+/// at word starts and runs of consecutive characters.
+///
+/// # Returns
+/// * `Option<i32>` - The score, or `None` if the characters of `pattern` don't all appear in
+///   `text` in order.
+///
+/// # Examples
+/// ```
+/// use ironyyy::service::quick_open::fuzzy_score;
+/// assert!(fuzzy_score("lgn", "Login") > fuzzy_score("lgn", "Fall gains"));
+/// assert_eq!(fuzzy_score("xyz", "Login"), None);
+/// ```
+#[must_use]
+pub fn fuzzy_score(pattern: &str, text: &str) -> Option<i32> {
+    let mut pattern = pattern.chars().flat_map(char::to_lowercase).peekable();
+    let mut score = 0;
+    let mut previous: Option<char> = None;
+    let mut last_match: Option<usize> = None;
+    for (index, c) in text.chars().enumerate() {
+        let Some(&wanted) = pattern.peek() else { break };
+        if c.to_lowercase().eq(std::iter::once(wanted)) {
+            score += MATCH_SCORE;
+            if previous.is_none_or(|previous| !previous.is_alphanumeric()) {
+                score += WORD_START_BONUS;
+            }
+            match last_match {
+                Some(last) if last + 1 == index => score += CONSECUTIVE_BONUS,
+                Some(last) => score -= GAP_PENALTY * i32::try_from(index - last - 1).unwrap_or(i32::MAX),
+                None => (),
+            }
+            last_match = Some(index);
+            pattern.next();
+        }
+        previous = Some(c);
+    }
+    pattern.peek().is_none().then_some(score)
+}
+
+/// Finds the epics and stories whose short ID and title match every space-separated word of
+/// `pattern`, best match first. Items in the trash are left out.
+///
+/// # Arguments
+/// * `state` - The database state to search.
+/// * `pattern` - What the user typed, e.g. `lgn pg` or `story-12`.
+/// * `limit` - The maximum number of hits to return.
+#[must_use]
+pub fn quick_open(state: &ClearTextDBState, pattern: &str, limit: usize) -> Vec<QuickOpenHit> {
+    let words: Vec<&str> = pattern.split_whitespace().collect();
+    if words.is_empty() {
+        return Vec::new();
+    }
+    let epics = state.epics.iter().filter(|epic| !epic.is_deleted()).map(|epic| (ItemRef::Epic(epic.epic_uuid), format!("{} {}", epic.key(), epic.title)));
+    let stories = state.stories.iter().filter(|story| !story.is_deleted()).map(|story| (ItemRef::Story(story.story_uuid), format!("{} {}", story.key(), story.title)));
+    let mut hits: Vec<QuickOpenHit> = epics
+        .chain(stories)
+        .filter_map(|(item, label)| {
+            let score = words.iter().map(|word| fuzzy_score(word, &label)).sum::<Option<i32>>()?;
+            Some(QuickOpenHit { item, label, score })
+        })
+        .collect();
+    hits.sort_by(|a, b| b.score.cmp(&a.score).then(a.label.len().cmp(&b.label.len())));
+    hits.truncate(limit);
+    hits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::service::{epics::create_epic, stories::create_story};
+    use crate::users::User;
+
+    #[test]
+    fn test_quick_open() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let epic_uuid = create_epic(&mut state, "Onboarding", String::new(), &SystemClock).unwrap();
+        let redesign = create_story(&mut state, epic_uuid, "Login page redesign", String::new(), &SystemClock).unwrap();
+        create_story(&mut state, epic_uuid, "Long running migration", String::new(), &SystemClock).unwrap();
+        let signup = create_story(&mut state, epic_uuid, "Signup", String::new(), &SystemClock).unwrap();
+
+        let hits = quick_open(&state, "lgn pg", 10);
+        assert_eq!(hits[0], QuickOpenHit { item: ItemRef::Story(redesign), label: "STORY-1 Login page redesign".to_string(), score: hits[0].score });
+        assert!(hits.iter().all(|hit| hit.item != ItemRef::Story(signup)));
+        assert_eq!(quick_open(&state, "story-3", 10)[0].item, ItemRef::Story(signup));
+        assert_eq!(quick_open(&state, "epic", 10).len(), 1);
+        assert!(quick_open(&state, "zzz", 10).is_empty());
+        assert!(quick_open(&state, " ", 10).is_empty());
+    }
+}
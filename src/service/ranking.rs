@@ -0,0 +1,197 @@
+//! Ranking operations: arranging epics, and the stories within each epic, in a hand-picked order.
+//!
+//! Epics are ranked against all other epics; stories against the other stories of the same epic
+//! (or, for stories outside any epic, against each other). Items with equal ranks, such as those
+//! from databases saved before ranking existed, keep their stored order.
+
+use super::{ServiceError, get_epic, get_story, require, update_epic, update_story};
+use crate::clock::Clock;
+use crate::db::ClearTextDBState;
+use crate::models::roles::Permission;
+use crate::models::{epics::Epic, stories::Story};
+use uuid::Uuid;
+
+/// Lists all epics not in the trash in rank order.
+#[must_use]
+pub fn ranked_epics(state: &ClearTextDBState) -> Vec<&Epic> {
+    let mut epics: Vec<&Epic> = state.epics.iter().filter(|epic| !epic.is_deleted()).collect();
+    epics.sort_by_key(|epic| epic.rank);
+    epics
+}
+
+/// Lists a story and its siblings not in the trash in rank order.
+///
+/// # Errors
+/// * `ServiceError::StoryNotFound` - If the story does not exist.
+pub fn ranked_siblings(state: &ClearTextDBState, story_uuid: Uuid) -> Result<Vec<&Story>, ServiceError> {
+    get_story(state, story_uuid)?;
+    let parent = parent_epic(state, story_uuid);
+    let mut stories: Vec<&Story> = state.stories.iter().filter(|story| !story.is_deleted() && parent_epic(state, story.story_uuid) == parent).collect();
+    stories.sort_by_key(|story| story.rank);
+    Ok(stories)
+}
+
+/// Lists an epic's stories not in the trash in rank order.
+///
+/// # Errors
+/// * `ServiceError::EpicNotFound` - If the epic does not exist.
+pub fn ranked_stories(state: &ClearTextDBState, epic_uuid: Uuid) -> Result<Vec<&Story>, ServiceError> {
+    let epic = get_epic(state, epic_uuid)?;
+    let mut stories: Vec<&Story> = state.stories.iter().filter(|story| !story.is_deleted() && epic.story_uuids.contains(&story.story_uuid)).collect();
+    stories.sort_by_key(|story| story.rank);
+    Ok(stories)
+}
+
+/// Returns the rank that places a new epic after all existing ones.
+#[must_use]
+pub fn next_epic_rank(state: &ClearTextDBState) -> u32 {
+    state.epics.iter().map(|epic| epic.rank + 1).max().unwrap_or(0)
+}
+
+/// Returns the rank that places a new story after all existing stories of an epic.
+///
+/// # Errors
+/// * `ServiceError::EpicNotFound` - If the epic does not exist.
+pub fn next_story_rank(state: &ClearTextDBState, epic_uuid: Uuid) -> Result<u32, ServiceError> {
+    Ok(ranked_stories(state, epic_uuid)?.iter().map(|story| story.rank + 1).max().unwrap_or(0))
+}
+
+/// Moves an epic to `position` (0-based) in the ranked list, clamped to the end of the list.
+///
+/// # Errors
+/// * `ServiceError::EpicNotFound` - If the epic does not exist.
+pub fn move_epic_to(state: &mut ClearTextDBState, epic_uuid: Uuid, position: usize, clock: &dyn Clock) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    get_epic(state, epic_uuid)?;
+    let order = reordered(ranked_epics(state).iter().map(|epic| epic.epic_uuid).collect(), epic_uuid, position);
+    for (rank, uuid) in (0..).zip(order) {
+        update_epic(state, uuid, clock, |epic| epic.rank = rank)?;
+    }
+    Ok(())
+}
+
+/// Moves an epic one place up; does nothing if it is already first.
+///
+/// # Errors
+/// * `ServiceError::EpicNotFound` - If the epic does not exist.
+pub fn move_epic_up(state: &mut ClearTextDBState, epic_uuid: Uuid, clock: &dyn Clock) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    let position = epic_position(state, epic_uuid)?;
+    move_epic_to(state, epic_uuid, position.saturating_sub(1), clock)
+}
+
+/// Moves an epic one place down; does nothing if it is already last.
+///
+/// # Errors
+/// * `ServiceError::EpicNotFound` - If the epic does not exist.
+pub fn move_epic_down(state: &mut ClearTextDBState, epic_uuid: Uuid, clock: &dyn Clock) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    let position = epic_position(state, epic_uuid)?;
+    move_epic_to(state, epic_uuid, position + 1, clock)
+}
+
+/// Moves a story to `position` (0-based) among its siblings, clamped to the end of the list.
+///
+/// # Errors
+/// * `ServiceError::StoryNotFound` - If the story does not exist.
+pub fn move_story_to(state: &mut ClearTextDBState, story_uuid: Uuid, position: usize, clock: &dyn Clock) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    let order = reordered(ranked_siblings(state, story_uuid)?.iter().map(|story| story.story_uuid).collect(), story_uuid, position);
+    for (rank, uuid) in (0..).zip(order) {
+        update_story(state, uuid, clock, |story| story.rank = rank)?;
+    }
+    Ok(())
+}
+
+/// Moves a story one place up among its siblings; does nothing if it is already first.
+///
+/// # Errors
+/// * `ServiceError::StoryNotFound` - If the story does not exist.
+pub fn move_story_up(state: &mut ClearTextDBState, story_uuid: Uuid, clock: &dyn Clock) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    let position = story_position(state, story_uuid)?;
+    move_story_to(state, story_uuid, position.saturating_sub(1), clock)
+}
+
+/// Moves a story one place down among its siblings; does nothing if it is already last.
+///
+/// # Errors
+/// * `ServiceError::StoryNotFound` - If the story does not exist.
+pub fn move_story_down(state: &mut ClearTextDBState, story_uuid: Uuid, clock: &dyn Clock) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    let position = story_position(state, story_uuid)?;
+    move_story_to(state, story_uuid, position + 1, clock)
+}
+
+/// Returns the epic containing a story, if any.
+pub(super) fn parent_epic(state: &ClearTextDBState, story_uuid: Uuid) -> Option<Uuid> {
+    state.epics.iter().find(|epic| epic.story_uuids.contains(&story_uuid)).map(|epic| epic.epic_uuid)
+}
+
+/// Returns an epic's index in the ranked list.
+fn epic_position(state: &ClearTextDBState, epic_uuid: Uuid) -> Result<usize, ServiceError> {
+    ranked_epics(state)
+        .iter()
+        .position(|epic| epic.epic_uuid == epic_uuid)
+        .ok_or(ServiceError::EpicNotFound(epic_uuid))
+}
+
+/// Returns a story's index among its ranked siblings.
+fn story_position(state: &ClearTextDBState, story_uuid: Uuid) -> Result<usize, ServiceError> {
+    ranked_siblings(state, story_uuid)?
+        .iter()
+        .position(|story| story.story_uuid == story_uuid)
+        .ok_or(ServiceError::StoryNotFound(story_uuid))
+}
+
+/// Moves `uuid` to `position` within `order`, clamping to the end.
+fn reordered(mut order: Vec<Uuid>, uuid: Uuid, position: usize) -> Vec<Uuid> {
+    order.retain(|other| *other != uuid);
+    order.insert(position.min(order.len()), uuid);
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::service::{epics::create_epic, stories::create_story};
+    use crate::users::User;
+
+    /// Lists the titles of a ranked list.
+    fn titles<'a>(items: impl IntoIterator<Item = &'a Story>) -> Vec<&'a str> {
+        items.into_iter().map(|story| story.title.as_str()).collect()
+    }
+
+    #[test]
+    fn test_move_stories_within_epic() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let epic_uuid = create_epic(&mut state, "Epic", String::new(), &SystemClock).unwrap();
+        let a = create_story(&mut state, epic_uuid, "a", String::new(), &SystemClock).unwrap();
+        create_story(&mut state, epic_uuid, "b", String::new(), &SystemClock).unwrap();
+        let c = create_story(&mut state, epic_uuid, "c", String::new(), &SystemClock).unwrap();
+        let other_epic = create_epic(&mut state, "Other", String::new(), &SystemClock).unwrap();
+        create_story(&mut state, other_epic, "x", String::new(), &SystemClock).unwrap();
+        assert_eq!(titles(ranked_stories(&state, epic_uuid).unwrap()), ["a", "b", "c"]);
+
+        move_story_up(&mut state, c, &SystemClock).unwrap();
+        assert_eq!(titles(ranked_stories(&state, epic_uuid).unwrap()), ["a", "c", "b"]);
+        move_story_up(&mut state, a, &SystemClock).unwrap();
+        move_story_down(&mut state, a, &SystemClock).unwrap();
+        assert_eq!(titles(ranked_stories(&state, epic_uuid).unwrap()), ["c", "a", "b"]);
+        move_story_to(&mut state, c, 10, &SystemClock).unwrap();
+        assert_eq!(titles(ranked_stories(&state, epic_uuid).unwrap()), ["a", "b", "c"]);
+        assert_eq!(titles(ranked_stories(&state, other_epic).unwrap()), ["x"]);
+    }
+
+    #[test]
+    fn test_move_epics() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let first = create_epic(&mut state, "First", String::new(), &SystemClock).unwrap();
+        let second = create_epic(&mut state, "Second", String::new(), &SystemClock).unwrap();
+        move_epic_down(&mut state, first, &SystemClock).unwrap();
+        let order: Vec<Uuid> = ranked_epics(&state).iter().map(|epic| epic.epic_uuid).collect();
+        assert_eq!(order, vec![second, first]);
+        assert!(move_epic_up(&mut state, Uuid::new_v4(), &SystemClock).is_err());
+    }
+}
@@ -0,0 +1,90 @@
+//! Reference operations: looking up epics and stories by UUID or by short ID (`EPIC-12`,
+//! `STORY-34`), as typed by the user.
+
+use super::{ServiceError, get_epic, get_story};
+use crate::db::ClearTextDBState;
+use crate::models::{epics::Epic, stories::Story};
+use uuid::Uuid;
+
+/// # Item Reference enum
+/// An epic or story that a piece of user input refers to.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum ItemRef {
+    /// An epic, by UUID.
+    Epic(Uuid),
+    /// A story, by UUID.
+    Story(Uuid),
+}
+
+/// Resolves a UUID or short ID (case-insensitive, e.g. `story-34`) to an epic or story.
+///
+/// # Errors
+/// * `ServiceError::UnknownReference` - If no epic or story matches.
+pub fn resolve(state: &ClearTextDBState, reference: &str) -> Result<ItemRef, ServiceError> {
+    let reference = reference.trim();
+    let unknown = || ServiceError::UnknownReference(reference.to_string());
+    if let Ok(uuid) = Uuid::parse_str(reference) {
+        return if get_epic(state, uuid).is_ok() {
+            Ok(ItemRef::Epic(uuid))
+        } else if get_story(state, uuid).is_ok() {
+            Ok(ItemRef::Story(uuid))
+        } else {
+            Err(unknown())
+        };
+    }
+    let (prefix, number) = reference.split_once('-').ok_or_else(unknown)?;
+    let number: u32 = number.parse().map_err(|_| unknown())?;
+    if prefix.eq_ignore_ascii_case("EPIC") {
+        state.epics.iter().find(|epic| epic.number == number).map(|epic| ItemRef::Epic(epic.epic_uuid)).ok_or_else(unknown)
+    } else if prefix.eq_ignore_ascii_case("STORY") {
+        state.stories.iter().find(|story| story.number == number).map(|story| ItemRef::Story(story.story_uuid)).ok_or_else(unknown)
+    } else {
+        Err(unknown())
+    }
+}
+
+/// Looks up an epic by UUID or short ID.
+///
+/// # Errors
+/// * `ServiceError::UnknownReference` - If no epic matches.
+pub fn find_epic<'a>(state: &'a ClearTextDBState, reference: &str) -> Result<&'a Epic, ServiceError> {
+    match resolve(state, reference)? {
+        ItemRef::Epic(uuid) => get_epic(state, uuid),
+        ItemRef::Story(_) => Err(ServiceError::UnknownReference(reference.trim().to_string())),
+    }
+}
+
+/// Looks up a story by UUID or short ID.
+///
+/// # Errors
+/// * `ServiceError::UnknownReference` - If no story matches.
+pub fn find_story<'a>(state: &'a ClearTextDBState, reference: &str) -> Result<&'a Story, ServiceError> {
+    match resolve(state, reference)? {
+        ItemRef::Story(uuid) => get_story(state, uuid),
+        ItemRef::Epic(_) => Err(ServiceError::UnknownReference(reference.trim().to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::service::{epics::create_epic, stories::create_story};
+    use crate::users::User;
+
+    #[test]
+    fn test_resolve_short_ids_and_uuids() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let epic_uuid = create_epic(&mut state, "Epic", String::new(), &SystemClock).unwrap();
+        create_story(&mut state, epic_uuid, "First", String::new(), &SystemClock).unwrap();
+        let second = create_story(&mut state, epic_uuid, "Second", String::new(), &SystemClock).unwrap();
+
+        assert_eq!(get_story(&state, second).unwrap().key(), "STORY-2");
+        assert_eq!(resolve(&state, "EPIC-1").unwrap(), ItemRef::Epic(epic_uuid));
+        assert_eq!(find_story(&state, " story-2 ").unwrap().story_uuid, second);
+        assert_eq!(find_story(&state, &second.to_string()).unwrap().title, "Second");
+        assert!(find_epic(&state, "STORY-1").is_err());
+        assert_eq!(resolve(&state, "EPIC-9"), Err(ServiceError::UnknownReference("EPIC-9".to_string())));
+        assert!(resolve(&state, "TASK-1").is_err());
+    }
+}
@@ -0,0 +1,111 @@
+//! Reminder operations: finding the epics and stories that are overdue or coming due soon, for the
+//! reminders shown when a database is unlocked.
+
+use super::{archive::{active_epics, active_stories}, references::ItemRef};
+use crate::clock::Clock;
+use crate::db::ClearTextDBState;
+use crate::models::Scheduled;
+use chrono::{Duration, NaiveDate};
+
+/// How many days ahead reminders look by default.
+pub const DEFAULT_REMINDER_DAYS: i64 = 3;
+
+/// # Urgency enum
+/// How pressing a reminder is.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Urgency {
+    /// The due date has passed.
+    Overdue,
+    /// Due today.
+    DueToday,
+    /// Due within the look-ahead window.
+    DueSoon,
+}
+
+/// # Reminder struct
+/// An epic or story that is overdue or coming due.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Reminder {
+    /// The item.
+    pub item: ItemRef,
+    /// The item's short ID and title, e.g. `STORY-12 Login page`.
+    pub label: String,
+    /// When the item is due.
+    pub due_date: NaiveDate,
+    /// How pressing it is.
+    pub urgency: Urgency,
+}
+
+/// # Reminders
+/// Lists the epics and stories that aren't done, archived, or deleted and are overdue or due
+/// within `days` days.
+///
+/// # Arguments
+/// * `state` - The database state to read.
+/// * `days` - How many days ahead to look; 0 only covers today.
+/// * `clock` - Gives today's date.
+///
+/// # Returns
+/// * `Vec<Reminder>` - The reminders, earliest due date first, epics before stories on the same day.
+#[must_use]
+pub fn reminders(state: &ClearTextDBState, days: i64, clock: &dyn Clock) -> Vec<Reminder> {
+    let today = clock.now().date_naive();
+    let horizon = today + Duration::days(days.max(0));
+    let remind = |item: ItemRef, label: String, scheduled: &dyn Scheduled| {
+        let due_date = scheduled.due_date().filter(|due| *due <= horizon)?;
+        if scheduled.status().is_done(&state.statuses) {
+            return None;
+        }
+        let urgency = match due_date.cmp(&today) {
+            std::cmp::Ordering::Less => Urgency::Overdue,
+            std::cmp::Ordering::Equal => Urgency::DueToday,
+            std::cmp::Ordering::Greater => Urgency::DueSoon,
+        };
+        Some(Reminder { item, label, due_date, urgency })
+    };
+    let epics = active_epics(state)
+        .into_iter()
+        .filter_map(|epic| remind(ItemRef::Epic(epic.epic_uuid), format!("{} {}", epic.key(), epic.title), epic));
+    let stories = active_stories(state)
+        .into_iter()
+        .filter_map(|story| remind(ItemRef::Story(story.story_uuid), format!("{} {}", story.key(), story.title), story));
+    let mut found: Vec<Reminder> = epics.chain(stories).collect();
+    found.sort_by_key(|reminder| reminder.due_date);
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+    use crate::models::Status;
+    use crate::service::{
+        epics::create_epic,
+        schedule::{set_epic_dates, set_story_dates},
+        stories::{create_story, set_story_status},
+    };
+    use crate::users::User;
+    use chrono::DateTime;
+
+    #[test]
+    fn test_reminders() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let clock = FixedClock::new(DateTime::from_timestamp(1_700_000_000, 0).unwrap());
+        let today = clock.now().date_naive();
+        let epic_uuid = create_epic(&mut state, "Launch", String::new(), &clock).unwrap();
+        set_epic_dates(&mut state, epic_uuid, None, Some(today + Duration::days(3)), &clock).unwrap();
+        let late = create_story(&mut state, epic_uuid, "Late", String::new(), &clock).unwrap();
+        let done = create_story(&mut state, epic_uuid, "Done", String::new(), &clock).unwrap();
+        let later = create_story(&mut state, epic_uuid, "Later", String::new(), &clock).unwrap();
+        set_story_dates(&mut state, late, None, Some(today - Duration::days(1)), &clock).unwrap();
+        set_story_dates(&mut state, done, None, Some(today), &clock).unwrap();
+        set_story_dates(&mut state, later, None, Some(today + Duration::days(10)), &clock).unwrap();
+        set_story_status(&mut state, done, Status::Closed, &clock).unwrap();
+
+        let found = reminders(&state, DEFAULT_REMINDER_DAYS, &clock);
+        assert_eq!(found.iter().map(|reminder| reminder.item).collect::<Vec<_>>(), vec![ItemRef::Story(late), ItemRef::Epic(epic_uuid)]);
+        assert_eq!((found[0].label.as_str(), found[0].urgency), ("STORY-1 Late", Urgency::Overdue));
+        assert_eq!(found[1].urgency, Urgency::DueSoon);
+        assert_eq!(reminders(&state, 0, &clock).len(), 1);
+    }
+}
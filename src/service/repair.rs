@@ -0,0 +1,190 @@
+//! Repair operations: finding and fixing broken links between epics and their stories, such as
+//! stories that belong to no epic, which can be left behind by older versions or edited files.
+
+use super::{ServiceError, epics::create_epic, ranking::next_story_rank, require, update_epic, update_story};
+use crate::clock::Clock;
+use crate::db::ClearTextDBState;
+use crate::models::roles::Permission;
+use std::fmt;
+use uuid::Uuid;
+
+/// The title of the epic that [`repair`] moves stories belonging to no epic into.
+pub const RECOVERED_EPIC_TITLE: &str = "Recovered stories";
+
+/// # Inconsistency enum
+/// A broken link between epics and stories.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Inconsistency {
+    /// A story that no epic lists.
+    OrphanStory {
+        /// The story.
+        story_uuid: Uuid,
+    },
+    /// An epic listing a story that doesn't exist.
+    MissingStory {
+        /// The epic.
+        epic_uuid: Uuid,
+        /// The story it lists.
+        story_uuid: Uuid,
+    },
+    /// A story listed more than once, by one epic or by several.
+    DuplicateMembership {
+        /// The story.
+        story_uuid: Uuid,
+        /// The epics listing it, once per listing, in stored order.
+        epic_uuids: Vec<Uuid>,
+    },
+}
+
+impl fmt::Display for Inconsistency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Inconsistency::OrphanStory { story_uuid } => write!(f, "story {story_uuid} belongs to no epic"),
+            Inconsistency::MissingStory { epic_uuid, story_uuid } => write!(f, "epic {epic_uuid} lists missing story {story_uuid}"),
+            Inconsistency::DuplicateMembership { story_uuid, epic_uuids } => write!(f, "story {story_uuid} is listed {} times", epic_uuids.len()),
+        }
+    }
+}
+
+/// # Find Inconsistencies
+/// Checks every epic's story list against the stories in the database.
+///
+/// # Returns
+/// * `Vec<Inconsistency>` - Every problem found: missing stories and duplicate listings in epic
+///   order, then stories belonging to no epic.
+#[must_use]
+pub fn find_inconsistencies(state: &ClearTextDBState) -> Vec<Inconsistency> {
+    let mut found = Vec::new();
+    let mut listings: Vec<(Uuid, Vec<Uuid>)> = Vec::new();
+    for epic in &state.epics {
+        for story_uuid in &epic.story_uuids {
+            if !state.stories.iter().any(|story| story.story_uuid == *story_uuid) {
+                found.push(Inconsistency::MissingStory { epic_uuid: epic.epic_uuid, story_uuid: *story_uuid });
+            } else if let Some((_, epic_uuids)) = listings.iter_mut().find(|(uuid, _)| uuid == story_uuid) {
+                epic_uuids.push(epic.epic_uuid);
+            } else {
+                listings.push((*story_uuid, vec![epic.epic_uuid]));
+            }
+        }
+    }
+    found.extend(
+        listings
+            .iter()
+            .filter(|(_, epic_uuids)| epic_uuids.len() > 1)
+            .map(|(story_uuid, epic_uuids)| Inconsistency::DuplicateMembership { story_uuid: *story_uuid, epic_uuids: epic_uuids.clone() }),
+    );
+    found.extend(
+        state
+            .stories
+            .iter()
+            .filter(|story| !listings.iter().any(|(story_uuid, _)| *story_uuid == story.story_uuid))
+            .map(|story| Inconsistency::OrphanStory { story_uuid: story.story_uuid }),
+    );
+    found
+}
+
+/// # Repair
+/// Fixes one inconsistency:
+/// * a story belonging to no epic is moved to the end of the [`RECOVERED_EPIC_TITLE`] epic,
+///   which is created if needed;
+/// * a missing story is removed from the epic listing it;
+/// * a story listed more than once is kept only at its first listing.
+///
+/// # Errors
+/// * `ServiceError::EpicNotFound` / `ServiceError::StoryNotFound` - If the inconsistency is out of
+///   date and names an item that no longer exists.
+pub fn repair(state: &mut ClearTextDBState, inconsistency: &Inconsistency, clock: &dyn Clock) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    match inconsistency {
+        Inconsistency::OrphanStory { story_uuid } => {
+            let recovered = state.epics.iter().find(|epic| epic.title == RECOVERED_EPIC_TITLE && !epic.is_deleted()).map(|epic| epic.epic_uuid);
+            let epic_uuid = match recovered {
+                Some(epic_uuid) => epic_uuid,
+                None => create_epic(state, RECOVERED_EPIC_TITLE, String::new(), clock)?,
+            };
+            let rank = next_story_rank(state, epic_uuid)?;
+            update_story(state, *story_uuid, clock, |story| story.rank = rank)?;
+            update_epic(state, epic_uuid, clock, |epic| epic.story_uuids.push(*story_uuid))
+        }
+        Inconsistency::MissingStory { epic_uuid, story_uuid } => {
+            update_epic(state, *epic_uuid, clock, |epic| epic.story_uuids.retain(|uuid| uuid != story_uuid))
+        }
+        Inconsistency::DuplicateMembership { story_uuid, epic_uuids } => {
+            let mut kept = false;
+            for epic_uuid in epic_uuids.iter().enumerate().filter(|(index, uuid)| !epic_uuids[..*index].contains(uuid)).map(|(_, uuid)| *uuid) {
+                update_epic(state, epic_uuid, clock, |epic| {
+                    epic.story_uuids.retain(|uuid| {
+                        let keep = uuid != story_uuid || !kept;
+                        kept |= uuid == story_uuid;
+                        keep
+                    });
+                })?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// # Repair All
+/// Finds the current inconsistencies and repairs each one `confirm` accepts. Pass `|_| true` to
+/// repair everything, or a prompt to let the user decide one by one.
+///
+/// # Returns
+/// * `Result<Vec<Inconsistency>, ServiceError>` - The inconsistencies that were repaired.
+/// # Errors
+/// * `ServiceError` - If a repair fails; earlier repairs are kept.
+pub fn repair_all(state: &mut ClearTextDBState, mut confirm: impl FnMut(&Inconsistency) -> bool, clock: &dyn Clock) -> Result<Vec<Inconsistency>, ServiceError> {
+    require(state, Permission::Edit)?;
+    let mut repaired = Vec::new();
+    for inconsistency in find_inconsistencies(state) {
+        if confirm(&inconsistency) {
+            repair(state, &inconsistency, clock)?;
+            repaired.push(inconsistency);
+        }
+    }
+    Ok(repaired)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::models::stories::Story;
+    use crate::service::{get_epic, stories::create_story};
+    use crate::users::User;
+
+    #[test]
+    fn test_find_and_repair_inconsistencies() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let first = create_epic(&mut state, "First", String::new(), &SystemClock).unwrap();
+        let second = create_epic(&mut state, "Second", String::new(), &SystemClock).unwrap();
+        let shared = create_story(&mut state, first, "Shared", String::new(), &SystemClock).unwrap();
+        let orphan = Story::new("Orphan".to_string(), String::new());
+        let (orphan_uuid, missing) = (orphan.story_uuid, Uuid::new_v4());
+        state.stories.push(orphan);
+        state.epics[0].story_uuids.push(shared);
+        state.epics[1].story_uuids.extend([missing, shared]);
+        assert!(find_inconsistencies(&ClearTextDBState::new(User::new("bob".to_string(), "password").unwrap())).is_empty());
+
+        let found = find_inconsistencies(&state);
+        assert_eq!(
+            found,
+            vec![
+                Inconsistency::MissingStory { epic_uuid: second, story_uuid: missing },
+                Inconsistency::DuplicateMembership { story_uuid: shared, epic_uuids: vec![first, first, second] },
+                Inconsistency::OrphanStory { story_uuid: orphan_uuid },
+            ]
+        );
+        assert_eq!(found[1].to_string(), format!("story {shared} is listed 3 times"));
+
+        let repaired = repair_all(&mut state, |inconsistency| !matches!(inconsistency, Inconsistency::OrphanStory { .. }), &SystemClock).unwrap();
+        assert_eq!(repaired.len(), 2);
+        assert_eq!(get_epic(&state, first).unwrap().story_uuids, vec![shared]);
+        assert!(get_epic(&state, second).unwrap().story_uuids.is_empty());
+
+        repair_all(&mut state, |_| true, &SystemClock).unwrap();
+        let recovered = state.epics.iter().find(|epic| epic.title == RECOVERED_EPIC_TITLE).unwrap();
+        assert_eq!(recovered.story_uuids, vec![orphan_uuid]);
+        assert!(find_inconsistencies(&state).is_empty());
+    }
+}
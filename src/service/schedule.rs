@@ -0,0 +1,136 @@
+//! Scheduling operations: setting start and due dates, and sorting and filtering epics and stories
+//! by them.
+
+use super::{ServiceError, require, update_epic, update_story};
+use crate::clock::Clock;
+use crate::db::ClearTextDBState;
+use crate::models::roles::Permission;
+use crate::models::{Scheduled, statuses::StatusDefinition, validation::validate_date_range};
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+/// Sets (or clears) an epic's start and due dates.
+///
+/// # Errors
+/// * `ServiceError::Validation` - If both dates are set and the start is after the due date.
+/// * `ServiceError::EpicNotFound` - If the epic does not exist.
+pub fn set_epic_dates(
+    state: &mut ClearTextDBState,
+    epic_uuid: Uuid,
+    start_date: Option<NaiveDate>,
+    due_date: Option<NaiveDate>,
+    clock: &dyn Clock,
+) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    validate_date_range(start_date, due_date)?;
+    update_epic(state, epic_uuid, clock, |epic| {
+        epic.start_date = start_date;
+        epic.due_date = due_date;
+    })
+}
+
+/// Sets (or clears) a story's start and due dates.
+///
+/// # Errors
+/// * `ServiceError::Validation` - If both dates are set and the start is after the due date.
+/// * `ServiceError::StoryNotFound` - If the story does not exist.
+pub fn set_story_dates(
+    state: &mut ClearTextDBState,
+    story_uuid: Uuid,
+    start_date: Option<NaiveDate>,
+    due_date: Option<NaiveDate>,
+    clock: &dyn Clock,
+) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    validate_date_range(start_date, due_date)?;
+    update_story(state, story_uuid, clock, |story| {
+        story.start_date = start_date;
+        story.due_date = due_date;
+    })
+}
+
+/// Returns the items that are past due and not done.
+///
+/// # Examples
+/// ```rust
+/// use chrono::NaiveDate;
+/// use ironyyy::models::{statuses::default_statuses, stories::Story};
+/// use ironyyy::service::schedule::overdue;
+/// let mut story = Story::new("Ship it".to_string(), String::new());
+/// story.due_date = NaiveDate::from_ymd_opt(2024, 3, 1);
+/// let stories = vec![story];
+/// let statuses = default_statuses();
+/// assert_eq!(overdue(&stories, NaiveDate::from_ymd_opt(2024, 3, 2).unwrap(), &statuses).len(), 1);
+/// assert!(overdue(&stories, NaiveDate::from_ymd_opt(2024, 3, 1).unwrap(), &statuses).is_empty());
+/// ```
+#[must_use]
+pub fn overdue<'a, T: Scheduled>(items: &'a [T], today: NaiveDate, statuses: &[StatusDefinition]) -> Vec<&'a T> {
+    items.iter().filter(|item| item.is_overdue(today, statuses)).collect()
+}
+
+/// Returns the items due within `from..=to`, in due-date order.
+#[must_use]
+pub fn due_between<T: Scheduled>(items: &[T], from: NaiveDate, to: NaiveDate) -> Vec<&T> {
+    let mut due: Vec<&T> = items
+        .iter()
+        .filter(|item| item.due_date().is_some_and(|date| (from..=to).contains(&date)))
+        .collect();
+    sort_by_due_date(&mut due);
+    due
+}
+
+/// Sorts items by due date, earliest first; items without a due date go last.
+///
+/// The sort is stable, so items sharing a due date keep their relative order.
+pub fn sort_by_due_date<T: Scheduled>(items: &mut [&T]) {
+    items.sort_by_key(|item| (item.due_date().is_none(), item.due_date()));
+}
+
+/// Sorts items by start date, earliest first; items without a start date go last.
+pub fn sort_by_start_date<T: Scheduled>(items: &mut [&T]) {
+    items.sort_by_key(|item| (item.start_date().is_none(), item.start_date()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::models::{Status, stories::Story, validation::ValidationError};
+    use crate::users::User;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, day).unwrap()
+    }
+
+    #[test]
+    fn test_dates_sorting_and_overdue() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let uuids: Vec<Uuid> = ["late", "undated", "early", "closed"]
+            .into_iter()
+            .map(|title| {
+                let story = Story::new(title.to_string(), String::new());
+                let uuid = story.story_uuid;
+                state.stories.push(story);
+                uuid
+            })
+            .collect();
+
+        assert_eq!(
+            set_story_dates(&mut state, uuids[0], Some(date(20)), Some(date(10)), &SystemClock),
+            Err(ValidationError::InvalidDateRange.into())
+        );
+        set_story_dates(&mut state, uuids[0], Some(date(1)), Some(date(20)), &SystemClock).unwrap();
+        set_story_dates(&mut state, uuids[2], None, Some(date(5)), &SystemClock).unwrap();
+        set_story_dates(&mut state, uuids[3], None, Some(date(1)), &SystemClock).unwrap();
+        state.stories[3].status = Status::Closed;
+
+        let mut sorted: Vec<&Story> = state.stories.iter().collect();
+        sort_by_due_date(&mut sorted);
+        let titles: Vec<&str> = sorted.iter().map(|story| story.title.as_str()).collect();
+        assert_eq!(titles, ["closed", "early", "late", "undated"]);
+
+        let overdue_titles: Vec<&str> = overdue(&state.stories, date(15), &state.statuses).iter().map(|story| story.title.as_str()).collect();
+        assert_eq!(overdue_titles, ["early"]);
+        assert_eq!(due_between(&state.stories, date(2), date(20)).len(), 2);
+    }
+}
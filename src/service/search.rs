@@ -0,0 +1,163 @@
+//! Search operations: an in-memory full-text index over epics and stories, built from the
+//! decrypted database when it is unlocked.
+//!
+//! A story's searchable text is its title, description, checklist, task titles, and work log
+//! notes; an epic's is its title and description. Words match by prefix, so `log` finds "login".
+
+use crate::db::ClearTextDBState;
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+/// # Search Hit struct
+/// An item matching a search, with how well it matched.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct SearchHit {
+    /// The matching epic or story.
+    pub item_uuid: Uuid,
+    /// How many times the searched words occur in the item; higher is better.
+    pub score: usize,
+}
+
+/// # Search Index struct
+/// Maps every word in the database to the items containing it.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SearchIndex {
+    /// Each word, with the items containing it and how often.
+    postings: BTreeMap<String, HashMap<Uuid, usize>>,
+    /// Each indexed item's words, so the item can be re-indexed.
+    items: HashMap<Uuid, Vec<String>>,
+}
+
+impl SearchIndex {
+    /// Indexes every epic and story in the database.
+    #[must_use]
+    pub fn build(state: &ClearTextDBState) -> Self {
+        let mut index = Self::default();
+        for epic in &state.epics {
+            index.reindex(state, epic.epic_uuid);
+        }
+        for story in &state.stories {
+            index.reindex(state, story.story_uuid);
+        }
+        index
+    }
+
+    /// Brings one epic or story up to date after it changed, or drops it if it no longer exists.
+    pub fn reindex(&mut self, state: &ClearTextDBState, item_uuid: Uuid) {
+        for word in self.items.remove(&item_uuid).unwrap_or_default() {
+            if let Some(postings) = self.postings.get_mut(&word) {
+                postings.remove(&item_uuid);
+                if postings.is_empty() {
+                    self.postings.remove(&word);
+                }
+            }
+        }
+        let mut words = tokenize(&searchable_text(state, item_uuid));
+        for word in &words {
+            *self.postings.entry(word.clone()).or_default().entry(item_uuid).or_default() += 1;
+        }
+        words.sort();
+        words.dedup();
+        if !words.is_empty() {
+            self.items.insert(item_uuid, words);
+        }
+    }
+
+    /// Finds the items containing every word of `query` (each as a word prefix), best match
+    /// first. Items in the trash are left out.
+    #[must_use]
+    pub fn search(&self, state: &ClearTextDBState, query: &str) -> Vec<SearchHit> {
+        let mut words = tokenize(query);
+        words.sort();
+        words.dedup();
+        let Some((first, rest)) = words.split_first() else {
+            return Vec::new();
+        };
+        let mut scores = self.prefix_counts(first);
+        for word in rest {
+            let counts = self.prefix_counts(word);
+            scores.retain(|item_uuid, score| match counts.get(item_uuid) {
+                Some(count) => {
+                    *score += count;
+                    true
+                }
+                None => false,
+            });
+        }
+        let deleted = |item_uuid: &Uuid| {
+            state.epics.iter().any(|epic| epic.epic_uuid == *item_uuid && epic.is_deleted())
+                || state.stories.iter().any(|story| story.story_uuid == *item_uuid && story.is_deleted())
+        };
+        let mut hits: Vec<SearchHit> = scores
+            .into_iter()
+            .filter(|(item_uuid, _)| !deleted(item_uuid))
+            .map(|(item_uuid, score)| SearchHit { item_uuid, score })
+            .collect();
+        hits.sort_by(|a, b| b.score.cmp(&a.score).then(a.item_uuid.cmp(&b.item_uuid)));
+        hits
+    }
+
+    /// Counts, per item, the occurrences of every indexed word starting with `prefix`.
+    fn prefix_counts(&self, prefix: &str) -> HashMap<Uuid, usize> {
+        let mut counts = HashMap::new();
+        for (_, postings) in self.postings.range(prefix.to_string()..).take_while(|(word, _)| word.starts_with(prefix)) {
+            for (item_uuid, count) in postings {
+                *counts.entry(*item_uuid).or_default() += count;
+            }
+        }
+        counts
+    }
+}
+
+/// Collects the text an epic or story is found by.
+fn searchable_text(state: &ClearTextDBState, item_uuid: Uuid) -> String {
+    if let Some(epic) = state.epics.iter().find(|epic| epic.epic_uuid == item_uuid) {
+        return format!("{} {}", epic.title, epic.description);
+    }
+    let Some(story) = state.stories.iter().find(|story| story.story_uuid == item_uuid) else {
+        return String::new();
+    };
+    let checklist = story.checklist.iter().map(|item| item.text.as_str());
+    let tasks = state.tasks.iter().filter(|task| story.task_uuids.contains(&task.task_uuid)).map(|task| task.title.as_str());
+    let notes = state.work_logs.iter().filter(|work_log| work_log.story_uuid == item_uuid).map(|work_log| work_log.note.as_str());
+    [story.title.as_str(), story.description.as_str()].into_iter().chain(checklist).chain(tasks).chain(notes).collect::<Vec<_>>().join(" ")
+}
+
+/// Splits text into lowercase words of letters and digits.
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric()).filter(|word| !word.is_empty()).map(str::to_lowercase).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::service::{checklists::add_checklist_item, epics::create_epic, stories::{create_story, rename_story}, tasks::add_task, trash::soft_delete_story};
+    use crate::users::User;
+
+    #[test]
+    fn test_search() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let epic_uuid = create_epic(&mut state, "Accounts", "Everything about logging in".to_string(), &SystemClock).unwrap();
+        let login = create_story(&mut state, epic_uuid, "Login form", "Login with TOTP".to_string(), &SystemClock).unwrap();
+        let reset = create_story(&mut state, epic_uuid, "Password reset", String::new(), &SystemClock).unwrap();
+        add_task(&mut state, reset, "Email the login link", &SystemClock).unwrap();
+        add_checklist_item(&mut state, reset, "Expires after an hour".to_string(), &SystemClock).unwrap();
+        let index = SearchIndex::build(&state);
+
+        let uuids = |index: &SearchIndex, state: &ClearTextDBState, query: &str| index.search(state, query).iter().map(|hit| hit.item_uuid).collect::<Vec<_>>();
+        let hits = index.search(&state, "LOGIN");
+        assert_eq!(hits[0], SearchHit { item_uuid: login, score: 2 });
+        assert_eq!(hits.len(), 2);
+        assert_eq!(uuids(&index, &state, "log link"), vec![reset]);
+        assert_eq!(uuids(&index, &state, "expires"), vec![reset]);
+        assert!(index.search(&state, "  ").is_empty());
+
+        let mut index = index;
+        rename_story(&mut state, login, "Sign-in form", &SystemClock).unwrap();
+        index.reindex(&state, login);
+        assert_eq!(uuids(&index, &state, "sign"), vec![login]);
+        soft_delete_story(&mut state, login, &SystemClock).unwrap();
+        assert!(uuids(&index, &state, "sign").is_empty());
+    }
+}
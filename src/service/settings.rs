@@ -0,0 +1,40 @@
+//! Settings operations: changing the user's interface preferences. Any role may change them.
+
+use crate::db::ClearTextDBState;
+use crate::models::settings::{ListName, ListPreferences, Locale, ThemeName};
+
+/// Chooses the color theme of the line-based pages.
+pub fn set_theme(state: &mut ClearTextDBState, theme: ThemeName) {
+    state.settings.theme = theme;
+    state.mark_dirty();
+}
+
+/// Chooses whether to start in the full-screen interface.
+pub fn set_full_screen(state: &mut ClearTextDBState, full_screen: bool) {
+    state.settings.full_screen = full_screen;
+    state.mark_dirty();
+}
+
+/// Chooses how many minutes without input lock the session; 0 turns the auto-lock off.
+pub fn set_lock_after(state: &mut ClearTextDBState, minutes: u32) {
+    state.settings.lock_after_minutes = minutes;
+    state.mark_dirty();
+}
+
+/// Chooses the language of the pages.
+pub fn set_locale(state: &mut ClearTextDBState, locale: Locale) {
+    state.settings.locale = locale;
+    state.mark_dirty();
+}
+
+/// Turns the screen-reader-friendly output on or off.
+pub fn set_accessible(state: &mut ClearTextDBState, accessible: bool) {
+    state.settings.accessible = accessible;
+    state.mark_dirty();
+}
+
+/// Remembers how a list page was last sorted and filtered.
+pub fn set_list_preferences(state: &mut ClearTextDBState, name: ListName, preferences: ListPreferences) {
+    state.settings.lists.insert(name, preferences);
+    state.mark_dirty();
+}
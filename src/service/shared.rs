@@ -0,0 +1,187 @@
+//! Shared database operations: managing the members and saving, checked against the role the
+//! shared database was opened with. Only owners may manage the members or delete a shared
+//! database, there is always at least one owner, and viewers can't save.
+
+use super::{ServiceError, require};
+use crate::db::shared::{SharedSession, detect_shared};
+use crate::db::storage::Session;
+use crate::models::roles::{Permission, Role};
+use std::path::Path;
+use uuid::Uuid;
+
+/// Lets the user of `member` open the shared database with `role`. Their database must be
+/// unlocked, which proves their password, e.g. with [`Session::unlock`] while they type it on
+/// this machine. A member added again gets a fresh copy of the data key.
+///
+/// # Errors
+/// * `ServiceError::NotAllowed` - If the shared database wasn't opened by an owner.
+/// * `"The last owner must stay an owner"` - If `member` is the only owner and `role` isn't
+///   [`Role::Owner`].
+/// * `SecurityError` - If the data key can't be wrapped.
+pub fn add_member(shared: &mut SharedSession, member: &Session, role: Role) -> Result<(), Box<dyn std::error::Error>> {
+    require(&shared.state, Permission::ManageMembers)?;
+    if role != Role::Owner && shared.is_last_owner(member.state.user.user_uuid) {
+        return Err("The last owner must stay an owner".into());
+    }
+    shared.add_member(member, role)?;
+    Ok(())
+}
+
+/// Changes a member's role. The last owner can't be given another role.
+///
+/// # Returns
+/// * `Result<bool, ServiceError>` - Whether the user is a member and their role was changed.
+/// # Errors
+/// * `ServiceError::NotAllowed` - If the shared database wasn't opened by an owner.
+pub fn set_role(shared: &mut SharedSession, user_uuid: Uuid, role: Role) -> Result<bool, ServiceError> {
+    require(&shared.state, Permission::ManageMembers)?;
+    if role != Role::Owner && shared.is_last_owner(user_uuid) {
+        return Ok(false);
+    }
+    Ok(shared.set_role(user_uuid, role))
+}
+
+/// Stops a user from opening the shared database and replaces its data key. The last owner can't
+/// be removed.
+///
+/// # Returns
+/// * `Result<bool, Box<dyn std::error::Error>>` - Whether the user was a member and was
+///   removed.
+/// # Errors
+/// * `ServiceError::NotAllowed` - If the shared database wasn't opened by an owner.
+/// * `SecurityError` - If the new data key can't be generated or wrapped.
+pub fn remove_member(shared: &mut SharedSession, user_uuid: Uuid) -> Result<bool, Box<dyn std::error::Error>> {
+    require(&shared.state, Permission::ManageMembers)?;
+    if shared.is_last_owner(user_uuid) {
+        return Ok(false);
+    }
+    Ok(shared.remove_member(user_uuid)?)
+}
+
+/// Deletes the shared database file, for every member.
+///
+/// # Errors
+/// * `ServiceError::NotAllowed` - If the shared database wasn't opened by an owner.
+/// * `std::io::Error` - If the file exists but can't be removed.
+pub fn delete_shared(shared: SharedSession, databases_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    require(&shared.state, Permission::DeleteProject)?;
+    shared.delete(databases_dir)?;
+    Ok(())
+}
+
+/// Saves the shared database.
+///
+/// # Errors
+/// * `ServiceError::NotAllowed` - If it was opened by a viewer.
+/// * Same as [`SharedSession::save`].
+pub fn save_shared(shared: &mut SharedSession, databases_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    require(&shared.state, Permission::Edit)?;
+    shared.save(databases_dir)
+}
+
+/// Updates the username in the memberships of every shared database the logged-in user of
+/// `session` belongs to, e.g. after [`crate::users::change_username`]. Any member may rename
+/// themself, whatever their role.
+///
+/// # Errors
+/// * `std::io::Error` - If the shared folder can't be read.
+/// * Same as [`SharedSession::open`] and [`SharedSession::save`].
+pub fn rename_member(databases_dir: &Path, session: &Session) -> Result<(), Box<dyn std::error::Error>> {
+    let user = &session.state.user;
+    for (database_uuid, _) in detect_shared(databases_dir, user.user_uuid)? {
+        let mut shared = SharedSession::open(databases_dir, database_uuid, session)?;
+        if shared.set_username(user.user_uuid, &user.username) {
+            shared.save(databases_dir)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::db::ClearTextDBState;
+    use crate::db::shared::{read_shared, shared_path};
+    use crate::service::epics::create_epic;
+    use crate::service::projects::{create_project, delete_project, switch_project};
+    use crate::users::{User, change_password, change_username};
+
+    /// Creates and saves a user's own database.
+    fn session_for(databases_dir: &Path, name: &str) -> Session {
+        let mut session = Session::new(ClearTextDBState::new(User::new(name.to_string(), "a long enough password").unwrap()), "a long enough password").unwrap();
+        session.save(databases_dir).unwrap();
+        session
+    }
+
+    #[test]
+    fn test_membership_survives_password_and_username_changes() {
+        let databases_dir = std::env::temp_dir().join(format!("ironyyy-membership-{}", Uuid::new_v4()));
+        let (alice, mut bob) = (session_for(&databases_dir, "alice"), session_for(&databases_dir, "bob"));
+        let mut shared = SharedSession::create("Team".to_string(), &alice).unwrap();
+        add_member(&mut shared, &bob, Role::Viewer).unwrap();
+        save_shared(&mut shared, &databases_dir).unwrap();
+
+        change_password(&mut bob, &databases_dir, "a long enough password", "correct horse battery", &SystemClock).unwrap();
+        let bob_uuid = bob.state.user.user_uuid;
+        let mut bob = Session::unlock(&databases_dir, bob_uuid, "correct horse battery").unwrap();
+        assert!(SharedSession::open(&databases_dir, shared.database_uuid, &bob).is_ok());
+
+        change_username(&mut bob, &databases_dir, "robert", &SystemClock).unwrap();
+        let opened = SharedSession::open(&databases_dir, shared.database_uuid, &alice).unwrap();
+        assert!(opened.members().contains(&(bob_uuid, "robert".to_string(), Role::Viewer)));
+        std::fs::remove_dir_all(&databases_dir).unwrap();
+    }
+
+    #[test]
+    fn test_roles_limit_what_members_can_do() {
+        let databases_dir = std::env::temp_dir().join(format!("ironyyy-roles-{}", Uuid::new_v4()));
+        let (alice, bob, carol) = (session_for(&databases_dir, "alice"), session_for(&databases_dir, "bob"), session_for(&databases_dir, "carol"));
+        let mut shared = SharedSession::create("Team".to_string(), &alice).unwrap();
+        let project_uuid = create_project(&mut shared.state, "Website", String::new()).unwrap();
+        add_member(&mut shared, &bob, Role::Editor).unwrap();
+        add_member(&mut shared, &carol, Role::Viewer).unwrap();
+        assert!(add_member(&mut shared, &alice, Role::Editor).is_err());
+        assert_eq!(set_role(&mut shared, alice.state.user.user_uuid, Role::Viewer), Ok(false));
+        assert!(!remove_member(&mut shared, alice.state.user.user_uuid).unwrap());
+        save_shared(&mut shared, &databases_dir).unwrap();
+
+        let mut viewer = SharedSession::open(&databases_dir, shared.database_uuid, &carol).unwrap();
+        assert_eq!(viewer.state.role(), Role::Viewer);
+        let refused = ServiceError::NotAllowed(Role::Viewer, Permission::Edit);
+        assert_eq!(create_epic(&mut viewer.state, "Launch", String::new(), &SystemClock), Err(refused.clone()));
+        assert!(switch_project(&mut viewer.state, Some(project_uuid)).is_ok());
+        assert_eq!(save_shared(&mut viewer, &databases_dir).unwrap_err().to_string(), refused.to_string());
+
+        let mut editor = SharedSession::open(&databases_dir, shared.database_uuid, &bob).unwrap();
+        assert!(create_epic(&mut editor.state, "Launch", String::new(), &SystemClock).is_ok());
+        let refused = ServiceError::NotAllowed(Role::Editor, Permission::DeleteProject);
+        assert_eq!(delete_project(&mut editor.state, project_uuid), Err(refused.clone()));
+        let refused_members = ServiceError::NotAllowed(Role::Editor, Permission::ManageMembers);
+        assert_eq!(remove_member(&mut editor, carol.state.user.user_uuid).unwrap_err().to_string(), refused_members.to_string());
+        assert_eq!(delete_shared(editor.clone(), &databases_dir).unwrap_err().to_string(), refused.to_string());
+
+        assert!(delete_project(&mut shared.state, project_uuid).is_ok());
+        assert_eq!(set_role(&mut shared, carol.state.user.user_uuid, Role::Editor), Ok(true));
+        delete_shared(shared, &databases_dir).unwrap();
+        assert!(detect_shared(&databases_dir, bob.state.user.user_uuid).unwrap().is_empty());
+        std::fs::remove_dir_all(&databases_dir).unwrap();
+    }
+
+    #[test]
+    fn test_roles_in_the_file_cant_be_raised_by_editing_it() {
+        let databases_dir = std::env::temp_dir().join(format!("ironyyy-tamper-{}", Uuid::new_v4()));
+        let (alice, carol) = (session_for(&databases_dir, "alice"), session_for(&databases_dir, "carol"));
+        let mut shared = SharedSession::create("Team".to_string(), &alice).unwrap();
+        add_member(&mut shared, &carol, Role::Viewer).unwrap();
+        save_shared(&mut shared, &databases_dir).unwrap();
+
+        // Changing the encrypted data without the key makes it unreadable rather than raising a role.
+        let path = shared_path(&databases_dir, shared.database_uuid);
+        let mut db = read_shared(&databases_dir, shared.database_uuid).unwrap();
+        db.data.encrypted_data[0] ^= 1;
+        std::fs::write(&path, serde_json::to_string(&db).unwrap()).unwrap();
+        assert!(SharedSession::open(&databases_dir, shared.database_uuid, &carol).is_err());
+        std::fs::remove_dir_all(&databases_dir).unwrap();
+    }
+}
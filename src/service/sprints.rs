@@ -0,0 +1,179 @@
+//! Sprint operations: planning stories into sprints, closing sprints, and carrying unfinished work
+//! over to the next one.
+
+use super::{ServiceError, get_story, require};
+use crate::db::ClearTextDBState;
+use crate::models::roles::Permission;
+use crate::models::{sprints::Sprint, validation::validate_date_range};
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+/// Creates a new, empty sprint.
+///
+/// # Arguments
+/// * `state` - The database state to modify.
+/// * `name` - The name of the sprint.
+/// * `start` - The first day of the sprint.
+/// * `end` - The last day of the sprint.
+/// # Returns
+/// * `Result<Uuid, ServiceError>` - The UUID of the new sprint.
+/// # Errors
+/// * `ServiceError::Validation` - If `start` is after `end`.
+pub fn create_sprint(state: &mut ClearTextDBState, name: String, start: NaiveDate, end: NaiveDate) -> Result<Uuid, ServiceError> {
+    require(state, Permission::Edit)?;
+    validate_date_range(Some(start), Some(end))?;
+    let sprint = Sprint::new(name, start, end);
+    let sprint_uuid = sprint.sprint_uuid;
+    state.mark_dirty();
+    state.sprints.push(sprint);
+    Ok(sprint_uuid)
+}
+
+/// Looks up a sprint by UUID.
+///
+/// # Errors
+/// * `ServiceError::SprintNotFound` - If no such sprint exists.
+pub fn get_sprint(state: &ClearTextDBState, sprint_uuid: Uuid) -> Result<&Sprint, ServiceError> {
+    state
+        .sprints
+        .iter()
+        .find(|sprint| sprint.sprint_uuid == sprint_uuid)
+        .ok_or(ServiceError::SprintNotFound(sprint_uuid))
+}
+
+/// Looks up an open sprint by UUID for modification.
+///
+/// # Errors
+/// * `ServiceError::SprintNotFound` - If no such sprint exists.
+/// * `ServiceError::SprintClosed` - If the sprint is closed.
+fn get_open_sprint_mut(state: &mut ClearTextDBState, sprint_uuid: Uuid) -> Result<&mut Sprint, ServiceError> {
+    state.mark_dirty();
+    let sprint = state
+        .sprints
+        .iter_mut()
+        .find(|sprint| sprint.sprint_uuid == sprint_uuid)
+        .ok_or(ServiceError::SprintNotFound(sprint_uuid))?;
+    if sprint.closed {
+        return Err(ServiceError::SprintClosed(sprint_uuid));
+    }
+    Ok(sprint)
+}
+
+/// Plans a story into a sprint, taking it out of any other open sprint.
+///
+/// Closed sprints keep their membership as a record of what was worked on.
+/// # Errors
+/// * `ServiceError::StoryNotFound` - If the story does not exist.
+/// * `ServiceError::SprintNotFound` - If the sprint does not exist.
+/// * `ServiceError::SprintClosed` - If the sprint is closed.
+pub fn assign_story_to_sprint(state: &mut ClearTextDBState, story_uuid: Uuid, sprint_uuid: Uuid) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    get_story(state, story_uuid)?;
+    get_open_sprint_mut(state, sprint_uuid)?;
+    for sprint in state.sprints.iter_mut().filter(|sprint| !sprint.closed) {
+        sprint.story_uuids.retain(|uuid| *uuid != story_uuid);
+    }
+    get_open_sprint_mut(state, sprint_uuid)?.story_uuids.push(story_uuid);
+    Ok(())
+}
+
+/// Takes a story out of a sprint.
+///
+/// # Errors
+/// * `ServiceError::SprintNotFound` - If the sprint does not exist.
+/// * `ServiceError::SprintClosed` - If the sprint is closed.
+pub fn remove_story_from_sprint(state: &mut ClearTextDBState, story_uuid: Uuid, sprint_uuid: Uuid) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    get_open_sprint_mut(state, sprint_uuid)?.story_uuids.retain(|uuid| *uuid != story_uuid);
+    Ok(())
+}
+
+/// Returns the open sprint a story is planned into, if any.
+#[must_use]
+pub fn current_sprint_of(state: &ClearTextDBState, story_uuid: Uuid) -> Option<&Sprint> {
+    state
+        .sprints
+        .iter()
+        .find(|sprint| !sprint.closed && sprint.story_uuids.contains(&story_uuid))
+}
+
+/// Closes a sprint, moving its unfinished stories out of it.
+///
+/// Stories that aren't done are removed from the sprint and, if `carry_over_to` is given,
+/// planned into that sprint instead; otherwise they return to the backlog.
+/// # Arguments
+/// * `state` - The database state to modify.
+/// * `sprint_uuid` - The sprint to close.
+/// * `carry_over_to` - The open sprint to move unfinished stories into, if any.
+/// # Returns
+/// * `Result<Vec<Uuid>, ServiceError>` - The UUIDs of the unfinished stories.
+/// # Errors
+/// * `ServiceError::SprintNotFound` - If either sprint does not exist.
+/// * `ServiceError::SprintClosed` - If either sprint is already closed.
+pub fn close_sprint(state: &mut ClearTextDBState, sprint_uuid: Uuid, carry_over_to: Option<Uuid>) -> Result<Vec<Uuid>, ServiceError> {
+    require(state, Permission::Edit)?;
+    if let Some(next_uuid) = carry_over_to {
+        get_open_sprint_mut(state, next_uuid)?;
+    }
+    let members = get_open_sprint_mut(state, sprint_uuid)?.story_uuids.clone();
+    let unfinished: Vec<Uuid> = members
+        .into_iter()
+        .filter(|story_uuid| get_story(state, *story_uuid).is_ok_and(|story| !story.status.is_done(&state.statuses)))
+        .collect();
+
+    let sprint = get_open_sprint_mut(state, sprint_uuid)?;
+    sprint.story_uuids.retain(|uuid| !unfinished.contains(uuid));
+    sprint.closed = true;
+
+    if let Some(next_uuid) = carry_over_to {
+        let next = get_open_sprint_mut(state, next_uuid)?;
+        for story_uuid in &unfinished {
+            if !next.story_uuids.contains(story_uuid) {
+                next.story_uuids.push(*story_uuid);
+            }
+        }
+    }
+    Ok(unfinished)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Status, stories::Story, validation::ValidationError};
+    use crate::users::User;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, day).unwrap()
+    }
+
+    #[test]
+    fn test_close_sprint_carries_over_unfinished_stories() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let done = Story::new("Done".to_string(), String::new());
+        let (done_uuid, todo_uuid) = (done.story_uuid, Uuid::new_v4());
+        state.stories.push(done);
+        state.stories.push(Story { story_uuid: todo_uuid, ..Story::new("Todo".to_string(), String::new()) });
+        state.stories[0].status = Status::Closed;
+
+        assert_eq!(
+            create_sprint(&mut state, "Bad".to_string(), date(14), date(1)),
+            Err(ValidationError::InvalidDateRange.into())
+        );
+        let first = create_sprint(&mut state, "Sprint 1".to_string(), date(1), date(14)).unwrap();
+        let second = create_sprint(&mut state, "Sprint 2".to_string(), date(15), date(28)).unwrap();
+        assign_story_to_sprint(&mut state, done_uuid, first).unwrap();
+        assign_story_to_sprint(&mut state, todo_uuid, second).unwrap();
+        assign_story_to_sprint(&mut state, todo_uuid, first).unwrap();
+        assert!(get_sprint(&state, second).unwrap().story_uuids.is_empty());
+
+        assert_eq!(close_sprint(&mut state, first, Some(second)).unwrap(), vec![todo_uuid]);
+        assert_eq!(get_sprint(&state, first).unwrap().story_uuids, vec![done_uuid]);
+        assert_eq!(current_sprint_of(&state, todo_uuid).unwrap().sprint_uuid, second);
+        assert_eq!(current_sprint_of(&state, done_uuid), None);
+        assert_eq!(close_sprint(&mut state, first, None), Err(ServiceError::SprintClosed(first)));
+        assert_eq!(
+            assign_story_to_sprint(&mut state, done_uuid, first),
+            Err(ServiceError::SprintClosed(first))
+        );
+    }
+}
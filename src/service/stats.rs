@@ -0,0 +1,98 @@
+//! Stats operations: rolling epics and stories up into counts by status, epic, and label, for
+//! the dashboard and report exports.
+
+use super::{archive::active_stories, ranking::ranked_epics};
+use crate::clock::Clock;
+use crate::db::ClearTextDBState;
+use crate::models::{
+    Scheduled,
+    stats::{CategoryCounts, EpicStats, Stats},
+};
+use chrono::Duration;
+
+/// # Stats
+/// Summarises the epics and stories that aren't archived or deleted.
+///
+/// # Arguments
+/// * `state` - The database state to summarise.
+/// * `clock` - Gives today's date for overdue counts and the start of the last seven days.
+///
+/// # Returns
+/// * `Stats` - The counts, with one entry per active epic in rank order.
+#[must_use]
+pub fn stats(state: &ClearTextDBState, clock: &dyn Clock) -> Stats {
+    let now = clock.now();
+    let today = now.date_naive();
+    let week_ago = now - Duration::days(7);
+    let epics: Vec<_> = ranked_epics(state).into_iter().filter(|epic| !epic.archived).collect();
+    let stories = active_stories(state);
+    let mut totals = Stats::default();
+
+    for epic in &epics {
+        totals.epics.add(epic.status.category(&state.statuses));
+        let mut epic_stats = EpicStats { epic_uuid: epic.epic_uuid, stories: CategoryCounts::default(), overdue_stories: 0 };
+        for story in stories.iter().filter(|story| epic.story_uuids.contains(&story.story_uuid)) {
+            epic_stats.stories.add(story.status.category(&state.statuses));
+            if story.is_overdue(today, &state.statuses) {
+                epic_stats.overdue_stories += 1;
+            }
+        }
+        totals.per_epic.push(epic_stats);
+    }
+    for story in &stories {
+        totals.stories.add(story.status.category(&state.statuses));
+        *totals.stories_by_status.entry(story.status).or_default() += 1;
+        for label_uuid in &story.labels {
+            *totals.stories_by_label.entry(*label_uuid).or_default() += 1;
+        }
+    }
+    totals.overdue_epics = epics.iter().filter(|epic| epic.is_overdue(today, &state.statuses)).count();
+    totals.overdue_stories = stories.iter().filter(|story| story.is_overdue(today, &state.statuses)).count();
+    totals.touched_this_week = epics.iter().filter(|epic| epic.updated_at >= week_ago).count()
+        + stories.iter().filter(|story| story.updated_at >= week_ago).count();
+    totals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{FixedClock, SystemClock};
+    use crate::models::Status;
+    use crate::service::{
+        archive::archive_story,
+        epics::create_epic,
+        labels::{create_label, label_story},
+        schedule::set_story_dates,
+        stories::{create_story, set_story_status},
+    };
+    use crate::users::User;
+    use chrono::DateTime;
+
+    #[test]
+    fn test_stats() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let old = FixedClock::new(DateTime::from_timestamp(1_700_000_000, 0).unwrap());
+        let epic_uuid = create_epic(&mut state, "Launch", String::new(), &old).unwrap();
+        let other = create_epic(&mut state, "Other", String::new(), &old).unwrap();
+        let login = create_story(&mut state, epic_uuid, "Login", String::new(), &old).unwrap();
+        let docs = create_story(&mut state, epic_uuid, "Docs", String::new(), &old).unwrap();
+        let deploy = create_story(&mut state, epic_uuid, "Deploy", String::new(), &old).unwrap();
+        let hidden = create_story(&mut state, other, "Hidden", String::new(), &old).unwrap();
+        set_story_status(&mut state, login, Status::Closed, &old).unwrap();
+        set_story_status(&mut state, docs, Status::InProgress, &old).unwrap();
+        set_story_dates(&mut state, deploy, None, old.now().date_naive().pred_opt(), &old).unwrap();
+        let infra = create_label(&mut state, "infra".to_string(), "#0000ff".to_string()).unwrap();
+        label_story(&mut state, docs, infra, &old).unwrap();
+        archive_story(&mut state, hidden, &old).unwrap();
+
+        let totals = stats(&state, &old);
+        assert_eq!(totals.stories, CategoryCounts { todo: 1, in_progress: 1, done: 1 });
+        assert_eq!(totals.epics.total(), 2);
+        assert_eq!(totals.stories_by_status.get(&Status::Closed), Some(&1));
+        assert_eq!(totals.stories_by_label.get(&infra), Some(&1));
+        assert_eq!(totals.per_epic[0], EpicStats { epic_uuid, stories: totals.stories, overdue_stories: 1 });
+        assert_eq!(totals.per_epic[1].stories.total(), 0);
+        assert_eq!((totals.overdue_epics, totals.overdue_stories, totals.touched_this_week), (0, 1, 5));
+        assert_eq!(stats(&state, &SystemClock).touched_this_week, 0);
+    }
+}
@@ -0,0 +1,176 @@
+//! Status operations: defining, renaming, reordering, and removing custom workflow statuses.
+
+use super::{ServiceError, epic_uuids_where, require, story_uuids_where, update_epic, update_story};
+use crate::clock::Clock;
+use crate::db::ClearTextDBState;
+use crate::models::roles::Permission;
+use crate::models::{
+    Status,
+    statuses::{StatusCategory, StatusDefinition, find_definition},
+};
+use uuid::Uuid;
+
+/// Adds a custom status at the end of the workflow.
+///
+/// # Returns
+/// * `Result<Status, ServiceError>` - The new `Status::Custom` value.
+/// # Errors
+/// * `ServiceError::NotAllowed` - If the role the database was opened with can't make changes.
+pub fn create_status(state: &mut ClearTextDBState, name: String, category: StatusCategory) -> Result<Status, ServiceError> {
+    require(state, Permission::Edit)?;
+    let status = Status::Custom(Uuid::new_v4());
+    let order = state.statuses.iter().map(|definition| definition.order + 1).max().unwrap_or(0);
+    state.mark_dirty();
+    state.statuses.push(StatusDefinition { status, name, category, order });
+    Ok(status)
+}
+
+/// Lists the database's statuses in workflow order.
+#[must_use]
+pub fn ordered_statuses(state: &ClearTextDBState) -> Vec<&StatusDefinition> {
+    let mut statuses: Vec<&StatusDefinition> = state.statuses.iter().collect();
+    statuses.sort_by_key(|definition| definition.order);
+    statuses
+}
+
+/// Fails if `status` is a custom status the database doesn't define. Built-ins always pass.
+///
+/// # Errors
+/// * `ServiceError::StatusNotFound` - If the custom status is unknown.
+pub fn ensure_status_defined(state: &ClearTextDBState, status: Status) -> Result<(), ServiceError> {
+    match status {
+        Status::Custom(uuid) if find_definition(&state.statuses, status).is_none() => Err(ServiceError::StatusNotFound(uuid)),
+        _ => Ok(()),
+    }
+}
+
+/// Renames a status. Built-in statuses can be renamed too.
+///
+/// # Errors
+/// * `ServiceError::StatusNotFound` - If the status has no definition.
+pub fn rename_status(state: &mut ClearTextDBState, status: Status, name: String) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    definition_mut(state, status)?.name = name;
+    Ok(())
+}
+
+/// Moves a status to a new position in the workflow.
+///
+/// # Errors
+/// * `ServiceError::StatusNotFound` - If the status has no definition.
+pub fn set_status_order(state: &mut ClearTextDBState, status: Status, order: u32) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    definition_mut(state, status)?.order = order;
+    Ok(())
+}
+
+/// Changes the category of a custom status.
+///
+/// # Errors
+/// * `ServiceError::BuiltinStatus` - If `status` is a built-in status.
+/// * `ServiceError::StatusNotFound` - If the status has no definition.
+pub fn set_status_category(state: &mut ClearTextDBState, status: Status, category: StatusCategory) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    if status.builtin_category().is_some() {
+        return Err(ServiceError::BuiltinStatus);
+    }
+    definition_mut(state, status)?.category = category;
+    Ok(())
+}
+
+/// Deletes a custom status, moving every epic and story that uses it to `replacement`.
+///
+/// # Errors
+/// * `ServiceError::BuiltinStatus` - If `status` is a built-in status.
+/// * `ServiceError::StatusNotFound` - If either status is unknown, or `replacement` is `status`.
+pub fn delete_status(state: &mut ClearTextDBState, status: Status, replacement: Status, clock: &dyn Clock) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    let Status::Custom(uuid) = status else {
+        return Err(ServiceError::BuiltinStatus);
+    };
+    ensure_status_defined(state, status)?;
+    if let Status::Custom(replacement_uuid) = replacement
+        && replacement_uuid == uuid
+    {
+        return Err(ServiceError::StatusNotFound(uuid));
+    }
+    ensure_status_defined(state, replacement)?;
+
+    state.mark_dirty();
+    state.statuses.retain(|definition| definition.status != status);
+    for epic_uuid in epic_uuids_where(state, |epic| epic.status == status) {
+        update_epic(state, epic_uuid, clock, |epic| epic.status = replacement)?;
+    }
+    for story_uuid in story_uuids_where(state, |story| story.status == status) {
+        update_story(state, story_uuid, clock, |story| story.status = replacement)?;
+    }
+    Ok(())
+}
+
+/// Looks up a status definition for modification.
+fn definition_mut(state: &mut ClearTextDBState, status: Status) -> Result<&mut StatusDefinition, ServiceError> {
+    state.mark_dirty();
+    state
+        .statuses
+        .iter_mut()
+        .find(|definition| definition.status == status)
+        .ok_or(ServiceError::StatusNotFound(match status {
+            Status::Custom(uuid) => uuid,
+            _ => Uuid::nil(),
+        }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::models::stories::Story;
+    use crate::service::stories::set_story_status;
+    use crate::users::User;
+
+    #[test]
+    fn test_custom_status_lifecycle() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let story = Story::new("Story".to_string(), String::new());
+        let story_uuid = story.story_uuid;
+        state.stories.push(story);
+
+        let review = create_status(&mut state, "QA".to_string(), StatusCategory::InProgress).unwrap();
+        let names: Vec<&str> = ordered_statuses(&state).iter().map(|definition| definition.name.as_str()).collect();
+        assert_eq!(names, ["Open", "In Progress", "Blocked", "In Review", "Cancelled", "Closed", "QA"]);
+        set_status_order(&mut state, Status::Open, 10).unwrap();
+        assert_eq!(ordered_statuses(&state)[5].status, review);
+        assert_eq!(ordered_statuses(&state)[6].status, Status::Open);
+
+        set_story_status(&mut state, story_uuid, review, &SystemClock).unwrap();
+        assert_eq!(state.stories[0].status.name(&state.statuses), "QA");
+        set_status_category(&mut state, review, StatusCategory::Done).unwrap();
+        assert!(state.stories[0].status.is_done(&state.statuses));
+        assert_eq!(set_status_category(&mut state, Status::Open, StatusCategory::Done), Err(ServiceError::BuiltinStatus));
+
+        assert_eq!(delete_status(&mut state, Status::Closed, Status::Open, &SystemClock), Err(ServiceError::BuiltinStatus));
+        delete_status(&mut state, review, Status::InProgress, &SystemClock).unwrap();
+        assert_eq!(state.stories[0].status, Status::InProgress);
+        assert!(set_story_status(&mut state, story_uuid, review, &SystemClock).is_err());
+    }
+
+    #[test]
+    fn test_old_databases_get_builtin_statuses() {
+        let state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let mut value = serde_json::to_value(&state).unwrap();
+        value.as_object_mut().unwrap().remove("statuses");
+        let migrated: ClearTextDBState = serde_json::from_value(value).unwrap();
+        assert_eq!(migrated.statuses, crate::models::statuses::default_statuses());
+        assert_eq!(serde_json::to_string(&Status::Closed).unwrap(), "\"Closed\"");
+    }
+
+    #[test]
+    fn test_new_builtins_are_added_to_old_lists() {
+        let mut statuses = crate::models::statuses::default_statuses();
+        statuses.retain(|definition| matches!(definition.status, Status::Open | Status::InProgress | Status::Closed));
+        crate::models::statuses::add_missing_builtins(&mut statuses);
+        assert_eq!(statuses.len(), Status::BUILTINS.len());
+        assert!(Status::Cancelled.is_done(&statuses) && Status::Blocked.is_open(&statuses));
+        assert_eq!(Status::InReview.name(&statuses), "In Review");
+    }
+}
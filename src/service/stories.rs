@@ -0,0 +1,340 @@
+//! Story operations.
+
+use super::{
+    ServiceError, check_transition, dependencies::open_blockers, epic_uuids_where, epics::auto_close_epics_of, get_story, ranking::{next_story_rank, parent_epic},
+    require, story_uuids_where, update_epic, update_story,
+};
+use crate::clock::Clock;
+use crate::db::ClearTextDBState;
+use crate::models::roles::Permission;
+use crate::models::validation::{validate_description, validate_title};
+use crate::models::{Status, events::Event, stories::Story, workflow::TransitionAction};
+use uuid::Uuid;
+
+/// Creates a new, numbered story at the end of an epic, stamped with the current time.
+///
+/// # Returns
+/// * `Result<Uuid, ServiceError>` - The UUID of the new story.
+/// # Errors
+/// * `ServiceError::Validation` - If the title or description is rejected.
+/// * `ServiceError::EpicNotFound` - If the epic does not exist.
+pub fn create_story(state: &mut ClearTextDBState, epic_uuid: Uuid, title: &str, description: String, clock: &dyn Clock) -> Result<Uuid, ServiceError> {
+    require(state, Permission::Edit)?;
+    validate_description(&description)?;
+    let title = validate_title(title)?;
+    let rank = next_story_rank(state, epic_uuid)?;
+    let mut story = Story::new(title, description);
+    story.rank = rank;
+    story.number = state.next_story_number;
+    state.next_story_number += 1;
+    story.created_at = clock.now();
+    story.updated_at = story.created_at;
+    let story_uuid = story.story_uuid;
+    state.mark_dirty();
+    state.stories.push(story);
+    state.emit(Event::StoryCreated { story_uuid, epic_uuid: Some(epic_uuid) });
+    update_epic(state, epic_uuid, clock, |epic| epic.story_uuids.push(story_uuid))?;
+    Ok(story_uuid)
+}
+
+/// Copies a story with [`Story::duplicate`] and adds the copy to the end of the same epic, numbered
+/// and stamped with the current time.
+///
+/// # Returns
+/// * `Result<Uuid, ServiceError>` - The UUID of the copy.
+/// # Errors
+/// * `ServiceError::StoryNotFound` - If the story does not exist.
+/// * `ServiceError::Validation` - If the suffixed title is too long.
+pub fn duplicate_story(state: &mut ClearTextDBState, story_uuid: Uuid, clock: &dyn Clock) -> Result<Uuid, ServiceError> {
+    require(state, Permission::Edit)?;
+    let mut copy = get_story(state, story_uuid)?.duplicate();
+    validate_title(&copy.title)?;
+    let epic_uuid = parent_epic(state, story_uuid);
+    if let Some(epic_uuid) = epic_uuid {
+        copy.rank = next_story_rank(state, epic_uuid)?;
+    }
+    copy.number = state.next_story_number;
+    state.next_story_number += 1;
+    copy.created_at = clock.now();
+    copy.updated_at = copy.created_at;
+    let copy_uuid = copy.story_uuid;
+    state.mark_dirty();
+    state.stories.push(copy);
+    state.emit(Event::StoryCreated { story_uuid: copy_uuid, epic_uuid });
+    if let Some(epic_uuid) = epic_uuid {
+        update_epic(state, epic_uuid, clock, |epic| epic.story_uuids.push(copy_uuid))?;
+    }
+    Ok(copy_uuid)
+}
+
+/// Changes a story's title, trimming it.
+///
+/// # Errors
+/// * `ServiceError::Validation` - If the title is blank or too long.
+/// * `ServiceError::StoryNotFound` - If the story does not exist.
+pub fn rename_story(state: &mut ClearTextDBState, story_uuid: Uuid, title: &str, clock: &dyn Clock) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    let title = validate_title(title)?;
+    update_story(state, story_uuid, clock, |story| story.title = title)
+}
+
+/// Changes a story's description.
+///
+/// # Errors
+/// * `ServiceError::Validation` - If the description is too long.
+/// * `ServiceError::StoryNotFound` - If the story does not exist.
+pub fn set_story_description(state: &mut ClearTextDBState, story_uuid: Uuid, description: String, clock: &dyn Clock) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    validate_description(&description)?;
+    update_story(state, story_uuid, clock, |story| story.description = description)
+}
+
+/// Changes a story's status with an ordinary move.
+///
+/// A story can't be moved to a done status while any of its blockers are still open.
+/// # Errors
+/// * `ServiceError::StoryNotFound` - If the story does not exist.
+/// * `ServiceError::StatusNotFound` - If `status` is a custom status the database doesn't define.
+/// * `ServiceError::ActionRequired` / `ServiceError::InvalidTransition` - If the workflow refuses the move.
+/// * `ServiceError::BlockedBy` - If finishing a story whose blockers are still open.
+pub fn set_story_status(state: &mut ClearTextDBState, story_uuid: Uuid, status: Status, clock: &dyn Clock) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    transition_story(state, story_uuid, status, TransitionAction::Move, clock)
+}
+
+/// Reopens a finished story, moving it to `status`.
+///
+/// # Errors
+/// Same as [`set_story_status`].
+pub fn reopen_story(state: &mut ClearTextDBState, story_uuid: Uuid, status: Status, clock: &dyn Clock) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    transition_story(state, story_uuid, status, TransitionAction::Reopen, clock)
+}
+
+/// Moves a story to `status` with the given action, enforcing the workflow and blockers.
+///
+/// Finishing the last open story of an epic closes the epic if `auto_close_epics` is on.
+///
+/// # Errors
+/// Same as [`set_story_status`].
+pub fn transition_story(state: &mut ClearTextDBState, story_uuid: Uuid, status: Status, action: TransitionAction, clock: &dyn Clock) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    let current = get_story(state, story_uuid)?.status;
+    check_transition(state, current, status, action)?;
+    if status.is_done(&state.statuses) {
+        let blockers: Vec<Uuid> = open_blockers(state, story_uuid)?.iter().map(|story| story.story_uuid).collect();
+        if !blockers.is_empty() {
+            return Err(ServiceError::BlockedBy(blockers));
+        }
+    }
+    update_story(state, story_uuid, clock, |story| story.status = status)?;
+    auto_close_epics_of(state, story_uuid, clock)?;
+    Ok(())
+}
+
+/// # Batch Transition struct
+/// The outcome of moving several stories at once with [`transition_stories`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BatchTransition {
+    /// The stories that were moved, in the order given.
+    pub transitioned: Vec<Uuid>,
+    /// The stories that were left as they were, each with the reason.
+    pub skipped: Vec<(Uuid, ServiceError)>,
+}
+
+/// Moves each of a set of stories to `status` with [`transition_story`], carrying on past the
+/// ones the workflow or their blockers refuse.
+///
+/// # Arguments
+/// * `state` - The database state to modify.
+/// * `story_uuids` - The stories to move. Repeated UUIDs are only moved once.
+/// * `status` - The status to move them to.
+/// * `action` - The kind of move, checked against the workflow for each story.
+/// * `clock` - The source of the history timestamps.
+/// # Returns
+/// * `BatchTransition` - Which stories were moved and which were skipped, and why.
+pub fn transition_stories(state: &mut ClearTextDBState, story_uuids: &[Uuid], status: Status, action: TransitionAction, clock: &dyn Clock) -> BatchTransition {
+    let mut outcome = BatchTransition::default();
+    for (index, story_uuid) in story_uuids.iter().copied().enumerate() {
+        if story_uuids[..index].contains(&story_uuid) {
+            continue;
+        }
+        match transition_story(state, story_uuid, status, action, clock) {
+            Ok(()) => outcome.transitioned.push(story_uuid),
+            Err(error) => outcome.skipped.push((story_uuid, error)),
+        }
+    }
+    outcome
+}
+
+/// Moves a story to the end of another epic, taking it out of the epic it was in.
+///
+/// # Errors
+/// * `ServiceError::StoryNotFound` - If the story does not exist.
+/// * `ServiceError::EpicNotFound` - If the target epic does not exist.
+pub fn move_story_to_epic(state: &mut ClearTextDBState, story_uuid: Uuid, epic_uuid: Uuid, clock: &dyn Clock) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    get_story(state, story_uuid)?;
+    if parent_epic(state, story_uuid) == Some(epic_uuid) {
+        return Ok(());
+    }
+    let rank = next_story_rank(state, epic_uuid)?;
+    for other_uuid in epic_uuids_where(state, |epic| epic.story_uuids.contains(&story_uuid)) {
+        update_epic(state, other_uuid, clock, |epic| epic.story_uuids.retain(|uuid| *uuid != story_uuid))?;
+    }
+    update_epic(state, epic_uuid, clock, |epic| epic.story_uuids.push(story_uuid))?;
+    update_story(state, story_uuid, clock, |story| story.rank = rank)
+}
+
+/// Deletes a story along with its tasks, work logs, and attachment metadata, and removes every
+/// reference to it from epics, sprints, other stories' blockers and links.
+///
+/// The encrypted attachment files are left on disk; pass the returned UUIDs to
+/// [`crate::db::attachments::delete_attachment_files`] once the change is saved.
+/// # Returns
+/// * `Result<(Story, Vec<Uuid>), ServiceError>` - The removed story and its removed attachments.
+/// # Errors
+/// * `ServiceError::StoryNotFound` - If the story does not exist.
+pub fn delete_story(state: &mut ClearTextDBState, story_uuid: Uuid, clock: &dyn Clock) -> Result<(Story, Vec<Uuid>), ServiceError> {
+    require(state, Permission::Edit)?;
+    let index = state
+        .stories
+        .iter()
+        .position(|story| story.story_uuid == story_uuid)
+        .ok_or(ServiceError::StoryNotFound(story_uuid))?;
+    state.mark_dirty();
+    let story = state.stories.remove(index);
+    state.emit(Event::StoryDeleted { story_uuid });
+    for epic_uuid in epic_uuids_where(state, |epic| epic.story_uuids.contains(&story_uuid)) {
+        update_epic(state, epic_uuid, clock, |epic| epic.story_uuids.retain(|uuid| *uuid != story_uuid))?;
+    }
+    let references = |other: &Story| other.blocked_by.contains(&story_uuid) || other.links.iter().any(|link| link.story_uuid == story_uuid);
+    for other_uuid in story_uuids_where(state, references) {
+        update_story(state, other_uuid, clock, |other| {
+            other.blocked_by.retain(|uuid| *uuid != story_uuid);
+            other.links.retain(|link| link.story_uuid != story_uuid);
+        })?;
+    }
+    for sprint in &mut state.sprints {
+        sprint.story_uuids.retain(|uuid| *uuid != story_uuid);
+    }
+    state.tasks.retain(|task| !story.task_uuids.contains(&task.task_uuid));
+    state.work_logs.retain(|work_log| work_log.story_uuid != story_uuid);
+    state.attachments.retain(|attachment| !story.attachments.contains(&attachment.attachment_uuid));
+    let attachment_uuids = story.attachments.clone();
+    Ok((story, attachment_uuids))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{FixedClock, SystemClock};
+    use crate::models::validation::{MAX_DESCRIPTION_LENGTH, MAX_TITLE_LENGTH, ValidationError};
+    use crate::service::{epics::create_epic, get_epic, tasks::add_task};
+    use crate::users::User;
+    use chrono::{DateTime, Duration};
+
+    #[test]
+    fn test_create_story_timestamps() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let clock = FixedClock::new(DateTime::from_timestamp(1_700_000_000, 0).unwrap());
+        let epic_uuid = create_epic(&mut state, "Epic", String::new(), &clock).unwrap();
+        clock.advance(Duration::minutes(5));
+        let story_uuid = create_story(&mut state, epic_uuid, "Story", String::new(), &clock).unwrap();
+        assert_eq!(state.epics[0].story_uuids, vec![story_uuid]);
+        assert_eq!(state.epics[0].updated_at, clock.now());
+        assert_eq!(state.epics[0].created_at, clock.now() - Duration::minutes(5));
+
+        clock.advance(Duration::minutes(5));
+        set_story_status(&mut state, story_uuid, Status::InProgress, &clock).unwrap();
+        let story = get_story(&state, story_uuid).unwrap();
+        assert_eq!((story.created_at, story.updated_at), (clock.now() - Duration::minutes(5), clock.now()));
+        assert!(create_story(&mut state, Uuid::new_v4(), "x", String::new(), &clock).is_err());
+    }
+
+    #[test]
+    fn test_reopen_requires_action() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let story = Story::new("Story".to_string(), String::new());
+        let story_uuid = story.story_uuid;
+        state.stories.push(story);
+
+        set_story_status(&mut state, story_uuid, Status::Closed, &SystemClock).unwrap();
+        assert_eq!(
+            set_story_status(&mut state, story_uuid, Status::Open, &SystemClock),
+            Err(ServiceError::ActionRequired(TransitionAction::Reopen))
+        );
+        reopen_story(&mut state, story_uuid, Status::InProgress, &SystemClock).unwrap();
+        assert_eq!(state.stories[0].status, Status::InProgress);
+    }
+    #[test]
+    fn test_titles_are_validated() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let epic_uuid = create_epic(&mut state, "Epic", String::new(), &SystemClock).unwrap();
+        let story_uuid = create_story(&mut state, epic_uuid, "  Story  ", String::new(), &SystemClock).unwrap();
+        assert_eq!(get_story(&state, story_uuid).unwrap().title, "Story");
+
+        let blank = rename_story(&mut state, story_uuid, " \t", &SystemClock);
+        assert_eq!(blank, Err(ServiceError::Validation(ValidationError::EmptyTitle)));
+        let long = "x".repeat(MAX_TITLE_LENGTH + 1);
+        assert_eq!(create_story(&mut state, epic_uuid, &long, String::new(), &SystemClock), Err(ValidationError::TitleTooLong.into()));
+        let huge = "x".repeat(MAX_DESCRIPTION_LENGTH + 1);
+        assert_eq!(set_story_description(&mut state, story_uuid, huge, &SystemClock), Err(ValidationError::DescriptionTooLong.into()));
+        rename_story(&mut state, story_uuid, "Renamed", &SystemClock).unwrap();
+        assert_eq!(get_story(&state, story_uuid).unwrap().title, "Renamed");
+    }
+
+    #[test]
+    fn test_duplicate_story() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let epic_uuid = create_epic(&mut state, "Epic", String::new(), &SystemClock).unwrap();
+        let story_uuid = create_story(&mut state, epic_uuid, "Weekly report", "Collect metrics".to_string(), &SystemClock).unwrap();
+        add_task(&mut state, story_uuid, "Draft", &SystemClock).unwrap();
+        set_story_status(&mut state, story_uuid, Status::InProgress, &SystemClock).unwrap();
+
+        let copy_uuid = duplicate_story(&mut state, story_uuid, &SystemClock).unwrap();
+        let copy = get_story(&state, copy_uuid).unwrap();
+        assert_eq!((copy.title.as_str(), copy.description.as_str()), ("Weekly report (copy)", "Collect metrics"));
+        assert_eq!((copy.status, copy.key(), copy.rank), (Status::Open, "STORY-2".to_string(), 1));
+        assert!(copy.task_uuids.is_empty());
+        assert_eq!(state.epics[0].story_uuids, vec![story_uuid, copy_uuid]);
+        let missing = Uuid::new_v4();
+        assert_eq!(duplicate_story(&mut state, missing, &SystemClock), Err(ServiceError::StoryNotFound(missing)));
+    }
+
+    #[test]
+    fn test_transition_stories_reports_skipped() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let epic_uuid = create_epic(&mut state, "Epic", String::new(), &SystemClock).unwrap();
+        let open = create_story(&mut state, epic_uuid, "Open", String::new(), &SystemClock).unwrap();
+        let closed = create_story(&mut state, epic_uuid, "Closed", String::new(), &SystemClock).unwrap();
+        set_story_status(&mut state, closed, Status::Closed, &SystemClock).unwrap();
+        let missing = Uuid::new_v4();
+
+        let outcome = transition_stories(&mut state, &[open, closed, missing, open], Status::InProgress, TransitionAction::Move, &SystemClock);
+        assert_eq!(outcome.transitioned, vec![open]);
+        assert_eq!(
+            outcome.skipped,
+            vec![(closed, ServiceError::ActionRequired(TransitionAction::Reopen)), (missing, ServiceError::StoryNotFound(missing))]
+        );
+        assert_eq!(get_story(&state, open).unwrap().status, Status::InProgress);
+        assert_eq!(get_story(&state, closed).unwrap().status, Status::Closed);
+    }
+
+    #[test]
+    fn test_move_story_to_epic() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let from = create_epic(&mut state, "From", String::new(), &SystemClock).unwrap();
+        let to = create_epic(&mut state, "To", String::new(), &SystemClock).unwrap();
+        let story_uuid = create_story(&mut state, from, "Login", String::new(), &SystemClock).unwrap();
+        let existing = create_story(&mut state, to, "Docs", String::new(), &SystemClock).unwrap();
+
+        move_story_to_epic(&mut state, story_uuid, to, &SystemClock).unwrap();
+        assert!(get_epic(&state, from).unwrap().story_uuids.is_empty());
+        assert_eq!(get_epic(&state, to).unwrap().story_uuids, vec![existing, story_uuid]);
+        assert!(get_story(&state, story_uuid).unwrap().rank > get_story(&state, existing).unwrap().rank);
+        let missing = Uuid::new_v4();
+        assert_eq!(move_story_to_epic(&mut state, story_uuid, missing, &SystemClock), Err(ServiceError::EpicNotFound(missing)));
+        assert_eq!(move_story_to_epic(&mut state, missing, to, &SystemClock), Err(ServiceError::StoryNotFound(missing)));
+    }
+}
@@ -0,0 +1,170 @@
+//! Task operations: creating, editing, completing, and removing a story's tasks.
+
+use super::{ServiceError, get_story, require, story_uuids_where, update_story};
+use crate::clock::Clock;
+use crate::db::ClearTextDBState;
+use crate::models::roles::Permission;
+use crate::models::{tasks::Task, validation::validate_title};
+use uuid::Uuid;
+
+/// Adds a new task to a story.
+///
+/// # Arguments
+/// * `state` - The database state to modify.
+/// * `story_uuid` - The story the task belongs to.
+/// * `title` - The title of the task (trimmed before saving).
+/// # Returns
+/// * `Result<Uuid, ServiceError>` - The UUID of the new task.
+/// # Errors
+/// * `ServiceError::Validation` - If the title is blank or too long.
+/// * `ServiceError::StoryNotFound` - If the story does not exist.
+pub fn add_task(state: &mut ClearTextDBState, story_uuid: Uuid, title: &str, clock: &dyn Clock) -> Result<Uuid, ServiceError> {
+    require(state, Permission::Edit)?;
+    let task = Task::new(validate_title(title)?);
+    let task_uuid = task.task_uuid;
+    update_story(state, story_uuid, clock, |story| story.task_uuids.push(task_uuid))?;
+    state.mark_dirty();
+    state.tasks.push(task);
+    Ok(task_uuid)
+}
+
+/// Looks up a task by UUID.
+///
+/// # Errors
+/// * `ServiceError::TaskNotFound` - If no such task exists.
+pub fn get_task(state: &ClearTextDBState, task_uuid: Uuid) -> Result<&Task, ServiceError> {
+    state
+        .tasks
+        .iter()
+        .find(|task| task.task_uuid == task_uuid)
+        .ok_or(ServiceError::TaskNotFound(task_uuid))
+}
+
+/// Looks up a task by UUID for modification, marking the database dirty.
+///
+/// # Errors
+/// * `ServiceError::TaskNotFound` - If no such task exists.
+pub fn get_task_mut(state: &mut ClearTextDBState, task_uuid: Uuid) -> Result<&mut Task, ServiceError> {
+    require(state, Permission::Edit)?;
+    state.mark_dirty();
+    state
+        .tasks
+        .iter_mut()
+        .find(|task| task.task_uuid == task_uuid)
+        .ok_or(ServiceError::TaskNotFound(task_uuid))
+}
+
+/// Lists a story's tasks in the order they were added.
+///
+/// # Errors
+/// * `ServiceError::StoryNotFound` - If the story does not exist.
+pub fn list_tasks(state: &ClearTextDBState, story_uuid: Uuid) -> Result<Vec<&Task>, ServiceError> {
+    get_story(state, story_uuid)?
+        .task_uuids
+        .iter()
+        .map(|task_uuid| get_task(state, *task_uuid))
+        .collect()
+}
+
+/// Renames a task, trimming the new title.
+///
+/// # Errors
+/// * `ServiceError::Validation` - If the title is blank or too long.
+/// * `ServiceError::TaskNotFound` - If the task does not exist.
+pub fn rename_task(state: &mut ClearTextDBState, task_uuid: Uuid, title: &str) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    let title = validate_title(title)?;
+    get_task_mut(state, task_uuid)?.title = title;
+    Ok(())
+}
+
+/// Marks a task as done or not done.
+///
+/// # Errors
+/// * `ServiceError::TaskNotFound` - If the task does not exist.
+pub fn set_task_done(state: &mut ClearTextDBState, task_uuid: Uuid, done: bool) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    get_task_mut(state, task_uuid)?.done = done;
+    Ok(())
+}
+
+/// Assigns a task to a user, or unassigns it with `None`.
+///
+/// # Errors
+/// * `ServiceError::TaskNotFound` - If the task does not exist.
+pub fn assign_task(state: &mut ClearTextDBState, task_uuid: Uuid, assignee: Option<Uuid>) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    get_task_mut(state, task_uuid)?.assignee = assignee;
+    Ok(())
+}
+
+/// Deletes a task and removes it from its story.
+///
+/// # Returns
+/// * `Result<Task, ServiceError>` - The removed task.
+/// # Errors
+/// * `ServiceError::TaskNotFound` - If the task does not exist.
+pub fn delete_task(state: &mut ClearTextDBState, task_uuid: Uuid, clock: &dyn Clock) -> Result<Task, ServiceError> {
+    require(state, Permission::Edit)?;
+    let index = state
+        .tasks
+        .iter()
+        .position(|task| task.task_uuid == task_uuid)
+        .ok_or(ServiceError::TaskNotFound(task_uuid))?;
+    for story_uuid in story_uuids_where(state, |story| story.task_uuids.contains(&task_uuid)) {
+        update_story(state, story_uuid, clock, |story| story.task_uuids.retain(|uuid| *uuid != task_uuid))?;
+    }
+    state.mark_dirty();
+    Ok(state.tasks.remove(index))
+}
+
+/// Renders a story's tasks as checklist lines, e.g. `[x] Write migration`.
+///
+/// # Errors
+/// * `ServiceError::StoryNotFound` - If the story does not exist.
+/// * `ServiceError::TaskNotFound` - If the story refers to a missing task.
+pub fn render_tasks(state: &ClearTextDBState, story_uuid: Uuid) -> Result<Vec<String>, ServiceError> {
+    Ok(list_tasks(state, story_uuid)?.iter().map(ToString::to_string).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::models::stories::Story;
+    use crate::users::User;
+
+    fn state_with_story() -> (ClearTextDBState, Uuid) {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let story = Story::new("Story".to_string(), String::new());
+        let story_uuid = story.story_uuid;
+        state.stories.push(story);
+        (state, story_uuid)
+    }
+
+    #[test]
+    fn test_task_crud() {
+        let (mut state, story_uuid) = state_with_story();
+        let first = add_task(&mut state, story_uuid, "Write migration", &SystemClock).unwrap();
+        let second = add_task(&mut state, story_uuid, "Review", &SystemClock).unwrap();
+
+        set_task_done(&mut state, first, true).unwrap();
+        rename_task(&mut state, second, "Code review").unwrap();
+        let assignee = Uuid::new_v4();
+        assign_task(&mut state, second, Some(assignee)).unwrap();
+        assert_eq!(get_task(&state, second).unwrap().assignee, Some(assignee));
+        assert_eq!(render_tasks(&state, story_uuid).unwrap(), vec!["[x] Write migration", "[ ] Code review"]);
+
+        assert_eq!(delete_task(&mut state, first, &SystemClock).unwrap().task_uuid, first);
+        assert_eq!(state.stories[0].task_uuids, vec![second]);
+        assert_eq!(delete_task(&mut state, first, &SystemClock), Err(ServiceError::TaskNotFound(first)));
+    }
+
+    #[test]
+    fn test_add_task_to_missing_story() {
+        let (mut state, _) = state_with_story();
+        let missing = Uuid::new_v4();
+        assert_eq!(add_task(&mut state, missing, "Orphan", &SystemClock), Err(ServiceError::StoryNotFound(missing)));
+        assert!(state.tasks.is_empty());
+    }
+}
@@ -0,0 +1,141 @@
+//! Trash operations: soft-deleting epics and stories, restoring them, and purging them for good.
+//!
+//! A deleted item keeps its place in the database with a `deleted_at` time, and the list queries
+//! leave it out. [`purge_deleted`] removes trashed items permanently.
+
+use super::{
+    ServiceError,
+    epics::{DeletePolicy, delete_epic},
+    get_epic, require,
+    stories::delete_story,
+    story_uuids_where, update_epic, update_story,
+};
+use crate::clock::Clock;
+use crate::db::ClearTextDBState;
+use crate::models::roles::Permission;
+use crate::models::{epics::Epic, stories::Story};
+use uuid::Uuid;
+
+/// Moves an epic to the trash together with those of its stories that aren't there already.
+///
+/// # Errors
+/// * `ServiceError::EpicNotFound` - If the epic does not exist.
+pub fn soft_delete_epic(state: &mut ClearTextDBState, epic_uuid: Uuid, clock: &dyn Clock) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    let now = clock.now();
+    let child_uuids = get_epic(state, epic_uuid)?.story_uuids.clone();
+    update_epic(state, epic_uuid, clock, |epic| epic.deleted_at = Some(now))?;
+    for story_uuid in story_uuids_where(state, |story| child_uuids.contains(&story.story_uuid) && !story.is_deleted()) {
+        update_story(state, story_uuid, clock, |story| story.deleted_at = Some(now))?;
+    }
+    Ok(())
+}
+
+/// Restores an epic from the trash, together with the stories that were deleted along with it.
+///
+/// # Errors
+/// * `ServiceError::EpicNotFound` - If the epic does not exist.
+pub fn restore_epic(state: &mut ClearTextDBState, epic_uuid: Uuid, clock: &dyn Clock) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    let epic = get_epic(state, epic_uuid)?;
+    let (deleted_at, child_uuids) = (epic.deleted_at, epic.story_uuids.clone());
+    update_epic(state, epic_uuid, clock, |epic| epic.deleted_at = None)?;
+    if deleted_at.is_some() {
+        for story_uuid in story_uuids_where(state, |story| child_uuids.contains(&story.story_uuid) && story.deleted_at == deleted_at) {
+            update_story(state, story_uuid, clock, |story| story.deleted_at = None)?;
+        }
+    }
+    Ok(())
+}
+
+/// Moves a single story to the trash.
+///
+/// # Errors
+/// * `ServiceError::StoryNotFound` - If the story does not exist.
+pub fn soft_delete_story(state: &mut ClearTextDBState, story_uuid: Uuid, clock: &dyn Clock) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    let now = clock.now();
+    update_story(state, story_uuid, clock, |story| story.deleted_at = Some(now))
+}
+
+/// Restores a single story from the trash.
+///
+/// # Errors
+/// * `ServiceError::StoryNotFound` - If the story does not exist.
+pub fn restore_story(state: &mut ClearTextDBState, story_uuid: Uuid, clock: &dyn Clock) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    update_story(state, story_uuid, clock, |story| story.deleted_at = None)
+}
+
+/// Lists the epics in the trash.
+#[must_use]
+pub fn deleted_epics(state: &ClearTextDBState) -> Vec<&Epic> {
+    state.epics.iter().filter(|epic| epic.is_deleted()).collect()
+}
+
+/// Lists the stories in the trash.
+#[must_use]
+pub fn deleted_stories(state: &ClearTextDBState) -> Vec<&Story> {
+    state.stories.iter().filter(|story| story.is_deleted()).collect()
+}
+
+/// Permanently removes everything in the trash, cleaning up references as [`delete_story`] and
+/// [`delete_epic`] do. Stories of a deleted epic go with it, even if they were restored on their
+/// own.
+///
+/// # Returns
+/// * `Result<(usize, Vec<Uuid>), ServiceError>` - The number of epics and stories removed, and the
+///   removed attachments whose files should be deleted.
+/// # Errors
+/// * `ServiceError` - If a cleanup step fails.
+pub fn purge_deleted(state: &mut ClearTextDBState, clock: &dyn Clock) -> Result<(usize, Vec<Uuid>), ServiceError> {
+    require(state, Permission::Edit)?;
+    let before = state.epics.len() + state.stories.len();
+    let mut attachment_uuids = Vec::new();
+    for story_uuid in story_uuids_where(state, Story::is_deleted) {
+        attachment_uuids.extend(delete_story(state, story_uuid, clock)?.1);
+    }
+    let epic_uuids: Vec<Uuid> = deleted_epics(state).iter().map(|epic| epic.epic_uuid).collect();
+    for epic_uuid in epic_uuids {
+        attachment_uuids.extend(delete_epic(state, epic_uuid, DeletePolicy::Cascade, clock)?.1);
+    }
+    Ok((before - state.epics.len() - state.stories.len(), attachment_uuids))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+    use crate::service::{archive::active_stories, epics::create_epic, get_story, ranking::ranked_epics, stories::create_story};
+    use crate::users::User;
+    use chrono::{DateTime, Duration};
+
+    #[test]
+    fn test_soft_delete_restore_and_purge() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let clock = FixedClock::new(DateTime::from_timestamp(1_700_000_000, 0).unwrap());
+        let epic_uuid = create_epic(&mut state, "Epic", String::new(), &clock).unwrap();
+        let earlier = create_story(&mut state, epic_uuid, "Deleted earlier", String::new(), &clock).unwrap();
+        let story_uuid = create_story(&mut state, epic_uuid, "Story", String::new(), &clock).unwrap();
+        soft_delete_story(&mut state, earlier, &clock).unwrap();
+        assert_eq!(active_stories(&state).len(), 1);
+
+        clock.advance(Duration::minutes(1));
+        soft_delete_epic(&mut state, epic_uuid, &clock).unwrap();
+        assert!(ranked_epics(&state).is_empty() && active_stories(&state).is_empty());
+        assert_eq!(get_story(&state, story_uuid).unwrap().deleted_at, Some(clock.now()));
+
+        // Restoring the epic brings back only the story deleted along with it.
+        restore_epic(&mut state, epic_uuid, &clock).unwrap();
+        assert_eq!(active_stories(&state).iter().map(|story| story.story_uuid).collect::<Vec<_>>(), vec![story_uuid]);
+        assert_eq!(deleted_stories(&state).len(), 1);
+
+        assert_eq!(purge_deleted(&mut state, &clock).unwrap().0, 1);
+        assert!(get_story(&state, earlier).is_err());
+        assert_eq!(get_epic(&state, epic_uuid).unwrap().story_uuids, vec![story_uuid]);
+
+        soft_delete_epic(&mut state, epic_uuid, &clock).unwrap();
+        assert_eq!(purge_deleted(&mut state, &clock).unwrap().0, 2);
+        assert!(state.epics.is_empty() && state.stories.is_empty());
+    }
+}
@@ -0,0 +1,474 @@
+//! Undo operations: running mutations as commands on an undo stack, so they can be undone and
+//! redone.
+//!
+//! Each command is reversed by restoring a snapshot of the project data taken just before it ran,
+//! so every cascade a command triggers (history entries, auto-closed epics, cleaned-up references)
+//! is undone with it. The snapshots leave out the user's account and settings: undo never brings
+//! back an old password or preference, and the stack holds no credentials.
+
+use super::{
+    ServiceError,
+    archive::archive_epic,
+    custom_fields::{set_epic_field, set_story_field},
+    epics::{DeletePolicy, create_epic, delete_epic, rename_epic, set_epic_description, set_epic_status},
+    stories::{create_story, delete_story, move_story_to_epic, rename_story, set_story_description, set_story_status},
+    labels::label_epic,
+    references::ItemRef,
+    schedule::{set_epic_dates, set_story_dates},
+    trash::soft_delete_epic,
+};
+use crate::clock::Clock;
+use crate::db::ClearTextDBState;
+use crate::models::custom_fields::{FieldId, FieldValue};
+use crate::models::{Status, attachments::Attachment, epics::Epic, goals::Goal, history::HistoryEntry, projects::Project};
+use crate::models::{sprints::Sprint, stories::Story, tasks::Task, work_logs::WorkLog};
+use chrono::NaiveDate;
+use std::fmt;
+use uuid::Uuid;
+
+/// How many commands an [`UndoStack`] remembers by default.
+pub const DEFAULT_UNDO_LIMIT: usize = 100;
+
+/// # Command enum
+/// A reversible change to epics or stories.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Command {
+    /// Creates an epic (see [`create_epic`]), then labels it and sets its priority.
+    CreateEpic {
+        /// The new epic's title.
+        title: String,
+        /// The new epic's description.
+        description: String,
+        /// The labels to give it (see [`label_epic`]).
+        labels: Vec<Uuid>,
+        /// The priority field and the option to set it to, if any (see [`set_epic_field`]).
+        priority: Option<(FieldId, FieldValue)>,
+    },
+    /// Renames an epic (see [`rename_epic`]).
+    RenameEpic {
+        /// The epic to rename.
+        epic_uuid: Uuid,
+        /// The new title.
+        title: String,
+    },
+    /// Changes an epic's description (see [`set_epic_description`]).
+    SetEpicDescription {
+        /// The epic to change.
+        epic_uuid: Uuid,
+        /// The new description.
+        description: String,
+    },
+    /// Changes an epic's status (see [`set_epic_status`]).
+    SetEpicStatus {
+        /// The epic to change.
+        epic_uuid: Uuid,
+        /// The new status.
+        status: Status,
+    },
+    /// Archives an epic and its stories (see [`archive_epic`]).
+    ArchiveEpic {
+        /// The epic to archive.
+        epic_uuid: Uuid,
+    },
+    /// Moves an epic and its stories to the trash (see [`soft_delete_epic`]).
+    TrashEpic {
+        /// The epic to move to the trash.
+        epic_uuid: Uuid,
+    },
+    /// Deletes an epic (see [`delete_epic`]).
+    DeleteEpic {
+        /// The epic to delete.
+        epic_uuid: Uuid,
+        /// What happens to its stories.
+        policy: DeletePolicy,
+    },
+    /// Creates a story in an epic (see [`create_story`]), then sets its status, priority, and due
+    /// date.
+    CreateStory {
+        /// The epic to add the story to.
+        epic_uuid: Uuid,
+        /// The new story's title.
+        title: String,
+        /// The new story's description.
+        description: String,
+        /// The status to start in (see [`set_story_status`]).
+        status: Status,
+        /// The priority field and the option to set it to, if any (see [`set_story_field`]).
+        priority: Option<(FieldId, FieldValue)>,
+        /// The due date, if any (see [`set_story_dates`]).
+        due_date: Option<NaiveDate>,
+    },
+    /// Renames a story (see [`rename_story`]).
+    RenameStory {
+        /// The story to rename.
+        story_uuid: Uuid,
+        /// The new title.
+        title: String,
+    },
+    /// Changes a story's description (see [`set_story_description`]).
+    SetStoryDescription {
+        /// The story to change.
+        story_uuid: Uuid,
+        /// The new description.
+        description: String,
+    },
+    /// Changes a story's status (see [`set_story_status`]).
+    SetStoryStatus {
+        /// The story to change.
+        story_uuid: Uuid,
+        /// The new status.
+        status: Status,
+    },
+    /// Moves a story to another epic (see [`move_story_to_epic`]).
+    MoveStory {
+        /// The story to move.
+        story_uuid: Uuid,
+        /// The epic to move it to.
+        epic_uuid: Uuid,
+    },
+    /// Deletes a story (see [`delete_story`]).
+    DeleteStory {
+        /// The story to delete.
+        story_uuid: Uuid,
+    },
+    /// Changes any of an epic's or story's title, description, and dates at once.
+    EditItem {
+        /// The epic or story to change.
+        item: ItemRef,
+        /// The new title, if it changed.
+        title: Option<String>,
+        /// The new description, if it changed.
+        description: Option<String>,
+        /// The new start and due dates, set together (see [`set_epic_dates`]), if either changed.
+        dates: Option<(Option<NaiveDate>, Option<NaiveDate>)>,
+    },
+}
+
+impl Command {
+    /// Runs the command.
+    ///
+    /// # Returns
+    /// * `Result<Option<Uuid>, ServiceError>` - The UUID of the created item, for create commands.
+    /// # Errors
+    /// * `ServiceError` - Whatever the underlying service operation returns.
+    pub fn apply(self, state: &mut ClearTextDBState, clock: &dyn Clock) -> Result<Option<Uuid>, ServiceError> {
+        match self {
+            Command::CreateEpic { title, description, labels, priority } => {
+                let epic_uuid = create_epic(state, &title, description, clock)?;
+                for label_uuid in labels {
+                    label_epic(state, epic_uuid, label_uuid, clock)?;
+                }
+                if let Some((field_id, value)) = priority {
+                    set_epic_field(state, epic_uuid, field_id, Some(value), clock)?;
+                }
+                Ok(Some(epic_uuid))
+            }
+            Command::RenameEpic { epic_uuid, title } => rename_epic(state, epic_uuid, &title, clock).map(|()| None),
+            Command::SetEpicDescription { epic_uuid, description } => set_epic_description(state, epic_uuid, description, clock).map(|()| None),
+            Command::SetEpicStatus { epic_uuid, status } => set_epic_status(state, epic_uuid, status, clock).map(|()| None),
+            Command::ArchiveEpic { epic_uuid } => archive_epic(state, epic_uuid, clock).map(|()| None),
+            Command::TrashEpic { epic_uuid } => soft_delete_epic(state, epic_uuid, clock).map(|()| None),
+            Command::DeleteEpic { epic_uuid, policy } => delete_epic(state, epic_uuid, policy, clock).map(|_| None),
+            Command::CreateStory { epic_uuid, title, description, status, priority, due_date } => {
+                let story_uuid = create_story(state, epic_uuid, &title, description, clock)?;
+                if status != Status::Open {
+                    set_story_status(state, story_uuid, status, clock)?;
+                }
+                if let Some((field_id, value)) = priority {
+                    set_story_field(state, story_uuid, field_id, Some(value), clock)?;
+                }
+                if due_date.is_some() {
+                    set_story_dates(state, story_uuid, None, due_date, clock)?;
+                }
+                Ok(Some(story_uuid))
+            }
+            Command::RenameStory { story_uuid, title } => rename_story(state, story_uuid, &title, clock).map(|()| None),
+            Command::SetStoryDescription { story_uuid, description } => set_story_description(state, story_uuid, description, clock).map(|()| None),
+            Command::SetStoryStatus { story_uuid, status } => set_story_status(state, story_uuid, status, clock).map(|()| None),
+            Command::MoveStory { story_uuid, epic_uuid } => move_story_to_epic(state, story_uuid, epic_uuid, clock).map(|()| None),
+            Command::DeleteStory { story_uuid } => delete_story(state, story_uuid, clock).map(|_| None),
+            Command::EditItem { item: ItemRef::Epic(epic_uuid), title, description, dates } => {
+                if let Some(title) = title {
+                    rename_epic(state, epic_uuid, &title, clock)?;
+                }
+                if let Some(description) = description {
+                    set_epic_description(state, epic_uuid, description, clock)?;
+                }
+                if let Some((start_date, due_date)) = dates {
+                    set_epic_dates(state, epic_uuid, start_date, due_date, clock)?;
+                }
+                Ok(None)
+            }
+            Command::EditItem { item: ItemRef::Story(story_uuid), title, description, dates } => {
+                if let Some(title) = title {
+                    rename_story(state, story_uuid, &title, clock)?;
+                }
+                if let Some(description) = description {
+                    set_story_description(state, story_uuid, description, clock)?;
+                }
+                if let Some((start_date, due_date)) = dates {
+                    set_story_dates(state, story_uuid, start_date, due_date, clock)?;
+                }
+                Ok(None)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Command {
+    /// Describes the command for "Undo ..." and "Redo ..." prompts, e.g. `create epic "Launch"`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Command::CreateEpic { title, .. } => write!(f, "create epic {title:?}"),
+            Command::RenameEpic { title, .. } => write!(f, "rename epic to {title:?}"),
+            Command::SetEpicDescription { .. } => write!(f, "edit epic description"),
+            Command::SetEpicStatus { status, .. } => write!(f, "set epic status to {status:?}"),
+            Command::ArchiveEpic { .. } => write!(f, "archive epic"),
+            Command::TrashEpic { .. } => write!(f, "move epic to the trash"),
+            Command::DeleteEpic { .. } => write!(f, "delete epic"),
+            Command::CreateStory { title, .. } => write!(f, "create story {title:?}"),
+            Command::RenameStory { title, .. } => write!(f, "rename story to {title:?}"),
+            Command::SetStoryDescription { .. } => write!(f, "edit story description"),
+            Command::SetStoryStatus { status, .. } => write!(f, "set story status to {status:?}"),
+            Command::MoveStory { .. } => write!(f, "move story to another epic"),
+            Command::DeleteStory { .. } => write!(f, "delete story"),
+            Command::EditItem { item: ItemRef::Epic(_), .. } => write!(f, "edit epic"),
+            Command::EditItem { item: ItemRef::Story(_), .. } => write!(f, "edit story"),
+        }
+    }
+}
+
+/// # Snapshot struct
+/// The parts of a database state a command can change: the epics and stories, their history, and
+/// the items that refer to them.
+#[derive(Clone, Debug)]
+struct Snapshot {
+    /// The epics.
+    epics: Vec<Epic>,
+    /// The stories.
+    stories: Vec<Story>,
+    /// The stories' tasks.
+    tasks: Vec<Task>,
+    /// The sprints, which list stories.
+    sprints: Vec<Sprint>,
+    /// The attachment metadata.
+    attachments: Vec<Attachment>,
+    /// The change history.
+    history: Vec<HistoryEntry>,
+    /// The time logged against stories.
+    work_logs: Vec<WorkLog>,
+    /// The projects, which list epics.
+    projects: Vec<Project>,
+    /// The goals, which list epics.
+    goals: Vec<Goal>,
+    /// The number the next new epic gets.
+    next_epic_number: u32,
+    /// The number the next new story gets.
+    next_story_number: u32,
+}
+
+impl Snapshot {
+    /// Takes the snapshot out of a state that is no longer needed.
+    fn of(state: ClearTextDBState) -> Self {
+        Self {
+            epics: state.epics,
+            stories: state.stories,
+            tasks: state.tasks,
+            sprints: state.sprints,
+            attachments: state.attachments,
+            history: state.history,
+            work_logs: state.work_logs,
+            projects: state.projects,
+            goals: state.goals,
+            next_epic_number: state.next_epic_number,
+            next_story_number: state.next_story_number,
+        }
+    }
+
+    /// Puts the snapshot into `state`, returning what it replaced.
+    fn restore(mut self, state: &mut ClearTextDBState) -> Self {
+        std::mem::swap(&mut self.epics, &mut state.epics);
+        std::mem::swap(&mut self.stories, &mut state.stories);
+        std::mem::swap(&mut self.tasks, &mut state.tasks);
+        std::mem::swap(&mut self.sprints, &mut state.sprints);
+        std::mem::swap(&mut self.attachments, &mut state.attachments);
+        std::mem::swap(&mut self.history, &mut state.history);
+        std::mem::swap(&mut self.work_logs, &mut state.work_logs);
+        std::mem::swap(&mut self.projects, &mut state.projects);
+        std::mem::swap(&mut self.goals, &mut state.goals);
+        std::mem::swap(&mut self.next_epic_number, &mut state.next_epic_number);
+        std::mem::swap(&mut self.next_story_number, &mut state.next_story_number);
+        state.mark_dirty();
+        self
+    }
+}
+
+/// # Undo Stack struct
+/// The commands run in a session, with the project data to go back to for each.
+#[derive(Clone, Debug)]
+pub struct UndoStack {
+    /// Done commands, most recent last, each with a snapshot from just before it ran.
+    undo: Vec<(String, Snapshot)>,
+    /// Undone commands, most recently undone last, each with a snapshot from just before it was
+    /// undone.
+    redo: Vec<(String, Snapshot)>,
+    /// How many commands to remember; the oldest are forgotten first.
+    limit: usize,
+}
+
+impl Default for UndoStack {
+    fn default() -> Self {
+        Self::new(DEFAULT_UNDO_LIMIT)
+    }
+}
+
+impl UndoStack {
+    /// Creates an empty undo stack remembering up to `limit` commands.
+    #[must_use]
+    pub fn new(limit: usize) -> Self {
+        Self { undo: Vec::new(), redo: Vec::new(), limit }
+    }
+
+    /// Runs a command and records it for undoing. A failed command leaves the state untouched.
+    ///
+    /// # Returns
+    /// * `Result<Option<Uuid>, ServiceError>` - The UUID of the created item, for create commands.
+    /// # Errors
+    /// * `ServiceError` - Whatever the command returns.
+    pub fn execute(&mut self, state: &mut ClearTextDBState, command: Command, clock: &dyn Clock) -> Result<Option<Uuid>, ServiceError> {
+        let label = command.to_string();
+        self.execute_with(state, label, |state| command.apply(state, clock))
+    }
+
+    /// Runs any mutation and records it for undoing under `label`. A failed mutation leaves the
+    /// state untouched.
+    ///
+    /// Undoing only restores the project data a [`Command`] can change; changes `change` makes to
+    /// anything else, such as labels or settings, are kept.
+    ///
+    /// # Returns
+    /// * `Result<R, ServiceError>` - Whatever `change` returned.
+    /// # Errors
+    /// * `ServiceError` - Whatever `change` returns.
+    pub fn execute_with<R>(
+        &mut self,
+        state: &mut ClearTextDBState,
+        label: impl Into<String>,
+        change: impl FnOnce(&mut ClearTextDBState) -> Result<R, ServiceError>,
+    ) -> Result<R, ServiceError> {
+        let before = state.clone();
+        match change(state) {
+            Ok(result) => {
+                self.undo.push((label.into(), Snapshot::of(before)));
+                if self.undo.len() > self.limit {
+                    self.undo.remove(0);
+                }
+                self.redo.clear();
+                Ok(result)
+            }
+            Err(error) => {
+                *state = before;
+                Err(error)
+            }
+        }
+    }
+
+    /// Undoes the most recent command.
+    ///
+    /// # Returns
+    /// * `Option<String>` - The undone command's label, or `None` if there was nothing to undo.
+    pub fn undo(&mut self, state: &mut ClearTextDBState) -> Option<String> {
+        let (label, before) = self.undo.pop()?;
+        self.redo.push((label.clone(), before.restore(state)));
+        Some(label)
+    }
+
+    /// Redoes the most recently undone command.
+    ///
+    /// # Returns
+    /// * `Option<String>` - The redone command's label, or `None` if there was nothing to redo.
+    pub fn redo(&mut self, state: &mut ClearTextDBState) -> Option<String> {
+        let (label, after) = self.redo.pop()?;
+        self.undo.push((label.clone(), after.restore(state)));
+        Some(label)
+    }
+
+    /// The label of the command [`UndoStack::undo`] would undo.
+    #[must_use]
+    pub fn undo_label(&self) -> Option<&str> {
+        self.undo.last().map(|(label, _)| label.as_str())
+    }
+
+    /// The label of the command [`UndoStack::redo`] would redo.
+    #[must_use]
+    pub fn redo_label(&self) -> Option<&str> {
+        self.redo.last().map(|(label, _)| label.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::service::{get_epic, get_story};
+    use crate::users::User;
+
+    #[test]
+    fn test_undo_and_redo() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let mut stack = UndoStack::default();
+        let create = Command::CreateEpic { title: "Launch".to_string(), description: String::new(), labels: Vec::new(), priority: None };
+        let epic_uuid = stack.execute(&mut state, create, &SystemClock).unwrap().unwrap();
+        let create = Command::CreateStory { epic_uuid, title: "Docs".to_string(), description: String::new(), status: Status::Open, priority: None, due_date: None };
+        let story_uuid = stack.execute(&mut state, create, &SystemClock).unwrap().unwrap();
+        stack.execute(&mut state, Command::SetStoryStatus { story_uuid, status: Status::Closed }, &SystemClock).unwrap();
+        assert_eq!(stack.undo_label(), Some("set story status to Closed"));
+
+        assert_eq!(stack.undo(&mut state).as_deref(), Some("set story status to Closed"));
+        assert_eq!(get_story(&state, story_uuid).unwrap().status, Status::Open);
+        stack.undo(&mut state);
+        assert!(get_story(&state, story_uuid).is_err());
+        assert!(get_epic(&state, epic_uuid).unwrap().story_uuids.is_empty());
+
+        assert_eq!(stack.redo(&mut state).as_deref(), Some("create story \"Docs\""));
+        assert_eq!(get_epic(&state, epic_uuid).unwrap().story_uuids, vec![story_uuid]);
+        stack.execute(&mut state, Command::DeleteStory { story_uuid }, &SystemClock).unwrap();
+        assert_eq!(stack.redo_label(), None);
+        stack.undo(&mut state);
+        assert_eq!(get_story(&state, story_uuid).unwrap().title, "Docs");
+    }
+
+    #[test]
+    fn test_failed_commands_and_limit() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let mut stack = UndoStack::new(1);
+        let missing = Uuid::new_v4();
+        let failed = stack.execute(&mut state, Command::RenameEpic { epic_uuid: missing, title: "x".to_string() }, &SystemClock);
+        assert_eq!(failed, Err(ServiceError::EpicNotFound(missing)));
+        assert!(stack.undo(&mut state).is_none());
+
+        for title in ["First", "Second"] {
+            stack.execute(&mut state, Command::CreateEpic { title: title.to_string(), description: String::new(), labels: Vec::new(), priority: None }, &SystemClock).unwrap();
+        }
+        stack.undo(&mut state);
+        assert!(stack.undo(&mut state).is_none());
+        assert_eq!(state.epics.len(), 1);
+    }
+
+    #[test]
+    fn test_undo_keeps_the_account_and_settings() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let mut stack = UndoStack::default();
+        stack.execute(&mut state, Command::CreateEpic { title: "Launch".to_string(), description: String::new(), labels: Vec::new(), priority: None }, &SystemClock).unwrap();
+        state.user = User::new("alice".to_string(), "new password").unwrap();
+        state.settings.lock_after_minutes = 5;
+
+        stack.undo(&mut state);
+        assert!(state.epics.is_empty() && state.is_dirty());
+        assert!(state.user.verify_password("new password").unwrap());
+        assert_eq!(state.settings.lock_after_minutes, 5);
+        stack.redo(&mut state);
+        assert_eq!(state.epics.len(), 1);
+        assert!(state.user.verify_password("new password").unwrap());
+    }
+}
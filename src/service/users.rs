@@ -0,0 +1,55 @@
+//! User operations: creating accounts and keeping their timestamps current.
+
+use crate::clock::Clock;
+use crate::db::ClearTextDBState;
+use crate::security::SecurityError;
+use crate::users::User;
+
+/// Creates a new user account stamped with the current time.
+///
+/// # Errors
+/// * `SecurityError::Argon2` / `SecurityError::PasswordHash` - If the password could not be hashed.
+pub fn register_user(username: String, password: &str, clock: &dyn Clock) -> Result<User, SecurityError> {
+    let mut user = User::new(username, password)?;
+    user.created_at = clock.now();
+    user.updated_at = user.created_at;
+    Ok(user)
+}
+
+/// Applies a change to the logged-in user and, if anything changed, bumps their `updated_at`
+/// timestamp and marks the database dirty.
+///
+/// # Returns
+/// * `R` - Whatever `change` returned.
+pub fn update_user<R>(state: &mut ClearTextDBState, clock: &dyn Clock, change: impl FnOnce(&mut User) -> R) -> R {
+    let before = state.user.clone();
+    let result = change(&mut state.user);
+    if state.user != before {
+        state.user.updated_at = clock.now();
+        state.mark_dirty();
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+    use chrono::{DateTime, Duration};
+
+    #[test]
+    fn test_user_timestamps() {
+        let clock = FixedClock::new(DateTime::from_timestamp(1_700_000_000, 0).unwrap());
+        let user = register_user("alice".to_string(), "password", &clock).unwrap();
+        assert_eq!((user.created_at, user.updated_at), (clock.now(), clock.now()));
+        let created_at = user.created_at;
+        let mut state = ClearTextDBState::new(user);
+
+        clock.advance(Duration::hours(1));
+        update_user(&mut state, &clock, |_| ());
+        assert_eq!(state.user.updated_at, created_at);
+        update_user(&mut state, &clock, |user| user.username = "alicia".to_string());
+        assert_eq!(state.user.updated_at, clock.now());
+        assert_eq!(state.user.created_at, created_at);
+    }
+}
@@ -0,0 +1,114 @@
+//! Watcher operations: subscribing users to epics and stories, and their inbox of changes to the
+//! items they watch.
+
+use super::{ServiceError, get_epic_mut, get_story_mut, history::activity_feed, require};
+use crate::db::ClearTextDBState;
+use crate::models::roles::Permission;
+use crate::models::history::HistoryEntry;
+use uuid::Uuid;
+
+/// Adds a user to an epic's watchers. Watching twice is harmless.
+///
+/// # Errors
+/// * `ServiceError::EpicNotFound` - If the epic does not exist.
+pub fn watch_epic(state: &mut ClearTextDBState, epic_uuid: Uuid, user_uuid: Uuid) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    let watchers = &mut get_epic_mut(state, epic_uuid)?.watchers;
+    if !watchers.contains(&user_uuid) {
+        watchers.push(user_uuid);
+    }
+    Ok(())
+}
+
+/// Removes a user from an epic's watchers.
+///
+/// # Errors
+/// * `ServiceError::EpicNotFound` - If the epic does not exist.
+pub fn unwatch_epic(state: &mut ClearTextDBState, epic_uuid: Uuid, user_uuid: Uuid) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    get_epic_mut(state, epic_uuid)?.watchers.retain(|uuid| *uuid != user_uuid);
+    Ok(())
+}
+
+/// Adds a user to a story's watchers. Watching twice is harmless.
+///
+/// # Errors
+/// * `ServiceError::StoryNotFound` - If the story does not exist.
+pub fn watch_story(state: &mut ClearTextDBState, story_uuid: Uuid, user_uuid: Uuid) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    let watchers = &mut get_story_mut(state, story_uuid)?.watchers;
+    if !watchers.contains(&user_uuid) {
+        watchers.push(user_uuid);
+    }
+    Ok(())
+}
+
+/// Removes a user from a story's watchers.
+///
+/// # Errors
+/// * `ServiceError::StoryNotFound` - If the story does not exist.
+pub fn unwatch_story(state: &mut ClearTextDBState, story_uuid: Uuid, user_uuid: Uuid) -> Result<(), ServiceError> {
+    require(state, Permission::Edit)?;
+    get_story_mut(state, story_uuid)?.watchers.retain(|uuid| *uuid != user_uuid);
+    Ok(())
+}
+
+/// Lists the UUIDs of the epics and stories a user watches.
+#[must_use]
+pub fn watched_by(state: &ClearTextDBState, user_uuid: Uuid) -> Vec<Uuid> {
+    let epics = state.epics.iter().filter(|epic| epic.watchers.contains(&user_uuid)).map(|epic| epic.epic_uuid);
+    let stories = state.stories.iter().filter(|story| story.watchers.contains(&user_uuid)).map(|story| story.story_uuid);
+    epics.chain(stories).collect()
+}
+
+/// Returns the most recent changes to the items a user watches, newest first.
+///
+/// # Arguments
+/// * `state` - The database state to read.
+/// * `user_uuid` - The watching user.
+/// * `limit` - The maximum number of entries to return.
+#[must_use]
+pub fn inbox(state: &ClearTextDBState, user_uuid: Uuid, limit: usize) -> Vec<&HistoryEntry> {
+    let watched = watched_by(state, user_uuid);
+    let mut entries = activity_feed(state, usize::MAX);
+    entries.retain(|entry| watched.contains(&entry.item_uuid));
+    entries.truncate(limit);
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::models::Status;
+    use crate::service::{epics::create_epic, stories::{create_story, rename_story, set_story_status}};
+    use crate::users::User;
+
+    #[test]
+    fn test_inbox_shows_watched_changes() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let me = state.user.user_uuid;
+        let epic_uuid = create_epic(&mut state, "Epic", String::new(), &SystemClock).unwrap();
+        let watched = create_story(&mut state, epic_uuid, "Watched", String::new(), &SystemClock).unwrap();
+        let ignored = create_story(&mut state, epic_uuid, "Ignored", String::new(), &SystemClock).unwrap();
+        watch_story(&mut state, watched, me).unwrap();
+        watch_story(&mut state, watched, me).unwrap();
+        assert_eq!(watched_by(&state, me), vec![watched]);
+
+        set_story_status(&mut state, watched, Status::InProgress, &SystemClock).unwrap();
+        set_story_status(&mut state, ignored, Status::InProgress, &SystemClock).unwrap();
+        let entries = inbox(&state, me, 10);
+        assert_eq!(entries.len(), 1);
+        assert_eq!((entries[0].item_uuid, entries[0].field.as_str()), (watched, "status"));
+
+        unwatch_story(&mut state, watched, me).unwrap();
+        rename_story(&mut state, watched, "Renamed", &SystemClock).unwrap();
+        assert!(inbox(&state, me, 10).is_empty());
+        watch_epic(&mut state, epic_uuid, me).unwrap();
+        assert_eq!(watched_by(&state, me), vec![epic_uuid]);
+        unwatch_epic(&mut state, epic_uuid, me).unwrap();
+        assert!(watched_by(&state, me).is_empty());
+        let missing = Uuid::new_v4();
+        assert_eq!(watch_epic(&mut state, missing, me), Err(ServiceError::EpicNotFound(missing)));
+    }
+}
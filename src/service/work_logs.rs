@@ -0,0 +1,165 @@
+//! Work log operations: timing work on stories and totalling the time spent.
+//!
+//! Only one timer runs at a time per database, since a database belongs to one person.
+
+use super::{ServiceError, get_epic, get_story, require};
+use crate::clock::Clock;
+use crate::db::ClearTextDBState;
+use crate::models::roles::Permission;
+use crate::models::work_logs::WorkLog;
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+/// Starts a timer on a story.
+///
+/// # Returns
+/// * `Result<Uuid, ServiceError>` - The UUID of the running work log.
+/// # Errors
+/// * `ServiceError::StoryNotFound` - If the story does not exist.
+/// * `ServiceError::TimerRunning` - If a timer is already running.
+pub fn start_timer(state: &mut ClearTextDBState, story_uuid: Uuid, clock: &dyn Clock) -> Result<Uuid, ServiceError> {
+    require(state, Permission::Edit)?;
+    get_story(state, story_uuid)?;
+    if let Some(running) = running_timer(state) {
+        return Err(ServiceError::TimerRunning(running.work_log_uuid));
+    }
+    let work_log = WorkLog::start(story_uuid, clock.now());
+    let work_log_uuid = work_log.work_log_uuid;
+    state.mark_dirty();
+    state.work_logs.push(work_log);
+    Ok(work_log_uuid)
+}
+
+/// Stops the running timer, recording its duration and a note.
+///
+/// # Returns
+/// * `Result<&WorkLog, ServiceError>` - The finished work log.
+/// # Errors
+/// * `ServiceError::NoTimerRunning` - If no timer is running.
+pub fn stop_timer<'a>(state: &'a mut ClearTextDBState, note: String, clock: &dyn Clock) -> Result<&'a WorkLog, ServiceError> {
+    require(state, Permission::Edit)?;
+    let now = clock.now();
+    state.mark_dirty();
+    let work_log = state
+        .work_logs
+        .iter_mut()
+        .find(|work_log| work_log.is_running())
+        .ok_or(ServiceError::NoTimerRunning)?;
+    let elapsed = work_log.duration(now).num_seconds();
+    work_log.duration_seconds = Some(u64::try_from(elapsed).unwrap_or(0));
+    work_log.note = note;
+    Ok(work_log)
+}
+
+/// Returns the running timer, if any.
+#[must_use]
+pub fn running_timer(state: &ClearTextDBState) -> Option<&WorkLog> {
+    state.work_logs.iter().find(|work_log| work_log.is_running())
+}
+
+/// Records time spent on a story after the fact.
+///
+/// # Returns
+/// * `Result<Uuid, ServiceError>` - The UUID of the new work log.
+/// # Errors
+/// * `ServiceError::StoryNotFound` - If the story does not exist.
+pub fn log_work(
+    state: &mut ClearTextDBState,
+    story_uuid: Uuid,
+    started_at: DateTime<Utc>,
+    duration_seconds: u64,
+    note: String,
+) -> Result<Uuid, ServiceError> {
+    require(state, Permission::Edit)?;
+    get_story(state, story_uuid)?;
+    let work_log = WorkLog {
+        duration_seconds: Some(duration_seconds),
+        note,
+        ..WorkLog::start(story_uuid, started_at)
+    };
+    let work_log_uuid = work_log.work_log_uuid;
+    state.mark_dirty();
+    state.work_logs.push(work_log);
+    Ok(work_log_uuid)
+}
+
+/// Deletes a work log entry.
+///
+/// # Errors
+/// * `ServiceError::WorkLogNotFound` - If the entry does not exist.
+pub fn delete_work_log(state: &mut ClearTextDBState, work_log_uuid: Uuid) -> Result<WorkLog, ServiceError> {
+    require(state, Permission::Edit)?;
+    let index = state
+        .work_logs
+        .iter()
+        .position(|work_log| work_log.work_log_uuid == work_log_uuid)
+        .ok_or(ServiceError::WorkLogNotFound(work_log_uuid))?;
+    state.mark_dirty();
+    Ok(state.work_logs.remove(index))
+}
+
+/// Lists a story's work log entries, oldest first.
+#[must_use]
+pub fn story_work_logs(state: &ClearTextDBState, story_uuid: Uuid) -> Vec<&WorkLog> {
+    let mut work_logs: Vec<&WorkLog> = state.work_logs.iter().filter(|work_log| work_log.story_uuid == story_uuid).collect();
+    work_logs.sort_by_key(|work_log| work_log.started_at);
+    work_logs
+}
+
+/// Totals the time spent on a story, including a running timer up to now.
+#[must_use]
+pub fn story_time_spent(state: &ClearTextDBState, story_uuid: Uuid, clock: &dyn Clock) -> Duration {
+    let now = clock.now();
+    story_work_logs(state, story_uuid)
+        .iter()
+        .map(|work_log| work_log.duration(now))
+        .fold(Duration::zero(), |total, duration| total + duration)
+}
+
+/// Totals the time spent on all of an epic's stories.
+///
+/// # Errors
+/// * `ServiceError::EpicNotFound` - If the epic does not exist.
+pub fn epic_time_spent(state: &ClearTextDBState, epic_uuid: Uuid, clock: &dyn Clock) -> Result<Duration, ServiceError> {
+    Ok(get_epic(state, epic_uuid)?
+        .story_uuids
+        .iter()
+        .map(|story_uuid| story_time_spent(state, *story_uuid, clock))
+        .fold(Duration::zero(), |total, duration| total + duration))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+    use crate::models::{epics::Epic, stories::Story};
+    use crate::users::User;
+
+    #[test]
+    fn test_timer_and_totals() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let mut epic = Epic::new("Epic".to_string(), String::new());
+        let [first, second]: [Uuid; 2] = std::array::from_fn(|_| {
+            let story = Story::new("Story".to_string(), String::new());
+            let uuid = story.story_uuid;
+            epic.story_uuids.push(uuid);
+            state.stories.push(story);
+            uuid
+        });
+        let epic_uuid = epic.epic_uuid;
+        state.epics.push(epic);
+        let clock = FixedClock::new(DateTime::from_timestamp(1_700_000_000, 0).unwrap());
+
+        let running = start_timer(&mut state, first, &clock).unwrap();
+        assert_eq!(start_timer(&mut state, second, &clock), Err(ServiceError::TimerRunning(running)));
+        clock.advance(Duration::minutes(25));
+        assert_eq!(story_time_spent(&state, first, &clock), Duration::minutes(25));
+        assert_eq!(stop_timer(&mut state, "Pairing".to_string(), &clock).unwrap().duration_seconds, Some(25 * 60));
+        assert_eq!(stop_timer(&mut state, String::new(), &clock), Err(ServiceError::NoTimerRunning));
+
+        log_work(&mut state, second, clock.now(), 3600, "Review".to_string()).unwrap();
+        clock.advance(Duration::hours(1));
+        assert_eq!(story_time_spent(&state, first, &clock), Duration::minutes(25));
+        assert_eq!(epic_time_spent(&state, epic_uuid, &clock).unwrap(), Duration::minutes(85));
+    }
+}
@@ -0,0 +1,109 @@
+//! # Terminal
+//! Puts the terminal the application runs in into raw mode, so keys reach the
+//! [line editor](crate::pages::line_editor::LineEditor) one at a time as they are typed, instead
+//! of as whole lines after the terminal's own line editing, and nothing typed is echoed by the
+//! terminal: the editor draws the line itself, with passwords masked. Ctrl+Z and Ctrl+C reach the
+//! application as keys, to undo and to end the input, instead of the shell suspending or killing
+//! it with the database unsaved. The terminal is put back as
+//! it was when the [`RawMode`] guard is dropped, or by a panic hook if the application panics
+//! first. When standard input isn't a terminal, e.g. when keys are piped in, it is left alone.
+
+use crate::pages::line_editor::{Key, KeyDecoder};
+use std::io::Read;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether a [`RawMode`] guard holds the terminal.
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// The terminal's settings from before raw mode, put back by [`restore`].
+#[cfg(unix)]
+static SAVED: std::sync::Mutex<Option<rustix::termios::Termios>> = std::sync::Mutex::new(None);
+
+/// # `RawMode` struct
+/// Holds standard input's terminal in raw mode until it is dropped.
+#[derive(Debug)]
+pub struct RawMode(());
+
+impl RawMode {
+    /// Switches standard input's terminal to raw mode, with a panic hook that switches it back.
+    ///
+    /// # Returns
+    /// * `Option<RawMode>` - The guard, or `None` if standard input isn't a terminal or its
+    ///   settings can't be changed, in which case they are left as they were.
+    #[must_use]
+    pub fn enable() -> Option<Self> {
+        #[cfg(unix)]
+        {
+            use rustix::termios::{LocalModes, OptionalActions, SpecialCodeIndex, isatty, tcgetattr, tcsetattr};
+            let stdin = std::io::stdin();
+            if !isatty(&stdin) {
+                return None;
+            }
+            let saved = tcgetattr(&stdin).ok()?;
+            let mut raw = saved.clone();
+            raw.local_modes.remove(LocalModes::ECHO | LocalModes::ICANON | LocalModes::ISIG);
+            raw.special_codes[SpecialCodeIndex::VMIN] = 1;
+            raw.special_codes[SpecialCodeIndex::VTIME] = 0;
+            tcsetattr(&stdin, OptionalActions::Flush, &raw).ok()?;
+            *SAVED.lock().unwrap_or_else(std::sync::PoisonError::into_inner) = Some(saved);
+            let hook = std::panic::take_hook();
+            std::panic::set_hook(Box::new(move |info| {
+                restore();
+                hook(info);
+            }));
+            ACTIVE.store(true, Ordering::SeqCst);
+            Some(Self(()))
+        }
+        #[cfg(not(unix))]
+        None
+    }
+
+    /// Whether the terminal is in raw mode, so the keys typed aren't echoed unless the line
+    /// editor [echoes](crate::pages::line_editor::LineEditor::set_echo) them.
+    #[must_use]
+    pub fn is_active() -> bool {
+        ACTIVE.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        restore();
+    }
+}
+
+/// Puts the terminal back as it was before raw mode, if it was changed and not put back yet.
+fn restore() {
+    ACTIVE.store(false, Ordering::SeqCst);
+    #[cfg(unix)]
+    if let Some(saved) = SAVED.lock().unwrap_or_else(std::sync::PoisonError::into_inner).take() {
+        // Best effort: there is nothing left to do if the terminal went away.
+        let _ = rustix::termios::tcsetattr(std::io::stdin(), rustix::termios::OptionalActions::Now, &saved);
+    }
+}
+
+/// The keys typed on a terminal in raw mode, read from `input` as they arrive, until it ends or
+/// can't be read. Each key is decoded as soon as its last byte is read, with a [`KeyDecoder`].
+pub fn keys(mut input: impl Read) -> impl Iterator<Item = Key> {
+    let mut buffer = [0; 64];
+    let mut decoder = KeyDecoder::default();
+    std::iter::from_fn(move || match input.read(&mut buffer) {
+        Ok(0) | Err(_) => None,
+        Ok(read) => Some(decoder.feed(&buffer[..read])),
+    })
+    .flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keys_are_read_as_they_arrive() {
+        let typed: &[u8] = b"ab\x7f\x1b[D\r";
+        assert_eq!(keys(typed).collect::<Vec<_>>(), [Key::Char('a'), Key::Char('b'), Key::Backspace, Key::Left, Key::Enter]);
+        // A key split between reads is decoded once the rest arrives.
+        let split = std::io::Read::chain(&b"\x1b["[..], &b"A\xc3"[..]).chain(&b"\xa9"[..]);
+        assert_eq!(keys(split).collect::<Vec<_>>(), [Key::Up, Key::Char('é')]);
+    }
+}
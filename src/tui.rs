@@ -0,0 +1,530 @@
+//! # Full-Screen Interface
+//! A ratatui interface with three panels side by side: the epic tree, the selected epic's
+//! stories, and the details of the selected epic or story. The layout is recomputed on every
+//! frame, so it follows the terminal as it is resized, and panels can be focused and rows
+//! selected with the mouse as well as the keyboard.
+//!
+//! Changes go through the same pages as in the line-based interface: `Enter` opens the selected
+//! item's page, `n` the page that creates an epic (or a story in the selected epic, from the story
+//! list), and `e` the edit page. The page is drawn in the detail pane and gets the lines typed
+//! under it, and what it asks for is handled by the same [`Navigator`] and [`App::handle`], so
+//! changes can be undone with `Ctrl+Z`. The global keybindings for undo, redo, help, and locking
+//! work from the panels as well. When the session locks, the interface stops for the lock screen
+//! and carries on where it was once unlocked.
+//!
+//! Only built with the `tui` feature. It is started instead of the line-based pages when the
+//! [`Settings::full_screen`](crate::models::settings::Settings::full_screen) setting is on or the
+//! `--tui` flag is passed.
+
+use crate::app::App;
+use crate::clock::Clock;
+use crate::db::ClearTextDBState;
+use crate::models::stories::Story;
+use crate::nav::Navigator;
+use crate::nav::routes::Route;
+use crate::pages::i18n::{tr, tr_with};
+use crate::pages::keybindings::{KeyAction, KeyContext, KeyMap};
+use crate::pages::lock::AutoLock;
+use crate::pages::markdown::render_plain;
+use crate::pages::{Page, PageAction, Size};
+use crate::service::hierarchy::{epic_tree, subtree_progress};
+use crate::service::{get_epic, get_story};
+use ratatui::crossterm::event::{
+    self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use ratatui::crossterm::execute;
+use ratatui::layout::{Constraint, Layout, Position, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::{Block, Borders, List, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use std::io;
+use uuid::Uuid;
+
+/// # Panel enum
+/// One of the three panels, in left-to-right order.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Panel {
+    /// The epic tree.
+    #[default]
+    Epics,
+    /// The selected epic's stories.
+    Stories,
+    /// The details of the selected epic or story.
+    Detail,
+}
+
+impl Panel {
+    /// Every panel, left to right.
+    const ALL: [Panel; 3] = [Panel::Epics, Panel::Stories, Panel::Detail];
+
+    /// The panel to the right, wrapping around.
+    fn next(self) -> Self {
+        match self {
+            Panel::Epics => Panel::Stories,
+            Panel::Stories => Panel::Detail,
+            Panel::Detail => Panel::Epics,
+        }
+    }
+
+    /// The panel to the left, wrapping around.
+    fn previous(self) -> Self {
+        self.next().next()
+    }
+}
+
+/// # `TuiApp` struct
+/// The state of the full-screen interface between frames.
+#[derive(Default)]
+pub struct TuiApp {
+    /// The focused panel.
+    focus: Panel,
+    /// The selection and scroll position of the epic tree.
+    epics: ListState,
+    /// The selection and scroll position of the story list.
+    stories: ListState,
+    /// How far the detail pane is scrolled.
+    detail_scroll: u16,
+    /// Where each panel was last drawn, for mapping mouse clicks.
+    areas: [Rect; 3],
+    /// The pages opened from the panels; the one shown is drawn in the detail pane.
+    pages: Navigator,
+    /// The line being typed for the page shown.
+    input: String,
+    /// Whether the user asked to leave, or the session locked.
+    quit: bool,
+    /// Whether the session locked, by the user's asking or after sitting idle.
+    locked: bool,
+}
+
+impl TuiApp {
+    /// Creates the interface with the first epic selected, for a session that locks after a while
+    /// without input if `auto_lock` is given.
+    #[must_use]
+    pub fn new(auto_lock: Option<AutoLock>) -> Self {
+        let mut pages = Navigator::default();
+        pages.sign_in(auto_lock);
+        Self { epics: ListState::default().with_selected(Some(0)), pages, ..Self::default() }
+    }
+
+    /// The focused panel.
+    #[must_use]
+    pub fn focus(&self) -> Panel {
+        self.focus
+    }
+
+    /// Whether the user asked to leave, or the session locked.
+    #[must_use]
+    pub fn should_quit(&self) -> bool {
+        self.quit
+    }
+
+    /// Whether the session locked, so the lock screen has to be shown before carrying on.
+    #[must_use]
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+
+    /// Carries on once the user unlocked the session, with the pages as they were left.
+    pub fn unlock(&mut self, clock: &dyn Clock) {
+        self.pages.unlock(clock);
+        self.locked = false;
+        self.quit = false;
+    }
+
+    /// The selected epic, if there are any epics.
+    #[must_use]
+    pub fn selected_epic(&self, state: &ClearTextDBState) -> Option<Uuid> {
+        epic_rows(state).get(self.epics.selected()?).map(|(epic_uuid, _)| *epic_uuid)
+    }
+
+    /// The selected story, if the selected epic has any stories.
+    #[must_use]
+    pub fn selected_story(&self, state: &ClearTextDBState) -> Option<Uuid> {
+        self.stories_of_selection(state).get(self.stories.selected()?).map(|story| story.story_uuid)
+    }
+
+    /// Draws the three panels over the whole frame, with the messages for the user and the keys
+    /// under them.
+    pub fn draw(&mut self, frame: &mut Frame, app: &App) {
+        let state = &app.session.state;
+        let mut footer = app.toasts.render(app.clock().as_ref());
+        footer.push(tr(if self.pages.is_empty() { "tui-hints" } else { "tui-page-hints" }));
+        let [main, footer_area] = Layout::vertical([Constraint::Min(0), Constraint::Length(u16::try_from(footer.len()).unwrap_or(u16::MAX))]).areas(frame.area());
+        let areas = Layout::horizontal([Constraint::Percentage(30), Constraint::Percentage(30), Constraint::Percentage(40)]).areas(main);
+        self.areas = areas;
+        let [epics_area, stories_area, detail_area] = areas;
+
+        let epic_lines: Vec<String> = epic_rows(state).into_iter().map(|(_, line)| line).collect();
+        frame.render_stateful_widget(List::new(epic_lines).block(self.block(Panel::Epics, &tr("tui-epics"))).highlight_style(highlight()), epics_area, &mut self.epics);
+
+        let story_lines: Vec<String> = self.stories_of_selection(state).iter().map(|story| format!("{} {}", story.key(), story.title)).collect();
+        frame.render_stateful_widget(List::new(story_lines).block(self.block(Panel::Stories, &tr("tui-stories"))).highlight_style(highlight()), stories_area, &mut self.stories);
+
+        let (lines, scroll) = if self.pages.is_empty() { (self.detail_lines(state), self.detail_scroll) } else { (self.page_lines(detail_area), 0) };
+        let detail = Paragraph::new(lines.join("\n")).block(self.block(Panel::Detail, &tr("tui-details"))).wrap(Wrap { trim: false }).scroll((scroll, 0));
+        frame.render_widget(detail, detail_area);
+        frame.render_widget(Paragraph::new(footer.join("\n")), footer_area);
+    }
+
+    /// Handles one terminal event. Resizes need nothing beyond the redraw that follows.
+    pub fn handle_event(&mut self, event: &Event, app: &mut App) {
+        match event {
+            Event::Key(key) if key.kind == KeyEventKind::Press => {
+                // As in the line-based interface, input after the session sat idle locks it.
+                if let PageAction::Lock = self.pages.check_idle(app.clock().as_ref()) {
+                    self.perform(PageAction::Lock, app);
+                } else if self.pages.is_empty() {
+                    self.handle_panel_key(*key, app);
+                } else {
+                    self.handle_page_key(*key, app);
+                }
+            }
+            Event::Mouse(mouse) => self.handle_mouse(*mouse, &app.session.state),
+            _ => {}
+        }
+    }
+
+    /// Moves between the panels and rows, opens pages from them, or looks the key up among the
+    /// global keybindings.
+    fn handle_panel_key(&mut self, key: KeyEvent, app: &mut App) {
+        let state = &app.session.state;
+        let typed = if let KeyCode::Char(c) = key.code { spelled(c, key.modifiers) } else { String::new() };
+        let action = match (key.code, typed.as_str()) {
+            (KeyCode::Esc, _) | (_, "q") => PageAction::Quit,
+            (KeyCode::Tab | KeyCode::Right, _) | (_, "l") => {
+                self.focus = self.focus.next();
+                PageAction::None
+            }
+            (KeyCode::BackTab | KeyCode::Left, _) | (_, "h") => {
+                self.focus = self.focus.previous();
+                PageAction::None
+            }
+            (KeyCode::Down, _) | (_, "j") => {
+                self.scroll(self.focus, 1, state);
+                PageAction::None
+            }
+            (KeyCode::Up, _) | (_, "k") => {
+                self.scroll(self.focus, -1, state);
+                PageAction::None
+            }
+            (KeyCode::Enter, _) => self.selected_route(state, Route::Epic, Route::Story).map_or(PageAction::None, PageAction::Open),
+            (_, "e") => self.selected_route(state, Route::Edit, Route::Edit).map_or(PageAction::None, PageAction::Open),
+            (_, "n") => match self.selected_epic(state).filter(|_| self.focus != Panel::Epics) {
+                Some(epic_uuid) => PageAction::Open(Route::NewStory(Some(epic_uuid.to_string()))),
+                None => PageAction::Open(Route::NewEpic),
+            },
+            (_, typed) => match KeyMap::active().lookup(KeyContext::Global, typed) {
+                Some((KeyAction::Quit, _)) => PageAction::Quit,
+                Some((KeyAction::Lock, _)) => PageAction::Lock,
+                Some((KeyAction::Undo, _)) => PageAction::Undo,
+                Some((KeyAction::Redo, _)) => PageAction::Redo,
+                Some((KeyAction::Help, _)) => PageAction::Open(Route::Help),
+                _ => PageAction::None,
+            },
+        };
+        match action {
+            PageAction::None => (),
+            PageAction::Quit => self.quit = true,
+            action => self.perform(action, app),
+        }
+    }
+
+    /// Edits the line typed for the page shown, hands it over on `Enter`, and goes back to the
+    /// page below, or to the panels, on `Esc` with nothing typed.
+    fn handle_page_key(&mut self, key: KeyEvent, app: &mut App) {
+        match key.code {
+            // Like the line editor, undo and redo are handed over straight away.
+            KeyCode::Char(c @ ('z' | 'y')) if key.modifiers.contains(KeyModifiers::CONTROL) => self.submit(&spelled(c, key.modifiers), app),
+            KeyCode::Char(_) if key.modifiers.contains(KeyModifiers::CONTROL) => (),
+            KeyCode::Char(c) => self.input.push(c),
+            KeyCode::Backspace => {
+                self.input.pop();
+            }
+            KeyCode::Enter => {
+                let line = std::mem::take(&mut self.input);
+                self.submit(&line, app);
+            }
+            KeyCode::Esc if self.input.is_empty() => self.perform(PageAction::Pop, app),
+            KeyCode::Esc => self.input.clear(),
+            _ => {}
+        }
+    }
+
+    /// Hands a line to the page shown, as [`Navigator::run`] does.
+    fn submit(&mut self, line: &str, app: &mut App) {
+        let action = self.pages.handle_input(line);
+        self.perform(action, app);
+    }
+
+    /// Carries out an action as the line-based interface does: the navigator goes to the pages it
+    /// names, and [`App::handle`] opens routes, applies changes, and undoes them. Closing the last
+    /// page goes back to the panels; quitting from a page, or locking, stops the interface.
+    fn perform(&mut self, action: PageAction, app: &mut App) {
+        let mut locked = false;
+        let mut owner = |pages: &mut Navigator, action| match action {
+            // The lock screen runs outside the interface, which stops until it is unlocked.
+            PageAction::Lock => {
+                locked = true;
+                PageAction::Quit
+            }
+            action => app.handle(pages, action),
+        };
+        let action = self.pages.dispatch(action);
+        let running = self.pages.settle(action, &mut owner) && self.pages.settle_entered(&mut owner);
+        self.locked |= locked;
+        self.quit |= locked || (!running && !self.pages.is_empty());
+    }
+
+    /// Focuses the clicked panel and selects the clicked row; the wheel scrolls the panel under
+    /// the pointer.
+    fn handle_mouse(&mut self, mouse: MouseEvent, state: &ClearTextDBState) {
+        let position = Position::new(mouse.column, mouse.row);
+        let Some((panel, area)) = Panel::ALL.into_iter().zip(self.areas).find(|(_, area)| area.contains(position)) else {
+            return;
+        };
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                self.focus = panel;
+                let row = usize::from(mouse.row.saturating_sub(area.y + 1));
+                match panel {
+                    Panel::Epics if mouse.row > area.y => {
+                        let index = self.epics.offset() + row;
+                        if index < epic_rows(state).len() {
+                            self.select_epic(index);
+                        }
+                    }
+                    Panel::Stories if mouse.row > area.y => {
+                        let index = self.stories.offset() + row;
+                        if index < self.stories_of_selection(state).len() {
+                            self.stories.select(Some(index));
+                            self.detail_scroll = 0;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            MouseEventKind::ScrollDown => self.scroll(panel, 1, state),
+            MouseEventKind::ScrollUp => self.scroll(panel, -1, state),
+            _ => {}
+        }
+    }
+
+    /// Moves a panel's selection (or the detail pane's scroll position) by `delta` rows.
+    fn scroll(&mut self, panel: Panel, delta: isize, state: &ClearTextDBState) {
+        let step = |current: Option<usize>, len: usize| current.unwrap_or(0).saturating_add_signed(delta).min(len.saturating_sub(1));
+        match panel {
+            Panel::Epics => self.select_epic(step(self.epics.selected(), epic_rows(state).len())),
+            Panel::Stories => {
+                let len = self.stories_of_selection(state).len();
+                self.stories.select((len > 0).then(|| step(self.stories.selected(), len)));
+                self.detail_scroll = 0;
+            }
+            Panel::Detail => self.detail_scroll = self.detail_scroll.saturating_add_signed(i16::try_from(delta).unwrap_or(0)),
+        }
+    }
+
+    /// Selects an epic, clearing the story selection.
+    fn select_epic(&mut self, index: usize) {
+        if self.epics.selected() != Some(index) {
+            self.stories = ListState::default();
+        }
+        self.epics.select(Some(index));
+        self.detail_scroll = 0;
+    }
+
+    /// The route to the selected story, or to the selected epic if the epic tree is focused or no
+    /// story is selected.
+    fn selected_route(&self, state: &ClearTextDBState, epic: fn(String) -> Route, story: fn(String) -> Route) -> Option<Route> {
+        match self.selected_story(state).filter(|_| self.focus != Panel::Epics) {
+            Some(story_uuid) => Some(story(story_uuid.to_string())),
+            None => self.selected_epic(state).map(|epic_uuid| epic(epic_uuid.to_string())),
+        }
+    }
+
+    /// The page shown, rendered to fit the detail pane, with the line being typed under it.
+    fn page_lines(&self, area: Rect) -> Vec<String> {
+        let size = Size::new(area.width.saturating_sub(2), area.height.saturating_sub(3));
+        let mut lines = self.pages.render(size);
+        lines.truncate(usize::from(size.height));
+        let masked = self.pages.current().is_some_and(Page::masks_input) && self.pages.modal().is_none();
+        lines.push(format!("> {}", if masked { "*".repeat(self.input.chars().count()) } else { self.input.clone() }));
+        lines
+    }
+
+    /// The active stories of the selected epic, in rank order.
+    fn stories_of_selection<'a>(&self, state: &'a ClearTextDBState) -> Vec<&'a Story> {
+        let Some(epic) = self.selected_epic(state).and_then(|epic_uuid| get_epic(state, epic_uuid).ok()) else {
+            return Vec::new();
+        };
+        epic.story_uuids
+            .iter()
+            .filter_map(|story_uuid| get_story(state, *story_uuid).ok())
+            .filter(|story| !story.archived && !story.is_deleted())
+            .collect()
+    }
+
+    /// The detail pane's text: the selected story, or the selected epic if no story is selected.
+    fn detail_lines(&self, state: &ClearTextDBState) -> Vec<String> {
+        if let Some(story) = self.selected_story(state).and_then(|story_uuid| get_story(state, story_uuid).ok()) {
+            let mut lines = vec![format!("{} {}", story.key(), story.title), tr_with("field-status", &[("value", &story.status.name(&state.statuses))])];
+            lines.extend(story.due_date.map(|date| tr_with("field-due", &[("value", &date)])));
+            lines.push(String::new());
+            lines.extend(render_plain(&story.description));
+            return lines;
+        }
+        let Some(epic) = self.selected_epic(state).and_then(|epic_uuid| get_epic(state, epic_uuid).ok()) else {
+            return vec![tr("tui-no-epics")];
+        };
+        let progress = subtree_progress(state, epic.epic_uuid).unwrap_or_default();
+        let mut lines = vec![
+            format!("{} {}", epic.key(), epic.title),
+            tr_with("field-status", &[("value", &epic.status.name(&state.statuses))]),
+            tr_with("field-progress", &[("value", &progress)]),
+        ];
+        lines.extend(epic.due_date.map(|date| tr_with("field-due", &[("value", &date)])));
+        lines.push(String::new());
+        lines.extend(render_plain(&epic.description));
+        lines
+    }
+
+    /// A panel's border, highlighted when focused.
+    fn block(&self, panel: Panel, title: &str) -> Block<'static> {
+        let style = if self.focus == panel { Style::new().add_modifier(Modifier::BOLD) } else { Style::new().add_modifier(Modifier::DIM) };
+        Block::new().borders(Borders::ALL).border_style(style).title(title.to_string())
+    }
+}
+
+/// The style of the selected row.
+fn highlight() -> Style {
+    Style::new().add_modifier(Modifier::REVERSED)
+}
+
+/// A key as the [keybindings](KeyMap) spell it, e.g. `Z` for Shift+Z and `^Z` for Ctrl+Z.
+fn spelled(c: char, modifiers: KeyModifiers) -> String {
+    if modifiers.contains(KeyModifiers::CONTROL) { format!("^{}", c.to_ascii_uppercase()) } else { c.to_string() }
+}
+
+/// The active epics in tree order, each with its indented line.
+fn epic_rows(state: &ClearTextDBState) -> Vec<(Uuid, String)> {
+    epic_tree(state)
+        .into_iter()
+        .filter(|(_, epic)| !epic.archived)
+        .map(|(depth, epic)| (epic.epic_uuid, format!("{}{} {}", "  ".repeat(depth), epic.key(), epic.title)))
+        .collect()
+}
+
+/// Runs the full-screen interface until the user quits or the session locks, then restores the
+/// terminal.
+///
+/// # Returns
+/// * `bool` - Whether the session locked, for the caller to show the lock screen and, once it is
+///   [unlocked](TuiApp::unlock), run the interface again.
+/// # Errors
+/// * `io::Error` - If the terminal can't be set up, drawn to, or read from.
+pub fn run(screen: &mut TuiApp, app: &mut App) -> io::Result<bool> {
+    let mut terminal = ratatui::init();
+    let result = execute!(io::stdout(), EnableMouseCapture).and_then(|()| event_loop(&mut terminal, screen, app));
+    let restored = execute!(io::stdout(), DisableMouseCapture);
+    ratatui::restore();
+    result.and(restored).map(|()| screen.is_locked())
+}
+
+/// Draws and handles events until the user quits or the session locks.
+fn event_loop(terminal: &mut DefaultTerminal, screen: &mut TuiApp, app: &mut App) -> io::Result<()> {
+    while !screen.should_quit() {
+        terminal.draw(|frame| screen.draw(frame, app))?;
+        screen.handle_event(&event::read()?, app);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::db::storage::Session;
+    use crate::service::{epics::create_epic, stories::create_story};
+    use crate::users::User;
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+    use std::sync::Arc;
+
+    /// The owner of a session on `state` that is never saved.
+    fn app(state: ClearTextDBState) -> App {
+        App::new(Session::new(state, "password").unwrap(), std::env::temp_dir(), Arc::new(SystemClock))
+    }
+
+    /// Presses each key of `keys`, with `\n` for `Enter`.
+    fn type_keys(screen: &mut TuiApp, app: &mut App, keys: &str) {
+        for c in keys.chars() {
+            let code = if c == '\n' { KeyCode::Enter } else { KeyCode::Char(c) };
+            screen.handle_event(&Event::Key(KeyEvent::new(code, KeyModifiers::NONE)), app);
+        }
+    }
+
+    #[test]
+    fn test_panels_follow_keys_mouse_and_size() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        let launch = create_epic(&mut state, "Launch", "Ship **it**".to_string(), &SystemClock).unwrap();
+        let docs = create_epic(&mut state, "Docs", String::new(), &SystemClock).unwrap();
+        create_story(&mut state, launch, "Login", String::new(), &SystemClock).unwrap();
+        let signup = create_story(&mut state, launch, "Signup", "Email first".to_string(), &SystemClock).unwrap();
+        let mut app = app(state);
+        let mut screen = TuiApp::new(None);
+        let mut terminal = Terminal::new(TestBackend::new(90, 10)).unwrap();
+        terminal.draw(|frame| screen.draw(frame, &app)).unwrap();
+        assert_eq!(screen.areas[2], Rect::new(54, 0, 36, 9));
+        assert!(terminal.backend().to_string().contains("Ship it"));
+
+        let key = |code| Event::Key(KeyEvent::new(code, KeyModifiers::NONE));
+        screen.handle_event(&key(KeyCode::Tab), &mut app);
+        screen.handle_event(&key(KeyCode::Down), &mut app);
+        screen.handle_event(&key(KeyCode::Down), &mut app);
+        let state = &app.session.state;
+        assert_eq!((screen.focus(), screen.selected_story(state)), (Panel::Stories, Some(signup)));
+        terminal.draw(|frame| screen.draw(frame, &app)).unwrap();
+        assert!(terminal.backend().to_string().contains("Email first"));
+
+        let click = Event::Mouse(MouseEvent { kind: MouseEventKind::Down(MouseButton::Left), column: 3, row: 2, modifiers: KeyModifiers::NONE });
+        screen.handle_event(&click, &mut app);
+        let state = &app.session.state;
+        assert_eq!((screen.focus(), screen.selected_epic(state), screen.selected_story(state)), (Panel::Epics, Some(docs), None));
+
+        terminal.backend_mut().resize(60, 8);
+        terminal.draw(|frame| screen.draw(frame, &app)).unwrap();
+        assert_eq!(screen.areas[0], Rect::new(0, 0, 18, 7));
+        screen.handle_event(&key(KeyCode::Char('q')), &mut app);
+        assert!(screen.should_quit());
+    }
+
+    #[test]
+    fn test_changes_go_through_the_pages_and_can_be_undone() {
+        let mut state = ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap());
+        create_epic(&mut state, "Launch", String::new(), &SystemClock).unwrap();
+        let mut app = app(state);
+        let mut screen = TuiApp::new(None);
+        let titles = |app: &App| app.session.state.epics.iter().map(|epic| epic.title.clone()).collect::<Vec<_>>();
+
+        // The new epic's page replaces the wizard in the detail pane.
+        type_keys(&mut screen, &mut app, "nDocs\n:w\n\ny\n");
+        assert_eq!(titles(&app), ["Launch", "Docs"]);
+        let mut terminal = Terminal::new(TestBackend::new(120, 20)).unwrap();
+        terminal.draw(|frame| screen.draw(frame, &app)).unwrap();
+        assert!(terminal.backend().to_string().contains("=== EPIC-2 Docs ==="));
+
+        // Closing the last page goes back to the panels, where Ctrl+Z undoes the change.
+        screen.handle_event(&Event::Key(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)), &mut app);
+        assert!(screen.pages.is_empty() && !screen.should_quit());
+        screen.handle_event(&Event::Key(KeyEvent::new(KeyCode::Char('z'), KeyModifiers::CONTROL)), &mut app);
+        assert_eq!(titles(&app), ["Launch"]);
+        assert_eq!(app.toasts.log().last().unwrap().message, "Undid create epic \"Docs\".");
+
+        // Locking stops the interface until the session is unlocked, with the page left open.
+        type_keys(&mut screen, &mut app, "\nZ\n");
+        assert!(screen.is_locked() && screen.should_quit());
+        screen.unlock(app.clock().as_ref());
+        assert!(!screen.should_quit());
+        terminal.draw(|frame| screen.draw(frame, &app)).unwrap();
+        assert!(terminal.backend().to_string().contains("=== EPIC-1 Launch ==="));
+    }
+}
@@ -1,32 +1,571 @@
 //! # Users
-//! Module for managing user accounts. 
+//! Module for managing user accounts.
 
-use easy_totp::EasyTotp;
+use crate::audit::{AuditEntry, AuditEvent};
+use crate::clock::Clock;
+use crate::db::attachments::reencrypt_attachments;
+use crate::db::storage::{Session, detect_users};
+use crate::models::Timestamped;
+use crate::models::validation::{ValidationError, validate_password, validate_username};
+use crate::security::backup_codes::{generate_backup_codes, redeem_backup_code, BackupCode};
+use crate::security::fido2::SecurityKeyCredential;
+#[cfg(feature = "fido2")]
+use crate::security::fido2::{authenticate_security_key, HmacSecretAuthenticator};
+use crate::security::hotp::{HotpInstance, HOTP_LOOK_AHEAD};
+use crate::security::totp::{generate_totp_with_config, verify_totp, TotpConfig, TotpInstance};
+use crate::security::{Argon2EncryptionKey, Argon2Hash, SecurityError};
+use crate::service::shared::rename_member;
+use crate::service::undo::UndoStack;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
 use uuid::Uuid;
 
 /// # User struct
 /// Represents a user in the system.
 #[derive(Clone, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[allow(clippy::struct_field_names)]
 pub struct User {
     /// The username of the user.
     pub username: String,
     /// The unique identifier of the user.
     pub user_uuid: Uuid,
     /// The hashed password of the user.
-    pub password_hash: String,
+    pub password_hash: Argon2Hash,
     /// Optional two-factor authentication instance
-    pub totp_instance: Option<EasyTotp>,    
+    pub totp_instance: Option<TotpInstance>,
+    /// Optional counter-based 2FA instance, an alternative to TOTP for devices with unreliable clocks
+    #[serde(default)]
+    pub hotp_instance: Option<HotpInstance>,
+    /// Registered FIDO2 security keys
+    #[serde(default)]
+    pub security_keys: Vec<SecurityKeyCredential>,
+    /// Hashed single-use backup codes for 2FA recovery
+    #[serde(default)]
+    pub backup_codes: Vec<BackupCode>,
+    /// Security-relevant changes to this account
+    #[serde(default)]
+    pub audit_log: Vec<AuditEntry>,
+    /// Random secret the user's key pair for [shared databases](crate::db::shared) is made from.
+    /// It only lives in the user's own encrypted database, and doesn't change with the password
+    /// or the data key. Databases saved before shared databases existed get one when unlocked.
+    #[serde(default)]
+    pub member_secret: Option<[u8; 32]>,
+    /// When the account was created (set by the service layer)
+    #[serde(default)]
+    pub created_at: DateTime<Utc>,
+    /// When the account was last modified (set by the service layer)
+    #[serde(default)]
+    pub updated_at: DateTime<Utc>,
 }
 
 impl User {
-    /// Creates a new user with the given username and password hash.
-    pub fn new(username: String, password_hash: String) -> Self {
-        Self {
+    /// Creates a new user with the given username and password, hashing the password with the
+    /// freshly generated UUID as salt.
+    ///
+    /// # Errors
+    /// * `SecurityError::Argon2` / `SecurityError::PasswordHash` - If the password could not be hashed.
+    /// * `SecurityError::TryRngCore` - If the member secret could not be generated.
+    pub fn new(username: String, password: &str) -> Result<Self, SecurityError> {
+        let user_uuid = Uuid::new_v4();
+        Ok(Self {
             username,
-            user_uuid: Uuid::new_v4(),
-            password_hash,
+            user_uuid,
+            password_hash: Argon2Hash::new(password, user_uuid)?,
             totp_instance: None,
+            hotp_instance: None,
+            security_keys: Vec::new(),
+            backup_codes: Vec::new(),
+            audit_log: Vec::new(),
+            member_secret: Some(Argon2EncryptionKey::random()?.0),
+            created_at: DateTime::default(),
+            updated_at: DateTime::default(),
+        })
+    }
+
+    /// Verifies a plaintext password against the stored hash.
+    ///
+    /// # Errors
+    /// * `SecurityError::Argon2` / `SecurityError::PasswordHash` - If the password could not be hashed.
+    pub fn verify_password(&self, password: &str) -> Result<bool, SecurityError> {
+        self.password_hash.verify_password(password, self.user_uuid)
+    }
+
+    /// Enables TOTP 2FA for this user and generates a fresh set of backup codes.
+    ///
+    /// Any previously issued backup codes and any HOTP enrollment are invalidated.
+    /// # Arguments
+    /// * `config` - The digits, period, and algorithm to enroll with.
+    /// # Returns
+    /// * `Result<Vec<String>, SecurityError>` - The plaintext backup codes, to be shown to the user exactly once.
+    /// # Errors
+    /// * `SecurityError::TryRngCore` - If the TOTP secret or backup codes could not be generated.
+    pub fn enable_totp(&mut self, config: TotpConfig) -> Result<Vec<String>, SecurityError> {
+        let totp = generate_totp_with_config(&self.username, config)?;
+        let (plaintext_codes, hashed_codes) = generate_backup_codes(self.user_uuid)?;
+        self.totp_instance = Some(totp);
+        self.hotp_instance = None;
+        self.backup_codes = hashed_codes;
+        Ok(plaintext_codes)
+    }
+
+    /// Enables counter-based HOTP 2FA for this user (instead of TOTP) and generates a fresh set of
+    /// backup codes.
+    ///
+    /// Any previously issued backup codes and any TOTP enrollment are invalidated.
+    /// # Returns
+    /// * `Result<Vec<String>, SecurityError>` - The plaintext backup codes, to be shown to the user exactly once.
+    /// # Errors
+    /// * `SecurityError::TryRngCore` - If the HOTP secret or backup codes could not be generated.
+    pub fn enable_hotp(&mut self) -> Result<Vec<String>, SecurityError> {
+        let hotp = HotpInstance::new(&self.username)?;
+        let (plaintext_codes, hashed_codes) = generate_backup_codes(self.user_uuid)?;
+        self.hotp_instance = Some(hotp);
+        self.totp_instance = None;
+        self.backup_codes = hashed_codes;
+        Ok(plaintext_codes)
+    }
+
+    /// Returns whether the user has a second factor (TOTP, HOTP, or a security key) enrolled.
+    #[must_use]
+    pub fn has_second_factor(&self) -> bool {
+        self.totp_instance.is_some() || self.hotp_instance.is_some() || !self.security_keys.is_empty()
+    }
+
+    /// Redeems a backup code, marking it as consumed if it matches an unused code.
+    ///
+    /// # Returns
+    /// * `Result<bool, SecurityError>` - Whether the code was valid and has now been consumed.
+    /// # Errors
+    /// * `SecurityError::Argon2` / `SecurityError::PasswordHash` - If the code could not be hashed.
+    pub fn redeem_backup_code(&mut self, code: &str) -> Result<bool, SecurityError> {
+        redeem_backup_code(&mut self.backup_codes, code, self.user_uuid)
+    }
+
+    /// Returns the number of backup codes that have not been used yet.
+    #[must_use]
+    pub fn remaining_backup_codes(&self) -> usize {
+        self.backup_codes.iter().filter(|backup_code| !backup_code.consumed).count()
+    }
+
+    /// Starts a TOTP re-enrollment by generating a new, pending TOTP secret.
+    ///
+    /// The new secret keeps the current configuration (or the default one if TOTP is not enabled).
+    /// The user's current secret stays active until the pending one is confirmed with
+    /// [`User::confirm_totp_rotation`].
+    /// # Errors
+    /// * `SecurityError::TryRngCore` - If the new secret could not be generated.
+    pub fn begin_totp_rotation(&self) -> Result<TotpInstance, SecurityError> {
+        let config = self.totp_instance.as_ref().map(|totp| totp.config).unwrap_or_default();
+        generate_totp_with_config(&self.username, config)
+    }
+
+    /// Completes a TOTP re-enrollment: if `code` is valid for the pending secret, it replaces
+    /// (and thereby invalidates) the current one.
+    ///
+    /// # Arguments
+    /// * `pending` - The secret returned by [`User::begin_totp_rotation`].
+    /// * `code` - A code generated from the pending secret.
+    /// * `clock` - The source of the current time.
+    /// # Returns
+    /// * `Result<bool, SecurityError>` - Whether the code was valid and the secret was switched.
+    /// # Errors
+    /// * `SecurityError::Totp` - If the TOTP token could not be generated.
+    pub fn confirm_totp_rotation(&mut self, mut pending: TotpInstance, code: &str, clock: &dyn Clock) -> Result<bool, SecurityError> {
+        if !verify_totp(&mut pending, code.trim(), clock)? {
+            return Ok(false);
+        }
+        self.totp_instance = Some(pending);
+        Ok(true)
+    }
+
+    /// Completes a TOTP enrollment: if `code` is valid for the pending secret (e.g. one from
+    /// [`crate::security::totp::generate_totp`]), it becomes the user's second factor, replacing any
+    /// HOTP enrollment, with a fresh set of backup codes. The change is recorded in the audit log.
+    ///
+    /// # Arguments
+    /// * `pending` - The secret the user scanned.
+    /// * `code` - A code generated from the pending secret.
+    /// * `clock` - The source of the current time.
+    /// # Returns
+    /// * `Result<Option<Vec<String>>, SecurityError>` - The plaintext backup codes, to be shown to
+    ///   the user exactly once, or `None` if the code was not valid.
+    /// # Errors
+    /// * `SecurityError::Totp` - If the TOTP token could not be generated.
+    /// * `SecurityError::TryRngCore` - If the backup codes could not be generated.
+    pub fn confirm_totp_enrollment(&mut self, mut pending: TotpInstance, code: &str, clock: &dyn Clock) -> Result<Option<Vec<String>>, SecurityError> {
+        if !verify_totp(&mut pending, code.trim(), clock)? {
+            return Ok(None);
+        }
+        let (plaintext_codes, hashed_codes) = generate_backup_codes(self.user_uuid)?;
+        self.totp_instance = Some(pending);
+        self.hotp_instance = None;
+        self.backup_codes = hashed_codes;
+        self.audit_log.push(AuditEntry::new(clock.now(), AuditEvent::SecondFactorEnabled));
+        Ok(Some(plaintext_codes))
+    }
+
+    /// Checks that one of this user's registered security keys is present, e.g. on the lock
+    /// screen. Security keys are registered with [`Session::add_security_key`], which also makes
+    /// the database need one to be unlocked.
+    ///
+    /// # Returns
+    /// * `Result<Option<[u8; 32]>, SecurityError>` - The key's `hmac-secret` output, or `None` if
+    ///   no registered key answered.
+    /// # Errors
+    /// * `SecurityError::Fido2` - If the authenticator failed.
+    #[cfg(feature = "fido2")]
+    pub fn verify_security_key(&self, authenticator: &dyn HmacSecretAuthenticator) -> Result<Option<[u8; 32]>, SecurityError> {
+        Ok(authenticate_security_key(authenticator, &self.security_keys)?.map(|(_, output)| output))
+    }
+
+    /// Disables two-factor authentication after re-verifying the account password and a currently
+    /// valid code (TOTP, HOTP, or an unused backup code), and records the change in the audit log.
+    ///
+    /// All backup codes are discarded along with the second factor.
+    /// # Arguments
+    /// * `password` - The account password.
+    /// * `code` - A current second-factor code or an unused backup code.
+    /// * `clock` - The source of the current time.
+    /// # Returns
+    /// * `Result<bool, SecurityError>` - Whether both checks passed and 2FA was disabled. Users
+    ///   without a second factor get `false`.
+    /// # Errors
+    /// * `SecurityError` - If the password or the expected code could not be computed.
+    pub fn disable_second_factor(&mut self, password: &str, code: &str, clock: &dyn Clock) -> Result<bool, SecurityError> {
+        if !self.has_second_factor() || !self.verify_password(password)? || !self.verify_second_factor(code, clock)? {
+            return Ok(false);
+        }
+        self.totp_instance = None;
+        self.hotp_instance = None;
+        self.backup_codes.clear();
+        self.audit_log.push(AuditEntry::new(clock.now(), AuditEvent::SecondFactorDisabled));
+        Ok(true)
+    }
+
+    /// Verifies the second factor at login: a current TOTP code, an HOTP code within the
+    /// look-ahead window, or an unused backup code.
+    ///
+    /// A matching backup code is consumed, a matching TOTP code can't be used again, and a matching
+    /// HOTP code advances the counter, so the user record must be saved afterwards. Users without a second factor always pass.
+    /// # Arguments
+    /// * `code` - The code entered by the user.
+    /// * `clock` - The source of the current time, for TOTP verification.
+    /// # Errors
+    /// * `SecurityError::Totp` / `SecurityError::Hotp` - If the expected code could not be generated.
+    /// * `SecurityError::Argon2` / `SecurityError::PasswordHash` - If a backup code could not be hashed.
+    pub fn verify_second_factor(&mut self, code: &str, clock: &dyn Clock) -> Result<bool, SecurityError> {
+        if !self.has_second_factor() {
+            return Ok(true);
         }
+        if let Some(totp) = &mut self.totp_instance
+            && verify_totp(totp, code.trim(), clock)?
+        {
+            return Ok(true);
+        }
+        if let Some(hotp) = &mut self.hotp_instance
+            && hotp.verify(code, HOTP_LOOK_AHEAD)?
+        {
+            return Ok(true);
+        }
+        self.redeem_backup_code(code)
+    }
+}
+
+/// # Account Change Error enum
+/// Why the password or username wasn't changed. In every case the database file and the session
+/// are left as they were.
+#[derive(Debug)]
+pub enum AccountChangeError {
+    /// The current password is wrong.
+    WrongPassword,
+    /// The new password breaks the password policy, or the new username is empty or taken.
+    Invalid(ValidationError),
+    /// The new password couldn't be hashed or the key derived from it.
+    Security(SecurityError),
+    /// The database files couldn't be read, or the database couldn't be encrypted or written.
+    Save(Box<dyn std::error::Error>),
+}
+
+impl fmt::Display for AccountChangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccountChangeError::WrongPassword => write!(f, "The current password is wrong"),
+            AccountChangeError::Invalid(error) => write!(f, "{error}"),
+            AccountChangeError::Security(error) => write!(f, "{error}"),
+            AccountChangeError::Save(error) => write!(f, "Could not save the database: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for AccountChangeError {}
+
+impl From<SecurityError> for AccountChangeError {
+    fn from(err: SecurityError) -> Self {
+        AccountChangeError::Security(err)
+    }
+}
+
+/// Changes the logged-in user's password: checks the old one, holds the new one to the password
+/// policy, derives the new password key, and replaces the data key with a new random one wrapped
+/// with it. The database and its attachments are encrypted again with the new data key, so the old
+/// password and a copy of the old file can't open anything saved from now on. Shared databases
+/// still open, since the user's member secret is kept. The session only switches to the new
+/// password hash and keys once the file is written, so a failure leaves the old password working.
+///
+/// # Arguments
+/// * `session` - The unlocked database of the user.
+/// * `databases_dir` - Where the database file is.
+/// * `old` - The current password.
+/// * `new` - The new password.
+/// * `clock` - The source of the current time, for the audit log.
+/// # Errors
+/// * `AccountChangeError` - If the old password is wrong, the new one breaks the policy, or the
+///   database couldn't be written.
+pub fn change_password(session: &mut Session, databases_dir: &Path, old: &str, new: &str, clock: &dyn Clock) -> Result<(), AccountChangeError> {
+    let user = &session.state.user;
+    if !user.verify_password(old)? {
+        return Err(AccountChangeError::WrongPassword);
     }
-}
\ No newline at end of file
+    validate_password(new, &user.username).map_err(AccountChangeError::Invalid)?;
+    let password_key = Argon2EncryptionKey::new(new, user.user_uuid)?;
+    let mut changed = session.clone();
+    changed.state.user.password_hash = Argon2Hash::new(new, user.user_uuid)?;
+    changed.state.user.updated_at = clock.now();
+    changed.state.user.audit_log.push(AuditEntry::new(clock.now(), AuditEvent::PasswordChanged));
+    changed.set_password_key(password_key);
+    changed.key = Argon2EncryptionKey::random()?;
+    // The snapshots on the undo stack still name the attachments' old nonces.
+    changed.undo = UndoStack::default();
+    let staged = reencrypt_attachments(&mut changed.state, &session.key, &changed.key, databases_dir).map_err(AccountChangeError::Save)?;
+    if let Err(err) = changed.save(databases_dir) {
+        staged.discard();
+        return Err(AccountChangeError::Save(err));
+    }
+    staged.commit().map_err(|err| AccountChangeError::Save(Box::new(err)))?;
+    *session = changed;
+    Ok(())
+}
+
+/// Renames the logged-in user, keeping their UUID. The database file is named by the UUID, so
+/// only the username in its clear-text header is rewritten, along with the account name an
+/// authenticator app is shown for the TOTP or HOTP secret and the user's entries in the member
+/// lists of shared databases. The shared databases are renamed first, and put back if they or the
+/// user's own file can't be written, so the two never disagree. The session only switches to the
+/// new name once everything is written.
+///
+/// # Arguments
+/// * `session` - The unlocked database of the user.
+/// * `databases_dir` - Where the database files are, to check the name isn't taken.
+/// * `username` - The new username; surrounding whitespace is trimmed.
+/// * `clock` - The source of the current time, for the audit log.
+/// # Returns
+/// * `Result<Vec<(Uuid, String)>, AccountChangeError>` - The detected users, read again so the
+///   login page lists the new name.
+/// # Errors
+/// * `AccountChangeError` - If the name is empty or another user has it, or the database files
+///   couldn't be read or written.
+pub fn change_username(session: &mut Session, databases_dir: &Path, username: &str, clock: &dyn Clock) -> Result<Vec<(Uuid, String)>, AccountChangeError> {
+    let user_uuid = session.state.user.user_uuid;
+    let users = detect_users(databases_dir).map_err(|err| AccountChangeError::Save(Box::new(err)))?;
+    let others = users.iter().filter(|(uuid, _)| *uuid != user_uuid).map(|(_, name)| name.as_str());
+    let username = validate_username(username, others).map_err(AccountChangeError::Invalid)?;
+    let mut changed = session.clone();
+    let user = &mut changed.state.user;
+    if let Some(totp) = &mut user.totp_instance {
+        totp.set_account_name(username.clone());
+    }
+    if let Some(hotp) = &mut user.hotp_instance {
+        hotp.set_account_name(username.clone());
+    }
+    user.username = username;
+    user.updated_at = clock.now();
+    user.audit_log.push(AuditEntry::new(clock.now(), AuditEvent::UsernameChanged));
+    rename_member(databases_dir, &changed).and_then(|()| changed.save(databases_dir)).map_err(|err| {
+        // Best effort: the error worth reporting is the one that stopped the rename.
+        let _ = rename_member(databases_dir, session);
+        AccountChangeError::Save(err)
+    })?;
+    *session = changed;
+    detect_users(databases_dir).map_err(|err| AccountChangeError::Save(Box::new(err)))
+}
+
+impl Timestamped for User {
+    fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    fn updated_at(&self) -> DateTime<Utc> {
+        self.updated_at
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::db::ClearTextDBState;
+    use crate::db::attachments::{AttachmentTarget, extract_attachment, save_attachment};
+
+    #[test]
+    fn test_backup_codes_are_single_use() {
+        let mut user = User::new("alice".to_string(), "password").unwrap();
+        let codes = user.enable_totp(TotpConfig::default()).unwrap();
+        assert_eq!(user.remaining_backup_codes(), codes.len());
+
+        assert!(user.verify_second_factor(&codes[0], &SystemClock).unwrap());
+        assert_eq!(user.remaining_backup_codes(), codes.len() - 1);
+        assert!(!user.verify_second_factor(&codes[0], &SystemClock).unwrap());
+        assert!(!user.verify_second_factor("not-a-code", &SystemClock).unwrap());
+    }
+
+    #[test]
+    fn test_totp_rotation_requires_code_from_new_secret() {
+        let mut user = User::new("carol".to_string(), "password").unwrap();
+        user.enable_totp(TotpConfig::default()).unwrap();
+        let old_totp = user.totp_instance.clone().unwrap();
+
+        let pending = user.begin_totp_rotation().unwrap();
+        assert!(!user.confirm_totp_rotation(pending.clone(), "not-a-code", &SystemClock).unwrap());
+        assert_eq!(user.totp_instance.as_ref(), Some(&old_totp));
+
+        let new_code = crate::security::totp::generate_token_at(&pending, &SystemClock).unwrap();
+        assert!(user.confirm_totp_rotation(pending, &new_code, &SystemClock).unwrap());
+        assert_eq!(crate::security::totp::generate_token_at(user.totp_instance.as_ref().unwrap(), &SystemClock).unwrap(), new_code);
+        // The code that confirmed the rotation can't be replayed at login.
+        assert!(!user.verify_second_factor(&new_code, &SystemClock).unwrap());
+    }
+
+    #[test]
+    fn test_hotp_second_factor_advances_counter() {
+        let mut user = User::new("dave".to_string(), "password").unwrap();
+        user.enable_hotp().unwrap();
+        assert!(user.totp_instance.is_none());
+
+        let code = user.hotp_instance.as_ref().unwrap().generate(2).unwrap();
+        assert!(user.verify_second_factor(&code, &SystemClock).unwrap());
+        assert_eq!(user.hotp_instance.as_ref().unwrap().counter, 3);
+        assert!(!user.verify_second_factor(&code, &SystemClock).unwrap());
+    }
+
+    #[test]
+    fn test_disable_second_factor_requires_password_and_code() {
+        let clock = SystemClock;
+        let mut user = User::new("erin".to_string(), "password").unwrap();
+        let codes = user.enable_totp(TotpConfig::default()).unwrap();
+        let totp = user.totp_instance.clone().unwrap();
+        let code = crate::security::totp::generate_token_at(&totp, &clock).unwrap();
+
+        assert!(!user.disable_second_factor("wrong", &code, &clock).unwrap());
+        assert!(!user.disable_second_factor("password", "not-a-code", &clock).unwrap());
+        assert!(user.has_second_factor());
+        assert!(user.audit_log.is_empty());
+
+        assert!(user.disable_second_factor("password", &codes[0], &clock).unwrap());
+        assert!(!user.has_second_factor());
+        assert!(user.backup_codes.is_empty());
+        assert_eq!(user.audit_log.len(), 1);
+        assert_eq!(user.audit_log[0].event, AuditEvent::SecondFactorDisabled);
+    }
+
+    #[test]
+    fn test_reenabling_totp_invalidates_old_codes() {
+        let mut user = User::new("bob".to_string(), "password").unwrap();
+        let old_codes = user.enable_totp(TotpConfig::default()).unwrap();
+        let new_codes = user.enable_totp(TotpConfig::default()).unwrap();
+        assert!(!user.redeem_backup_code(&old_codes[0]).unwrap());
+        assert!(user.redeem_backup_code(&new_codes[0]).unwrap());
+    }
+
+    #[test]
+    fn test_totp_enrollment_requires_valid_code() {
+        let mut user = User::new("erin".to_string(), "password").unwrap();
+        user.enable_hotp().unwrap();
+        let pending = crate::security::totp::generate_totp(&user.username).unwrap();
+        assert_eq!(user.confirm_totp_enrollment(pending.clone(), "not-a-code", &SystemClock).unwrap(), None);
+        assert!(user.totp_instance.is_none());
+
+        let code = crate::security::totp::generate_token_at(&pending, &SystemClock).unwrap();
+        let codes = user.confirm_totp_enrollment(pending, &code, &SystemClock).unwrap().unwrap();
+        assert!(user.totp_instance.is_some() && user.hotp_instance.is_none());
+        assert!(!user.verify_second_factor(&code, &SystemClock).unwrap());
+        assert_eq!(user.remaining_backup_codes(), codes.len());
+        assert_eq!(user.audit_log[0].event, AuditEvent::SecondFactorEnabled);
+    }
+
+    #[test]
+    fn test_change_password_rotates_the_data_key() {
+        let databases_dir = std::env::temp_dir().join(format!("ironyyy-password-{}", Uuid::new_v4()));
+        let user = User::new("alice".to_string(), "password").unwrap();
+        let user_uuid = user.user_uuid;
+        let mut session = Session::new(ClearTextDBState::new(user), "password").unwrap();
+        let story = crate::models::stories::Story::new("Story".to_string(), String::new());
+        let target = AttachmentTarget::Story(story.story_uuid);
+        session.state.stories.push(story);
+        let attachment = save_attachment(&mut session.state, &session.key, &databases_dir, target, "notes.txt".to_string(), b"notes", &SystemClock).unwrap();
+        session.save(&databases_dir).unwrap();
+        let old_slot = crate::db::storage::read_database(&databases_dir, user_uuid).unwrap().password_slot.unwrap();
+
+        assert!(matches!(change_password(&mut session, &databases_dir, "wrong", "correct horse battery", &SystemClock), Err(AccountChangeError::WrongPassword)));
+        assert!(matches!(change_password(&mut session, &databases_dir, "password", "short", &SystemClock), Err(AccountChangeError::Invalid(ValidationError::PasswordTooShort))));
+        change_password(&mut session, &databases_dir, "password", "correct horse battery", &SystemClock).unwrap();
+        assert!(session.state.user.verify_password("correct horse battery").unwrap());
+        assert_eq!(session.state.user.audit_log.last().unwrap().event, AuditEvent::PasswordChanged);
+        assert!(Session::unlock(&databases_dir, user_uuid, "password").is_err());
+        let unlocked = Session::unlock(&databases_dir, user_uuid, "correct horse battery").unwrap();
+        assert_eq!(unlocked.state.user, session.state.user);
+        // The attachment was encrypted again with the new data key.
+        assert_eq!(extract_attachment(&unlocked.state, &unlocked.key, &databases_dir, attachment).unwrap(), b"notes");
+        let old_key = old_slot.unwrap_key(&Argon2EncryptionKey::new("password", user_uuid).unwrap()).unwrap();
+        assert_ne!(old_key, unlocked.key);
+        assert!(crate::db::storage::read_database(&databases_dir, user_uuid).unwrap().to_clear_text(&old_key).is_err());
+        assert!(extract_attachment(&unlocked.state, &old_key, &databases_dir, attachment).is_err());
+        std::fs::remove_dir_all(&databases_dir).unwrap();
+    }
+
+    #[test]
+    fn test_change_username_keeps_the_uuid() {
+        let databases_dir = std::env::temp_dir().join(format!("ironyyy-username-{}", Uuid::new_v4()));
+        let session_for = |name: &str| {
+            let user = User::new(name.to_string(), "password").unwrap();
+            Session::new(ClearTextDBState::new(user), "password").unwrap()
+        };
+        let mut alice = session_for("alice");
+        alice.save(&databases_dir).unwrap();
+        session_for("bob").save(&databases_dir).unwrap();
+        let user_uuid = alice.state.user.user_uuid;
+
+        assert!(matches!(change_username(&mut alice, &databases_dir, "Bob", &SystemClock), Err(AccountChangeError::Invalid(ValidationError::DuplicateName(_)))));
+        assert!(matches!(change_username(&mut alice, &databases_dir, "  ", &SystemClock), Err(AccountChangeError::Invalid(ValidationError::EmptyName))));
+        let users = change_username(&mut alice, &databases_dir, " alicia ", &SystemClock).unwrap();
+        assert_eq!(users.iter().map(|(_, name)| name.as_str()).collect::<Vec<_>>(), ["alicia", "bob"]);
+        assert!(users.contains(&(user_uuid, "alicia".to_string())));
+        assert_eq!(alice.state.user.audit_log.last().unwrap().event, AuditEvent::UsernameChanged);
+        assert_eq!(Session::unlock(&databases_dir, user_uuid, "password").unwrap().state.user.username, "alicia");
+        std::fs::remove_dir_all(&databases_dir).unwrap();
+    }
+
+    #[test]
+    fn test_failed_rename_changes_nothing() {
+        let databases_dir = std::env::temp_dir().join(format!("ironyyy-rename-{}", Uuid::new_v4()));
+        let mut alice = Session::new(ClearTextDBState::new(User::new("alice".to_string(), "password").unwrap()), "password").unwrap();
+        alice.state.user.enable_hotp().unwrap();
+        alice.save(&databases_dir).unwrap();
+        let user_uuid = alice.state.user.user_uuid;
+        let mut shared = crate::db::shared::SharedSession::create("Team".to_string(), &alice).unwrap();
+        crate::service::shared::save_shared(&mut shared, &databases_dir).unwrap();
+
+        // A shared database file that can't be written makes the rename fail.
+        let path = crate::db::shared::shared_path(&databases_dir, shared.database_uuid);
+        std::fs::create_dir(path.with_extension("json.tmp")).unwrap();
+        assert!(matches!(change_username(&mut alice, &databases_dir, "alicia", &SystemClock), Err(AccountChangeError::Save(_))));
+        assert_eq!(alice.state.user.username, "alice");
+        assert_eq!(Session::unlock(&databases_dir, user_uuid, "password").unwrap().state.user.username, "alice");
+
+        std::fs::remove_dir(path.with_extension("json.tmp")).unwrap();
+        change_username(&mut alice, &databases_dir, "alicia", &SystemClock).unwrap();
+        assert!(alice.state.user.hotp_instance.as_ref().unwrap().provisioning_uri().unwrap().contains(":alicia?"));
+        std::fs::remove_dir_all(&databases_dir).unwrap();
+    }
+}